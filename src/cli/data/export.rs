@@ -63,6 +63,10 @@ impl Context for Export {
             skip_wal: false,
             streaming_output: false,
             streaming_id: None,
+            search_after: None,
+            quick_scan: false,
+            segment_ids: None,
+            params: None,
         };
 
         let req = search::Request {
@@ -39,8 +39,8 @@ use crate::{
     },
     handler::http::request::websocket::session::WsSession,
     service::{
-        db::scheduler as db_scheduler, enrichment::StreamTable, enrichment_table::geoip::Geoip,
-        pipeline::batch_execution::ExecutablePipeline,
+        db::scheduler as db_scheduler, enrichment::CompressedStreamTable,
+        enrichment_table::geoip::Geoip, pipeline::batch_execution::ExecutablePipeline,
     },
 };
 
@@ -72,7 +72,8 @@ pub static DASHBOARD_REPORTS: Lazy<RwHashMap<String, reports::Report>> =
     Lazy::new(Default::default);
 pub static SYSLOG_ROUTES: Lazy<RwHashMap<String, SyslogRoute>> = Lazy::new(Default::default);
 pub static SYSLOG_ENABLED: Lazy<Arc<RwLock<bool>>> = Lazy::new(|| Arc::new(RwLock::new(false)));
-pub static ENRICHMENT_TABLES: Lazy<RwHashMap<String, StreamTable>> = Lazy::new(Default::default);
+pub static ENRICHMENT_TABLES: Lazy<RwHashMap<String, CompressedStreamTable>> =
+    Lazy::new(Default::default);
 pub static ENRICHMENT_REGISTRY: Lazy<Arc<TableRegistry>> =
     Lazy::new(|| Arc::new(TableRegistry::default()));
 
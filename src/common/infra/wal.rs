@@ -20,11 +20,12 @@ use config::{
     get_config, ider,
     meta::stream::{PartitionTimeLevel, StreamParams, StreamType},
     metrics,
-    utils::async_file::get_file_contents,
+    utils::{async_file::get_file_contents, time::now_micros},
     FILE_EXT_JSON,
 };
 use hashbrown::HashMap;
 use once_cell::sync::Lazy;
+use serde::Serialize;
 use tokio::{
     fs::{create_dir_all, File, OpenOptions},
     io::AsyncWriteExt,
@@ -39,7 +40,12 @@ static SEARCHING_FILES: Lazy<parking_lot::RwLock<SearchingFileLocker>> =
     Lazy::new(|| parking_lot::RwLock::new(SearchingFileLocker::new()));
 
 // SEARCHING_REQUESTS for searching requests, in use, should not move to s3
-static SEARCHING_REQUESTS: Lazy<parking_lot::RwLock<HashMap<String, Vec<String>>>> =
+// the value is the lock time in microseconds and the locked files
+static SEARCHING_REQUESTS: Lazy<parking_lot::RwLock<HashMap<String, (i64, Vec<String>)>>> =
+    Lazy::new(Default::default);
+
+// SEARCHING_READERS for searches still reading their locked files, by trace_id
+static SEARCHING_READERS: Lazy<parking_lot::RwLock<HashMap<String, usize>>> =
     Lazy::new(Default::default);
 
 type RwData = RwLock<HashMap<String, Arc<RwFile>>>;
@@ -407,7 +413,7 @@ pub fn clean_lock_files() {
 pub fn lock_request(trace_id: &str, files: &[String]) {
     log::info!("[trace_id {}] lock_request for wal files", trace_id);
     let mut locker = SEARCHING_REQUESTS.write();
-    locker.insert(trace_id.to_string(), files.to_vec());
+    locker.insert(trace_id.to_string(), (now_micros(), files.to_vec()));
 }
 
 pub fn release_request(trace_id: &str) {
@@ -415,13 +421,111 @@ pub fn release_request(trace_id: &str) {
         return;
     }
     log::info!("[trace_id {}] release_request for wal files", trace_id);
+    release_request_files(trace_id);
+}
+
+/// Releases the files locked by a request regardless of the node role,
+/// returns the number of released files or `None` if the request is unknown.
+pub fn force_release_request(trace_id: &str) -> Option<usize> {
+    log::warn!(
+        "[trace_id {}] force release_request for wal files",
+        trace_id
+    );
+    release_request_files(trace_id)
+}
+
+fn release_request_files(trace_id: &str) -> Option<usize> {
     let mut locker = SEARCHING_REQUESTS.write();
     let files = locker.remove(trace_id);
     locker.shrink_to_fit();
     drop(locker);
-    if let Some(files) = files {
+    files.map(|(_, files)| {
         release_files(&files);
+        files.len()
+    })
+}
+
+/// Marks the search of a trace_id as reading its locked files until the
+/// returned guard is dropped, the locks of a running reader are never reaped.
+pub fn track_reader(trace_id: &str) -> ReaderGuard {
+    let mut readers = SEARCHING_READERS.write();
+    *readers.entry(trace_id.to_string()).or_insert(0) += 1;
+    ReaderGuard(trace_id.to_string())
+}
+
+pub struct ReaderGuard(String);
+
+impl Drop for ReaderGuard {
+    fn drop(&mut self) {
+        let mut readers = SEARCHING_READERS.write();
+        if let Some(count) = readers.get_mut(&self.0) {
+            *count -= 1;
+            if *count == 0 {
+                readers.remove(&self.0);
+                readers.shrink_to_fit();
+            }
+        }
+    }
+}
+
+pub fn reader_exists(trace_id: &str) -> bool {
+    SEARCHING_READERS.read().contains_key(trace_id)
+}
+
+/// Releases the files of requests locked before `expired_before` (in
+/// microseconds) whose search is no longer reading, these are searches that
+/// were dropped without releasing. Returns the trace_ids of the reaped requests.
+pub fn release_expired_requests(expired_before: i64) -> Vec<String> {
+    let expired = SEARCHING_REQUESTS
+        .read()
+        .iter()
+        .filter(|(trace_id, (locked_at, _))| {
+            *locked_at < expired_before && !reader_exists(trace_id)
+        })
+        .map(|(trace_id, _)| trace_id.clone())
+        .collect::<Vec<_>>();
+    for trace_id in expired.iter() {
+        log::warn!(
+            "[trace_id {}] release expired request for wal files",
+            trace_id
+        );
+        release_request_files(trace_id);
     }
+    expired
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LockedFile {
+    pub file: String,
+    pub lock_count: usize,
+    pub trace_ids: Vec<String>,
+}
+
+/// Lists the currently locked files with the requests holding them.
+pub fn list_locked_files() -> Vec<LockedFile> {
+    let mut owners: HashMap<&str, Vec<String>> = HashMap::new();
+    let requests = SEARCHING_REQUESTS.read();
+    for (trace_id, (_, files)) in requests.iter() {
+        for file in files.iter() {
+            owners.entry(file).or_default().push(trace_id.clone());
+        }
+    }
+    let locker = SEARCHING_FILES.read();
+    let mut files = locker
+        .inner
+        .iter()
+        .map(|(file, count)| {
+            let mut trace_ids = owners.remove(file.as_str()).unwrap_or_default();
+            trace_ids.sort();
+            LockedFile {
+                file: file.clone(),
+                lock_count: *count,
+                trace_ids,
+            }
+        })
+        .collect::<Vec<_>>();
+    files.sort_by(|a, b| a.file.cmp(&b.file));
+    files
 }
 
 #[cfg(test)]
@@ -459,4 +563,50 @@ mod tests {
         assert_eq!(file.size().await, data.len() as i64);
         assert!(file.name().contains(&format!("{}/{}", thread_id, key)));
     }
+
+    #[test]
+    fn test_wal_locks_force_release() {
+        let trace_id = "test_wal_locks_force_release";
+        let file = "files/default/logs/test_wal_locks/0/test_wal_locks.arrow".to_string();
+        lock_files(&[file.clone()]);
+        lock_request(trace_id, &[file.clone()]);
+
+        let locked = list_locked_files();
+        let entry = locked.iter().find(|f| f.file == file).unwrap();
+        assert_eq!(entry.lock_count, 1);
+        assert_eq!(entry.trace_ids, vec![trace_id.to_string()]);
+
+        assert_eq!(force_release_request(trace_id), Some(1));
+        assert!(!lock_files_exists(&file));
+        assert!(list_locked_files().iter().all(|f| f.file != file));
+        assert_eq!(force_release_request(trace_id), None);
+    }
+
+    #[test]
+    fn test_wal_locks_release_expired() {
+        let trace_id = "test_wal_locks_release_expired";
+        let file = "files/default/logs/test_wal_locks/0/test_wal_expired.arrow".to_string();
+        let reader = track_reader(trace_id);
+        lock_files(&[file.clone()]);
+        lock_request(trace_id, &[file.clone()]);
+
+        // the request is still within the ttl
+        let reaped = release_expired_requests(now_micros() - 3_600_000_000);
+        assert!(!reaped.contains(&trace_id.to_string()));
+        assert!(lock_files_exists(&file));
+
+        // the ttl passed but the search is still reading
+        SEARCHING_REQUESTS.write().get_mut(trace_id).unwrap().0 -= 7_200_000_000;
+        let reaped = release_expired_requests(now_micros() - 3_600_000_000);
+        assert!(!reaped.contains(&trace_id.to_string()));
+        assert!(lock_files_exists(&file));
+
+        // the search is gone and the ttl passed
+        drop(reader);
+        assert!(!reader_exists(trace_id));
+        let reaped = release_expired_requests(now_micros() - 3_600_000_000);
+        assert!(reaped.contains(&trace_id.to_string()));
+        assert!(!lock_files_exists(&file));
+        assert_eq!(force_release_request(trace_id), None);
+    }
 }
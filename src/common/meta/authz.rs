@@ -30,3 +30,32 @@ impl Authz {
         }
     }
 }
+
+/// Request to check whether a user can perform an action on an object.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct PermissionCheckRequest {
+    pub user_id: String,
+    /// Http method of the action, e.g. `GET` or `DELETE`.
+    pub method: String,
+    /// Object in the `type:name` form, e.g. `logs:default`.
+    pub object: String,
+    #[serde(default)]
+    pub parent_id: String,
+}
+
+/// Values evaluated by OpenFGA for a permission check.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PermissionTuple {
+    pub org_id: String,
+    pub user_id: String,
+    pub method: String,
+    pub object: String,
+    pub parent_id: String,
+    pub role: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct PermissionCheckResponse {
+    pub allowed: bool,
+    pub tuple: PermissionTuple,
+}
@@ -325,6 +325,7 @@ pub enum IngestionRequest<'a> {
     KinesisFH(&'a KinesisFHRequest),
     RUM(&'a web::Bytes),
     Usage(&'a web::Bytes),
+    Webhook(&'a web::Bytes),
 }
 
 pub enum IngestionData<'a> {
@@ -151,6 +151,15 @@ pub struct OrganizationSettingPayload {
     pub enable_websocket_search: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub min_auto_refresh_interval: Option<u32>,
+    /// Maximum ingestion request body size in bytes, 0 resets it to the
+    /// global `ZO_PAYLOAD_LIMIT`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_ingest_body_size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_rejected_stream: Option<bool>,
+    /// Replaces all the traces stream routes when set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub traces_stream_routing: Option<Vec<TracesStreamRoute>>,
 }
 
 #[derive(Serialize, ToSchema, Deserialize, Debug, Clone)]
@@ -169,6 +178,26 @@ pub struct OrganizationSetting {
     pub enable_websocket_search: bool,
     #[serde(default = "default_auto_refresh_interval")]
     pub min_auto_refresh_interval: u32,
+    /// Maximum ingestion request body size in bytes, 0 means the global
+    /// `ZO_PAYLOAD_LIMIT` applies.
+    #[serde(default)]
+    pub max_ingest_body_size: usize,
+    /// Write records dropped during ingestion to the `_rejected` stream.
+    #[serde(default)]
+    pub enable_rejected_stream: bool,
+    /// Routes the OTLP spans of requests that don't name a stream by
+    /// resource attribute, the first matching route wins.
+    #[serde(default)]
+    pub traces_stream_routing: Vec<TracesStreamRoute>,
+}
+
+/// Spans whose resource has `attribute` set to `value` are written to
+/// `stream`.
+#[derive(Serialize, ToSchema, Deserialize, Debug, Clone, PartialEq)]
+pub struct TracesStreamRoute {
+    pub attribute: String,
+    pub value: String,
+    pub stream: String,
 }
 
 impl Default for OrganizationSetting {
@@ -180,6 +209,9 @@ impl Default for OrganizationSetting {
             toggle_ingestion_logs: default_toggle_ingestion_logs(),
             enable_websocket_search: default_enable_websocket_search(),
             min_auto_refresh_interval: default_auto_refresh_interval(),
+            max_ingest_body_size: 0,
+            enable_rejected_stream: false,
+            traces_stream_routing: vec![],
         }
     }
 }
@@ -46,6 +46,129 @@ pub struct View {
     pub view_name: String,
 }
 
+impl View {
+    /// The search query of the view, built from the logs page state the UI
+    /// saves: the query under `data.query` is the full sql in sql mode, else
+    /// a filter on the single stream selected in `data.stream.selectedStream`.
+    /// A relative time range ends now.
+    pub fn search_query(&self) -> Result<config::meta::search::Query, String> {
+        let state = self
+            .data
+            .get("data")
+            .ok_or_else(|| "the view has no search state".to_string())?;
+        let query = state
+            .get("query")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .trim();
+        let sql_mode = self
+            .data
+            .pointer("/meta/sqlMode")
+            .and_then(|v| v.as_bool())
+            .unwrap_or_default();
+        let sql = if sql_mode {
+            if query.is_empty() {
+                return Err("the view has no query".to_string());
+            }
+            query.to_string()
+        } else {
+            let streams = match state.pointer("/stream/selectedStream") {
+                Some(serde_json::Value::Array(streams)) => streams
+                    .iter()
+                    .filter_map(stream_name)
+                    .collect::<Vec<_>>(),
+                Some(stream) => stream_name(stream).into_iter().collect(),
+                None => vec![],
+            };
+            let [stream] = streams.as_slice() else {
+                return Err(format!(
+                    "the view must select a single stream, got {}",
+                    streams.len()
+                ));
+            };
+            let mut sql = format!("SELECT * FROM \"{}\"", stream.replace('"', "\"\""));
+            if !query.is_empty() {
+                sql.push_str(&format!(" WHERE {query}"));
+            }
+            sql
+        };
+
+        let datetime = state.get("datetime");
+        let (start_time, end_time) = match datetime
+            .and_then(|v| v.get("type"))
+            .and_then(|v| v.as_str())
+        {
+            Some("absolute") => {
+                let time = |key: &str| datetime.and_then(|v| v.get(key)).and_then(|v| v.as_i64());
+                match (time("startTime"), time("endTime")) {
+                    (Some(start), Some(end)) => (start, end),
+                    _ => return Err("the view has an invalid time range".to_string()),
+                }
+            }
+            _ => {
+                let period = datetime
+                    .and_then(|v| v.get("relativeTimePeriod"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("15m");
+                let end = chrono::Utc::now();
+                let start = relative_start(end, period)
+                    .ok_or_else(|| format!("the view has an invalid time period {period}"))?;
+                (start.timestamp_micros(), end.timestamp_micros())
+            }
+        };
+
+        let toggle_function = self
+            .data
+            .pointer("/meta/toggleFunction")
+            .and_then(|v| v.as_bool())
+            .unwrap_or_default();
+        let query_fn = state
+            .get("tempFunctionContent")
+            .and_then(|v| v.as_str())
+            .map(|v| v.trim())
+            .filter(|v| toggle_function && !v.is_empty())
+            .map(|v| v.to_string());
+
+        Ok(config::meta::search::Query {
+            sql,
+            start_time,
+            end_time,
+            query_fn,
+            ..Default::default()
+        })
+    }
+}
+
+/// The selected streams are names, older views saved `{label, value}` objects.
+fn stream_name(stream: &serde_json::Value) -> Option<String> {
+    match stream {
+        serde_json::Value::String(name) => Some(name.clone()),
+        serde_json::Value::Object(obj) => obj.get("value")?.as_str().map(|v| v.to_string()),
+        _ => None,
+    }
+    .filter(|name| !name.is_empty())
+}
+
+/// The start of a relative time period of the UI, like `15m` or `7d`, ending
+/// at `end`.
+fn relative_start(
+    end: chrono::DateTime<chrono::Utc>,
+    period: &str,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    let unit = period.chars().last()?;
+    let value = period[..period.len() - unit.len_utf8()].parse::<u32>().ok()?;
+    let seconds = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86400,
+        'w' => 7 * 86400,
+        'M' => return end.checked_sub_months(chrono::Months::new(value)),
+        _ => return None,
+    };
+    end.checked_sub_signed(chrono::Duration::seconds(value as i64 * seconds))
+}
+
 /// Save the bandwidth for a given view, without sending the actual data
 /// This is expected to be used for listing views.
 #[derive(Serialize, Deserialize, ToSchema)]
@@ -68,6 +191,26 @@ pub struct DeleteViewResponse {
     // pub view_name: String,
 }
 
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct DeleteViewsRequest {
+    pub view_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct DeleteViewsResponse {
+    pub org_id: String,
+    pub results: Vec<DeleteViewResult>,
+}
+
+/// Outcome of deleting one view of a bulk delete.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct DeleteViewResult {
+    pub view_id: String,
+    pub deleted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, ToSchema)]
 pub struct CreateViewResponse {
     pub org_id: String,
@@ -63,6 +63,9 @@ pub struct StreamSchema {
 #[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 pub struct ListStream {
     pub list: Vec<Stream>,
+    /// Number of streams matching the filters, before pagination
+    #[serde(default)]
+    pub total: usize,
 }
 
 pub struct SchemaEvolution {
@@ -82,6 +85,26 @@ pub struct StreamDeleteFields {
     pub fields: Vec<String>,
 }
 
+/// What deleting a stream removes, and the alerts and dashboards still
+/// reading it
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct StreamDeletePlan {
+    pub file_num: i64,
+    /// In bytes
+    pub storage_size: f64,
+    /// In bytes
+    pub compressed_size: f64,
+    pub schema_versions: usize,
+    /// Names of the alerts on the stream
+    pub alerts: Vec<String>,
+    /// Titles of the dashboards querying the stream
+    pub dashboards: Vec<String>,
+    /// Must be passed as `confirm` to delete a stream with alerts or
+    /// dashboards
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirm_token: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -450,6 +450,69 @@ pub struct UserRoleRequest {
     pub name: String,
 }
 
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct BulkRoleAssignRequest {
+    pub role: String,
+    pub users: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct BulkRoleAssignResult {
+    pub email: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl BulkRoleAssignResult {
+    pub fn ok(email: &str) -> Self {
+        Self {
+            email: email.to_string(),
+            success: true,
+            error: None,
+        }
+    }
+
+    pub fn error(email: &str, error: impl ToString) -> Self {
+        Self {
+            email: email.to_string(),
+            success: false,
+            error: Some(error.to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct BulkRoleAssignResponse {
+    pub results: Vec<BulkRoleAssignResult>,
+}
+
+/// An action the user is permitted to perform on an object. The object is in
+/// the `type:name` form, `*` stands for all objects of the organization.
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
+pub struct EffectivePermission {
+    pub object: String,
+    pub action: String,
+}
+
+impl EffectivePermission {
+    pub fn new(object: &str, action: &str) -> Self {
+        Self {
+            object: object.to_string(),
+            action: action.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct EffectivePermissionsResponse {
+    pub user_id: String,
+    pub role: String,
+    pub permissions: Vec<EffectivePermission>,
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
 pub struct RolesResponse {
     pub label: String,
@@ -150,6 +150,7 @@ async fn migrate_pipelines() -> Result<(), anyhow::Error> {
                     source: pipeline_source,
                     nodes,
                     edges,
+                    dead_letter_stream: None,
                 };
                 new_pipeline_by_source.insert(
                     StreamParams::new(
@@ -190,6 +191,7 @@ async fn migrate_pipelines() -> Result<(), anyhow::Error> {
                     source: pipeline_source,
                     nodes: vec![source_node],
                     edges: vec![],
+                    dead_letter_stream: None,
                 }
             });
 
@@ -321,6 +323,7 @@ async fn migrate_pipelines() -> Result<(), anyhow::Error> {
                 source: pipeline_source,
                 nodes: vec![source_node],
                 edges: vec![],
+                dead_letter_stream: None,
             }
         });
 
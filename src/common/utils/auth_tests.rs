@@ -2951,6 +2951,11 @@ mod tests {
                 cookie_secure_only: bool::default(),
                 ext_auth_salt: String::default(),
                 script_server_token: String::default(),
+                password_min_length: usize::default(),
+                password_require_uppercase: bool::default(),
+                password_require_lowercase: bool::default(),
+                password_require_digit: bool::default(),
+                password_require_special: bool::default(),
             },
             report_server: config::ReportServer {
                 enable_report_server: bool::default(),
@@ -2983,6 +2988,7 @@ mod tests {
                 tls_cert_domain: String::default(),
                 tls_cert_path: String::default(),
                 tls_key_path: String::default(),
+                ipc_compression: String::default(),
             },
             websocket: config::WebSocket {
                 enabled: bool::default(),
@@ -2990,6 +2996,11 @@ mod tests {
                 session_max_lifetime_secs: i64::default(),
                 session_gc_interval_secs: i64::default(),
                 ping_interval_secs: i64::default(),
+                ping_timeout_secs: i64::default(),
+                max_sessions_per_user: usize::default(),
+                max_sessions_per_org: usize::default(),
+                max_unbounded_response_hits: usize::default(),
+                tail_interval_ms: u64::default(),
             },
             route: config::Route {
                 timeout: u64::default(),
@@ -3025,6 +3036,7 @@ mod tests {
                 feature_filelist_dedup_enabled: bool::default(),
                 feature_query_queue_enabled: bool::default(),
                 feature_query_partition_strategy: String::default(),
+                feature_search_partition_strategy: String::default(),
                 feature_query_infer_schema: bool::default(),
                 feature_query_exclude_all: bool::default(),
                 feature_query_without_index: bool::default(),
@@ -3033,6 +3045,8 @@ mod tests {
                 feature_join_match_one_enabled: bool::default(),
                 feature_join_right_side_max_rows: usize::default(),
                 feature_query_skip_wal: bool::default(),
+                query_default_sort_enabled: bool::default(),
+                feature_query_lenient_schema_coercion: bool::default(),
                 ui_enabled: bool::default(),
                 ui_sql_base64_enabled: bool::default(),
                 metrics_dedup_enabled: bool::default(),
@@ -3084,6 +3098,7 @@ mod tests {
                 inverted_index_search_format: String::default(),
                 inverted_index_tantivy_mode: String::default(),
                 inverted_index_count_optimizer_enabled: bool::default(),
+                inverted_index_max_terms: usize::default(),
                 inverted_index_camel_case_tokenizer_disabled: bool::default(),
                 full_text_search_type: String::default(),
                 query_on_stream_selection: bool::default(),
@@ -3108,7 +3123,11 @@ mod tests {
                 use_multi_result_cache: bool::default(),
                 result_cache_selection_strategy: String::default(),
                 result_cache_discard_duration: i64::default(),
+                result_cache_ttl: i64::default(),
                 metrics_cache_enabled: bool::default(),
+                metrics_rollup_enabled: bool::default(),
+                metrics_rollup_resolution: i64::default(),
+                metrics_rollup_interval: u64::default(),
                 swagger_enabled: bool::default(),
                 fake_es_version: String::default(),
                 min_auto_refresh_interval: u32::default(),
@@ -3127,6 +3146,7 @@ mod tests {
                 udschema_max_fields: usize::default(),
                 schema_max_fields_to_enable_uds: usize::default(),
                 user_defined_schema_max_fields: usize::default(),
+                schema_max_fields: usize::default(),
                 mem_table_max_size: usize::default(),
                 mem_table_bucket_num: 1,
                 mem_persist_interval: u64::default(),
@@ -3142,13 +3162,20 @@ mod tests {
                 query_thread_num: usize::default(),
                 query_timeout: u64::default(),
                 query_ingester_timeout: u64::default(),
+                wal_lock_ttl: u64::default(),
+                query_queue_permits: usize::default(),
+                query_queue_timeout: u64::default(),
                 query_default_limit: i64::default(),
+                query_max_result_window: i64::default(),
+                stream_sample_max_size: i64::default(),
                 query_partition_by_secs: usize::default(),
                 query_group_base_speed: usize::default(),
                 circuit_breaker_enabled: bool::default(),
                 circuit_breaker_watching_window: i64::default(),
                 circuit_breaker_reset_window_num: i64::default(),
                 circuit_breaker_slow_request_threshold: u64::default(),
+                http_org_rate_limit: u64::default(),
+                http_org_rate_limit_burst: u64::default(),
                 ingest_allowed_upto: i64::default(),
                 ingest_flatten_level: u32::default(),
                 ignore_file_retention_by_stream: bool::default(),
@@ -3174,6 +3201,7 @@ mod tests {
                 enrichment_table_limit: usize::default(),
                 http_request_timeout: u64::default(),
                 http_keep_alive: u64::default(),
+                http_keep_alive_routes: String::default(),
                 http_slow_log_threshold: u64::default(),
                 http_shutdown_timeout: u64::default(),
                 alert_schedule_interval: i64::default(),
@@ -3198,6 +3226,7 @@ mod tests {
                 quick_mode_force_enabled: bool::default(),
                 quick_mode_num_fields: usize::default(),
                 quick_mode_strategy: String::default(),
+                quick_scan_max_files: usize::default(),
                 sql_db_connections_min: u32::default(),
                 sql_db_connections_max: u32::default(),
                 sql_db_connections_acquire_timeout: u64::default(),
@@ -3326,6 +3355,9 @@ mod tests {
                 max_idle_per_host: usize::default(),
                 keepalive_timeout: u64::default(),
                 multi_part_upload_size: usize::default(),
+                read_retries: usize::default(),
+                read_retry_backoff: u64::default(),
+                named_stores: String::default(),
             },
             sns: config::Sns {
                 endpoint: String::default(),
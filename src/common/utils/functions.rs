@@ -13,11 +13,23 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
 
 use config::{
     meta::function::VRLCompilerConfig, GEO_IP_ASN_ENRICHMENT_TABLE, GEO_IP_CITY_ENRICHMENT_TABLE,
 };
+use tracing::{
+    field::{Field, Visit},
+    Event, Subscriber,
+};
+use tracing_subscriber::{
+    layer::{Context, SubscriberExt},
+    Layer,
+};
 use vector_enrichment::{Table, TableRegistry};
 
 use crate::common::{
@@ -42,6 +54,48 @@ pub fn init_vrl_runtime() -> vrl::compiler::runtime::Runtime {
     vrl::compiler::runtime::Runtime::new(vrl::prelude::state::RuntimeState::default())
 }
 
+/// Runs `f` collecting the output of the VRL `log` calls it makes on this
+/// thread.
+pub fn capture_vrl_logs<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+    let logs = Arc::new(Mutex::new(Vec::new()));
+    let subscriber = tracing_subscriber::registry().with(VrlLogLayer { logs: logs.clone() });
+    let ret = tracing::subscriber::with_default(subscriber, f);
+    let logs = std::mem::take(&mut *logs.lock().unwrap());
+    (ret, logs)
+}
+
+struct VrlLogLayer {
+    logs: Arc<Mutex<Vec<String>>>,
+}
+
+impl<S: Subscriber> Layer<S> for VrlLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if !metadata.target().starts_with("vrl") {
+            return;
+        }
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        self.logs
+            .lock()
+            .unwrap()
+            .push(format!("[{}] {}", metadata.level(), visitor.message));
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{value:?}");
+        }
+    }
+}
+
 pub fn get_vrl_compiler_config(org_id: &str) -> VRLCompilerConfig {
     let en_tables = ENRICHMENT_TABLES.clone();
     let mut functions = vrl::stdlib::all();
@@ -51,10 +105,16 @@ pub fn get_vrl_compiler_config(org_id: &str) -> VRLCompilerConfig {
 
     for table in en_tables.iter() {
         if table.org_id == org_id || table.org_id == DEFAULT_ORG {
-            tables.insert(
-                table.stream_name.to_owned(),
-                Box::new(table.value().clone()),
-            );
+            match table.value().to_table() {
+                Ok(table) => {
+                    tables.insert(table.stream_name.to_owned(), Box::new(table));
+                }
+                Err(e) => log::error!(
+                    "decompress enrichment table {}/{} error: {e}",
+                    table.org_id,
+                    table.stream_name
+                ),
+            }
         }
     }
     drop(en_tables);
@@ -95,6 +95,9 @@ pub(crate) fn get_folder(query: &Query<HashMap<String, String>>) -> String {
     }
 }
 
+/// Header used by clients to pass their own trace id, it is echoed back in responses
+pub(crate) const TRACE_ID_HEADER: &str = "x-trace-id";
+
 #[inline(always)]
 pub(crate) fn get_or_create_trace_id(headers: &HeaderMap, span: &tracing::Span) -> String {
     let cfg = config::get_config();
@@ -127,6 +130,8 @@ pub(crate) fn get_or_create_trace_id(headers: &HeaderMap, span: &tracing::Span)
             log::warn!("Failed to parse valid trace_id from received [Traceparent] header");
             config::ider::uuid()
         }
+    } else if let Some(trace_id) = get_trace_id_from_header(headers) {
+        trace_id
     } else if !span.is_none() {
         span.context().span().span_context().trace_id().to_string()
     } else {
@@ -134,6 +139,20 @@ pub(crate) fn get_or_create_trace_id(headers: &HeaderMap, span: &tracing::Span)
     }
 }
 
+/// Returns the client provided `X-Trace-Id`, ignoring values that are not safe to log
+fn get_trace_id_from_header(headers: &HeaderMap) -> Option<String> {
+    let trace_id = headers.get(TRACE_ID_HEADER)?.to_str().ok()?.trim();
+    if trace_id.is_empty()
+        || trace_id.len() > 128
+        || !trace_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return None;
+    }
+    Some(trace_id.to_string())
+}
+
 /// This function can handle IPv4 and IPv6 addresses which may have port numbers appended
 pub fn parse_ip_addr(ip_address: &str) -> Result<(IpAddr, Option<u16>), AddrParseError> {
     let mut port: Option<u16> = None;
@@ -215,6 +234,41 @@ mod tests {
         assert_eq!(resp, Some(StreamType::Traces));
     }
 
+    #[test]
+    fn test_get_or_create_trace_id() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static(TRACE_ID_HEADER),
+            "client-trace-123".parse().unwrap(),
+        );
+        assert_eq!(
+            get_or_create_trace_id(&headers, &tracing::Span::none()),
+            "client-trace-123"
+        );
+
+        // traceparent takes precedence
+        headers.insert(
+            HeaderName::from_static("traceparent"),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(
+            get_or_create_trace_id(&headers, &tracing::Span::none()),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+
+        // invalid values are replaced by a generated id
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static(TRACE_ID_HEADER),
+            "bad trace id".parse().unwrap(),
+        );
+        let trace_id = get_or_create_trace_id(&headers, &tracing::Span::none());
+        assert!(!trace_id.is_empty());
+        assert_ne!(trace_id, "bad trace id");
+    }
+
     /// Test logic for IP parsing
     #[test]
     fn test_ip_parsing() {
@@ -26,6 +26,8 @@ use sqlparser::{
     parser::Parser,
 };
 
+use crate::service::search::cache::result_utils::calc_result_cache_ratio;
+
 #[inline(always)]
 pub(crate) fn get_search_type_from_ws_req(
     search_event_type: &SearchEventType,
@@ -133,11 +135,7 @@ pub fn _calc_result_cache_ratio(accumulated_results: &[SearchResultType]) -> usi
                 SearchResultType::Cached(c) => (search_hits, cache_hits + c.hits.len()),
             });
 
-    let total_hits = search_hits + cache_hits;
-    if total_hits == 0 {
-        return 0; // avoid division by zero
-    }
-    ((cache_hits as f64 / total_hits as f64) * 100.0).round() as usize
+    calc_result_cache_ratio(cache_hits, search_hits)
 }
 
 #[cfg(test)]
@@ -395,6 +395,42 @@ pub struct WebSocket {
     pub session_gc_interval_secs: i64,
     #[env_config(name = "ZO_WEBSOCKET_PING_INTERVAL_SECS", default = 15)]
     pub ping_interval_secs: i64,
+    #[env_config(
+        name = "ZO_WEBSOCKET_PING_TIMEOUT_SECS",
+        default = 45,
+        help = "Close the session if no pong is received within this time after a server ping"
+    )]
+    pub ping_timeout_secs: i64,
+    #[env_config(
+        name = "ZO_WEBSOCKET_MAX_SESSIONS_PER_USER",
+        default = 0,
+        help = "Max concurrent websocket sessions per user in an org, 0 means unlimited"
+    )]
+    pub max_sessions_per_user: usize,
+    #[env_config(
+        name = "ZO_WEBSOCKET_MAX_SESSIONS_PER_ORG",
+        default = 0,
+        help = "Max concurrent websocket sessions per org, 0 means unlimited"
+    )]
+    pub max_sessions_per_org: usize,
+    #[env_config(
+        name = "ZO_WEBSOCKET_MAX_UNBOUNDED_RESPONSE_HITS",
+        default = 0,
+        help = "Max hits returned over all the websocket responses of a size -1 query, 0 means unlimited"
+    )]
+    pub max_unbounded_response_hits: usize,
+    #[env_config(
+        name = "ZO_WEBSOCKET_TAIL_INTERVAL_MS",
+        default = 1000,
+        help = "Interval in milliseconds at which live tails poll the memtable for new records"
+    )]
+    pub tail_interval_ms: u64,
+    #[env_config(
+        name = "ZO_WEBSOCKET_TAIL_MAX_HITS",
+        default = 1000,
+        help = "Max records pushed to a live tail per poll, the newest are kept, 0 means unlimited"
+    )]
+    pub tail_max_hits: usize,
 }
 
 #[derive(EnvConfig)]
@@ -523,6 +559,24 @@ pub struct Auth {
     pub ext_auth_salt: String,
     #[env_config(name = "O2_SCRIPT_SERVER_TOKEN")]
     pub script_server_token: String,
+    #[env_config(
+        name = "ZO_PASSWORD_MIN_LENGTH",
+        default = 0,
+        help = "Minimum length of user passwords, 0 disables the check"
+    )]
+    pub password_min_length: usize,
+    #[env_config(name = "ZO_PASSWORD_REQUIRE_UPPERCASE", default = false)]
+    pub password_require_uppercase: bool,
+    #[env_config(name = "ZO_PASSWORD_REQUIRE_LOWERCASE", default = false)]
+    pub password_require_lowercase: bool,
+    #[env_config(name = "ZO_PASSWORD_REQUIRE_DIGIT", default = false)]
+    pub password_require_digit: bool,
+    #[env_config(
+        name = "ZO_PASSWORD_REQUIRE_SPECIAL",
+        default = false,
+        help = "Require at least one character that is not a letter or a digit"
+    )]
+    pub password_require_special: bool,
 }
 
 #[derive(EnvConfig)]
@@ -579,6 +633,12 @@ pub struct Grpc {
     pub tls_cert_path: String,
     #[env_config(name = "ZO_GRPC_TLS_KEY_PATH", default = "")]
     pub tls_key_path: String,
+    #[env_config(
+        name = "ZO_GRPC_IPC_COMPRESSION",
+        default = "zstd",
+        help = "Compression used for arrow IPC data in grpc search responses, support: zstd, lz4, none"
+    )]
+    pub ipc_compression: String,
 }
 
 #[derive(EnvConfig)]
@@ -663,6 +723,12 @@ pub struct Common {
     pub feature_query_queue_enabled: bool,
     #[env_config(name = "ZO_FEATURE_QUERY_PARTITION_STRATEGY", default = "file_num")]
     pub feature_query_partition_strategy: String,
+    #[env_config(
+        name = "ZO_FEATURE_SEARCH_PARTITION_STRATEGY",
+        default = "time",
+        help = "Default strategy of _search_partition, time: equal time slices, size: slices with balanced record counts"
+    )]
+    pub feature_search_partition_strategy: String,
     #[env_config(name = "ZO_FEATURE_QUERY_INFER_SCHEMA", default = false)]
     pub feature_query_infer_schema: bool,
     #[env_config(name = "ZO_FEATURE_QUERY_EXCLUDE_ALL", default = true)]
@@ -687,6 +753,18 @@ pub struct Common {
         help = "Skip WAL for query"
     )]
     pub feature_query_skip_wal: bool,
+    #[env_config(
+        name = "ZO_QUERY_DEFAULT_SORT_ENABLED",
+        default = true,
+        help = "Sort the results of simple queries without ORDER BY by _timestamp descending"
+    )]
+    pub query_default_sort_enabled: bool,
+    #[env_config(
+        name = "ZO_FEATURE_QUERY_LENIENT_SCHEMA_COERCION",
+        default = false,
+        help = "Cast values of older schema versions which can't be converted to the latest column type to null instead of failing the query"
+    )]
+    pub feature_query_lenient_schema_coercion: bool,
     #[env_config(name = "ZO_UI_ENABLED", default = true)]
     pub ui_enabled: bool,
     #[env_config(name = "ZO_UI_SQL_BASE64_ENABLED", default = false)]
@@ -862,7 +940,7 @@ pub struct Common {
     #[env_config(
         name = "ZO_INVERTED_INDEX_SEARCH_FORMAT",
         default = "tantivy",
-        help = "InvertedIndex search format, parquet(default), tantivy."
+        help = "InvertedIndex search format, parquet(default), tantivy, both. both requires the both store format, the parquet index selects the files and tantivy the rows within them."
     )]
     pub inverted_index_search_format: String,
     #[env_config(
@@ -883,6 +961,12 @@ pub struct Common {
         help = "Toggle inverted index count optimizer."
     )]
     pub inverted_index_count_optimizer_enabled: bool,
+    #[env_config(
+        name = "ZO_INVERTED_INDEX_MAX_TERMS",
+        default = 64,
+        help = "Max number of terms searched through the inverted index in one query, beyond it the files are searched without the index. 0 means no limit."
+    )]
+    pub inverted_index_max_terms: usize,
     #[deprecated(since = "0.14.3", note = "will be removed in 0.15.0")]
     #[env_config(
         name = "ZO_FULL_TEXT_SEARCH_TYPE",
@@ -986,12 +1070,36 @@ pub struct Common {
         help = "Discard data of last n seconds from cached results"
     )]
     pub result_cache_discard_duration: i64,
+    #[env_config(
+        name = "ZO_RESULT_CACHE_TTL",
+        default = 0,
+        help = "Seconds after which cached query results are recomputed, 0 keeps them until evicted. Streams can override it with the result_cache_ttl setting"
+    )]
+    pub result_cache_ttl: i64,
     #[env_config(
         name = "ZO_METRICS_CACHE_ENABLED",
         default = true,
         help = "Enable result cache for PromQL metrics queries"
     )]
     pub metrics_cache_enabled: bool,
+    #[env_config(
+        name = "ZO_METRICS_ROLLUP_ENABLED",
+        default = false,
+        help = "Aggregate metric streams into rollup streams of coarser resolution, used by PromQL range queries with a step of at least the rollup resolution"
+    )]
+    pub metrics_rollup_enabled: bool,
+    #[env_config(
+        name = "ZO_METRICS_ROLLUP_RESOLUTION",
+        default = 3600,
+        help = "Resolution of the metric rollup streams, unit seconds"
+    )]
+    pub metrics_rollup_resolution: i64,
+    #[env_config(
+        name = "ZO_METRICS_ROLLUP_INTERVAL",
+        default = 600,
+        help = "Interval of the metric rollup job, unit seconds"
+    )]
+    pub metrics_rollup_interval: u64,
     #[env_config(name = "ZO_SWAGGER_ENABLED", default = true)]
     pub swagger_enabled: bool,
     #[env_config(name = "ZO_FAKE_ES_VERSION", default = "")]
@@ -1046,6 +1154,12 @@ pub struct Limit {
         help = "Maximum number of fields allowed in user-defined schema"
     )]
     pub user_defined_schema_max_fields: usize,
+    #[env_config(
+        name = "ZO_SCHEMA_MAX_FIELDS",
+        default = 0,
+        help = "Maximum number of fields in a stream schema, new fields over this limit are dropped at ingestion. 0 means no limit, streams can override it with the max_schema_fields setting"
+    )]
+    pub schema_max_fields: usize,
     // MB, total data size in memory, default is 50% of system memory
     #[env_config(name = "ZO_MEM_TABLE_MAX_SIZE", default = 0)]
     pub mem_table_max_size: usize,
@@ -1083,8 +1197,38 @@ pub struct Limit {
     #[env_config(name = "ZO_QUERY_INGESTER_TIMEOUT", default = 0)]
     // default equal to query_timeout
     pub query_ingester_timeout: u64,
+    #[env_config(
+        name = "ZO_WAL_LOCK_TTL",
+        default = 0,
+        help = "Seconds after which WAL files locked by a search that is no longer running are released automatically, default is 2 * ZO_QUERY_TIMEOUT"
+    )]
+    pub wal_lock_ttl: u64,
+    #[env_config(
+        name = "ZO_QUERY_QUEUE_PERMITS",
+        default = 1,
+        help = "Number of searches that can run at the same time when the query queue is enabled"
+    )]
+    pub query_queue_permits: usize,
+    #[env_config(
+        name = "ZO_QUERY_QUEUE_TIMEOUT",
+        default = 600,
+        help = "Seconds a search waits in the query queue before failing with 503, 0 means wait forever"
+    )]
+    pub query_queue_timeout: u64,
     #[env_config(name = "ZO_QUERY_DEFAULT_LIMIT", default = 1000)]
     pub query_default_limit: i64,
+    #[env_config(
+        name = "ZO_QUERY_MAX_RESULT_WINDOW",
+        default = 0,
+        help = "Max value of from + size for a search request, 0 means unlimited"
+    )]
+    pub query_max_result_window: i64,
+    #[env_config(
+        name = "ZO_STREAM_SAMPLE_MAX_SIZE",
+        default = 100,
+        help = "Max number of records returned by the stream sample API"
+    )]
+    pub stream_sample_max_size: i64,
     #[env_config(name = "ZO_QUERY_PARTITION_BY_SECS", default = 1)] // seconds
     pub query_partition_by_secs: usize,
     #[env_config(name = "ZO_QUERY_GROUP_BASE_SPEED", default = 768)] // MB/s/core
@@ -1105,7 +1249,11 @@ pub struct Limit {
     pub metrics_leader_push_interval: u64,
     #[env_config(name = "ZO_METRICS_LEADER_ELECTION_INTERVAL", default = 30)]
     pub metrics_leader_election_interval: i64,
-    #[env_config(name = "ZO_METRICS_MAX_SERIES_PER_QUERY", default = 30000)]
+    #[env_config(
+        name = "ZO_METRICS_MAX_SERIES_PER_QUERY",
+        default = 30000,
+        help = "Maximum number of series a PromQL selector may match, queries exceeding it fail"
+    )]
     pub metrics_max_series_per_query: usize,
     #[env_config(name = "ZO_METRICS_MAX_POINTS_PER_SERIES", default = 30000)]
     pub metrics_max_points_per_series: usize,
@@ -1139,6 +1287,12 @@ pub struct Limit {
     pub http_request_timeout: u64,
     #[env_config(name = "ZO_ACTIX_KEEP_ALIVE", default = 5)] // seconds
     pub http_keep_alive: u64,
+    #[env_config(
+        name = "ZO_ACTIX_KEEP_ALIVE_ROUTES",
+        default = "",
+        help = "Keep-alive per path prefix as comma separated `prefix=seconds` pairs, the longest matching prefix wins. 0 closes the connection after the response. Values above ZO_ACTIX_KEEP_ALIVE are capped at it, e.g. `/api/default/_search_stream=5,/api/=0`"
+    )]
+    pub http_keep_alive_routes: String,
     #[env_config(name = "ZO_ACTIX_SHUTDOWN_TIMEOUT", default = 5)] // seconds
     pub http_shutdown_timeout: u64,
     #[env_config(name = "ZO_ACTIX_SLOW_LOG_THRESHOLD", default = 5)] // seconds
@@ -1155,6 +1309,18 @@ pub struct Limit {
         help = "Trigger circuit break if over this threshold in watching window, and will be reset after 2 * watching window"
     )] // slow requests
     pub circuit_breaker_slow_request_threshold: u64,
+    #[env_config(
+        name = "ZO_HTTP_ORG_RATE_LIMIT",
+        default = 0,
+        help = "Maximum requests per second per organization on a node, 0 disables rate limiting"
+    )]
+    pub http_org_rate_limit: u64,
+    #[env_config(
+        name = "ZO_HTTP_ORG_RATE_LIMIT_BURST",
+        default = 0,
+        help = "Maximum burst of requests per organization, defaults to the rate limit when 0"
+    )]
+    pub http_org_rate_limit_burst: u64,
     #[env_config(name = "ZO_ALERT_SCHEDULE_INTERVAL", default = 10)] // seconds
     pub alert_schedule_interval: i64,
     #[env_config(name = "ZO_ALERT_SCHEDULE_CONCURRENCY", default = 5)]
@@ -1215,6 +1381,12 @@ pub struct Limit {
     pub quick_mode_num_fields: usize,
     #[env_config(name = "ZO_QUICK_MODE_STRATEGY", default = "")]
     pub quick_mode_strategy: String, // first, last, both
+    #[env_config(
+        name = "ZO_QUICK_SCAN_MAX_FILES",
+        default = 100,
+        help = "Number of most recent files searched per stream by a quick_scan search"
+    )]
+    pub quick_scan_max_files: usize,
     #[env_config(name = "ZO_META_CONNECTION_POOL_MIN_SIZE", default = 0)] // number of connections
     pub sql_db_connections_min: u32,
     #[env_config(name = "ZO_META_CONNECTION_POOL_MAX_SIZE", default = 0)] // number of connections
@@ -1588,6 +1760,24 @@ pub struct S3 {
         help = "The size of the file will switch to multi-part upload in MB"
     )]
     pub multi_part_upload_size: usize,
+    #[env_config(
+        name = "ZO_S3_READ_RETRIES",
+        default = 3,
+        help = "Retries of a file read failing with a transient error, on top of the client retries"
+    )]
+    pub read_retries: usize,
+    #[env_config(
+        name = "ZO_S3_READ_RETRY_BACKOFF",
+        default = 100,
+        help = "Backoff in milliseconds before the first read retry, doubled on each retry"
+    )]
+    pub read_retry_backoff: u64,
+    #[env_config(
+        name = "ZO_S3_NAMED_STORES",
+        default = "",
+        help = "Extra object stores streams can be assigned to with the storage_name setting, name:location pairs separated by comma. The location is a bucket of ZO_S3_PROVIDER, or a directory with local disk storage."
+    )]
+    pub named_stores: String,
 }
 
 #[derive(Debug, EnvConfig)]
@@ -1787,6 +1977,26 @@ pub fn init() -> Config {
 }
 
 fn check_limit_config(cfg: &mut Config) -> Result<(), anyhow::Error> {
+    for route in cfg
+        .limit
+        .http_keep_alive_routes
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+    {
+        match route.split_once('=') {
+            Some((prefix, secs))
+                if prefix.starts_with('/') && secs.trim().parse::<u64>().is_ok() => {}
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "ZO_ACTIX_KEEP_ALIVE_ROUTES entry `{route}` is invalid, expected `path_prefix=seconds`"
+                ));
+            }
+        }
+    }
+    if cfg.limit.wal_lock_ttl == 0 {
+        cfg.limit.wal_lock_ttl = cfg.limit.query_timeout * 2;
+    }
     // set real cpu num
     cfg.limit.real_cpu_num = max(1, sysinfo::get_cpu_limit());
     // set at least 2 threads
@@ -1916,6 +2126,12 @@ fn check_common_config(cfg: &mut Config) -> Result<(), anyhow::Error> {
     if cfg.limit.metrics_cache_max_entries == 0 {
         cfg.limit.metrics_cache_max_entries = 100_000;
     }
+    if cfg.common.metrics_rollup_resolution <= 0 {
+        cfg.common.metrics_rollup_resolution = 3600;
+    }
+    if cfg.common.metrics_rollup_interval == 0 {
+        cfg.common.metrics_rollup_interval = 600;
+    }
 
     // check search job retention
     if cfg.limit.search_job_retention == 0 {
@@ -2018,12 +2234,17 @@ fn check_common_config(cfg: &mut Config) -> Result<(), anyhow::Error> {
             cfg.common.inverted_index_search_format =
                 cfg.common.inverted_index_store_format.clone();
         }
-        if cfg.common.inverted_index_search_format == "both" {
+        // searching both needs both index files
+        if cfg.common.inverted_index_search_format == "both"
+            && cfg.common.inverted_index_store_format != "both"
+        {
             cfg.common.inverted_index_search_format = "parquet".to_string();
         }
-        if !["parquet", "tantivy"].contains(&cfg.common.inverted_index_search_format.as_str()) {
+        if !["both", "parquet", "tantivy"]
+            .contains(&cfg.common.inverted_index_search_format.as_str())
+        {
             return Err(anyhow::anyhow!(
-                "ZO_INVERTED_INDEX_SEARCH_FORMAT must be one of parquet, tantivy."
+                "ZO_INVERTED_INDEX_SEARCH_FORMAT must be one of parquet, tantivy, both."
             ));
         }
     }
@@ -2045,6 +2266,15 @@ fn check_grpc_config(cfg: &mut Config) -> Result<(), anyhow::Error> {
     {
         return Err(anyhow::anyhow!("ZO_GRPC_TLS_CERT_DOMAIN, ZO_GRPC_TLS_CERT_PATH and ZO_GRPC_TLS_KEY_PATH must be set when ZO_GRPC_TLS_ENABLED is true"));
     }
+    cfg.grpc.ipc_compression = cfg.grpc.ipc_compression.trim().to_lowercase();
+    if cfg.grpc.ipc_compression.is_empty() {
+        cfg.grpc.ipc_compression = "none".to_string();
+    }
+    if !["zstd", "lz4", "none"].contains(&cfg.grpc.ipc_compression.as_str()) {
+        return Err(anyhow::anyhow!(
+            "ZO_GRPC_IPC_COMPRESSION must be one of zstd, lz4 or none"
+        ));
+    }
     Ok(())
 }
 
@@ -2450,9 +2680,36 @@ fn check_s3_config(cfg: &mut Config) -> Result<(), anyhow::Error> {
         cfg.s3.keepalive_timeout = 20;
     }
 
+    parse_named_stores(&cfg.s3.named_stores)?;
+
     Ok(())
 }
 
+/// Parses `ZO_S3_NAMED_STORES` into (name, location) pairs.
+pub fn parse_named_stores(value: &str) -> Result<Vec<(String, String)>, anyhow::Error> {
+    let mut stores: Vec<(String, String)> = Vec::new();
+    for item in value.split(',').map(|v| v.trim()).filter(|v| !v.is_empty()) {
+        let Some((name, location)) = item.split_once(':') else {
+            return Err(anyhow::anyhow!(
+                "ZO_S3_NAMED_STORES entry must be name:location, got: {item}"
+            ));
+        };
+        let (name, location) = (name.trim(), location.trim());
+        if name.is_empty() || location.is_empty() {
+            return Err(anyhow::anyhow!(
+                "ZO_S3_NAMED_STORES entry must be name:location, got: {item}"
+            ));
+        }
+        if stores.iter().any(|(n, _)| n == name) {
+            return Err(anyhow::anyhow!(
+                "ZO_S3_NAMED_STORES has duplicate store: {name}"
+            ));
+        }
+        stores.push((name.to_string(), location.to_string()));
+    }
+    Ok(stores)
+}
+
 fn check_pipeline_config(cfg: &mut Config) -> Result<(), anyhow::Error> {
     // pipeline
     if cfg.pipeline.remote_stream_wal_dir.is_empty() {
@@ -2558,6 +2815,20 @@ mod tests {
         cfg.s3.provider = "".to_string();
         check_s3_config(&mut cfg).unwrap();
         assert_eq!(cfg.s3.provider, "aws");
+        cfg.s3.named_stores = "fast:bucket-fast, cold:/data/cold".to_string();
+        check_s3_config(&mut cfg).unwrap();
+        assert_eq!(
+            parse_named_stores(&cfg.s3.named_stores).unwrap(),
+            vec![
+                ("fast".to_string(), "bucket-fast".to_string()),
+                ("cold".to_string(), "/data/cold".to_string())
+            ]
+        );
+        cfg.s3.named_stores = "fast:a,fast:b".to_string();
+        assert!(check_s3_config(&mut cfg).is_err());
+        cfg.s3.named_stores = "fast".to_string();
+        assert!(check_s3_config(&mut cfg).is_err());
+        cfg.s3.named_stores = "".to_string();
 
         // SNS configuration tests
         // Test default values
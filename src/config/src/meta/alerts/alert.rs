@@ -47,6 +47,15 @@ pub struct Alert {
     #[serde(default)]
     pub trigger_condition: TriggerCondition,
     pub destinations: Vec<String>,
+    /// Template to use for a destination instead of the destination's own
+    /// template, keyed by destination name.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination_templates: Option<HashMap<String, String>>,
+    /// Locale of the template variants used to render the notifications.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_locale: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub context_attributes: Option<HashMap<String, String>>,
     #[serde(default)]
@@ -94,6 +103,8 @@ impl Default for Alert {
             query_condition: QueryCondition::default(),
             trigger_condition: TriggerCondition::default(),
             destinations: vec![],
+            destination_templates: None,
+            template_locale: None,
             context_attributes: None,
             row_template: "".to_string(),
             description: "".to_string(),
@@ -117,6 +128,16 @@ impl Alert {
         format!("{}/{}/{}", self.stream_type, self.stream_name, self.name)
     }
 
+    /// Returns the template configured on the alert for the given destination,
+    /// if any. Destinations without one use their own template.
+    pub fn get_destination_template(&self, destination: &str) -> Option<&str> {
+        self.destination_templates
+            .as_ref()
+            .and_then(|templates| templates.get(destination))
+            .map(|t| t.as_str())
+            .filter(|t| !t.is_empty())
+    }
+
     /// Checks the last satisfied at time for the alert from the scheduled_jobs table first.
     /// If it is not present, then it uses the last_satisfied_at time from the alert table.
     /// Use this function instead of `get_last_satisfied_at_from_table` to get the actual timestamp.
@@ -180,6 +201,21 @@ impl Alert {
     }
 }
 
+/// Outcome of replaying an alert over a past time range.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AlertBacktestResponse {
+    pub start_time: i64,
+    pub end_time: i64,
+    /// Number of intervals in which the alert was evaluated.
+    pub intervals: usize,
+    /// Number of intervals in which the alert would have fired.
+    pub fired: usize,
+    /// End time of each interval in which the alert would have fired.
+    pub fired_at: Vec<i64>,
+    /// Number of intervals that failed to evaluate.
+    pub errors: usize,
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct AlertListFilter {
     pub enabled: Option<bool>,
@@ -0,0 +1,274 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::meta::{
+    alerts::alert::Alert,
+    destinations::{Destination, Module, Template},
+    folder::{Folder, DEFAULT_FOLDER},
+};
+
+/// The alerting configuration of an org, the alerts with the templates and
+/// destinations they use, exported as a single document to back it up or to
+/// import it into another org.
+///
+/// Alerts refer to destinations and destinations to templates by name, so the
+/// references still resolve in the org the bundle is imported into. The
+/// folders of the alerts are included too, the importing org matches them by
+/// name as their ids are specific to the exporting org.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AlertConfigBundle {
+    pub version: u32,
+    #[serde(default)]
+    pub templates: Vec<Template>,
+    #[serde(default)]
+    pub destinations: Vec<Destination>,
+    #[serde(default)]
+    pub folders: Vec<Folder>,
+    #[serde(default)]
+    pub alerts: Vec<BundleAlert>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BundleAlert {
+    /// Id of the folder in the exporting org, one of the folders of the
+    /// bundle. Imported into the default folder when the bundle has no such
+    /// folder and the org has none with this id either.
+    #[serde(default = "default_folder")]
+    pub folder_id: String,
+    pub alert: Alert,
+}
+
+fn default_folder() -> String {
+    DEFAULT_FOLDER.to_string()
+}
+
+/// Names of the templates and destinations a bundle refers to without
+/// including them, they must exist in the org the bundle is imported into.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ExternalReferences {
+    pub templates: BTreeSet<String>,
+    pub destinations: BTreeSet<String>,
+}
+
+impl AlertConfigBundle {
+    pub const VERSION: u32 = 1;
+
+    pub fn new(
+        templates: Vec<Template>,
+        destinations: Vec<Destination>,
+        folders: Vec<Folder>,
+        alerts: Vec<BundleAlert>,
+    ) -> Self {
+        Self {
+            version: Self::VERSION,
+            templates,
+            destinations,
+            folders,
+            alerts,
+        }
+    }
+
+    /// Moves the content of the bundle to the given org. The ids are cleared,
+    /// the importing org assigns its own.
+    pub fn set_org(&mut self, org_id: &str) {
+        for template in self.templates.iter_mut() {
+            template.id = None;
+            template.org_id = org_id.to_string();
+            template.is_default = false;
+        }
+        for destination in self.destinations.iter_mut() {
+            destination.id = None;
+            destination.org_id = org_id.to_string();
+        }
+        for BundleAlert { alert, .. } in self.alerts.iter_mut() {
+            alert.id = None;
+            alert.org_id = org_id.to_string();
+        }
+    }
+
+    pub fn external_references(&self) -> ExternalReferences {
+        let templates = self
+            .templates
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect::<BTreeSet<_>>();
+        let destinations = self
+            .destinations
+            .iter()
+            .map(|d| d.name.as_str())
+            .collect::<BTreeSet<_>>();
+
+        let mut refs = ExternalReferences::default();
+        let mut add_template = |name: &str| {
+            if !templates.contains(name) {
+                refs.templates.insert(name.to_string());
+            }
+        };
+        for destination in self.destinations.iter() {
+            if let Module::Alert { template, .. } = &destination.module {
+                add_template(template);
+            }
+        }
+        for BundleAlert { alert, .. } in self.alerts.iter() {
+            for template in alert.destination_templates.iter().flat_map(|v| v.values()) {
+                add_template(template);
+            }
+        }
+        for BundleAlert { alert, .. } in self.alerts.iter() {
+            let names = alert
+                .destinations
+                .iter()
+                .chain(alert.destination_templates.iter().flat_map(|v| v.keys()));
+            for name in names {
+                if !destinations.contains(name.as_str()) {
+                    refs.destinations.insert(name.to_string());
+                }
+            }
+        }
+        refs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::json;
+
+    fn template(org_id: &str, name: &str) -> Template {
+        json::from_value(json::json!({
+            "id": null,
+            "org_id": org_id,
+            "name": name,
+            "is_default": false,
+            "type": "http",
+            "body": "{\"text\": \"{alert_name} is firing\"}",
+        }))
+        .unwrap()
+    }
+
+    fn destination(org_id: &str, name: &str, template: &str) -> Destination {
+        json::from_value(json::json!({
+            "id": null,
+            "org_id": org_id,
+            "name": name,
+            "module": {
+                "alert": {
+                    "template": template,
+                    "destination_type": {"type": "http", "url": "http://localhost/hook"},
+                },
+            },
+        }))
+        .unwrap()
+    }
+
+    fn folder(folder_id: &str, name: &str) -> Folder {
+        Folder {
+            folder_id: folder_id.to_string(),
+            name: name.to_string(),
+            description: String::new(),
+        }
+    }
+
+    fn alert(org_id: &str, name: &str, destinations: &[&str]) -> BundleAlert {
+        BundleAlert {
+            folder_id: "ops".to_string(),
+            alert: json::from_value(json::json!({
+                "name": name,
+                "org_id": org_id,
+                "stream_type": "logs",
+                "stream_name": "app",
+                "destinations": destinations,
+            }))
+            .unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_alert_config_bundle_round_trip() {
+        let bundle = AlertConfigBundle::new(
+            vec![template("org_a", "slack")],
+            vec![
+                destination("org_a", "ops_hook", "slack"),
+                destination("org_a", "dev_hook", "slack"),
+            ],
+            vec![folder("ops", "Operations")],
+            vec![
+                alert("org_a", "errors", &["ops_hook"]),
+                alert("org_a", "latency", &["ops_hook", "dev_hook"]),
+            ],
+        );
+        let exported = json::to_string(&bundle).unwrap();
+
+        let mut imported: AlertConfigBundle = json::from_str(&exported).unwrap();
+        assert_eq!(imported.version, AlertConfigBundle::VERSION);
+        imported.set_org("org_b");
+        assert_eq!(
+            imported.external_references(),
+            ExternalReferences::default()
+        );
+
+        assert_eq!(imported.templates.len(), 1);
+        assert_eq!(imported.templates[0].org_id, "org_b");
+        assert_eq!(imported.destinations.len(), 2);
+        for destination in imported.destinations.iter() {
+            assert_eq!(destination.org_id, "org_b");
+            let Module::Alert { template, .. } = &destination.module else {
+                unreachable!()
+            };
+            assert_eq!(template, "slack");
+        }
+        assert_eq!(imported.folders, bundle.folders);
+        assert_eq!(imported.alerts.len(), 2);
+        for (src, dst) in bundle.alerts.iter().zip(imported.alerts.iter()) {
+            assert_eq!(dst.folder_id, "ops");
+            assert_eq!(dst.alert.org_id, "org_b");
+            assert_eq!(dst.alert.name, src.alert.name);
+            assert_eq!(dst.alert.destinations, src.alert.destinations);
+        }
+    }
+
+    #[test]
+    fn test_alert_config_bundle_external_references() {
+        let mut bundle = AlertConfigBundle::new(
+            vec![],
+            vec![destination("org_a", "ops_hook", "slack")],
+            vec![],
+            vec![alert("org_a", "errors", &["ops_hook", "pager"])],
+        );
+        bundle.alerts[0].alert.destination_templates = Some(
+            [("ops_hook".to_string(), "email_body".to_string())]
+                .into_iter()
+                .collect(),
+        );
+        assert_eq!(
+            bundle.external_references(),
+            ExternalReferences {
+                templates: ["email_body", "slack"].map(String::from).into(),
+                destinations: ["pager"].map(String::from).into(),
+            }
+        );
+
+        // the folder defaults to the default folder
+        let alert: BundleAlert = json::from_value(json::json!({
+            "alert": {"name": "errors", "stream_name": "app", "destinations": []},
+        }))
+        .unwrap();
+        assert_eq!(alert.folder_id, DEFAULT_FOLDER);
+    }
+}
@@ -19,6 +19,7 @@ use utoipa::ToSchema;
 use crate::{meta::search::SearchEventType, utils::json::Value};
 
 pub mod alert;
+pub mod bundle;
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema, PartialEq)]
 pub struct TriggerCondition {
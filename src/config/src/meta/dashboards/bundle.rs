@@ -0,0 +1,219 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::meta::{
+    dashboards::Dashboard,
+    folder::{Folder, DEFAULT_FOLDER},
+};
+
+/// The dashboards of an org with their folders, exported as a single document
+/// to keep them in git or to import them into another org.
+///
+/// Folders and dashboards keep their ids, so importing the same bundle again
+/// updates what the previous import created instead of duplicating it.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DashboardConfigBundle {
+    pub version: u32,
+    #[serde(default)]
+    pub folders: Vec<BundleFolder>,
+    #[serde(default)]
+    pub dashboards: Vec<BundleDashboard>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BundleFolder {
+    pub folder_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl From<Folder> for BundleFolder {
+    fn from(value: Folder) -> Self {
+        Self {
+            folder_id: value.folder_id,
+            name: value.name,
+            description: value.description,
+        }
+    }
+}
+
+impl From<BundleFolder> for Folder {
+    fn from(value: BundleFolder) -> Self {
+        Self {
+            folder_id: value.folder_id,
+            name: value.name,
+            description: value.description,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BundleDashboard {
+    #[serde(default = "default_folder")]
+    pub folder_id: String,
+    pub dashboard: Dashboard,
+}
+
+fn default_folder() -> String {
+    DEFAULT_FOLDER.to_string()
+}
+
+impl DashboardConfigBundle {
+    pub const VERSION: u32 = 1;
+
+    /// Sorts the folders and dashboards by id so that exporting unchanged
+    /// dashboards gives the same document.
+    pub fn new(mut folders: Vec<BundleFolder>, mut dashboards: Vec<BundleDashboard>) -> Self {
+        folders.sort_by(|a, b| a.folder_id.cmp(&b.folder_id));
+        dashboards.sort_by(|a, b| {
+            (a.folder_id.as_str(), a.dashboard.dashboard_id())
+                .cmp(&(b.folder_id.as_str(), b.dashboard.dashboard_id()))
+        });
+        Self {
+            version: Self::VERSION,
+            folders,
+            dashboards,
+        }
+    }
+
+    /// Ids of the folders the dashboards are in that the bundle doesn't
+    /// include, they must exist in the org the bundle is imported into. The
+    /// default folder is created when missing.
+    pub fn external_folders(&self) -> BTreeSet<String> {
+        let folders = self
+            .folders
+            .iter()
+            .map(|f| f.folder_id.as_str())
+            .collect::<BTreeSet<_>>();
+        self.dashboards
+            .iter()
+            .map(|d| d.folder_id.as_str())
+            .filter(|id| *id != DEFAULT_FOLDER && !folders.contains(id))
+            .map(String::from)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::utils::json;
+
+    fn folder(folder_id: &str, name: &str) -> BundleFolder {
+        BundleFolder {
+            folder_id: folder_id.to_string(),
+            name: name.to_string(),
+            description: String::new(),
+        }
+    }
+
+    fn dashboard(folder_id: &str, dashboard_id: &str, title: &str) -> BundleDashboard {
+        BundleDashboard {
+            folder_id: folder_id.to_string(),
+            dashboard: json::from_value(json::json!({
+                "version": 5,
+                "hash": "0",
+                "v5": {
+                    "version": 5,
+                    "dashboardId": dashboard_id,
+                    "title": title,
+                    "description": "",
+                    "role": "",
+                    "owner": "root@example.com",
+                    "created": "2025-01-01T00:00:00Z",
+                    "tabs": [],
+                },
+            }))
+            .unwrap(),
+        }
+    }
+
+    fn relationships(bundle: &DashboardConfigBundle) -> BTreeMap<String, Vec<(String, String)>> {
+        let mut map = BTreeMap::<String, Vec<(String, String)>>::new();
+        for d in bundle.dashboards.iter() {
+            map.entry(d.folder_id.clone()).or_default().push((
+                d.dashboard.dashboard_id().unwrap().to_string(),
+                d.dashboard.title().unwrap().to_string(),
+            ));
+        }
+        map
+    }
+
+    #[test]
+    fn test_dashboard_config_bundle_round_trip() {
+        let bundle = DashboardConfigBundle::new(
+            vec![folder("f2", "Network"), folder("f1", "Hosts")],
+            vec![
+                dashboard("f2", "d3", "Traffic"),
+                dashboard("f1", "d1", "CPU"),
+                dashboard(DEFAULT_FOLDER, "d4", "Overview"),
+                dashboard("f1", "d2", "Memory"),
+            ],
+        );
+        assert_eq!(bundle.folders[0].folder_id, "f1");
+        assert_eq!(
+            bundle
+                .dashboards
+                .iter()
+                .map(|d| d.dashboard.dashboard_id().unwrap())
+                .collect::<Vec<_>>(),
+            ["d4", "d1", "d2", "d3"]
+        );
+        let exported = json::to_string(&bundle).unwrap();
+
+        let imported: DashboardConfigBundle = json::from_str(&exported).unwrap();
+        assert_eq!(imported.version, DashboardConfigBundle::VERSION);
+        assert_eq!(imported.folders, bundle.folders);
+        assert_eq!(relationships(&imported), relationships(&bundle));
+        assert_eq!(
+            relationships(&imported)["f1"],
+            [
+                ("d1".to_string(), "CPU".to_string()),
+                ("d2".to_string(), "Memory".to_string())
+            ]
+        );
+        assert!(imported.external_folders().is_empty());
+
+        // exporting the imported dashboards again gives the same document
+        let reexported = DashboardConfigBundle::new(imported.folders, imported.dashboards);
+        assert_eq!(json::to_string(&reexported).unwrap(), exported);
+    }
+
+    #[test]
+    fn test_dashboard_config_bundle_external_folders() {
+        let bundle = DashboardConfigBundle::new(
+            vec![folder("f1", "Hosts")],
+            vec![
+                dashboard("f1", "d1", "CPU"),
+                dashboard("f9", "d2", "Memory"),
+                dashboard(DEFAULT_FOLDER, "d3", "Overview"),
+            ],
+        );
+        assert_eq!(bundle.external_folders(), ["f9"].map(String::from).into());
+
+        // the folder defaults to the default folder
+        let mut value = json::to_value(dashboard("f1", "d1", "CPU")).unwrap();
+        value.as_object_mut().unwrap().remove("folder_id");
+        let dashboard: BundleDashboard = json::from_value(value).unwrap();
+        assert_eq!(dashboard.folder_id, DEFAULT_FOLDER);
+    }
+}
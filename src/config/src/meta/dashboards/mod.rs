@@ -183,6 +183,7 @@ impl Dashboard {
     }
 }
 
+pub mod bundle;
 pub mod reports;
 pub mod v1;
 pub mod v2;
@@ -108,6 +108,17 @@ impl fmt::Display for HTTPType {
     }
 }
 
+/// Outcome of sending a test notification to a destination.
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct DestinationTestResponse {
+    pub success: bool,
+    /// Status code returned by the upstream, only set for http destinations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    /// Response body of the upstream, or the error if sending failed.
+    pub message: String,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct Template {
@@ -118,6 +129,54 @@ pub struct Template {
     #[serde(rename = "type")]
     pub template_type: TemplateType,
     pub body: String,
+    /// Localized variants of the template keyed by locale, e.g. `fr` or `pt-BR`.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub locales: HashMap<String, TemplateLocale>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TemplateLocale {
+    pub body: String,
+    /// Overrides the title of email templates.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+impl Template {
+    /// Returns the template to render for the given locale. A missing locale
+    /// falls back to its language, e.g. `fr` for `fr-CA`, and then to the
+    /// default body of the template.
+    pub fn localized(&self, locale: Option<&str>) -> Template {
+        let mut template = self.clone();
+        let Some(variant) = locale.and_then(|locale| self.get_locale(locale)) else {
+            return template;
+        };
+        template.body = variant.body.clone();
+        if let (TemplateType::Email { title }, Some(locale_title)) =
+            (&mut template.template_type, &variant.title)
+        {
+            if !locale_title.is_empty() {
+                *title = locale_title.clone();
+            }
+        }
+        template
+    }
+
+    fn get_locale(&self, locale: &str) -> Option<&TemplateLocale> {
+        let find = |locale: &str| {
+            self.locales
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(locale))
+                .map(|(_, v)| v)
+        };
+        let locale = locale.trim();
+        if locale.is_empty() {
+            return None;
+        }
+        find(locale).or_else(|| locale.split(['-', '_']).next().and_then(find))
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -139,3 +198,69 @@ impl fmt::Display for TemplateType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn localized_template() -> Template {
+        Template {
+            id: None,
+            org_id: "default".to_string(),
+            name: "alert".to_string(),
+            is_default: false,
+            template_type: TemplateType::Email {
+                title: "Alert fired".to_string(),
+            },
+            body: "{alert_name} fired".to_string(),
+            locales: HashMap::from_iter([
+                (
+                    "fr".to_string(),
+                    TemplateLocale {
+                        body: "{alert_name} déclenchée".to_string(),
+                        title: Some("Alerte déclenchée".to_string()),
+                    },
+                ),
+                (
+                    "pt-BR".to_string(),
+                    TemplateLocale {
+                        body: "{alert_name} disparou".to_string(),
+                        title: None,
+                    },
+                ),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_template_localized() {
+        let template = localized_template();
+
+        let fr = template.localized(Some("fr"));
+        assert_eq!(fr.body, "{alert_name} déclenchée");
+        assert!(
+            matches!(fr.template_type, TemplateType::Email { title } if title == "Alerte déclenchée")
+        );
+
+        let pt = template.localized(Some("pt-br"));
+        assert_eq!(pt.body, "{alert_name} disparou");
+        assert!(
+            matches!(pt.template_type, TemplateType::Email { title } if title == "Alert fired")
+        );
+
+        // the language is used when the region has no variant
+        assert_eq!(
+            template.localized(Some("fr-CA")).body,
+            "{alert_name} déclenchée"
+        );
+    }
+
+    #[test]
+    fn test_template_localized_fallback() {
+        let template = localized_template();
+        assert_eq!(template.localized(None).body, "{alert_name} fired");
+        assert_eq!(template.localized(Some("")).body, "{alert_name} fired");
+        assert_eq!(template.localized(Some("de-DE")).body, "{alert_name} fired");
+        assert_eq!(template.localized(Some("pt")).body, "{alert_name} fired");
+    }
+}
@@ -20,7 +20,10 @@ use vrl::{
     prelude::Function,
 };
 
-use crate::{meta::stream::StreamType, utils::json};
+use crate::{
+    meta::{pipeline::PipelineDependencyResponse, stream::StreamType},
+    utils::json,
+};
 
 #[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -48,12 +51,21 @@ pub struct TestVRLRequest {
 #[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 pub struct TestVRLResponse {
     pub results: Vec<VRLResult>, // Transformed events
+    /// Time spent running the function over all the events, in microseconds
+    #[serde(default)]
+    pub took: u64,
+    /// Output of the `log` calls the function made
+    #[serde(default)]
+    pub logs: Vec<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 pub struct VRLResult {
     pub message: String,
     pub event: json::Value,
+    /// Fields of the event the function changed
+    #[serde(default)]
+    pub diff: FieldDiff,
 }
 
 impl VRLResult {
@@ -61,7 +73,41 @@ impl VRLResult {
         Self {
             message: message.to_string(),
             event,
+            diff: FieldDiff::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FieldDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+}
+
+impl FieldDiff {
+    /// Compares the top level fields of two flattened events.
+    pub fn new(input: &json::Value, output: &json::Value) -> Self {
+        let empty = json::Map::new();
+        let input = input.as_object().unwrap_or(&empty);
+        let output = output.as_object().unwrap_or(&empty);
+        let mut diff = Self::default();
+        for (key, value) in output {
+            match input.get(key) {
+                None => diff.added.push(key.to_string()),
+                Some(v) if v != value => diff.modified.push(key.to_string()),
+                _ => {}
+            }
         }
+        diff.removed = input
+            .keys()
+            .filter(|key| !output.contains_key(*key))
+            .cloned()
+            .collect();
+        diff.added.sort();
+        diff.removed.sort();
+        diff.modified.sort();
+        diff
     }
 }
 
@@ -96,6 +142,61 @@ pub struct FunctionList {
     pub list: Vec<Transform>,
 }
 
+pub const FUNCTION_BUNDLE_VERSION: u32 = 1;
+
+/// Portable set of functions used to move functions between organizations.
+/// Stream associations are org specific and are not part of the bundle.
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct FunctionBundle {
+    #[serde(default = "default_bundle_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub functions: Vec<Transform>,
+}
+
+impl FunctionBundle {
+    pub fn new(functions: Vec<Transform>) -> Self {
+        Self {
+            version: FUNCTION_BUNDLE_VERSION,
+            functions: functions
+                .into_iter()
+                .map(|mut f| {
+                    f.streams = None;
+                    f
+                })
+                .collect(),
+        }
+    }
+}
+
+fn default_bundle_version() -> u32 {
+    FUNCTION_BUNDLE_VERSION
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct FunctionImportResponse {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub skipped: Vec<String>,
+    pub errors: Vec<FunctionImportError>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct FunctionImportError {
+    pub name: String,
+    pub error: String,
+}
+
+/// Returned with a 409 when deleting a function still used by pipelines or
+/// alerts, lists the dependents to update before deleting it.
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct FunctionInUseResponse {
+    pub code: u16,
+    pub message: String,
+    #[serde(flatten)]
+    pub dependents: PipelineDependencyResponse,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct VRLConfig {
     pub runtime: VrlRuntime,
@@ -179,4 +280,35 @@ mod tests {
         assert_eq!(f1.name, f2.name);
         assert_eq!(format!("{:?}", f1), format!("{:?}", f2));
     }
+
+    #[test]
+    fn test_function_bundle() {
+        let trans = Transform {
+            function: ".a = 1 \n .".to_string(),
+            name: "set_a".to_string(),
+            trans_type: Some(0),
+            params: "row".to_string(),
+            num_args: 1,
+            streams: Some(vec![StreamOrder {
+                stream: "test".to_string(),
+                order: 1,
+                stream_type: StreamType::Logs,
+                is_removed: false,
+                apply_before_flattening: false,
+            }]),
+        };
+        let bundle = FunctionBundle::new(vec![trans.clone()]);
+        assert_eq!(bundle.version, FUNCTION_BUNDLE_VERSION);
+        assert!(bundle.functions[0].streams.is_none());
+        assert_eq!(bundle.functions[0], trans);
+
+        let bundle_str = json::to_string(&bundle).unwrap();
+        let bundle2: FunctionBundle = json::from_str(&bundle_str).unwrap();
+        assert_eq!(bundle2.functions, bundle.functions);
+
+        let bundle3: FunctionBundle =
+            json::from_str(r#"{"functions":[{"name":"f","function":"."}]}"#).unwrap();
+        assert_eq!(bundle3.version, FUNCTION_BUNDLE_VERSION);
+        assert_eq!(bundle3.functions[0].trans_type, Some(0));
+    }
 }
@@ -37,6 +37,24 @@ impl From<&String> for InvertedIndexFormat {
     }
 }
 
+impl InvertedIndexFormat {
+    /// The parquet index selects the files, per segment of rows
+    pub fn use_parquet(&self) -> bool {
+        matches!(
+            self,
+            InvertedIndexFormat::Parquet | InvertedIndexFormat::Both
+        )
+    }
+
+    /// The tantivy index selects the rows within the files
+    pub fn use_tantivy(&self) -> bool {
+        matches!(
+            self,
+            InvertedIndexFormat::Tantivy | InvertedIndexFormat::Both
+        )
+    }
+}
+
 impl std::fmt::Display for InvertedIndexFormat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
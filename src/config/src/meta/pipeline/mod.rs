@@ -57,6 +57,9 @@ pub struct Pipeline {
     pub source: PipelineSource,
     pub nodes: Vec<Node>,
     pub edges: Vec<Edge>,
+    /// Stream where the records failing a node are written with the error and the failing node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dead_letter_stream: Option<StreamParams>,
 }
 
 impl Pipeline {
@@ -89,6 +92,8 @@ impl Pipeline {
     /// 7. In the same branch, unchecked `after_flattened` FunctionNode can't follow checked
     ///    `after_flattened` checked FunctionNode
     /// 8. EnrichmentTables can only be used in Scheduled pipelines
+    /// 9. dead letter stream, if any, has the same stream type as the source and is not the source
+    ///    stream
     ///
     /// If all satisfies, populates the [Pipeline::source] with the first node in nodes list
     pub fn validate(&mut self) -> Result<()> {
@@ -145,6 +150,22 @@ impl Pipeline {
             }
         }
 
+        // ck 9
+        if let Some(dead_letter) = &self.dead_letter_stream {
+            let source = self.get_source_stream_params();
+            if dead_letter.stream_name.is_empty() {
+                return Err(anyhow!("Dead letter stream name can't be empty"));
+            }
+            if dead_letter.stream_type != source.stream_type {
+                return Err(anyhow!(
+                    "Dead letter stream must have the same stream type as the source stream"
+                ));
+            }
+            if dead_letter == &source {
+                return Err(anyhow!("Dead letter stream can't be the source stream"));
+            }
+        }
+
         // ck 5
         if self.edges.len() < self.nodes.len() - 1 {
             return Err(anyhow!(
@@ -226,6 +247,56 @@ impl Pipeline {
             .collect()
     }
 
+    /// Collects every stream and remote destination the pipeline writes to by traversing the
+    /// node graph from the source node. The dead letter stream, if any, is included.
+    pub fn get_downstream_destinations(&self) -> Result<PipelineDestinations> {
+        let node_map = self.get_node_map();
+        let graph = self.build_adjacency_list(&node_map)?;
+        let mut destinations = PipelineDestinations {
+            pipeline_id: self.id.clone(),
+            ..Default::default()
+        };
+        let Some(source_id) = self.nodes.first().map(|node| node.get_node_id()) else {
+            return Ok(destinations);
+        };
+
+        let mut visited = HashSet::new();
+        let mut to_visit = vec![source_id.clone()];
+        while let Some(node_id) = to_visit.pop() {
+            if !visited.insert(node_id.clone()) {
+                continue;
+            }
+            if node_id != source_id {
+                match node_map.get(&node_id) {
+                    Some(NodeData::Stream(stream_params)) => {
+                        if !destinations.streams.contains(stream_params) {
+                            destinations.streams.push(stream_params.clone());
+                        }
+                    }
+                    Some(NodeData::RemoteStream(remote_stream)) => {
+                        let name = remote_stream.destination_name.to_string();
+                        if !destinations.remote_destinations.contains(&name) {
+                            destinations.remote_destinations.push(name);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(children) = graph.get(&node_id) {
+                to_visit.extend(children.iter().cloned());
+            }
+        }
+        if let Some(dead_letter_stream) = &self.dead_letter_stream {
+            if !destinations.streams.contains(dead_letter_stream) {
+                destinations.streams.push(dead_letter_stream.clone());
+            }
+        }
+
+        destinations.streams.sort_by_key(|s| s.to_string());
+        destinations.remote_destinations.sort();
+        Ok(destinations)
+    }
+
     /// Returns the number of functions nodes in this pipeline.
     ///
     /// Used for usage report.
@@ -303,6 +374,7 @@ where
     String: Type<R::Database> + Decode<'r, R::Database>,
     i32: Type<R::Database> + Decode<'r, R::Database>,
     bool: Type<R::Database> + Decode<'r, R::Database>,
+    Option<String>: Type<R::Database> + Decode<'r, R::Database>,
 {
     fn from_row(row: &'r R) -> Result<Self, Error> {
         let id: String = row.try_get("id")?;
@@ -343,6 +415,11 @@ where
             )
         };
 
+        let dead_letter_stream = row
+            .try_get::<Option<String>, _>("dead_letter_stream")?
+            .filter(|v| !v.is_empty())
+            .map(|v| json::from_str(&v).expect("Deserializing dead letter stream from ROW error"));
+
         Ok(Pipeline {
             id,
             version,
@@ -353,6 +430,7 @@ where
             source,
             nodes,
             edges,
+            dead_letter_stream,
         })
     }
 }
@@ -362,15 +440,61 @@ pub struct PipelineList {
     pub list: Vec<Pipeline>,
 }
 
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct PipelineDrainStatus {
+    pub pipeline_id: String,
+    /// Number of batches currently being processed by the pipeline on all the ingesters
+    pub in_flight_batches: usize,
+    pub drained: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PipelineNodeStats {
+    pub node_id: String,
+    pub node_type: String,
+    /// Records received by the node
+    pub received: u64,
+    /// Records sent to the children of the node, or to the destination for leaf nodes
+    pub processed: u64,
+    /// Records not passed on, e.g. filtered out by a condition or failed
+    pub dropped: u64,
+    pub errors: u64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct PipelineDestinations {
+    pub pipeline_id: String,
+    /// Streams the pipeline writes to, including the dead letter stream
+    pub streams: Vec<StreamParams>,
+    /// Names of the remote destinations the pipeline forwards records to
+    pub remote_destinations: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct PipelineStats {
+    pub pipeline_id: String,
+    pub nodes: Vec<PipelineNodeStats>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 pub struct PipelineDependencyItem {
     pub id: String,
     pub name: String,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct AlertDependencyItem {
+    pub id: Option<String>,
+    pub name: String,
+    pub stream_type: StreamType,
+    pub stream_name: String,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 pub struct PipelineDependencyResponse {
     pub list: Vec<PipelineDependencyItem>,
+    #[serde(default)]
+    pub alerts: Vec<AlertDependencyItem>,
 }
 
 /// DFS traversal to check:
@@ -571,4 +695,141 @@ mod tests {
         let new_nodes = json::from_str::<Option<Vec<Node>>>(&nodes);
         assert!(new_nodes.is_ok());
     }
+
+    #[test]
+    fn test_pipeline_dead_letter_stream_validation() {
+        let payload = json::json!(
+          {
+            "name": "pipeline test",
+            "nodes": [
+              {
+                "id": "1",
+                "data": {
+                  "node_type": "stream",
+                  "org_id": "default",
+                  "stream_name": "default",
+                  "stream_type": "logs"
+                },
+                "position": { "x": 100, "y": 100 },
+                "io_type": "input"
+              },
+              {
+                "id": "2",
+                "data": {
+                  "node_type": "stream",
+                  "org_id": "default",
+                  "stream_name": "dest",
+                  "stream_type": "logs"
+                },
+                "position": { "x": 100, "y": 100 },
+                "io_type": "output"
+              }
+            ],
+            "edges": [{ "id": "e1-2", "source": "1", "target": "2" }],
+            "dead_letter_stream": {
+              "org_id": "default",
+              "stream_name": "dead_letter",
+              "stream_type": "logs"
+            }
+          }
+        );
+        let mut pl = json::from_value::<Pipeline>(payload).unwrap();
+        assert!(pl.validate().is_ok());
+
+        pl.dead_letter_stream = Some(StreamParams::new("default", "default", StreamType::Logs));
+        assert!(pl.validate().is_err());
+        pl.dead_letter_stream = Some(StreamParams::new(
+            "default",
+            "dead_letter",
+            StreamType::Metrics,
+        ));
+        assert!(pl.validate().is_err());
+    }
+
+    #[test]
+    fn test_pipeline_downstream_destinations() {
+        let payload = json::json!(
+          {
+            "pipeline_id": "pl1",
+            "name": "multi destination",
+            "nodes": [
+              {
+                "id": "1",
+                "data": {
+                  "node_type": "stream",
+                  "org_id": "default",
+                  "stream_name": "source",
+                  "stream_type": "logs"
+                },
+                "position": { "x": 100, "y": 100 },
+                "io_type": "input"
+              },
+              {
+                "id": "2",
+                "data": {
+                  "node_type": "condition",
+                  "conditions": [{ "column": "level", "operator": "=", "value": "error", "ignore_case": false }]
+                },
+                "position": { "x": 100, "y": 100 },
+                "io_type": "default"
+              },
+              {
+                "id": "3",
+                "data": {
+                  "node_type": "stream",
+                  "org_id": "default",
+                  "stream_name": "errors",
+                  "stream_type": "logs"
+                },
+                "position": { "x": 100, "y": 100 },
+                "io_type": "output"
+              },
+              {
+                "id": "4",
+                "data": {
+                  "node_type": "stream",
+                  "org_id": "default",
+                  "stream_name": "all",
+                  "stream_type": "logs"
+                },
+                "position": { "x": 100, "y": 100 },
+                "io_type": "output"
+              },
+              {
+                "id": "5",
+                "data": {
+                  "node_type": "remote_stream",
+                  "org_id": "default",
+                  "destination_name": "remote"
+                },
+                "position": { "x": 100, "y": 100 },
+                "io_type": "output"
+              }
+            ],
+            "edges": [
+              { "id": "e1-2", "source": "1", "target": "2" },
+              { "id": "e2-3", "source": "2", "target": "3" },
+              { "id": "e1-4", "source": "1", "target": "4" },
+              { "id": "e1-5", "source": "1", "target": "5" }
+            ],
+            "dead_letter_stream": {
+              "org_id": "default",
+              "stream_name": "dead_letter",
+              "stream_type": "logs"
+            }
+          }
+        );
+        let pl = json::from_value::<Pipeline>(payload).unwrap();
+        let destinations = pl.get_downstream_destinations().unwrap();
+        assert_eq!(destinations.pipeline_id, "pl1");
+        assert_eq!(
+            destinations.streams,
+            vec![
+                StreamParams::new("default", "all", StreamType::Logs),
+                StreamParams::new("default", "dead_letter", StreamType::Logs),
+                StreamParams::new("default", "errors", StreamType::Logs),
+            ]
+        );
+        assert_eq!(destinations.remote_destinations, vec!["remote".to_string()]);
+    }
 }
@@ -44,6 +44,14 @@ pub struct ClusterLeader {
     pub updated_by: String, // instance id of ingestor
 }
 
+/// Time range `[start, end)` of the raw samples of a metric stream already
+/// rolled up at some resolution.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RollupOffset {
+    pub start: i64,
+    pub end: i64,
+}
+
 // cf. https://github.com/prometheus/prometheus/blob/f5fcaa3872ce03808567fabc56afc9cf61c732cb/model/textparse/interface.go#L106-L119
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, Display, ToSchema)]
 #[strum(serialize_all = "lowercase")]
@@ -220,6 +228,42 @@ pub struct RequestFormatQuery {
     pub query: String,
 }
 
+/// Request cardinality statistics of the metric series.
+#[derive(Debug, Deserialize)]
+pub struct RequestTsdbStatus {
+    /// Number of items to return for each list of statistics.
+    pub limit: Option<usize>,
+}
+
+// refer: https://prometheus.io/docs/prometheus/latest/querying/api/#tsdb-stats
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseTsdbStatus {
+    pub head_stats: TsdbHeadStats,
+    pub series_count_by_metric_name: Vec<TsdbStat>,
+    pub label_value_count_by_label_name: Vec<TsdbStat>,
+    pub memory_in_bytes_by_label_name: Vec<TsdbStat>,
+    pub series_count_by_label_value_pair: Vec<TsdbStat>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TsdbHeadStats {
+    pub num_series: u64,
+    pub num_label_pairs: u64,
+    pub chunk_count: u64,
+    /// Milliseconds.
+    pub min_time: i64,
+    /// Milliseconds.
+    pub max_time: i64,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct TsdbStat {
+    pub name: String,
+    pub value: u64,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Function {
     Avg,
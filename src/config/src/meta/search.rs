@@ -18,7 +18,7 @@ use serde::{Deserialize, Deserializer, Serialize};
 use utoipa::ToSchema;
 
 use crate::{
-    meta::sql::OrderBy,
+    meta::{bitvec::BitVec, sql::OrderBy},
     utils::{base64, json},
 };
 
@@ -122,9 +122,32 @@ pub struct Query {
     pub streaming_output: bool,
     #[serde(default)]
     pub streaming_id: Option<String>,
+    // sort values of the last hit of the previous page, used for cursor pagination instead of
+    // `from`. pass an empty list to get the cursor of the first page.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Vec<Object>>)]
+    pub search_after: Option<Vec<json::Value>>,
+    /// Only search the most recent files of each stream and skip the
+    /// inverted index, the response is flagged partial when files were skipped
+    #[serde(default)]
+    pub quick_scan: bool,
+    /// Debug only, root user: search the given segments of the files instead
+    /// of the ones computed by the inverted index, keyed by file key with
+    /// inclusive segment id ranges. Never served from the result cache
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub segment_ids: Option<std::collections::HashMap<String, Vec<(usize, usize)>>>,
+    /// Values of the `$name`, `$1` or `?` placeholders of the sql, `?` are
+    /// numbered from 1 in order
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub params: Option<std::collections::BTreeMap<String, json::Value>>,
 }
 
-fn default_size() -> i64 {
+pub fn default_size() -> i64 {
     crate::get_config().limit.query_default_limit
 }
 
@@ -145,6 +168,10 @@ impl Default for Query {
             skip_wal: false,
             streaming_output: false,
             streaming_id: None,
+            search_after: None,
+            quick_scan: false,
+            segment_ids: None,
+            params: None,
         }
     }
 }
@@ -164,6 +191,9 @@ impl Request {
             RequestEncoding::Empty => {}
         }
         self.encoding = RequestEncoding::Empty;
+        if self.query.params.is_some() {
+            self.query.sql = crate::meta::sql::number_positional_params(&self.query.sql);
+        }
         Ok(())
     }
 }
@@ -214,6 +244,48 @@ pub struct Response {
     pub work_group: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub order_by: Option<OrderBy>,
+    // cursor to pass as `search_after` to get the next page
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Vec<Object>>)]
+    pub search_after: Option<Vec<json::Value>>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scan_stats: Option<ResponseScanStats>,
+}
+
+/// Where the files of a search were read from and how much was scanned.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ResponseScanStats {
+    pub files: i64,
+    pub records: i64,
+    pub scan_size: i64,       // mb
+    pub compressed_size: i64, // mb
+    pub idx_scan_size: i64,   // mb
+    pub querier_files: i64,
+    pub memory_cached_files: i64,
+    pub disk_cached_files: i64,
+    /// Files the queriers downloaded from the object storage
+    pub downloaded_files: i64,
+    pub idx_took: usize,
+}
+
+impl ResponseScanStats {
+    pub fn new(stats: &ScanStats, idx_took: usize) -> Self {
+        let cached_files = stats.querier_memory_cached_files + stats.querier_disk_cached_files;
+        ResponseScanStats {
+            files: stats.files,
+            records: stats.records,
+            scan_size: stats.original_size,
+            compressed_size: stats.compressed_size,
+            idx_scan_size: stats.idx_scan_size,
+            querier_files: stats.querier_files,
+            memory_cached_files: stats.querier_memory_cached_files,
+            disk_cached_files: stats.querier_disk_cached_files,
+            downloaded_files: (stats.querier_files - cached_files).max(0),
+            idx_took,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default, ToSchema)]
@@ -271,6 +343,8 @@ impl Response {
             result_cache_ratio: 0,
             work_group: None,
             order_by: None,
+            search_after: None,
+            scan_stats: None,
         }
     }
 
@@ -345,6 +419,10 @@ impl Response {
         self.idx_scan_size = val;
     }
 
+    pub fn set_scan_stats(&mut self, val: ResponseScanStats) {
+        self.scan_stats = Some(val);
+    }
+
     pub fn set_scan_records(&mut self, val: usize) {
         self.scan_records = val;
     }
@@ -390,6 +468,33 @@ pub struct SearchPartitionRequest {
     pub query_fn: Option<String>,
     #[serde(default)]
     pub streaming_output: bool,
+    #[serde(default)]
+    pub partition_strategy: Option<SearchPartitionStrategy>,
+    /// Values of the placeholders of the sql, see [Query::params]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub params: Option<std::collections::BTreeMap<String, json::Value>>,
+}
+
+/// How `_search_partition` splits the query time range
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchPartitionStrategy {
+    /// Equal time slices
+    #[default]
+    Time,
+    /// Slices holding roughly the same number of records, estimated from file_list
+    Size,
+}
+
+impl From<&String> for SearchPartitionStrategy {
+    fn from(s: &String) -> Self {
+        match s.to_lowercase().as_str() {
+            "size" => SearchPartitionStrategy::Size,
+            _ => SearchPartitionStrategy::Time,
+        }
+    }
 }
 
 impl SearchPartitionRequest {
@@ -407,6 +512,9 @@ impl SearchPartitionRequest {
             RequestEncoding::Empty => {}
         }
         self.encoding = RequestEncoding::Empty;
+        if self.params.is_some() {
+            self.sql = crate::meta::sql::number_positional_params(&self.sql);
+        }
         Ok(())
     }
 }
@@ -422,6 +530,8 @@ impl From<&Request> for SearchPartitionRequest {
             clusters: req.clusters.clone(),
             query_fn: req.query.query_fn.clone(),
             streaming_output: req.query.streaming_output,
+            partition_strategy: None,
+            params: req.query.params.clone(),
         }
     }
 }
@@ -499,6 +609,10 @@ impl SearchHistoryRequest {
                 skip_wal: false,
                 streaming_output: false,
                 streaming_id: None,
+                search_after: None,
+                quick_scan: false,
+                segment_ids: None,
+                params: None,
             },
             encoding: RequestEncoding::Empty,
             regions: Vec::new(),
@@ -645,6 +759,15 @@ pub struct CancelQueryResponse {
     pub is_success: bool,
 }
 
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct CancelAllQueryResponse {
+    pub cancelled: usize,
+    pub trace_ids: Vec<String>,
+    /// Queries that couldn't be cancelled
+    #[serde(default)]
+    pub failed: Vec<String>,
+}
+
 #[derive(Clone, Debug, Copy, Default, Serialize, Deserialize, ToSchema)]
 pub struct ScanStats {
     pub files: i64,
@@ -697,10 +820,43 @@ impl From<Query> for cluster_rpc::SearchQuery {
             query_fn: query.query_fn.unwrap_or_default(),
             action_id: query.action_id.unwrap_or_default(),
             skip_wal: query.skip_wal,
+            quick_scan: query.quick_scan,
+            segment_ids: query
+                .segment_ids
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(key, ranges)| cluster_rpc::IdxFileName {
+                    key,
+                    segment_ids: Some(segment_ranges_to_bitvec(&ranges).into_vec()),
+                })
+                .collect(),
+            params: query
+                .params
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(name, value)| (name, value.to_string()))
+                .collect(),
         }
     }
 }
 
+/// Builds the segment bitmap of a file from inclusive segment id ranges.
+pub fn segment_ranges_to_bitvec(ranges: &[(usize, usize)]) -> BitVec {
+    let len = ranges
+        .iter()
+        .filter(|(start, end)| start <= end)
+        .map(|(_, end)| end.saturating_add(1))
+        .max()
+        .unwrap_or_default();
+    let mut segment_ids = BitVec::repeat(false, len);
+    for (start, end) in ranges {
+        if start <= end {
+            segment_ids[*start..=*end].fill(true);
+        }
+    }
+    segment_ids
+}
+
 impl From<&ScanStats> for cluster_rpc::ScanStats {
     fn from(req: &ScanStats) -> Self {
         cluster_rpc::ScanStats {
@@ -903,6 +1059,8 @@ pub struct MultiSearchPartitionRequest {
     pub query_fn: Option<String>,
     #[serde(default)]
     pub streaming_output: bool,
+    #[serde(default)]
+    pub partition_strategy: Option<SearchPartitionStrategy>,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
@@ -1030,6 +1188,10 @@ impl MultiStreamRequest {
                     skip_wal: self.skip_wal,
                     streaming_output: false,
                     streaming_id: None,
+                    search_after: None,
+                    quick_scan: false,
+                    segment_ids: None,
+                    params: None,
                 },
                 regions: self.regions.clone(),
                 clusters: self.clusters.clone(),
@@ -1055,6 +1217,47 @@ pub struct PaginationQuery {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_search_partition_request_params() {
+        let req: Request = json::from_value(json::json!({
+            "query": {
+                "sql": "SELECT * FROM t WHERE a = ? AND b = $name",
+                "start_time": 0,
+                "end_time": 10,
+                "params": { "1": 500, "name": "x" }
+            }
+        }))
+        .unwrap();
+        let mut partition_req = SearchPartitionRequest::from(&req);
+        partition_req.decode().unwrap();
+        assert_eq!(
+            partition_req.sql,
+            "SELECT * FROM t WHERE a = $1 AND b = $name"
+        );
+        assert_eq!(partition_req.params, req.query.params);
+    }
+
+    #[test]
+    fn test_segment_ids_to_search_query() {
+        let query = Query {
+            sql: "SELECT * FROM t".to_string(),
+            segment_ids: Some(std::collections::HashMap::from([(
+                "files/default/logs/t/a.parquet".to_string(),
+                vec![(1, 2), (5, 5)],
+            )])),
+            ..Default::default()
+        };
+        let query: cluster_rpc::SearchQuery = query.into();
+        assert_eq!(query.segment_ids.len(), 1);
+        let file = &query.segment_ids[0];
+        assert_eq!(file.key, "files/default/logs/t/a.parquet");
+        let segment_ids = BitVec::from_slice(file.segment_ids.as_ref().unwrap());
+        assert_eq!(segment_ids.iter_ones().collect::<Vec<_>>(), vec![1, 2, 5]);
+
+        let query: cluster_rpc::SearchQuery = Query::default().into();
+        assert!(query.segment_ids.is_empty());
+    }
+
     #[test]
     fn test_response() {
         let mut res = Response::default();
@@ -1067,6 +1270,55 @@ mod tests {
         assert_eq!(res.total, 11);
     }
 
+    #[test]
+    fn test_response_scan_stats() {
+        let stats = ScanStats {
+            files: 10,
+            records: 1000,
+            original_size: 20,
+            compressed_size: 4,
+            querier_files: 10,
+            querier_memory_cached_files: 3,
+            querier_disk_cached_files: 2,
+            idx_scan_size: 1,
+            idx_took: 7,
+        };
+        let mut res = Response::default();
+        res.set_scan_stats(ResponseScanStats::new(&stats, 7));
+        let scan_stats = res.scan_stats.as_ref().unwrap();
+        assert_eq!(scan_stats.files, 10);
+        assert_eq!(scan_stats.downloaded_files, 5);
+        assert_eq!(
+            scan_stats.memory_cached_files
+                + scan_stats.disk_cached_files
+                + scan_stats.downloaded_files,
+            scan_stats.querier_files
+        );
+
+        let value = json::to_value(&res).unwrap();
+        let scan_stats = value.get("scan_stats").unwrap();
+        for field in [
+            "files",
+            "records",
+            "scan_size",
+            "compressed_size",
+            "idx_scan_size",
+            "querier_files",
+            "memory_cached_files",
+            "disk_cached_files",
+            "downloaded_files",
+            "idx_took",
+        ] {
+            assert!(scan_stats.get(field).is_some(), "missing {field}");
+        }
+        assert_eq!(scan_stats["scan_size"], 20);
+        assert_eq!(scan_stats["idx_took"], 7);
+
+        // not serialized when the search didn't report stats
+        let value = json::to_value(Response::default()).unwrap();
+        assert!(value.get("scan_stats").is_none());
+    }
+
     #[test]
     fn test_request_encoding() {
         let req = json::json!(
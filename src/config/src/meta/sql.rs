@@ -50,7 +50,7 @@ pub fn resolve_stream_names(sql: &str) -> Result<Vec<String>, anyhow::Error> {
     let dialect = &PostgreSqlDialect {};
     let statement = DFParser::parse_sql_with_dialect(sql, dialect)?
         .pop_back()
-        .unwrap();
+        .ok_or_else(|| anyhow::anyhow!("sql is empty"))?;
     let (table_refs, _) = resolve_table_references(&statement, true)?;
     let mut tables = Vec::new();
     for table in table_refs {
@@ -63,7 +63,7 @@ pub fn resolve_stream_names_with_type(sql: &str) -> Result<Vec<TableReference>,
     let dialect = &PostgreSqlDialect {};
     let statement = DFParser::parse_sql_with_dialect(sql, dialect)?
         .pop_back()
-        .unwrap();
+        .ok_or_else(|| anyhow::anyhow!("sql is empty"))?;
     let (table_refs, _) = resolve_table_references(&statement, true)?;
     let mut tables = Vec::new();
     for table in table_refs {
@@ -72,6 +72,96 @@ pub fn resolve_stream_names_with_type(sql: &str) -> Result<Vec<TableReference>,
     Ok(tables)
 }
 
+/// Rewrites the positional `?` placeholders of a sql to `$1`, `$2`, ... in
+/// order, leaving the ones in string literals (`E'..'` and `$tag$..$tag$`
+/// included), quoted identifiers and comments alone.
+pub fn number_positional_params(sql: &str) -> String {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let chars = sql.chars().collect::<Vec<_>>();
+    let mut out = String::with_capacity(sql.len());
+    let mut num = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let c = chars[i];
+        i += 1;
+        match c {
+            '\'' | '"' => {
+                // E'..' strings escape quotes with backslashes
+                let escapes = c == '\''
+                    && start > 0
+                    && matches!(chars[start - 1], 'E' | 'e')
+                    && (start < 2 || !is_ident(chars[start - 2]));
+                while i < chars.len() {
+                    let q = chars[i];
+                    i += 1;
+                    if escapes && q == '\\' {
+                        i += 1;
+                    } else if q == c {
+                        // a doubled quote is an escaped one and keeps the literal open
+                        if chars.get(i) == Some(&c) {
+                            i += 1;
+                        } else {
+                            break;
+                        }
+                    }
+                }
+            }
+            '$' => {
+                // $tag$..$tag$ strings, $1 and $name are placeholders
+                let tag_len = chars[i..].iter().take_while(|c| is_ident(**c)).count();
+                let tag_end = i + tag_len;
+                if chars.get(tag_end) == Some(&'$')
+                    && !chars.get(i).is_some_and(|c| c.is_ascii_digit())
+                {
+                    let tag = &chars[start..=tag_end];
+                    i = tag_end + 1;
+                    while i < chars.len() && !chars[i..].starts_with(tag) {
+                        i += 1;
+                    }
+                    i += tag.len();
+                }
+            }
+            '-' if chars.get(i) == Some(&'-') => {
+                while i < chars.len() {
+                    i += 1;
+                    if chars[i - 1] == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.get(i) == Some(&'*') => {
+                i += 1;
+                let mut prev = ' ';
+                while i < chars.len() {
+                    let q = chars[i];
+                    i += 1;
+                    if prev == '*' && q == '/' {
+                        break;
+                    }
+                    prev = q;
+                }
+            }
+            '?' => {
+                match chars.get(i) {
+                    // ?1 is an explicit position
+                    Some(n) if n.is_ascii_digit() => out.push('$'),
+                    // jsonb operators
+                    Some('|') | Some('&') => out.push(c),
+                    _ => {
+                        num += 1;
+                        out.push_str(&format!("${num}"));
+                    }
+                }
+                continue;
+            }
+            _ => {}
+        }
+        out.extend(&chars[start..i.min(chars.len())]);
+    }
+    out
+}
+
 pub trait TableReferenceExt {
     fn stream_type(&self) -> String;
     fn stream_name(&self) -> String;
@@ -1221,4 +1311,37 @@ mod tests {
         let names = resolve_stream_names_with_type(sql).unwrap();
         println!("{:?}", names);
     }
+
+    #[test]
+    fn test_resolve_stream_names_empty_sql() {
+        assert!(resolve_stream_names(";").is_err());
+        assert!(resolve_stream_names_with_type(";").is_err());
+    }
+
+    #[test]
+    fn test_number_positional_params() {
+        assert_eq!(
+            number_positional_params("SELECT * FROM t WHERE a = ? AND b = ? AND c = ?5"),
+            "SELECT * FROM t WHERE a = $1 AND b = $2 AND c = $5"
+        );
+        // literals, quoted identifiers and comments are left alone
+        assert_eq!(
+            number_positional_params(
+                "SELECT \"what?\" FROM t WHERE a = 'it''s ?' AND b = ? -- why?\n/* ? */ AND c ?| d"
+            ),
+            "SELECT \"what?\" FROM t WHERE a = 'it''s ?' AND b = $1 -- why?\n/* ? */ AND c ?| d"
+        );
+        // escape and dollar quoted strings too, dollar placeholders are kept
+        assert_eq!(
+            number_positional_params(
+                r"SELECT * FROM t WHERE a = E'it\'s ?' AND b = $$what?$$ AND c = $x$ ? $x$ AND d = ? AND e = $name AND f = $1"
+            ),
+            r"SELECT * FROM t WHERE a = E'it\'s ?' AND b = $$what?$$ AND c = $x$ ? $x$ AND d = $1 AND e = $name AND f = $1"
+        );
+        // backslashes only escape quotes in E strings
+        assert_eq!(
+            number_positional_params(r"SELECT * FROM t WHERE name = 'e\' AND b = ?"),
+            r"SELECT * FROM t WHERE name = 'e\' AND b = $1"
+        );
+    }
 }
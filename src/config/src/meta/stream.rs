@@ -13,7 +13,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{cmp::max, fmt::Display};
+use std::{cmp::max, collections::BTreeMap, fmt::Display};
 
 use chrono::{DateTime, Duration, TimeZone, Utc};
 use hashbrown::HashMap;
@@ -545,6 +545,176 @@ pub struct UpdateStreamSettings {
     pub approx_partition: Option<bool>,
     #[serde(default)]
     pub extended_retention_days: UpdateSettingsWrapper<TimeRange>,
+    #[serde(skip_serializing_if = "Option::None")]
+    #[serde(default)]
+    pub webhook_mapping: Option<WebhookMapping>,
+    /// Replaces all field aliases when set
+    #[serde(skip_serializing_if = "Option::None")]
+    #[serde(default)]
+    pub field_aliases: Option<BTreeMap<String, String>>,
+    /// An empty string resets to `_timestamp`
+    #[serde(skip_serializing_if = "Option::None")]
+    #[serde(default)]
+    pub timestamp_field: Option<String>,
+    #[serde(skip_serializing_if = "Option::None")]
+    #[serde(default)]
+    pub schema_mode: Option<SchemaMode>,
+    #[serde(skip_serializing_if = "Option::None")]
+    #[serde(default)]
+    pub drop_unknown_fields: Option<bool>,
+    #[serde(skip_serializing_if = "Option::None")]
+    #[serde(default)]
+    pub max_schema_fields: Option<usize>,
+    #[serde(skip_serializing_if = "Option::None")]
+    #[serde(default)]
+    pub disable_default_sort: Option<bool>,
+    /// Replaces all field masks when set
+    #[serde(skip_serializing_if = "Option::None")]
+    #[serde(default)]
+    pub field_masks: Option<BTreeMap<String, MaskStrategy>>,
+    /// Replaces all row filters when set
+    #[serde(skip_serializing_if = "Option::None")]
+    #[serde(default)]
+    pub row_filters: Option<BTreeMap<String, String>>,
+    /// Replaces all labels when set
+    #[serde(skip_serializing_if = "Option::None")]
+    #[serde(default)]
+    pub labels: Option<BTreeMap<String, String>>,
+    /// An empty string resets to `ZO_INVERTED_INDEX_SPLIT_CHARS`, only
+    /// accepted before the stream has data
+    #[serde(skip_serializing_if = "Option::None")]
+    #[serde(default)]
+    pub inverted_index_split_chars: Option<String>,
+    /// An empty string moves the stream back to the default store
+    #[serde(skip_serializing_if = "Option::None")]
+    #[serde(default)]
+    pub storage_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::None")]
+    #[serde(default)]
+    pub ingestion_paused: Option<bool>,
+    /// A ratio of 1 keeps all traces
+    #[serde(skip_serializing_if = "Option::None")]
+    #[serde(default)]
+    pub trace_sampling_ratio: Option<f64>,
+    /// Replaces all relabel rules when set
+    #[serde(skip_serializing_if = "Option::None")]
+    #[serde(default)]
+    pub relabel_rules: Option<Vec<RelabelRule>>,
+    #[serde(skip_serializing_if = "Option::None")]
+    #[serde(default)]
+    pub result_cache_ttl: Option<i64>,
+}
+
+/// Whether ingestion may add new fields to the stream schema.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SchemaMode {
+    /// New fields are added to the schema
+    #[default]
+    Flexible,
+    /// Records with fields missing from the schema are rejected, or have
+    /// those fields dropped when `drop_unknown_fields` is set
+    Strict,
+}
+
+/// How a masked field is obscured in search results.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MaskStrategy {
+    /// The whole value is replaced
+    Full,
+    /// Only the last 4 characters are kept
+    Partial,
+    /// The value is replaced by its sha256 digest, so equal values still match
+    Hash,
+}
+
+impl MaskStrategy {
+    /// The SQL expression masking `expr` in a query, nulls stay null.
+    pub fn sql_expr(&self, expr: &str) -> String {
+        const MASK: &str = "'********'";
+        let value = format!("CAST({expr} AS VARCHAR)");
+        let masked = match self {
+            MaskStrategy::Full => MASK.to_string(),
+            MaskStrategy::Partial => format!(
+                "CASE WHEN character_length({value}) <= 4 THEN {MASK} ELSE concat('****', right({value}, 4)) END"
+            ),
+            MaskStrategy::Hash => format!("encode(sha256({value}), 'hex')"),
+        };
+        format!("CASE WHEN {expr} IS NULL THEN NULL ELSE {masked} END")
+    }
+}
+
+/// What a relabel rule does with a metric sample.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RelabelAction {
+    /// Sets `target_label` from `replacement` when the source label matches
+    #[default]
+    Replace,
+    /// Drops the samples whose source label doesn't match
+    Keep,
+    /// Drops the samples whose source label matches
+    Drop,
+    /// Removes the labels whose name matches
+    LabelDrop,
+}
+
+/// A rule rewriting the labels of ingested metric samples, like a Prometheus
+/// `metric_relabel_configs` entry with a single source label. The regex is
+/// anchored at both ends and a missing label has an empty value.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct RelabelRule {
+    #[serde(default)]
+    pub action: RelabelAction,
+    #[serde(default)]
+    pub source_label: String,
+    #[serde(default = "default_relabel_regex")]
+    pub regex: String,
+    #[serde(default)]
+    pub target_label: String,
+    /// May refer to the regex capture groups, e.g. `$1`
+    #[serde(default = "default_relabel_replacement")]
+    pub replacement: String,
+}
+
+fn default_relabel_regex() -> String {
+    "(.*)".to_string()
+}
+
+fn default_relabel_replacement() -> String {
+    "$1".to_string()
+}
+
+impl RelabelRule {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Err(e) = regex::Regex::new(&self.regex) {
+            return Err(format!("invalid relabel regex {}: {e}", self.regex));
+        }
+        match self.action {
+            RelabelAction::Replace if self.target_label.is_empty() => {
+                Err("relabel action replace needs a target_label".to_string())
+            }
+            RelabelAction::Keep | RelabelAction::Drop if self.source_label.is_empty() => {
+                Err("relabel actions keep and drop need a source_label".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// How the `_webhook` endpoint maps an arbitrary vendor payload to a record.
+/// Paths are dot separated keys into the payload, e.g. `repository.full_name`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct WebhookMapping {
+    /// Path of the event time, copied to `_timestamp`
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp_field: Option<String>,
+    /// Target field name by source path, applied before flattening
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
@@ -672,6 +842,65 @@ pub struct StreamSettings {
     pub index_updated_at: i64,
     #[serde(default)]
     pub extended_retention_days: Vec<TimeRange>,
+    #[serde(default)]
+    pub webhook_mapping: Option<WebhookMapping>,
+    /// Canonical field name by ingested field name, applied before the
+    /// record is written so only the canonical name reaches the schema
+    #[serde(default)]
+    pub field_aliases: BTreeMap<String, String>,
+    /// Field carrying the event time of ingested records, used for
+    /// `_timestamp` when the record has none
+    #[serde(default)]
+    pub timestamp_field: Option<String>,
+    #[serde(default)]
+    pub schema_mode: SchemaMode,
+    /// In strict mode, drop unknown fields instead of rejecting the record
+    #[serde(default)]
+    pub drop_unknown_fields: bool,
+    /// Overrides `ZO_SCHEMA_MAX_FIELDS` when set, 0 uses the global limit
+    #[serde(default)]
+    pub max_schema_fields: usize,
+    /// Don't add `ORDER BY _timestamp DESC` to queries without ORDER BY
+    #[serde(default)]
+    pub disable_default_sort: bool,
+    /// Fields masked in search results for users who can't edit the stream
+    #[serde(default)]
+    pub field_masks: BTreeMap<String, MaskStrategy>,
+    /// Role name to SQL condition, ANDed to the queries of users with that
+    /// role so they only see the matching rows
+    #[serde(default)]
+    pub row_filters: BTreeMap<String, String>,
+    /// Free-form key-value labels, like `team` or `env`, to organize streams
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    /// Overrides `ZO_INVERTED_INDEX_SPLIT_CHARS` to tokenize the full text
+    /// search fields of this stream in the parquet and tantivy inverted
+    /// indexes, when set
+    #[serde(default)]
+    pub inverted_index_split_chars: Option<String>,
+    /// Named store of `ZO_S3_NAMED_STORES` the files of this stream are
+    /// read from and written to, the default store when unset
+    #[serde(default)]
+    pub storage_name: Option<String>,
+    /// Ingestion of this stream is refused, or its records dropped where a
+    /// request writes to several streams, until it is resumed
+    #[serde(default)]
+    pub ingestion_paused: bool,
+    /// Share of the traces kept by trace ingestion, picked by trace_id, all
+    /// traces are kept when unset. The spans of a trace sent in the same
+    /// request as an error span are always kept, the ones sent in other
+    /// requests follow the ratio
+    #[serde(default)]
+    pub trace_sampling_ratio: Option<f64>,
+    /// Rules applied in order to the labels of the samples ingested into
+    /// this metric stream, before its pipeline
+    #[serde(default)]
+    pub relabel_rules: Vec<RelabelRule>,
+    /// Seconds after which the cached query results of this stream are
+    /// recomputed, overrides `ZO_RESULT_CACHE_TTL` when set, 0 uses the
+    /// global ttl
+    #[serde(default)]
+    pub result_cache_ttl: i64,
 }
 
 impl Serialize for StreamSettings {
@@ -723,6 +952,85 @@ impl Serialize for StreamSettings {
                 state.skip_field("flatten_level")?;
             }
         }
+        if self.field_aliases.is_empty() {
+            state.skip_field("field_aliases")?;
+        } else {
+            state.serialize_field("field_aliases", &self.field_aliases)?;
+        }
+        match self.timestamp_field.as_ref() {
+            Some(field) => {
+                state.serialize_field("timestamp_field", field)?;
+            }
+            None => {
+                state.skip_field("timestamp_field")?;
+            }
+        }
+        match self.webhook_mapping.as_ref() {
+            Some(mapping) => {
+                state.serialize_field("webhook_mapping", mapping)?;
+            }
+            None => {
+                state.skip_field("webhook_mapping")?;
+            }
+        }
+        if self.schema_mode == SchemaMode::Flexible {
+            state.skip_field("schema_mode")?;
+        } else {
+            state.serialize_field("schema_mode", &self.schema_mode)?;
+        }
+        if self.drop_unknown_fields {
+            state.serialize_field("drop_unknown_fields", &self.drop_unknown_fields)?;
+        } else {
+            state.skip_field("drop_unknown_fields")?;
+        }
+        if self.max_schema_fields > 0 {
+            state.serialize_field("max_schema_fields", &self.max_schema_fields)?;
+        } else {
+            state.skip_field("max_schema_fields")?;
+        }
+        if self.disable_default_sort {
+            state.serialize_field("disable_default_sort", &self.disable_default_sort)?;
+        } else {
+            state.skip_field("disable_default_sort")?;
+        }
+        if self.field_masks.is_empty() {
+            state.skip_field("field_masks")?;
+        } else {
+            state.serialize_field("field_masks", &self.field_masks)?;
+        }
+        if self.row_filters.is_empty() {
+            state.skip_field("row_filters")?;
+        } else {
+            state.serialize_field("row_filters", &self.row_filters)?;
+        }
+        if self.labels.is_empty() {
+            state.skip_field("labels")?;
+        } else {
+            state.serialize_field("labels", &self.labels)?;
+        }
+        match self.inverted_index_split_chars.as_ref() {
+            Some(chars) => state.serialize_field("inverted_index_split_chars", chars)?,
+            None => state.skip_field("inverted_index_split_chars")?,
+        }
+        match self.storage_name.as_ref() {
+            Some(name) => state.serialize_field("storage_name", name)?,
+            None => state.skip_field("storage_name")?,
+        }
+        state.serialize_field("ingestion_paused", &self.ingestion_paused)?;
+        match self.trace_sampling_ratio {
+            Some(ratio) => state.serialize_field("trace_sampling_ratio", &ratio)?,
+            None => state.skip_field("trace_sampling_ratio")?,
+        }
+        if self.relabel_rules.is_empty() {
+            state.skip_field("relabel_rules")?;
+        } else {
+            state.serialize_field("relabel_rules", &self.relabel_rules)?;
+        }
+        if self.result_cache_ttl > 0 {
+            state.serialize_field("result_cache_ttl", &self.result_cache_ttl)?;
+        } else {
+            state.skip_field("result_cache_ttl")?;
+        }
         state.end()
     }
 }
@@ -848,6 +1156,84 @@ impl From<&str> for StreamSettings {
             }
         }
 
+        let webhook_mapping = settings
+            .get("webhook_mapping")
+            .and_then(|v| json::from_value(v.clone()).ok());
+
+        let field_aliases = settings
+            .get("field_aliases")
+            .and_then(|v| json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+
+        let timestamp_field = settings
+            .get("timestamp_field")
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string());
+
+        let schema_mode = settings
+            .get("schema_mode")
+            .and_then(|v| json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+
+        let drop_unknown_fields = settings
+            .get("drop_unknown_fields")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let max_schema_fields = settings
+            .get("max_schema_fields")
+            .and_then(|v| v.as_u64())
+            .unwrap_or_default() as usize;
+
+        let disable_default_sort = settings
+            .get("disable_default_sort")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let field_masks = settings
+            .get("field_masks")
+            .and_then(|v| json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+
+        let row_filters = settings
+            .get("row_filters")
+            .and_then(|v| json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+
+        let labels = settings
+            .get("labels")
+            .and_then(|v| json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+
+        let inverted_index_split_chars = settings
+            .get("inverted_index_split_chars")
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string());
+
+        let storage_name = settings
+            .get("storage_name")
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string());
+
+        let ingestion_paused = settings
+            .get("ingestion_paused")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let trace_sampling_ratio = settings
+            .get("trace_sampling_ratio")
+            .and_then(|v| v.as_f64());
+
+        let relabel_rules = settings
+            .get("relabel_rules")
+            .and_then(|v| json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+
+        let result_cache_ttl = settings
+            .get("result_cache_ttl")
+            .and_then(|v| v.as_i64())
+            .unwrap_or_default();
+
         Self {
             partition_time_level,
             partition_keys,
@@ -863,6 +1249,22 @@ impl From<&str> for StreamSettings {
             distinct_value_fields,
             index_updated_at,
             extended_retention_days,
+            webhook_mapping,
+            field_aliases,
+            timestamp_field,
+            schema_mode,
+            drop_unknown_fields,
+            max_schema_fields,
+            disable_default_sort,
+            field_masks,
+            row_filters,
+            labels,
+            inverted_index_split_chars,
+            storage_name,
+            ingestion_paused,
+            trace_sampling_ratio,
+            relabel_rules,
+            result_cache_ttl,
         }
     }
 }
@@ -902,7 +1304,41 @@ impl StreamPartition {
     }
 
     pub fn get_partition_key(&self, value: &str) -> String {
-        format!("{}={}", self.field, self.get_partition_value(value))
+        format!("{}={}", self.column(), self.get_partition_value(value))
+    }
+
+    /// Column holding the partition value. A nested json path like
+    /// `resource.service.name` names the column flattening stores it in,
+    /// `resource_service_name`, so queries on that column prune partitions.
+    pub fn column(&self) -> String {
+        if !self.field.contains('.') {
+            return self.field.clone();
+        }
+        let mut column = self.field.replace('.', "_");
+        crate::utils::flatten::format_key(&mut column);
+        column
+    }
+
+    /// Value of the partition field in a record, following the nested json
+    /// path of the field when the record has no such top level field.
+    pub fn get_record_value<'a>(&self, record: &'a Map<String, Value>) -> Option<&'a Value> {
+        if let Some(val) = record.get(&self.field) {
+            return Some(val);
+        }
+        if !self.field.contains('.') {
+            return None;
+        }
+        // flattened record
+        if let Some(val) = record.get(&self.column()) {
+            return Some(val);
+        }
+        // record kept nested, past the stream flatten level
+        let mut path = self.field.split('.');
+        let mut val = record.get(path.next()?)?;
+        for key in path {
+            val = val.get(key)?;
+        }
+        Some(val)
     }
 
     pub fn get_partition_value(&self, value: &str) -> String {
@@ -1096,6 +1532,31 @@ mod tests {
         assert_eq!(file_meta, resp);
     }
 
+    #[test]
+    fn test_nested_partition_field() {
+        let part = StreamPartition::new("resource.service.name");
+        assert_eq!(part.column(), "resource_service_name");
+        assert_eq!(part.get_partition_key("api"), "resource_service_name=api");
+
+        let flattened = json::json!({"resource_service_name": "api", "level": "info"});
+        let flattened = flattened.as_object().unwrap();
+        assert_eq!(part.get_record_value(flattened), Some(&json::json!("api")));
+        let nested = json::json!({"resource": {"service": {"name": "web"}}});
+        let nested = nested.as_object().unwrap();
+        assert_eq!(part.get_record_value(nested), Some(&json::json!("web")));
+        let missing = json::json!({"resource": {"service": "web"}});
+        assert_eq!(part.get_record_value(missing.as_object().unwrap()), None);
+
+        // top level fields are unchanged
+        let part = StreamPartition::new("Level");
+        assert_eq!(part.column(), "Level");
+        let record = json::json!({"Level": "info"});
+        assert_eq!(
+            part.get_record_value(record.as_object().unwrap()),
+            Some(&json::json!("info"))
+        );
+    }
+
     #[cfg(feature = "gxhash")]
     #[test]
     fn test_hash_partition() {
@@ -1218,4 +1679,17 @@ mod tests {
         let expected_res = vec![TimeRange::new(0, 199), TimeRange::new(200, 300)];
         assert_eq!(TimeRange::flatten_overlapping_ranges(ranges), expected_res);
     }
+
+    #[test]
+    fn test_stream_settings_labels() {
+        let mut settings = StreamSettings::default();
+        assert!(!json::to_string(&settings).unwrap().contains("labels"));
+
+        settings.labels = BTreeMap::from([
+            ("team".to_string(), "billing".to_string()),
+            ("env".to_string(), "prod".to_string()),
+        ]);
+        let data = json::to_string(&settings).unwrap();
+        assert_eq!(StreamSettings::from(data.as_str()).labels, settings.labels);
+    }
 }
@@ -36,4 +36,17 @@ pub struct SearchEventReq {
     pub search_event_context: Option<SearchEventContext>,
     #[serde(default)]
     pub fallback_order_by_col: Option<String>,
+    #[serde(default)]
+    pub partition_strategy: Option<crate::meta::search::SearchPartitionStrategy>,
+}
+
+/// Subscribes to the records ingested in a stream from now on, `filter` is
+/// an optional sql condition the records must match.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TailEventReq {
+    pub trace_id: String,
+    pub stream_name: String,
+    pub stream_type: crate::meta::stream::StreamType,
+    #[serde(default)]
+    pub filter: Option<String>,
 }
@@ -621,6 +621,32 @@ pub static META_NUM_DASHBOARDS: Lazy<IntGaugeVec> = Lazy::new(|| {
     .expect("Metric created")
 });
 
+// metrics for alert evaluation
+pub static ALERT_EVAL_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    HistogramVec::new(
+        HistogramOpts::new(
+            "alert_eval_duration",
+            "Alert evaluation time in seconds. ".to_owned() + HELP_SUFFIX,
+        )
+        .namespace(NAMESPACE)
+        .const_labels(create_const_labels()),
+        &["organization", "alert"],
+    )
+    .expect("Metric created")
+});
+pub static ALERT_EVAL_FAILURES: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "alert_eval_failures",
+            "Failed alert evaluations. ".to_owned() + HELP_SUFFIX,
+        )
+        .namespace(NAMESPACE)
+        .const_labels(create_const_labels()),
+        &["organization", "alert"],
+    )
+    .expect("Metric created")
+});
+
 // metrics for query manager
 pub static QUERY_RUNNING_NUMS: Lazy<IntGaugeVec> = Lazy::new(|| {
     IntGaugeVec::new(
@@ -925,6 +951,14 @@ fn register_metrics(registry: &Registry) {
         .register(Box::new(META_NUM_DASHBOARDS.clone()))
         .expect("Metric registered");
 
+    // alert evaluation stats
+    registry
+        .register(Box::new(ALERT_EVAL_DURATION.clone()))
+        .expect("Metric registered");
+    registry
+        .register(Box::new(ALERT_EVAL_FAILURES.clone()))
+        .expect("Metric registered");
+
     // db stats
     registry
         .register(Box::new(DB_QUERY_NUMS.clone()))
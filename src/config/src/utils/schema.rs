@@ -288,10 +288,190 @@ pub fn filter_source_by_partition_key(source: &str, filters: &[(String, Vec<Stri
     })
 }
 
+/// Converts the schema to an Avro record schema named after the stream.
+/// Nullable fields become a union with `null` defaulting to `null`.
+pub fn to_avro_schema(name: &str, schema: &Schema) -> Value {
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let typ = avro_type(field.data_type());
+            let mut f = Map::new();
+            f.insert("name".to_string(), Value::String(field.name().to_string()));
+            if field.is_nullable() {
+                f.insert(
+                    "type".to_string(),
+                    Value::Array(vec![Value::String("null".to_string()), typ]),
+                );
+                f.insert("default".to_string(), Value::Null);
+            } else {
+                f.insert("type".to_string(), typ);
+            }
+            Value::Object(f)
+        })
+        .collect::<Vec<_>>();
+    serde_json::json!({
+        "type": "record",
+        "name": name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>(),
+        "fields": fields,
+    })
+}
+
+fn avro_type(data_type: &DataType) -> Value {
+    match data_type {
+        DataType::Null => "null".into(),
+        DataType::Boolean => "boolean".into(),
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::UInt8 | DataType::UInt16 => {
+            "int".into()
+        }
+        DataType::Int64 | DataType::UInt32 | DataType::UInt64 => "long".into(),
+        DataType::Float16 | DataType::Float32 => "float".into(),
+        DataType::Float64 => "double".into(),
+        DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => "bytes".into(),
+        DataType::Date32 => serde_json::json!({"type": "int", "logicalType": "date"}),
+        DataType::Timestamp(unit, _) => {
+            let logical_type = match unit {
+                arrow_schema::TimeUnit::Second | arrow_schema::TimeUnit::Millisecond => {
+                    "timestamp-millis"
+                }
+                _ => "timestamp-micros",
+            };
+            serde_json::json!({"type": "long", "logicalType": logical_type})
+        }
+        DataType::List(f) | DataType::LargeList(f) => {
+            serde_json::json!({"type": "array", "items": avro_type(f.data_type())})
+        }
+        _ => "string".into(),
+    }
+}
+
+/// Converts the schema to a JSON Schema (draft 2020-12) object titled after
+/// the stream. Non-nullable fields are listed as required.
+pub fn to_json_schema(name: &str, schema: &Schema) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for field in schema.fields() {
+        let mut typ = json_schema_type(field.data_type());
+        if field.is_nullable() {
+            if let Some(Value::String(t)) = typ.get("type").cloned() {
+                typ["type"] = serde_json::json!([t, "null"]);
+            }
+        } else {
+            required.push(Value::String(field.name().to_string()));
+        }
+        properties.insert(field.name().to_string(), typ);
+    }
+    serde_json::json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": name,
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+fn json_schema_type(data_type: &DataType) -> Value {
+    match data_type {
+        DataType::Null => serde_json::json!({"type": "null"}),
+        DataType::Boolean => serde_json::json!({"type": "boolean"}),
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64
+        | DataType::Date32
+        | DataType::Timestamp(..) => serde_json::json!({"type": "integer"}),
+        DataType::Float16 | DataType::Float32 | DataType::Float64 => {
+            serde_json::json!({"type": "number"})
+        }
+        DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => {
+            serde_json::json!({"type": "string", "contentEncoding": "base64"})
+        }
+        DataType::List(f) | DataType::LargeList(f) => {
+            serde_json::json!({"type": "array", "items": json_schema_type(f.data_type())})
+        }
+        _ => serde_json::json!({"type": "string"}),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn stream_schema() -> Schema {
+        Schema::new(vec![
+            Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false),
+            Field::new("log", DataType::Utf8, true),
+            Field::new("code", DataType::Int32, true),
+            Field::new("took", DataType::Float64, true),
+            Field::new("success", DataType::Boolean, true),
+            Field::new(
+                "tags",
+                DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))),
+                true,
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_to_avro_schema() {
+        let avro = to_avro_schema("k8s-logs", &stream_schema());
+        assert_eq!(avro["type"], "record");
+        assert_eq!(avro["name"], "k8s_logs");
+        let fields = avro["fields"].as_array().unwrap();
+        assert_eq!(fields.len(), 6);
+        assert_eq!(fields[0]["name"], TIMESTAMP_COL_NAME);
+        assert_eq!(fields[0]["type"], "long");
+        let types = fields[1..]
+            .iter()
+            .map(|f| f["type"][1].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![
+                Value::from("string"),
+                Value::from("int"),
+                Value::from("double"),
+                Value::from("boolean"),
+                serde_json::json!({"type": "array", "items": "string"}),
+            ]
+        );
+        assert!(fields[1..]
+            .iter()
+            .all(|f| f["type"][0] == "null" && f["default"].is_null()));
+    }
+
+    #[test]
+    fn test_to_json_schema() {
+        let json_schema = to_json_schema("k8s-logs", &stream_schema());
+        assert_eq!(json_schema["title"], "k8s-logs");
+        assert_eq!(json_schema["type"], "object");
+        assert_eq!(
+            json_schema["required"],
+            serde_json::json!([TIMESTAMP_COL_NAME])
+        );
+        let props = &json_schema["properties"];
+        assert_eq!(props[TIMESTAMP_COL_NAME]["type"], "integer");
+        assert_eq!(props["log"]["type"], serde_json::json!(["string", "null"]));
+        assert_eq!(
+            props["code"]["type"],
+            serde_json::json!(["integer", "null"])
+        );
+        assert_eq!(props["took"]["type"], serde_json::json!(["number", "null"]));
+        assert_eq!(
+            props["success"]["type"],
+            serde_json::json!(["boolean", "null"])
+        );
+        assert_eq!(props["tags"]["type"], serde_json::json!(["array", "null"]));
+        assert_eq!(props["tags"]["items"]["type"], "string");
+    }
+
     #[test]
     fn test_matches_by_partition_key_with_str() {
         let path = "files/default/logs/gke-fluentbit/2023/04/14/08/kuberneteshost=gke-dev1/kubernetesnamespacename=ziox-dev/7052558621820981249.parquet";
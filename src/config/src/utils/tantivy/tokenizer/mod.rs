@@ -14,8 +14,10 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 mod o2_tokenizer;
+mod split_chars_tokenizer;
 
 pub use o2_tokenizer::O2Tokenizer;
+pub use split_chars_tokenizer::SplitCharsTokenizer;
 use tantivy::tokenizer::{SimpleTokenizer, TextAnalyzer, Token};
 
 use crate::get_config;
@@ -39,6 +41,35 @@ pub fn o2_tokenizer_build() -> TextAnalyzer {
     }
 }
 
+/// The [o2_tokenizer_build] of a stream, splitting on `split_chars` instead
+/// when the stream overrides them. The index of the stream must be built and
+/// searched with the same ones.
+pub fn o2_tokenizer_build_with(split_chars: &str) -> TextAnalyzer {
+    if split_chars.is_empty() {
+        return o2_tokenizer_build();
+    }
+    tantivy::tokenizer::TextAnalyzer::builder(SplitCharsTokenizer::new(split_chars))
+        .filter(tantivy::tokenizer::RemoveLongFilter::limit(40))
+        .filter(tantivy::tokenizer::LowerCaser)
+        .build()
+}
+
+/// The [o2_collect_tokens] of a stream splitting on `split_chars`, see
+/// [o2_tokenizer_build_with].
+pub fn o2_collect_tokens_with(text: &str, split_chars: &str) -> Vec<String> {
+    if split_chars.is_empty() {
+        return o2_collect_tokens(text);
+    }
+    let mut a = TextAnalyzer::from(SplitCharsTokenizer::new(split_chars));
+    let mut token_stream = a.token_stream(text);
+    let mut tokens: Vec<String> = Vec::new();
+    let mut add_token = |token: &Token| {
+        tokens.push(token.text.to_lowercase());
+    };
+    token_stream.process(&mut add_token);
+    tokens
+}
+
 pub fn o2_collect_tokens(text: &str) -> Vec<String> {
     let mut a = if get_config()
         .common
@@ -0,0 +1,119 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::str::CharIndices;
+
+use tantivy::tokenizer::{Token, TokenStream, Tokenizer};
+
+/// Tokenize the text by splitting on whitespaces and the given characters,
+/// for the streams overriding the split chars.
+#[derive(Clone)]
+pub struct SplitCharsTokenizer {
+    split_chars: Vec<char>,
+    token: Token,
+}
+
+impl SplitCharsTokenizer {
+    pub fn new(split_chars: &str) -> Self {
+        Self {
+            split_chars: split_chars.chars().collect(),
+            token: Token::default(),
+        }
+    }
+}
+
+/// TokenStream produced by the `SplitCharsTokenizer`.
+pub struct SplitCharsTokenStream<'a> {
+    text: &'a str,
+    chars: CharIndices<'a>,
+    split_chars: &'a [char],
+    token: &'a mut Token,
+}
+
+impl Tokenizer for SplitCharsTokenizer {
+    type TokenStream<'a> = SplitCharsTokenStream<'a>;
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> SplitCharsTokenStream<'a> {
+        self.token.reset();
+        SplitCharsTokenStream {
+            text,
+            chars: text.char_indices(),
+            split_chars: &self.split_chars,
+            token: &mut self.token,
+        }
+    }
+}
+
+impl SplitCharsTokenStream<'_> {
+    fn is_split(&self, c: char) -> bool {
+        c.is_whitespace() || self.split_chars.contains(&c)
+    }
+}
+
+impl TokenStream for SplitCharsTokenStream<'_> {
+    fn advance(&mut self) -> bool {
+        self.token.text.clear();
+        self.token.position = self.token.position.wrapping_add(1);
+        while let Some((offset_from, c)) = self.chars.next() {
+            if self.is_split(c) {
+                continue;
+            }
+            let split_chars = self.split_chars;
+            let offset_to = (self.chars)
+                .find(|(_, c)| c.is_whitespace() || split_chars.contains(c))
+                .map_or(self.text.len(), |(offset, _)| offset);
+            self.token.offset_from = offset_from;
+            self.token.offset_to = offset_to;
+            self.token.text.push_str(&self.text[offset_from..offset_to]);
+            return true;
+        }
+        false
+    }
+
+    fn token(&self) -> &Token {
+        self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::tokenizer::TextAnalyzer;
+
+    use super::*;
+
+    #[test]
+    fn test_split_chars_tokenizer() {
+        let mut a = TextAnalyzer::from(SplitCharsTokenizer::new("/|"));
+        let mut token_stream = a.token_stream("GET /api/v1|user-id 200 中文");
+        let mut tokens = vec![];
+        token_stream.process(&mut |token: &Token| {
+            tokens.push((token.text.clone(), token.offset_from, token.offset_to))
+        });
+        assert_eq!(
+            tokens,
+            vec![
+                ("GET".to_string(), 0, 3),
+                ("api".to_string(), 5, 8),
+                ("v1".to_string(), 9, 11),
+                ("user-id".to_string(), 12, 19),
+                ("200".to_string(), 20, 23),
+                ("中文".to_string(), 24, 30),
+            ]
+        );
+    }
+}
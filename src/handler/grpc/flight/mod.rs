@@ -45,9 +45,11 @@ use tracing_opentelemetry::OpenTelemetrySpanExt;
 #[cfg(feature = "enterprise")]
 use crate::service::search::SEARCH_SERVER;
 use crate::{
+    common::infra::wal::ReaderGuard,
     handler::grpc::MetadataMap,
     service::search::{
         grpc::flight as grpcFlight, request::FlightSearchRequest, utils::AsyncDefer,
+        SearchGateGuard, SEARCH_GATE,
     },
 };
 
@@ -97,6 +99,11 @@ impl FlightService for FlightServiceImpl {
             timeout
         );
 
+        // held until the stream is dropped, along with the WAL locks
+        let gate = SEARCH_GATE.track();
+        // keeps the WAL locks of this search from being reaped while it runs
+        let reader = crate::common::infra::wal::track_reader(&trace_id);
+
         #[cfg(feature = "enterprise")]
         if is_super_cluster && !SEARCH_SERVER.contain_key(&trace_id).await {
             SEARCH_SERVER
@@ -112,7 +119,7 @@ impl FlightService for FlightServiceImpl {
         }
 
         // 2. prepare dataufion context
-        let (ctx, physical_plan, defer, scan_stats) = match result {
+        let (ctx, physical_plan, defer, scan_stats, partial_err) = match result {
             Ok(v) => v,
             Err(e) => {
                 // clear session data
@@ -148,11 +155,11 @@ impl FlightService for FlightServiceImpl {
             println!("{}", plan);
         }
 
-        schema = add_scan_stats_to_schema(schema, scan_stats);
+        schema = add_scan_stats_to_schema(schema, scan_stats, &partial_err);
 
         let start = std::time::Instant::now();
         let write_options: IpcWriteOptions = IpcWriteOptions::default()
-            .try_with_compression(Some(CompressionType::ZSTD))
+            .try_with_compression(get_ipc_compression(&cfg.grpc.ipc_compression))
             .map_err(|e| Status::internal(e.to_string()))?;
         let flight_data_stream = FlightDataEncoderBuilder::new()
             .with_schema(schema)
@@ -168,6 +175,8 @@ impl FlightService for FlightServiceImpl {
                     Status::internal(e.to_string())
                 })?,
                 defer,
+                gate,
+                reader,
                 start,
                 timeout,
             ))
@@ -246,6 +255,9 @@ struct FlightSenderStream {
     trace_id: String,
     stream: SendableRecordBatchStream,
     defer: Option<AsyncDefer>,
+    // dropped after the WAL locks are released in drop
+    _gate: SearchGateGuard,
+    _reader: ReaderGuard,
     start: std::time::Instant,
     timeout: u64,
 }
@@ -255,6 +267,8 @@ impl FlightSenderStream {
         trace_id: String,
         stream: SendableRecordBatchStream,
         defer: Option<AsyncDefer>,
+        gate: SearchGateGuard,
+        reader: ReaderGuard,
         start: std::time::Instant,
         timeout: u64,
     ) -> Self {
@@ -262,6 +276,8 @@ impl FlightSenderStream {
             trace_id,
             stream,
             defer,
+            _gate: gate,
+            _reader: reader,
             start,
             timeout,
         }
@@ -335,6 +351,7 @@ type PlanResult = (
     Arc<dyn datafusion::physical_plan::ExecutionPlan>,
     Option<AsyncDefer>,
     ScanStats,
+    String,
 );
 
 #[cfg(feature = "enterprise")]
@@ -344,12 +361,12 @@ async fn get_ctx_and_physical_plan(
     req: &FlightSearchRequest,
 ) -> Result<PlanResult, infra::errors::Error> {
     if req.super_cluster_info.is_super_cluster {
-        let (ctx, physical_plan, defer, scan_stats) =
+        let (ctx, physical_plan, defer, scan_stats, partial_err) =
             crate::service::search::super_cluster::follower::search(trace_id, req).await?;
-        Ok((ctx, physical_plan, Some(defer), scan_stats))
+        Ok((ctx, physical_plan, Some(defer), scan_stats, partial_err))
     } else {
         let (ctx, physical_plan, scan_stats) = grpcFlight::search(trace_id, req).await?;
-        Ok((ctx, physical_plan, None, scan_stats))
+        Ok((ctx, physical_plan, None, scan_stats, String::new()))
     }
 }
 
@@ -360,12 +377,104 @@ async fn get_ctx_and_physical_plan(
     req: &FlightSearchRequest,
 ) -> Result<PlanResult, infra::errors::Error> {
     let (ctx, physical_plan, scan_stats) = grpcFlight::search(trace_id, req).await?;
-    Ok((ctx, physical_plan, None, scan_stats))
+    Ok((ctx, physical_plan, None, scan_stats, String::new()))
 }
 
-fn add_scan_stats_to_schema(schema: Arc<Schema>, scan_stats: ScanStats) -> Arc<Schema> {
+fn add_scan_stats_to_schema(
+    schema: Arc<Schema>,
+    scan_stats: ScanStats,
+    partial_err: &str,
+) -> Arc<Schema> {
     let mut metadata = schema.metadata().clone();
     let stats_string = serde_json::to_string(&scan_stats).unwrap_or_default();
     metadata.insert("scan_stats".to_string(), stats_string);
+    // the remote scan marks the search as partial
+    if !partial_err.is_empty() {
+        metadata.insert("partial_err".to_string(), partial_err.to_string());
+    }
     Arc::new(schema.as_ref().clone().with_metadata(metadata))
 }
+
+fn get_ipc_compression(name: &str) -> Option<CompressionType> {
+    match name.trim().to_lowercase().as_str() {
+        "zstd" => Some(CompressionType::ZSTD),
+        "lz4" => Some(CompressionType::LZ4_FRAME),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use arrow::{
+        array::{Int64Array, StringArray},
+        ipc::{reader::StreamReader, writer::StreamWriter},
+    };
+    use arrow_schema::{DataType, Field};
+
+    use super::*;
+
+    #[test]
+    fn test_get_ipc_compression() {
+        assert_eq!(get_ipc_compression("zstd"), Some(CompressionType::ZSTD));
+        assert_eq!(get_ipc_compression("LZ4"), Some(CompressionType::LZ4_FRAME));
+        assert_eq!(get_ipc_compression("none"), None);
+        assert_eq!(get_ipc_compression(""), None);
+    }
+
+    #[test]
+    fn test_add_scan_stats_to_schema() {
+        let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+        let stats = ScanStats {
+            files: 3,
+            ..Default::default()
+        };
+
+        let full = add_scan_stats_to_schema(schema.clone(), stats, "");
+        assert!(full.metadata().contains_key("scan_stats"));
+        assert!(!full.metadata().contains_key("partial_err"));
+
+        let partial = add_scan_stats_to_schema(schema, stats, "skipped files");
+        assert_eq!(partial.metadata()["partial_err"], "skipped files");
+    }
+
+    #[test]
+    fn test_ipc_compression_roundtrip() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("log", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from_iter_values(0..1000)),
+                Arc::new(StringArray::from_iter_values(
+                    (0..1000).map(|i| format!("log line {}", i % 10)),
+                )),
+            ],
+        )
+        .unwrap();
+
+        let mut sizes = HashMap::new();
+        for name in ["zstd", "lz4", "none"] {
+            let compression = get_ipc_compression(name);
+            let options = IpcWriteOptions::default()
+                .try_with_compression(compression)
+                .unwrap();
+            let mut buf = Vec::new();
+            let mut writer =
+                StreamWriter::try_new_with_options(&mut buf, &schema, options).unwrap();
+            writer.write(&batch).unwrap();
+            writer.finish().unwrap();
+            drop(writer);
+            sizes.insert(name, buf.len());
+
+            let reader = StreamReader::try_new(Cursor::new(buf), None).unwrap();
+            let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(batches, vec![batch.clone()], "compression: {name}");
+        }
+        assert!(sizes["zstd"] < sizes["none"]);
+        assert!(sizes["lz4"] < sizes["none"]);
+    }
+}
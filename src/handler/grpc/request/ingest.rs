@@ -21,10 +21,11 @@ use config::{
 };
 use proto::cluster_rpc::{
     ingest_server::Ingest, IngestionRequest, IngestionResponse, IngestionType,
+    PipelineStatusRequest, PipelineStatusResponse,
 };
 use tonic::{Request, Response, Status};
 
-use crate::service::ingestion::create_log_ingestion_req;
+use crate::service::{ingestion::create_log_ingestion_req, pipeline};
 
 #[derive(Default)]
 pub struct Ingester;
@@ -175,4 +176,14 @@ impl Ingest for Ingester {
 
         Ok(Response::new(reply))
     }
+
+    async fn pipeline_status(
+        &self,
+        request: Request<PipelineStatusRequest>,
+    ) -> Result<Response<PipelineStatusResponse>, Status> {
+        let req = request.into_inner();
+        Ok(Response::new(pipeline::get_local_pipeline_status(
+            &req.pipeline_id,
+        )))
+    }
 }
@@ -41,25 +41,29 @@ impl LogsService for LogsServer {
         }
 
         let in_req = request.into_inner();
-        let org_id = metadata.get(&cfg.grpc.org_header_key);
-        if org_id.is_none() {
-            return Err(Status::invalid_argument(msg));
-        }
-        let stream_name = metadata.get(&cfg.grpc.stream_header_key);
-        let mut in_stream_name: Option<&str> = None;
-        if let Some(stream_name) = stream_name {
-            in_stream_name = Some(stream_name.to_str().unwrap());
+        let org_id = match metadata.get(&cfg.grpc.org_header_key) {
+            Some(v) => v
+                .to_str()
+                .map_err(|_| Status::invalid_argument("Invalid organization id"))?,
+            None => return Err(Status::invalid_argument(msg)),
         };
-
-        let user_id = metadata.get("user_id");
-        let mut user_email: &str = "";
-        if let Some(user_id) = user_id {
-            user_email = user_id.to_str().unwrap();
+        let in_stream_name = match metadata.get(&cfg.grpc.stream_header_key) {
+            Some(v) => Some(
+                v.to_str()
+                    .map_err(|_| Status::invalid_argument("Invalid stream name"))?,
+            ),
+            None => None,
+        };
+        let user_email = match metadata.get("user_id") {
+            Some(v) => v
+                .to_str()
+                .map_err(|_| Status::invalid_argument("Invalid user id"))?,
+            None => "",
         };
 
         match crate::service::logs::otlp_grpc::handle_grpc_request(
             0,
-            org_id.unwrap().to_str().unwrap(),
+            org_id,
             in_req,
             true,
             in_stream_name,
@@ -85,3 +89,76 @@ impl LogsService for LogsServer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry_proto::tonic::{
+        common::v1::{any_value::Value, AnyValue},
+        logs::v1::{LogRecord, ResourceLogs, ScopeLogs},
+    };
+    use tonic::{
+        metadata::{MetadataKey, MetadataValue},
+        Code,
+    };
+
+    use super::*;
+
+    fn export_request() -> ExportLogsServiceRequest {
+        ExportLogsServiceRequest {
+            resource_logs: vec![ResourceLogs {
+                scope_logs: vec![ScopeLogs {
+                    log_records: vec![LogRecord {
+                        body: Some(AnyValue {
+                            value: Some(Value::StringValue("hello".to_string())),
+                        }),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_requires_org_id() {
+        let resp = LogsServer
+            .export(tonic::Request::new(export_request()))
+            .await;
+        assert_eq!(resp.unwrap_err().code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_export() {
+        let cfg = config::get_config();
+        let mut req = tonic::Request::new(export_request());
+        req.metadata_mut().insert(
+            MetadataKey::from_bytes(cfg.grpc.org_header_key.as_bytes()).unwrap(),
+            MetadataValue::from_static("test_grpc_logs"),
+        );
+        req.metadata_mut().insert(
+            MetadataKey::from_bytes(cfg.grpc.stream_header_key.as_bytes()).unwrap(),
+            MetadataValue::from_static("grpc_logs"),
+        );
+        req.metadata_mut()
+            .insert("user_id", MetadataValue::from_static("a@a.com"));
+        let resp = LogsServer.export(req).await.unwrap();
+        assert!(resp.into_inner().partial_success.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_export_rejects_invalid_metadata() {
+        let cfg = config::get_config();
+        let mut req = tonic::Request::new(export_request());
+        req.metadata_mut().insert(
+            MetadataKey::from_bytes(cfg.grpc.org_header_key.as_bytes()).unwrap(),
+            MetadataValue::from_static("default"),
+        );
+        req.metadata_mut().insert(
+            MetadataKey::from_bytes(cfg.grpc.stream_header_key.as_bytes()).unwrap(),
+            MetadataValue::try_from(&b"logs\xff"[..]).unwrap(),
+        );
+        let resp = LogsServer.export(req).await;
+        assert_eq!(resp.unwrap_err().code(), Code::InvalidArgument);
+    }
+}
@@ -44,6 +44,7 @@ impl Metrics for MetricsQuerier {
         let req: &MetricsQueryRequest = req.get_ref();
         let org_id = &req.org_id;
         let stream_type = StreamType::Metrics.as_str();
+        let _gate = crate::service::search::SEARCH_GATE.track();
         let result = SearchService::grpc::search(req).await.map_err(|err| {
             let time = start.elapsed().as_secs_f64();
             metrics::GRPC_RESPONSE_TIME
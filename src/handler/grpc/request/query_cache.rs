@@ -73,7 +73,8 @@ impl QueryCache for QueryCacheServerImpl {
         request: Request<DeleteResultCacheRequest>,
     ) -> Result<Response<DeleteResultCacheResponse>, Status> {
         let req: DeleteResultCacheRequest = request.into_inner();
-        let deleted = cacher::delete_cache(&req.path).await.is_ok();
+        let time_range = (req.end_time > 0).then_some((req.start_time, req.end_time));
+        let deleted = cacher::delete_cache(&req.path, time_range).await.is_ok();
 
         Ok(Response::new(DeleteResultCacheResponse { deleted }))
     }
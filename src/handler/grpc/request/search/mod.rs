@@ -25,7 +25,7 @@ use proto::cluster_rpc::{
     search_server::Search, CancelQueryRequest, CancelQueryResponse, DeleteResultRequest,
     DeleteResultResponse, GetResultRequest, GetResultResponse, QueryStatusRequest,
     QueryStatusResponse, SearchPartitionRequest, SearchPartitionResponse, SearchRequest,
-    SearchResponse,
+    SearchResponse, TailRequest, TailResponse,
 };
 use tonic::{Request, Response, Status};
 
@@ -195,6 +195,24 @@ impl Search for Searcher {
         }
     }
 
+    async fn tail(&self, req: Request<TailRequest>) -> Result<Response<TailResponse>, Status> {
+        let req = req.into_inner();
+        let _gate = SearchService::SEARCH_GATE.track();
+        let stream_type = StreamType::from(req.stream_type.as_str());
+        let (hits, seq) = SearchService::tail::search_memtable_since(
+            &req.org_id,
+            stream_type,
+            &req.stream_name,
+            &req.sql,
+            req.since,
+        )
+        .await
+        .map_err(|e| Status::internal(format!("tail failed: {e}")))?;
+        let hits = json::to_vec(&hits)
+            .map_err(|e| Status::internal(format!("failed to serialize tail hits: {e}")))?;
+        Ok(Response::new(TailResponse { hits, seq }))
+    }
+
     #[cfg(feature = "enterprise")]
     async fn get_result(
         &self,
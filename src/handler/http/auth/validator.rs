@@ -28,6 +28,7 @@ use o2_openfga::config::get_config as get_openfga_config;
 use crate::{
     common::{
         meta::{
+            authz::PermissionTuple,
             ingestion::INGESTION_EP,
             user::{
                 AuthTokensExt, DBUser, TokenValidationResponse, TokenValidationResponseBuilder,
@@ -119,6 +120,25 @@ pub async fn validate_token(token: &str, org_id: &str) -> Result<(), Error> {
     }
 }
 
+/// `validate_service_account_token` resolves a bearer token on an ingestion
+/// endpoint to the service account owning it, for clients which can't send
+/// basic auth.
+///
+/// ### Args:
+/// - token: The bearer token presented by the client
+/// - path: The request path relative to the api prefix, starting with the org
+///
+/// Returns the service account email, which together with the token validates
+/// through `validate_credentials` like basic auth would.
+pub fn validate_service_account_token(token: &str, path: &str) -> Option<String> {
+    let path_columns = path.split('/').collect::<Vec<&str>>();
+    if !INGESTION_EP.iter().any(|s| path_columns.contains(s)) {
+        return None;
+    }
+    let org_id = path_columns.first()?;
+    users::get_service_account_by_token(org_id, token).map(|user| user.email)
+}
+
 pub async fn validate_credentials(
     user_id: &str,
     user_password: &str,
@@ -602,7 +622,15 @@ async fn oo_validator_internal(
         };
         validator(req, &username, &password, auth_info, path_prefix).await
     } else if auth_info.auth.starts_with("Bearer") {
-        super::token::token_validator(req, auth_info).await
+        let token = auth_info.auth.strip_prefix("Bearer").unwrap().trim();
+        let path = extract_relative_path(req.request().path(), path_prefix);
+        match validate_service_account_token(token, &path) {
+            Some(user_id) => {
+                let token = token.to_string();
+                validator(req, &user_id, &token, auth_info, path_prefix).await
+            }
+            None => super::token::token_validator(req, auth_info).await,
+        }
     } else if auth_info.auth.starts_with("{\"auth_ext\":") {
         let auth_tokens: AuthTokensExt =
             config::utils::json::from_str(&auth_info.auth).unwrap_or_default();
@@ -762,27 +790,44 @@ pub(crate) async fn check_permissions(
         return true;
     }
 
-    let object_str = auth_info.o2_type;
-    let obj_str = if object_str.contains("##user_id##") {
-        object_str.replace("##user_id##", user_id)
+    let tuple = permission_tuple(user_id, &auth_info, &role);
+    o2_openfga::authorizer::authz::is_allowed(
+        &tuple.org_id,
+        &tuple.user_id,
+        &tuple.method,
+        &tuple.object,
+        &tuple.parent_id,
+        &tuple.role,
+    )
+    .await
+}
+
+/// Builds the values that are evaluated by OpenFGA to check the permissions
+/// of the user.
+#[cfg_attr(not(feature = "enterprise"), allow(dead_code))]
+pub(crate) fn permission_tuple(
+    user_id: &str,
+    auth_info: &AuthExtractor,
+    role: &UserRole,
+) -> PermissionTuple {
+    let object = if auth_info.o2_type.contains("##user_id##") {
+        auth_info.o2_type.replace("##user_id##", user_id)
     } else {
-        object_str
+        auth_info.o2_type.clone()
     };
     let org_id = if auth_info.org_id.eq("organizations") {
         user_id
     } else {
         &auth_info.org_id
     };
-
-    o2_openfga::authorizer::authz::is_allowed(
-        org_id,
-        user_id,
-        &auth_info.method,
-        &obj_str,
-        &auth_info.parent_id,
-        &role.to_string(),
-    )
-    .await
+    PermissionTuple {
+        org_id: org_id.to_string(),
+        user_id: user_id.to_string(),
+        method: auth_info.method.clone(),
+        object,
+        parent_id: auth_info.parent_id.clone(),
+        role: role.to_string(),
+    }
 }
 
 #[cfg(not(feature = "enterprise"))]
@@ -894,6 +939,40 @@ mod tests {
     use super::*;
     use crate::common::meta::user::UserRequest;
 
+    #[test]
+    fn test_permission_tuple() {
+        let auth_info = AuthExtractor {
+            auth: "".to_string(),
+            method: "GET".to_string(),
+            o2_type: "logs:default".to_string(),
+            org_id: "org1".to_string(),
+            bypass_check: false,
+            parent_id: "".to_string(),
+        };
+        let tuple = permission_tuple("user@example.com", &auth_info, &UserRole::Admin);
+        assert_eq!(
+            tuple,
+            PermissionTuple {
+                org_id: "org1".to_string(),
+                user_id: "user@example.com".to_string(),
+                method: "GET".to_string(),
+                object: "logs:default".to_string(),
+                parent_id: "".to_string(),
+                role: UserRole::Admin.to_string(),
+            }
+        );
+
+        // user specific objects and the organizations listing are scoped to the user
+        let auth_info = AuthExtractor {
+            o2_type: "user:##user_id##".to_string(),
+            org_id: "organizations".to_string(),
+            ..auth_info
+        };
+        let tuple = permission_tuple("user@example.com", &auth_info, &UserRole::Admin);
+        assert_eq!(tuple.org_id, "user@example.com");
+        assert_eq!(tuple.object, "user:user@example.com");
+    }
+
     #[tokio::test]
     async fn test_validation_response_builder_from_db_user() {
         let user = DBUser {
@@ -1016,4 +1095,67 @@ mod tests {
         );
         assert!(validate_user(init_user, pwd).await.unwrap().is_valid);
     }
+
+    #[tokio::test]
+    async fn test_bearer_service_account_ingestion() {
+        let org_id = "default";
+        let sa_id = "ingester@example.com";
+        let init_user = "root@example.com";
+        let pwd = "Complexpass#123";
+
+        infra_db::create_table().await.unwrap();
+        users::create_root_user(
+            org_id,
+            UserRequest {
+                email: init_user.to_string(),
+                password: pwd.to_string(),
+                role: UserRole::Root,
+                first_name: "root".to_owned(),
+                last_name: "".to_owned(),
+                is_external: false,
+            },
+        )
+        .await
+        .unwrap();
+        users::post_user(
+            org_id,
+            UserRequest {
+                email: sa_id.to_string(),
+                password: pwd.to_string(),
+                role: UserRole::ServiceAccount,
+                first_name: "ingester".to_owned(),
+                last_name: "".to_owned(),
+                is_external: false,
+            },
+            init_user,
+        )
+        .await
+        .unwrap();
+        let token = users::get_user(Some(org_id), sa_id).await.unwrap().token;
+
+        assert_eq!(
+            validate_service_account_token(&token, "default/logs1/_json"),
+            Some(sa_id.to_string())
+        );
+        assert!(validate_service_account_token(&token, "default/streams").is_none());
+        assert!(validate_service_account_token("invalid", "default/logs1/_json").is_none());
+        assert!(validate_service_account_token(&token, "other/logs1/_json").is_none());
+
+        let req = actix_web::test::TestRequest::post()
+            .uri("/api/default/logs1/_json")
+            .to_srv_request();
+        let auth_info = AuthExtractor {
+            auth: format!("Bearer {token}"),
+            method: "POST".to_string(),
+            o2_type: "stream:logs1".to_string(),
+            org_id: org_id.to_string(),
+            bypass_check: true,
+            parent_id: "".to_string(),
+        };
+        let req = oo_validator_internal(req, auth_info, "/api/")
+            .await
+            .map_err(|(e, _)| e)
+            .unwrap();
+        assert_eq!(req.headers().get("user_id").unwrap(), sa_id);
+    }
 }
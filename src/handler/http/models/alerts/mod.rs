@@ -54,6 +54,19 @@ pub struct Alert {
 
     pub destinations: Vec<String>,
 
+    /// Optional template to use for a destination instead of the
+    /// destination's own template, keyed by destination name.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination_templates: Option<HashMap<String, String>>,
+
+    /// Locale of the template variants used to render the notifications,
+    /// e.g. `fr` or `pt-BR`. Templates without the locale use their default
+    /// body.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_locale: Option<String>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub context_attributes: Option<HashMap<String, String>>,
 
@@ -289,6 +302,8 @@ impl From<(meta_alerts::alert::Alert, Option<Trigger>)> for Alert {
             query_condition: alert.query_condition.into(),
             trigger_condition: alert.trigger_condition.into(),
             destinations: alert.destinations,
+            destination_templates: alert.destination_templates,
+            template_locale: alert.template_locale,
             context_attributes: alert.context_attributes,
             row_template: alert.row_template,
             description: alert.description,
@@ -465,6 +480,8 @@ impl From<Alert> for meta_alerts::alert::Alert {
         alert.query_condition = value.query_condition.into();
         alert.trigger_condition = value.trigger_condition.into();
         alert.destinations = value.destinations;
+        alert.destination_templates = value.destination_templates;
+        alert.template_locale = value.template_locale;
         alert.context_attributes = value.context_attributes;
         alert.row_template = value.row_template;
         alert.description = value.description;
@@ -35,6 +35,21 @@ pub struct CreateAlertRequestBody {
 #[derive(Clone, Debug, Deserialize, ToSchema)]
 pub struct UpdateAlertRequestBody(pub Alert);
 
+/// HTTP request body for `BacktestAlert` endpoint.
+#[derive(Clone, Debug, Deserialize, ToSchema)]
+pub struct BacktestAlertRequestBody {
+    /// Start of the time range to replay the alert over, in microseconds.
+    /// Defaults to one day before `end_time`.
+    pub start_time: Option<i64>,
+
+    /// End of the time range to replay the alert over, in microseconds.
+    /// Defaults to now.
+    pub end_time: Option<i64>,
+
+    #[serde(flatten)]
+    pub alert: Alert,
+}
+
 /// HTTP request body for `MoveAlerts` endpoint.
 #[derive(Clone, Debug, Deserialize, ToSchema)]
 pub struct MoveAlertsRequestBody {
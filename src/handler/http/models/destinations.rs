@@ -151,6 +151,7 @@ impl From<meta_dest::Template> for Template {
             is_default: value.is_default.then_some(true),
             template_type,
             title,
+            locales: value.locales,
         }
     }
 }
@@ -171,6 +172,7 @@ impl Template {
             is_default: self.is_default.unwrap_or_default(),
             template_type,
             body: self.body,
+            locales: self.locales,
         }
     }
 }
@@ -260,4 +262,9 @@ pub struct Template {
     pub template_type: DestinationType,
     #[serde(default)]
     pub title: String,
+    /// Localized variants of the body, and of the title for email templates,
+    /// keyed by locale.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub locales: HashMap<String, meta_dest::TemplateLocale>,
 }
@@ -129,6 +129,32 @@ async fn get_destination(path: web::Path<(String, String)>) -> Result<HttpRespon
     }
 }
 
+/// TestDestination
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Alerts",
+    operation_id = "TestDestination",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("destination_name" = String, Path, description = "Destination name"),
+      ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = DestinationTestResponse),
+        (status = 404, description = "NotFound", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[post("/{org_id}/alerts/destinations/{destination_name}/test")]
+async fn test_destination(path: web::Path<(String, String)>) -> Result<HttpResponse, Error> {
+    let (org_id, name) = path.into_inner();
+    match destinations::test_destination(&org_id, &name).await {
+        Ok(resp) => Ok(MetaHttpResponse::json(resp)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
 /// ListDestinations
 #[utoipa::path(
     context_path = "/api",
@@ -14,8 +14,12 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse};
+use chrono::{Duration, Utc};
 use config::meta::{
-    alerts::alert::Alert as MetaAlert,
+    alerts::{
+        alert::{Alert as MetaAlert, AlertBacktestResponse},
+        bundle::AlertConfigBundle,
+    },
     folder::DEFAULT_FOLDER,
     triggers::{Trigger, TriggerModule},
 };
@@ -27,14 +31,21 @@ use crate::{
     common::{meta::http::HttpResponse as MetaHttpResponse, utils::auth::UserEmail},
     handler::http::models::alerts::{
         requests::{
-            CreateAlertRequestBody, EnableAlertQuery, ListAlertsQuery, MoveAlertsRequestBody,
-            UpdateAlertRequestBody,
+            BacktestAlertRequestBody, CreateAlertRequestBody, EnableAlertQuery, ListAlertsQuery,
+            MoveAlertsRequestBody, UpdateAlertRequestBody,
         },
         responses::{EnableAlertResponseBody, GetAlertResponseBody, ListAlertsResponseBody},
     },
     service::{
-        alerts::alert::{self, AlertError},
-        db::scheduler,
+        alerts::{
+            alert::{self, AlertError},
+            bundle::{self, AlertConfigError},
+        },
+        db::{
+            alerts::{destinations::DestinationError, templates::TemplateError},
+            scheduler,
+        },
+        folders::FolderError,
     },
 };
 
@@ -55,8 +66,15 @@ impl From<AlertError> for HttpResponse {
             AlertError::CreateAlreadyExists => MetaHttpResponse::conflict(value),
             AlertError::CreateFolderNotFound => MetaHttpResponse::not_found(value),
             AlertError::MoveDestinationFolderNotFound => MetaHttpResponse::not_found(value),
+            AlertError::MoveDestinationFolder(_) => MetaHttpResponse::internal_error(value),
             AlertError::AlertNotFound => MetaHttpResponse::not_found(value),
             AlertError::AlertDestinationNotFound { .. } => MetaHttpResponse::not_found(value),
+            AlertError::AlertDestinationTemplateNotFound { .. } => {
+                MetaHttpResponse::not_found(value)
+            }
+            AlertError::AlertDestinationTemplateUnknownDestination { .. } => {
+                MetaHttpResponse::bad_request(value)
+            }
             AlertError::StreamNotFound { .. } => MetaHttpResponse::not_found(value),
             AlertError::DecodeVrl(err) => MetaHttpResponse::bad_request(err),
             AlertError::ParseCron(err) => MetaHttpResponse::bad_request(err),
@@ -73,6 +91,25 @@ impl From<AlertError> for HttpResponse {
             AlertError::PermittedAlertsMissingUser => MetaHttpResponse::forbidden(""),
             AlertError::PermittedAlertsValidator(err) => MetaHttpResponse::forbidden(err),
             AlertError::NotSupportedAlertDestinationType(err) => MetaHttpResponse::forbidden(err),
+            AlertError::BacktestInvalidTimeRange => MetaHttpResponse::bad_request(value),
+            AlertError::BacktestInvalidTriggerCondition => MetaHttpResponse::bad_request(value),
+            AlertError::BacktestTooManyIntervals { .. } => MetaHttpResponse::bad_request(value),
+            AlertError::TemplateLocaleTooLong { .. } => MetaHttpResponse::bad_request(value),
+        }
+    }
+}
+
+impl From<AlertConfigError> for HttpResponse {
+    fn from(value: AlertConfigError) -> Self {
+        match &value {
+            AlertConfigError::Export(_)
+            | AlertConfigError::Template(_, TemplateError::InfraError(_))
+            | AlertConfigError::Destination(_, DestinationError::InfraError(_))
+            | AlertConfigError::Folder(_, FolderError::InfraError(_))
+            | AlertConfigError::Alert(_, AlertError::InfraError(_)) => {
+                MetaHttpResponse::internal_error(value)
+            }
+            _ => MetaHttpResponse::bad_request(value),
         }
     }
 }
@@ -347,6 +384,45 @@ async fn trigger_alert(path: web::Path<(String, Ksuid)>) -> HttpResponse {
     }
 }
 
+/// BacktestAlert
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Alerts",
+    operation_id = "BacktestAlert",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+      ),
+    request_body(content = BacktestAlertRequestBody, description = "Alert data and the time range to replay it over", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = AlertBacktestResponse),
+        (status = 400, description = "Error",   content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[post("/v2/{org_id}/alerts/backtest")]
+pub async fn backtest_alert(
+    path: web::Path<String>,
+    req_body: web::Json<BacktestAlertRequestBody>,
+) -> HttpResponse {
+    let org_id = path.into_inner();
+    let req_body = req_body.into_inner();
+
+    let end_time = req_body
+        .end_time
+        .unwrap_or_else(|| Utc::now().timestamp_micros());
+    let start_time = req_body.start_time.unwrap_or_else(|| {
+        end_time.saturating_sub(Duration::try_days(1).unwrap().num_microseconds().unwrap())
+    });
+    let alert: MetaAlert = req_body.alert.into();
+
+    match alert::backtest(&org_id, alert, start_time, end_time).await {
+        Ok(resp_body) => MetaHttpResponse::json(resp_body),
+        Err(e) => e.into(),
+    }
+}
+
 /// MoveAlerts
 #[utoipa::path(
     context_path = "/api",
@@ -391,3 +467,57 @@ async fn move_alerts(
         Err(e) => e.into(),
     }
 }
+
+/// ExportAlertConfig
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Alerts",
+    operation_id = "ExportAlertConfig",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    responses(
+        (status = 200, description = "Alerts, templates and destinations of the organization", content_type = "application/json", body = Object),
+        (status = 500, description = "Failure",  content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[get("/v2/{org_id}/alerts/export")]
+async fn export_config(path: web::Path<String>) -> HttpResponse {
+    let org_id = path.into_inner();
+    match bundle::export_config(&org_id).await {
+        Ok(bundle) => MetaHttpResponse::json(bundle),
+        Err(e) => e.into(),
+    }
+}
+
+/// ImportAlertConfig
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Alerts",
+    operation_id = "ImportAlertConfig",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    request_body(content = Object, description = "Alert config exported from an organization", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = HttpResponse),
+        (status = 400, description = "Error",   content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[post("/v2/{org_id}/alerts/import")]
+async fn import_config(
+    path: web::Path<String>,
+    req_body: web::Json<AlertConfigBundle>,
+) -> HttpResponse {
+    let org_id = path.into_inner();
+    match bundle::import_config(&org_id, req_body.into_inner()).await {
+        Ok(_) => MetaHttpResponse::ok("Alert config imported"),
+        Err(e) => e.into(),
+    }
+}
@@ -18,7 +18,12 @@ use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse};
 #[cfg(feature = "enterprise")]
 use o2_dex::meta::auth::RoleRequest;
 
-use crate::common::meta::user::{UserGroup, UserGroupRequest, UserRoleRequest};
+use crate::common::meta::{
+    authz::PermissionCheckRequest,
+    user::{
+        BulkRoleAssignRequest, BulkRoleAssignResult, UserGroup, UserGroupRequest, UserRoleRequest,
+    },
+};
 
 #[cfg(feature = "enterprise")]
 #[post("/{org_id}/roles")]
@@ -150,6 +155,86 @@ pub async fn update_role(
     Ok(HttpResponse::Forbidden().json("Not Supported"))
 }
 
+#[cfg(feature = "enterprise")]
+#[post("/{org_id}/roles/bulk_assign")]
+pub async fn bulk_assign_role(
+    org_id: web::Path<String>,
+    assign_req: web::Json<BulkRoleAssignRequest>,
+) -> Result<HttpResponse, Error> {
+    use crate::{common::meta::user::BulkRoleAssignResponse, service::users};
+
+    let org_id = org_id.into_inner();
+    let BulkRoleAssignRequest {
+        role,
+        users: emails,
+    } = assign_req.into_inner();
+
+    let (candidates, mut results) = validate_role_assignees(emails);
+    let mut assignees = Vec::with_capacity(candidates.len());
+    for email in candidates {
+        if users::get_user(Some(&org_id), &email).await.is_some() {
+            assignees.push(email);
+        } else {
+            results.push(BulkRoleAssignResult::error(
+                &email,
+                "User not found in organization",
+            ));
+        }
+    }
+
+    if !assignees.is_empty() {
+        // all the users are written to OpenFGA in a single batch
+        match o2_openfga::authorizer::roles::update_role(
+            &org_id,
+            &role,
+            None,
+            None,
+            Some(assignees.iter().cloned().collect()),
+            None,
+        )
+        .await
+        {
+            Ok(_) => results.extend(
+                assignees
+                    .iter()
+                    .map(|email| BulkRoleAssignResult::ok(email)),
+            ),
+            Err(err) => results.extend(
+                assignees
+                    .iter()
+                    .map(|email| BulkRoleAssignResult::error(email, &err)),
+            ),
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(BulkRoleAssignResponse { results }))
+}
+
+#[cfg(not(feature = "enterprise"))]
+#[post("/{org_id}/roles/bulk_assign")]
+pub async fn bulk_assign_role(
+    _org_id: web::Path<String>,
+    _assign_req: web::Json<BulkRoleAssignRequest>,
+) -> Result<HttpResponse, Error> {
+    Ok(HttpResponse::Forbidden().json("Not Supported"))
+}
+
+/// Normalizes the emails of a bulk role assignment, returning the unique
+/// valid emails and the failed results of the invalid ones.
+fn validate_role_assignees(emails: Vec<String>) -> (Vec<String>, Vec<BulkRoleAssignResult>) {
+    let mut valid = Vec::with_capacity(emails.len());
+    let mut invalid = Vec::new();
+    for email in emails {
+        let email = email.trim().to_lowercase();
+        if !crate::service::users::is_valid_email(&email) {
+            invalid.push(BulkRoleAssignResult::error(&email, "Invalid email"));
+        } else if !valid.contains(&email) {
+            valid.push(email);
+        }
+    }
+    (valid, invalid)
+}
+
 #[cfg(feature = "enterprise")]
 #[get("/{org_id}/roles/{role_id}/permissions/{resource}")]
 pub async fn get_role_permissions(
@@ -352,3 +437,260 @@ pub async fn delete_group(path: web::Path<(String, String)>) -> Result<HttpRespo
 pub async fn delete_group(_path: web::Path<(String, String)>) -> Result<HttpResponse, Error> {
     Ok(HttpResponse::Forbidden().json("Not Supported"))
 }
+
+#[cfg(feature = "enterprise")]
+#[post("/{org_id}/permissions/check")]
+pub async fn check_permission(
+    org_id: web::Path<String>,
+    check_req: web::Json<PermissionCheckRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    use crate::{
+        common::{
+            meta::{http::HttpResponse as MetaHttpResponse, user::UserRole},
+            utils::auth::{is_root_user, AuthExtractor},
+        },
+        handler::http::auth::validator::check_permissions,
+        service::users,
+    };
+
+    let org_id = org_id.into_inner();
+    let check_req = check_req.into_inner();
+
+    // Only admins can inspect the permissions of other users
+    let caller_id = req
+        .headers()
+        .get("user_id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if !is_root_user(caller_id) {
+        match users::get_user(Some(&org_id), caller_id).await {
+            Some(caller) if caller.role.eq(&UserRole::Admin) => {}
+            _ => return Ok(MetaHttpResponse::forbidden("Unauthorized Access")),
+        }
+    }
+
+    let Some(user) = users::get_user(Some(&org_id), &check_req.user_id).await else {
+        return Ok(MetaHttpResponse::not_found("User not found"));
+    };
+    let auth_info = AuthExtractor {
+        auth: "".to_string(),
+        method: check_req.method.to_uppercase(),
+        o2_type: check_req.object,
+        org_id,
+        bypass_check: false,
+        parent_id: check_req.parent_id,
+    };
+    let response = permission_check_response(user, auth_info, |user, auth_info| async move {
+        check_permissions(&user.email, auth_info, user.role, user.is_external).await
+    })
+    .await;
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Evaluates `auth_info` for `user` with `is_allowed`, along with the tuple
+/// it was evaluated as.
+#[cfg(feature = "enterprise")]
+async fn permission_check_response<F, Fut>(
+    user: crate::common::meta::user::User,
+    auth_info: crate::common::utils::auth::AuthExtractor,
+    is_allowed: F,
+) -> crate::common::meta::authz::PermissionCheckResponse
+where
+    F: FnOnce(crate::common::meta::user::User, crate::common::utils::auth::AuthExtractor) -> Fut,
+    Fut: std::future::Future<Output = bool>,
+{
+    let tuple = crate::handler::http::auth::validator::permission_tuple(
+        &user.email,
+        &auth_info,
+        &user.role,
+    );
+    let allowed = is_allowed(user, auth_info).await;
+    crate::common::meta::authz::PermissionCheckResponse { allowed, tuple }
+}
+
+#[cfg(not(feature = "enterprise"))]
+#[post("/{org_id}/permissions/check")]
+pub async fn check_permission(
+    _org_id: web::Path<String>,
+    _check_req: web::Json<PermissionCheckRequest>,
+) -> Result<HttpResponse, Error> {
+    Ok(HttpResponse::Forbidden().json("Not Supported"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_role_assignees() {
+        let (valid, invalid) = validate_role_assignees(vec![
+            "alice@example.com".to_string(),
+            " Bob@Example.com ".to_string(),
+            "not-an-email".to_string(),
+        ]);
+        assert_eq!(valid, vec!["alice@example.com", "bob@example.com"]);
+        assert_eq!(
+            invalid,
+            vec![BulkRoleAssignResult::error("not-an-email", "Invalid email")]
+        );
+
+        let (valid, invalid) = validate_role_assignees(vec![
+            "alice@example.com".to_string(),
+            "ALICE@example.com".to_string(),
+        ]);
+        assert_eq!(valid, vec!["alice@example.com"]);
+        assert!(invalid.is_empty());
+    }
+
+    #[cfg(feature = "enterprise")]
+    #[tokio::test]
+    async fn test_bulk_assign_role() {
+        use actix_web::{http::StatusCode, test, App};
+
+        use crate::common::meta::user::BulkRoleAssignResponse;
+
+        infra::table::migrate().await.unwrap();
+        let app = test::init_service(App::new().service(bulk_assign_role)).await;
+        let req = test::TestRequest::post()
+            .uri("/test_bulk_assign_role/roles/bulk_assign")
+            .set_json(BulkRoleAssignRequest {
+                role: "editor".to_string(),
+                users: vec![
+                    "not-an-email".to_string(),
+                    " Nobody@Example.com ".to_string(),
+                ],
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        // every user gets a result, the failed ones without touching the role
+        let body: BulkRoleAssignResponse = test::read_body_json(resp).await;
+        assert_eq!(
+            body.results,
+            vec![
+                BulkRoleAssignResult::error("not-an-email", "Invalid email"),
+                BulkRoleAssignResult::error("nobody@example.com", "User not found in organization"),
+            ]
+        );
+    }
+
+    #[cfg(not(feature = "enterprise"))]
+    #[tokio::test]
+    async fn test_bulk_assign_role() {
+        use actix_web::{http::StatusCode, test, App};
+
+        let app = test::init_service(App::new().service(bulk_assign_role)).await;
+        let req = test::TestRequest::post()
+            .uri("/default/roles/bulk_assign")
+            .set_json(BulkRoleAssignRequest::default())
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[cfg(feature = "enterprise")]
+    #[tokio::test]
+    async fn test_check_permission() {
+        use actix_web::{http::StatusCode, test, App};
+
+        use crate::common::{
+            infra::config::USERS,
+            meta::{
+                authz::PermissionCheckResponse,
+                user::{User, UserRole},
+            },
+        };
+
+        let org_id = "test_check_permission";
+        for (email, role) in [
+            ("admin@example.com", UserRole::Admin),
+            ("viewer@example.com", UserRole::Viewer),
+        ] {
+            USERS.insert(
+                format!("{org_id}/{email}"),
+                User {
+                    email: email.to_string(),
+                    password: "pass#123".to_string(),
+                    role,
+                    salt: String::new(),
+                    token: "token".to_string(),
+                    rum_token: None,
+                    first_name: "".to_string(),
+                    last_name: "".to_string(),
+                    org: org_id.to_string(),
+                    is_external: false,
+                    password_ext: None,
+                },
+            );
+        }
+        let app = test::init_service(App::new().service(check_permission)).await;
+        let check = |caller: &str, user_id: &str| {
+            test::TestRequest::post()
+                .uri(&format!("/{org_id}/permissions/check"))
+                .insert_header(("user_id", caller))
+                .set_json(PermissionCheckRequest {
+                    user_id: user_id.to_string(),
+                    method: "get".to_string(),
+                    object: "logs:default".to_string(),
+                    parent_id: "".to_string(),
+                })
+                .to_request()
+        };
+
+        // only admins can check the permissions of other users
+        let resp = test::call_service(&app, check("viewer@example.com", "admin@example.com")).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        let resp = test::call_service(&app, check("admin@example.com", "nobody@example.com")).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let resp = test::call_service(&app, check("admin@example.com", "viewer@example.com")).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: PermissionCheckResponse = test::read_body_json(resp).await;
+        assert_eq!(body.tuple.org_id, org_id);
+        assert_eq!(body.tuple.user_id, "viewer@example.com");
+        assert_eq!(body.tuple.method, "GET");
+        assert_eq!(body.tuple.object, "logs:default");
+        assert_eq!(body.tuple.role, UserRole::Viewer.to_string());
+        // without OpenFGA every permission is granted
+        assert!(body.allowed);
+
+        // the evaluated permission is returned as is, denied included
+        let viewer = USERS
+            .get(&format!("{org_id}/viewer@example.com"))
+            .unwrap()
+            .clone();
+        let auth_info = |method: &str| crate::common::utils::auth::AuthExtractor {
+            auth: "".to_string(),
+            method: method.to_string(),
+            o2_type: "logs:default".to_string(),
+            org_id: org_id.to_string(),
+            bypass_check: false,
+            parent_id: "".to_string(),
+        };
+        let read_only = |_: User, auth_info: crate::common::utils::auth::AuthExtractor| async move {
+            auth_info.method == "GET"
+        };
+        let resp = permission_check_response(viewer.clone(), auth_info("GET"), read_only).await;
+        assert!(resp.allowed);
+        let resp = permission_check_response(viewer, auth_info("DELETE"), read_only).await;
+        assert!(!resp.allowed);
+        assert_eq!(resp.tuple.method, "DELETE");
+        assert_eq!(resp.tuple.user_id, "viewer@example.com");
+    }
+
+    #[cfg(not(feature = "enterprise"))]
+    #[tokio::test]
+    async fn test_check_permission() {
+        use actix_web::{http::StatusCode, test, App};
+
+        let app = test::init_service(App::new().service(check_permission)).await;
+        let req = test::TestRequest::post()
+            .uri("/default/permissions/check")
+            .set_json(PermissionCheckRequest::default())
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -16,6 +16,7 @@
 use std::collections::HashMap;
 
 use actix_web::{delete, get, http, post, put, web, HttpRequest, HttpResponse, Responder};
+use config::meta::dashboards::bundle::DashboardConfigBundle;
 
 use crate::{
     common::meta::http::HttpResponse as MetaHttpResponse,
@@ -24,7 +25,14 @@ use crate::{
         ListDashboardsQuery, ListDashboardsResponseBody, MoveDashboardRequestBody,
         UpdateDashboardRequestBody, UpdateDashboardResponseBody,
     },
-    service::dashboards::{self, DashboardError},
+    service::{
+        dashboards::{
+            self,
+            bundle::{self, DashboardConfigError},
+            DashboardError,
+        },
+        folders::FolderError,
+    },
 };
 
 pub mod reports;
@@ -45,6 +53,31 @@ impl From<DashboardError> for HttpResponse {
             DashboardError::DistinctValueError => MetaHttpResponse::internal_error("Error in updating distinct values"),
             DashboardError::MoveDashboardDeleteOld(dashb_id, folder_id, e) => MetaHttpResponse::internal_error(format!("error deleting the dashboard {dashb_id} from old folder {folder_id} : {e}")),
             DashboardError::ListPermittedDashboardsError(err) => MetaHttpResponse::forbidden(err),
+            DashboardError::InvalidDashboard(errors) => HttpResponse::BadRequest().json(config::utils::json::json!({
+                "code": http::StatusCode::BAD_REQUEST.as_u16(),
+                "message": "Invalid dashboard",
+                "errors": errors,
+            })),
+        }
+    }
+}
+
+impl From<DashboardConfigError> for HttpResponse {
+    fn from(value: DashboardConfigError) -> Self {
+        match value {
+            DashboardConfigError::Export(_)
+            | DashboardConfigError::Folder(_, FolderError::InfraError(_)) => {
+                MetaHttpResponse::internal_error(value)
+            }
+            DashboardConfigError::Dashboard(_, err @ DashboardError::InvalidDashboard(_)) => {
+                err.into()
+            }
+            DashboardConfigError::Dashboard(_, DashboardError::InfraError(_))
+            | DashboardConfigError::Dashboard(_, DashboardError::DistinctValueError)
+            | DashboardConfigError::Dashboard(_, DashboardError::CreateDefaultFolder) => {
+                MetaHttpResponse::internal_error(value)
+            }
+            _ => MetaHttpResponse::bad_request(value),
         }
     }
 }
@@ -271,6 +304,66 @@ async fn move_dashboard(
     }
 }
 
+/// ExportDashboards
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Dashboards",
+    operation_id = "ExportDashboards",
+    security(
+        ("Authorization" = [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Dashboards and folders of the organization", body = Object),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal Server Error", body = HttpResponse),
+    ),
+)]
+#[get("/{org_id}/config/export")]
+async fn export_all(path: web::Path<String>) -> impl Responder {
+    let org_id = path.into_inner();
+    match bundle::export_all(&org_id).await {
+        Ok(bundle) => HttpResponse::Ok().json(bundle),
+        Err(err) => err.into(),
+    }
+}
+
+/// ImportDashboards
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Dashboards",
+    operation_id = "ImportDashboards",
+    security(
+        ("Authorization" = [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    request_body(
+        content = Object,
+        description = "Dashboards and folders exported from an organization",
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Dashboards imported", body = HttpResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Invalid dashboards", body = HttpResponse),
+    ),
+)]
+#[post("/{org_id}/config/import")]
+async fn import_all(
+    path: web::Path<String>,
+    req_body: web::Json<DashboardConfigBundle>,
+) -> impl Responder {
+    let org_id = path.into_inner();
+    match bundle::import_all(&org_id, req_body.into_inner()).await {
+        Ok(()) => HttpResponse::Ok().json(MetaHttpResponse::message(
+            http::StatusCode::OK.into(),
+            "Dashboards imported".to_string(),
+        )),
+        Err(err) => err.into(),
+    }
+}
+
 fn get_folder(req: HttpRequest) -> String {
     let query = web::Query::<HashMap<String, String>>::from_query(req.query_string()).unwrap();
     crate::common::utils::http::get_folder(&query)
@@ -16,13 +16,16 @@
 use std::io::Error;
 
 use actix_multipart::Multipart;
-use actix_web::{post, web, HttpRequest, HttpResponse};
+use actix_web::{get, post, web, HttpRequest, HttpResponse};
 use config::SIZE_IN_MB;
 use hashbrown::HashMap;
 
 use crate::{
     common::meta::http::HttpResponse as MetaHttpResponse,
-    service::enrichment_table::{extract_multipart, save_enrichment_data},
+    service::enrichment_table::{
+        extract_multipart, get_enrichment_table_preview, get_enrichment_table_rows, lookup_rows,
+        save_enrichment_data,
+    },
 };
 
 /// CreateEnrichmentTable
@@ -94,3 +97,77 @@ pub async fn save_enrichment_table(
         )),
     }
 }
+
+/// LookupEnrichmentTable
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Functions",
+    operation_id = "LookupEnrichmentTable",
+    security(
+        ("Authorization" = [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("table_name" = String, Path, description = "Table name"),
+        ("key" = String, Query, description = "Column to match"),
+        ("value" = String, Query, description = "Value the column must equal"),
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Matching rows", body = Vec<Object>),
+        (status = StatusCode::BAD_REQUEST, description = "Bad Request", body = HttpResponse),
+        (status = StatusCode::NOT_FOUND, description = "Table not found", body = HttpResponse),
+    ),
+)]
+#[get("/{org_id}/enrichment_tables/{table_name}/lookup")]
+pub async fn lookup(
+    path: web::Path<(String, String)>,
+    query: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse, Error> {
+    let (org_id, table_name) = path.into_inner();
+    let Some(key) = query.get("key").filter(|v| !v.is_empty()) else {
+        return Ok(MetaHttpResponse::bad_request("key is required"));
+    };
+    let Some(value) = query.get("value") else {
+        return Ok(MetaHttpResponse::bad_request("value is required"));
+    };
+    match get_enrichment_table_rows(&org_id, &table_name).await {
+        Ok(Some(rows)) => Ok(MetaHttpResponse::json(lookup_rows(rows, key, value))),
+        Ok(None) => Ok(MetaHttpResponse::not_found("enrichment table not found")),
+        Err(e) => Ok(MetaHttpResponse::internal_error(e)),
+    }
+}
+
+/// PreviewEnrichmentTable
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Functions",
+    operation_id = "PreviewEnrichmentTable",
+    security(
+        ("Authorization" = [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("table_name" = String, Path, description = "Table name"),
+        ("size" = Option<usize>, Query, description = "Number of rows to return, default 10, max 1000"),
+    ),
+    responses(
+        (status = StatusCode::OK, description = "First rows of the table", body = Vec<Object>),
+        (status = StatusCode::NOT_FOUND, description = "Table not found", body = HttpResponse),
+    ),
+)]
+#[get("/{org_id}/enrichment_tables/{table_name}/preview")]
+pub async fn preview(
+    path: web::Path<(String, String)>,
+    query: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse, Error> {
+    let (org_id, table_name) = path.into_inner();
+    let size = query
+        .get("size")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(10);
+    match get_enrichment_table_preview(&org_id, &table_name, size).await {
+        Ok(Some(rows)) => Ok(MetaHttpResponse::json(rows)),
+        Ok(None) => Ok(MetaHttpResponse::not_found("enrichment table not found")),
+        Err(e) => Ok(MetaHttpResponse::internal_error(e)),
+    }
+}
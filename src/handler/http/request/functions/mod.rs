@@ -13,10 +13,10 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::io::Error;
+use std::{collections::HashMap, io::Error};
 
 use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse};
-use config::meta::function::{TestVRLRequest, Transform};
+use config::meta::function::{FunctionBundle, TestVRLRequest, Transform};
 
 /// CreateFunction
 #[utoipa::path(
@@ -95,6 +95,85 @@ async fn list_functions(
     crate::service::functions::list_functions(org_id.into_inner(), _permitted).await
 }
 
+/// ExportFunctions
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Functions",
+    operation_id = "exportFunctions",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = FunctionBundle),
+    )
+)]
+#[get("/{org_id}/functions/export")]
+pub async fn export_functions(
+    org_id: web::Path<String>,
+    _req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let mut _permitted = None;
+    // Get List of allowed objects
+    #[cfg(feature = "enterprise")]
+    {
+        let user_id = _req.headers().get("user_id").unwrap();
+        match crate::handler::http::auth::validator::list_objects_for_user(
+            &org_id,
+            user_id.to_str().unwrap(),
+            "GET",
+            "function",
+        )
+        .await
+        {
+            Ok(list) => {
+                _permitted = list;
+            }
+            Err(e) => {
+                return Ok(crate::common::meta::http::HttpResponse::forbidden(
+                    e.to_string(),
+                ));
+            }
+        }
+        // Get List of allowed objects ends
+    }
+
+    crate::service::functions::export_functions(&org_id, _permitted).await
+}
+
+/// ImportFunctions
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Functions",
+    operation_id = "importFunctions",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("overwrite" = Option<bool>, Query, description = "Overwrite functions with the same name, default is to skip them"),
+    ),
+    request_body(content = FunctionBundle, description = "Exported functions", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = FunctionImportResponse),
+        (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[post("/{org_id}/functions/import")]
+pub async fn import_functions(
+    path: web::Path<String>,
+    bundle: web::Json<FunctionBundle>,
+    query: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse, Error> {
+    let org_id = path.into_inner();
+    let overwrite = query
+        .get("overwrite")
+        .is_some_and(|v| v.eq_ignore_ascii_case("true"));
+    crate::service::functions::import_functions(&org_id, bundle.into_inner(), overwrite).await
+}
+
 /// DeleteFunction
 #[utoipa::path(
     context_path = "/api",
@@ -106,17 +185,24 @@ async fn list_functions(
     params(
         ("org_id" = String, Path, description = "Organization name"),
         ("name" = String, Path, description = "Function name"),
-        ("force" = bool, Query, description = "Force delete function regardless pipeline dependencies"),
+        ("force" = Option<bool>, Query, description = "Force delete function regardless pipeline and alert dependencies"),
     ),
     responses(
         (status = 200, description = "Success",  content_type = "application/json", body = HttpResponse),
         (status = 404, description = "NotFound", content_type = "application/json", body = HttpResponse),
+        (status = 409, description = "Function in use", content_type = "application/json", body = FunctionInUseResponse),
     )
 )]
 #[delete("/{org_id}/functions/{name}")]
-async fn delete_function(path: web::Path<(String, String)>) -> Result<HttpResponse, Error> {
+async fn delete_function(
+    path: web::Path<(String, String)>,
+    query: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse, Error> {
     let (org_id, name) = path.into_inner();
-    crate::service::functions::delete_function(org_id, name).await
+    let force = query
+        .get("force")
+        .is_some_and(|v| v.eq_ignore_ascii_case("true"));
+    crate::service::functions::delete_function(org_id, name, force).await
 }
 
 /// UpdateFunction
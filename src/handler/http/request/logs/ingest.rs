@@ -24,13 +24,23 @@ use crate::{
             GCPIngestionRequest, IngestionRequest, KinesisFHIngestionResponse, KinesisFHRequest,
         },
     },
-    handler::http::request::{CONTENT_TYPE_JSON, CONTENT_TYPE_PROTO},
+    handler::http::request::{get_content_type, read_body, read_json_body, ContentType},
     service::{
+        db::organization::get_org_setting,
         logs,
         logs::otlp_http::{logs_json_handler, logs_proto_handler},
     },
 };
 
+/// Returns the maximum ingestion body size for the org, falling back to the
+/// global payload limit when the org doesn't override it.
+async fn get_body_size_limit(org_id: &str) -> usize {
+    match get_org_setting(org_id).await {
+        Ok(setting) if setting.max_ingest_body_size > 0 => setting.max_ingest_body_size,
+        _ => config::get_config().limit.req_payload_limit,
+    }
+}
+
 /// _bulk ES compatible ingestion API
 #[utoipa::path(
     context_path = "/api",
@@ -52,10 +62,14 @@ use crate::{
 pub async fn bulk(
     thread_id: web::Data<usize>,
     org_id: web::Path<String>,
-    body: web::Bytes,
+    payload: web::Payload,
     in_req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
     let org_id = org_id.into_inner();
+    let body = match read_body(&in_req, payload, get_body_size_limit(&org_id).await).await {
+        Ok(body) => body,
+        Err(resp) => return Ok(resp),
+    };
     let user_email = in_req.headers().get("user_id").unwrap().to_str().unwrap();
     Ok(
         match logs::bulk::ingest(**thread_id, &org_id, body, user_email).await {
@@ -93,10 +107,14 @@ pub async fn bulk(
 pub async fn multi(
     thread_id: web::Data<usize>,
     path: web::Path<(String, String)>,
-    body: web::Bytes,
+    payload: web::Payload,
     in_req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
     let (org_id, stream_name) = path.into_inner();
+    let body = match read_body(&in_req, payload, get_body_size_limit(&org_id).await).await {
+        Ok(body) => body,
+        Err(resp) => return Ok(resp),
+    };
     let user_email = in_req.headers().get("user_id").unwrap().to_str().unwrap();
     Ok(
         match logs::ingest::ingest(
@@ -149,10 +167,14 @@ pub async fn multi(
 pub async fn json(
     thread_id: web::Data<usize>,
     path: web::Path<(String, String)>,
-    body: web::Bytes,
+    payload: web::Payload,
     in_req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
     let (org_id, stream_name) = path.into_inner();
+    let body = match read_body(&in_req, payload, get_body_size_limit(&org_id).await).await {
+        Ok(body) => body,
+        Err(resp) => return Ok(resp),
+    };
     let user_email = in_req.headers().get("user_id").unwrap().to_str().unwrap();
     Ok(
         match logs::ingest::ingest(
@@ -183,6 +205,66 @@ pub async fn json(
     )
 }
 
+/// _webhook ingestion API
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Logs",
+    operation_id = "LogsIngestionWebhook",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("stream_name" = String, Path, description = "Stream name"),
+    ),
+    request_body(content = String, description = "Webhook payload (json object or array), mapped by the stream's webhook_mapping setting", content_type = "application/json", example = json!({"action": "opened", "repository": {"full_name": "octocat/Hello-World"}, "sender": {"login": "octocat"}})),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = IngestionResponse, example = json!({"code": 200,"status": [{"name": "github","successful": 1,"failed": 0}]})),
+        (status = 500, description = "Failure", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[post("/{org_id}/{stream_name}/_webhook")]
+pub async fn webhook(
+    thread_id: web::Data<usize>,
+    path: web::Path<(String, String)>,
+    payload: web::Payload,
+    in_req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let (org_id, stream_name) = path.into_inner();
+    let body = match read_body(&in_req, payload, get_body_size_limit(&org_id).await).await {
+        Ok(body) => body,
+        Err(resp) => return Ok(resp),
+    };
+    let user_email = in_req.headers().get("user_id").unwrap().to_str().unwrap();
+    Ok(
+        match logs::ingest::ingest(
+            **thread_id,
+            &org_id,
+            &stream_name,
+            IngestionRequest::Webhook(&body),
+            user_email,
+            None,
+        )
+        .await
+        {
+            Ok(v) => match v.code {
+                503 => HttpResponse::ServiceUnavailable().json(v),
+                _ => MetaHttpResponse::json(v),
+            },
+            Err(e) => {
+                log::error!(
+                    "Error processing request {org_id}/{stream_name}/_webhook: {:?}",
+                    e
+                );
+                HttpResponse::BadRequest().json(MetaHttpResponse::error(
+                    http::StatusCode::BAD_REQUEST.into(),
+                    e.to_string(),
+                ))
+            }
+        },
+    )
+}
+
 /// _kinesis_firehose ingestion API
 #[utoipa::path(
     context_path = "/api",
@@ -205,10 +287,15 @@ pub async fn json(
 pub async fn handle_kinesis_request(
     thread_id: web::Data<usize>,
     path: web::Path<(String, String)>,
-    post_data: web::Json<KinesisFHRequest>,
+    payload: web::Payload,
     in_req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
     let (org_id, stream_name) = path.into_inner();
+    let post_data: KinesisFHRequest =
+        match read_json_body(&in_req, payload, get_body_size_limit(&org_id).await).await {
+            Ok(v) => v,
+            Err(resp) => return Ok(resp),
+        };
     let user_email = in_req.headers().get("user_id").unwrap().to_str().unwrap();
     let request_id = post_data.request_id.clone();
     let request_time = post_data
@@ -219,12 +306,19 @@ pub async fn handle_kinesis_request(
             **thread_id,
             &org_id,
             &stream_name,
-            IngestionRequest::KinesisFH(&post_data.into_inner()),
+            IngestionRequest::KinesisFH(&post_data),
             user_email,
             None,
         )
         .await
         {
+            Ok(v) if v.code == 503 => {
+                HttpResponse::ServiceUnavailable().json(KinesisFHIngestionResponse {
+                    request_id,
+                    timestamp: request_time,
+                    error_message: v.error,
+                })
+            }
             Ok(_) => MetaHttpResponse::json(KinesisFHIngestionResponse {
                 request_id,
                 timestamp: request_time,
@@ -246,23 +340,31 @@ pub async fn handle_kinesis_request(
 pub async fn handle_gcp_request(
     thread_id: web::Data<usize>,
     path: web::Path<(String, String)>,
-    post_data: web::Json<GCPIngestionRequest>,
+    payload: web::Payload,
     in_req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
     let (org_id, stream_name) = path.into_inner();
+    let post_data: GCPIngestionRequest =
+        match read_json_body(&in_req, payload, get_body_size_limit(&org_id).await).await {
+            Ok(v) => v,
+            Err(resp) => return Ok(resp),
+        };
     let user_email = in_req.headers().get("user_id").unwrap().to_str().unwrap();
     Ok(
         match logs::ingest::ingest(
             **thread_id,
             &org_id,
             &stream_name,
-            IngestionRequest::GCP(&post_data.into_inner()),
+            IngestionRequest::GCP(&post_data),
             user_email,
             None,
         )
         .await
         {
-            Ok(v) => MetaHttpResponse::json(v),
+            Ok(v) => match v.code {
+                503 => HttpResponse::ServiceUnavailable().json(v),
+                _ => MetaHttpResponse::json(v),
+            },
             Err(e) => {
                 log::error!(
                     "Error processing request {org_id}/{stream_name}/_gcp: {:?}",
@@ -293,16 +395,23 @@ pub async fn otlp_logs_write(
     thread_id: web::Data<usize>,
     org_id: web::Path<String>,
     req: HttpRequest,
-    body: web::Bytes,
+    payload: web::Payload,
 ) -> Result<HttpResponse, Error> {
     let org_id = org_id.into_inner();
-    let content_type = req.headers().get("Content-Type").unwrap().to_str().unwrap();
+    let content_type = match get_content_type(&req) {
+        Ok(v) => v,
+        Err(resp) => return Ok(resp),
+    };
+    let body = match read_body(&req, payload, get_body_size_limit(&org_id).await).await {
+        Ok(body) => body,
+        Err(resp) => return Ok(resp),
+    };
     let user_email = req.headers().get("user_id").unwrap().to_str().unwrap();
     let in_stream_name = req
         .headers()
         .get(&config::get_config().grpc.stream_header_key)
         .map(|header| header.to_str().unwrap());
-    if content_type.eq(CONTENT_TYPE_PROTO) {
+    if content_type == ContentType::Proto {
         // log::info!("otlp::logs_proto_handler");
         match logs_proto_handler(**thread_id, &org_id, body, in_stream_name, user_email).await {
             Ok(v) => Ok(v),
@@ -318,7 +427,7 @@ pub async fn otlp_logs_write(
                 )))
             }
         }
-    } else if content_type.starts_with(CONTENT_TYPE_JSON) {
+    } else if content_type == ContentType::Json {
         // log::info!("otlp::logs_json_handler");
         match logs_json_handler(**thread_id, &org_id, body, in_stream_name, user_email).await {
             Ok(v) => Ok(v),
@@ -341,3 +450,83 @@ pub async fn otlp_logs_write(
         )))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use actix_web::FromRequest;
+    use infra::db as infra_db;
+
+    use super::*;
+    use crate::{
+        common::meta::organization::OrganizationSetting, service::db::organization::set_org_setting,
+    };
+
+    async fn payload(body: &'static [u8]) -> (HttpRequest, web::Payload) {
+        let (req, mut pl) = actix_web::test::TestRequest::default()
+            .set_payload(body)
+            .to_http_parts();
+        let payload = web::Payload::from_request(&req, &mut pl).await.unwrap();
+        (req, payload)
+    }
+
+    #[tokio::test]
+    async fn test_org_body_size_limit() {
+        infra_db::create_table().await.unwrap();
+        let global_limit = config::get_config().limit.req_payload_limit;
+        let body = br#"[{"log":"hello world"}]"#;
+
+        // raised limit
+        let setting = OrganizationSetting {
+            max_ingest_body_size: global_limit * 2,
+            ..Default::default()
+        };
+        set_org_setting("org_body_raised", &setting).await.unwrap();
+        let limit = get_body_size_limit("org_body_raised").await;
+        assert_eq!(limit, global_limit * 2);
+        let (req, pl) = payload(body).await;
+        assert!(read_body(&req, pl, limit).await.is_ok());
+
+        // lowered limit
+        let setting = OrganizationSetting {
+            max_ingest_body_size: 8,
+            ..Default::default()
+        };
+        set_org_setting("org_body_lowered", &setting).await.unwrap();
+        let limit = get_body_size_limit("org_body_lowered").await;
+        assert_eq!(limit, 8);
+        let (req, pl) = payload(body).await;
+        let resp = read_body(&req, pl, limit).await.unwrap_err();
+        assert_eq!(resp.status(), http::StatusCode::PAYLOAD_TOO_LARGE);
+
+        // no override
+        assert_eq!(get_body_size_limit("org_body_default").await, global_limit);
+    }
+
+    #[tokio::test]
+    async fn test_org_body_size_limit_compressed() {
+        use std::io::Write;
+
+        let body = format!("[{}]", vec![r#"{"log":"hello world"}"#; 50].join(",")).into_bytes();
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(&body).unwrap();
+        let gzip = gzip.finish().unwrap();
+        let gzipped = |data: Vec<u8>| async move {
+            let (req, mut pl) = actix_web::test::TestRequest::default()
+                .insert_header((http::header::CONTENT_ENCODING, "gzip"))
+                .set_payload(data)
+                .to_http_parts();
+            let payload = web::Payload::from_request(&req, &mut pl).await.unwrap();
+            (req, payload)
+        };
+
+        // the body is decompressed
+        let (req, pl) = gzipped(gzip.clone()).await;
+        assert_eq!(read_body(&req, pl, 4096).await.unwrap(), body);
+
+        // the limit applies to the decompressed body
+        assert!(gzip.len() < body.len());
+        let (req, pl) = gzipped(gzip).await;
+        let resp = read_body(&req, pl, body.len() - 1).await.unwrap_err();
+        assert_eq!(resp.status(), http::StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -19,7 +19,7 @@ use actix_web::{http, post, web, HttpRequest, HttpResponse};
 
 use crate::{
     common::meta::http::HttpResponse as MetaHttpResponse,
-    handler::http::request::{CONTENT_TYPE_JSON, CONTENT_TYPE_PROTO},
+    handler::http::request::{get_content_type, read_body, ContentType},
     service::metrics,
 };
 
@@ -41,8 +41,16 @@ use crate::{
     )
 )]
 #[post("/{org_id}/ingest/metrics/_json")]
-pub async fn json(org_id: web::Path<String>, body: web::Bytes) -> Result<HttpResponse, Error> {
+pub async fn json(
+    org_id: web::Path<String>,
+    req: HttpRequest,
+    payload: web::Payload,
+) -> Result<HttpResponse, Error> {
     let org_id = org_id.into_inner();
+    let body = match read_body(&req, payload, config::get_config().limit.req_payload_limit).await {
+        Ok(v) => v,
+        Err(resp) => return Ok(resp),
+    };
     Ok(match metrics::json::ingest(&org_id, body).await {
         Ok(v) => HttpResponse::Ok().json(v),
         Err(e) => {
@@ -71,18 +79,23 @@ pub async fn json(org_id: web::Path<String>, body: web::Bytes) -> Result<HttpRes
 pub async fn otlp_metrics_write(
     org_id: web::Path<String>,
     req: HttpRequest,
-    body: web::Bytes,
+    payload: web::Payload,
 ) -> Result<HttpResponse, Error> {
     let org_id = org_id.into_inner();
-    let content_type = req.headers().get("Content-Type").unwrap().to_str().unwrap();
-    if content_type.eq(CONTENT_TYPE_PROTO) {
-        metrics::otlp::otlp_proto(&org_id, body).await
-    } else if content_type.starts_with(CONTENT_TYPE_JSON) {
-        metrics::otlp::otlp_json(&org_id, body).await
-    } else {
-        Ok(HttpResponse::BadRequest().json(MetaHttpResponse::error(
+    let content_type = match get_content_type(&req) {
+        Ok(v) => v,
+        Err(resp) => return Ok(resp),
+    };
+    let body = match read_body(&req, payload, config::get_config().limit.req_payload_limit).await {
+        Ok(v) => v,
+        Err(resp) => return Ok(resp),
+    };
+    match content_type {
+        ContentType::Proto => metrics::otlp::otlp_proto(&org_id, body).await,
+        ContentType::Json => metrics::otlp::otlp_json(&org_id, body).await,
+        ContentType::Other => Ok(HttpResponse::BadRequest().json(MetaHttpResponse::error(
             http::StatusCode::BAD_REQUEST.into(),
             "Bad Request".to_string(),
-        )))
+        ))),
     }
 }
@@ -13,6 +13,13 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use actix_web::{dev::Decompress, http, web, HttpRequest, HttpResponse};
+use config::utils::json;
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+
+use crate::common::meta::http::HttpResponse as MetaHttpResponse;
+
 #[cfg(feature = "enterprise")]
 pub mod actions;
 pub mod alerts;
@@ -46,3 +53,229 @@ pub mod websocket;
 
 pub const CONTENT_TYPE_JSON: &str = "application/json";
 pub const CONTENT_TYPE_PROTO: &str = "application/x-protobuf";
+
+/// Body formats of the ingestion endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentType {
+    Json,
+    Proto,
+    Other,
+}
+
+impl ContentType {
+    /// Parses a `Content-Type` value, ignoring case and parameters like `charset`.
+    pub fn parse(value: &str) -> Self {
+        let mime = value.split(';').next().unwrap_or_default().trim();
+        if mime.eq_ignore_ascii_case(CONTENT_TYPE_JSON) {
+            ContentType::Json
+        } else if mime.eq_ignore_ascii_case(CONTENT_TYPE_PROTO) {
+            ContentType::Proto
+        } else {
+            ContentType::Other
+        }
+    }
+}
+
+/// Returns the request content type, or a 400 when the header is missing.
+pub fn get_content_type(req: &HttpRequest) -> Result<ContentType, HttpResponse> {
+    match req
+        .headers()
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(v) => Ok(ContentType::parse(v)),
+        None => Err(HttpResponse::BadRequest().json(MetaHttpResponse::error(
+            http::StatusCode::BAD_REQUEST.into(),
+            "Missing Content-Type header".to_string(),
+        ))),
+    }
+}
+
+/// Reads an ingestion request body, responding with 413 once it grows over `limit`.
+///
+/// Bodies sent with a `Content-Encoding` actix supports (gzip, deflate, br, zstd) are
+/// decompressed as they are read, off the async workers for large chunks, and the limit
+/// applies to the decompressed body. Broken bodies get a 400.
+pub async fn read_body(
+    req: &HttpRequest,
+    payload: web::Payload,
+    limit: usize,
+) -> Result<web::Bytes, HttpResponse> {
+    let mut payload = Decompress::from_headers(payload, req.headers());
+    let mut body = web::BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(|e| {
+            HttpResponse::BadRequest().json(MetaHttpResponse::error(
+                http::StatusCode::BAD_REQUEST.into(),
+                format!("Failed to read request body: {e}"),
+            ))
+        })?;
+        if body.len() + chunk.len() > limit {
+            return Err(payload_too_large(limit));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body.freeze())
+}
+
+/// Reads a JSON ingestion request body through [read_body], a body that doesn't parse
+/// gets a 400.
+pub async fn read_json_body<T: DeserializeOwned>(
+    req: &HttpRequest,
+    payload: web::Payload,
+    limit: usize,
+) -> Result<T, HttpResponse> {
+    let body = read_body(req, payload, limit).await?;
+    json::from_slice(&body).map_err(|e| {
+        HttpResponse::BadRequest().json(MetaHttpResponse::error(
+            http::StatusCode::BAD_REQUEST.into(),
+            format!("Failed to parse request body: {e}"),
+        ))
+    })
+}
+
+fn payload_too_large(limit: usize) -> HttpResponse {
+    HttpResponse::PayloadTooLarge().json(MetaHttpResponse::error(
+        http::StatusCode::PAYLOAD_TOO_LARGE.into(),
+        format!("Request body exceeds the ingestion limit of {limit} bytes"),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use actix_web::FromRequest;
+
+    use super::*;
+
+    const BODY: &[u8] = br#"[{"log":"hello world"},{"log":"hello again"}]"#;
+
+    #[test]
+    fn test_content_type_parse() {
+        assert_eq!(ContentType::parse("application/json"), ContentType::Json);
+        assert_eq!(
+            ContentType::parse("application/json; charset=utf-8"),
+            ContentType::Json
+        );
+        assert_eq!(ContentType::parse("Application/JSON"), ContentType::Json);
+        assert_eq!(
+            ContentType::parse("application/x-protobuf"),
+            ContentType::Proto
+        );
+        assert_eq!(
+            ContentType::parse("APPLICATION/X-PROTOBUF;charset=UTF-8"),
+            ContentType::Proto
+        );
+        assert_eq!(ContentType::parse("text/plain"), ContentType::Other);
+        assert_eq!(ContentType::parse(""), ContentType::Other);
+    }
+
+    #[test]
+    fn test_get_content_type() {
+        let req = actix_web::test::TestRequest::post()
+            .insert_header((
+                http::header::CONTENT_TYPE,
+                "application/json; charset=utf-8",
+            ))
+            .to_http_request();
+        assert_eq!(get_content_type(&req).unwrap(), ContentType::Json);
+
+        let req = actix_web::test::TestRequest::post().to_http_request();
+        let resp = get_content_type(&req).unwrap_err();
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+    }
+
+    async fn read(encoding: &str, data: Vec<u8>, limit: usize) -> Result<web::Bytes, HttpResponse> {
+        let (req, mut pl) = actix_web::test::TestRequest::post()
+            .uri("/api/default/default/_json")
+            .insert_header((http::header::CONTENT_ENCODING, encoding))
+            .set_payload(data)
+            .to_http_parts();
+        let payload = web::Payload::from_request(&req, &mut pl).await.unwrap();
+        read_body(&req, payload, limit).await
+    }
+
+    /// A brotli stream storing `data` in one uncompressed meta-block, brotli has no
+    /// encoder among the dependencies.
+    fn brotli_uncompressed(data: &[u8]) -> Vec<u8> {
+        // WBITS = 16 (0), ISLAST = 0, MNIBBLES = 4 (00), MLEN - 1 on 16 bits and
+        // ISUNCOMPRESSED = 1, packed from the least significant bit
+        let header = (((data.len() - 1) as u32) << 4) | (1 << 20);
+        let mut out = header.to_le_bytes()[..3].to_vec();
+        out.extend_from_slice(data);
+        // ISLAST = 1, ISLASTEMPTY = 1
+        out.push(0b11);
+        out
+    }
+
+    #[tokio::test]
+    async fn test_read_body_decompress() {
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(BODY).unwrap();
+        let gzip = gzip.finish().unwrap();
+        let mut deflate =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        deflate.write_all(BODY).unwrap();
+        let deflate = deflate.finish().unwrap();
+        let zstd = zstd::encode_all(BODY, 3).unwrap();
+
+        for (encoding, data) in [
+            ("identity", BODY.to_vec()),
+            ("gzip", gzip),
+            ("deflate", deflate),
+            ("zstd", zstd),
+            ("br", brotli_uncompressed(BODY)),
+        ] {
+            let body = read(encoding, data, 1024).await.unwrap();
+            assert_eq!(&body[..], BODY, "encoding: {encoding}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_body_decompress_errors() {
+        for encoding in ["gzip", "deflate", "zstd"] {
+            let resp = read(encoding, BODY.to_vec(), 1024).await.unwrap_err();
+            assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST, "{encoding}");
+        }
+
+        // the decompressed size is limited too
+        let zstd = zstd::encode_all(&[b'a'; 4096][..], 3).unwrap();
+        assert!(zstd.len() < 1024);
+        let resp = read("zstd", zstd, 1024).await.unwrap_err();
+        assert_eq!(resp.status(), http::StatusCode::PAYLOAD_TOO_LARGE);
+        let resp = read("br", brotli_uncompressed(&[b'a'; 2048]), 1024)
+            .await
+            .unwrap_err();
+        assert_eq!(resp.status(), http::StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_read_body_unknown_encoding() {
+        // prometheus remote write bodies are snappy blocks decoded by the service
+        let body = read("snappy", BODY.to_vec(), 1024).await.unwrap();
+        assert_eq!(&body[..], BODY);
+    }
+
+    #[tokio::test]
+    async fn test_read_json_body() {
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(BODY).unwrap();
+        let (req, mut pl) = actix_web::test::TestRequest::post()
+            .insert_header((http::header::CONTENT_ENCODING, "gzip"))
+            .set_payload(gzip.finish().unwrap())
+            .to_http_parts();
+        let payload = web::Payload::from_request(&req, &mut pl).await.unwrap();
+        let rows: Vec<json::Value> = read_json_body(&req, payload, 1024).await.unwrap();
+        assert_eq!(rows.len(), 2);
+
+        let (req, mut pl) = actix_web::test::TestRequest::post()
+            .set_payload(&BODY[1..])
+            .to_http_parts();
+        let payload = web::Payload::from_request(&req, &mut pl).await.unwrap();
+        let resp = read_json_body::<Vec<json::Value>>(&req, payload, 1024)
+            .await
+            .unwrap_err();
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+    }
+}
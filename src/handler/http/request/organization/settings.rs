@@ -101,6 +101,26 @@ async fn create(
         }
     }
 
+    if let Some(max_ingest_body_size) = settings.max_ingest_body_size {
+        field_found = true;
+        data.max_ingest_body_size = max_ingest_body_size;
+    }
+    if let Some(enable_rejected_stream) = settings.enable_rejected_stream {
+        field_found = true;
+        data.enable_rejected_stream = enable_rejected_stream;
+    }
+    if let Some(routes) = settings.traces_stream_routing {
+        if routes.iter().any(|route| {
+            route.attribute.is_empty() || route.value.is_empty() || route.stream.is_empty()
+        }) {
+            return Ok(MetaHttpResponse::bad_request(
+                "traces_stream_routing routes need an attribute, a value and a stream",
+            ));
+        }
+        field_found = true;
+        data.traces_stream_routing = routes;
+    }
+
     if !field_found {
         return Ok(MetaHttpResponse::bad_request("No valid field found"));
     }
@@ -17,11 +17,17 @@ use std::io::Error;
 
 use actix_web::{delete, get, http, post, put, web, HttpRequest, HttpResponse};
 use ahash::HashMap;
-use config::{ider, meta::pipeline::Pipeline};
+use config::{
+    ider,
+    meta::pipeline::{Pipeline, PipelineDestinations, PipelineDrainStatus, PipelineStats},
+};
 
 use crate::{
     common::meta::http::HttpResponse as MetaHttpResponse,
-    service::{db::pipeline::PipelineError, pipeline},
+    service::{
+        db::pipeline::PipelineError,
+        pipeline::{self, backfill::BackfillJob},
+    },
 };
 
 impl From<PipelineError> for HttpResponse {
@@ -29,6 +35,7 @@ impl From<PipelineError> for HttpResponse {
         match value {
             PipelineError::InfraError(err) => MetaHttpResponse::internal_error(err),
             PipelineError::NotFound(_) => MetaHttpResponse::not_found(value),
+            PipelineError::BackfillNotFound(_) => MetaHttpResponse::not_found(value),
             PipelineError::Modified(_) => MetaHttpResponse::conflict(value),
             error => MetaHttpResponse::bad_request(error),
         }
@@ -258,3 +265,183 @@ pub async fn enable_pipeline(
         Err(e) => Ok(e.into()),
     }
 }
+
+/// GetPipelineDrainStatus
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Pipelines",
+    operation_id = "getPipelineDrainStatus",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("pipeline_id" = String, Path, description = "Pipeline ID"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = PipelineDrainStatus),
+        (status = 404, description = "NotFound", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[get("/{org_id}/pipelines/{pipeline_id}/drain_status")]
+pub async fn get_drain_status(path: web::Path<(String, String)>) -> Result<HttpResponse, Error> {
+    let (org_id, pipeline_id) = path.into_inner();
+    match pipeline::get_drain_status(&org_id, &pipeline_id).await {
+        Ok(status) => Ok(MetaHttpResponse::json(status)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+/// GetPipelineDestinations
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Pipelines",
+    operation_id = "getPipelineDestinations",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("pipeline_id" = String, Path, description = "Pipeline ID"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = PipelineDestinations),
+        (status = 404, description = "NotFound", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[get("/{org_id}/pipelines/{pipeline_id}/destinations")]
+pub async fn get_pipeline_destinations(
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, Error> {
+    let (org_id, pipeline_id) = path.into_inner();
+    match pipeline::get_pipeline_destinations(&org_id, &pipeline_id).await {
+        Ok(destinations) => Ok(MetaHttpResponse::json(destinations)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+/// GetPipelineStats
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Pipelines",
+    operation_id = "getPipelineStats",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("pipeline_id" = String, Path, description = "Pipeline ID"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = PipelineStats),
+        (status = 404, description = "NotFound", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[get("/{org_id}/pipelines/{pipeline_id}/stats")]
+pub async fn get_pipeline_stats(path: web::Path<(String, String)>) -> Result<HttpResponse, Error> {
+    let (org_id, pipeline_id) = path.into_inner();
+    match pipeline::get_pipeline_stats(&org_id, &pipeline_id).await {
+        Ok(stats) => Ok(MetaHttpResponse::json(stats)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+/// BackfillPipeline
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Pipelines",
+    operation_id = "backfillPipeline",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("pipeline_id" = String, Path, description = "Pipeline ID"),
+        ("start_time" = i64, Query, description = "Start of the source records to reprocess, in microseconds"),
+        ("end_time" = i64, Query, description = "End of the source records to reprocess, in microseconds"),
+    ),
+    responses(
+        (status = 200, description = "Success",  content_type = "application/json", body = BackfillJob),
+        (status = 400, description = "Failure",  content_type = "application/json", body = HttpResponse),
+        (status = 404, description = "NotFound", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[post("/{org_id}/pipelines/{pipeline_id}/backfill")]
+pub async fn backfill_pipeline(
+    path: web::Path<(String, String)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let (org_id, pipeline_id) = path.into_inner();
+    let query = web::Query::<HashMap<String, String>>::from_query(req.query_string()).unwrap();
+    let (Some(start_time), Some(end_time)) = (
+        query.get("start_time").and_then(|v| v.parse::<i64>().ok()),
+        query.get("end_time").and_then(|v| v.parse::<i64>().ok()),
+    ) else {
+        return Ok(MetaHttpResponse::bad_request(
+            "start_time and end_time are required",
+        ));
+    };
+    match pipeline::backfill::backfill(&org_id, &pipeline_id, start_time, end_time).await {
+        Ok(job) => Ok(MetaHttpResponse::json(job)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+/// GetPipelineBackfill
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Pipelines",
+    operation_id = "getPipelineBackfill",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("pipeline_id" = String, Path, description = "Pipeline ID"),
+        ("job_id" = String, Path, description = "Backfill job ID"),
+    ),
+    responses(
+        (status = 200, description = "Success",  content_type = "application/json", body = BackfillJob),
+        (status = 404, description = "NotFound", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[get("/{org_id}/pipelines/{pipeline_id}/backfill/{job_id}")]
+pub async fn get_backfill(
+    path: web::Path<(String, String, String)>,
+) -> Result<HttpResponse, Error> {
+    let (org_id, pipeline_id, job_id) = path.into_inner();
+    match pipeline::backfill::get_backfill(&org_id, &pipeline_id, &job_id).await {
+        Ok(job) => Ok(MetaHttpResponse::json(job)),
+        Err(e) => Ok(e.into()),
+    }
+}
+
+/// CancelPipelineBackfill
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Pipelines",
+    operation_id = "cancelPipelineBackfill",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("pipeline_id" = String, Path, description = "Pipeline ID"),
+        ("job_id" = String, Path, description = "Backfill job ID"),
+    ),
+    responses(
+        (status = 200, description = "Success",  content_type = "application/json", body = BackfillJob),
+        (status = 400, description = "Failure",  content_type = "application/json", body = HttpResponse),
+        (status = 404, description = "NotFound", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[delete("/{org_id}/pipelines/{pipeline_id}/backfill/{job_id}")]
+pub async fn cancel_backfill(
+    path: web::Path<(String, String, String)>,
+) -> Result<HttpResponse, Error> {
+    let (org_id, pipeline_id, job_id) = path.into_inner();
+    match pipeline::backfill::cancel_backfill(&org_id, &pipeline_id, &job_id).await {
+        Ok(job) => Ok(MetaHttpResponse::json(job)),
+        Err(e) => Ok(e.into()),
+    }
+}
@@ -24,6 +24,7 @@ use {config::meta::stream::StreamType, o2_openfga::meta::mapping::OFGA_MODELS};
 
 use crate::{
     common::{meta::http::HttpResponse as MetaHttpResponse, utils::http::get_or_create_trace_id},
+    handler::http::request::{get_content_type, read_body, ContentType},
     service::{metrics, promql},
 };
 
@@ -48,11 +49,19 @@ use crate::{
 pub async fn remote_write(
     org_id: web::Path<String>,
     req: HttpRequest,
-    body: web::Bytes,
+    payload: web::Payload,
 ) -> Result<HttpResponse, Error> {
     let org_id = org_id.into_inner();
-    let content_type = req.headers().get("Content-Type").unwrap().to_str().unwrap();
-    if content_type == "application/x-protobuf" {
+    let content_type = match get_content_type(&req) {
+        Ok(v) => v,
+        Err(resp) => return Ok(resp),
+    };
+    // the snappy block encoding of remote write is left for the service to decode
+    let body = match read_body(&req, payload, config::get_config().limit.req_payload_limit).await {
+        Ok(v) => v,
+        Err(resp) => return Ok(resp),
+    };
+    if content_type == ContentType::Proto {
         Ok(match metrics::prom::remote_write(&org_id, body).await {
             Ok(_) => HttpResponse::Ok().into(),
             Err(e) => HttpResponse::BadRequest().json(MetaHttpResponse::error(
@@ -646,7 +655,7 @@ pub async fn series_post(
 async fn series(
     org_id: &str,
     req: config::meta::promql::RequestSeries,
-    _in_req: HttpRequest,
+    in_req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
     let config::meta::promql::RequestSeries {
         matcher,
@@ -676,7 +685,7 @@ async fn series(
             None => "".to_string(),
         };
 
-        let user_id = _in_req.headers().get("user_id").unwrap();
+        let user_id = in_req.headers().get("user_id").unwrap();
         let user_email = user_id.to_str().unwrap();
 
         if !is_root_user(user_email) {
@@ -712,8 +721,12 @@ async fn series(
         }
     }
 
+    let user_id = in_req
+        .headers()
+        .get("user_id")
+        .and_then(|v| v.to_str().ok());
     Ok(
-        match metrics::prom::get_series(org_id, selector, start, end).await {
+        match metrics::prom::get_series(org_id, user_id, selector, start, end).await {
             Ok(resp) => HttpResponse::Ok().json(promql::ApiFuncResponse::ok(resp, None)),
             Err(err) => {
                 log::error!("get_series failed: {err}");
@@ -852,6 +865,7 @@ async fn labels(
 pub async fn label_values(
     path: web::Path<(String, String)>,
     req: web::Query<config::meta::promql::RequestLabelValues>,
+    in_req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
     let (org_id, label_name) = path.into_inner();
     let config::meta::promql::RequestLabelValues {
@@ -866,8 +880,14 @@ pub async fn label_values(
                 .json(promql::ApiFuncResponse::<()>::err_bad_data(e, None)));
         }
     };
+    let user_id = in_req
+        .headers()
+        .get("user_id")
+        .and_then(|v| v.to_str().ok());
     Ok(
-        match metrics::prom::get_label_values(&org_id, label_name, selector, start, end).await {
+        match metrics::prom::get_label_values(&org_id, user_id, label_name, selector, start, end)
+            .await
+        {
             Ok(resp) => HttpResponse::Ok().json(promql::ApiFuncResponse::ok(resp, None)),
             Err(err) => {
                 log::error!("get_label_values failed: {err}");
@@ -879,6 +899,77 @@ pub async fn label_values(
     )
 }
 
+/// prometheus TSDB stats
+// refer: https://prometheus.io/docs/prometheus/latest/querying/api/#tsdb-stats
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Metrics",
+    operation_id = "PrometheusTsdbStatus",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("limit" = Option<usize>, Query, description = "Maximum number of items to return for each set of statistics, 10 by default"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = HttpResponse, example = json!({
+            "status": "success",
+            "data": {
+                "headStats": {
+                    "numSeries": 508,
+                    "numLabelPairs": 1234,
+                    "chunkCount": 0,
+                    "minTime": 1591516800000,
+                    "maxTime": 1598896800143
+                },
+                "seriesCountByMetricName": [
+                    {
+                        "name": "net_conntrack_dialer_conn_failed_total",
+                        "value": 20
+                    }
+                ],
+                "labelValueCountByLabelName": [
+                    {
+                        "name": "__name__",
+                        "value": 211
+                    }
+                ],
+                "memoryInBytesByLabelName": [
+                    {
+                        "name": "__name__",
+                        "value": 8266
+                    }
+                ],
+                "seriesCountByLabelValuePair": [
+                    {
+                        "name": "job=prometheus",
+                        "value": 425
+                    }
+                ]
+            }
+        })),
+        (status = 500, description = "Failure", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[get("/{org_id}/prometheus/api/v1/status/tsdb")]
+pub async fn tsdb_status(
+    org_id: web::Path<String>,
+    req: web::Query<config::meta::promql::RequestTsdbStatus>,
+) -> Result<HttpResponse, Error> {
+    Ok(
+        match metrics::prom::get_tsdb_status(&org_id, req.into_inner()).await {
+            Ok(resp) => HttpResponse::Ok().json(promql::ApiFuncResponse::ok(resp, None)),
+            Err(err) => {
+                log::error!("get_tsdb_status failed: {err}");
+                HttpResponse::InternalServerError().json(
+                    promql::ApiFuncResponse::<()>::err_internal(err.to_string(), None),
+                )
+            }
+        },
+    )
+}
+
 fn validate_metadata_params(
     matcher: Option<String>,
     start: Option<String>,
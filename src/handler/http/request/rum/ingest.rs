@@ -16,7 +16,7 @@
 use std::io::{prelude::*, Error};
 
 use actix_multipart::form::{bytes::Bytes, MultipartForm};
-use actix_web::{post, web, HttpResponse};
+use actix_web::{post, web, HttpRequest, HttpResponse};
 use config::utils::json;
 use flate2::read::ZlibDecoder;
 use serde::{Deserialize, Serialize};
@@ -26,6 +26,7 @@ use crate::{
         http::HttpResponse as MetaHttpResponse, ingestion::IngestionRequest,
         middleware_data::RumExtraData,
     },
+    handler::http::request::read_body,
     service::logs,
 };
 
@@ -108,10 +109,15 @@ pub struct View {
 #[post("/v1/{org_id}/rum")]
 pub async fn data(
     path: web::Path<String>,
-    body: web::Bytes,
+    req: HttpRequest,
+    payload: web::Payload,
     rum_query_data: web::ReqData<RumExtraData>,
 ) -> Result<HttpResponse, Error> {
     let org_id: String = path.into_inner();
+    let body = match read_body(&req, payload, config::get_config().limit.req_payload_limit).await {
+        Ok(v) => v,
+        Err(resp) => return Ok(resp),
+    };
     let extend_json = &rum_query_data.data;
     Ok(
         match logs::ingest::ingest(
@@ -150,10 +156,15 @@ pub async fn data(
 #[post("/v1/{org_id}/logs")]
 pub async fn log(
     path: web::Path<String>,
-    body: web::Bytes,
+    req: HttpRequest,
+    payload: web::Payload,
     rum_query_data: web::ReqData<RumExtraData>,
 ) -> Result<HttpResponse, Error> {
     let org_id = path.into_inner();
+    let body = match read_body(&req, payload, config::get_config().limit.req_payload_limit).await {
+        Ok(v) => v,
+        Err(resp) => return Ok(resp),
+    };
     let extend_json = &rum_query_data.data;
     Ok(
         match logs::ingest::ingest(
@@ -13,10 +13,14 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{collections::HashMap, io::Error};
+use std::{collections::HashMap, io::Error, sync::Arc};
 
-use actix_web::{get, http::StatusCode, post, web, HttpRequest, HttpResponse};
-use arrow_schema::Schema;
+use actix_web::{
+    get,
+    http::{header, StatusCode},
+    post, web, HttpRequest, HttpResponse,
+};
+use arrow_schema::{DataType, Field, Schema};
 use chrono::{Duration, Utc};
 use config::{
     get_config,
@@ -24,11 +28,14 @@ use config::{
         search::{SearchEventType, SearchHistoryHitResponse},
         self_reporting::usage::{RequestStats, UsageType, USAGE_STREAM},
         sql::resolve_stream_names,
-        stream::StreamType,
+        stream::{FileMeta, StreamType},
     },
     metrics,
-    utils::{base64, json},
-    DISTINCT_FIELDS, TIMESTAMP_COL_NAME,
+    utils::{
+        base64, json, parquet::write_recordbatch_to_parquet,
+        record_batch_ext::convert_json_to_record_batch, schema::infer_json_schema_from_map,
+    },
+    DISTINCT_FIELDS, ID_COL_NAME, TIMESTAMP_COL_NAME,
 };
 use infra::{cache::stats, errors};
 use tracing::{Instrument, Span};
@@ -45,7 +52,7 @@ use crate::{
             http::{
                 get_or_create_trace_id, get_search_event_context_from_request,
                 get_search_type_from_request, get_stream_type_from_request,
-                get_use_cache_from_request, get_work_group,
+                get_use_cache_from_request, get_work_group, TRACE_ID_HEADER,
             },
             stream::get_settings_max_query_range,
         },
@@ -117,6 +124,71 @@ async fn can_use_distinct_stream(
     all_fields_distinct && all_query_fields_distinct
 }
 
+const PARQUET_CONTENT_TYPE: &str = "application/vnd.apache.parquet";
+const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Body format of the search response, picked with the `format` query param.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ResponseFormat {
+    Json,
+    Ndjson,
+    Parquet,
+}
+
+/// Streams the hits of a search one JSON document per line.
+fn hits_to_ndjson(
+    hits: Vec<json::Value>,
+) -> impl futures::Stream<Item = Result<web::Bytes, Error>> {
+    futures::stream::iter(hits.into_iter().map(|hit| {
+        let mut line = json::to_vec(&hit)?;
+        line.push(b'\n');
+        Ok(web::Bytes::from(line))
+    }))
+}
+
+/// Writes the hits of a search as a parquet file. Columns of the queried
+/// streams keep their type from `stream_schema`, only the computed ones, like
+/// aggregates, are inferred from the hits.
+async fn hits_to_parquet(
+    hits: &[json::Value],
+    stream_schema: &Schema,
+) -> Result<Vec<u8>, anyhow::Error> {
+    let records = hits
+        .iter()
+        .filter_map(|v| v.as_object())
+        .collect::<Vec<_>>();
+    let schema = if !records.is_empty() {
+        let inferred = infer_json_schema_from_map(records.iter().copied(), StreamType::Logs)?;
+        let fields = inferred
+            .fields()
+            .iter()
+            .map(|field| match stream_schema.field_with_name(field.name()) {
+                Ok(stream_field) => Arc::new(stream_field.clone().with_nullable(true)),
+                Err(_) => field.clone(),
+            })
+            .collect::<Vec<_>>();
+        Schema::new(fields)
+    } else if !stream_schema.fields().is_empty() {
+        stream_schema.clone()
+    } else {
+        Schema::new(vec![Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false)])
+    };
+    let schema = Arc::new(schema);
+    let mut batches = Vec::new();
+    if !records.is_empty() {
+        let data = records
+            .into_iter()
+            .map(|v| Arc::new(json::Value::Object(v.clone())))
+            .collect::<Vec<_>>();
+        batches.push(convert_json_to_record_batch(&schema, &data)?);
+    }
+    let meta = FileMeta {
+        records: batches.iter().map(|b| b.num_rows() as i64).sum(),
+        ..Default::default()
+    };
+    write_recordbatch_to_parquet(schema, &batches, &[], &meta).await
+}
+
 /// SearchStreamData
 #[utoipa::path(
     context_path = "/api",
@@ -127,6 +199,9 @@ async fn can_use_distinct_stream(
     ),
     params(
         ("org_id" = String, Path, description = "Organization name"),
+        ("timeout" = Option<i64>, Query, description = "timeout, seconds, overrides the request body timeout"),
+        ("format" = Option<String>, Query, description = "response format, json (default), ndjson or parquet"),
+        ("view_id" = Option<String>, Query, description = "saved view whose query runs, the request query overrides the parts it sets"),
     ),
     request_body(content = SearchRequest, description = "Search query", content_type = "application/json", example = json!({
         "query": {
@@ -197,14 +272,51 @@ pub async fn search(
 
     let use_cache = cfg.common.result_cache_enabled && get_use_cache_from_request(&query);
     // handle encoding for query and aggs
-    let mut req: config::meta::search::Request = match json::from_slice(&body) {
+    // a request running a saved view may leave the sql to the view
+    let req = match query.get("view_id") {
+        Some(_) => json::from_slice::<json::Value>(&body).and_then(|mut req| {
+            if let Some(query) = req.get_mut("query").and_then(|v| v.as_object_mut()) {
+                query
+                    .entry("sql")
+                    .or_insert_with(|| json::Value::String(String::new()));
+            }
+            json::from_value(req)
+        }),
+        None => json::from_slice(&body),
+    };
+    let mut req: config::meta::search::Request = match req {
         Ok(v) => v,
         Err(e) => return Ok(MetaHttpResponse::bad_request(e)),
     };
     if let Err(e) = req.decode() {
         return Ok(MetaHttpResponse::bad_request(e));
     }
+    if let Some(view_id) = query.get("view_id") {
+        if let Err(e) = SearchService::apply_saved_view(&org_id, view_id, &mut req.query).await {
+            return Ok(MetaHttpResponse::bad_request(e));
+        }
+    }
     req.use_cache = Some(use_cache);
+    if let Some(timeout) = query.get("timeout").and_then(|v| v.parse::<i64>().ok()) {
+        req.timeout = timeout;
+    }
+    let format = match query.get("format").map(|v| v.to_lowercase()).as_deref() {
+        None | Some("json") => ResponseFormat::Json,
+        Some("parquet") => ResponseFormat::Parquet,
+        Some("ndjson") => ResponseFormat::Ndjson,
+        Some(v) => {
+            return Ok(MetaHttpResponse::bad_request(format!(
+                "unsupported format: {v}, only json, ndjson and parquet are supported"
+            )));
+        }
+    };
+    if let Err(e) = validate_result_window(
+        req.query.from,
+        req.query.size,
+        cfg.limit.query_max_result_window,
+    ) {
+        return Ok(MetaHttpResponse::bad_request(e));
+    }
 
     // set search event type
     if req.search_type.is_none() {
@@ -233,6 +345,31 @@ pub async fn search(
         }
     };
 
+    // search_after breaks ties on the record id when the stream stores it
+    let has_record_id = req.query.search_after.is_some()
+        && match stream_names.as_slice() {
+            [stream_name] => infra::schema::get(&org_id, stream_name, stream_type)
+                .await
+                .is_ok_and(|schema| schema.field_with_name(ID_COL_NAME).is_ok()),
+            _ => false,
+        };
+
+    // parquet keeps the column types of the streams
+    let stream_schema = match format {
+        ResponseFormat::Parquet => {
+            let mut schemas = Vec::with_capacity(stream_names.len());
+            for stream_name in stream_names.iter() {
+                schemas.push(
+                    infra::schema::get(&org_id, stream_name, stream_type)
+                        .await
+                        .unwrap_or_else(|_| Schema::empty()),
+                );
+            }
+            Schema::try_merge(schemas).unwrap_or_else(|_| Schema::empty())
+        }
+        _ => Schema::empty(),
+    };
+
     // get stream settings
     for stream_name in stream_names {
         if let Some(settings) =
@@ -320,6 +457,26 @@ pub async fn search(
         }
     }
 
+    // continue after the cursor of the previous page
+    let search_after_fields = match req.query.search_after.take() {
+        Some(search_after) => {
+            match SearchService::sql::apply_search_after(
+                &req.query.sql,
+                &search_after,
+                has_record_id,
+            ) {
+                Ok((sql, fields)) => {
+                    req.query.sql = sql;
+                    req.query.from = 0;
+                    req.use_cache = Some(false);
+                    Some(fields)
+                }
+                Err(e) => return Ok(MetaHttpResponse::bad_request(e)),
+            }
+        }
+        None => None,
+    };
+
     // run search with cache
     let res = SearchService::cache::search(
         &trace_id,
@@ -332,17 +489,60 @@ pub async fn search(
     .instrument(http_span)
     .await;
     match res {
-        Ok(res) => Ok(HttpResponse::Ok().json(res)),
+        Ok(mut res) => {
+            if let Some(fields) = search_after_fields {
+                res.search_after =
+                    match SearchService::sql::get_search_after_cursor(&res.hits, &fields) {
+                        Ok(cursor) => cursor,
+                        Err(e) => return Ok(MetaHttpResponse::bad_request(e)),
+                    };
+            }
+            match format {
+                ResponseFormat::Json => Ok(HttpResponse::Ok()
+                    .insert_header((TRACE_ID_HEADER, trace_id))
+                    .json(res)),
+                ResponseFormat::Ndjson => Ok(HttpResponse::Ok()
+                    .insert_header((TRACE_ID_HEADER, trace_id))
+                    .insert_header((header::CONTENT_TYPE, NDJSON_CONTENT_TYPE))
+                    .streaming(hits_to_ndjson(res.hits))),
+                ResponseFormat::Parquet => {
+                    Ok(match hits_to_parquet(&res.hits, &stream_schema).await {
+                        Ok(data) => HttpResponse::Ok()
+                            .insert_header((TRACE_ID_HEADER, trace_id.as_str()))
+                            .insert_header((header::CONTENT_TYPE, PARQUET_CONTENT_TYPE))
+                            .insert_header((
+                                header::CONTENT_DISPOSITION,
+                                format!("attachment; filename=\"{trace_id}.parquet\""),
+                            ))
+                            .body(data),
+                        Err(e) => {
+                            log::error!("[trace_id {trace_id}] search to parquet error: {e}");
+                            MetaHttpResponse::internal_error(e)
+                        }
+                    })
+                }
+            }
+        }
         Err(err) => {
             http_report_metrics(start, &org_id, stream_type, "", "500", "_search");
             log::error!("[trace_id {trace_id}] search error: {}", err);
             Ok(match err {
                 errors::Error::ErrorCode(code) => match code {
                     errors::ErrorCodes::SearchCancelQuery(_) => HttpResponse::TooManyRequests()
+                        .insert_header((TRACE_ID_HEADER, trace_id.as_str()))
                         .json(meta::http::HttpResponse::error_code_with_trace_id(
                             code,
                             Some(trace_id),
                         )),
+                    errors::ErrorCodes::SearchQueueTimeout(_)
+                    | errors::ErrorCodes::SearchNodeShuttingDown(_) => {
+                        HttpResponse::ServiceUnavailable()
+                            .insert_header((TRACE_ID_HEADER, trace_id.as_str()))
+                            .json(meta::http::HttpResponse::error_code_with_trace_id(
+                                code,
+                                Some(trace_id),
+                            ))
+                    }
                     _ => HttpResponse::InternalServerError().json(
                         meta::http::HttpResponse::error_code_with_trace_id(code, Some(trace_id)),
                     ),
@@ -486,13 +686,16 @@ pub async fn around(
         .inc();
     // get a local search queue lock
     #[cfg(not(feature = "enterprise"))]
-    let locker = SearchService::QUEUE_LOCKER.clone();
-    #[cfg(not(feature = "enterprise"))]
-    let locker = locker.lock().await;
-    #[cfg(not(feature = "enterprise"))]
-    if !cfg.common.feature_query_queue_enabled {
-        drop(locker);
-    }
+    let _locker = match SearchService::acquire_queue_permit(&trace_id).await {
+        Ok(permit) => permit,
+        Err(e) => {
+            metrics::QUERY_PENDING_NUMS
+                .with_label_values(&[&org_id])
+                .dec();
+            log::error!("{e}");
+            return Ok(MetaHttpResponse::service_unavailable(e));
+        }
+    };
     #[cfg(not(feature = "enterprise"))]
     let took_wait = start.elapsed().as_millis() as usize;
     #[cfg(feature = "enterprise")]
@@ -538,6 +741,10 @@ pub async fn around(
             skip_wal: false,
             streaming_output: false,
             streaming_id: None,
+            search_after: None,
+            quick_scan: false,
+            segment_ids: None,
+            params: None,
         },
         encoding: config::meta::search::RequestEncoding::Empty,
         regions: regions.clone(),
@@ -555,7 +762,7 @@ pub async fn around(
         Ok(res) => res,
         Err(err) => {
             http_report_metrics(start, &org_id, stream_type, &stream_name, "500", "_around");
-            log::error!("search around error: {:?}", err);
+            log::error!("[trace_id {trace_id}] search around error: {:?}", err);
             return Ok(match err {
                 errors::Error::ErrorCode(code) => match code {
                     errors::ErrorCodes::SearchCancelQuery(_) => HttpResponse::TooManyRequests()
@@ -594,6 +801,10 @@ pub async fn around(
             skip_wal: false,
             streaming_output: false,
             streaming_id: None,
+            search_after: None,
+            quick_scan: false,
+            segment_ids: None,
+            params: None,
         },
         encoding: config::meta::search::RequestEncoding::Empty,
         regions,
@@ -611,7 +822,7 @@ pub async fn around(
         Ok(res) => res,
         Err(err) => {
             http_report_metrics(start, &org_id, stream_type, &stream_name, "500", "_around");
-            log::error!("search around error: {:?}", err);
+            log::error!("[trace_id {trace_id}] search around error: {:?}", err);
             return Ok(match err {
                 errors::Error::ErrorCode(code) => match code {
                     errors::ErrorCodes::SearchCancelQuery(_) => HttpResponse::TooManyRequests()
@@ -660,7 +871,7 @@ pub async fn around(
         min_ts: Some(around_start_time),
         max_ts: Some(around_end_time),
         cached_ratio: Some(resp.cached_ratio),
-        trace_id: Some(trace_id),
+        trace_id: Some(trace_id.clone()),
         took_wait_in_queue: match (
             resp_forward.took_detail.as_ref(),
             resp_backward.took_detail.as_ref(),
@@ -690,7 +901,9 @@ pub async fn around(
     )
     .await;
 
-    Ok(HttpResponse::Ok().json(resp))
+    Ok(HttpResponse::Ok()
+        .insert_header((TRACE_ID_HEADER, trace_id))
+        .json(resp))
 }
 
 /// SearchTopNValues
@@ -1016,6 +1229,15 @@ async fn values_v1(
                                 code,
                                 Some(trace_id),
                             )),
+                        errors::ErrorCodes::SearchQueueTimeout(_)
+                        | errors::ErrorCodes::SearchNodeShuttingDown(_) => {
+                            HttpResponse::ServiceUnavailable().json(
+                                meta::http::HttpResponse::error_code_with_trace_id(
+                                    code,
+                                    Some(trace_id),
+                                ),
+                            )
+                        }
                         _ => HttpResponse::InternalServerError().json(
                             meta::http::HttpResponse::error_code_with_trace_id(
                                 code,
@@ -1095,7 +1317,7 @@ async fn values_v1(
         max_ts: Some(end_time),
         cached_ratio: Some(resp.cached_ratio),
         search_type: Some(SearchEventType::Values),
-        trace_id: Some(trace_id),
+        trace_id: Some(trace_id.clone()),
         took_wait_in_queue: if resp.took_detail.is_some() {
             let resp_took = resp.took_detail.as_ref().unwrap();
             // Consider only the cluster wait queue duration
@@ -1118,7 +1340,9 @@ async fn values_v1(
     )
     .await;
 
-    Ok(HttpResponse::Ok().json(resp))
+    Ok(HttpResponse::Ok()
+        .insert_header((TRACE_ID_HEADER, trace_id))
+        .json(resp))
 }
 
 /// SearchStreamPartition
@@ -1201,12 +1425,19 @@ pub async fn search_partition(
     match search_res {
         Ok(res) => {
             http_report_metrics(start, &org_id, stream_type, "", "200", "_search_partition");
-            Ok(HttpResponse::Ok().json(res))
+            Ok(HttpResponse::Ok()
+                .insert_header((TRACE_ID_HEADER, trace_id))
+                .json(res))
         }
         Err(err) => {
             http_report_metrics(start, &org_id, stream_type, "", "500", "_search_partition");
-            log::error!("search error: {:?}", err);
+            log::error!("[trace_id {trace_id}] search partition error: {:?}", err);
             Ok(match err {
+                errors::Error::ErrorCode(code @ errors::ErrorCodes::SearchNodeShuttingDown(_)) => {
+                    HttpResponse::ServiceUnavailable().json(
+                        meta::http::HttpResponse::error_code_with_trace_id(code, Some(trace_id)),
+                    )
+                }
                 errors::Error::ErrorCode(code) => HttpResponse::InternalServerError().json(
                     meta::http::HttpResponse::error_code_with_trace_id(code, Some(trace_id)),
                 ),
@@ -1332,13 +1563,16 @@ pub async fn search_history(
 
     // handle search queue lock and timing
     #[cfg(not(feature = "enterprise"))]
-    let locker = SearchService::QUEUE_LOCKER.clone();
-    #[cfg(not(feature = "enterprise"))]
-    let locker = locker.lock().await;
-    #[cfg(not(feature = "enterprise"))]
-    if !cfg.common.feature_query_queue_enabled {
-        drop(locker);
-    }
+    let _locker = match SearchService::acquire_queue_permit(&trace_id).await {
+        Ok(permit) => permit,
+        Err(e) => {
+            metrics::QUERY_PENDING_NUMS
+                .with_label_values(&[&org_id])
+                .dec();
+            log::error!("{e}");
+            return Ok(MetaHttpResponse::service_unavailable(e));
+        }
+    };
     #[cfg(not(feature = "enterprise"))]
     let took_wait = start.elapsed().as_millis() as usize;
     #[cfg(feature = "enterprise")]
@@ -1437,7 +1671,7 @@ pub async fn search_history(
         max_ts: Some(req.end_time),
         cached_ratio: Some(search_res.cached_ratio),
         search_type: Some(SearchEventType::Other),
-        trace_id: Some(trace_id),
+        trace_id: Some(trace_id.clone()),
         took_wait_in_queue,
         work_group: search_res.work_group.clone(),
         ..Default::default()
@@ -1454,5 +1688,129 @@ pub async fn search_history(
     )
     .await;
 
-    Ok(HttpResponse::Ok().json(search_res))
+    Ok(HttpResponse::Ok()
+        .insert_header((TRACE_ID_HEADER, trace_id))
+        .json(search_res))
+}
+
+/// Reject deep pagination requests whose `from + size` exceeds `max_window`,
+/// `max_window` of 0 means unlimited. A `size` of 0 or less, unlimited, only
+/// counts `from`.
+fn validate_result_window(from: i64, size: i64, max_window: i64) -> Result<(), String> {
+    if from < 0 {
+        return Err(format!(
+            "from must be greater than or equal to 0, got {from}"
+        ));
+    }
+    if max_window <= 0 {
+        return Ok(());
+    }
+    let window = from.saturating_add(size.max(0));
+    if window > max_window {
+        return Err(format!(
+            "Result window is too large, from + size must be less than or equal to {max_window} but was {window}. Use search_after to page through large result sets instead"
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_result_window() {
+        assert!(validate_result_window(0, 100, 0).is_ok());
+        assert!(validate_result_window(9_000, 1_000, 10_000).is_ok());
+        let err = validate_result_window(9_001, 1_000, 10_000).unwrap_err();
+        assert!(err.contains("search_after"));
+        assert!(validate_result_window(i64::MAX, 1_000, 10_000).is_err());
+        // size -1 is unlimited and is bounded by other limits
+        assert!(validate_result_window(0, -1, 10_000).is_ok());
+        assert!(validate_result_window(10_000, 0, 10_000).is_ok());
+        // but can't skip past the window
+        assert!(validate_result_window(10_001, -1, 10_000).is_err());
+        assert!(validate_result_window(10_001, 0, 10_000).is_err());
+        assert!(validate_result_window(-1, 10, 10_000).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_hits_to_ndjson() {
+        use futures::TryStreamExt;
+
+        let hits = (0..3)
+            .map(|i| json::json!({"_timestamp": i, "log": format!("line {i}\nwith newline")}))
+            .collect::<Vec<_>>();
+        let chunks = hits_to_ndjson(hits.clone())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let body = chunks.concat();
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body.ends_with('\n'));
+        let lines = body.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), hits.len());
+        for (line, hit) in lines.iter().zip(hits.iter()) {
+            assert_eq!(&json::from_str::<json::Value>(line).unwrap(), hit);
+        }
+
+        let chunks = hits_to_ndjson(vec![])
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert!(chunks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_hits_to_parquet() {
+        let stream_schema = Schema::new(vec![
+            Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false),
+            Field::new("level", DataType::Utf8, true),
+            Field::new("code", DataType::Float64, true),
+        ]);
+        let hits = vec![
+            json::json!({"_timestamp": 1_700_000_000_000_000i64, "level": "info", "code": 200, "cnt": 3}),
+            json::json!({"_timestamp": 1_700_000_000_000_001i64, "level": "error", "cnt": 1}),
+        ];
+        let data = bytes::Bytes::from(hits_to_parquet(&hits, &stream_schema).await.unwrap());
+        let (schema, batches) = config::utils::parquet::read_recordbatch_from_bytes(&data)
+            .await
+            .unwrap();
+        assert_eq!(
+            schema
+                .field_with_name(TIMESTAMP_COL_NAME)
+                .unwrap()
+                .data_type(),
+            &DataType::Int64
+        );
+        assert_eq!(
+            schema.field_with_name("level").unwrap().data_type(),
+            &DataType::Utf8
+        );
+        // the stream type wins over the type the hits look like
+        assert_eq!(
+            schema.field_with_name("code").unwrap().data_type(),
+            &DataType::Float64
+        );
+        // computed columns are inferred from the hits
+        assert_eq!(
+            schema.field_with_name("cnt").unwrap().data_type(),
+            &DataType::Int64
+        );
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 2);
+
+        // an empty result is still a valid parquet file with the stream columns
+        let data = bytes::Bytes::from(hits_to_parquet(&[], &stream_schema).await.unwrap());
+        let (schema, batches) = config::utils::parquet::read_recordbatch_from_bytes(&data)
+            .await
+            .unwrap();
+        assert!(schema.field_with_name("level").is_ok());
+        assert!(batches.is_empty());
+
+        let data = bytes::Bytes::from(hits_to_parquet(&[], &Schema::empty()).await.unwrap());
+        let (schema, _) = config::utils::parquet::read_recordbatch_from_bytes(&data)
+            .await
+            .unwrap();
+        assert!(schema.field_with_name(TIMESTAMP_COL_NAME).is_ok());
+    }
 }
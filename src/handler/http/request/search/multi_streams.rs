@@ -329,13 +329,16 @@ pub async fn search_multi(
             .inc();
         // get a local search queue lock
         #[cfg(not(feature = "enterprise"))]
-        let locker = SearchService::QUEUE_LOCKER.clone();
-        #[cfg(not(feature = "enterprise"))]
-        let locker = locker.lock().await;
-        #[cfg(not(feature = "enterprise"))]
-        if !cfg.common.feature_query_queue_enabled {
-            drop(locker);
-        }
+        let _locker = match SearchService::acquire_queue_permit(&trace_id).await {
+            Ok(permit) => permit,
+            Err(e) => {
+                metrics::QUERY_PENDING_NUMS
+                    .with_label_values(&[&org_id])
+                    .dec();
+                log::error!("{e}");
+                return Ok(MetaHttpResponse::service_unavailable(e));
+            }
+        };
         #[cfg(not(feature = "enterprise"))]
         let took_wait = start.elapsed().as_millis() as usize;
         #[cfg(feature = "enterprise")]
@@ -935,13 +938,16 @@ pub async fn around_multi(
             .inc();
         // get a local search queue lock
         #[cfg(not(feature = "enterprise"))]
-        let locker = SearchService::QUEUE_LOCKER.clone();
-        #[cfg(not(feature = "enterprise"))]
-        let locker = locker.lock().await;
-        #[cfg(not(feature = "enterprise"))]
-        if !cfg.common.feature_query_queue_enabled {
-            drop(locker);
-        }
+        let _locker = match SearchService::acquire_queue_permit(&trace_id).await {
+            Ok(permit) => permit,
+            Err(e) => {
+                metrics::QUERY_PENDING_NUMS
+                    .with_label_values(&[&org_id])
+                    .dec();
+                log::error!("{e}");
+                return Ok(MetaHttpResponse::service_unavailable(e));
+            }
+        };
         #[cfg(not(feature = "enterprise"))]
         let took_wait = start.elapsed().as_millis() as usize;
         #[cfg(feature = "enterprise")]
@@ -973,6 +979,10 @@ pub async fn around_multi(
                 skip_wal: false,
                 streaming_output: false,
                 streaming_id: None,
+                search_after: None,
+                quick_scan: false,
+                segment_ids: None,
+                params: None,
             },
             encoding: config::meta::search::RequestEncoding::Empty,
             regions: regions.clone(),
@@ -1051,6 +1061,10 @@ pub async fn around_multi(
                 skip_wal: false,
                 streaming_output: false,
                 streaming_id: None,
+                search_after: None,
+                quick_scan: false,
+                segment_ids: None,
+                params: None,
             },
             encoding: config::meta::search::RequestEncoding::Empty,
             regions: regions.clone(),
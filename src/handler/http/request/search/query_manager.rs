@@ -13,9 +13,10 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::io::Error;
+use std::{future::Future, io::Error};
 
 use actix_web::{delete, get, put, web, HttpResponse};
+use config::meta::search::{CancelAllQueryResponse, CancelQueryResponse, QueryStatus};
 use o2_enterprise::enterprise::common::infra::config::get_config as get_o2_config;
 
 use crate::common::meta::http::HttpResponse as MetaHttpResponse;
@@ -43,6 +44,27 @@ pub async fn cancel_multiple_query(
     cancel_query_inner(&org_id, &trace_ids).await
 }
 
+#[put("/{org_id}/query_manager/cancel_all")]
+pub async fn cancel_all_query(params: web::Path<String>) -> Result<HttpResponse, Error> {
+    let org_id = params.into_inner();
+    let query_status = if get_o2_config().super_cluster.enabled {
+        crate::service::search::super_cluster_query_status(&config::ider::uuid()).await
+    } else {
+        crate::service::search::query_status().await
+    };
+    let query_status = match query_status {
+        Ok(v) => v,
+        Err(e) => return Ok(MetaHttpResponse::bad_request(e)),
+    };
+    let trace_ids = get_org_trace_ids(&query_status.status, &org_id);
+    let res = cancel_all_queries(&org_id, trace_ids, |trace_id| {
+        let org_id = org_id.clone();
+        async move { cancel_one_query(&org_id, &trace_id).await }
+    })
+    .await;
+    Ok(HttpResponse::Ok().json(res))
+}
+
 #[get("/{org_id}/query_manager/status")]
 pub async fn query_status(_params: web::Path<String>) -> Result<HttpResponse, Error> {
     let res = crate::service::search::query_status().await;
@@ -56,20 +78,186 @@ pub async fn cancel_query_inner(org_id: &str, trace_ids: &[&str]) -> Result<Http
     if trace_ids.is_empty() {
         return Ok(HttpResponse::BadRequest().json("Invalid trace_id"));
     }
+    match cancel_queries(org_id, trace_ids).await {
+        Ok(res) => Ok(HttpResponse::Ok().json(res)),
+        Err(e) => Ok(MetaHttpResponse::bad_request(e)),
+    }
+}
+
+async fn cancel_queries(
+    org_id: &str,
+    trace_ids: &[&str],
+) -> Result<Vec<CancelQueryResponse>, infra::errors::Error> {
     let mut res = Vec::with_capacity(trace_ids.len());
     for trace_id in trace_ids {
         if trace_id.is_empty() {
             continue;
         }
-        let ret = if get_o2_config().super_cluster.enabled {
-            o2_enterprise::enterprise::super_cluster::search::cancel_query(org_id, trace_id).await
-        } else {
-            crate::service::search::cancel_query(org_id, trace_id).await
+        res.push(cancel_one_query(org_id, trace_id).await?);
+    }
+    Ok(res)
+}
+
+async fn cancel_one_query(
+    org_id: &str,
+    trace_id: &str,
+) -> Result<CancelQueryResponse, infra::errors::Error> {
+    if get_o2_config().super_cluster.enabled {
+        o2_enterprise::enterprise::super_cluster::search::cancel_query(org_id, trace_id).await
+    } else {
+        crate::service::search::cancel_query(org_id, trace_id).await
+    }
+}
+
+/// Cancels the queries one by one, a query that fails to cancel doesn't stop the others
+/// and is reported in `failed`.
+async fn cancel_all_queries<F, Fut>(
+    org_id: &str,
+    trace_ids: Vec<String>,
+    cancel: F,
+) -> CancelAllQueryResponse
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<CancelQueryResponse, infra::errors::Error>>,
+{
+    let mut res = CancelAllQueryResponse::default();
+    for trace_id in trace_ids {
+        match cancel(trace_id.clone()).await {
+            Ok(status) if status.is_success => res.trace_ids.push(trace_id),
+            Ok(_) => res.failed.push(trace_id),
+            Err(e) => {
+                log::error!("[trace_id {trace_id}] cancel query of org {org_id} error: {e}");
+                res.failed.push(trace_id);
+            }
+        }
+    }
+    res.cancelled = res.trace_ids.len();
+    res
+}
+
+/// Returns the trace ids of the queries running or waiting for the org.
+fn get_org_trace_ids(status: &[QueryStatus], org_id: &str) -> Vec<String> {
+    let mut trace_ids = status
+        .iter()
+        .filter(|s| s.org_id.as_deref() == Some(org_id))
+        .map(|s| s.trace_id.clone())
+        .collect::<Vec<_>>();
+    trace_ids.sort();
+    trace_ids.dedup();
+    trace_ids
+}
+
+#[cfg(test)]
+mod tests {
+    use o2_enterprise::enterprise::search::TaskStatus;
+    use proto::cluster_rpc::{search_server::Search, CancelQueryRequest};
+
+    use super::*;
+    use crate::{
+        handler::grpc::request::search::Searcher, service::search::query_status_from_results,
+    };
+
+    #[test]
+    fn test_get_org_trace_ids() {
+        let status = |trace_id: &str, org_id: Option<&str>, status: &str| QueryStatus {
+            trace_id: trace_id.to_string(),
+            org_id: org_id.map(|v| v.to_string()),
+            status: status.to_string(),
+            ..Default::default()
         };
-        match ret {
-            Ok(status) => res.push(status),
-            Err(e) => return Ok(MetaHttpResponse::bad_request(e)),
+        let running = vec![
+            status("t2", Some("org1"), "processing"),
+            status("t1", Some("org1"), "waiting"),
+            status("t3", Some("org2"), "processing"),
+            status("t4", None, "processing"),
+        ];
+        assert_eq!(
+            get_org_trace_ids(&running, "org1"),
+            vec!["t1".to_string(), "t2".to_string()]
+        );
+        assert!(get_org_trace_ids(&running, "org3").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_all_queries() {
+        let org_id = "test_cancel_all_queries";
+        let searcher = Searcher::new();
+        let mut receivers = Vec::new();
+        for (trace_id, task_org_id) in [
+            ("cancel_all_1", org_id),
+            ("cancel_all_2", org_id),
+            ("cancel_all_3", "other_org"),
+        ] {
+            let (sender, receiver) = tokio::sync::oneshot::channel();
+            searcher
+                .insert(
+                    trace_id.to_string(),
+                    TaskStatus::new_leader(
+                        vec![sender],
+                        false,
+                        None,
+                        Some(task_org_id.to_string()),
+                        Some("logs".to_string()),
+                        Some("SELECT * FROM default".to_string()),
+                        Some(0),
+                        Some(1),
+                        None,
+                    ),
+                )
+                .await;
+            receivers.push(receiver);
         }
+
+        let status = query_status_from_results(searcher.get_task_status().await.into_iter());
+        let trace_ids = get_org_trace_ids(&status.status, org_id);
+        let res = cancel_all_queries(org_id, trace_ids, |trace_id| {
+            let searcher = searcher.clone();
+            async move {
+                let request = tonic::Request::new(CancelQueryRequest {
+                    trace_id: trace_id.clone(),
+                });
+                match searcher.cancel_query(request).await {
+                    Ok(res) => Ok(CancelQueryResponse {
+                        trace_id,
+                        is_success: res.into_inner().is_success,
+                    }),
+                    Err(e) => Err(infra::errors::Error::Message(e.to_string())),
+                }
+            }
+        })
+        .await;
+        assert_eq!(res.cancelled, 2);
+        assert_eq!(res.trace_ids, vec!["cancel_all_1", "cancel_all_2"]);
+        assert!(res.failed.is_empty());
+
+        // both queries of the org were aborted, the other org's query keeps running
+        let mut receivers = receivers.into_iter();
+        assert!(receivers.next().unwrap().await.is_ok());
+        assert!(receivers.next().unwrap().await.is_ok());
+        let mut other = receivers.next().unwrap();
+        assert!(matches!(
+            other.try_recv(),
+            Err(tokio::sync::oneshot::error::TryRecvError::Empty)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_all_queries_partial_failure() {
+        let trace_ids = vec!["t1".to_string(), "t2".to_string(), "t3".to_string()];
+        let res = cancel_all_queries("org1", trace_ids, |trace_id| async move {
+            if trace_id == "t2" {
+                Err(infra::errors::Error::Message("node error".to_string()))
+            } else {
+                Ok(CancelQueryResponse {
+                    trace_id,
+                    is_success: true,
+                })
+            }
+        })
+        .await;
+        // the queries cancelled before and after the failure are still counted
+        assert_eq!(res.cancelled, 2);
+        assert_eq!(res.trace_ids, vec!["t1", "t3"]);
+        assert_eq!(res.failed, vec!["t2"]);
     }
-    Ok(HttpResponse::Ok().json(res))
 }
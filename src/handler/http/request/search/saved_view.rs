@@ -15,7 +15,8 @@
 
 use std::io::Error;
 
-use actix_web::{delete, get, post, put, web, HttpResponse};
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse};
+use hashbrown::HashSet;
 
 use crate::{
     common::{
@@ -23,7 +24,8 @@ use crate::{
             authz::Authz,
             http::HttpResponse as MetaHttpResponse,
             saved_view::{
-                CreateViewRequest, CreateViewResponse, DeleteViewResponse, UpdateViewRequest, View,
+                CreateViewRequest, CreateViewResponse, DeleteViewResponse, DeleteViewResult,
+                DeleteViewsRequest, DeleteViewsResponse, UpdateViewRequest, View,
             },
         },
         utils::auth::{remove_ownership, set_ownership},
@@ -143,6 +145,115 @@ pub async fn delete_view(path: web::Path<(String, String)>) -> Result<HttpRespon
     }
 }
 
+// DeleteSavedViewsBulk
+//
+// Delete several views associated with this given org.
+//
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Saved Views",
+    operation_id = "DeleteSavedViewsBulk",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    request_body(content = DeleteViewsRequest, description = "The view_ids to delete", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = DeleteViewsResponse, example = json!({
+            "org_id": "some-org-id",
+            "results": [
+                {"view_id": "view_id_1", "deleted": true},
+                {"view_id": "view_id_2", "deleted": false, "error": "Unauthorized Access"}
+            ]
+        })),
+        (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[delete("/{org_id}/savedviews")]
+pub async fn delete_views(
+    path: web::Path<String>,
+    req: web::Json<DeleteViewsRequest>,
+    in_req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let org_id = path.into_inner();
+    let view_ids = req
+        .into_inner()
+        .view_ids
+        .iter()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect::<Vec<_>>();
+    if view_ids.is_empty() {
+        return Ok(MetaHttpResponse::bad_request("view_ids is empty"));
+    }
+
+    #[cfg(feature = "enterprise")]
+    let allowed = {
+        let user_id = in_req
+            .headers()
+            .get("user_id")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        let mut allowed = HashSet::new();
+        for view_id in view_ids.iter() {
+            if crate::common::utils::auth::check_permissions(
+                Some(view_id.to_string()),
+                &org_id,
+                user_id,
+                "savedviews",
+                "DELETE",
+            )
+            .await
+            {
+                allowed.insert(view_id.to_string());
+            }
+        }
+        allowed
+    };
+    #[cfg(not(feature = "enterprise"))]
+    let allowed = {
+        let _ = in_req;
+        view_ids.iter().cloned().collect::<HashSet<_>>()
+    };
+
+    let results = delete_views_inner(&org_id, &view_ids, &allowed).await;
+    Ok(MetaHttpResponse::json(DeleteViewsResponse {
+        org_id,
+        results,
+    }))
+}
+
+/// Deletes each view the user is allowed to delete, one result per id.
+async fn delete_views_inner(
+    org_id: &str,
+    view_ids: &[String],
+    allowed: &HashSet<String>,
+) -> Vec<DeleteViewResult> {
+    let mut results = Vec::with_capacity(view_ids.len());
+    for view_id in view_ids {
+        let ret = if !allowed.contains(view_id) {
+            Err("Unauthorized Access".to_string())
+        } else if saved_view::get_view(org_id, view_id).await.is_err() {
+            Err("View not found".to_string())
+        } else {
+            saved_view::delete_view(org_id, view_id)
+                .await
+                .map_err(|e| e.to_string())
+        };
+        if ret.is_ok() {
+            remove_ownership(org_id, "savedviews", Authz::new(view_id)).await;
+        }
+        results.push(DeleteViewResult {
+            view_id: view_id.to_string(),
+            deleted: ret.is_ok(),
+            error: ret.err(),
+        });
+    }
+    results
+}
+
 // CreateSavedViews
 //
 // Create a view for later retrieval associated with the given search.
@@ -249,4 +360,37 @@ mod tests {
         let json_body: CreateViewResponse = test::read_body_json(resp).await;
         assert!(!json_body.view_id.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_delete_views_inner() {
+        let org_id = "bulk_delete_views";
+        let mut view_ids = Vec::new();
+        for name in ["owned", "not-owned"] {
+            let view = CreateViewRequest {
+                data: "base64-encoded-data".into(),
+                view_name: name.into(),
+            };
+            view_ids.push(saved_view::set_view(org_id, &view).await.unwrap().view_id);
+        }
+        view_ids.push("missing".to_string());
+        let allowed = [view_ids[0].clone(), "missing".to_string()]
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        let results = delete_views_inner(org_id, &view_ids, &allowed).await;
+        let outcomes = results
+            .iter()
+            .map(|r| (r.view_id.as_str(), r.deleted, r.error.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outcomes,
+            vec![
+                (view_ids[0].as_str(), true, None),
+                (view_ids[1].as_str(), false, Some("Unauthorized Access")),
+                ("missing", false, Some("View not found")),
+            ]
+        );
+        assert!(saved_view::get_view(org_id, &view_ids[0]).await.is_err());
+        assert!(saved_view::get_view(org_id, &view_ids[1]).await.is_ok());
+    }
 }
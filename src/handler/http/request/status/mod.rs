@@ -18,7 +18,7 @@ use std::{io::Error, sync::Arc};
 use actix_web::{
     cookie,
     cookie::{Cookie, SameSite},
-    get, head,
+    delete, get, head,
     http::header,
     put, web, HttpRequest, HttpResponse,
 };
@@ -36,6 +36,8 @@ use infra::{
     file_list,
     schema::{STREAM_SCHEMAS, STREAM_SCHEMAS_COMPRESSED, STREAM_SCHEMAS_LATEST},
 };
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
 use serde::Serialize;
 use utoipa::ToSchema;
 #[cfg(feature = "enterprise")]
@@ -79,11 +81,24 @@ use crate::{
     },
 };
 
+/// Outcome of the last config reload on this node.
+static CONFIG_RELOAD_STATUS: Lazy<RwLock<ConfigReloadStatus>> =
+    Lazy::new(|| RwLock::new(ConfigReloadStatus::default()));
+
 #[derive(Serialize, ToSchema)]
 pub struct HealthzResponse {
     status: String,
 }
 
+#[derive(Clone, Debug, Default, Serialize, ToSchema)]
+pub struct ConfigReloadStatus {
+    /// Time of the last reload in microseconds, 0 if never reloaded
+    last_reload_at: i64,
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
 #[derive(Serialize)]
 struct ConfigResponse<'a> {
     version: String,
@@ -156,6 +171,40 @@ pub async fn healthz() -> Result<HttpResponse, Error> {
     }))
 }
 
+#[derive(Serialize, ToSchema)]
+pub struct StorageHealthzResponse {
+    status: String,
+    reachable: bool,
+    latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Healthz of the object storage used by the node
+#[utoipa::path(
+    path = "/healthz/storage",
+    tag = "Meta",
+    responses(
+        (status = 200, description="Status OK", content_type = "application/json", body = StorageHealthzResponse, example = json!({"status": "ok", "reachable": true, "latency_ms": 12})),
+        (status = 503, description="Storage unreachable", content_type = "application/json", body = StorageHealthzResponse, example = json!({"status": "not ok", "reachable": false, "latency_ms": 10000, "error": "no response within 10000 ms"})),
+    )
+)]
+#[get("/healthz/storage")]
+pub async fn storage_healthz() -> Result<HttpResponse, Error> {
+    let health = infra::storage::check_health().await;
+    let (mut resp, status) = if health.reachable {
+        (HttpResponse::Ok(), "ok")
+    } else {
+        (HttpResponse::ServiceUnavailable(), "not ok")
+    };
+    Ok(resp.json(StorageHealthzResponse {
+        status: status.to_string(),
+        reachable: health.reachable,
+        latency_ms: health.latency_ms,
+        error: health.error,
+    }))
+}
+
 /// Healthz HEAD
 /// Vector pipeline healthcheck support
 #[head("/healthz")]
@@ -313,7 +362,7 @@ pub async fn zo_config() -> Result<HttpResponse, Error> {
 }
 
 #[get("/status")]
-pub async fn cache_status() -> Result<HttpResponse, Error> {
+pub async fn cache_status(req: HttpRequest) -> Result<HttpResponse, Error> {
     let cfg = get_config();
     let mut stats: HashMap<&str, json::Value> = HashMap::default();
     stats.insert("LOCAL_NODE_UUID", json::json!(LOCAL_NODE.uuid.clone()));
@@ -348,6 +397,20 @@ pub async fn cache_status() -> Result<HttpResponse, Error> {
         }),
     );
 
+    let query = web::Query::<HashMap<String, String>>::from_query(req.query_string()).unwrap();
+    let by_stream = match query.get("by_stream") {
+        Some(v) => v.parse::<bool>().unwrap_or_default(),
+        None => false,
+    };
+    if by_stream {
+        let mem_streams = cache::file_data::memory::stream_stats().await;
+        let disk_streams = cache::file_data::disk::stream_stats(FileType::DATA).await;
+        stats.insert(
+            "FILE_DATA_STREAMS",
+            json::json!({"memory": mem_streams, "disk": disk_streams}),
+        );
+    }
+
     let file_list_num = file_list::len().await;
     let file_list_max_id = file_list::get_max_pk_value().await.unwrap_or_default();
     stats.insert(
@@ -380,16 +443,9 @@ pub async fn cache_status() -> Result<HttpResponse, Error> {
 
 #[get("")]
 pub async fn config_reload() -> Result<HttpResponse, Error> {
-    if let Err(e) = config::refresh_config() {
-        return Ok(
-            HttpResponse::InternalServerError().json(serde_json::json!({"status": e.to_string()}))
-        );
-    }
-    #[cfg(feature = "enterprise")]
-    if let Err(e) = refresh_o2_config()
-        .and_then(|_| refresh_dex_config())
-        .and_then(|_| refresh_openfga_config())
-    {
+    let ret = refresh_all_config();
+    record_config_reload(&ret, chrono::Utc::now().timestamp_micros());
+    if let Err(e) = ret {
         return Ok(
             HttpResponse::InternalServerError().json(serde_json::json!({"status": e.to_string()}))
         );
@@ -414,6 +470,27 @@ pub async fn config_reload() -> Result<HttpResponse, Error> {
     Ok(HttpResponse::Ok().json(serde_json::json!({"status": status})))
 }
 
+#[get("/status")]
+pub async fn config_reload_status() -> Result<HttpResponse, Error> {
+    Ok(HttpResponse::Ok().json(CONFIG_RELOAD_STATUS.read().clone()))
+}
+
+fn refresh_all_config() -> Result<(), anyhow::Error> {
+    config::refresh_config()?;
+    #[cfg(feature = "enterprise")]
+    refresh_o2_config()
+        .and_then(|_| refresh_dex_config())
+        .and_then(|_| refresh_openfga_config())?;
+    Ok(())
+}
+
+fn record_config_reload(ret: &Result<(), anyhow::Error>, reload_at: i64) {
+    let mut status = CONFIG_RELOAD_STATUS.write();
+    status.last_reload_at = reload_at;
+    status.success = ret.is_ok();
+    status.error = ret.as_ref().err().map(|e| e.to_string());
+}
+
 async fn get_stream_schema_status() -> (usize, usize, usize) {
     let mut stream_num = 0;
     let mut stream_schema_num = 0;
@@ -792,7 +869,7 @@ async fn flush_node() -> Result<HttpResponse, Error> {
     crate::common::infra::wal::clean_lock_files();
 
     match ingester::flush_all().await {
-        Ok(_) => Ok(MetaHttpResponse::json(true)),
+        Ok(stat) => Ok(MetaHttpResponse::json(stat)),
         Err(e) => Ok(MetaHttpResponse::internal_error(e)),
     }
 }
@@ -803,8 +880,56 @@ async fn list_node() -> Result<HttpResponse, Error> {
     Ok(MetaHttpResponse::json(nodes))
 }
 
+#[get("/wal_locks")]
+async fn wal_locks() -> Result<HttpResponse, Error> {
+    let files = crate::common::infra::wal::list_locked_files();
+    Ok(MetaHttpResponse::json(files))
+}
+
+#[delete("/wal_locks/{trace_id}")]
+async fn release_wal_locks(path: web::Path<String>) -> Result<HttpResponse, Error> {
+    let trace_id = path.into_inner();
+    match crate::common::infra::wal::force_release_request(&trace_id) {
+        Some(released) => Ok(MetaHttpResponse::json(
+            json::json!({"trace_id": trace_id, "released_files": released}),
+        )),
+        None => Ok(MetaHttpResponse::not_found(format!(
+            "no wal locks held by trace_id {trace_id}"
+        ))),
+    }
+}
+
 #[get("/metrics")]
 async fn node_metrics() -> Result<HttpResponse, Error> {
     let metrics = config::utils::sysinfo::get_node_metrics();
     Ok(MetaHttpResponse::json(metrics))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_config_reload_status() {
+        let app =
+            actix_web::test::init_service(actix_web::App::new().service(config_reload_status))
+                .await;
+
+        record_config_reload(&Ok(()), 1000);
+        let req = actix_web::test::TestRequest::get()
+            .uri("/status")
+            .to_request();
+        let resp: json::Value = actix_web::test::call_and_read_body_json(&app, req).await;
+        assert_eq!(resp, json::json!({"last_reload_at": 1000, "success": true}));
+
+        record_config_reload(&Err(anyhow::anyhow!("invalid ZO_HTTP_PORT")), 2000);
+        let req = actix_web::test::TestRequest::get()
+            .uri("/status")
+            .to_request();
+        let resp: json::Value = actix_web::test::call_and_read_body_json(&app, req).await;
+        assert_eq!(
+            resp,
+            json::json!({"last_reload_at": 2000, "success": false, "error": "invalid ZO_HTTP_PORT"})
+        );
+    }
+}
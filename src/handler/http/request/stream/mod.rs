@@ -20,8 +20,12 @@ use std::{
 
 use actix_web::{delete, get, http, post, put, web, HttpRequest, HttpResponse, Responder};
 use config::{
-    meta::stream::{StreamSettings, StreamType, UpdateStreamSettings},
-    utils::schema::format_stream_name,
+    get_config,
+    meta::{
+        search::SearchEventType,
+        stream::{StreamSettings, StreamType, UpdateStreamSettings},
+    },
+    utils::{json, schema::format_stream_name},
 };
 
 use crate::{
@@ -31,9 +35,9 @@ use crate::{
             http::HttpResponse as MetaHttpResponse,
             stream::{ListStream, StreamDeleteFields},
         },
-        utils::http::get_stream_type_from_request,
+        utils::http::{get_or_create_trace_id, get_stream_type_from_request},
     },
-    service::stream,
+    service::{compact, search as SearchService, stream},
 };
 
 /// GetSchema
@@ -48,6 +52,7 @@ use crate::{
         ("org_id" = String, Path, description = "Organization name"),
         ("stream_name" = String, Path, description = "Stream name"),
         ("type" = String, Query, description = "Stream type"),
+        ("format" = Option<String>, Query, description = "Return the schema as `avro` or `json_schema` instead of the stream details"),
     ),
     responses(
         (status = 200, description = "Success", content_type = "application/json", body = Stream),
@@ -62,7 +67,12 @@ async fn schema(
     let (org_id, stream_name) = path.into_inner();
     let query = web::Query::<HashMap<String, String>>::from_query(req.query_string()).unwrap();
     let stream_type = get_stream_type_from_request(&query).unwrap_or_default();
-    stream::get_stream(&org_id, &stream_name, stream_type).await
+    match query.get("format") {
+        Some(format) => {
+            stream::get_stream_schema_as(&org_id, &stream_name, stream_type, format).await
+        }
+        None => stream::get_stream(&org_id, &stream_name, stream_type).await,
+    }
 }
 
 /// CreateStreamSettings
@@ -198,6 +208,48 @@ async fn update_settings(
     Ok(main_stream_res)
 }
 
+/// Pause or resume the ingestion of a stream
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Streams",
+    operation_id = "StreamIngestionPause",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("stream_name" = String, Path, description = "Stream name"),
+        ("type" = String, Query, description = "Stream type"),
+        ("paused" = bool, Query, description = "true to refuse new records, false to accept them again"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = HttpResponse),
+        (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[put("/{org_id}/streams/{stream_name}/ingestion")]
+async fn pause_ingestion(
+    path: web::Path<(String, String)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let (org_id, mut stream_name) = path.into_inner();
+    if !config::get_config().common.skip_formatting_stream_name {
+        stream_name = format_stream_name(&stream_name);
+    }
+    let query = web::Query::<HashMap<String, String>>::from_query(req.query_string()).unwrap();
+    let stream_type = get_stream_type_from_request(&query).unwrap_or_default();
+    let Some(paused) = query.get("paused").and_then(|v| v.parse::<bool>().ok()) else {
+        return Ok(MetaHttpResponse::bad_request(
+            "paused is required and must be true or false",
+        ));
+    };
+    let settings = UpdateStreamSettings {
+        ingestion_paused: Some(paused),
+        ..Default::default()
+    };
+    stream::update_stream_settings(&org_id, &stream_name, stream_type, settings).await
+}
+
 /// DeleteStreamFields
 #[utoipa::path(
     context_path = "/api",
@@ -257,9 +309,11 @@ async fn delete_fields(
         ("org_id" = String, Path, description = "Organization name"),
         ("stream_name" = String, Path, description = "Stream name"),
         ("type" = String, Query, description = "Stream type"),
+        ("dry_run" = Option<bool>, Query, description = "Only report what would be deleted and the alerts and dashboards using the stream"),
+        ("confirm" = Option<String>, Query, description = "confirm_token of the dry run, required when alerts or dashboards use the stream"),
     ),
     responses(
-        (status = 200, description = "Success", content_type = "application/json", body = HttpResponse),
+        (status = 200, description = "Success, the StreamDeletePlan for a dry run", content_type = "application/json", body = HttpResponse),
         (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
     )
 )]
@@ -271,7 +325,11 @@ async fn delete(
     let (org_id, stream_name) = path.into_inner();
     let query = web::Query::<HashMap<String, String>>::from_query(req.query_string()).unwrap();
     let stream_type = get_stream_type_from_request(&query).unwrap_or_default();
-    stream::delete_stream(&org_id, &stream_name, stream_type).await
+    let dry_run = query
+        .get("dry_run")
+        .is_some_and(|v| v.eq_ignore_ascii_case("true"));
+    let confirm = query.get("confirm").map(|v| v.as_str());
+    stream::delete_stream(&org_id, &stream_name, stream_type, dry_run, confirm).await
 }
 
 /// ListStreams
@@ -285,6 +343,12 @@ async fn delete(
     params(
         ("org_id" = String, Path, description = "Organization name"),
         ("type" = String, Query, description = "Stream type"),
+        ("label" = Option<String>, Query, description = "Only streams with all these labels, comma separated `key:value` or `key`"),
+        ("keyword" = Option<String>, Query, description = "Only streams whose name contains the keyword"),
+        ("sort" = Option<String>, Query, description = "Sort by `name` (default), `size` or `doc_count`"),
+        ("order" = Option<String>, Query, description = "`asc` (default) or `desc`"),
+        ("page_num" = Option<usize>, Query, description = "Page number, starting from 1"),
+        ("page_size" = Option<usize>, Query, description = "Streams per page, all streams when not set"),
     ),
     responses(
         (status = 200, description = "Success", content_type = "application/json", body = ListStream),
@@ -309,6 +373,10 @@ async fn list(org_id: web::Path<String>, req: HttpRequest) -> impl Responder {
         },
         None => false,
     };
+    let list_query = match stream::StreamListQuery::try_from(&query.0) {
+        Ok(v) => v,
+        Err(e) => return Ok(MetaHttpResponse::bad_request(e)),
+    };
     let mut _stream_list_from_rbac = None;
     // Get List of allowed objects
     #[cfg(feature = "enterprise")]
@@ -348,8 +416,11 @@ async fn list(org_id: web::Path<String>, req: HttpRequest) -> impl Responder {
         _stream_list_from_rbac,
     )
     .await;
-    indices.sort_by(|a, b| a.name.cmp(&b.name));
-    Ok(HttpResponse::Ok().json(ListStream { list: indices }))
+    if let Some(labels) = query.get("label") {
+        indices = stream::filter_streams_by_labels(indices, labels);
+    }
+    let (list, total) = list_query.apply(indices);
+    Ok(HttpResponse::Ok().json(ListStream { list, total }))
 }
 
 #[utoipa::path(
@@ -363,6 +434,8 @@ async fn list(org_id: web::Path<String>, req: HttpRequest) -> impl Responder {
         ("org_id" = String, Path, description = "Organization name"),
         ("stream_name" = String, Path, description = "Stream name"),
         ("type" = String, Query, description = "Stream type"),
+        ("start_time" = Option<i64>, Query, description = "Start of the time range to delete, in microseconds"),
+        ("end_time" = Option<i64>, Query, description = "End of the time range to delete, in microseconds, all the cached results when not set"),
     ),
     responses(
         (status = 200, description = "Success", content_type = "application/json", body = HttpResponse),
@@ -383,13 +456,29 @@ async fn delete_stream_cache(
     let (org_id, stream_name) = path.into_inner();
     let query = web::Query::<HashMap<String, String>>::from_query(req.query_string()).unwrap();
     let stream_type = get_stream_type_from_request(&query).unwrap_or_default();
+    let time_range = match query.get("end_time").map(|v| v.parse::<i64>()) {
+        None => None,
+        Some(Ok(end_time)) => {
+            let start_time = query
+                .get("start_time")
+                .and_then(|v| v.parse::<i64>().ok())
+                .unwrap_or_default();
+            if end_time <= 0 || start_time > end_time {
+                return Ok(MetaHttpResponse::bad_request(
+                    "end_time must be positive and not before start_time",
+                ));
+            }
+            Some((start_time, end_time))
+        }
+        Some(Err(e)) => return Ok(MetaHttpResponse::bad_request(e)),
+    };
     let path = if stream_name.eq("_all") {
         org_id
     } else {
         format!("{}/{}/{}", org_id, stream_type, stream_name)
     };
 
-    match crate::service::search::cluster::cacher::delete_cached_results(path).await {
+    match crate::service::search::cluster::cacher::delete_cached_results(path, time_range).await {
         true => Ok(HttpResponse::Ok().json(MetaHttpResponse::message(
             http::StatusCode::OK.into(),
             "cache deleted".to_string(),
@@ -400,3 +489,271 @@ async fn delete_stream_cache(
         ))),
     }
 }
+
+/// Compact a stream now instead of waiting for the scheduled compaction
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Streams",
+    operation_id = "StreamCompact",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("stream_name" = String, Path, description = "Stream name"),
+        ("type" = String, Query, description = "Stream type"),
+        ("start_time" = Option<i64>, Query, description = "Start time in microseconds, the stream creation time when not set"),
+        ("end_time" = Option<i64>, Query, description = "End time in microseconds, now when not set"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = ManualCompactJob),
+        (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
+        (status = 404, description = "NotFound", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[post("/{org_id}/streams/{stream_name}/compact")]
+async fn compact(
+    path: web::Path<(String, String)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let (org_id, stream_name) = path.into_inner();
+    let query = web::Query::<HashMap<String, String>>::from_query(req.query_string()).unwrap();
+    let stream_type = get_stream_type_from_request(&query).unwrap_or_default();
+    let parse_time = |name: &str| query.get(name).map(|v| v.parse::<i64>());
+    let time_range = match (parse_time("start_time"), parse_time("end_time")) {
+        (None, None) => None,
+        (Some(Ok(start)), None) => Some((start, config::utils::time::now_micros())),
+        (Some(Ok(start)), Some(Ok(end))) => Some((start, end)),
+        _ => {
+            return Ok(MetaHttpResponse::bad_request(
+                "start_time is required with end_time and both must be in microseconds",
+            ));
+        }
+    };
+    match compact::manual::compact_stream(&org_id, stream_type, &stream_name, time_range).await {
+        Ok(job) => Ok(MetaHttpResponse::json(job)),
+        Err(e) => Ok(manual_compact_error(e)),
+    }
+}
+
+/// Status of a compaction started with the compact endpoint
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Streams",
+    operation_id = "StreamCompactStatus",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("stream_name" = String, Path, description = "Stream name"),
+        ("job_id" = String, Path, description = "Compaction job id"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = ManualCompactStatus),
+        (status = 404, description = "NotFound", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[get("/{org_id}/streams/{stream_name}/compact/{job_id}")]
+async fn compact_status(path: web::Path<(String, String, String)>) -> Result<HttpResponse, Error> {
+    let (org_id, stream_name, job_id) = path.into_inner();
+    match compact::manual::get_status(&org_id, &job_id).await {
+        Ok(status) if status.stream_name == stream_name => Ok(MetaHttpResponse::json(status)),
+        Ok(_) => Ok(manual_compact_error(
+            compact::manual::ManualCompactError::JobNotFound(job_id),
+        )),
+        Err(e) => Ok(manual_compact_error(e)),
+    }
+}
+
+fn manual_compact_error(e: compact::manual::ManualCompactError) -> HttpResponse {
+    use compact::manual::ManualCompactError;
+    match e {
+        ManualCompactError::StreamNotFound(_) | ManualCompactError::JobNotFound(_) => {
+            MetaHttpResponse::not_found(e)
+        }
+        ManualCompactError::StreamDeleting(_) | ManualCompactError::InvalidTimeRange => {
+            MetaHttpResponse::bad_request(e)
+        }
+        ManualCompactError::Infra(_) | ManualCompactError::Other(_) => {
+            MetaHttpResponse::internal_error(e)
+        }
+    }
+}
+
+/// Sample recent records of a stream
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Streams",
+    operation_id = "StreamSample",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("stream_name" = String, Path, description = "Stream name"),
+        ("type" = String, Query, description = "Stream type"),
+        ("size" = Option<i64>, Query, description = "Number of records, capped by ZO_STREAM_SAMPLE_MAX_SIZE"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = SearchResponse),
+        (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
+        (status = 404, description = "NotFound", content_type = "application/json", body = HttpResponse),
+        (status = 500, description = "Failure", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[get("/{org_id}/streams/{stream_name}/sample")]
+async fn sample(
+    path: web::Path<(String, String)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let cfg = get_config();
+    let (org_id, stream_name) = path.into_inner();
+    let query = web::Query::<HashMap<String, String>>::from_query(req.query_string()).unwrap();
+    let stream_type = get_stream_type_from_request(&query).unwrap_or_default();
+    let user_id = req
+        .headers()
+        .get("user_id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_string();
+    let size = get_sample_size(query.get("size"), cfg.limit.stream_sample_max_size);
+
+    // Check permissions on stream
+    #[cfg(feature = "enterprise")]
+    if let Some(res) = crate::handler::http::request::search::utils::check_stream_permissions(
+        &stream_name,
+        &org_id,
+        &user_id,
+        &stream_type,
+    )
+    .await
+    {
+        return Ok(res);
+    }
+
+    match infra::schema::get(&org_id, &stream_name, stream_type).await {
+        Ok(schema) if schema.fields().is_empty() => {
+            return Ok(MetaHttpResponse::not_found("stream not found"));
+        }
+        Ok(_) => {}
+        Err(e) => return Ok(MetaHttpResponse::internal_error(e)),
+    }
+
+    let stats = infra::cache::stats::get_stream_stats(&org_id, &stream_name, stream_type);
+    let trace_id = get_or_create_trace_id(req.headers(), &tracing::Span::none());
+    let search_req = config::meta::search::Request {
+        query: config::meta::search::Query {
+            sql: sample_sql(&stream_name, &cfg.common.column_timestamp),
+            size,
+            start_time: stats.doc_time_min,
+            end_time: chrono::Utc::now().timestamp_micros(),
+            ..Default::default()
+        },
+        search_type: Some(SearchEventType::Other),
+        use_cache: Some(false),
+        ..Default::default()
+    };
+    match SearchService::search(&trace_id, &org_id, stream_type, Some(user_id), &search_req).await {
+        Ok(mut res) => {
+            res.hits = latest_hits(res.hits, &cfg.common.column_timestamp, size as usize);
+            res.size = res.hits.len() as i64;
+            Ok(HttpResponse::Ok().json(res))
+        }
+        Err(e) => {
+            log::error!("[trace_id {trace_id}] stream sample error: {}", e);
+            Ok(MetaHttpResponse::internal_error(e))
+        }
+    }
+}
+
+/// The query of the most recent records of the stream, the stream name is
+/// quoted so it can't escape the identifier.
+fn sample_sql(stream_name: &str, ts_column: &str) -> String {
+    format!(
+        "SELECT * FROM \"{}\" ORDER BY {ts_column} DESC",
+        stream_name.replace('"', "\"\"")
+    )
+}
+
+/// Number of sample records to return, `size` query param capped by `max`.
+fn get_sample_size(size: Option<&String>, max: i64) -> i64 {
+    size.and_then(|v| v.parse::<i64>().ok())
+        .filter(|v| *v > 0)
+        .map_or(max, |v| v.min(max))
+}
+
+/// Keeps the `n` most recent hits, ordered by timestamp descending.
+fn latest_hits(mut hits: Vec<json::Value>, ts_column: &str, n: usize) -> Vec<json::Value> {
+    let ts = |hit: &json::Value| hit.get(ts_column).and_then(|v| v.as_i64()).unwrap_or(0);
+    hits.sort_by_key(|hit| std::cmp::Reverse(ts(hit)));
+    hits.truncate(n);
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test, App};
+    use infra::schema::{SchemaCache, STREAM_SCHEMAS_LATEST};
+
+    use super::*;
+
+    #[test]
+    fn test_sample_sql() {
+        assert_eq!(
+            sample_sql("app_logs", "_timestamp"),
+            "SELECT * FROM \"app_logs\" ORDER BY _timestamp DESC"
+        );
+        let sql = sample_sql("x\" UNION SELECT * FROM \"secrets", "_timestamp");
+        assert_eq!(
+            sql,
+            "SELECT * FROM \"x\"\" UNION SELECT * FROM \"\"secrets\" ORDER BY _timestamp DESC"
+        );
+        // the whole name is a single table name
+        assert_eq!(
+            config::meta::sql::resolve_stream_names(&sql).unwrap(),
+            vec!["x\" UNION SELECT * FROM \"secrets".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sample_stream_not_found() {
+        let org_id = "test_sample_stream_not_found";
+        STREAM_SCHEMAS_LATEST.write().await.insert(
+            format!("{org_id}/{}/missing", StreamType::Logs),
+            SchemaCache::new(arrow_schema::Schema::empty()),
+        );
+        let app = test::init_service(App::new().service(sample)).await;
+        let req = test::TestRequest::get()
+            .uri(&format!("/{org_id}/streams/missing/sample?type=logs"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_get_sample_size() {
+        assert_eq!(get_sample_size(None, 100), 100);
+        assert_eq!(get_sample_size(Some(&"10".to_string()), 100), 10);
+        assert_eq!(get_sample_size(Some(&"1000".to_string()), 100), 100);
+        assert_eq!(get_sample_size(Some(&"-1".to_string()), 100), 100);
+        assert_eq!(get_sample_size(Some(&"abc".to_string()), 100), 100);
+    }
+
+    #[test]
+    fn test_latest_hits() {
+        let hits = [3, 5, 1, 4, 2]
+            .iter()
+            .map(|ts| json::json!({"_timestamp": ts, "log": format!("line {ts}")}))
+            .collect::<Vec<_>>();
+        let hits = latest_hits(hits, "_timestamp", 3);
+        assert_eq!(hits.len(), 3);
+        let ts = hits
+            .iter()
+            .map(|hit| hit["_timestamp"].as_i64().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ts, vec![5, 4, 3]);
+
+        assert_eq!(latest_hits(vec![], "_timestamp", 3).len(), 0);
+    }
+}
@@ -21,15 +21,22 @@ use infra::errors;
 use serde::Serialize;
 use tracing::{Instrument, Span};
 
+#[cfg(feature = "enterprise")]
+use crate::handler::http::request::search::utils::check_stream_permissions;
 use crate::{
     common::{
         meta::{self, http::HttpResponse as MetaHttpResponse},
         utils::http::get_or_create_trace_id,
     },
-    handler::http::request::{CONTENT_TYPE_JSON, CONTENT_TYPE_PROTO},
+    handler::http::request::{get_content_type, read_body, ContentType},
     service::{search as SearchService, traces},
 };
 
+// Max number of spans scanned to build a service graph
+const SERVICE_GRAPH_MAX_SPANS: usize = 100_000;
+// Max number of trace ids in the IN list of a single query
+const TRACE_IDS_PER_QUERY: usize = 1000;
+
 /// TracesIngest
 #[utoipa::path(
     context_path = "/api",
@@ -48,42 +55,57 @@ use crate::{
 pub async fn traces_write(
     org_id: web::Path<String>,
     req: HttpRequest,
-    body: web::Bytes,
+    payload: web::Payload,
 ) -> Result<HttpResponse, Error> {
-    handle_req(org_id, req, body).await
+    handle_req(org_id, req, payload).await
 }
 
 #[post("/{org_id}/v1/traces")]
 pub async fn otlp_traces_write(
     org_id: web::Path<String>,
     req: HttpRequest,
-    body: web::Bytes,
+    payload: web::Payload,
 ) -> Result<HttpResponse, Error> {
-    handle_req(org_id, req, body).await
+    handle_req(org_id, req, payload).await
 }
 
 async fn handle_req(
     org_id: web::Path<String>,
     req: HttpRequest,
-    body: web::Bytes,
+    payload: web::Payload,
 ) -> Result<HttpResponse, Error> {
     let org_id = org_id.into_inner();
-    let content_type = req.headers().get("Content-Type").unwrap().to_str().unwrap();
-    let in_stream_name = req
-        .headers()
-        .get(&get_config().grpc.stream_header_key)
-        .map(|header| header.to_str().unwrap());
-    if content_type.eq(CONTENT_TYPE_PROTO) {
-        traces::otlp_proto(&org_id, body, in_stream_name).await
-    } else if content_type.starts_with(CONTENT_TYPE_JSON) {
-        traces::otlp_json(&org_id, body, in_stream_name).await
-    } else {
-        Ok(
+    let content_type = match get_content_type(&req) {
+        Ok(v) => v,
+        Err(resp) => return Ok(resp),
+    };
+    let body = match read_body(&req, payload, get_config().limit.req_payload_limit).await {
+        Ok(v) => v,
+        Err(resp) => return Ok(resp),
+    };
+    let in_stream_name = match req.headers().get(&get_config().grpc.stream_header_key) {
+        Some(header) => match header.to_str() {
+            Ok(v) => Some(v),
+            Err(_) => {
+                return Ok(
+                    HttpResponse::BadRequest().json(meta::http::HttpResponse::error(
+                        http::StatusCode::BAD_REQUEST.into(),
+                        "Invalid stream name header".to_string(),
+                    )),
+                );
+            }
+        },
+        None => None,
+    };
+    match content_type {
+        ContentType::Proto => traces::otlp_proto(&org_id, body, in_stream_name).await,
+        ContentType::Json => traces::otlp_json(&org_id, body, in_stream_name).await,
+        ContentType::Other => Ok(
             HttpResponse::BadRequest().json(meta::http::HttpResponse::error(
                 http::StatusCode::BAD_REQUEST.into(),
                 "Bad Request".to_string(),
             )),
-        )
+        ),
     }
 }
 
@@ -104,6 +126,7 @@ async fn handle_req(
         ("start_time" = i64, Query, description = "start time"),
         ("end_time" = i64, Query, description = "end time"),
         ("timeout" = Option<i64>, Query, description = "timeout, seconds"),
+        ("duration_unit" = Option<String>, Query, description = "unit of duration, start_time and end_time in the response: us, ms or ns, default keeps start_time/end_time in ns and duration in us"),
     ),
     responses(
         (status = 200, description = "Success", content_type = "application/json", body = SearchResponse, example = json!({
@@ -206,6 +229,14 @@ pub async fn get_latest_traces(
     let size = query
         .get("size")
         .map_or(10, |v| v.parse::<i64>().unwrap_or(10));
+    let duration_unit = match query
+        .get("duration_unit")
+        .map(|v| v.parse::<DurationUnit>())
+    {
+        Some(Ok(v)) => Some(v),
+        Some(Err(e)) => return Ok(MetaHttpResponse::bad_request(e)),
+        None => None,
+    };
     let mut start_time = query
         .get("start_time")
         .map_or(0, |v| v.parse::<i64>().unwrap_or(0));
@@ -244,13 +275,16 @@ pub async fn get_latest_traces(
         .inc();
     // get a local search queue lock
     #[cfg(not(feature = "enterprise"))]
-    let locker = SearchService::QUEUE_LOCKER.clone();
-    #[cfg(not(feature = "enterprise"))]
-    let locker = locker.lock().await;
-    #[cfg(not(feature = "enterprise"))]
-    if !cfg.common.feature_query_queue_enabled {
-        drop(locker);
-    }
+    let _locker = match SearchService::acquire_queue_permit(&trace_id).await {
+        Ok(permit) => permit,
+        Err(e) => {
+            metrics::QUERY_PENDING_NUMS
+                .with_label_values(&[&org_id])
+                .dec();
+            log::error!("{e}");
+            return Ok(MetaHttpResponse::service_unavailable(e));
+        }
+    };
     #[cfg(not(feature = "enterprise"))]
     let took_wait = start.elapsed().as_millis() as usize;
     #[cfg(feature = "enterprise")]
@@ -289,6 +323,10 @@ pub async fn get_latest_traces(
             skip_wal: false,
             streaming_output: false,
             streaming_id: None,
+            search_after: None,
+            quick_scan: false,
+            segment_ids: None,
+            params: None,
         },
         encoding: config::meta::search::RequestEncoding::Empty,
         regions: vec![],
@@ -383,32 +421,311 @@ pub async fn get_latest_traces(
     let trace_ids = traces_data
         .values()
         .map(|v| v.trace_id.clone())
-        .collect::<Vec<String>>()
-        .join("','");
-    let query_sql = format!(
-        "SELECT {}, trace_id, start_time, end_time, duration, service_name, operation_name, span_status FROM {stream_name} WHERE trace_id IN ('{}') ORDER BY {} ASC",
-        TIMESTAMP_COL_NAME, trace_ids, TIMESTAMP_COL_NAME,
-    );
-    req.query.from = 0;
+        .collect::<Vec<String>>();
     req.query.size = 9999;
-    req.query.sql = query_sql.to_string();
     req.query.start_time = start_time;
     req.query.end_time = end_time;
     let mut traces_service_name: HashMap<String, HashMap<String, u16>> = HashMap::new();
 
+    for in_list in build_trace_id_in_lists(&trace_ids, TRACE_IDS_PER_QUERY) {
+        req.query.from = 0;
+        req.query.sql = format!(
+            "SELECT {}, trace_id, start_time, end_time, duration, service_name, operation_name, span_status FROM {stream_name} WHERE trace_id IN ({}) ORDER BY {} ASC",
+            TIMESTAMP_COL_NAME, in_list, TIMESTAMP_COL_NAME,
+        );
+        loop {
+            let search_res =
+                SearchService::search(&trace_id, &org_id, stream_type, user_id.clone(), &req)
+                    .instrument(http_span.clone())
+                    .await;
+
+            let resp_search = match search_res {
+                Ok(res) => res,
+                Err(err) => {
+                    let time = start.elapsed().as_secs_f64();
+                    metrics::HTTP_RESPONSE_TIME
+                        .with_label_values(&[
+                            "/api/org/traces/latest",
+                            "500",
+                            &org_id,
+                            &stream_name,
+                            stream_type.as_str(),
+                        ])
+                        .observe(time);
+                    metrics::HTTP_INCOMING_REQUESTS
+                        .with_label_values(&[
+                            "/api/org/traces/latest",
+                            "500",
+                            &org_id,
+                            &stream_name,
+                            stream_type.as_str(),
+                        ])
+                        .inc();
+                    log::error!("get traces latest data error: {:?}", err);
+                    return Ok(match err {
+                        errors::Error::ErrorCode(code) => match code {
+                            errors::ErrorCodes::SearchCancelQuery(_) => {
+                                HttpResponse::TooManyRequests()
+                                    .json(meta::http::HttpResponse::error_code(code))
+                            }
+                            _ => HttpResponse::InternalServerError()
+                                .json(meta::http::HttpResponse::error_code(code)),
+                        },
+                        _ => HttpResponse::InternalServerError().json(
+                            meta::http::HttpResponse::error(
+                                http::StatusCode::INTERNAL_SERVER_ERROR.into(),
+                                err.to_string(),
+                            ),
+                        ),
+                    });
+                }
+            };
+
+            let resp_size = resp_search.hits.len() as i64;
+            for item in resp_search.hits {
+                let trace_id = item.get("trace_id").unwrap().as_str().unwrap().to_string();
+                let trace_start_time = json::get_int_value(item.get("start_time").unwrap());
+                let trace_end_time = json::get_int_value(item.get("end_time").unwrap());
+                let duration = json::get_int_value(item.get("duration").unwrap());
+                let service_name = item
+                    .get("service_name")
+                    .unwrap()
+                    .as_str()
+                    .unwrap()
+                    .to_string();
+                let span_status = item
+                    .get("span_status")
+                    .unwrap()
+                    .as_str()
+                    .unwrap()
+                    .to_string();
+                let trace = traces_data.get_mut(&trace_id).unwrap();
+                if trace.first_event.is_null() {
+                    trace.first_event = item.clone();
+                }
+                trace.spans[0] += 1;
+                if span_status.eq("ERROR") {
+                    trace.spans[1] += 1;
+                }
+                if trace.duration < duration {
+                    trace.duration = duration;
+                }
+                if trace.start_time == 0 || trace.start_time > trace_start_time {
+                    trace.start_time = trace_start_time;
+                }
+                if trace.end_time < trace_end_time {
+                    trace.end_time = trace_end_time;
+                }
+                let service_name_map = traces_service_name.entry(trace_id.clone()).or_default();
+                let count = service_name_map.entry(service_name.clone()).or_default();
+                *count += 1;
+            }
+            if resp_size < req.query.size {
+                break;
+            }
+            req.query.from += req.query.size;
+        }
+    }
+
+    // apply service_name to traces_data
+    for (trace_id, service_name_map) in traces_service_name {
+        let trace = traces_data.get_mut(&trace_id).unwrap();
+        for (service_name, count) in service_name_map {
+            trace.service_name.push(TraceServiceNameItem {
+                service_name,
+                count,
+            });
+        }
+    }
+    let mut traces_data = traces_data
+        .into_values()
+        .collect::<Vec<TraceResponseItem>>();
+    traces_data.sort_by(|a, b| b.start_time.cmp(&a.start_time));
+    if let Some(unit) = duration_unit {
+        traces_data
+            .iter_mut()
+            .for_each(|trace| trace.convert_time_unit(unit));
+    }
+
+    let time = start.elapsed().as_secs_f64();
+    metrics::HTTP_RESPONSE_TIME
+        .with_label_values(&[
+            "/api/org/traces/latest",
+            "200",
+            &org_id,
+            &stream_name,
+            stream_type.as_str(),
+        ])
+        .observe(time);
+    metrics::HTTP_INCOMING_REQUESTS
+        .with_label_values(&[
+            "/api/org/traces/latest",
+            "200",
+            &org_id,
+            &stream_name,
+            stream_type.as_str(),
+        ])
+        .inc();
+
+    let mut resp: HashMap<&str, json::Value> = HashMap::new();
+    resp.insert("took", json::Value::from((time * 1000.0) as usize));
+    resp.insert("total", json::Value::from(traces_data.len()));
+    resp.insert("from", json::Value::from(from));
+    resp.insert("size", json::Value::from(size));
+    resp.insert("hits", json::to_value(traces_data).unwrap());
+    resp.insert("trace_id", json::Value::from(trace_id));
+    if !range_error.is_empty() {
+        resp.insert("function_error", json::Value::String(range_error));
+    }
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+/// GetServiceGraph
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Traces",
+    operation_id = "GetServiceGraph",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("stream_name" = String, Path, description = "Stream name"),
+        ("filter" = Option<String>, Query, description = "filter, eg: a=b AND c=d"),
+        ("start_time" = i64, Query, description = "start time"),
+        ("end_time" = i64, Query, description = "end time"),
+        ("timeout" = Option<i64>, Query, description = "timeout, seconds"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = Object, example = json!({
+            "took": 155,
+            "edges": [
+                {
+                    "from": "frontend",
+                    "to": "checkout",
+                    "calls": 20,
+                    "errors": 1
+                }
+            ],
+            "is_partial": false
+        })),
+        (status = 400, description = "Failure", content_type = "application/json", body = HttpResponse),
+        (status = 500, description = "Failure", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[get("/{org_id}/{stream_name}/traces/service_graph")]
+pub async fn service_graph(
+    path: web::Path<(String, String)>,
+    in_req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let start = std::time::Instant::now();
+    let cfg = get_config();
+
+    let (org_id, stream_name) = path.into_inner();
+    let http_span = if cfg.common.tracing_search_enabled {
+        tracing::info_span!(
+            "/api/{org_id}/{stream_name}/traces/service_graph",
+            org_id = org_id.clone(),
+            stream_name = stream_name.clone()
+        )
+    } else {
+        Span::none()
+    };
+    let trace_id = get_or_create_trace_id(in_req.headers(), &http_span);
+    let user_id = match in_req.headers().get("user_id").map(|v| v.to_str()) {
+        Some(Ok(v)) => v.to_string(),
+        _ => return Ok(MetaHttpResponse::bad_request("Invalid user_id header")),
+    };
+    let query = match web::Query::<HashMap<String, String>>::from_query(in_req.query_string()) {
+        Ok(v) => v,
+        Err(e) => return Ok(MetaHttpResponse::bad_request(e)),
+    };
+
+    // Check permissions on stream
+    #[cfg(feature = "enterprise")]
+    if let Some(resp) =
+        check_stream_permissions(&stream_name, &org_id, &user_id, &StreamType::Traces).await
+    {
+        return Ok(resp);
+    }
+
+    let filter = match query.get("filter") {
+        Some(v) => v.to_string(),
+        None => "".to_string(),
+    };
+    let start_time = query
+        .get("start_time")
+        .map_or(0, |v| v.parse::<i64>().unwrap_or(0));
+    if start_time == 0 {
+        return Ok(MetaHttpResponse::bad_request("start_time is empty"));
+    }
+    let end_time = query
+        .get("end_time")
+        .map_or(0, |v| v.parse::<i64>().unwrap_or(0));
+    if end_time == 0 {
+        return Ok(MetaHttpResponse::bad_request("end_time is empty"));
+    }
+    let timeout = query
+        .get("timeout")
+        .map_or(0, |v| v.parse::<i64>().unwrap_or(0));
+
+    let query_sql = format!(
+        "SELECT trace_id, span_id, reference_parent_span_id, service_name, span_status FROM {stream_name}"
+    );
+    let query_sql = if filter.is_empty() {
+        query_sql
+    } else {
+        format!("{query_sql} WHERE {filter}")
+    };
+    // the spans are read page by page, a total order keeps the pages from
+    // overlapping or skipping spans
+    let query_sql = format!("{query_sql} ORDER BY trace_id, span_id");
+    let mut req = config::meta::search::Request {
+        query: config::meta::search::Query {
+            sql: query_sql,
+            from: 0,
+            size: 9999,
+            start_time,
+            end_time,
+            quick_mode: false,
+            query_type: "".to_string(),
+            track_total_hits: false,
+            uses_zo_fn: false,
+            query_fn: None,
+            action_id: None,
+            skip_wal: false,
+            streaming_output: false,
+            streaming_id: None,
+            search_after: None,
+            quick_scan: false,
+            segment_ids: None,
+            params: None,
+        },
+        encoding: config::meta::search::RequestEncoding::Empty,
+        regions: vec![],
+        clusters: vec![],
+        timeout,
+        search_type: None,
+        search_event_context: None,
+        use_cache: None,
+    };
+    let stream_type = StreamType::Traces;
+    let user_id = Some(user_id);
+
+    let mut spans = Vec::new();
+    let mut is_partial = false;
     loop {
         let search_res =
             SearchService::search(&trace_id, &org_id, stream_type, user_id.clone(), &req)
                 .instrument(http_span.clone())
                 .await;
-
         let resp_search = match search_res {
             Ok(res) => res,
             Err(err) => {
                 let time = start.elapsed().as_secs_f64();
                 metrics::HTTP_RESPONSE_TIME
                     .with_label_values(&[
-                        "/api/org/traces/latest",
+                        "/api/org/traces/service_graph",
                         "500",
                         &org_id,
                         &stream_name,
@@ -417,14 +734,14 @@ pub async fn get_latest_traces(
                     .observe(time);
                 metrics::HTTP_INCOMING_REQUESTS
                     .with_label_values(&[
-                        "/api/org/traces/latest",
+                        "/api/org/traces/service_graph",
                         "500",
                         &org_id,
                         &stream_name,
                         stream_type.as_str(),
                     ])
                     .inc();
-                log::error!("get traces latest data error: {:?}", err);
+                log::error!("get traces service graph error: {:?}", err);
                 return Ok(match err {
                     errors::Error::ErrorCode(code) => match code {
                         errors::ErrorCodes::SearchCancelQuery(_) => HttpResponse::TooManyRequests()
@@ -439,69 +756,24 @@ pub async fn get_latest_traces(
                 });
             }
         };
-
         let resp_size = resp_search.hits.len() as i64;
-        for item in resp_search.hits {
-            let trace_id = item.get("trace_id").unwrap().as_str().unwrap().to_string();
-            let trace_start_time = json::get_int_value(item.get("start_time").unwrap());
-            let trace_end_time = json::get_int_value(item.get("end_time").unwrap());
-            let duration = json::get_int_value(item.get("duration").unwrap());
-            let service_name = item
-                .get("service_name")
-                .unwrap()
-                .as_str()
-                .unwrap()
-                .to_string();
-            let span_status = item
-                .get("span_status")
-                .unwrap()
-                .as_str()
-                .unwrap()
-                .to_string();
-            let trace = traces_data.get_mut(&trace_id).unwrap();
-            if trace.first_event.is_null() {
-                trace.first_event = item.clone();
-            }
-            trace.spans[0] += 1;
-            if span_status.eq("ERROR") {
-                trace.spans[1] += 1;
-            }
-            if trace.duration < duration {
-                trace.duration = duration;
-            }
-            if trace.start_time == 0 || trace.start_time > trace_start_time {
-                trace.start_time = trace_start_time;
-            }
-            if trace.end_time < trace_end_time {
-                trace.end_time = trace_end_time;
-            }
-            let service_name_map = traces_service_name.entry(trace_id.clone()).or_default();
-            let count = service_name_map.entry(service_name.clone()).or_default();
-            *count += 1;
-        }
+        spans.extend(resp_search.hits);
         if resp_size < req.query.size {
             break;
         }
+        if spans.len() >= SERVICE_GRAPH_MAX_SPANS {
+            is_partial = true;
+            break;
+        }
         req.query.from += req.query.size;
     }
 
-    // apply service_name to traces_data
-    for (trace_id, service_name_map) in traces_service_name {
-        let trace = traces_data.get_mut(&trace_id).unwrap();
-        for (service_name, count) in service_name_map {
-            trace.service_name.push(TraceServiceNameItem {
-                service_name,
-                count,
-            });
-        }
-    }
-    let mut traces_data = traces_data.values().collect::<Vec<&TraceResponseItem>>();
-    traces_data.sort_by(|a, b| b.start_time.cmp(&a.start_time));
+    let edges = build_service_graph(&spans);
 
     let time = start.elapsed().as_secs_f64();
     metrics::HTTP_RESPONSE_TIME
         .with_label_values(&[
-            "/api/org/traces/latest",
+            "/api/org/traces/service_graph",
             "200",
             &org_id,
             &stream_name,
@@ -510,7 +782,7 @@ pub async fn get_latest_traces(
         .observe(time);
     metrics::HTTP_INCOMING_REQUESTS
         .with_label_values(&[
-            "/api/org/traces/latest",
+            "/api/org/traces/service_graph",
             "200",
             &org_id,
             &stream_name,
@@ -520,17 +792,68 @@ pub async fn get_latest_traces(
 
     let mut resp: HashMap<&str, json::Value> = HashMap::new();
     resp.insert("took", json::Value::from((time * 1000.0) as usize));
-    resp.insert("total", json::Value::from(traces_data.len()));
-    resp.insert("from", json::Value::from(from));
-    resp.insert("size", json::Value::from(size));
-    resp.insert("hits", json::to_value(traces_data).unwrap());
+    resp.insert("edges", json::to_value(edges).unwrap());
+    resp.insert("is_partial", json::Value::from(is_partial));
     resp.insert("trace_id", json::Value::from(trace_id));
-    if !range_error.is_empty() {
-        resp.insert("function_error", json::Value::String(range_error));
-    }
     Ok(HttpResponse::Ok().json(resp))
 }
 
+/// Build the caller -> callee edges between services from a list of spans.
+/// Calls within the same service and spans whose parent is not in the list are skipped.
+fn build_service_graph(spans: &[json::Value]) -> Vec<ServiceGraphEdge> {
+    fn get_str(span: &json::Value, key: &str) -> Option<String> {
+        span.get(key)
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_string())
+    }
+
+    // (trace_id, span_id) -> service_name
+    let mut span_services: HashMap<(String, String), String> = HashMap::with_capacity(spans.len());
+    for span in spans {
+        if let (Some(trace_id), Some(span_id), Some(service_name)) = (
+            get_str(span, "trace_id"),
+            get_str(span, "span_id"),
+            get_str(span, "service_name"),
+        ) {
+            span_services.insert((trace_id, span_id), service_name);
+        }
+    }
+
+    let mut edges: HashMap<(String, String), ServiceGraphEdge> = HashMap::new();
+    for span in spans {
+        let (Some(trace_id), Some(parent_span_id), Some(service_name)) = (
+            get_str(span, "trace_id"),
+            get_str(span, "reference_parent_span_id"),
+            get_str(span, "service_name"),
+        ) else {
+            continue;
+        };
+        let Some(parent_service) = span_services.get(&(trace_id, parent_span_id)) else {
+            continue;
+        };
+        if *parent_service == service_name {
+            continue;
+        }
+        let edge = edges
+            .entry((parent_service.clone(), service_name.clone()))
+            .or_insert_with(|| ServiceGraphEdge {
+                from: parent_service.clone(),
+                to: service_name,
+                calls: 0,
+                errors: 0,
+            });
+        edge.calls += 1;
+        if get_str(span, "span_status").is_some_and(|v| v == "ERROR") {
+            edge.errors += 1;
+        }
+    }
+
+    let mut edges = edges.into_values().collect::<Vec<_>>();
+    edges.sort_by(|a, b| a.from.cmp(&b.from).then_with(|| a.to.cmp(&b.to)));
+    edges
+}
+
 #[derive(Debug, Serialize)]
 struct TraceResponseItem {
     trace_id: String,
@@ -542,8 +865,212 @@ struct TraceResponseItem {
     first_event: serde_json::Value,
 }
 
+impl TraceResponseItem {
+    /// Convert `start_time`/`end_time` (nanoseconds) and `duration` (microseconds) to `unit`
+    fn convert_time_unit(&mut self, unit: DurationUnit) {
+        self.start_time = unit.convert_nanos(self.start_time);
+        self.end_time = unit.convert_nanos(self.end_time);
+        self.duration = unit.convert_micros(self.duration);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DurationUnit {
+    Nanoseconds,
+    Microseconds,
+    Milliseconds,
+}
+
+impl std::str::FromStr for DurationUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ns" => Ok(DurationUnit::Nanoseconds),
+            "us" => Ok(DurationUnit::Microseconds),
+            "ms" => Ok(DurationUnit::Milliseconds),
+            _ => Err(format!(
+                "invalid duration_unit: {s}, supported values are us, ms and ns"
+            )),
+        }
+    }
+}
+
+impl DurationUnit {
+    fn convert_nanos(self, v: i64) -> i64 {
+        match self {
+            DurationUnit::Nanoseconds => v,
+            DurationUnit::Microseconds => v / 1_000,
+            DurationUnit::Milliseconds => v / 1_000_000,
+        }
+    }
+
+    fn convert_micros(self, v: i64) -> i64 {
+        match self {
+            DurationUnit::Nanoseconds => v * 1_000,
+            DurationUnit::Microseconds => v,
+            DurationUnit::Milliseconds => v / 1_000,
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct TraceServiceNameItem {
     service_name: String,
     count: u16,
 }
+
+#[derive(Debug, PartialEq, Serialize)]
+struct ServiceGraphEdge {
+    from: String,
+    to: String,
+    calls: u64,
+    errors: u64,
+}
+
+/// Builds the quoted `IN` lists for the trace ids, at most `chunk_size` ids per list.
+fn build_trace_id_in_lists(trace_ids: &[String], chunk_size: usize) -> Vec<String> {
+    trace_ids
+        .chunks(chunk_size.max(1))
+        .map(|chunk| {
+            chunk
+                .iter()
+                .map(|id| format!("'{}'", id.replace('\'', "''")))
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::App;
+
+    use super::*;
+
+    #[test]
+    fn test_build_trace_id_in_lists() {
+        let ids = vec!["abc".to_string(), "x'); DROP TABLE t; --".to_string()];
+        assert_eq!(
+            build_trace_id_in_lists(&ids, TRACE_IDS_PER_QUERY),
+            vec!["'abc','x''); DROP TABLE t; --'".to_string()]
+        );
+
+        let ids = (0..2500).map(|i| format!("{i:032x}")).collect::<Vec<_>>();
+        let lists = build_trace_id_in_lists(&ids, TRACE_IDS_PER_QUERY);
+        assert_eq!(lists.len(), 3);
+        assert_eq!(lists[0].split(',').count(), 1000);
+        assert_eq!(lists[2].split(',').count(), 500);
+        assert!(lists[2].ends_with(&format!("'{:032x}'", 2499)));
+
+        assert!(build_trace_id_in_lists(&[], TRACE_IDS_PER_QUERY).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_traces_write_without_content_type() {
+        let app = actix_web::test::init_service(
+            App::new().service(traces_write).service(otlp_traces_write),
+        )
+        .await;
+        for uri in ["/default/traces", "/default/v1/traces"] {
+            let req = actix_web::test::TestRequest::post()
+                .uri(uri)
+                .set_payload("{}")
+                .to_request();
+            let resp = actix_web::test::call_service(&app, req).await;
+            assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST, "{uri}");
+        }
+    }
+
+    fn span(
+        trace_id: &str,
+        span_id: &str,
+        parent: &str,
+        service: &str,
+        status: &str,
+    ) -> json::Value {
+        json::json!({
+            "trace_id": trace_id,
+            "span_id": span_id,
+            "reference_parent_span_id": parent,
+            "service_name": service,
+            "span_status": status,
+        })
+    }
+
+    #[test]
+    fn test_build_service_graph() {
+        let spans = vec![
+            span("t1", "a", "", "frontend", "UNSET"),
+            span("t1", "b", "a", "checkout", "UNSET"),
+            span("t1", "c", "b", "payment", "ERROR"),
+            span("t1", "d", "b", "checkout", "UNSET"),
+            span("t2", "a", "", "frontend", "UNSET"),
+            span("t2", "b", "a", "checkout", "ERROR"),
+            // parent not in the result set
+            span("t3", "x", "missing", "payment", "UNSET"),
+        ];
+        let edges = build_service_graph(&spans);
+        assert_eq!(
+            edges,
+            vec![
+                ServiceGraphEdge {
+                    from: "checkout".to_string(),
+                    to: "payment".to_string(),
+                    calls: 1,
+                    errors: 1,
+                },
+                ServiceGraphEdge {
+                    from: "frontend".to_string(),
+                    to: "checkout".to_string(),
+                    calls: 2,
+                    errors: 1,
+                },
+            ]
+        );
+    }
+
+    fn trace_item() -> TraceResponseItem {
+        TraceResponseItem {
+            trace_id: "t1".to_string(),
+            start_time: 1_700_000_000_123_456_789,
+            end_time: 1_700_000_000_125_456_789,
+            duration: 2_000,
+            spans: [1, 0],
+            service_name: vec![],
+            first_event: json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn test_trace_item_convert_time_unit_ms() {
+        let mut item = trace_item();
+        item.convert_time_unit("ms".parse().unwrap());
+        assert_eq!(item.start_time, 1_700_000_000_123);
+        assert_eq!(item.end_time, 1_700_000_000_125);
+        assert_eq!(item.duration, 2);
+        assert_eq!(item.end_time - item.start_time, item.duration);
+    }
+
+    #[test]
+    fn test_trace_item_convert_time_unit_us_ns() {
+        let mut item = trace_item();
+        item.convert_time_unit(DurationUnit::Microseconds);
+        assert_eq!(item.start_time, 1_700_000_000_123_456);
+        assert_eq!(item.duration, 2_000);
+
+        let mut item = trace_item();
+        item.convert_time_unit(DurationUnit::Nanoseconds);
+        assert_eq!(item.start_time, 1_700_000_000_123_456_789);
+        assert_eq!(item.duration, 2_000_000);
+
+        assert!("s".parse::<DurationUnit>().is_err());
+    }
+
+    #[test]
+    fn test_build_service_graph_empty() {
+        assert!(build_service_graph(&[]).is_empty());
+        let spans = vec![span("t1", "a", "", "frontend", "UNSET")];
+        assert!(build_service_graph(&spans).is_empty());
+    }
+}
@@ -40,8 +40,8 @@ use crate::{
         meta::{
             self,
             user::{
-                AuthTokens, RolesResponse, SignInResponse, SignInUser, UpdateUser, UserOrgRole,
-                UserRequest, UserRole,
+                AuthTokens, EffectivePermissionsResponse, RolesResponse, SignInResponse,
+                SignInUser, UpdateUser, UserOrgRole, UserRequest, UserRole,
             },
         },
         utils::auth::{generate_presigned_url, UserEmail},
@@ -618,6 +618,42 @@ pub async fn get_auth(_req: HttpRequest) -> Result<HttpResponse, Error> {
     }
 }
 
+/// UserEffectivePermissions
+#[utoipa::path(
+    context_path = "/api",
+    tag = "Users",
+    operation_id = "UserEffectivePermissions",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("email_id" = String, Path, description = "User's email id"),
+      ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = EffectivePermissionsResponse),
+        (status = 404, description = "NotFound", content_type = "application/json", body = HttpResponse),
+    )
+)]
+#[get("/{org_id}/users/{email_id}/permissions")]
+pub async fn effective_permissions(
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, Error> {
+    let (org_id, email_id) = path.into_inner();
+    let email_id = email_id.trim().to_lowercase();
+    let Some(user) = users::get_user(Some(&org_id), &email_id).await else {
+        return Ok(meta::http::HttpResponse::not_found("User not found"));
+    };
+    match users::get_effective_permissions(&org_id, &user).await {
+        Ok(permissions) => Ok(HttpResponse::Ok().json(EffectivePermissionsResponse {
+            user_id: user.email,
+            role: user.role.to_string(),
+            permissions,
+        })),
+        Err(e) => Ok(meta::http::HttpResponse::internal_error(e)),
+    }
+}
+
 /// ListUsers
 #[utoipa::path(
     context_path = "/api",
@@ -697,4 +733,54 @@ mod tests {
 
         assert!(!response_body.url.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_effective_permissions() {
+        use crate::common::{infra::config::USERS, meta::user::User};
+
+        infra::table::migrate().await.unwrap();
+        let org_id = "test_effective_permissions";
+        USERS.insert(
+            format!("{org_id}/admin@example.com"),
+            User {
+                email: "admin@example.com".to_string(),
+                password: "pass#123".to_string(),
+                role: UserRole::Admin,
+                salt: String::new(),
+                token: "token".to_string(),
+                rum_token: None,
+                first_name: "".to_string(),
+                last_name: "".to_string(),
+                org: org_id.to_string(),
+                is_external: false,
+                password_ext: None,
+            },
+        );
+        let app = test::init_service(App::new().service(effective_permissions)).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/{org_id}/users/Admin@Example.com/permissions"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::OK);
+        let body: EffectivePermissionsResponse = test::read_body_json(resp).await;
+        assert_eq!(body.user_id, "admin@example.com");
+        assert_eq!(body.role, UserRole::Admin.to_string());
+        // without OpenFGA an admin may do everything on every object
+        let actions = body
+            .permissions
+            .iter()
+            .map(|p| (p.object.as_str(), p.action.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            actions,
+            vec![("*", "GET"), ("*", "POST"), ("*", "PUT"), ("*", "DELETE")]
+        );
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/{org_id}/users/nobody@example.com/permissions"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::NOT_FOUND);
+    }
 }
@@ -18,6 +18,7 @@ pub mod session;
 pub mod sort;
 pub mod utils;
 
+use actix_http::ws::{CloseCode, CloseReason};
 use actix_web::{get, web, Error, HttpRequest, HttpResponse};
 use config::get_config;
 use session::WsSession;
@@ -45,20 +46,53 @@ pub async fn websocket(
     let prefix = format!("{}/api/", get_config().common.base_uri);
     let path = req.path().strip_prefix(&prefix).unwrap().to_string();
 
-    let user_id = in_req
-        .headers()
-        .get("user_id")
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("")
-        .to_string();
+    start_session(&in_req, stream, org_id, request_id, path)
+}
+
+/// Opens the websocket session for the authenticated user of the request and
+/// spawns the handler of its messages.
+fn start_session(
+    req: &HttpRequest,
+    stream: web::Payload,
+    org_id: String,
+    request_id: String,
+    path: String,
+) -> Result<HttpResponse, Error> {
+    // searches must always run as the authenticated user of this session
+    let Some(user_id) = get_user_id(req) else {
+        log::warn!(
+            "[WS_HANDLER]: Rejecting websocket request {} without an authenticated user",
+            request_id
+        );
+        return Ok(HttpResponse::Unauthorized().body("Unauthorized Access"));
+    };
 
-    let (res, session, msg_stream) = actix_ws::handle(&req, stream)?;
+    let (res, session, msg_stream) = actix_ws::handle(req, stream)?;
 
-    let ws_session = WsSession::new(session);
-    sessions_cache_utils::insert_session(&request_id, ws_session);
+    let ws_session = WsSession::new(session, &org_id, &user_id);
+    if let Err((reason, mut ws_session)) =
+        sessions_cache_utils::try_insert_session(&request_id, ws_session)
+    {
+        log::warn!(
+            "[WS_HANDLER]: Rejecting websocket request {} for user {} in org {}: {}",
+            request_id,
+            user_id,
+            org_id,
+            reason
+        );
+        actix_web::rt::spawn(async move {
+            let _ = ws_session
+                .close(Some(CloseReason {
+                    code: CloseCode::Policy,
+                    description: Some(reason),
+                }))
+                .await;
+        });
+        return Ok(res);
+    }
     log::info!(
         "[WS_HANDLER]: Node Role: {} Got websocket request for request_id: {}",
-        cfg.common.node_role,
+        get_config().common.node_role,
         request_id,
     );
 
@@ -68,9 +102,93 @@ pub async fn websocket(
     Ok(res)
 }
 
+/// Get the authenticated user id set on the request by the auth middleware
+fn get_user_id(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get("user_id")
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty())
+        .map(|v| v.to_string())
+}
+
 /// Initialize the job init for websocket
 pub async fn init() -> Result<(), anyhow::Error> {
     // Run the garbage collector for websocket sessions
     sessions_cache_utils::run_gc_ws_sessions().await;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test, FromRequest};
+
+    use super::*;
+
+    async fn ws_request(user_id: Option<&str>) -> (HttpRequest, web::Payload) {
+        let mut req = test::TestRequest::get()
+            .insert_header(("upgrade", "websocket"))
+            .insert_header(("connection", "upgrade"))
+            .insert_header(("sec-websocket-version", "13"))
+            .insert_header(("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ=="));
+        if let Some(user_id) = user_id {
+            req = req.insert_header(("user_id", user_id));
+        }
+        let (req, mut pl) = req.to_http_parts();
+        let payload = web::Payload::from_request(&req, &mut pl).await.unwrap();
+        (req, payload)
+    }
+
+    #[actix_web::test]
+    async fn test_start_session_as_authenticated_user() {
+        let (req, payload) = ws_request(Some("user1@example.com")).await;
+        let resp = start_session(
+            &req,
+            payload,
+            "default".to_string(),
+            "ws_session_user".to_string(),
+            "default/ws/ws_session_user".to_string(),
+        )
+        .unwrap();
+        assert_eq!(resp.status(), StatusCode::SWITCHING_PROTOCOLS);
+        let session = sessions_cache_utils::get_mut_session("ws_session_user").unwrap();
+        assert_eq!(session.user_id(), "user1@example.com");
+        drop(session);
+        sessions_cache_utils::remove_session("ws_session_user");
+    }
+
+    #[actix_web::test]
+    async fn test_start_session_without_user() {
+        let (req, payload) = ws_request(None).await;
+        let resp = start_session(
+            &req,
+            payload,
+            "default".to_string(),
+            "ws_session_no_user".to_string(),
+            "default/ws/ws_session_no_user".to_string(),
+        )
+        .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert!(!sessions_cache_utils::contains_session(
+            "ws_session_no_user"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_user_id_uses_session_user() {
+        let req = test::TestRequest::default()
+            .insert_header(("user_id", "user1@example.com"))
+            .to_http_request();
+        assert_eq!(get_user_id(&req), Some("user1@example.com".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_get_user_id_missing_user() {
+        let req = test::TestRequest::default().to_http_request();
+        assert_eq!(get_user_id(&req), None);
+
+        let req = test::TestRequest::default()
+            .insert_header(("user_id", ""))
+            .to_http_request();
+        assert_eq!(get_user_id(&req), None);
+    }
+}
@@ -171,9 +171,15 @@ pub async fn handle_search_request(
     );
     // Step 1: Search result cache
     if req.payload.query.from == 0 {
-        let c_resp =
-            cache::check_cache_v2(&trace_id, org_id, stream_type, &req.payload, req.use_cache)
-                .await?;
+        let c_resp = cache::check_cache_v2(
+            &trace_id,
+            org_id,
+            stream_type,
+            Some(user_id),
+            &req.payload,
+            req.use_cache,
+        )
+        .await?;
         let local_c_resp = c_resp.clone();
         let cached_resp = local_c_resp.cached_response;
         let mut deltas = local_c_resp.deltas;
@@ -324,6 +330,44 @@ async fn do_search(
     res.map(handle_partial_response)
 }
 
+/// The most hits sent over all the responses of a search, -1 for unlimited: the
+/// requested size, or `max_hits` for `size: -1` queries when it isn't 0.
+fn hits_limit(req_size: i64, max_hits: usize) -> i64 {
+    if req_size == -1 && max_hits > 0 {
+        max_hits as i64
+    } else {
+        req_size
+    }
+}
+
+/// Truncate the hits of a `size: -1` response so the responses of the search
+/// hold at most `max_hits` in total, `sent` being the hits of the previous
+/// responses, and mark it as partial. `max_hits` of 0 means unlimited.
+fn cap_unbounded_response(
+    mut res: Response,
+    req_size: i64,
+    max_hits: usize,
+    sent: i64,
+) -> Response {
+    let remaining = (max_hits as i64 - sent).max(0) as usize;
+    if req_size != -1 || max_hits == 0 || res.hits.len() <= remaining {
+        return res;
+    }
+    let msg = format!(
+        "Response truncated to {} hits, please narrow the time range or add a limit",
+        max_hits
+    );
+    res.hits.truncate(remaining);
+    res.size = remaining as i64;
+    res.is_partial = true;
+    res.function_error = if res.function_error.is_empty() {
+        msg
+    } else {
+        format!("{} \n {}", msg, res.function_error)
+    };
+    res
+}
+
 fn handle_partial_response(mut res: Response) -> Response {
     if res.is_partial {
         res.function_error = if res.function_error.is_empty() {
@@ -487,7 +531,8 @@ async fn handle_cache_responses_and_deltas(
         }
 
         // Stop if reached the requested result size
-        if req_size != -1 && curr_res_size >= req_size {
+        let limit = hits_limit(req_size, get_config().websocket.max_unbounded_response_hits);
+        if limit != -1 && curr_res_size >= limit {
             log::info!(
                 "[WS_SEARCH] trace_id: {} Reached requested result size: {}, stopping search",
                 trace_id,
@@ -552,6 +597,8 @@ async fn process_delta(
         partitions.sort_by(|a, b| b[0].cmp(&a[0]));
     }
 
+    let max_hits = get_config().websocket.max_unbounded_response_hits;
+
     for (idx, &[start_time, end_time]) in partitions.iter().enumerate() {
         // Check if the cancellation flag is set
         if let Some(is_cancelled) = search_registry_utils::is_cancelled(&trace_id) {
@@ -574,6 +621,7 @@ async fn process_delta(
 
         // use cache for delta search
         let mut search_res = do_search(&req, org_id, user_id, true).await?;
+        let sent = *curr_res_size;
         *curr_res_size += search_res.hits.len() as i64;
 
         log::info!(
@@ -602,6 +650,7 @@ async fn process_delta(
                     search_res.total = cache_hits;
                 }
             }
+            search_res = cap_unbounded_response(search_res, req_size, max_hits, sent);
 
             // Accumulate the result
             if is_streaming_aggs {
@@ -666,7 +715,8 @@ async fn process_delta(
         }
 
         // Stop if reached the request result size
-        if req_size != -1 && *curr_res_size >= req_size {
+        let limit = hits_limit(req_size, max_hits);
+        if limit != -1 && *curr_res_size >= limit {
             log::info!(
                 "[WS_SEARCH]: Reached requested result size ({}), stopping search",
                 req_size
@@ -683,13 +733,10 @@ async fn process_delta(
     Ok(())
 }
 
-async fn get_partitions(
-    req: &SearchEventReq,
-    org_id: &str,
-    user_id: &str,
-) -> Result<SearchPartitionResponse, Error> {
-    let search_payload = req.payload.clone();
-    let search_partition_req = SearchPartitionRequest {
+/// The `_search_partition` request of a websocket search request.
+fn search_partition_request(req: &SearchEventReq) -> SearchPartitionRequest {
+    let search_payload = &req.payload;
+    SearchPartitionRequest {
         sql: search_payload.query.sql.clone(),
         start_time: search_payload.query.start_time,
         end_time: search_payload.query.end_time,
@@ -699,7 +746,17 @@ async fn get_partitions(
         // vrl is not required for _search_partition
         query_fn: Default::default(),
         streaming_output: true,
-    };
+        partition_strategy: req.partition_strategy,
+        params: search_payload.query.params.clone(),
+    }
+}
+
+async fn get_partitions(
+    req: &SearchEventReq,
+    org_id: &str,
+    user_id: &str,
+) -> Result<SearchPartitionResponse, Error> {
+    let search_partition_req = search_partition_request(req);
 
     let res = SearchService::search_partition(
         &req.trace_id,
@@ -753,6 +810,7 @@ async fn send_cached_responses(
     let mut cached = cached.clone();
 
     // add cache hits to `curr_res_size`
+    let sent = *curr_res_size;
     *curr_res_size += cached.cached_response.hits.len() as i64;
 
     // truncate hits if `curr_res_size` is greater than `req_size`
@@ -767,6 +825,12 @@ async fn send_cached_responses(
     }
 
     cached.cached_response = order_search_results(cached.cached_response, fallback_order_by_col);
+    cached.cached_response = cap_unbounded_response(
+        cached.cached_response,
+        req_size,
+        get_config().websocket.max_unbounded_response_hits,
+        sent,
+    );
 
     // Accumulate the result
     accumulated_results.push(SearchResultType::Cached(cached.cached_response.clone()));
@@ -852,6 +916,7 @@ async fn do_partitioned_search(
     }
 
     let mut curr_res_size = 0;
+    let max_hits = get_config().websocket.max_unbounded_response_hits;
 
     log::info!(
         "[WS_SEARCH] Found {} partitions for trace_id: {}, partitions: {:#?}",
@@ -885,10 +950,12 @@ async fn do_partitioned_search(
 
         // do not use cache for partitioned search without cache
         let mut search_res = do_search(&req, org_id, user_id, false).await?;
+        let sent = curr_res_size;
         curr_res_size += search_res.hits.len() as i64;
 
         if !search_res.hits.is_empty() {
             search_res = order_search_results(search_res, req.fallback_order_by_col);
+            search_res = cap_unbounded_response(search_res, req_size, max_hits, sent);
 
             // check range error
             if !range_error.is_empty() {
@@ -926,7 +993,8 @@ async fn do_partitioned_search(
         }
 
         // Stop if reached the requested result size
-        if req_size != -1 && curr_res_size >= req_size {
+        let limit = hits_limit(req_size, max_hits);
+        if limit != -1 && curr_res_size >= limit {
             log::info!(
                 "[WS_SEARCH]: Reached requested result size ({}), stopping search",
                 req_size
@@ -1073,3 +1141,86 @@ async fn write_results_to_cache(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use config::utils::json;
+
+    use super::*;
+
+    fn aggregate_response(hits: usize) -> Response {
+        Response {
+            hits: (0..hits).map(|i| json::json!({"k": i, "cnt": 1})).collect(),
+            total: hits,
+            size: -1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_search_partition_request_strategy() {
+        let req: SearchEventReq = json::from_value(json::json!({
+            "trace_id": "t1",
+            "payload": {
+                "query": {"sql": "SELECT * FROM logs", "start_time": 0, "end_time": 10}
+            },
+            "time_offset": null,
+            "stream_type": "logs",
+            "use_cache": false,
+            "search_type": "ui",
+            "partition_strategy": "size",
+        }))
+        .unwrap();
+        let partition_req = search_partition_request(&req);
+        assert_eq!(
+            partition_req.partition_strategy,
+            Some(config::meta::search::SearchPartitionStrategy::Size)
+        );
+        assert_eq!(partition_req.sql, "SELECT * FROM logs");
+        assert!(partition_req.streaming_output);
+    }
+
+    #[test]
+    fn test_cap_unbounded_response() {
+        let res = cap_unbounded_response(aggregate_response(100), -1, 10, 0);
+        assert_eq!(res.hits.len(), 10);
+        assert_eq!(res.size, 10);
+        assert!(res.is_partial);
+        assert!(res.function_error.contains("truncated to 10 hits"));
+    }
+
+    #[test]
+    fn test_cap_unbounded_response_merged_total() {
+        // the cap holds over the responses of all the partitions
+        let mut sent = 0;
+        let mut hits = 0;
+        for _ in 0..5 {
+            if sent >= hits_limit(-1, 10) {
+                break;
+            }
+            let res = cap_unbounded_response(aggregate_response(4), -1, 10, sent);
+            sent += 4;
+            hits += res.hits.len();
+        }
+        assert_eq!(hits, 10);
+        assert_eq!(sent, 12);
+
+        // the requested size is the limit of bounded queries
+        assert_eq!(hits_limit(100, 10), 100);
+        assert_eq!(hits_limit(-1, 0), -1);
+    }
+
+    #[test]
+    fn test_cap_unbounded_response_within_limit() {
+        let res = cap_unbounded_response(aggregate_response(10), -1, 10, 0);
+        assert_eq!(res.hits.len(), 10);
+        assert!(!res.is_partial);
+
+        // bounded requests and an unlimited cap are left untouched
+        let res = cap_unbounded_response(aggregate_response(100), 100, 10, 0);
+        assert_eq!(res.hits.len(), 100);
+        let res = cap_unbounded_response(aggregate_response(100), -1, 0, 50);
+        assert_eq!(res.hits.len(), 100);
+        assert!(!res.is_partial);
+    }
+}
@@ -22,7 +22,7 @@ use actix_http::ws::{CloseCode, CloseReason};
 use actix_ws::{MessageStream, Session};
 use config::{
     get_config,
-    meta::websocket::{SearchEventReq, SearchResultType},
+    meta::websocket::{SearchEventReq, SearchResultType, TailEventReq},
 };
 use dashmap::DashMap;
 use futures::StreamExt;
@@ -38,13 +38,16 @@ use tokio::sync::mpsc;
 
 use super::utils::search_registry_utils::SearchState;
 #[cfg(feature = "enterprise")]
-use crate::handler::http::request::websocket::utils::search_registry_utils;
-use crate::handler::http::request::websocket::{
-    search,
-    utils::{sessions_cache_utils, WsClientEvents, WsServerEvents},
-};
+use crate::handler::http::request::websocket::utils::{enterprise_utils, search_registry_utils};
 #[cfg(feature = "enterprise")]
 use crate::service::self_reporting::audit;
+use crate::{
+    handler::http::request::websocket::{
+        search,
+        utils::{sessions_cache_utils, WsClientEvents, WsServerEvents},
+    },
+    service::search::tail,
+};
 
 // Global registry for search requests by `trace_id`
 pub static SEARCH_REGISTRY: Lazy<DashMap<String, SearchState>> = Lazy::new(DashMap::new);
@@ -52,6 +55,8 @@ pub static SEARCH_REGISTRY: Lazy<DashMap<String, SearchState>> = Lazy::new(DashM
 // Do not clone the session, instead use a reference to the session
 pub struct WsSession {
     inner: Option<Session>,
+    org_id: String,
+    user_id: String,
     // Utc timestamp in microseconds
     last_activity_ts: i64,
     // Utc timestamp in microseconds
@@ -60,16 +65,26 @@ pub struct WsSession {
 }
 
 impl WsSession {
-    pub fn new(inner: Session) -> Self {
+    pub fn new(inner: Session, org_id: &str, user_id: &str) -> Self {
         let now = chrono::Utc::now().timestamp_micros();
         Self {
             inner: Some(inner),
+            org_id: org_id.to_string(),
+            user_id: user_id.to_string(),
             last_activity_ts: now,
             created_ts: now,
             message_in_flight: AtomicBool::new(false),
         }
     }
 
+    pub fn org_id(&self) -> &str {
+        &self.org_id
+    }
+
+    pub fn user_id(&self) -> &str {
+        &self.user_id
+    }
+
     pub fn update_activity(&mut self) {
         self.last_activity_ts = chrono::Utc::now().timestamp_micros();
     }
@@ -127,16 +142,69 @@ impl WsSession {
     }
 }
 
+/// Tracks server-initiated pings so half-open connections can be detected
+#[derive(Debug, Default)]
+pub struct Heartbeat {
+    // Utc timestamp in microseconds of the oldest ping not yet answered by a pong
+    awaiting_pong_since: Option<i64>,
+}
+
+impl Heartbeat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a ping sent to the client, keeping the oldest unanswered one
+    pub fn ping_sent(&mut self, now: i64) {
+        if self.awaiting_pong_since.is_none() {
+            self.awaiting_pong_since = Some(now);
+        }
+    }
+
+    /// Record a pong received from the client
+    pub fn pong_received(&mut self) {
+        self.awaiting_pong_since = None;
+    }
+
+    /// Returns true if a ping has been waiting for a pong longer than the timeout
+    pub fn is_timed_out(&self, now: i64, timeout_micros: i64) -> bool {
+        self.awaiting_pong_since
+            .is_some_and(|ts| now - ts > timeout_micros)
+    }
+}
+
 pub async fn run(
-    mut msg_stream: MessageStream,
+    msg_stream: MessageStream,
     user_id: String,
     req_id: String,
     org_id: String,
     path: String,
 ) {
     let cfg = get_config();
-    let mut ping_interval =
-        tokio::time::interval(Duration::from_secs(cfg.websocket.ping_interval_secs as u64));
+    run_with_heartbeat(
+        msg_stream,
+        user_id,
+        req_id,
+        org_id,
+        path,
+        Duration::from_secs(cfg.websocket.ping_interval_secs as u64),
+        Duration::from_secs(cfg.websocket.ping_timeout_secs as u64),
+    )
+    .await
+}
+
+async fn run_with_heartbeat(
+    mut msg_stream: MessageStream,
+    user_id: String,
+    req_id: String,
+    org_id: String,
+    path: String,
+    ping_interval: Duration,
+    ping_timeout: Duration,
+) {
+    let mut ping_interval = tokio::time::interval(ping_interval);
+    let ping_timeout_micros = ping_timeout.as_micros() as i64;
+    let mut heartbeat = Heartbeat::new();
     let mut close_reason: Option<CloseReason> = None;
 
     loop {
@@ -161,6 +229,7 @@ pub async fn run(
                     }
                     Ok(actix_ws::Message::Pong(_)) => {
                         log::debug!("[WS_HANDLER] Received pong from {}", req_id);
+                        heartbeat.pong_received();
                     }
                     Ok(actix_ws::Message::Text(msg)) => {
                         log::info!("[WS_HANDLER]: Request Id: {} Node Role: {} Received message: {}",
@@ -193,11 +262,25 @@ pub async fn run(
             }
             // Heartbeat to keep the connection alive
             _ = ping_interval.tick() => {
+                let now = chrono::Utc::now().timestamp_micros();
+                if heartbeat.is_timed_out(now, ping_timeout_micros) {
+                    log::warn!(
+                        "[WS_HANDLER]: req_id: {} No pong received within {} secs, closing session",
+                        req_id,
+                        ping_timeout.as_secs()
+                    );
+                    close_reason = Some(CloseReason {
+                        code: CloseCode::Away,
+                        description: Some(format!("req_id {} Ping timeout", req_id)),
+                    });
+                    break;
+                }
                 if let Some(mut session) = sessions_cache_utils::get_mut_session(&req_id) {
                     if let Err(e) = session.ping(&[]).await {
                         log::error!("[WS_HANDLER] Failed to send ping: {}", e);
                         break;
                     }
+                    heartbeat.ping_sent(now);
                 }
             }
         }
@@ -292,6 +375,9 @@ pub async fn handle_text_message(
                     });
                     cleanup_and_close_session(req_id, close_reason).await;
                 }
+                WsClientEvents::Tail(tail_req) => {
+                    handle_tail_event(*tail_req, org_id, user_id, req_id).await;
+                }
             }
         }
         Err(e) => {
@@ -370,6 +456,9 @@ pub async fn send_message(req_id: &str, msg: String) -> Result<(), Error> {
 }
 
 async fn cleanup_and_close_session(req_id: &str, close_reason: Option<CloseReason>) {
+    // the client is gone, stop any searches and tails still running for it
+    sessions_cache_utils::cleanup_searches_for_session(req_id);
+
     if let Some(mut session) = sessions_cache_utils::get_mut_session(req_id) {
         if let Some(reason) = close_reason.as_ref() {
             log::info!(
@@ -533,6 +622,88 @@ async fn handle_search_event(
     });
 }
 
+// Live tail handler, pushes the records ingested in the stream until the tail
+// is cancelled or the session goes away
+async fn handle_tail_event(tail_req: TailEventReq, org_id: &str, user_id: &str, req_id: &str) {
+    let trace_id = tail_req.trace_id.clone();
+
+    #[cfg(feature = "enterprise")]
+    if let Err(e) = enterprise_utils::check_permissions(
+        &tail_req.stream_name,
+        tail_req.stream_type,
+        user_id,
+        org_id,
+    )
+    .await
+    {
+        let err_res = WsServerEvents::error_response(
+            Error::Message(e),
+            Some(req_id.to_string()),
+            Some(trace_id),
+        );
+        let _ = send_message(req_id, err_res.to_json().to_string()).await;
+        return;
+    }
+
+    let sql = match tail::tail_sql(
+        org_id,
+        user_id,
+        tail_req.stream_type,
+        &tail_req.stream_name,
+        tail_req.filter.as_deref(),
+    )
+    .await
+    {
+        Ok(sql) => sql,
+        Err(e) => {
+            let err_res =
+                WsServerEvents::error_response(e, Some(req_id.to_string()), Some(trace_id));
+            let _ = send_message(req_id, err_res.to_json().to_string()).await;
+            return;
+        }
+    };
+
+    // registered like a search so it can be cancelled and is cleaned up with
+    // the session
+    let (cancel_tx, mut cancel_rx) = mpsc::channel(1);
+    SEARCH_REGISTRY.insert(
+        trace_id.clone(),
+        SearchState::Running {
+            cancel_tx,
+            req_id: req_id.to_string(),
+        },
+    );
+
+    let query = tail::TailQuery {
+        trace_id: trace_id.clone(),
+        org_id: org_id.to_string(),
+        stream_type: tail_req.stream_type,
+        stream_name: tail_req.stream_name.clone(),
+        sql,
+    };
+    let req_id = req_id.to_string();
+    let cfg = get_config();
+    let interval = Duration::from_millis(cfg.websocket.tail_interval_ms.max(1));
+    let max_hits = cfg.websocket.tail_max_hits;
+    tokio::spawn(async move {
+        let is_alive = || sessions_cache_utils::contains_session(&req_id);
+        let push = |hits| {
+            let (trace_id, req_id) = (trace_id.clone(), req_id.clone());
+            async move {
+                let res = WsServerEvents::TailResponse { trace_id, hits };
+                send_message(&req_id, res.to_json().to_string()).await
+            }
+        };
+        tokio::select! {
+            _ = tail::run(interval, max_hits, query, is_alive, push) => {}
+            _ = cancel_rx.recv() => {
+                log::info!("[WS_HANDLER]: trace_id: {} Tail cancelled", trace_id);
+            }
+        }
+        cleanup_search_resources(&trace_id).await;
+    });
+}
+
 // Cancel handler
 #[cfg(feature = "enterprise")]
 async fn handle_cancel_event(trace_id: &str) -> Result<(), anyhow::Error> {
@@ -604,3 +775,81 @@ async fn cleanup_search_resources(trace_id: &str) {
     SEARCH_REGISTRY.remove(trace_id);
     log::debug!("[WS_HANDLER]: trace_id: {}, Resources cleaned up", trace_id);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heartbeat_pong_received_in_time() {
+        let mut heartbeat = Heartbeat::new();
+        heartbeat.ping_sent(0);
+        assert!(!heartbeat.is_timed_out(10, 20));
+        heartbeat.pong_received();
+        assert!(!heartbeat.is_timed_out(100, 20));
+    }
+
+    #[test]
+    fn test_heartbeat_missed_pong_times_out() {
+        let mut heartbeat = Heartbeat::new();
+        heartbeat.ping_sent(0);
+        // a second ping must not reset the deadline of the unanswered one
+        heartbeat.ping_sent(15);
+        assert!(!heartbeat.is_timed_out(20, 20));
+        assert!(heartbeat.is_timed_out(21, 20));
+    }
+
+    #[actix_web::test]
+    async fn test_run_closes_session_after_missed_pong() {
+        use actix_http::{BoxedPayloadStream, Payload};
+        use actix_web::{test::TestRequest, web, FromRequest};
+
+        let req_id = "test_heartbeat_req_id";
+        let trace_id = "test_heartbeat_trace_id";
+        // a client that never answers, its connection stays open
+        let (req, _) = TestRequest::get()
+            .insert_header(("upgrade", "websocket"))
+            .insert_header(("connection", "upgrade"))
+            .insert_header(("sec-websocket-version", "13"))
+            .insert_header(("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ=="))
+            .to_http_parts();
+        let stream: BoxedPayloadStream = Box::pin(futures::stream::pending());
+        let mut pl = Payload::from(stream);
+        let payload = web::Payload::from_request(&req, &mut pl).await.unwrap();
+        let (_resp, session, msg_stream) = actix_ws::handle(&req, payload).unwrap();
+        assert!(sessions_cache_utils::try_insert_session(
+            req_id,
+            WsSession::new(session, "default", "user@example.com")
+        )
+        .is_ok());
+
+        let (cancel_tx, mut cancel_rx) = mpsc::channel(1);
+        SEARCH_REGISTRY.insert(
+            trace_id.to_string(),
+            SearchState::Running {
+                cancel_tx,
+                req_id: req_id.to_string(),
+            },
+        );
+
+        let ret = tokio::time::timeout(
+            Duration::from_secs(5),
+            run_with_heartbeat(
+                msg_stream,
+                "user@example.com".to_string(),
+                req_id.to_string(),
+                "default".to_string(),
+                "default/ws/test_heartbeat_req_id".to_string(),
+                Duration::from_millis(10),
+                Duration::from_millis(30),
+            ),
+        )
+        .await;
+        assert!(ret.is_ok());
+
+        // the session is dropped and its searches are cancelled
+        assert!(!sessions_cache_utils::contains_session(req_id));
+        assert!(!SEARCH_REGISTRY.contains_key(trace_id));
+        assert!(cancel_rx.try_recv().is_ok());
+    }
+}
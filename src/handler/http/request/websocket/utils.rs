@@ -14,7 +14,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use actix_web::http::StatusCode;
-use config::meta::websocket::SearchEventReq;
+use config::meta::websocket::{SearchEventReq, TailEventReq};
 use infra::{errors, errors::Error};
 use serde::{Deserialize, Serialize};
 
@@ -89,6 +89,8 @@ pub mod sessions_cache_utils {
     use actix_ws::{CloseCode, CloseReason};
     use config::get_config;
     use futures::FutureExt;
+    use once_cell::sync::Lazy;
+    use parking_lot::Mutex;
 
     use super::search_registry_utils::SearchState;
     use crate::{
@@ -161,7 +163,8 @@ pub mod sessions_cache_utils {
         log::info!("[WS_GC] Remaining active sessions: {}", len_sessions());
     }
 
-    fn cleanup_searches_for_session(session_id: &str) {
+    /// Cancel and remove all searches registered for the given session
+    pub fn cleanup_searches_for_session(session_id: &str) {
         let searches_to_remove: Vec<String> = SEARCH_REGISTRY
             .iter()
             .filter_map(|entry| {
@@ -196,9 +199,68 @@ pub mod sessions_cache_utils {
         }
     }
 
-    /// Insert a new session into the cache
-    pub fn insert_session(session_id: &str, session: WsSession) {
+    // Serializes the session limit checks with the inserts, so concurrent
+    // requests can't all pass the check before any of them is inserted
+    static SESSION_ADMISSION: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+    /// Insert a new session into the cache unless it would exceed the configured
+    /// per-user or per-org concurrent session limits, in which case the session
+    /// is handed back with the reason
+    pub fn try_insert_session(
+        session_id: &str,
+        session: WsSession,
+    ) -> Result<(), (String, WsSession)> {
+        let cfg = get_config();
+        try_insert_session_with_limits(
+            session_id,
+            session,
+            cfg.websocket.max_sessions_per_user,
+            cfg.websocket.max_sessions_per_org,
+        )
+    }
+
+    pub(crate) fn try_insert_session_with_limits(
+        session_id: &str,
+        session: WsSession,
+        max_per_user: usize,
+        max_per_org: usize,
+    ) -> Result<(), (String, WsSession)> {
+        let _admission = SESSION_ADMISSION.lock();
+        let (mut org_sessions, mut user_sessions) = (0, 0);
+        for entry in WS_SESSIONS.iter() {
+            if entry.value().org_id() == session.org_id() {
+                org_sessions += 1;
+                if entry.value().user_id() == session.user_id() {
+                    user_sessions += 1;
+                }
+            }
+        }
+        if let Err(reason) = check_limits(user_sessions, org_sessions, max_per_user, max_per_org) {
+            return Err((reason, session));
+        }
         WS_SESSIONS.insert(session_id.to_string(), session);
+        Ok(())
+    }
+
+    pub(crate) fn check_limits(
+        user_sessions: usize,
+        org_sessions: usize,
+        max_per_user: usize,
+        max_per_org: usize,
+    ) -> Result<(), String> {
+        if max_per_user > 0 && user_sessions >= max_per_user {
+            return Err(format!(
+                "Too many concurrent websocket sessions for user, max allowed: {}",
+                max_per_user
+            ));
+        }
+        if max_per_org > 0 && org_sessions >= max_per_org {
+            return Err(format!(
+                "Too many concurrent websocket sessions for org, max allowed: {}",
+                max_per_org
+            ));
+        }
+        Ok(())
     }
 
     /// Remove a session from the cache
@@ -269,6 +331,7 @@ pub mod search_registry_utils {
 /// - `Search`: Represents a request to search for data, containing a trace ID, a query payload, and
 ///   a query type.
 /// - `Cancel`: Represents a request to cancel a previous search, containing a trace ID.
+/// - `Tail`: Represents a request to push the records ingested in a stream as they arrive.
 ///
 /// The `WSQueryPayload` struct is used to encapsulate the details of a search query, including the
 /// SQL query, start time, and end time.
@@ -290,6 +353,7 @@ pub enum WsClientEvents {
     Benchmark {
         id: String,
     },
+    Tail(Box<TailEventReq>),
 }
 
 impl WsClientEvents {
@@ -299,6 +363,7 @@ impl WsClientEvents {
             #[cfg(feature = "enterprise")]
             WsClientEvents::Cancel { .. } => "cancel",
             WsClientEvents::Benchmark { .. } => "benchmark",
+            WsClientEvents::Tail(_) => "tail",
         }
         .to_string()
     }
@@ -343,6 +408,10 @@ pub enum WsServerEvents {
     End {
         trace_id: Option<String>,
     },
+    TailResponse {
+        trace_id: String,
+        hits: Vec<config::utils::json::Value>,
+    },
 }
 
 impl WsServerEvents {
@@ -373,3 +442,92 @@ impl WsServerEvents {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test::TestRequest, web, FromRequest};
+
+    use super::sessions_cache_utils::{
+        check_limits, contains_session, remove_session, try_insert_session_with_limits,
+    };
+    use crate::handler::http::request::websocket::session::WsSession;
+
+    async fn ws_session(org_id: &str, user_id: &str) -> WsSession {
+        let (req, mut pl) = TestRequest::get()
+            .insert_header(("upgrade", "websocket"))
+            .insert_header(("connection", "upgrade"))
+            .insert_header(("sec-websocket-version", "13"))
+            .insert_header(("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ=="))
+            .to_http_parts();
+        let payload = web::Payload::from_request(&req, &mut pl).await.unwrap();
+        let (_, session, _) = actix_ws::handle(&req, payload).unwrap();
+        WsSession::new(session, org_id, user_id)
+    }
+
+    #[tokio::test]
+    async fn test_try_insert_session_concurrently() {
+        let org_id = "test_ws_session_limits";
+        let mut sessions = Vec::new();
+        for i in 0..8 {
+            sessions.push((format!("ws_limit_{i}"), ws_session(org_id, "user").await));
+        }
+
+        // every request checks and inserts at the same time, only 3 may get in
+        let admitted = std::thread::scope(|scope| {
+            let handles = sessions
+                .into_iter()
+                .map(|(session_id, session)| {
+                    scope.spawn(move || {
+                        try_insert_session_with_limits(&session_id, session, 3, 0)
+                            .is_ok()
+                            .then_some(session_id)
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .filter_map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(admitted.len(), 3);
+        assert!(admitted
+            .iter()
+            .all(|session_id| contains_session(session_id)));
+
+        // a closed session frees its slot
+        remove_session(&admitted[0]);
+        let session = ws_session(org_id, "user").await;
+        assert!(try_insert_session_with_limits("ws_limit_next", session, 3, 0).is_ok());
+        let session = ws_session(org_id, "user").await;
+        let (reason, _) =
+            try_insert_session_with_limits("ws_limit_over", session, 3, 0).unwrap_err();
+        assert!(reason.contains("for user"));
+        assert!(!contains_session("ws_limit_over"));
+
+        for session_id in admitted.iter().skip(1) {
+            remove_session(session_id);
+        }
+        remove_session("ws_limit_next");
+    }
+
+    #[test]
+    fn test_check_limits_per_user() {
+        for open_sessions in 0..3 {
+            assert!(check_limits(open_sessions, open_sessions, 3, 0).is_ok());
+        }
+        // past the limit
+        assert!(check_limits(3, 3, 3, 0).is_err());
+        assert!(check_limits(4, 4, 3, 0).is_err());
+        // 0 means unlimited
+        assert!(check_limits(100, 100, 0, 0).is_ok());
+    }
+
+    #[test]
+    fn test_check_limits_per_org() {
+        assert!(check_limits(0, 1, 0, 2).is_ok());
+        assert!(check_limits(0, 2, 0, 2).is_err());
+        // the user limit is checked first
+        let err = check_limits(2, 2, 2, 2).unwrap_err();
+        assert!(err.contains("for user"));
+    }
+}
@@ -16,20 +16,138 @@
 use actix_web::{
     body::MessageBody,
     dev::{ServiceRequest, ServiceResponse},
-    http::{ConnectionType, StatusCode},
+    http::{
+        header::{HeaderName, HeaderValue},
+        ConnectionType, StatusCode,
+    },
 };
 use actix_web_lab::middleware::Next;
+use config::get_config;
 
+/// Closes the connection after error responses or when the client asked for
+/// it, and applies the keep-alive configured for the path in
+/// `ZO_ACTIX_KEEP_ALIVE_ROUTES`. The routes are read on every request so a
+/// config reload applies without a restart.
+///
+/// The server closes idle connections after `ZO_ACTIX_KEEP_ALIVE` whatever the
+/// route, so the route keep-alive can only shorten it and longer values are
+/// capped at it.
 pub async fn check_keep_alive(
     req: ServiceRequest,
     next: Next<impl MessageBody>,
 ) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let cfg = get_config();
+    let routes = cfg.limit.http_keep_alive_routes.clone();
+    keep_alive_by_route(&routes, cfg.limit.http_keep_alive, req, next).await
+}
+
+async fn keep_alive_by_route<B: MessageBody>(
+    routes: &str,
+    server_keep_alive: u64,
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<B>, actix_web::Error> {
     let req_conn_type = req.head().connection_type();
+    let route_keep_alive =
+        route_keep_alive(routes, req.path()).map(|secs| secs.min(server_keep_alive));
     let mut resp = next.call(req).await?;
-    if resp.status() >= StatusCode::BAD_REQUEST || req_conn_type == ConnectionType::Close {
+    if resp.status() >= StatusCode::BAD_REQUEST
+        || req_conn_type == ConnectionType::Close
+        || route_keep_alive == Some(0)
+    {
         resp.response_mut()
             .head_mut()
             .set_connection_type(ConnectionType::Close);
+    } else if let Some(secs) = route_keep_alive {
+        resp.response_mut()
+            .head_mut()
+            .set_connection_type(ConnectionType::KeepAlive);
+        if let Ok(value) = HeaderValue::from_str(&format!("timeout={secs}")) {
+            resp.headers_mut()
+                .insert(HeaderName::from_static("keep-alive"), value);
+        }
     }
     Ok(resp)
 }
+
+/// Returns the keep-alive seconds of the longest `prefix=seconds` entry
+/// matching the path.
+fn route_keep_alive(routes: &str, path: &str) -> Option<u64> {
+    routes
+        .split(',')
+        .filter_map(|route| {
+            let (prefix, secs) = route.trim().split_once('=')?;
+            let secs = secs.trim().parse::<u64>().ok()?;
+            path.starts_with(prefix).then_some((prefix.len(), secs))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, secs)| secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{body::BoxBody, web, App, HttpResponse};
+    use actix_web_lab::middleware::from_fn;
+
+    use super::*;
+
+    const ROUTES: &str = "/api/=0, /api/default/_search_stream=300";
+
+    #[test]
+    fn test_route_keep_alive() {
+        assert_eq!(
+            route_keep_alive(ROUTES, "/api/default/_search_stream"),
+            Some(300)
+        );
+        assert_eq!(route_keep_alive(ROUTES, "/api/default/_search"), Some(0));
+        assert_eq!(route_keep_alive(ROUTES, "/healthz"), None);
+        assert_eq!(route_keep_alive("", "/api/default/_search"), None);
+        assert_eq!(route_keep_alive("/api/=x", "/api/default/_search"), None);
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive_by_route() {
+        let app = actix_web::test::init_service(
+            App::new()
+                .wrap(from_fn(|req: ServiceRequest, next: Next<BoxBody>| {
+                    keep_alive_by_route(ROUTES, 120, req, next)
+                }))
+                .route("/api/{_:.*}", web::get().to(HttpResponse::Ok))
+                .route("/healthz", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let call = |uri: &'static str| {
+            let app = &app;
+            async move {
+                let req = actix_web::test::TestRequest::get().uri(uri).to_request();
+                actix_web::test::call_service(app, req).await
+            }
+        };
+
+        // streaming endpoints keep the connection open
+        let resp = call("/api/default/_search_stream").await;
+        assert_eq!(
+            resp.response().head().connection_type(),
+            ConnectionType::KeepAlive
+        );
+        // capped at the server keep-alive, which closes idle connections anyway
+        assert_eq!(resp.headers().get("keep-alive").unwrap(), "timeout=120");
+
+        // simple json endpoints close it
+        let resp = call("/api/default/_search").await;
+        assert_eq!(
+            resp.response().head().connection_type(),
+            ConnectionType::Close
+        );
+        assert!(resp.headers().get("keep-alive").is_none());
+
+        // other routes keep the server default
+        let resp = call("/healthz").await;
+        assert_ne!(
+            resp.response().head().connection_type(),
+            ConnectionType::Close
+        );
+        assert!(resp.headers().get("keep-alive").is_none());
+    }
+}
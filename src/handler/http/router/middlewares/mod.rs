@@ -14,7 +14,11 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 mod check_keep_alive;
+mod rate_limit;
 mod slow_log;
+mod swagger;
 
 pub use check_keep_alive::check_keep_alive;
+pub use rate_limit::RateLimit;
 pub use slow_log::SlowLog;
+pub use swagger::check_swagger_enabled;
@@ -0,0 +1,320 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{
+    future::{ready, Ready},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{self, header},
+    Error, HttpResponse,
+};
+use config::{get_config, RwHashMap};
+use futures_util::future::LocalBoxFuture;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+use crate::common::meta::http::HttpResponse as MetaHttpResponse;
+
+/// Shared by all http workers so the limit applies per node, not per worker.
+static ORG_RATE_LIMITER: Lazy<Arc<RateLimiter>> = Lazy::new(|| {
+    let cfg = get_config();
+    Arc::new(RateLimiter::new(
+        cfg.limit.http_org_rate_limit,
+        cfg.limit.http_org_rate_limit_burst,
+    ))
+});
+
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// Token bucket rate limiter keyed by organization.
+///
+/// A bucket which has been idle long enough to refill completely behaves
+/// exactly like a new one, so such buckets are dropped to keep the map
+/// bounded by the number of recently active organizations.
+pub struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    buckets: RwHashMap<String, TokenBucket>,
+    last_evicted_at: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(rate: u64, burst: u64) -> Self {
+        let burst = if burst == 0 { rate } else { burst };
+        RateLimiter {
+            rate: rate as f64,
+            burst: burst as f64,
+            buckets: Default::default(),
+            last_evicted_at: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Time after which an untouched bucket is full again.
+    fn idle_timeout(&self) -> Duration {
+        Duration::from_secs_f64(self.burst / self.rate)
+    }
+
+    /// Drops the buckets which have been idle for longer than the time they
+    /// need to refill, at most once per idle timeout.
+    fn evict_idle(&self, now: Instant) {
+        let idle_timeout = self.idle_timeout();
+        {
+            let mut last_evicted_at = self.last_evicted_at.lock();
+            if now.saturating_duration_since(*last_evicted_at) < idle_timeout {
+                return;
+            }
+            *last_evicted_at = now;
+        }
+        self.buckets
+            .retain(|_, bucket| now.saturating_duration_since(bucket.updated_at) < idle_timeout);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.rate > 0.0
+    }
+
+    /// Takes a token from the org's bucket, or returns how long the caller
+    /// should wait before the next token becomes available.
+    pub fn try_acquire(&self, org_id: &str, now: Instant) -> Result<(), Duration> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        self.evict_idle(now);
+        let mut bucket = self
+            .buckets
+            .entry(org_id.to_string())
+            .or_insert_with(|| TokenBucket {
+                tokens: self.burst,
+                updated_at: now,
+            });
+        let elapsed = now.saturating_duration_since(bucket.updated_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * self.rate).min(self.burst);
+        bucket.updated_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate))
+        }
+    }
+}
+
+/// Extracts the organization from paths like `/api/{org_id}/...`,
+/// `/aws/{org_id}/...`, `/gcp/{org_id}/...` and `/rum/v1/{org_id}/...`.
+/// Requests without an organization, like health checks, are not limited.
+///
+/// The middleware must be wrapped inside the authentication middleware of
+/// the scope, so only requests already authorized for this organization
+/// reach a bucket and unauthenticated clients can't drain it.
+fn get_org_id(path: &str, base_uri: &str) -> Option<String> {
+    let path = path.strip_prefix(base_uri).unwrap_or(path);
+    let mut columns = path.split('/').filter(|v| !v.is_empty());
+    let org_id = match columns.next()? {
+        "api" | "aws" | "gcp" => columns.next()?,
+        "rum" => columns.nth(1)?,
+        _ => return None,
+    };
+    Some(org_id.to_string())
+}
+
+pub struct RateLimit {
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimit {
+    pub fn new() -> Self {
+        RateLimit {
+            limiter: ORG_RATE_LIMITER.clone(),
+        }
+    }
+
+    pub fn with_limiter(limiter: Arc<RateLimiter>) -> Self {
+        RateLimit { limiter }
+    }
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RateLimitMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitMiddleware {
+            service,
+            limiter: self.limiter.clone(),
+        }))
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: S,
+    limiter: Arc<RateLimiter>,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if self.limiter.is_enabled() {
+            if let Some(org_id) = get_org_id(req.path(), &get_config().common.base_uri) {
+                if let Err(wait) = self.limiter.try_acquire(&org_id, Instant::now()) {
+                    let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+                    let resp = HttpResponse::TooManyRequests()
+                        .insert_header((header::RETRY_AFTER, retry_after.to_string()))
+                        .json(MetaHttpResponse::error(
+                            http::StatusCode::TOO_MANY_REQUESTS.into(),
+                            format!("Too many requests for organization {org_id}"),
+                        ));
+                    return Box::pin(
+                        async move { Ok(req.into_response(resp).map_into_right_body()) },
+                    );
+                }
+            }
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{web, App};
+
+    use super::*;
+
+    #[test]
+    fn test_get_org_id() {
+        assert_eq!(
+            get_org_id("/api/org1/logs/_json", ""),
+            Some("org1".to_string())
+        );
+        assert_eq!(
+            get_org_id("/o2/aws/org1/logs/_kinesis_firehose", "/o2"),
+            Some("org1".to_string())
+        );
+        assert_eq!(get_org_id("/rum/v1/org1/rum", ""), Some("org1".to_string()));
+        assert_eq!(get_org_id("/healthz", ""), None);
+        assert_eq!(get_org_id("/api", ""), None);
+    }
+
+    #[test]
+    fn test_rate_limiter_refill() {
+        let limiter = RateLimiter::new(2, 0);
+        let now = Instant::now();
+        assert!(limiter.try_acquire("org1", now).is_ok());
+        assert!(limiter.try_acquire("org1", now).is_ok());
+        let wait = limiter.try_acquire("org1", now).unwrap_err();
+        assert_eq!(wait, Duration::from_millis(500));
+        // other orgs have their own bucket
+        assert!(limiter.try_acquire("org2", now).is_ok());
+        assert_eq!(limiter.buckets.len(), 2);
+        assert!(limiter
+            .try_acquire("org1", now + Duration::from_millis(500))
+            .is_ok());
+        assert!(RateLimiter::new(0, 0).try_acquire("org1", now).is_ok());
+    }
+
+    #[test]
+    fn test_rate_limiter_evict_idle() {
+        let limiter = RateLimiter::new(2, 4);
+        let now = Instant::now();
+        assert!(limiter.try_acquire("org1", now).is_ok());
+        assert!(limiter.try_acquire("org2", now).is_ok());
+        assert_eq!(limiter.buckets.len(), 2);
+
+        // org2 stays active while org1 refills completely after 2s
+        let later = now + Duration::from_secs(2);
+        assert!(limiter.try_acquire("org2", later).is_ok());
+        let later = now + Duration::from_secs(3);
+        assert!(limiter.try_acquire("org3", later).is_ok());
+        let mut orgs = limiter
+            .buckets
+            .iter()
+            .map(|v| v.key().clone())
+            .collect::<Vec<_>>();
+        orgs.sort();
+        assert_eq!(orgs, vec!["org2".to_string(), "org3".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_burst() {
+        let limiter = Arc::new(RateLimiter::new(1, 3));
+        let app = actix_web::test::init_service(
+            App::new()
+                .wrap(RateLimit::with_limiter(limiter))
+                .route(
+                    "/api/{org_id}/{stream}/_json",
+                    web::post().to(HttpResponse::Ok),
+                )
+                .route("/healthz", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        // under the limit
+        for _ in 0..3 {
+            let req = actix_web::test::TestRequest::post()
+                .uri("/api/org1/logs/_json")
+                .to_request();
+            let resp = actix_web::test::call_service(&app, req).await;
+            assert!(resp.status().is_success());
+        }
+
+        // over the limit
+        let req = actix_web::test::TestRequest::post()
+            .uri("/api/org1/logs/_json")
+            .to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        assert_eq!(resp.status(), http::StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(resp.headers().get(header::RETRY_AFTER).unwrap(), "1");
+
+        // health checks are exempt
+        let req = actix_web::test::TestRequest::get()
+            .uri("/healthz")
+            .to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright 2024 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    HttpResponse,
+};
+use actix_web_lab::middleware::Next;
+use config::get_config;
+
+/// Hides the swagger UI and the openapi document when `ZO_SWAGGER_ENABLED`
+/// is off. The flag is read on every request so a config reload applies
+/// without a restart.
+pub async fn check_swagger_enabled(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let cfg = get_config();
+    swagger_guard(cfg.common.swagger_enabled, &cfg.common.base_uri, req, next).await
+}
+
+async fn swagger_guard<B: MessageBody>(
+    enabled: bool,
+    base_uri: &str,
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, actix_web::Error> {
+    if !enabled && is_swagger_path(req.path(), base_uri) {
+        return Ok(req
+            .into_response(HttpResponse::NotFound().finish())
+            .map_into_right_body());
+    }
+    next.call(req)
+        .await
+        .map(ServiceResponse::map_into_left_body)
+}
+
+fn is_swagger_path(path: &str, base_uri: &str) -> bool {
+    let path = path.strip_prefix(base_uri).unwrap_or(path);
+    // the first openapi document url already carries the base_uri
+    let path = path.strip_prefix(base_uri).unwrap_or(path);
+    path == "/swagger"
+        || path == "/docs"
+        || path.starts_with("/swagger/")
+        || path.starts_with("/api-doc/")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use actix_web::{body::BoxBody, http::StatusCode, web, App};
+    use actix_web_lab::middleware::from_fn;
+
+    use super::*;
+
+    #[test]
+    fn test_is_swagger_path() {
+        assert!(is_swagger_path("/swagger/", ""));
+        assert!(is_swagger_path("/swagger", ""));
+        assert!(is_swagger_path("/docs", ""));
+        assert!(is_swagger_path("/api-doc/openapi.json", ""));
+        assert!(is_swagger_path("/o2/swagger/index.html", "/o2"));
+        assert!(is_swagger_path("/o2/o2/api-doc/openapi.json", "/o2"));
+        assert!(!is_swagger_path("/api/default/_search", ""));
+        assert!(!is_swagger_path("/docs/other", ""));
+        assert!(!is_swagger_path("/o2/web/", "/o2"));
+    }
+
+    #[tokio::test]
+    async fn test_swagger_toggle() {
+        static ENABLED: AtomicBool = AtomicBool::new(true);
+        let app = actix_web::test::init_service(
+            App::new()
+                .wrap(from_fn(|req: ServiceRequest, next: Next<BoxBody>| {
+                    swagger_guard(ENABLED.load(Ordering::Relaxed), "", req, next)
+                }))
+                .route("/swagger/{_:.*}", web::get().to(HttpResponse::Ok))
+                .route("/healthz", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        for (enabled, status) in [
+            (true, StatusCode::OK),
+            (false, StatusCode::NOT_FOUND),
+            (true, StatusCode::OK),
+        ] {
+            ENABLED.store(enabled, Ordering::Relaxed);
+            let req = actix_web::test::TestRequest::get()
+                .uri("/swagger/index.html")
+                .to_request();
+            let resp = actix_web::test::call_service(&app, req).await;
+            assert_eq!(resp.status(), status);
+
+            // other routes are not affected
+            let req = actix_web::test::TestRequest::get()
+                .uri("/healthz")
+                .to_request();
+            let resp = actix_web::test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+        }
+    }
+}
@@ -199,6 +199,7 @@ pub fn get_basic_routes(svc: &mut web::ServiceConfig) {
     let cors = get_cors();
     svc.service(status::healthz)
         .service(status::healthz_head)
+        .service(status::storage_healthz)
         .service(status::schedulez);
     svc.service(
         web::scope("/auth")
@@ -218,21 +219,22 @@ pub fn get_basic_routes(svc: &mut web::ServiceConfig) {
             .service(status::enable_node)
             .service(status::flush_node)
             .service(status::list_node)
+            .service(status::wal_locks)
+            .service(status::release_wal_locks)
             .service(status::node_metrics),
     );
 
-    if get_config().common.swagger_enabled {
-        svc.service(
-            SwaggerUi::new("/swagger/{_:.*}")
-                .url(
-                    format!("{}/api-doc/openapi.json", get_config().common.base_uri),
-                    openapi::ApiDoc::openapi(),
-                )
-                .url("/api-doc/openapi.json", openapi::ApiDoc::openapi()),
-        );
-        svc.service(web::redirect("/swagger", "/swagger/"));
-        svc.service(web::redirect("/docs", "/swagger/"));
-    }
+    // always registered, `check_swagger_enabled` hides them when swagger is off
+    svc.service(
+        SwaggerUi::new("/swagger/{_:.*}")
+            .url(
+                format!("{}/api-doc/openapi.json", get_config().common.base_uri),
+                openapi::ApiDoc::openapi(),
+            )
+            .url("/api-doc/openapi.json", openapi::ApiDoc::openapi()),
+    );
+    svc.service(web::redirect("/swagger", "/swagger/"));
+    svc.service(web::redirect("/docs", "/swagger/"));
 
     if get_config().common.ui_enabled {
         svc.service(web::redirect("/", "./web/"));
@@ -254,13 +256,9 @@ pub fn get_basic_routes(svc: &mut web::ServiceConfig) {
                         } else {
                             let res = res.unwrap();
                             let req = res.request().clone();
-                            let body = res.into_body();
-                            let body = body.try_into_bytes().unwrap();
-                            let body = String::from_utf8(body.to_vec()).unwrap();
-                            let body = body.replace(
-                                r#"<base href="/" />"#,
-                                &format!(r#"<base href="{prefix}" />"#),
-                            );
+                            let body = ui::get_index_html(&prefix, || {
+                                res.into_body().try_into_bytes().unwrap_or_default()
+                            });
                             Ok(ServiceResponse::new(
                                 req,
                                 HttpResponse::Ok()
@@ -283,7 +281,11 @@ pub fn get_config_routes(svc: &mut web::ServiceConfig) {
             .wrap(cors.clone())
             .service(status::zo_config)
             .service(status::logout)
-            .service(web::scope("/reload").service(status::config_reload)),
+            .service(
+                web::scope("/reload")
+                    .service(status::config_reload)
+                    .service(status::config_reload_status),
+            ),
     );
 }
 
@@ -299,7 +301,11 @@ pub fn get_config_routes(svc: &mut web::ServiceConfig) {
             .service(status::refresh_token_with_dex)
             .service(status::logout)
             .service(users::service_accounts::exchange_token)
-            .service(web::scope("/reload").service(status::config_reload)),
+            .service(
+                web::scope("/reload")
+                    .service(status::config_reload)
+                    .service(status::config_reload_status),
+            ),
     );
 }
 
@@ -316,7 +322,10 @@ pub fn get_service_routes(svc: &mut web::ServiceConfig) {
     #[cfg(not(feature = "enterprise"))]
     let server = cfg.common.instance_name_short.to_string();
 
+    // NOTE: the rate limit is wrapped inside the authentication so that only
+    // authorized requests count against an organization's limit.
     let service = web::scope("/api")
+        .wrap(middlewares::RateLimit::new())
         .wrap(from_fn(audit_middleware))
         .wrap(HttpAuthentication::with_fn(
             super::auth::validator::oo_validator,
@@ -354,16 +363,22 @@ pub fn get_service_routes(svc: &mut web::ServiceConfig) {
         .service(stream::schema)
         .service(stream::settings)
         .service(stream::update_settings)
+        .service(stream::pause_ingestion)
         .service(stream::delete_fields)
         .service(stream::delete)
         .service(stream::list)
+        .service(stream::sample)
+        .service(stream::compact)
+        .service(stream::compact_status)
         .service(logs::ingest::bulk)
         .service(logs::ingest::multi)
         .service(logs::ingest::json)
+        .service(logs::ingest::webhook)
         .service(logs::ingest::otlp_logs_write)
         .service(traces::traces_write)
         .service(traces::otlp_traces_write)
         .service(traces::get_latest_traces)
+        .service(traces::service_graph)
         .service(metrics::ingest::json)
         .service(metrics::ingest::otlp_metrics_write)
         .service(promql::remote_write)
@@ -379,9 +394,12 @@ pub fn get_service_routes(svc: &mut web::ServiceConfig) {
         .service(promql::labels_get)
         .service(promql::labels_post)
         .service(promql::label_values)
+        .service(promql::tsdb_status)
         .service(promql::format_query_get)
         .service(promql::format_query_post)
         .service(enrichment_table::save_enrichment_table)
+        .service(enrichment_table::lookup)
+        .service(enrichment_table::preview)
         .service(search::search)
         .service(search::search_partition)
         .service(search::around)
@@ -392,9 +410,12 @@ pub fn get_service_routes(svc: &mut web::ServiceConfig) {
         .service(search::saved_view::get_view)
         .service(search::saved_view::get_views)
         .service(search::saved_view::delete_view)
+        .service(search::saved_view::delete_views)
         .service(functions::save_function)
         .service(functions::list_functions)
         .service(functions::test_function)
+        .service(functions::export_functions)
+        .service(functions::import_functions)
         .service(functions::delete_function)
         .service(functions::update_function)
         .service(functions::list_pipeline_dependencies)
@@ -404,6 +425,8 @@ pub fn get_service_routes(svc: &mut web::ServiceConfig) {
         .service(dashboards::get_dashboard)
         .service(dashboards::delete_dashboard)
         .service(dashboards::move_dashboard)
+        .service(dashboards::export_all)
+        .service(dashboards::import_all)
         .service(dashboards::reports::create_report)
         .service(dashboards::reports::update_report)
         .service(dashboards::reports::get_report)
@@ -429,6 +452,8 @@ pub fn get_service_routes(svc: &mut web::ServiceConfig) {
         .service(folders::deprecated::get_folder_by_name)
         .service(folders::deprecated::delete_folder)
         .service(alerts::create_alert)
+        .service(alerts::export_config)
+        .service(alerts::import_config)
         .service(alerts::get_alert)
         .service(alerts::update_alert)
         .service(alerts::delete_alert)
@@ -436,6 +461,7 @@ pub fn get_service_routes(svc: &mut web::ServiceConfig) {
         .service(alerts::enable_alert)
         .service(alerts::trigger_alert)
         .service(alerts::move_alerts)
+        .service(alerts::backtest_alert)
         .service(alerts::deprecated::save_alert)
         .service(alerts::deprecated::update_alert)
         .service(alerts::deprecated::get_alert)
@@ -454,6 +480,7 @@ pub fn get_service_routes(svc: &mut web::ServiceConfig) {
         .service(alerts::destinations::get_destination)
         .service(alerts::destinations::list_destinations)
         .service(alerts::destinations::delete_destination)
+        .service(alerts::destinations::test_destination)
         .service(kv::get)
         .service(kv::set)
         .service(kv::delete)
@@ -475,6 +502,7 @@ pub fn get_service_routes(svc: &mut web::ServiceConfig) {
         .service(logs::ingest::handle_gcp_request)
         .service(organization::org::create_org)
         .service(authz::fga::create_role)
+        .service(authz::fga::bulk_assign_role)
         .service(authz::fga::get_roles)
         .service(authz::fga::update_role)
         .service(authz::fga::get_role_permissions)
@@ -486,7 +514,9 @@ pub fn get_service_routes(svc: &mut web::ServiceConfig) {
         .service(authz::fga::get_users_with_role)
         .service(authz::fga::delete_role)
         .service(authz::fga::delete_group)
+        .service(authz::fga::check_permission)
         .service(users::list_roles)
+        .service(users::effective_permissions)
         .service(clusters::list_clusters)
         .service(pipeline::save_pipeline)
         .service(pipeline::update_pipeline)
@@ -494,6 +524,12 @@ pub fn get_service_routes(svc: &mut web::ServiceConfig) {
         .service(pipeline::list_streams_with_pipeline)
         .service(pipeline::delete_pipeline)
         .service(pipeline::enable_pipeline)
+        .service(pipeline::get_drain_status)
+        .service(pipeline::get_pipeline_stats)
+        .service(pipeline::backfill_pipeline)
+        .service(pipeline::get_backfill)
+        .service(pipeline::cancel_backfill)
+        .service(pipeline::get_pipeline_destinations)
         .service(search::multi_streams::search_multi)
         .service(search::multi_streams::_search_partition_multi)
         .service(search::multi_streams::around_multi)
@@ -518,6 +554,7 @@ pub fn get_service_routes(svc: &mut web::ServiceConfig) {
         .service(search::search_job::retry_job)
         .service(search::query_manager::query_status)
         .service(search::query_manager::cancel_multiple_query)
+        .service(search::query_manager::cancel_all_query)
         .service(search::query_manager::cancel_query)
         .service(keys::get)
         .service(keys::delete)
@@ -539,6 +576,7 @@ pub fn get_other_service_routes(svc: &mut web::ServiceConfig) {
     let cors = get_cors();
     svc.service(
         web::scope("/aws")
+            .wrap(middlewares::RateLimit::new())
             .wrap(cors.clone())
             .wrap(HttpAuthentication::with_fn(
                 super::auth::validator::validator_aws,
@@ -548,6 +586,7 @@ pub fn get_other_service_routes(svc: &mut web::ServiceConfig) {
 
     svc.service(
         web::scope("/gcp")
+            .wrap(middlewares::RateLimit::new())
             .wrap(cors.clone())
             .wrap(HttpAuthentication::with_fn(
                 super::auth::validator::validator_gcp,
@@ -560,6 +599,7 @@ pub fn get_other_service_routes(svc: &mut web::ServiceConfig) {
     // https://docs.rs/actix-web/latest/actix_web/middleware/index.html#ordering
     svc.service(
         web::scope("/rum")
+            .wrap(middlewares::RateLimit::new())
             .wrap(cors)
             .wrap(from_fn(RumExtraData::extractor))
             .wrap(HttpAuthentication::with_fn(
@@ -22,11 +22,13 @@ use crate::{common::meta, handler::http::request};
 #[openapi(
     paths(
         request::status::healthz,
+        request::status::storage_healthz,
         request::users::list,
         request::users::save,
         request::users::update,
         request::users::delete,
         request::users::add_user_to_org,
+        request::users::effective_permissions,
         request::organization::org::organizations,
         request::organization::org::org_summary,
         request::organization::org::get_user_passcode,
@@ -40,13 +42,19 @@ use crate::{common::meta, handler::http::request};
         request::stream::schema,
         request::stream::settings,
         request::stream::update_settings,
+        request::stream::pause_ingestion,
         request::stream::delete_fields,
         request::stream::delete,
+        request::stream::sample,
+        request::stream::compact,
+        request::stream::compact_status,
         request::logs::ingest::bulk,
         request::logs::ingest::multi,
         request::logs::ingest::json,
+        request::logs::ingest::webhook,
         request::traces::traces_write,
         request::traces::get_latest_traces,
+        request::traces::service_graph,
         request::metrics::ingest::json,
         request::promql::remote_write,
         request::promql::query_get,
@@ -55,8 +63,11 @@ use crate::{common::meta, handler::http::request};
         request::promql::series_get,
         request::promql::labels_get,
         request::promql::label_values,
+        request::promql::tsdb_status,
         request::promql::format_query_get,
         request::enrichment_table::save_enrichment_table,
+        request::enrichment_table::lookup,
+        request::enrichment_table::preview,
         request::rum::ingest::log,
         request::rum::ingest::data,
         request::rum::ingest::sessionreplay,
@@ -67,6 +78,7 @@ use crate::{common::meta, handler::http::request};
         request::search::search_history,
         request::search::saved_view::create_view,
         request::search::saved_view::delete_view,
+        request::search::saved_view::delete_views,
         request::search::saved_view::get_view,
         request::search::saved_view::get_views,
         request::search::saved_view::update_view,
@@ -88,12 +100,16 @@ use crate::{common::meta, handler::http::request};
         request::functions::delete_function,
         request::functions::list_pipeline_dependencies,
         request::functions::test_function,
+        request::functions::export_functions,
+        request::functions::import_functions,
         request::dashboards::create_dashboard,
         request::dashboards::update_dashboard,
         request::dashboards::list_dashboards,
         request::dashboards::get_dashboard,
         request::dashboards::delete_dashboard,
         request::dashboards::move_dashboard,
+        request::dashboards::export_all,
+        request::dashboards::import_all,
         request::dashboards::timed_annotations::create_annotations,
         request::dashboards::timed_annotations::get_annotations,
         request::dashboards::timed_annotations::delete_annotations,
@@ -115,6 +131,9 @@ use crate::{common::meta, handler::http::request};
         request::alerts::enable_alert,
         request::alerts::trigger_alert,
         request::alerts::move_alerts,
+        request::alerts::backtest_alert,
+        request::alerts::export_config,
+        request::alerts::import_config,
         request::alerts::templates::list_templates,
         request::alerts::templates::get_template,
         request::alerts::templates::save_template,
@@ -125,6 +144,7 @@ use crate::{common::meta, handler::http::request};
         request::alerts::destinations::save_destination,
         request::alerts::destinations::update_destination,
         request::alerts::destinations::delete_destination,
+        request::alerts::destinations::test_destination,
         request::kv::get,
         request::kv::set,
         request::kv::delete,
@@ -144,13 +164,22 @@ use crate::{common::meta, handler::http::request};
             meta::stream::Stream,
             meta::stream::StreamProperty,
             meta::stream::StreamDeleteFields,
+            meta::stream::StreamDeletePlan,
             meta::stream::ListStream,
+            crate::service::compact::manual::ManualCompactJob,
+            crate::service::compact::manual::ManualCompactStatus,
+            crate::service::compact::manual::ManualCompactState,
             config::meta::stream::StreamSettings,
             config::meta::stream::StreamPartition,
             config::meta::stream::StreamPartitionType,
             config::meta::stream::StreamStats,
             config::meta::stream::PartitionTimeLevel,
             config::meta::stream::UpdateStreamSettings,
+            config::meta::stream::WebhookMapping,
+            config::meta::stream::SchemaMode,
+            config::meta::stream::MaskStrategy,
+            config::meta::stream::RelabelAction,
+            config::meta::stream::RelabelRule,
             config::meta::dashboards::Dashboard,
             config::meta::dashboards::v1::AxisItem,
             config::meta::dashboards::v1::Dashboard,
@@ -165,6 +194,7 @@ use crate::{common::meta, handler::http::request};
             config::meta::dashboards::v1::CustomFieldsOption,
             config::meta::dashboards::v1::VariableList,
             config::meta::alerts::alert::Alert,
+            config::meta::alerts::alert::AlertBacktestResponse,
             config::meta::alerts::Aggregation,
             config::meta::alerts::AggFunction,
             config::meta::alerts::Condition,
@@ -175,6 +205,8 @@ use crate::{common::meta, handler::http::request};
             config::meta::alerts::QueryCondition,
             config::meta::alerts::TriggerCondition,
             config::meta::destinations::HTTPType,
+            config::meta::destinations::DestinationTestResponse,
+            config::meta::destinations::TemplateLocale,
             config::meta::timed_annotations::TimedAnnotation,
             config::meta::timed_annotations::TimedAnnotationReq,
             config::meta::timed_annotations::TimedAnnotationDelete,
@@ -196,6 +228,7 @@ use crate::{common::meta, handler::http::request};
             crate::handler::http::models::alerts::requests::CreateAlertRequestBody,
             crate::handler::http::models::alerts::requests::UpdateAlertRequestBody,
             crate::handler::http::models::alerts::requests::MoveAlertsRequestBody,
+            crate::handler::http::models::alerts::requests::BacktestAlertRequestBody,
             crate::handler::http::models::alerts::responses::GetAlertResponseBody,
             crate::handler::http::models::alerts::responses::ListAlertsResponseBody,
             crate::handler::http::models::alerts::responses::ListAlertsResponseBodyItem,
@@ -219,6 +252,10 @@ use crate::{common::meta, handler::http::request};
             crate::handler::http::models::folders::FolderType,
             config::meta::function::Transform,
             config::meta::function::FunctionList,
+            config::meta::function::FunctionBundle,
+            config::meta::function::FunctionImportResponse,
+            config::meta::function::FunctionImportError,
+            config::meta::function::FunctionInUseResponse,
             config::meta::function::StreamOrder,
             config::meta::function::TestVRLRequest,
             config::meta::sql::OrderBy,
@@ -227,6 +264,7 @@ use crate::{common::meta, handler::http::request};
             config::meta::search::RequestEncoding,
             config::meta::search::Response,
             config::meta::search::ResponseTook,
+            config::meta::search::ResponseScanStats,
             config::meta::search::ResponseNodeTook,
             config::meta::search::SearchEventType,
             config::meta::search::SearchEventContext,
@@ -248,6 +286,9 @@ use crate::{common::meta, handler::http::request};
             meta::saved_view::ViewsWithoutData,
             meta::saved_view::CreateViewRequest,
             meta::saved_view::DeleteViewResponse,
+            meta::saved_view::DeleteViewsRequest,
+            meta::saved_view::DeleteViewsResponse,
+            meta::saved_view::DeleteViewResult,
             meta::saved_view::CreateViewResponse,
             meta::saved_view::UpdateViewRequest,
             meta::user::UpdateUser,
@@ -257,6 +298,8 @@ use crate::{common::meta, handler::http::request};
             meta::user::UserList,
             meta::user::UserResponse,
             meta::user::SignInResponse,
+            meta::user::EffectivePermission,
+            meta::user::EffectivePermissionsResponse,
             meta::organization::OrgSummary,
             meta::organization::StreamSummary,
             meta::organization::PipelineSummary,
@@ -268,9 +311,11 @@ use crate::{common::meta, handler::http::request};
             meta::organization::PasscodeResponse,
             meta::organization::OrganizationSetting,
             meta::organization::OrganizationSettingResponse,
+            meta::organization::TracesStreamRoute,
             meta::organization::RumIngestionResponse,
             meta::organization::RumIngestionToken,
             request::status::HealthzResponse,
+            request::status::StorageHealthzResponse,
             meta::ingestion::BulkResponse,
             meta::ingestion::BulkResponseItem,
             meta::ingestion::ShardResponse,
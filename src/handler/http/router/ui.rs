@@ -17,8 +17,14 @@ use actix_web::{route, web};
 use actix_web_rust_embed_responder::{
     Compress, EmbedResponse, EmbedableFileResponse, IntoResponse,
 };
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
 use rust_embed_for_web::RustEmbed;
 
+/// The embedded index.html never changes, so the rewritten copy only depends
+/// on the base href prefix.
+static INDEX_HTML: Lazy<RwLock<Option<(String, web::Bytes)>>> = Lazy::new(Default::default);
+
 #[derive(RustEmbed)]
 #[folder = "web/dist/"]
 #[gzip = false]
@@ -42,12 +48,54 @@ pub async fn serve(path: web::Path<String>) -> EmbedResponse<EmbedableFileRespon
         .use_compression(Compress::Never)
 }
 
+/// Returns index.html with its base href set to `prefix`. The rewrite is done
+/// once and reused until the prefix changes, `body` is only called on a miss.
+pub fn get_index_html(prefix: &str, body: impl FnOnce() -> web::Bytes) -> web::Bytes {
+    if let Some((cached_prefix, html)) = INDEX_HTML.read().as_ref() {
+        if cached_prefix == prefix {
+            return html.clone();
+        }
+    }
+    let html = String::from_utf8_lossy(&body()).replace(
+        r#"<base href="/" />"#,
+        &format!(r#"<base href="{prefix}" />"#),
+    );
+    let html = web::Bytes::from(html);
+    // HEAD and not-modified responses carry no body, don't cache those
+    if !html.is_empty() {
+        *INDEX_HTML.write() = Some((prefix.to_string(), html.clone()));
+    }
+    html
+}
+
 #[cfg(test)]
 mod tests {
+    use std::cell::Cell;
+
     use actix_web::{test, App};
 
     use super::*;
 
+    #[test]
+    fn test_get_index_html() {
+        let calls = Cell::new(0);
+        let body = || {
+            calls.set(calls.get() + 1);
+            web::Bytes::from_static(br#"<head><base href="/" /></head>"#)
+        };
+
+        let html = get_index_html("/ui_test/web/", body);
+        assert_eq!(html, r#"<head><base href="/ui_test/web/" /></head>"#);
+        let again = get_index_html("/ui_test/web/", body);
+        assert_eq!(again, html);
+        assert_eq!(calls.get(), 1);
+
+        // a new base_uri rewrites again
+        let html = get_index_html("/ui_test2/web/", body);
+        assert_eq!(html, r#"<head><base href="/ui_test2/web/" /></head>"#);
+        assert_eq!(calls.get(), 2);
+    }
+
     #[tokio::test]
     async fn test_index_ok() {
         let app = test::init_service(App::new().service(serve)).await;
@@ -37,7 +37,7 @@ use hashbrown::HashMap;
 use once_cell::sync::Lazy;
 use tokio::sync::RwLock;
 
-use super::CacheStrategy;
+use super::{CacheStrategy, StreamCacheStats};
 use crate::{cache::meta::ResultCacheMeta, storage};
 
 static FILES: Lazy<Vec<RwLock<FileData>>> = Lazy::new(|| {
@@ -524,6 +524,12 @@ async fn load(root_dir: &PathBuf, scan_dir: &PathBuf) -> Result<(), anyhow::Erro
                         }
                     };
                     let data_size = meta.len() as usize;
+                    let created_at = meta
+                        .modified()
+                        .ok()
+                        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                        .map(|d| d.as_micros() as i64)
+                        .unwrap_or_else(config::utils::time::now_micros);
                     let mut file_key = fp
                         .strip_prefix(root_dir)
                         .unwrap()
@@ -585,6 +591,7 @@ async fn load(root_dir: &PathBuf, scan_dir: &PathBuf) -> Result<(), anyhow::Erro
                                 end_time: meta[1].parse().unwrap(),
                                 is_aggregate,
                                 is_descending,
+                                created_at,
                             },
                         );
                     } else if file_key.starts_with("metrics_results") {
@@ -669,6 +676,19 @@ pub async fn len(file_type: FileType) -> usize {
     total
 }
 
+pub async fn stream_stats(file_type: FileType) -> HashMap<String, StreamCacheStats> {
+    let files = match file_type {
+        FileType::DATA => &FILES,
+        FileType::RESULT => &RESULT_FILES,
+    };
+    let mut stats = HashMap::new();
+    for file in files.iter() {
+        let r = file.read().await;
+        r.data.stream_stats(&mut stats);
+    }
+    stats
+}
+
 #[inline]
 pub async fn is_empty(file_type: FileType) -> bool {
     let files = match file_type {
@@ -24,10 +24,11 @@ use config::{
     utils::hash::{gxhash, Sum64},
     RwHashMap,
 };
+use hashbrown::HashMap;
 use once_cell::sync::Lazy;
 use tokio::sync::RwLock;
 
-use super::CacheStrategy;
+use super::{CacheStrategy, StreamCacheStats};
 use crate::storage;
 
 static FILES: Lazy<Vec<RwLock<FileData>>> = Lazy::new(|| {
@@ -343,6 +344,15 @@ pub async fn len() -> usize {
     total
 }
 
+pub async fn stream_stats() -> HashMap<String, StreamCacheStats> {
+    let mut stats = HashMap::new();
+    for file in FILES.iter() {
+        let r = file.read().await;
+        r.data.stream_stats(&mut stats);
+    }
+    stats
+}
+
 #[inline]
 pub async fn is_empty() -> bool {
     for file in FILES.iter() {
@@ -18,8 +18,9 @@ pub mod memory;
 
 use std::{collections::VecDeque, ops::Range};
 
-use hashbrown::HashSet;
+use hashbrown::{HashMap, HashSet};
 use hashlink::lru_cache::LruCache;
+use serde::Serialize;
 
 const INITIAL_CACHE_SIZE: usize = 128;
 
@@ -30,6 +31,13 @@ pub enum CacheType {
     None,
 }
 
+/// Cache occupancy of a single stream
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StreamCacheStats {
+    pub files: usize,
+    pub bytes: usize,
+}
+
 enum CacheStrategy {
     Lru(LruCache<String, usize>),
     Fifo((VecDeque<(String, usize)>, HashSet<String>)),
@@ -94,6 +102,25 @@ impl CacheStrategy {
         }
     }
 
+    /// Adds the cached data files to `stats`, key: org_id/stream_type/stream_name
+    fn stream_stats(&self, stats: &mut HashMap<String, StreamCacheStats>) {
+        let mut add = |file: &str, size: usize| {
+            let columns = file.split('/').collect::<Vec<&str>>();
+            if columns.len() < 4 || columns[0] != "files" {
+                return;
+            }
+            let stream = stats
+                .entry(format!("{}/{}/{}", columns[1], columns[2], columns[3]))
+                .or_default();
+            stream.files += 1;
+            stream.bytes += size;
+        };
+        match self {
+            CacheStrategy::Lru(cache) => cache.iter().for_each(|(k, v)| add(k, *v)),
+            CacheStrategy::Fifo((queue, _)) => queue.iter().for_each(|(k, v)| add(k, *v)),
+        }
+    }
+
     fn remove_key(&mut self, key: &str) -> Option<(String, usize)> {
         match self {
             CacheStrategy::Lru(cache) => cache.remove_entry(key),
@@ -231,6 +258,31 @@ mod tests {
         assert!(cache.contains_key(key2));
     }
 
+    #[test]
+    fn test_stream_stats() {
+        for strategy in ["lru", "fifo"] {
+            let mut cache = CacheStrategy::new(strategy);
+            cache.insert("files/org1/logs/s1/2025/01/01/00/1.parquet".to_string(), 10);
+            cache.insert("files/org1/logs/s1/2025/01/01/01/2.parquet".to_string(), 20);
+            cache.insert("files/org1/logs/s2/2025/01/01/00/3.parquet".to_string(), 5);
+            cache.insert("results/org1/logs/s1/1.json".to_string(), 100);
+            let mut stats = HashMap::new();
+            cache.stream_stats(&mut stats);
+            assert_eq!(stats.len(), 2);
+            assert_eq!(
+                stats["org1/logs/s1"],
+                StreamCacheStats {
+                    files: 2,
+                    bytes: 30
+                }
+            );
+            assert_eq!(
+                stats["org1/logs/s2"],
+                StreamCacheStats { files: 1, bytes: 5 }
+            );
+        }
+    }
+
     #[test]
     fn test_fifo_cache_miss() {
         let mut cache = CacheStrategy::new("fifo");
@@ -21,4 +21,19 @@ pub struct ResultCacheMeta {
     pub end_time: i64,
     pub is_aggregate: bool,
     pub is_descending: bool,
+    /// When the results were cached, in microseconds
+    #[serde(default)]
+    pub created_at: i64,
+}
+
+impl ResultCacheMeta {
+    /// Whether the results were cached more than `ttl` seconds before `now`,
+    /// a `ttl` of 0 never expires.
+    pub fn is_expired(&self, ttl: i64, now: i64) -> bool {
+        ttl > 0 && self.created_at + ttl * 1_000_000 < now
+    }
+
+    pub fn overlaps(&self, start_time: i64, end_time: i64) -> bool {
+        self.start_time <= end_time && self.end_time >= start_time
+    }
 }
@@ -71,7 +71,7 @@ impl ObjectStore for CacheFS {
             });
         }
         // default to storage
-        storage::DEFAULT.get(location).await
+        storage::with_retry(&path, || storage::DEFAULT.get(location)).await
     }
 
     async fn get_opts(&self, location: &Path, options: GetOptions) -> Result<GetResult> {
@@ -104,7 +104,10 @@ impl ObjectStore for CacheFS {
             });
         }
         // default to storage
-        storage::DEFAULT.get_opts(location, options).await
+        storage::with_retry(&path, || {
+            storage::DEFAULT.get_opts(location, options.clone())
+        })
+        .await
     }
 
     async fn get_range(&self, location: &Path, range: Range<usize>) -> Result<Bytes> {
@@ -128,7 +131,7 @@ impl ObjectStore for CacheFS {
             });
         }
         // default
-        storage::DEFAULT.head(location).await
+        storage::with_retry(&path, || storage::DEFAULT.head(location)).await
     }
 
     #[tracing::instrument(name = "datafusion::storage::memory::list", skip_all)]
@@ -170,6 +170,8 @@ pub enum DestinationError {
 pub enum TemplateError {
     #[error("error converting template id: {0}")]
     ConvertingId(String),
+    #[error("error converting template locales: {0}")]
+    ConvertingLocales(String),
 }
 
 #[derive(ThisError, Debug)]
@@ -186,6 +188,8 @@ pub enum ErrorCodes {
     SearchCancelQuery(String),
     SearchTimeout(String),
     InvalidParams(String),
+    SearchQueueTimeout(String),
+    SearchNodeShuttingDown(String),
 }
 
 impl From<sea_orm::DbErr> for Error {
@@ -244,6 +248,8 @@ impl ErrorCodes {
             ErrorCodes::SearchCancelQuery(_) => 20009,
             ErrorCodes::SearchTimeout(_) => 20010,
             ErrorCodes::InvalidParams(_) => 20011,
+            ErrorCodes::SearchQueueTimeout(_) => 20012,
+            ErrorCodes::SearchNodeShuttingDown(_) => 20013,
         }
     }
 
@@ -269,6 +275,10 @@ impl ErrorCodes {
             ErrorCodes::SearchCancelQuery(_) => "Search query was cancelled".to_string(),
             ErrorCodes::SearchTimeout(_) => "Search query timed out".to_string(),
             ErrorCodes::InvalidParams(_) => "Invalid parameters".to_string(),
+            ErrorCodes::SearchQueueTimeout(_) => {
+                "Search query timed out waiting in queue".to_string()
+            }
+            ErrorCodes::SearchNodeShuttingDown(_) => "Search node is shutting down".to_string(),
         }
     }
 
@@ -286,6 +296,8 @@ impl ErrorCodes {
             ErrorCodes::SearchCancelQuery(msg) => msg.to_owned(),
             ErrorCodes::SearchTimeout(msg) => msg.to_owned(),
             ErrorCodes::InvalidParams(msg) => msg.to_owned(),
+            ErrorCodes::SearchQueueTimeout(msg) => msg.to_owned(),
+            ErrorCodes::SearchNodeShuttingDown(msg) => msg.to_owned(),
         }
     }
 
@@ -303,6 +315,8 @@ impl ErrorCodes {
             ErrorCodes::SearchCancelQuery(msg) => msg.to_string(),
             ErrorCodes::SearchTimeout(msg) => msg.to_owned(),
             ErrorCodes::InvalidParams(msg) => msg.to_owned(),
+            ErrorCodes::SearchQueueTimeout(msg) => msg.to_owned(),
+            ErrorCodes::SearchNodeShuttingDown(msg) => msg.to_owned(),
         }
     }
 
@@ -352,6 +366,9 @@ impl ErrorCodes {
             20008 => Ok(ErrorCodes::SearchSQLExecuteError(message)),
             20009 => Ok(ErrorCodes::SearchCancelQuery(message)),
             20010 => Ok(ErrorCodes::SearchTimeout(message)),
+            20011 => Ok(ErrorCodes::InvalidParams(message)),
+            20012 => Ok(ErrorCodes::SearchQueueTimeout(message)),
+            20013 => Ok(ErrorCodes::SearchNodeShuttingDown(message)),
             _ => Ok(ErrorCodes::ServerInternalError(json.to_string())),
         }
     }
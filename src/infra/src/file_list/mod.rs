@@ -163,6 +163,7 @@ pub trait FileList: Sync + Send + 'static {
     ) -> Result<i64>;
     async fn get_pending_jobs(&self, node: &str, limit: i64) -> Result<Vec<MergeJobRecord>>;
     async fn get_pending_jobs_count(&self) -> Result<stdHashMap<String, stdHashMap<String, i64>>>;
+    async fn get_jobs_status(&self, ids: &[i64]) -> Result<stdHashMap<i64, FileListJobStatus>>;
     async fn set_job_pending(&self, ids: &[i64]) -> Result<()>;
     async fn set_job_done(&self, ids: &[i64]) -> Result<()>;
     async fn update_running_jobs(&self, id: i64) -> Result<()>;
@@ -427,6 +428,15 @@ pub async fn get_pending_jobs_count() -> Result<stdHashMap<String, stdHashMap<St
     CLIENT.get_pending_jobs_count().await
 }
 
+/// Status of the given merge jobs, done jobs which were cleaned are missing.
+#[inline]
+pub async fn get_jobs_status(ids: &[i64]) -> Result<stdHashMap<i64, FileListJobStatus>> {
+    if ids.is_empty() {
+        return Ok(stdHashMap::new());
+    }
+    CLIENT.get_jobs_status(ids).await
+}
+
 #[inline]
 pub async fn set_job_pending(ids: &[i64]) -> Result<()> {
     CLIENT.set_job_pending(ids).await
@@ -590,11 +600,25 @@ pub enum FileListJobStatus {
     Done,
 }
 
+impl From<i32> for FileListJobStatus {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => FileListJobStatus::Running,
+            2 => FileListJobStatus::Done,
+            _ => FileListJobStatus::Pending,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, sqlx::FromRow)]
 pub struct FileId {
     pub id: i64,
     pub records: i64,
     pub original_size: i64,
     #[sqlx(default)]
+    pub min_ts: i64,
+    #[sqlx(default)]
+    pub max_ts: i64,
+    #[sqlx(default)]
     pub deleted: bool,
 }
@@ -543,7 +543,7 @@ SELECT stream, date, file, deleted, min_ts, max_ts, records, original_size, comp
                 .with_label_values(&["query_ids", "file_list"])
                 .inc();
                     let max_ts_upper_bound = super::calculate_max_ts_upper_bound(time_end, stream_type);
-                    let query = "SELECT id, records, original_size, deleted FROM file_list WHERE stream = ? AND max_ts >= ? AND max_ts <= ? AND min_ts <= ?;";
+                    let query = "SELECT id, records, original_size, min_ts, max_ts, deleted FROM file_list WHERE stream = ? AND max_ts >= ? AND max_ts <= ? AND min_ts <= ?;";
                     sqlx::query_as::<_, super::FileId>(query)
                     .bind(stream_key)
                     .bind(time_start)
@@ -1204,6 +1204,33 @@ SELECT stream, max(id) as id, CAST(COUNT(*) AS SIGNED) AS num
         Ok(ret)
     }
 
+    async fn get_jobs_status(
+        &self,
+        ids: &[i64],
+    ) -> Result<stdHashMap<i64, super::FileListJobStatus>> {
+        let pool = CLIENT.clone();
+        DB_QUERY_NUMS
+            .with_label_values(&["select", "file_list_jobs"])
+            .inc();
+        let sql = format!(
+            "SELECT id, status FROM file_list_jobs WHERE id IN ({});",
+            ids.iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        let ret = sqlx::query(&sql).fetch_all(&pool).await?;
+        Ok(ret
+            .iter()
+            .map(|r| {
+                (
+                    r.get::<i64, &str>("id"),
+                    r.get::<i32, &str>("status").into(),
+                )
+            })
+            .collect())
+    }
+
     async fn set_job_pending(&self, ids: &[i64]) -> Result<()> {
         let pool = CLIENT.clone();
         DB_QUERY_NUMS
@@ -129,3 +129,11 @@ pub async fn drop_table() -> Result<()> {
     CLIENT.drop_table().await?;
     Ok(())
 }
+
+/// Serializes the dead letter stream of the pipeline for the `dead_letter_stream` column.
+fn dead_letter_stream_str(pipeline: &Pipeline) -> Option<String> {
+    pipeline.dead_letter_stream.as_ref().map(|stream| {
+        config::utils::json::to_string(stream)
+            .expect("Serializing pipeline dead letter stream error")
+    })
+}
@@ -63,6 +63,7 @@ CREATE TABLE IF NOT EXISTS pipeline
     derived_stream  TEXT,
     nodes           TEXT,
     edges           TEXT,
+    dead_letter_stream TEXT,
     created_at      TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
             "#,
@@ -70,6 +71,17 @@ CREATE TABLE IF NOT EXISTS pipeline
         .execute(&pool)
         .await?;
 
+        // add dead_letter_stream column for old versions, ignore if it already exists
+        if let Err(e) = sqlx::query(r#"ALTER TABLE pipeline ADD COLUMN dead_letter_stream TEXT;"#)
+            .execute(&pool)
+            .await
+        {
+            if !e.to_string().contains("Duplicate column name") {
+                log::error!("[MYSQL] Unexpected error in adding column: {}", e);
+                return Err(e.into());
+            }
+        }
+
         Ok(())
     }
 
@@ -118,8 +130,8 @@ CREATE TABLE IF NOT EXISTS pipeline
                 );
                 sqlx::query(
                     r#"
-INSERT IGNORE INTO pipeline (id, version, enabled, name, description, org, source_type, stream_org, stream_name, stream_type, nodes, edges)
-    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
+INSERT IGNORE INTO pipeline (id, version, enabled, name, description, org, source_type, stream_org, stream_name, stream_type, nodes, edges, dead_letter_stream)
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
                     "#,
                 )
                 .bind(&pipeline.id)
@@ -134,6 +146,7 @@ INSERT IGNORE INTO pipeline (id, version, enabled, name, description, org, sourc
                 .bind(stream_type)
                 .bind(json::to_string(&pipeline.nodes).expect("Serializing pipeline nodes error"))
                 .bind(json::to_string(&pipeline.edges).expect("Serializing pipeline edges error"))
+                .bind(super::dead_letter_stream_str(pipeline))
                 .execute(&mut *tx)
                 .await
             }
@@ -145,8 +158,8 @@ INSERT IGNORE INTO pipeline (id, version, enabled, name, description, org, sourc
                 );
                 sqlx::query(
                     r#"
-INSERT IGNORE INTO pipeline (id, version, enabled, name, description, org, source_type, derived_stream, nodes, edges)
-    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
+INSERT IGNORE INTO pipeline (id, version, enabled, name, description, org, source_type, derived_stream, nodes, edges, dead_letter_stream)
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
                     "#,
                 )
                 .bind(&pipeline.id)
@@ -159,6 +172,7 @@ INSERT IGNORE INTO pipeline (id, version, enabled, name, description, org, sourc
                 .bind(derived_stream_str)
                 .bind(json::to_string(&pipeline.nodes).expect("Serializing pipeline nodes error"))
                 .bind(json::to_string(&pipeline.edges).expect("Serializing pipeline edges error"))
+                .bind(super::dead_letter_stream_str(pipeline))
                 .execute(&mut *tx)
                 .await
             }
@@ -192,7 +206,7 @@ INSERT IGNORE INTO pipeline (id, version, enabled, name, description, org, sourc
                 sqlx::query(
                     r#"
 UPDATE pipeline
-    SET version = ?, enabled = ?, name = ?, description = ?, org = ?, source_type = ?, stream_org = ?, stream_name = ?, stream_type = ?, nodes = ?, edges = ?
+    SET version = ?, enabled = ?, name = ?, description = ?, org = ?, source_type = ?, stream_org = ?, stream_name = ?, stream_type = ?, nodes = ?, edges = ?, dead_letter_stream = ?
     WHERE id =?;
                     "#,
                 )
@@ -207,6 +221,7 @@ UPDATE pipeline
                 .bind(stream_type)
                 .bind(json::to_string(&pipeline.nodes).expect("Serializing pipeline nodes error"))
                 .bind(json::to_string(&pipeline.edges).expect("Serializing pipeline edges error"))
+                .bind(super::dead_letter_stream_str(pipeline))
                 .bind(&pipeline.id)
                 .execute(&mut *tx)
                 .await
@@ -220,7 +235,7 @@ UPDATE pipeline
                 sqlx::query(
                     r#"
 UPDATE pipeline
-    SET version = ?, enabled = ?, name = ?, description = ?, org = ?, source_type = ?, derived_stream = ?, nodes = ?, edges = ?
+    SET version = ?, enabled = ?, name = ?, description = ?, org = ?, source_type = ?, derived_stream = ?, nodes = ?, edges = ?, dead_letter_stream = ?
     WHERE id = ?;
                     "#,
                 )
@@ -233,6 +248,7 @@ UPDATE pipeline
                 .bind(derived_stream_str)
                 .bind(json::to_string(&pipeline.nodes).expect("Serializing pipeline nodes error"))
                 .bind(json::to_string(&pipeline.edges).expect("Serializing pipeline edges error"))
+                .bind(super::dead_letter_stream_str(pipeline))
                 .bind(&pipeline.id)
                 .execute(&mut *tx)
                 .await
@@ -63,12 +63,23 @@ CREATE TABLE IF NOT EXISTS pipeline
     derived_stream  TEXT,
     nodes           TEXT,
     edges           TEXT,
+    dead_letter_stream TEXT,
     created_at      TIMESTAMP default CURRENT_TIMESTAMP
 );
             "#,
         )
         .execute(&*client)
         .await?;
+
+        // add dead_letter_stream column for old versions, ignore if it already exists
+        if let Err(e) = sqlx::query(r#"ALTER TABLE pipeline ADD COLUMN dead_letter_stream TEXT;"#)
+            .execute(&*client)
+            .await
+        {
+            if !e.to_string().contains("duplicate column name") {
+                return Err(e.into());
+            }
+        }
         Ok(())
     }
 
@@ -110,8 +121,8 @@ CREATE TABLE IF NOT EXISTS pipeline
                 );
                 sqlx::query(
                     r#"
-INSERT INTO pipeline (id, version, enabled, name, description, org, source_type, stream_org, stream_name, stream_type, nodes, edges)
-    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+INSERT INTO pipeline (id, version, enabled, name, description, org, source_type, stream_org, stream_name, stream_type, nodes, edges, dead_letter_stream)
+    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
     ON CONFLICT DO NOTHING;
                     "#,
                 )
@@ -127,6 +138,7 @@ INSERT INTO pipeline (id, version, enabled, name, description, org, source_type,
                 .bind(stream_type)
                 .bind(json::to_string(&pipeline.nodes).expect("Serializing pipeline nodes error"))
                 .bind(json::to_string(&pipeline.edges).expect("Serializing pipeline edges error"))
+                .bind(super::dead_letter_stream_str(pipeline))
                 .execute(&mut *tx)
                 .await
             }
@@ -138,8 +150,8 @@ INSERT INTO pipeline (id, version, enabled, name, description, org, source_type,
                 );
                 sqlx::query(
                     r#"
-INSERT INTO pipeline (id, version, enabled, name, description, org, source_type, derived_stream, nodes, edges)
-    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+INSERT INTO pipeline (id, version, enabled, name, description, org, source_type, derived_stream, nodes, edges, dead_letter_stream)
+    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
     ON CONFLICT DO NOTHING;
                     "#,
                 )
@@ -153,6 +165,7 @@ INSERT INTO pipeline (id, version, enabled, name, description, org, source_type,
                 .bind(derived_stream_str)
                 .bind(json::to_string(&pipeline.nodes).expect("Serializing pipeline nodes error"))
                 .bind(json::to_string(&pipeline.edges).expect("Serializing pipeline edges error"))
+                .bind(super::dead_letter_stream_str(pipeline))
                 .execute(&mut *tx)
                 .await
             }
@@ -189,8 +202,8 @@ INSERT INTO pipeline (id, version, enabled, name, description, org, source_type,
                 sqlx::query(
                     r#"
 UPDATE pipeline
-    SET version = $1, enabled = $2, name = $3, description = $4, org = $5, source_type = $6, stream_org = $7, stream_name = $8, stream_type = $9, nodes = $10, edges = $11
-    WHERE id = $12;
+    SET version = $1, enabled = $2, name = $3, description = $4, org = $5, source_type = $6, stream_org = $7, stream_name = $8, stream_type = $9, nodes = $10, edges = $11, dead_letter_stream = $12
+    WHERE id = $13;
                     "#,
                 )
                 .bind(pipeline.version)
@@ -204,6 +217,7 @@ UPDATE pipeline
                 .bind(stream_type)
                 .bind(json::to_string(&pipeline.nodes).expect("Serializing pipeline nodes error"))
                 .bind(json::to_string(&pipeline.edges).expect("Serializing pipeline edges error"))
+                .bind(super::dead_letter_stream_str(pipeline))
                 .bind(&pipeline.id)
                 .execute(&mut *tx)
                 .await
@@ -217,8 +231,8 @@ UPDATE pipeline
                 sqlx::query(
                     r#"
 UPDATE pipeline
-    SET version = $1, enabled = $2, name = $3, description = $4, org = $5, source_type = $6, derived_stream = $7, nodes = $8, edges = $9
-    WHERE id = $10;
+    SET version = $1, enabled = $2, name = $3, description = $4, org = $5, source_type = $6, derived_stream = $7, nodes = $8, edges = $9, dead_letter_stream = $10
+    WHERE id = $11;
                     "#,
                 )
                 .bind(pipeline.version)
@@ -230,6 +244,7 @@ UPDATE pipeline
                 .bind(derived_stream_str)
                 .bind(json::to_string(&pipeline.nodes).expect("Serializing pipeline nodes error"))
                 .bind(json::to_string(&pipeline.edges).expect("Serializing pipeline edges error"))
+                .bind(super::dead_letter_stream_str(pipeline))
                 .bind(&pipeline.id)
                 .execute(&mut *tx)
                 .await
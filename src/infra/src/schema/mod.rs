@@ -316,6 +316,19 @@ pub fn get_stream_setting_fts_fields(settings: &Option<StreamSettings>) -> Vec<S
     }
 }
 
+/// Characters splitting the full text search fields into inverted index
+/// terms, the same ones must be used to build the index and to search it.
+pub fn get_stream_setting_split_chars(settings: &Option<StreamSettings>) -> String {
+    match settings
+        .as_ref()
+        .and_then(|s| s.inverted_index_split_chars.as_ref())
+    {
+        Some(chars) => chars.clone(),
+        #[allow(deprecated)]
+        None => get_config().common.inverted_index_split_chars.clone(),
+    }
+}
+
 pub fn get_stream_setting_index_fields(settings: &Option<StreamSettings>) -> Vec<String> {
     let default_fields = SQL_SECONDARY_INDEX_SEARCH_FIELDS.clone();
     match settings {
@@ -810,4 +823,28 @@ mod tests {
         let res = get_stream_setting_fts_fields(&settings);
         assert!(!res.is_empty());
     }
+
+    #[test]
+    fn test_get_stream_setting_split_chars() {
+        use config::utils::inverted_index::split_token;
+
+        #[allow(deprecated)]
+        let global = get_config().common.inverted_index_split_chars.clone();
+        assert_eq!(get_stream_setting_split_chars(&None), global);
+        let mut settings = StreamSettings::default();
+        assert_eq!(
+            get_stream_setting_split_chars(&Some(settings.clone())),
+            global
+        );
+        settings.inverted_index_split_chars = Some("/".to_string());
+        let split_chars = get_stream_setting_split_chars(&Some(settings));
+        assert_eq!(split_chars, "/");
+
+        // the stream keeps the query string in the path term
+        let url = "/api/v1/users?id=42";
+        let tokens = split_token(url, &split_chars);
+        assert!(tokens.contains(&"users?id=42".to_string()));
+        assert!(!tokens.contains(&"users".to_string()));
+        assert!(split_token(url, "").contains(&"users".to_string()));
+    }
 }
@@ -25,6 +25,7 @@ use parquet::file::metadata::ParquetMetaDataReader;
 
 pub mod local;
 pub mod remote;
+pub mod routed;
 
 pub const CONCURRENT_REQUESTS: usize = 1000;
 
@@ -43,15 +44,39 @@ pub static LOCAL_WAL: Lazy<Box<dyn ObjectStore>> = Lazy::new(local_wal);
 /// let object_store = default();
 /// ```
 fn default() -> Box<dyn ObjectStore> {
-    if is_local_disk_storage() {
+    let store: Box<dyn ObjectStore> = if is_local_disk_storage() {
         std::fs::create_dir_all(&get_config().common.data_stream_dir)
             .expect("create stream data dir success");
         Box::<local::Local>::default()
     } else {
         Box::<remote::Remote>::default()
+    };
+    let named = named_stores();
+    if named.is_empty() {
+        store
+    } else {
+        Box::new(routed::Routed::new(store, named))
     }
 }
 
+/// Creates the stores of `ZO_S3_NAMED_STORES` streams can be assigned to.
+fn named_stores() -> hashbrown::HashMap<String, Box<dyn ObjectStore>> {
+    let cfg = get_config();
+    config::parse_named_stores(&cfg.s3.named_stores)
+        .expect("ZO_S3_NAMED_STORES is valid")
+        .into_iter()
+        .map(|(name, location)| {
+            let store: Box<dyn ObjectStore> = if is_local_disk_storage() {
+                std::fs::create_dir_all(&location).expect("create named store dir success");
+                Box::new(local::Local::new(&location, true))
+            } else {
+                Box::new(remote::Remote::new(&location))
+            };
+            (name, store)
+        })
+        .collect()
+}
+
 fn local_wal() -> Box<dyn ObjectStore> {
     let cfg = get_config();
     std::fs::create_dir_all(&cfg.common.data_wal_dir).expect("create wal dir success");
@@ -69,12 +94,67 @@ pub async fn list(prefix: &str) -> object_store::Result<Vec<String>> {
 }
 
 pub async fn get(file: &str) -> object_store::Result<bytes::Bytes> {
-    let data = DEFAULT.get(&file.into()).await?;
-    data.bytes().await
+    get_from(DEFAULT.as_ref(), file, None).await
 }
 
 pub async fn get_range(file: &str, range: Range<usize>) -> object_store::Result<bytes::Bytes> {
-    DEFAULT.get_range(&file.into(), range).await
+    get_from(DEFAULT.as_ref(), file, Some(range)).await
+}
+
+async fn get_from(
+    store: &dyn ObjectStore,
+    file: &str,
+    range: Option<Range<usize>>,
+) -> object_store::Result<bytes::Bytes> {
+    let path = Path::from(file);
+    with_retry(file, || async {
+        match range.clone() {
+            Some(range) => store.get_range(&path, range).await,
+            None => store.get(&path).await?.bytes().await,
+        }
+    })
+    .await
+}
+
+/// Runs a read of the file again with exponential backoff when it fails with
+/// a transient error, up to `ZO_S3_READ_RETRIES` times.
+pub async fn with_retry<T, F, Fut>(file: &str, read: F) -> object_store::Result<T>
+where
+    F: Fn() -> Fut,
+    Fut: std::future::Future<Output = object_store::Result<T>>,
+{
+    let cfg = get_config();
+    let mut retries = 0;
+    loop {
+        match read().await {
+            Err(e) if retries < cfg.s3.read_retries && is_retryable(&e) => {
+                let backoff = retry_backoff(cfg.s3.read_retry_backoff, retries);
+                log::warn!(
+                    "[STORAGE] read file {} failed, retry {} in {} ms: {}",
+                    file,
+                    retries + 1,
+                    backoff.as_millis(),
+                    e
+                );
+                tokio::time::sleep(backoff).await;
+                retries += 1;
+            }
+            ret => return ret,
+        }
+    }
+}
+
+/// Whether the error may go away on retry, like a server error or a
+/// timeout, as opposed to a missing file or a denied access.
+fn is_retryable(e: &object_store::Error) -> bool {
+    matches!(
+        e,
+        object_store::Error::Generic { .. } | object_store::Error::JoinError { .. }
+    )
+}
+
+fn retry_backoff(base_ms: u64, retries: usize) -> std::time::Duration {
+    std::time::Duration::from_millis(base_ms.saturating_mul(1 << retries.min(10)))
 }
 
 pub async fn head(file: &str) -> object_store::Result<ObjectMeta> {
@@ -176,6 +256,39 @@ async fn get_parquet_metadata(file: &str) -> Result<(usize, Arc<ParquetMetaData>
     ))
 }
 
+/// Reachability of the object store as seen from this node
+#[derive(Clone, Debug, PartialEq)]
+pub struct StorageHealth {
+    pub reachable: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Probes the configured object store by listing its first object.
+pub async fn check_health() -> StorageHealth {
+    let timeout = std::time::Duration::from_secs(get_config().s3.connect_timeout);
+    check_store_health(DEFAULT.as_ref(), timeout).await
+}
+
+async fn check_store_health(
+    store: &dyn ObjectStore,
+    timeout: std::time::Duration,
+) -> StorageHealth {
+    let start = std::time::Instant::now();
+    let ret = tokio::time::timeout(timeout, store.list(None).next()).await;
+    let latency_ms = start.elapsed().as_millis() as u64;
+    let error = match ret {
+        Ok(None | Some(Ok(_))) => None,
+        Ok(Some(Err(e))) => Some(e.to_string()),
+        Err(_) => Some(format!("no response within {} ms", timeout.as_millis())),
+    };
+    StorageHealth {
+        reachable: error.is_none(),
+        latency_ms,
+        error,
+    }
+}
+
 pub fn format_key(key: &str, with_prefix: bool) -> String {
     let cfg = get_config();
     if !is_local_disk_storage()
@@ -297,3 +410,228 @@ impl GetRangeExt for GetRange {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use futures::stream::BoxStream;
+    use object_store::{
+        memory::InMemory, GetOptions, GetResult, ListResult, MultipartUpload, PutMultipartOpts,
+        PutOptions, PutPayload, PutResult,
+    };
+
+    use super::*;
+
+    /// Store failing every request like an unreachable bucket
+    #[derive(Debug)]
+    struct Unreachable;
+
+    impl std::fmt::Display for Unreachable {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("unreachable storage")
+        }
+    }
+
+    fn unreachable_error() -> object_store::Error {
+        object_store::Error::Generic {
+            store: "unreachable",
+            source: "connection refused".into(),
+        }
+    }
+
+    #[async_trait]
+    impl ObjectStore for Unreachable {
+        async fn put_opts(
+            &self,
+            _location: &Path,
+            _payload: PutPayload,
+            _opts: PutOptions,
+        ) -> object_store::Result<PutResult> {
+            Err(unreachable_error())
+        }
+
+        async fn put_multipart_opts(
+            &self,
+            _location: &Path,
+            _opts: PutMultipartOpts,
+        ) -> object_store::Result<Box<dyn MultipartUpload>> {
+            Err(unreachable_error())
+        }
+
+        async fn get_opts(
+            &self,
+            _location: &Path,
+            _options: GetOptions,
+        ) -> object_store::Result<GetResult> {
+            Err(unreachable_error())
+        }
+
+        async fn delete(&self, _location: &Path) -> object_store::Result<()> {
+            Err(unreachable_error())
+        }
+
+        fn list(&self, _prefix: Option<&Path>) -> BoxStream<'_, object_store::Result<ObjectMeta>> {
+            futures::stream::once(async { Err(unreachable_error()) }).boxed()
+        }
+
+        async fn list_with_delimiter(
+            &self,
+            _prefix: Option<&Path>,
+        ) -> object_store::Result<ListResult> {
+            Err(unreachable_error())
+        }
+
+        async fn copy(&self, _from: &Path, _to: &Path) -> object_store::Result<()> {
+            Err(unreachable_error())
+        }
+
+        async fn copy_if_not_exists(&self, _from: &Path, _to: &Path) -> object_store::Result<()> {
+            Err(unreachable_error())
+        }
+    }
+
+    /// Store failing the first reads like a flaky bucket
+    #[derive(Debug)]
+    struct Flaky {
+        inner: InMemory,
+        failures: std::sync::atomic::AtomicUsize,
+        reads: std::sync::atomic::AtomicUsize,
+    }
+
+    impl Flaky {
+        fn new(failures: usize) -> Self {
+            Self {
+                inner: InMemory::new(),
+                failures: failures.into(),
+                reads: 0.into(),
+            }
+        }
+    }
+
+    impl std::fmt::Display for Flaky {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("flaky storage")
+        }
+    }
+
+    #[async_trait]
+    impl ObjectStore for Flaky {
+        async fn put_opts(
+            &self,
+            location: &Path,
+            payload: PutPayload,
+            opts: PutOptions,
+        ) -> object_store::Result<PutResult> {
+            self.inner.put_opts(location, payload, opts).await
+        }
+
+        async fn put_multipart_opts(
+            &self,
+            location: &Path,
+            opts: PutMultipartOpts,
+        ) -> object_store::Result<Box<dyn MultipartUpload>> {
+            self.inner.put_multipart_opts(location, opts).await
+        }
+
+        async fn get_opts(
+            &self,
+            location: &Path,
+            options: GetOptions,
+        ) -> object_store::Result<GetResult> {
+            use std::sync::atomic::Ordering;
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            let failures = self.failures.load(Ordering::SeqCst);
+            if failures > 0 {
+                self.failures.store(failures - 1, Ordering::SeqCst);
+                return Err(object_store::Error::Generic {
+                    store: "flaky",
+                    source: "503 Service Unavailable".into(),
+                });
+            }
+            self.inner.get_opts(location, options).await
+        }
+
+        async fn delete(&self, location: &Path) -> object_store::Result<()> {
+            self.inner.delete(location).await
+        }
+
+        fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, object_store::Result<ObjectMeta>> {
+            self.inner.list(prefix)
+        }
+
+        async fn list_with_delimiter(
+            &self,
+            prefix: Option<&Path>,
+        ) -> object_store::Result<ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+
+        async fn copy(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+            self.inner.copy(from, to).await
+        }
+
+        async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+            self.inner.copy_if_not_exists(from, to).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_with_retry() {
+        use std::sync::atomic::Ordering;
+
+        let file = "files/default/logs/a.parquet";
+        let store = Flaky::new(1);
+        store.put(&file.into(), "data".into()).await.unwrap();
+        // the first read fails, the retry succeeds
+        let data = get_from(&store, file, None).await.unwrap();
+        assert_eq!(data, bytes::Bytes::from("data"));
+        assert_eq!(store.reads.load(Ordering::SeqCst), 2);
+
+        let store = Flaky::new(1);
+        store.put(&file.into(), "data".into()).await.unwrap();
+        let data = get_from(&store, file, Some(1..3)).await.unwrap();
+        assert_eq!(data, bytes::Bytes::from("at"));
+        assert_eq!(store.reads.load(Ordering::SeqCst), 2);
+
+        // a missing file isn't retried
+        let store = Flaky::new(0);
+        let ret = get_from(&store, file, None).await;
+        assert!(matches!(ret, Err(object_store::Error::NotFound { .. })));
+        assert_eq!(store.reads.load(Ordering::SeqCst), 1);
+
+        // the retries are bounded
+        let store = Flaky::new(usize::MAX);
+        assert!(get_from(&store, file, None).await.is_err());
+        assert_eq!(
+            store.reads.load(Ordering::SeqCst),
+            get_config().s3.read_retries + 1
+        );
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        assert_eq!(retry_backoff(100, 0).as_millis(), 100);
+        assert_eq!(retry_backoff(100, 3).as_millis(), 800);
+        assert_eq!(retry_backoff(100, 30), retry_backoff(100, 10));
+    }
+
+    #[tokio::test]
+    async fn test_check_store_health() {
+        let timeout = std::time::Duration::from_secs(1);
+        let store = InMemory::new();
+        let health = check_store_health(&store, timeout).await;
+        assert!(health.reachable);
+        assert!(health.error.is_none());
+
+        store
+            .put(&"files/default/logs/a.parquet".into(), "data".into())
+            .await
+            .unwrap();
+        let health = check_store_health(&store, timeout).await;
+        assert!(health.reachable);
+
+        let health = check_store_health(&Unreachable, timeout).await;
+        assert!(!health.reachable);
+        assert!(health.error.unwrap().contains("connection refused"));
+    }
+}
@@ -30,14 +30,20 @@ pub struct Remote {
     client: LimitStore<Box<dyn object_store::ObjectStore>>,
 }
 
-impl Default for Remote {
-    fn default() -> Self {
+impl Remote {
+    pub fn new(bucket_name: &str) -> Self {
         Self {
-            client: LimitStore::new(init_client(), CONCURRENT_REQUESTS),
+            client: LimitStore::new(init_client(bucket_name), CONCURRENT_REQUESTS),
         }
     }
 }
 
+impl Default for Remote {
+    fn default() -> Self {
+        Remote::new(&get_config().s3.bucket_name)
+    }
+}
+
 impl std::fmt::Debug for Remote {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str("storage for remote")
@@ -230,7 +236,7 @@ impl ObjectStore for Remote {
     }
 }
 
-fn init_aws_config() -> object_store::Result<object_store::aws::AmazonS3> {
+fn init_aws_config(bucket_name: &str) -> object_store::Result<object_store::aws::AmazonS3> {
     let cfg = get_config();
     let mut opts = object_store::ClientOptions::default()
         .with_connect_timeout(std::time::Duration::from_secs(cfg.s3.connect_timeout))
@@ -257,7 +263,7 @@ fn init_aws_config() -> object_store::Result<object_store::aws::AmazonS3> {
     };
     let mut builder = object_store::aws::AmazonS3Builder::from_env()
         .with_client_options(opts)
-        .with_bucket_name(&cfg.s3.bucket_name)
+        .with_bucket_name(bucket_name)
         .with_retry(retry_config)
         .with_virtual_hosted_style_request(force_hosted_style);
     if !cfg.s3.server_url.is_empty() {
@@ -275,7 +281,9 @@ fn init_aws_config() -> object_store::Result<object_store::aws::AmazonS3> {
     builder.build()
 }
 
-fn init_azure_config() -> object_store::Result<object_store::azure::MicrosoftAzure> {
+fn init_azure_config(
+    bucket_name: &str,
+) -> object_store::Result<object_store::azure::MicrosoftAzure> {
     let cfg = get_config();
     let mut builder = object_store::azure::MicrosoftAzureBuilder::from_env()
         .with_client_options(
@@ -284,7 +292,7 @@ fn init_azure_config() -> object_store::Result<object_store::azure::MicrosoftAzu
                 .with_timeout(std::time::Duration::from_secs(cfg.s3.request_timeout))
                 .with_allow_invalid_certificates(cfg.s3.allow_invalid_certificates),
         )
-        .with_container_name(&cfg.s3.bucket_name);
+        .with_container_name(bucket_name);
     if !cfg.s3.access_key.is_empty() {
         builder = builder.with_account(&cfg.s3.access_key);
     }
@@ -294,7 +302,9 @@ fn init_azure_config() -> object_store::Result<object_store::azure::MicrosoftAzu
     builder.build()
 }
 
-fn init_gcp_config() -> object_store::Result<object_store::gcp::GoogleCloudStorage> {
+fn init_gcp_config(
+    bucket_name: &str,
+) -> object_store::Result<object_store::gcp::GoogleCloudStorage> {
     let cfg = get_config();
     let mut builder = object_store::gcp::GoogleCloudStorageBuilder::from_env()
         .with_client_options(
@@ -303,39 +313,39 @@ fn init_gcp_config() -> object_store::Result<object_store::gcp::GoogleCloudStora
                 .with_timeout(std::time::Duration::from_secs(cfg.s3.request_timeout))
                 .with_allow_invalid_certificates(cfg.s3.allow_invalid_certificates),
         )
-        .with_bucket_name(&cfg.s3.bucket_name);
+        .with_bucket_name(bucket_name);
     if !cfg.s3.access_key.is_empty() {
         builder = builder.with_service_account_path(&cfg.s3.access_key);
     }
     builder.build()
 }
 
-fn init_client() -> Box<dyn object_store::ObjectStore> {
+fn init_client(bucket_name: &str) -> Box<dyn object_store::ObjectStore> {
     let cfg = get_config();
     if cfg.common.print_key_config {
         log::info!("s3 init config: {:?}", cfg.s3);
     }
 
     match cfg.s3.provider.as_str() {
-        "aws" | "s3" => match init_aws_config() {
+        "aws" | "s3" => match init_aws_config(bucket_name) {
             Ok(client) => Box::new(client),
             Err(e) => {
                 panic!("s3 init config error: {:?}", e);
             }
         },
-        "azure" => match init_azure_config() {
+        "azure" => match init_azure_config(bucket_name) {
             Ok(client) => Box::new(client),
             Err(e) => {
                 panic!("azure init config error: {:?}", e);
             }
         },
-        "gcs" | "gcp" => match init_gcp_config() {
+        "gcs" | "gcp" => match init_gcp_config(bucket_name) {
             Ok(client) => Box::new(client),
             Err(e) => {
                 panic!("gcp init config error: {:?}", e);
             }
         },
-        _ => match init_aws_config() {
+        _ => match init_aws_config(bucket_name) {
             Ok(client) => Box::new(client),
             Err(e) => {
                 panic!("{} init config error: {:?}", cfg.s3.provider, e);
@@ -0,0 +1,369 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::ops::Range;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{stream::BoxStream, StreamExt};
+use hashbrown::HashMap;
+use object_store::{
+    path::Path, Error, GetOptions, GetResult, ListResult, MultipartUpload, ObjectMeta, ObjectStore,
+    PutMode, PutMultipartOpts, PutOptions, PutPayload, PutResult, Result,
+};
+
+use crate::schema::STREAM_SETTINGS;
+
+/// Object store sending the files of each stream to the named store picked
+/// by its `storage_name` setting, and the other files to the default store.
+pub struct Routed {
+    default: Box<dyn ObjectStore>,
+    named: HashMap<String, Box<dyn ObjectStore>>,
+}
+
+impl Routed {
+    pub fn new(
+        default: Box<dyn ObjectStore>,
+        named: HashMap<String, Box<dyn ObjectStore>>,
+    ) -> Self {
+        Self { default, named }
+    }
+
+    /// Returns the named store of the stream owning the file, if any.
+    async fn route(&self, location: &Path) -> Option<&dyn ObjectStore> {
+        let key = stream_key(location.as_ref())?;
+        let name = STREAM_SETTINGS
+            .read()
+            .await
+            .get(&key)
+            .and_then(|s| s.storage_name.clone())?;
+        match self.named.get(&name) {
+            Some(store) => Some(store.as_ref()),
+            None => {
+                log::warn!("[STORAGE] stream {key} uses unknown storage {name}, use default");
+                None
+            }
+        }
+    }
+
+    async fn store(&self, location: &Path) -> &dyn ObjectStore {
+        self.route(location)
+            .await
+            .unwrap_or_else(|| self.default.as_ref())
+    }
+
+    fn stores(&self) -> impl Iterator<Item = &dyn ObjectStore> {
+        std::iter::once(self.default.as_ref()).chain(self.named.values().map(|s| s.as_ref()))
+    }
+
+    /// Every store, starting with the current one of the stream owning the
+    /// file: files written before the stream moved stay where they were.
+    async fn read_order(&self, location: &Path) -> Vec<&dyn ObjectStore> {
+        let current = self.store(location).await;
+        std::iter::once(current)
+            .chain(self.stores().filter(|s| !std::ptr::addr_eq(*s, current)))
+            .collect()
+    }
+
+    /// Copies a file to the store of the stream owning `to`, reading it from
+    /// whichever store holds it when that isn't the same one.
+    async fn copy_with_mode(&self, from: &Path, to: &Path, mode: PutMode) -> Result<()> {
+        let to_store = self.store(to).await;
+        let ret = match mode {
+            PutMode::Create => to_store.copy_if_not_exists(from, to).await,
+            _ => to_store.copy(from, to).await,
+        };
+        match ret {
+            Err(Error::NotFound { .. }) => {
+                let data = self.get(from).await?.bytes().await?;
+                to_store
+                    .put_opts(to, data.into(), mode.into())
+                    .await
+                    .map(|_| ())
+            }
+            ret => ret,
+        }
+    }
+}
+
+/// `org/stream_type/stream` of a data file key like
+/// `files/org/logs/stream/2024/...`.
+fn stream_key(file: &str) -> Option<String> {
+    let mut columns = file.split('/');
+    if columns.next()? != "files" {
+        return None;
+    }
+    let (org_id, stream_type, stream_name) = (columns.next()?, columns.next()?, columns.next()?);
+    Some(format!("{org_id}/{stream_type}/{stream_name}"))
+}
+
+// files written before the stream was moved to another store stay where they
+// were written, reads try every store until one has the file
+macro_rules! read_with_fallback {
+    ($self:ident, $location:ident, | $store:ident | $read:expr) => {{
+        let mut ret = None;
+        for $store in $self.read_order($location).await {
+            match $read {
+                Err(e @ Error::NotFound { .. }) => {
+                    ret.get_or_insert(Err(e));
+                }
+                r => {
+                    ret = Some(r);
+                    break;
+                }
+            }
+        }
+        ret.expect("BUG: routed storage without stores")
+    }};
+}
+
+impl std::fmt::Debug for Routed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("storage routed by stream")
+    }
+}
+
+impl std::fmt::Display for Routed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("storage routed by stream")
+    }
+}
+
+#[async_trait]
+impl ObjectStore for Routed {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> Result<PutResult> {
+        self.store(location)
+            .await
+            .put_opts(location, payload, opts)
+            .await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOpts,
+    ) -> Result<Box<dyn MultipartUpload>> {
+        self.store(location)
+            .await
+            .put_multipart_opts(location, opts)
+            .await
+    }
+
+    async fn get(&self, location: &Path) -> Result<GetResult> {
+        read_with_fallback!(self, location, |store| store.get(location).await)
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> Result<GetResult> {
+        read_with_fallback!(self, location, |store| store
+            .get_opts(location, options.clone())
+            .await)
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> Result<Bytes> {
+        read_with_fallback!(self, location, |store| store
+            .get_range(location, range.clone())
+            .await)
+    }
+
+    async fn head(&self, location: &Path) -> Result<ObjectMeta> {
+        read_with_fallback!(self, location, |store| store.head(location).await)
+    }
+
+    async fn delete(&self, location: &Path) -> Result<()> {
+        // the file may be in any store, the stream may have moved since it was
+        // written or its settings may be gone with the stream
+        let mut ret = Ok(());
+        let mut found = false;
+        for store in self.stores() {
+            match store.delete(location).await {
+                Ok(()) => found = true,
+                Err(e @ Error::NotFound { .. }) => {
+                    if ret.is_ok() {
+                        ret = Err(e);
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        if found {
+            Ok(())
+        } else {
+            ret
+        }
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, Result<ObjectMeta>> {
+        // a stream may have files in several stores, list all of them
+        let streams = self.stores().map(|s| s.list(prefix)).collect::<Vec<_>>();
+        futures::stream::iter(streams).flatten().boxed()
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult> {
+        let mut result = ListResult {
+            common_prefixes: vec![],
+            objects: vec![],
+        };
+        for store in self.stores() {
+            let ret = store.list_with_delimiter(prefix).await?;
+            for common_prefix in ret.common_prefixes {
+                if !result.common_prefixes.contains(&common_prefix) {
+                    result.common_prefixes.push(common_prefix);
+                }
+            }
+            result.objects.extend(ret.objects);
+        }
+        Ok(result)
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+        self.copy_with_mode(from, to, PutMode::Overwrite).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+        self.copy_with_mode(from, to, PutMode::Create).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use config::meta::stream::StreamSettings;
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    #[test]
+    fn test_stream_key() {
+        assert_eq!(
+            stream_key("files/default/logs/hot/2024/01/01/00/a.parquet").as_deref(),
+            Some("default/logs/hot")
+        );
+        assert_eq!(stream_key("files/default"), None);
+        assert_eq!(stream_key("results/default/logs/a.json"), None);
+    }
+
+    #[tokio::test]
+    async fn test_routed_store() {
+        let stores = || -> Box<dyn ObjectStore> { Box::new(InMemory::new()) };
+        let store = Routed::new(
+            stores(),
+            HashMap::from([
+                ("fast".to_string(), stores()),
+                ("slow".to_string(), stores()),
+            ]),
+        );
+        let fast = store.named.get("fast").unwrap();
+        STREAM_SETTINGS.write().await.insert(
+            "test_routed/logs/hot".to_string(),
+            StreamSettings {
+                storage_name: Some("fast".to_string()),
+                ..Default::default()
+            },
+        );
+
+        // the stream reads from its assigned store
+        let hot: Path = "files/test_routed/logs/hot/2024/a.parquet".into();
+        fast.put(&hot, "fast".into()).await.unwrap();
+        let data = store.get(&hot).await.unwrap().bytes().await.unwrap();
+        assert_eq!(data, Bytes::from("fast"));
+
+        // and writes to it
+        let new_hot: Path = "files/test_routed/logs/hot/2024/b.parquet".into();
+        store.put(&new_hot, "new".into()).await.unwrap();
+        assert!(fast.head(&new_hot).await.is_ok());
+        assert!(store.default.head(&new_hot).await.is_err());
+
+        // files written before the stream moved are still read
+        let old_hot: Path = "files/test_routed/logs/hot/2023/a.parquet".into();
+        store.default.put(&old_hot, "old".into()).await.unwrap();
+        assert_eq!(
+            store.get_range(&old_hot, 0..3).await.unwrap(),
+            Bytes::from("old")
+        );
+
+        // other streams stay on the default store
+        let cold: Path = "files/test_routed/logs/cold/2024/a.parquet".into();
+        store.put(&cold, "cold".into()).await.unwrap();
+        assert!(store.default.head(&cold).await.is_ok());
+        assert!(fast.head(&cold).await.is_err());
+
+        // the stream moves to another named store, then back to the default
+        STREAM_SETTINGS.write().await.insert(
+            "test_routed/logs/hot".to_string(),
+            StreamSettings {
+                storage_name: Some("slow".to_string()),
+                ..Default::default()
+            },
+        );
+        let slow = store.named.get("slow").unwrap();
+        let slow_hot: Path = "files/test_routed/logs/hot/2025/a.parquet".into();
+        store.put(&slow_hot, "slow".into()).await.unwrap();
+        assert!(slow.head(&slow_hot).await.is_ok());
+        assert_eq!(
+            store.get(&hot).await.unwrap().bytes().await.unwrap(),
+            Bytes::from("fast")
+        );
+        STREAM_SETTINGS.write().await.remove("test_routed/logs/hot");
+        assert_eq!(
+            store.get(&hot).await.unwrap().bytes().await.unwrap(),
+            Bytes::from("fast")
+        );
+        assert_eq!(store.head(&slow_hot).await.unwrap().size, 4);
+        assert!(matches!(
+            store.get(&"files/test_routed/logs/hot/none".into()).await,
+            Err(Error::NotFound { .. })
+        ));
+
+        // copies read from the store holding the file and write to the store
+        // of the destination stream
+        let copied: Path = "files/test_routed/logs/cold/2025/a.parquet".into();
+        store.copy(&hot, &copied).await.unwrap();
+        assert_eq!(
+            store.default.get(&copied).await.unwrap().bytes().await.unwrap(),
+            Bytes::from("fast")
+        );
+        assert!(matches!(
+            store.copy_if_not_exists(&slow_hot, &copied).await,
+            Err(Error::AlreadyExists { .. })
+        ));
+        store.delete(&copied).await.unwrap();
+        store.delete(&slow_hot).await.unwrap();
+        STREAM_SETTINGS.write().await.insert(
+            "test_routed/logs/hot".to_string(),
+            StreamSettings {
+                storage_name: Some("fast".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let listed = store
+            .list(Some(&"files/test_routed".into()))
+            .map(|m| m.unwrap().location)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(listed.len(), 4);
+
+        // deletes reach the files of every store, with or without the settings
+        store.delete(&old_hot).await.unwrap();
+        assert!(store.default.head(&old_hot).await.is_err());
+        STREAM_SETTINGS.write().await.remove("test_routed/logs/hot");
+        store.delete(&hot).await.unwrap();
+        assert!(fast.head(&hot).await.is_err());
+    }
+}
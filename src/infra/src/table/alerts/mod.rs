@@ -68,6 +68,10 @@ impl TryFrom<alerts::Model> for MetaAlert {
         // Transform database JSON values into intermediate types which can be
         // directly translated into service layer types.
         let destinations: Vec<String> = serde_json::from_value(value.destinations)?;
+        let destination_templates: Option<HashMap<String, String>> = value
+            .destination_templates
+            .map(serde_json::from_value)
+            .transpose()?;
         let context_attributes: Option<HashMap<String, String>> = value
             .context_attributes
             .map(serde_json::from_value)
@@ -104,6 +108,8 @@ impl TryFrom<alerts::Model> for MetaAlert {
         alert.stream_name = value.stream_name;
         alert.is_real_time = value.is_real_time;
         alert.destinations = destinations;
+        alert.destination_templates = destination_templates;
+        alert.template_locale = value.template_locale;
         alert.context_attributes = context_attributes;
         alert.row_template = value.row_template.unwrap_or_default();
         alert.description = value.description.unwrap_or_default();
@@ -568,6 +574,12 @@ fn update_mutable_fields(
     let last_satisfied_at = alert.get_last_satisfied_at_from_table();
     let is_real_time = alert.is_real_time;
     let destinations = serde_json::to_value(alert.destinations)?;
+    let destination_templates = alert
+        .destination_templates
+        .filter(|t| !t.is_empty())
+        .map(serde_json::to_value)
+        .transpose()?;
+    let template_locale = alert.template_locale.filter(|s| !s.is_empty());
     let context_attributes = alert
         .context_attributes
         .map(serde_json::to_value)
@@ -641,6 +653,8 @@ fn update_mutable_fields(
 
     alert_am.is_real_time = Set(is_real_time);
     alert_am.destinations = Set(destinations);
+    alert_am.destination_templates = Set(destination_templates);
+    alert_am.template_locale = Set(template_locale);
     alert_am.context_attributes = Set(context_attributes);
     alert_am.row_template = Set(row_template);
     alert_am.description = Set(description);
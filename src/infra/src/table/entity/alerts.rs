@@ -14,6 +14,8 @@ pub struct Model {
     pub stream_name: String,
     pub is_real_time: bool,
     pub destinations: Json,
+    pub destination_templates: Option<Json>,
+    pub template_locale: Option<String>,
     pub context_attributes: Option<Json>,
     pub row_template: Option<String>,
     pub description: Option<String>,
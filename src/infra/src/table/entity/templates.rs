@@ -13,6 +13,7 @@ pub struct Model {
     pub r#type: String,
     pub body: String,
     pub title: Option<String>,
+    pub locales: Option<Json>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,91 @@
+// Copyright 2024 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Adds the template's locales column and the alert's template_locale column
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        add_column(
+            manager,
+            Templates::Table,
+            ColumnDef::new(Templates::Locales).json().null().to_owned(),
+        )
+        .await?;
+        add_column(
+            manager,
+            Alerts::Table,
+            ColumnDef::new(Alerts::TemplateLocale)
+                .string_len(32)
+                .null()
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // Reversing this migration is not supported.
+        Ok(())
+    }
+}
+
+// Adds the nullable column to the table.
+async fn add_column<T: IntoIden + 'static>(
+    manager: &SchemaManager<'_>,
+    table: T,
+    mut column: ColumnDef,
+) -> Result<(), DbErr> {
+    if matches!(manager.get_database_backend(), sea_orm::DbBackend::MySql) {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(table)
+                    .add_column(&mut column)
+                    .to_owned(),
+            )
+            .await?;
+    } else {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(table)
+                    .add_column_if_not_exists(&mut column)
+                    .to_owned(),
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Identifiers used in queries on the templates table.
+#[derive(DeriveIden)]
+enum Templates {
+    Table,
+    Locales,
+}
+
+/// Identifiers used in queries on the alerts table.
+#[derive(DeriveIden)]
+enum Alerts {
+    Table,
+    TemplateLocale,
+}
@@ -44,6 +44,8 @@ mod m20250125_133700_populate_destinations_table;
 mod m20250125_153005_delete_metas_destinations;
 mod m20250125_172300_delete_metas_templates;
 mod m20250213_000001_add_dashboard_updated_at;
+mod m20250214_000001_add_alert_destination_templates;
+mod m20250214_000002_add_template_locales;
 
 pub struct Migrator;
 
@@ -79,6 +81,8 @@ impl MigratorTrait for Migrator {
             Box::new(m20250125_133700_populate_destinations_table::Migration),
             Box::new(m20250125_153005_delete_metas_destinations::Migration),
             Box::new(m20250213_000001_add_dashboard_updated_at::Migration),
+            Box::new(m20250214_000001_add_alert_destination_templates::Migration),
+            Box::new(m20250214_000002_add_template_locales::Migration),
         ]
     }
 }
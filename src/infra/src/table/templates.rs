@@ -48,6 +48,12 @@ impl TryFrom<Model> for Template {
         };
         let id = svix_ksuid::Ksuid::from_str(&value.id)
             .map_err(|e| TemplateError::ConvertingId(e.to_string()))?;
+        let locales = value
+            .locales
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| TemplateError::ConvertingLocales(e.to_string()))?
+            .unwrap_or_default();
         Ok(Self {
             id: Some(id),
             org_id: value.org,
@@ -55,6 +61,7 @@ impl TryFrom<Model> for Template {
             is_default: value.is_default,
             template_type,
             body: value.body,
+            locales,
         })
     }
 }
@@ -68,6 +75,11 @@ pub async fn put(template: Template) -> Result<Template, Error> {
         TemplateType::Email { title } => Some(title.to_string()),
         _ => None,
     };
+    let locales = if template.locales.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_value(&template.locales)?)
+    };
     let mut active: ActiveModel = ActiveModel {
         id: NotSet,
         org: Set(template.org_id.to_string()),
@@ -76,6 +88,7 @@ pub async fn put(template: Template) -> Result<Template, Error> {
         r#type: Set(template.template_type.to_string()),
         body: Set(template.body),
         title: Set(title),
+        locales: Set(locales),
     };
     let model: Model = match get_model(client, &template.org_id, &template.name).await? {
         Some(model) => {
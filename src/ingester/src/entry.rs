@@ -14,14 +14,19 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::{
+    collections::BTreeSet,
     io::{Cursor, Read},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
 };
 
 use arrow::{array::Int64Array, record_batch::RecordBatch};
 use arrow_schema::Schema;
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use config::utils::record_batch_ext::{convert_json_to_record_batch, RecordBatchExt};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use snafu::ResultExt;
 
@@ -124,12 +129,65 @@ impl Default for Entry {
     }
 }
 
+/// Sequences of the batches written to the memtables of this node, with the
+/// ones still being written.
+struct BatchSeqs {
+    last: u64,
+    writing: BTreeSet<u64>,
+}
+
+// starts from the startup time, so the sequence keeps growing across restarts
+static BATCH_SEQS: Lazy<Mutex<BatchSeqs>> = Lazy::new(|| {
+    Mutex::new(BatchSeqs {
+        last: chrono::Utc::now().timestamp_micros() as u64,
+        writing: BTreeSet::new(),
+    })
+});
+
+/// The sequence up to which every batch is visible in the memtables of this
+/// node. Batches with a higher sequence may still be being written, so a
+/// reader keeping this as its cursor never skips one.
+pub fn last_batch_seq() -> u64 {
+    let seqs = BATCH_SEQS.lock().unwrap();
+    match seqs.writing.first() {
+        Some(seq) => seq - 1,
+        None => seqs.last,
+    }
+}
+
+/// The sequence of a batch being written to a memtable, taken while holding
+/// the memtable lock. The batch counts as written once this is dropped.
+pub(crate) struct BatchSeq(u64);
+
+impl BatchSeq {
+    pub(crate) fn next() -> Self {
+        let mut seqs = BATCH_SEQS.lock().unwrap();
+        seqs.last += 1;
+        let seq = seqs.last;
+        seqs.writing.insert(seq);
+        Self(seq)
+    }
+
+    pub(crate) fn get(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Drop for BatchSeq {
+    fn drop(&mut self) {
+        BATCH_SEQS.lock().unwrap().writing.remove(&self.0);
+    }
+}
+
 pub struct RecordBatchEntry {
     pub data: RecordBatch,
     pub data_json_size: usize,
     pub data_arrow_size: usize,
     pub min_ts: i64,
     pub max_ts: i64,
+    /// Ingestion order of the batch on this node, set when it is written to
+    /// the memtable, see [`last_batch_seq`]
+    seq: AtomicU64,
 }
 
 impl RecordBatchEntry {
@@ -150,8 +208,17 @@ impl RecordBatchEntry {
             data_arrow_size,
             min_ts,
             max_ts,
+            seq: AtomicU64::new(0),
         })
     }
+
+    pub fn seq(&self) -> u64 {
+        self.seq.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn set_seq(&self, seq: &BatchSeq) {
+        self.seq.store(seq.get(), Ordering::SeqCst);
+    }
 }
 
 fn pop_time_range(
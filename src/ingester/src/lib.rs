@@ -27,11 +27,13 @@ use std::{path::PathBuf, sync::Arc};
 
 use arrow_schema::Schema;
 use config::RwAHashMap;
-pub use entry::Entry;
+pub use entry::{last_batch_seq, Entry};
 pub use immutable::read_from_immutable;
 use once_cell::sync::Lazy;
 use tokio::sync::{mpsc, Mutex};
-pub use writer::{check_memtable_size, flush_all, get_writer, read_from_memtable, Writer};
+pub use writer::{
+    check_memtable_size, flush_all, get_writer, read_from_memtable, FlushStat, Writer,
+};
 
 pub(crate) type ReadRecordBatchEntry = (Arc<Schema>, Vec<Arc<entry::RecordBatchEntry>>);
 
@@ -82,6 +82,13 @@ impl MemTable {
         stream.read(time_range, partition_filters)
     }
 
+    /// Return the number of records buffered for each stream
+    pub(crate) fn num_rows(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.streams
+            .iter()
+            .map(|(name, stream)| (name.as_ref(), stream.num_rows()))
+    }
+
     pub(crate) async fn persist(
         &self,
         idx: usize,
@@ -105,6 +105,14 @@ impl Partition {
         partition.write(batch)
     }
 
+    pub(crate) fn num_rows(&self) -> usize {
+        self.files
+            .values()
+            .flat_map(|file| file.data.iter())
+            .map(|r| r.data.num_rows())
+            .sum()
+    }
+
     pub(crate) fn read(
         &self,
         time_range: Option<(i64, i64)>,
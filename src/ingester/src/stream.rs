@@ -56,6 +56,10 @@ impl Stream {
         Ok(arrow_size)
     }
 
+    pub(crate) fn num_rows(&self) -> usize {
+        self.partitions.values().map(Partition::num_rows).sum()
+    }
+
     pub(crate) fn read(
         &self,
         time_range: Option<(i64, i64)>,
@@ -14,6 +14,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::{
+    collections::BTreeMap,
     path::PathBuf,
     sync::{
         atomic::{AtomicI64, AtomicU64, Ordering},
@@ -30,12 +31,13 @@ use config::{
 };
 use hashbrown::HashSet;
 use once_cell::sync::Lazy;
+use serde::Serialize;
 use snafu::ResultExt;
 use tokio::sync::{mpsc, RwLock};
 use wal::Writer as WalWriter;
 
 use crate::{
-    entry::Entry,
+    entry::{BatchSeq, Entry},
     errors::*,
     immutable::{Immutable, IMMUTABLES},
     memtable::MemTable,
@@ -63,6 +65,47 @@ pub struct Writer {
     write_queue: Arc<mpsc::Sender<(WriterSignal, Vec<Entry>, bool)>>,
 }
 
+/// What a flush moved out of the memtables, so operators can confirm a drain.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct FlushStat {
+    /// Number of wal files closed
+    pub wal_files: usize,
+    /// Bytes of ingested data flushed, in json format
+    pub json_bytes: usize,
+    /// Records flushed, key: org_id/stream_type/stream_name
+    pub streams: BTreeMap<String, usize>,
+}
+
+impl FlushStat {
+    fn new(key: &WriterKey, memtable: &MemTable) -> Self {
+        let streams = memtable
+            .num_rows()
+            .filter(|(_, num)| *num > 0)
+            .map(|(stream, num)| {
+                (
+                    format!("{}/{}/{}", key.org_id, key.stream_type, stream),
+                    num,
+                )
+            })
+            .collect();
+        Self {
+            wal_files: 1,
+            json_bytes: memtable.size().0,
+            streams,
+        }
+    }
+}
+
+impl std::ops::AddAssign for FlushStat {
+    fn add_assign(&mut self, other: FlushStat) {
+        self.wal_files += other.wal_files;
+        self.json_bytes += other.json_bytes;
+        for (stream, num) in other.streams {
+            *self.streams.entry(stream).or_default() += num;
+        }
+    }
+}
+
 // check total memory size
 pub fn check_memtable_size() -> Result<()> {
     let total_mem_size = metrics::INGEST_MEMTABLE_ARROW_BYTES
@@ -176,19 +219,20 @@ pub async fn check_ttl() -> Result<()> {
     Ok(())
 }
 
-pub async fn flush_all() -> Result<()> {
+pub async fn flush_all() -> Result<FlushStat> {
+    let mut stat = FlushStat::default();
     for w in WRITERS.iter() {
         let mut w = w.write().await;
         let keys = w.keys().cloned().collect::<Vec<_>>();
         for r in w.values() {
-            r.close().await?; // close writer
+            stat += r.close().await?; // close writer
             metrics::INGEST_MEMTABLE_FILES.with_label_values(&[]).dec();
         }
         for key in keys {
             w.remove(&key);
         }
     }
-    Ok(())
+    Ok(stat)
 }
 
 impl Writer {
@@ -366,13 +410,20 @@ impl Writer {
         metrics::INGEST_MEMTABLE_LOCK_TIME
             .with_label_values(&[&self.key.org_id])
             .observe(mem_lock_time);
+        // the sequences are taken under the memtable lock and released once the
+        // batches are visible, see `last_batch_seq`
+        let mut seqs = Vec::with_capacity(batch_entries.len());
         for (entry, batch) in entries.into_iter().zip(batch_entries) {
             if entry.data_size == 0 {
                 continue;
             }
+            let seq = BatchSeq::next();
+            batch.set_seq(&seq);
+            seqs.push(seq);
             mem.write(entry.schema.clone().unwrap(), entry, batch)?;
         }
         drop(mem);
+        drop(seqs);
 
         // check fsync
         if fsync {
@@ -452,7 +503,7 @@ impl Writer {
         Ok(())
     }
 
-    pub async fn close(&self) -> Result<()> {
+    pub async fn close(&self) -> Result<FlushStat> {
         // wait for all messages to be processed
         if let Err(e) = self
             .write_queue
@@ -475,9 +526,10 @@ impl Writer {
         let old_mem = std::mem::replace(&mut *mem, new_mem);
         drop(mem);
 
+        let stat = FlushStat::new(&self.key, &old_mem);
         let table = Arc::new(Immutable::new(self.idx, self.key.clone(), old_mem));
         IMMUTABLES.write().await.insert(path, table);
-        Ok(())
+        Ok(stat)
     }
 
     pub async fn read(
@@ -532,3 +584,67 @@ impl WriterKey {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use arrow_schema::{DataType, Field};
+
+    use super::*;
+
+    fn seed(mem: &mut MemTable, stream: &str, num: usize) {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("_timestamp", DataType::Int64, false),
+            Field::new("log", DataType::Utf8, true),
+        ]));
+        let mut entry = Entry {
+            stream: stream.into(),
+            schema: Some(schema.clone()),
+            schema_key: "schema_key".into(),
+            partition_key: "2025/01/01/00".into(),
+            data: (0..num)
+                .map(|i| Arc::new(serde_json::json!({"_timestamp": i as i64 + 1, "log": "a"})))
+                .collect(),
+            data_size: 0,
+        };
+        entry.into_bytes().unwrap(); // sets data_size
+        let batch = entry.into_batch("logs".into(), schema.clone()).unwrap();
+        mem.write(schema, entry, batch).unwrap();
+    }
+
+    #[test]
+    fn test_flush_stat() {
+        let key = WriterKey::new("org1", "logs");
+        let mut mem1 = MemTable::new();
+        seed(&mut mem1, "default", 3);
+        seed(&mut mem1, "k8s", 2);
+        let mut mem2 = MemTable::new();
+        seed(&mut mem2, "default", 4);
+
+        let mut stat = FlushStat::default();
+        stat += FlushStat::new(&key, &mem1);
+        stat += FlushStat::new(&key, &mem2);
+        assert_eq!(stat.wal_files, 2);
+        assert_eq!(stat.json_bytes, mem1.size().0 + mem2.size().0);
+        assert_eq!(
+            stat.streams,
+            BTreeMap::from([
+                ("org1/logs/default".to_string(), 7),
+                ("org1/logs/k8s".to_string(), 2),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_last_batch_seq_waits_for_writes() {
+        let first = BatchSeq::next();
+        let second = BatchSeq::next();
+        assert!(second.get() > first.get());
+        // a later batch done first doesn't move the cursor past the earlier one
+        drop(second);
+        assert!(crate::last_batch_seq() < first.get());
+        let first_seq = first.get();
+        drop(first);
+        let third = BatchSeq::next();
+        assert!(third.get() > first_seq);
+    }
+}
@@ -32,6 +32,7 @@ pub async fn run() -> Result<(), anyhow::Error> {
     tokio::task::spawn(async move { parquet::run().await });
     tokio::task::spawn(async move { broadcast::run().await });
     tokio::task::spawn(async move { clean_empty_dirs().await });
+    tokio::task::spawn(async move { release_expired_wal_locks().await });
 
     Ok(())
 }
@@ -53,6 +54,27 @@ async fn clean_empty_dirs() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+async fn release_expired_wal_locks() -> Result<(), anyhow::Error> {
+    loop {
+        if is_offline() {
+            break;
+        }
+        tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
+        let ttl = config::get_config().limit.wal_lock_ttl as i64;
+        let expired_before = config::utils::time::now_micros() - ttl * 1_000_000;
+        let released = crate::common::infra::wal::release_expired_requests(expired_before);
+        if !released.is_empty() {
+            log::warn!(
+                "release_expired_wal_locks, released {} requests: {:?}",
+                released.len(),
+                released
+            );
+        }
+    }
+    log::info!("job::files::release_expired_wal_locks is stopped");
+    Ok(())
+}
+
 pub fn generate_storage_file_name(
     org_id: &str,
     stream_type: StreamType,
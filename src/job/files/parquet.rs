@@ -51,7 +51,7 @@ use config::{
             get_recordbatch_reader_from_bytes, read_metadata_from_file, read_schema_from_file,
         },
         schema_ext::SchemaExt,
-        tantivy::tokenizer::{o2_tokenizer_build, O2_TOKENIZER},
+        tantivy::tokenizer::{o2_tokenizer_build_with, O2_TOKENIZER},
     },
     FxIndexMap, INDEX_FIELD_NAME_FOR_ALL, INDEX_SEGMENT_LENGTH, PARQUET_BATCH_SIZE,
     TIMESTAMP_COL_NAME,
@@ -819,11 +819,13 @@ async fn merge_files(
         InvertedIndexFormat::Tantivy | InvertedIndexFormat::Both
     ) {
         let (schema, mut reader) = get_recordbatch_reader_from_bytes(&buf).await?;
+        let stream_settings = infra::schema::get_settings(&org_id, &stream_name, stream_type).await;
         let index_size = create_tantivy_index(
             "INGESTER",
             &new_file_key,
             &full_text_search_fields,
             &index_fields,
+            &infra::schema::get_stream_setting_split_chars(&stream_settings),
             schema,
             &mut reader,
         )
@@ -1142,7 +1144,8 @@ async fn prepare_index_record_batches(
     schema: Arc<Schema>,
     reader: &mut ParquetRecordBatchStream<std::io::Cursor<Bytes>>,
 ) -> Result<Vec<RecordBatch>, anyhow::Error> {
-    let cfg = get_config();
+    let stream_settings = infra::schema::get_settings(org_id, stream_name, stream_type).await;
+    let split_chars = infra::schema::get_stream_setting_split_chars(&stream_settings);
     let schema_fields = schema
         .fields()
         .iter()
@@ -1208,8 +1211,7 @@ async fn prepare_index_record_batches(
             // split the column into terms
             let terms = (0..num_rows)
                 .flat_map(|i| {
-                    #[allow(deprecated)]
-                    split_token(column_data.value(i), &cfg.common.inverted_index_split_chars)
+                    split_token(column_data.value(i), &split_chars)
                         .into_iter()
                         .map(|s| (s, i))
                         .collect::<Vec<_>>()
@@ -1372,6 +1374,7 @@ pub(crate) async fn create_tantivy_index(
     parquet_file_name: &str,
     full_text_search_fields: &[String],
     index_fields: &[String],
+    split_chars: &str,
     schema: Arc<Schema>,
     reader: &mut ParquetRecordBatchStream<std::io::Cursor<Bytes>>,
 ) -> Result<usize, anyhow::Error> {
@@ -1384,6 +1387,7 @@ pub(crate) async fn create_tantivy_index(
         reader,
         full_text_search_fields,
         index_fields,
+        split_chars,
         schema,
     )
     .await?;
@@ -1422,12 +1426,14 @@ pub(crate) async fn create_tantivy_index(
     Ok(index_size)
 }
 
-/// Create a tantivy index in the given directory for the record batch
+/// Create a tantivy index in the given directory for the record batch, the
+/// full text search fields split on `split_chars` when not empty
 pub(crate) async fn generate_tantivy_index<D: tantivy::Directory>(
     tantivy_dir: D,
     reader: &mut ParquetRecordBatchStream<std::io::Cursor<Bytes>>,
     full_text_search_fields: &[String],
     index_fields: &[String],
+    split_chars: &str,
     schema: Arc<Schema>,
 ) -> Result<Option<tantivy::Index>, anyhow::Error> {
     let mut tantivy_schema_builder = tantivy::schema::SchemaBuilder::new();
@@ -1537,7 +1543,7 @@ pub(crate) async fn generate_tantivy_index<D: tantivy::Directory>(
     }
 
     let tokenizer_manager = tantivy::tokenizer::TokenizerManager::default();
-    tokenizer_manager.register(O2_TOKENIZER, o2_tokenizer_build());
+    tokenizer_manager.register(O2_TOKENIZER, o2_tokenizer_build_with(split_chars));
     let mut index_writer = tantivy::IndexBuilder::new()
         .schema(tantivy_schema)
         .tokenizers(tokenizer_manager)
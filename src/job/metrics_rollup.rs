@@ -0,0 +1,64 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use config::{
+    cluster::LOCAL_NODE,
+    get_config,
+    meta::{cluster::Role, stream::StreamType},
+};
+use tokio::time::{self, Duration};
+
+use crate::{
+    common::infra::cluster::get_node_from_consistent_hash,
+    service::{db, metrics::rollup},
+};
+
+pub async fn run() -> Result<(), anyhow::Error> {
+    if !LOCAL_NODE.is_compactor() {
+        return Ok(());
+    }
+
+    let cfg = get_config();
+    if !cfg.common.metrics_rollup_enabled {
+        return Ok(());
+    }
+
+    let mut interval = time::interval(Duration::from_secs(cfg.common.metrics_rollup_interval));
+    interval.tick().await; // trigger the first run
+
+    loop {
+        interval.tick().await;
+        log::debug!("[METRICS_ROLLUP] Running metrics rollup job");
+        for org_id in db::schema::list_organizations_from_cache().await {
+            let streams = db::schema::list_streams_from_cache(&org_id, StreamType::Metrics).await;
+            for stream_name in streams {
+                if rollup::is_rollup_stream(&stream_name) {
+                    continue;
+                }
+                let Some(node_name) =
+                    get_node_from_consistent_hash(&stream_name, &Role::Compactor, None).await
+                else {
+                    continue; // no compactor node
+                };
+                if LOCAL_NODE.name.ne(&node_name) {
+                    continue; // not this node
+                }
+                if let Err(e) = rollup::rollup_stream(&org_id, &stream_name).await {
+                    log::error!("[METRICS_ROLLUP] rollup {org_id}/{stream_name} error: {e}");
+                }
+            }
+        }
+    }
+}
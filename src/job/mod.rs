@@ -34,6 +34,7 @@ mod compactor;
 pub(crate) mod files;
 mod flatten_compactor;
 pub mod metrics;
+mod metrics_rollup;
 mod mmdb_downloader;
 mod promql;
 mod promql_self_consume;
@@ -200,6 +201,7 @@ pub async fn init() -> Result<(), anyhow::Error> {
     tokio::task::spawn(async move { compactor::run().await });
     tokio::task::spawn(async move { flatten_compactor::run().await });
     tokio::task::spawn(async move { metrics::run().await });
+    tokio::task::spawn(async move { metrics_rollup::run().await });
     tokio::task::spawn(async move { promql::run().await });
     tokio::task::spawn(async move { alert_manager::run().await });
 
@@ -60,7 +60,12 @@ use openobserve::{
         http::router::*,
     },
     job, router,
-    service::{db, metadata, search::SEARCH_SERVER, self_reporting, tls::http_tls_config},
+    service::{
+        db, metadata,
+        search::{self, SEARCH_SERVER},
+        self_reporting,
+        tls::http_tls_config,
+    },
 };
 use opentelemetry::{global, trace::TracerProvider, KeyValue};
 use opentelemetry_otlp::WithExportConfig;
@@ -672,6 +677,7 @@ async fn init_http_server() -> Result<(), anyhow::Error> {
                             cfg.limit.circuit_breaker_enabled,
                         ))
                         .wrap(from_fn(middlewares::check_keep_alive))
+                        .wrap(from_fn(middlewares::check_swagger_enabled))
                         .service(router::http::config)
                         .service(router::http::config_paths)
                         .service(router::http::api)
@@ -690,6 +696,7 @@ async fn init_http_server() -> Result<(), anyhow::Error> {
                         cfg.limit.circuit_breaker_enabled,
                     ))
                     .wrap(from_fn(middlewares::check_keep_alive))
+                    .wrap(from_fn(middlewares::check_swagger_enabled))
                     .configure(get_config_routes)
                     .configure(get_service_routes)
                     .configure(get_other_service_routes)
@@ -783,6 +790,7 @@ async fn init_http_server_without_tracing() -> Result<(), anyhow::Error> {
                             cfg.limit.circuit_breaker_enabled,
                         ))
                         .wrap(from_fn(middlewares::check_keep_alive))
+                        .wrap(from_fn(middlewares::check_swagger_enabled))
                         .service(router::http::config)
                         .service(router::http::config_paths)
                         .service(router::http::api)
@@ -801,6 +809,7 @@ async fn init_http_server_without_tracing() -> Result<(), anyhow::Error> {
                         cfg.limit.circuit_breaker_enabled,
                     ))
                     .wrap(from_fn(middlewares::check_keep_alive))
+                    .wrap(from_fn(middlewares::check_swagger_enabled))
                     .configure(get_config_routes)
                     .configure(get_service_routes)
                     .configure(get_other_service_routes)
@@ -885,6 +894,11 @@ async fn graceful_shutdown(handle: ServerHandle) {
     }
     log::info!("Node is offline");
 
+    // let in-flight searches finish and release their WAL locks
+    let timeout = std::time::Duration::from_secs(max(1, get_config().limit.http_shutdown_timeout));
+    search::shutdown(timeout).await;
+    log::info!("Search queue drained");
+
     handle.stop(true).await;
 }
 
@@ -400,6 +400,12 @@ pub struct MetricsQueryRequest {
     pub timeout: i64,
     #[prost(bool, tag = "9")]
     pub no_cache: bool,
+    /// json, stream name -> field -> mask strategy
+    #[prost(string, tag = "10")]
+    pub field_masks: ::prost::alloc::string::String,
+    /// json, stream name -> row filter
+    #[prost(string, tag = "11")]
+    pub row_filters: ::prost::alloc::string::String,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -864,6 +870,31 @@ pub struct GetResultResponse {
     #[prost(bytes = "vec", tag = "1")]
     pub response: ::prost::alloc::vec::Vec<u8>,
 }
+/// Records of a stream in the memtable of an ingester, written after `since`
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TailRequest {
+    #[prost(string, tag = "1")]
+    pub trace_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub org_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub stream_type: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub stream_name: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub sql: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "6")]
+    pub since: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TailResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub hits: ::prost::alloc::vec::Vec<u8>,
+    #[prost(uint64, tag = "2")]
+    pub seq: u64,
+}
 /// Search request query
 #[derive(Eq)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -893,6 +924,16 @@ pub struct SearchQuery {
     pub skip_wal: bool,
     #[prost(string, tag = "15")]
     pub action_id: ::prost::alloc::string::String,
+    #[prost(bool, tag = "16")]
+    pub quick_scan: bool,
+    #[prost(message, repeated, tag = "17")]
+    pub segment_ids: ::prost::alloc::vec::Vec<IdxFileName>,
+    /// json encoded values of the sql placeholders
+    #[prost(map = "string, string", tag = "18")]
+    pub params: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -1207,6 +1248,25 @@ pub mod search_client {
                 .insert(GrpcMethod::new("cluster.Search", "DeleteResult"));
             self.inner.unary(req, path, codec).await
         }
+        pub async fn tail(
+            &mut self,
+            request: impl tonic::IntoRequest<super::TailRequest>,
+        ) -> std::result::Result<tonic::Response<super::TailResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/cluster.Search/Tail");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("cluster.Search", "Tail"));
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -1262,6 +1322,10 @@ pub mod search_server {
             tonic::Response<super::DeleteResultResponse>,
             tonic::Status,
         >;
+        async fn tail(
+            &self,
+            request: tonic::Request<super::TailRequest>,
+        ) -> std::result::Result<tonic::Response<super::TailResponse>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct SearchServer<T: Search> {
@@ -1660,6 +1724,52 @@ pub mod search_server {
                     };
                     Box::pin(fut)
                 }
+                "/cluster.Search/Tail" => {
+                    #[allow(non_camel_case_types)]
+                    struct TailSvc<T: Search>(pub Arc<T>);
+                    impl<
+                        T: Search,
+                    > tonic::server::UnaryService<super::TailRequest>
+                    for TailSvc<T> {
+                        type Response = super::TailResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::TailRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Search>::tail(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = TailSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -1740,6 +1850,36 @@ pub struct IngestionResponse {
     #[prost(string, tag = "2")]
     pub message: ::prost::alloc::string::String,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PipelineStatusRequest {
+    #[prost(string, tag = "1")]
+    pub pipeline_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PipelineStatusResponse {
+    #[prost(uint64, tag = "1")]
+    pub in_flight_batches: u64,
+    #[prost(message, repeated, tag = "2")]
+    pub nodes: ::prost::alloc::vec::Vec<PipelineNodeStats>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PipelineNodeStats {
+    #[prost(string, tag = "1")]
+    pub node_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub node_type: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub received: u64,
+    #[prost(uint64, tag = "4")]
+    pub processed: u64,
+    #[prost(uint64, tag = "5")]
+    pub dropped: u64,
+    #[prost(uint64, tag = "6")]
+    pub errors: u64,
+}
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum IngestionType {
@@ -1885,6 +2025,31 @@ pub mod ingest_client {
             req.extensions_mut().insert(GrpcMethod::new("cluster.Ingest", "Ingest"));
             self.inner.unary(req, path, codec).await
         }
+        pub async fn pipeline_status(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PipelineStatusRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::PipelineStatusResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/cluster.Ingest/PipelineStatus",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("cluster.Ingest", "PipelineStatus"));
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -1901,6 +2066,13 @@ pub mod ingest_server {
             tonic::Response<super::IngestionResponse>,
             tonic::Status,
         >;
+        async fn pipeline_status(
+            &self,
+            request: tonic::Request<super::PipelineStatusRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::PipelineStatusResponse>,
+            tonic::Status,
+        >;
     }
     #[derive(Debug)]
     pub struct IngestServer<T: Ingest> {
@@ -2025,6 +2197,52 @@ pub mod ingest_server {
                     };
                     Box::pin(fut)
                 }
+                "/cluster.Ingest/PipelineStatus" => {
+                    #[allow(non_camel_case_types)]
+                    struct PipelineStatusSvc<T: Ingest>(pub Arc<T>);
+                    impl<
+                        T: Ingest,
+                    > tonic::server::UnaryService<super::PipelineStatusRequest>
+                    for PipelineStatusSvc<T> {
+                        type Response = super::PipelineStatusResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PipelineStatusRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Ingest>::pipeline_status(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = PipelineStatusSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -2137,6 +2355,11 @@ pub struct MultiQueryCacheResponse {
 pub struct DeleteResultCacheRequest {
     #[prost(string, tag = "1")]
     pub path: ::prost::alloc::string::String,
+    /// only delete the results overlapping the time range, all when end_time is 0
+    #[prost(int64, tag = "2")]
+    pub start_time: i64,
+    #[prost(int64, tag = "3")]
+    pub end_time: i64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -2689,6 +2912,9 @@ pub struct SuperClusterInfo {
     /// use for super cluster
     #[prost(string, optional, tag = "4")]
     pub search_event_type: ::core::option::Option<::prost::alloc::string::String>,
+    /// only search the most recent files
+    #[prost(bool, tag = "5")]
+    pub quick_scan: bool,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -19,16 +19,17 @@ use std::{
 };
 
 use async_trait::async_trait;
-use chrono::{Duration, Local, TimeZone, Timelike, Utc};
+use chrono::{Duration, FixedOffset, Local, TimeZone, Timelike, Utc};
 use config::{
     get_config,
     meta::{
         alerts::{
-            alert::{Alert, AlertListFilter, ListAlertsParams},
+            alert::{Alert, AlertBacktestResponse, AlertListFilter, ListAlertsParams},
             FrequencyType, Operator, QueryType,
         },
         destinations::{
-            AwsSns, DestinationType, Email, Endpoint, HTTPType, Module, Template, TemplateType,
+            AwsSns, DestinationTestResponse, DestinationType, Email, Endpoint, HTTPType, Module,
+            Template, TemplateType,
         },
         folder::{Folder, FolderType, DEFAULT_FOLDER},
         search::{SearchEventContext, SearchEventType},
@@ -97,12 +98,22 @@ pub enum AlertError {
     #[error("Error moving alert to folder that cannot be found")]
     MoveDestinationFolderNotFound,
 
+    /// Error that occurs when the destination folder of a move cannot be read.
+    #[error("Error getting the destination folder of the alert move: {0}")]
+    MoveDestinationFolder(#[source] folders::FolderError),
+
     #[error("Alert not found")]
     AlertNotFound,
 
     #[error("Alert destination {dest} not found")]
     AlertDestinationNotFound { dest: String },
 
+    #[error("Alert destination template {template} not found")]
+    AlertDestinationTemplateNotFound { template: String },
+
+    #[error("Alert destination template is set for {dest} which is not an alert destination")]
+    AlertDestinationTemplateUnknownDestination { dest: String },
+
     #[error("Stream {stream_name} not found")]
     StreamNotFound { stream_name: String },
 
@@ -152,8 +163,30 @@ pub enum AlertError {
     /// Not support save destination remote pipeline for alert so far
     #[error("Not support save destination {0} type for alert so far")]
     NotSupportedAlertDestinationType(Module),
+
+    /// The time range of an alert backtest is empty.
+    #[error("Alert backtest end time must be greater than start time")]
+    BacktestInvalidTimeRange,
+
+    /// The period, frequency or timezone of the replayed alert is out of range.
+    #[error("Alert backtest needs a positive period and a valid frequency and timezone")]
+    BacktestInvalidTriggerCondition,
+
+    /// The time range of an alert backtest covers too many intervals.
+    #[error("Alert backtest can evaluate at most {max_intervals} intervals")]
+    BacktestTooManyIntervals { max_intervals: usize },
+
+    /// The template locale doesn't fit the alerts table column.
+    #[error("Alert template locale can be at most {max_len} characters")]
+    TemplateLocaleTooLong { max_len: usize },
 }
 
+/// The maximum number of intervals evaluated by a single alert backtest.
+const BACKTEST_MAX_INTERVALS: usize = 1440;
+
+/// The length of the `template_locale` column of the alerts table.
+const TEMPLATE_LOCALE_MAX_LEN: usize = 32;
+
 pub async fn save(
     org_id: &str,
     stream_name: &str,
@@ -211,8 +244,33 @@ async fn prepare_alert(
     if is_ofga_unsupported(&alert.name) {
         return Err(AlertError::AlertNameOfgaUnsupported);
     }
+    alert.template_locale = alert
+        .template_locale
+        .take()
+        .map(|locale| locale.trim().to_string())
+        .filter(|locale| !locale.is_empty());
+    if alert
+        .template_locale
+        .as_ref()
+        .is_some_and(|locale| locale.chars().count() > TEMPLATE_LOCALE_MAX_LEN)
+    {
+        return Err(AlertError::TemplateLocaleTooLong {
+            max_len: TEMPLATE_LOCALE_MAX_LEN,
+        });
+    }
     alert.org_id = org_id.to_string();
     let stream_type = alert.stream_type;
+    // SQL alerts may query several streams, in which case the stream is taken
+    // from the query when it is not given explicitly
+    let query_streams = query_stream_names(alert)?;
+    let stream_name = if stream_name.is_empty() {
+        query_streams
+            .first()
+            .map(|s| s.as_str())
+            .unwrap_or_default()
+    } else {
+        stream_name
+    };
     alert.stream_name = stream_name.to_string();
     alert.row_template = alert.row_template.trim().to_string();
 
@@ -298,6 +356,20 @@ async fn prepare_alert(
             }
         }
     }
+    if let Some(templates) = alert.destination_templates.as_ref() {
+        for (dest, template) in templates.iter() {
+            if !alert.destinations.contains(dest) {
+                return Err(AlertError::AlertDestinationTemplateUnknownDestination {
+                    dest: dest.to_string(),
+                });
+            }
+            if !template.is_empty() && db::alerts::templates::get(org_id, template).await.is_err() {
+                return Err(AlertError::AlertDestinationTemplateNotFound {
+                    template: template.to_string(),
+                });
+            }
+        }
+    }
 
     // before saving alert check alert context attributes
     if alert.context_attributes.is_some() {
@@ -358,31 +430,28 @@ async fn prepare_alert(
                 return Err(AlertError::SqlContainsSelectStar);
             }
 
-            let sql = alert.query_condition.sql.as_ref().unwrap();
-            let stream_names = match resolve_stream_names(sql) {
-                Ok(stream_names) => stream_names,
-                Err(e) => {
-                    return Err(AlertError::ResolveStreamNameError(e));
+            // SQL may contain multiple stream names, all of them must exist and
+            // the alert period must not exceed the max query range of any of them
+            for stream in query_streams.iter() {
+                if stream.eq(stream_name) {
+                    continue;
                 }
-            };
-
-            // SQL may contain multiple stream names, check for each stream
-            // if the alert period is greater than the max query range
-            for stream in stream_names.iter() {
-                if !stream.eq(stream_name) {
-                    if let Some(settings) =
-                        infra::schema::get_settings(org_id, stream, stream_type).await
+                let schema = infra::schema::get(org_id, stream, stream_type).await?;
+                if schema.fields().is_empty() {
+                    return Err(AlertError::StreamNotFound {
+                        stream_name: stream.to_owned(),
+                    });
+                }
+                if let Some(settings) = unwrap_stream_settings(&schema) {
+                    let max_query_range = settings.max_query_range;
+                    if max_query_range > 0
+                        && !alert.is_real_time
+                        && alert.trigger_condition.period > max_query_range * 60
                     {
-                        let max_query_range = settings.max_query_range;
-                        if max_query_range > 0
-                            && !alert.is_real_time
-                            && alert.trigger_condition.period > max_query_range * 60
-                        {
-                            return Err(AlertError::PeriodExceedsMaxQueryRange {
-                                max_query_range_hours: max_query_range,
-                                stream_name: stream_name.to_owned(),
-                            });
-                        }
+                        return Err(AlertError::PeriodExceedsMaxQueryRange {
+                            max_query_range_hours: max_query_range,
+                            stream_name: stream.to_owned(),
+                        });
                     }
                 }
             }
@@ -407,6 +476,21 @@ async fn prepare_alert(
     Ok(())
 }
 
+/// Returns the streams referenced by the query of a SQL alert. The first one
+/// is used as the alert's stream when none is given. Other alerts return no
+/// streams.
+fn query_stream_names(alert: &Alert) -> Result<Vec<String>, AlertError> {
+    if alert.query_condition.query_type != QueryType::SQL {
+        return Ok(vec![]);
+    }
+    match alert.query_condition.sql.as_deref() {
+        Some(sql) if !sql.is_empty() => {
+            resolve_stream_names(sql).map_err(AlertError::ResolveStreamNameError)
+        }
+        _ => Ok(vec![]),
+    }
+}
+
 /// Creates a new alert in the specified folder.
 pub async fn create<C: TransactionTrait>(
     conn: &C,
@@ -431,22 +515,58 @@ pub async fn create<C: TransactionTrait>(
 }
 
 /// Moves the alerts into the specified destination folder.
+///
+/// The destination folder and all of the alerts are validated before any alert
+/// is moved, and the alerts are moved in a single transaction, so either all
+/// of the alerts are moved or none of them are.
 pub async fn move_to_folder<C: ConnectionTrait + TransactionTrait>(
     conn: &C,
     org_id: &str,
     alert_ids: &[Ksuid],
     dst_folder_id: &str,
 ) -> Result<(), AlertError> {
+    ensure_move_destination_folder(org_id, dst_folder_id).await?;
+
+    let mut alerts = Vec::with_capacity(alert_ids.len());
     for alert_id in alert_ids {
-        let Some((_, alert)) = db::alerts::alert::get_by_id(conn, org_id, *alert_id).await? else {
+        let Some((_, mut alert)) = db::alerts::alert::get_by_id(conn, org_id, *alert_id).await?
+        else {
             return Err(AlertError::AlertNotFound);
         };
+        let alert_name = alert.name.clone();
+        let stream_name = alert.stream_name.clone();
+        prepare_alert(org_id, &stream_name, &alert_name, &mut alert, false).await?;
+        alerts.push(alert);
+    }
+
+    let txn = conn.begin().await.map_err(infra::errors::Error::from)?;
+    let mut moved = Vec::with_capacity(alerts.len());
+    for alert in alerts {
+        moved.push(table::alerts::update(&txn, org_id, Some(dst_folder_id), alert).await?);
+    }
+    txn.commit().await.map_err(infra::errors::Error::from)?;
 
-        update(conn, org_id, Some(dst_folder_id), alert).await?;
+    // the events and triggers only follow the committed moves
+    for alert in moved.iter() {
+        db::alerts::alert::after_update(org_id, Some(dst_folder_id), alert).await?;
     }
     Ok(())
 }
 
+/// Ensures that the destination folder of a move exists, creating the default
+/// folder if it is the destination and hasn't been created yet.
+async fn ensure_move_destination_folder(org_id: &str, folder_id: &str) -> Result<(), AlertError> {
+    match folders::get_folder(org_id, folder_id, FolderType::Alerts).await {
+        Ok(_) => Ok(()),
+        Err(folders::FolderError::NotFound) if folder_id == DEFAULT_FOLDER => {
+            create_default_alerts_folder(org_id).await
+        }
+        Err(folders::FolderError::NotFound) => Err(AlertError::MoveDestinationFolderNotFound),
+        Err(folders::FolderError::InfraError(e)) => Err(AlertError::InfraError(e)),
+        Err(e) => Err(AlertError::MoveDestinationFolder(e)),
+    }
+}
+
 /// Updates the alert.
 ///
 /// Updates the alert's parent folder if a `folder_id` is given.
@@ -671,6 +791,121 @@ pub async fn trigger_by_name(
     Ok((success_message, err_message))
 }
 
+/// Replays the alert over `[start_time, end_time]` and reports in how many of
+/// its scheduled intervals it would have fired.
+///
+/// The alert is evaluated at each time its trigger would have run, every
+/// `frequency` or at each time of its cron schedule, with the same scheduled
+/// evaluation that the alert triggers use, so each evaluation looks back
+/// `period` minutes from the time it runs. Nothing is notified and nothing is
+/// persisted.
+pub async fn backtest(
+    org_id: &str,
+    mut alert: Alert,
+    start_time: i64,
+    end_time: i64,
+) -> Result<AlertBacktestResponse, AlertError> {
+    if end_time <= start_time {
+        return Err(AlertError::BacktestInvalidTimeRange);
+    }
+    alert.org_id = org_id.to_string();
+    if alert.stream_name.is_empty() {
+        if let Some(stream) = query_stream_names(&alert)?.into_iter().next() {
+            alert.stream_name = stream;
+        }
+    }
+
+    let period = Duration::try_minutes(alert.trigger_condition.period)
+        .and_then(|d| d.num_microseconds())
+        .filter(|p| *p > 0)
+        .ok_or(AlertError::BacktestInvalidTriggerCondition)?;
+    let run_times = backtest_run_times(&alert, start_time, end_time)?;
+
+    let mut resp = AlertBacktestResponse {
+        start_time,
+        end_time,
+        intervals: run_times.len(),
+        ..Default::default()
+    };
+    for end in run_times {
+        let start = end - period;
+        let ret = alert
+            .query_condition
+            .evaluate_scheduled(
+                &alert.org_id,
+                Some(&alert.stream_name),
+                alert.stream_type,
+                &alert.trigger_condition,
+                (Some(start), end),
+                Some(SearchEventType::Alerts),
+                None,
+            )
+            .await;
+        match ret {
+            Ok((rows, _)) => {
+                if rows.is_some_and(|rows| !rows.is_empty()) {
+                    resp.fired += 1;
+                    resp.fired_at.push(end);
+                }
+            }
+            Err(e) => {
+                log::warn!("Alert backtest failed to evaluate interval [{start}, {end}]: {e}");
+                resp.errors += 1;
+            }
+        }
+    }
+    Ok(resp)
+}
+
+/// Returns the times in `(start_time, end_time]` at which the trigger of the
+/// alert would have run.
+fn backtest_run_times(
+    alert: &Alert,
+    start_time: i64,
+    end_time: i64,
+) -> Result<Vec<i64>, AlertError> {
+    let too_many = || AlertError::BacktestTooManyIntervals {
+        max_intervals: BACKTEST_MAX_INTERVALS,
+    };
+    if alert.trigger_condition.frequency_type == FrequencyType::Cron {
+        let schedule = Schedule::from_str(&alert.trigger_condition.cron)?;
+        // tz_offset is in minutes
+        let tz_offset = alert
+            .tz_offset
+            .checked_mul(60)
+            .and_then(FixedOffset::east_opt)
+            .ok_or(AlertError::BacktestInvalidTriggerCondition)?;
+        let start = tz_offset
+            .timestamp_micros(start_time)
+            .single()
+            .ok_or(AlertError::BacktestInvalidTimeRange)?;
+        let run_times = schedule
+            .after(&start)
+            .map(|t| t.timestamp_micros())
+            .take_while(|t| *t <= end_time)
+            .take(BACKTEST_MAX_INTERVALS + 1)
+            .collect::<Vec<_>>();
+        if run_times.len() > BACKTEST_MAX_INTERVALS {
+            return Err(too_many());
+        }
+        return Ok(run_times);
+    }
+
+    let frequency = if alert.trigger_condition.frequency > 0 {
+        alert.trigger_condition.frequency
+    } else {
+        std::cmp::max(60, get_config().limit.alert_schedule_interval)
+    };
+    let step = Duration::try_seconds(frequency)
+        .and_then(|d| d.num_microseconds())
+        .ok_or(AlertError::BacktestInvalidTriggerCondition)?;
+    let intervals = end_time.saturating_sub(start_time) / step;
+    if intervals > BACKTEST_MAX_INTERVALS as i64 {
+        return Err(too_many());
+    }
+    Ok((1..=intervals).map(|i| start_time + i * step).collect())
+}
+
 #[async_trait]
 pub trait AlertExt: Sync + Send + 'static {
     /// Returns the evaluated row data and the end time of the search timerange,
@@ -702,6 +937,19 @@ impl AlertExt for Alert {
         if self.is_real_time {
             self.query_condition.evaluate_realtime(row).await
         } else {
+            // the query of a multi-stream alert may outlive one of its streams
+            for stream in query_stream_names(self)? {
+                if stream != self.stream_name
+                    && infra::schema::get(&self.org_id, &stream, self.stream_type)
+                        .await?
+                        .fields()
+                        .is_empty()
+                {
+                    return Err(anyhow::anyhow!(
+                        "Stream {stream} queried by the alert not found"
+                    ));
+                }
+            }
             let search_event_ctx = SearchEventContext::with_alert(Some(format!(
                 "/alerts/{}/{}/{}/{}",
                 self.org_id, self.stream_type, self.stream_name, self.name
@@ -731,7 +979,12 @@ impl AlertExt for Alert {
         let mut success_message = "".to_string();
         let mut no_of_error = 0;
         for dest in self.destinations.iter() {
-            let (dest, template) = destinations::get_with_template(&self.org_id, dest).await?;
+            let (dest, template) = destinations::get_with_template(
+                &self.org_id,
+                dest,
+                self.get_destination_template(dest),
+            )
+            .await?;
             let Module::Alert {
                 destination_type, ..
             } = dest.module
@@ -740,6 +993,7 @@ impl AlertExt for Alert {
                     db::alerts::destinations::DestinationError::UnsupportedType,
                 ));
             };
+            let template = template.localized(self.template_locale.as_deref());
             match send_notification(
                 self,
                 &destination_type,
@@ -792,6 +1046,84 @@ async fn send_notification(
     start_time: Option<i64>,
     evaluation_timestamp: i64,
 ) -> Result<String, anyhow::Error> {
+    let (msg, email_subject) = render_notification(
+        alert,
+        dest_type,
+        template,
+        rows,
+        rows_end_time,
+        start_time,
+        evaluation_timestamp,
+    )
+    .await;
+
+    match dest_type {
+        DestinationType::Http(endpoint) => send_http_notification(endpoint, msg).await,
+        DestinationType::Email(email) => send_email_notification(&email_subject, email, msg).await,
+        DestinationType::Sns(aws_sns) => send_sns_notification(&alert.name, aws_sns, msg).await,
+    }
+}
+
+/// Sends a notification rendered from the template with a placeholder alert,
+/// so that a destination can be verified before an alert relies on it.
+pub async fn send_test_notification(
+    org_id: &str,
+    dest_type: &DestinationType,
+    template: &Template,
+) -> DestinationTestResponse {
+    let mut alert = Alert::default();
+    alert.name = "test_alert".to_string();
+    alert.org_id = org_id.to_string();
+    alert.stream_name = "test_stream".to_string();
+    alert.description = "This is a test notification".to_string();
+    let mut row = Map::new();
+    row.insert(
+        "message".to_string(),
+        Value::String("This is a test notification".to_string()),
+    );
+    let rows = vec![row];
+    let now = Utc::now().timestamp_micros();
+    let (msg, email_subject) =
+        render_notification(&alert, dest_type, template, &rows, now, None, now).await;
+
+    let resp = match dest_type {
+        DestinationType::Http(endpoint) => match send_http_request(endpoint, msg).await {
+            Ok((status, body)) => {
+                return DestinationTestResponse {
+                    success: status.is_success(),
+                    status_code: Some(status.as_u16()),
+                    message: body,
+                };
+            }
+            Err(e) => Err(e),
+        },
+        DestinationType::Email(email) => send_email_notification(&email_subject, email, msg).await,
+        DestinationType::Sns(aws_sns) => send_sns_notification(&alert.name, aws_sns, msg).await,
+    };
+    match resp {
+        Ok(message) => DestinationTestResponse {
+            success: true,
+            status_code: None,
+            message,
+        },
+        Err(e) => DestinationTestResponse {
+            success: false,
+            status_code: None,
+            message: e.to_string(),
+        },
+    }
+}
+
+/// Renders the message and the email subject of a notification.
+async fn render_notification(
+    alert: &Alert,
+    dest_type: &DestinationType,
+    template: &Template,
+    rows: &[Map<String, Value>],
+    rows_end_time: i64,
+    start_time: Option<i64>,
+    evaluation_timestamp: i64,
+) -> (String, String) {
     let rows_tpl_val = if alert.row_template.is_empty() {
         vec!["".to_string()]
     } else {
@@ -830,14 +1162,34 @@ async fn send_notification(
         template.name.clone()
     };
 
-    match dest_type {
-        DestinationType::Http(endpoint) => send_http_notification(endpoint, msg).await,
-        DestinationType::Email(email) => send_email_notification(&email_subject, email, msg).await,
-        DestinationType::Sns(aws_sns) => send_sns_notification(&alert.name, aws_sns, msg).await,
-    }
+    (msg, email_subject)
 }
 
 async fn send_http_notification(endpoint: &Endpoint, msg: String) -> Result<String, anyhow::Error> {
+    let (resp_status, resp_body) = send_http_request(endpoint, msg.clone()).await?;
+    if !resp_status.is_success() {
+        log::error!(
+            "Alert http notification failed with status: {}, body: {}, payload: {}",
+            resp_status,
+            resp_body,
+            msg
+        );
+        return Err(anyhow::anyhow!(
+            "sent error status: {}, err: {}",
+            resp_status,
+            resp_body
+        ));
+    }
+
+    Ok(format!("sent status: {}, body: {}", resp_status, resp_body))
+}
+
+/// Sends the message to the endpoint and returns the status and body of the
+/// response.
+async fn send_http_request(
+    endpoint: &Endpoint,
+    msg: String,
+) -> Result<(reqwest::StatusCode, String), anyhow::Error> {
     let client = if endpoint.skip_tls_verify {
         reqwest::Client::builder()
             .danger_accept_invalid_certs(true)
@@ -869,7 +1221,7 @@ async fn send_http_notification(endpoint: &Endpoint, msg: String) -> Result<Stri
         req = req.header("Content-type", "application/json");
     }
 
-    let resp = req.body(msg.clone()).send().await?;
+    let resp = req.body(msg).send().await?;
     let resp_status = resp.status();
     let resp_body = resp.text().await?;
     log::debug!(
@@ -878,21 +1230,7 @@ async fn send_http_notification(endpoint: &Endpoint, msg: String) -> Result<Stri
         resp_status,
         resp_body,
     );
-    if !resp_status.is_success() {
-        log::error!(
-            "Alert http notification failed with status: {}, body: {}, payload: {}",
-            resp_status,
-            resp_body,
-            msg
-        );
-        return Err(anyhow::anyhow!(
-            "sent error status: {}, err: {}",
-            resp_status,
-            resp_body
-        ));
-    }
-
-    Ok(format!("sent status: {}, body: {}", resp_status, resp_body))
+    Ok((resp_status, resp_body))
 }
 
 async fn send_email_notification(
@@ -1489,6 +1827,8 @@ async fn permitted_alerts(
 
 #[cfg(test)]
 mod tests {
+    use infra::db::{connect_to_orm, ORM_CLIENT};
+
     use super::*;
 
     #[tokio::test]
@@ -1502,4 +1842,362 @@ mod tests {
         // alert name should not contain /
         assert!(ret.is_err());
     }
+
+    #[test]
+    fn test_alert_query_stream_names() {
+        let mut alert = Alert::default();
+        alert.query_condition.query_type = QueryType::SQL;
+        alert.query_condition.sql = Some(
+            "SELECT a.host, COUNT(*) AS cnt FROM \"nginx\" a JOIN \"k8s_events\" b ON a.host = b.host \
+             GROUP BY a.host UNION ALL SELECT host, COUNT(*) AS cnt FROM \"syslog\" GROUP BY host"
+                .to_string(),
+        );
+        let mut streams = query_stream_names(&alert).unwrap();
+        streams.sort();
+        assert_eq!(streams, vec!["k8s_events", "nginx", "syslog"]);
+
+        alert.query_condition.sql = Some("SELEC host FORM nginx".to_string());
+        assert!(matches!(
+            query_stream_names(&alert),
+            Err(AlertError::ResolveStreamNameError(_))
+        ));
+
+        // only SQL alerts take their streams from the query
+        alert.query_condition.query_type = QueryType::Custom;
+        assert!(query_stream_names(&alert).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_prepare_multi_stream_sql_alert() {
+        use arrow_schema::{DataType, Field, Schema};
+        use config::meta::destinations::Destination;
+        use infra::schema::{SchemaCache, STREAM_SCHEMAS_LATEST};
+
+        use crate::common::infra::config::DESTINATIONS;
+
+        infra::table::migrate().await.unwrap();
+        let org_id = "test_multi_stream_alert";
+        DESTINATIONS.insert(
+            format!("{org_id}/multi_dest"),
+            Destination {
+                id: None,
+                org_id: org_id.to_string(),
+                name: "multi_dest".to_string(),
+                module: Module::Alert {
+                    template: "".to_string(),
+                    destination_type: DestinationType::Http(Endpoint {
+                        url: "http://localhost/hook".to_string(),
+                        method: HTTPType::POST,
+                        skip_tls_verify: false,
+                        headers: None,
+                    }),
+                },
+            },
+        );
+        for stream in ["nginx", "syslog"] {
+            STREAM_SCHEMAS_LATEST.write().await.insert(
+                format!("{org_id}/{}/{stream}", StreamType::Logs),
+                SchemaCache::new(Schema::new(vec![
+                    Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false),
+                    Field::new("host", DataType::Utf8, true),
+                ])),
+            );
+        }
+        let new_alert = |sql: &str| {
+            let mut alert = Alert::default();
+            alert.name = "multi_stream".to_string();
+            alert.stream_type = StreamType::Logs;
+            alert.destinations = vec!["multi_dest".to_string()];
+            alert.query_condition.query_type = QueryType::SQL;
+            alert.query_condition.sql = Some(sql.to_string());
+            alert
+        };
+
+        // the alert's stream is taken from the query
+        let mut alert = new_alert(
+            "SELECT a.host, COUNT(*) AS cnt FROM \"nginx\" a JOIN \"syslog\" b ON a.host = b.host GROUP BY a.host",
+        );
+        prepare_alert(org_id, "", "", &mut alert, true)
+            .await
+            .unwrap();
+        assert!(["nginx", "syslog"].contains(&alert.stream_name.as_str()));
+
+        // every queried stream must exist
+        let mut alert = new_alert(
+            "SELECT a.host, COUNT(*) AS cnt FROM \"nginx\" a JOIN \"k8s_missing\" b ON a.host = b.host GROUP BY a.host",
+        );
+        let ret = prepare_alert(org_id, "nginx", "", &mut alert, true).await;
+        assert!(matches!(
+            ret,
+            Err(AlertError::StreamNotFound { stream_name }) if stream_name == "k8s_missing"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_prepare_alert_template_locale() {
+        infra::table::migrate().await.unwrap();
+        let mut alert = Alert::default();
+        alert.name = "locale_alert".to_string();
+        alert.template_locale = Some("x".repeat(TEMPLATE_LOCALE_MAX_LEN + 1));
+        let ret = prepare_alert("test_alert_locale", "nginx", "", &mut alert, true).await;
+        assert!(matches!(
+            ret,
+            Err(AlertError::TemplateLocaleTooLong { max_len }) if max_len == 32
+        ));
+
+        // the locale is trimmed before it's checked
+        alert.template_locale = Some(format!(" {} ", "x".repeat(TEMPLATE_LOCALE_MAX_LEN)));
+        let ret = prepare_alert("test_alert_locale", "nginx", "", &mut alert, true).await;
+        assert!(!matches!(
+            ret,
+            Err(AlertError::TemplateLocaleTooLong { .. })
+        ));
+        assert_eq!(
+            alert.template_locale.as_deref(),
+            Some("x".repeat(TEMPLATE_LOCALE_MAX_LEN).as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prepare_alert_destination_templates() {
+        infra::table::migrate().await.unwrap();
+        let org_id = "test_alert_dest_templates";
+        for name in ["dest_default", "dest_terse"] {
+            db::alerts::templates::set(Template {
+                id: None,
+                org_id: org_id.to_string(),
+                name: name.to_string(),
+                is_default: false,
+                template_type: TemplateType::Http,
+                body: format!(r#"{{"text":"{name}"}}"#),
+                locales: Default::default(),
+            })
+            .await
+            .unwrap();
+        }
+        db::alerts::destinations::set(config::meta::destinations::Destination {
+            id: None,
+            org_id: org_id.to_string(),
+            name: "dest_hook".to_string(),
+            module: Module::Alert {
+                template: "dest_default".to_string(),
+                destination_type: DestinationType::Http(Endpoint {
+                    url: "http://localhost/hook".to_string(),
+                    method: HTTPType::POST,
+                    skip_tls_verify: false,
+                    headers: None,
+                }),
+            },
+        })
+        .await
+        .unwrap();
+
+        let mut alert = Alert::default();
+        alert.name = "dest_template_alert".to_string();
+        alert.destinations = vec!["dest_hook".to_string()];
+        alert.destination_templates = Some(hashbrown::HashMap::from_iter([(
+            "other".to_string(),
+            "dest_terse".to_string(),
+        )]));
+        let ret = prepare_alert(org_id, "nginx", "", &mut alert, true).await;
+        assert!(matches!(
+            ret,
+            Err(AlertError::AlertDestinationTemplateUnknownDestination { dest }) if dest == "other"
+        ));
+
+        alert.destination_templates = Some(hashbrown::HashMap::from_iter([(
+            "dest_hook".to_string(),
+            "missing".to_string(),
+        )]));
+        let ret = prepare_alert(org_id, "nginx", "", &mut alert, true).await;
+        assert!(matches!(
+            ret,
+            Err(AlertError::AlertDestinationTemplateNotFound { template }) if template == "missing"
+        ));
+
+        // valid templates pass, the alert then fails on its missing stream
+        alert.destination_templates = Some(hashbrown::HashMap::from_iter([(
+            "dest_hook".to_string(),
+            "dest_terse".to_string(),
+        )]));
+        let ret = prepare_alert(org_id, "nginx", "", &mut alert, true).await;
+        assert!(matches!(ret, Err(AlertError::StreamNotFound { .. })));
+
+        let (_, template) = destinations::get_with_template(
+            org_id,
+            "dest_hook",
+            alert.get_destination_template("dest_hook"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(template.name, "dest_terse");
+        let (_, template) = destinations::get_with_template(org_id, "dest_hook", None)
+            .await
+            .unwrap();
+        assert_eq!(template.name, "dest_default");
+    }
+
+    #[test]
+    fn test_backtest_run_times() {
+        let minute = 60_000_000;
+        let mut alert = Alert::default();
+        alert.trigger_condition.frequency = 120;
+        let run_times = backtest_run_times(&alert, 0, 10 * minute).unwrap();
+        assert_eq!(run_times.len(), 5);
+        assert_eq!(run_times[0], 2 * minute);
+        assert_eq!(run_times[4], 10 * minute);
+        assert!(matches!(
+            backtest_run_times(&alert, 0, 3000 * minute),
+            Err(AlertError::BacktestTooManyIntervals { .. })
+        ));
+
+        // cron alerts run on their schedule, whatever their period
+        alert.trigger_condition.frequency_type = FrequencyType::Cron;
+        alert.trigger_condition.cron = "0 */15 * * * *".to_string();
+        alert.trigger_condition.period = 5;
+        let run_times = backtest_run_times(&alert, 0, 60 * minute).unwrap();
+        assert_eq!(
+            run_times,
+            vec![15 * minute, 30 * minute, 45 * minute, 60 * minute]
+        );
+        alert.tz_offset = i32::MAX;
+        assert!(matches!(
+            backtest_run_times(&alert, 0, 60 * minute),
+            Err(AlertError::BacktestInvalidTriggerCondition)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_backtest_invalid_trigger_condition() {
+        let mut alert = Alert::default();
+        alert.stream_name = "app".to_string();
+        alert.trigger_condition.period = i64::MAX;
+        assert!(matches!(
+            backtest("default", alert.clone(), 0, 60_000_000).await,
+            Err(AlertError::BacktestInvalidTriggerCondition)
+        ));
+
+        alert.trigger_condition.period = 10;
+        alert.trigger_condition.frequency = i64::MAX;
+        assert!(matches!(
+            backtest("default", alert, 0, 60_000_000).await,
+            Err(AlertError::BacktestInvalidTriggerCondition)
+        ));
+    }
+
+    #[test]
+    fn test_alert_destination_templates() {
+        let mut alert = Alert::default();
+        alert.destinations = vec!["slack".to_string(), "email".to_string(), "web".to_string()];
+        assert_eq!(alert.get_destination_template("slack"), None);
+
+        alert.destination_templates = Some(hashbrown::HashMap::from_iter([
+            ("slack".to_string(), "terse".to_string()),
+            ("email".to_string(), "verbose".to_string()),
+            ("web".to_string(), "".to_string()),
+        ]));
+        assert_eq!(alert.get_destination_template("slack"), Some("terse"));
+        assert_eq!(alert.get_destination_template("email"), Some("verbose"));
+        // an empty mapping falls back to the destination's own template
+        assert_eq!(alert.get_destination_template("web"), None);
+        assert_eq!(alert.get_destination_template("other"), None);
+    }
+
+    #[tokio::test]
+    async fn test_send_test_notification_http() {
+        use tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::TcpListener,
+        };
+
+        // a mock webhook which accepts a single request and returns its body
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let webhook = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = Vec::new();
+            let mut chunk = [0; 1024];
+            loop {
+                let n = socket.read(&mut chunk).await.unwrap();
+                buf.extend_from_slice(&chunk[..n]);
+                let req = String::from_utf8_lossy(&buf).to_string();
+                if let Some((headers, body)) = req.split_once("\r\n\r\n") {
+                    let content_length = headers
+                        .lines()
+                        .find_map(|l| {
+                            let (k, v) = l.split_once(':')?;
+                            k.eq_ignore_ascii_case("content-length")
+                                .then(|| v.trim().parse::<usize>().ok())?
+                        })
+                        .unwrap_or(0);
+                    if n == 0 || body.len() >= content_length {
+                        socket
+                            .write_all(b"HTTP/1.1 202 Accepted\r\ncontent-length: 6\r\n\r\nqueued")
+                            .await
+                            .unwrap();
+                        return body.to_string();
+                    }
+                }
+            }
+        });
+
+        let dest_type = DestinationType::Http(Endpoint {
+            url: format!("http://{addr}/hook"),
+            method: HTTPType::POST,
+            skip_tls_verify: false,
+            headers: None,
+        });
+        let template = Template {
+            id: None,
+            org_id: "default".to_string(),
+            name: "test_template".to_string(),
+            is_default: false,
+            template_type: TemplateType::Http,
+            body: r#"{"text":"{alert_name} fired"}"#.to_string(),
+            locales: Default::default(),
+        };
+        let resp = send_test_notification("default", &dest_type, &template).await;
+        assert!(resp.success);
+        assert_eq!(resp.status_code, Some(202));
+        assert_eq!(resp.message, "queued");
+
+        let payload = webhook.await.unwrap();
+        assert_eq!(payload, r#"{"text":"test_alert fired"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_move_to_folder() {
+        let org_id = "test_move_alerts";
+        let client = ORM_CLIENT.get_or_init(connect_to_orm).await;
+
+        // moving into a folder that doesn't exist is rejected before any alert is read
+        let ret = move_to_folder(client, org_id, &[Ksuid::new(None, None)], "missing").await;
+        assert!(matches!(
+            ret,
+            Err(AlertError::MoveDestinationFolderNotFound)
+        ));
+
+        let folder = Folder {
+            folder_id: "".to_string(),
+            name: "move_target".to_string(),
+            description: "".to_string(),
+        };
+        let folder = match folders::save_folder(org_id, folder, FolderType::Alerts, false).await {
+            Ok(folder) => folder,
+            Err(folders::FolderError::FolderNameAlreadyExists) => {
+                folders::get_folder_by_name(org_id, "move_target", FolderType::Alerts)
+                    .await
+                    .unwrap()
+            }
+            Err(e) => panic!("save folder error: {e}"),
+        };
+        assert!(move_to_folder(client, org_id, &[], &folder.folder_id)
+            .await
+            .is_ok());
+
+        // an unknown alert fails the whole move
+        let ret =
+            move_to_folder(client, org_id, &[Ksuid::new(None, None)], &folder.folder_id).await;
+        assert!(matches!(ret, Err(AlertError::AlertNotFound)));
+    }
 }
@@ -0,0 +1,207 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use config::meta::{
+    alerts::{
+        alert::ListAlertsParams,
+        bundle::{AlertConfigBundle, BundleAlert},
+    },
+    folder::{FolderType, DEFAULT_FOLDER},
+};
+use infra::{
+    db::{connect_to_orm, ORM_CLIENT},
+    table,
+};
+
+use crate::service::{
+    alerts::{
+        alert::{self, AlertError},
+        destinations, templates,
+    },
+    db::{
+        self,
+        alerts::{destinations::DestinationError, templates::TemplateError},
+    },
+    folders::{self, FolderError},
+};
+
+/// Errors that can occur when exporting or importing the alerting
+/// configuration of an org.
+#[derive(Debug, thiserror::Error)]
+pub enum AlertConfigError {
+    #[error("Failed to read the alert config: {0}")]
+    Export(String),
+
+    #[error("Unsupported alert config version {0}")]
+    UnsupportedVersion(u32),
+
+    /// A template referenced by the imported config is neither part of it nor
+    /// in the org.
+    #[error("Template {0} not found")]
+    TemplateNotFound(String),
+
+    /// A destination referenced by the imported config is neither part of it
+    /// nor in the org.
+    #[error("Destination {0} not found")]
+    DestinationNotFound(String),
+
+    #[error("Template {0}: {1}")]
+    Template(String, TemplateError),
+
+    #[error("Destination {0}: {1}")]
+    Destination(String, DestinationError),
+
+    #[error("Folder {0}: {1}")]
+    Folder(String, FolderError),
+
+    #[error("Alert {0}: {1}")]
+    Alert(String, AlertError),
+}
+
+/// Exports the alerts of the org with their folders and the templates and
+/// destinations of the org. The templates of the default org, available to every org, are left
+/// out.
+pub async fn export_config(org_id: &str) -> Result<AlertConfigBundle, AlertConfigError> {
+    let templates = templates::list(org_id, None)
+        .await
+        .map_err(|e| AlertConfigError::Export(e.to_string()))?
+        .into_iter()
+        .filter(|t| t.org_id == org_id)
+        .collect();
+    let destinations = destinations::list(org_id, None, None)
+        .await
+        .map_err(|e| AlertConfigError::Export(e.to_string()))?;
+    let client = ORM_CLIENT.get_or_init(connect_to_orm).await;
+    let mut folders = HashMap::new();
+    let alerts = db::alerts::alert::list_with_folders(client, ListAlertsParams::new(org_id))
+        .await
+        .map_err(|e| AlertConfigError::Export(e.to_string()))?
+        .into_iter()
+        .map(|(folder, alert)| {
+            let folder_id = folder.folder_id.clone();
+            if folder_id != DEFAULT_FOLDER {
+                folders.entry(folder_id.clone()).or_insert(folder);
+            }
+            BundleAlert { folder_id, alert }
+        })
+        .collect();
+    let folders = folders.into_values().collect();
+    Ok(AlertConfigBundle::new(
+        templates,
+        destinations,
+        folders,
+        alerts,
+    ))
+}
+
+/// Imports an exported alerting configuration into the org, creating the
+/// templates, destinations, folders and alerts missing from the org and
+/// updating the existing ones, matched by name.
+///
+/// The references of the config to templates and destinations it doesn't
+/// include are checked against the org before anything is written.
+pub async fn import_config(
+    org_id: &str,
+    mut bundle: AlertConfigBundle,
+) -> Result<(), AlertConfigError> {
+    if bundle.version == 0 || bundle.version > AlertConfigBundle::VERSION {
+        return Err(AlertConfigError::UnsupportedVersion(bundle.version));
+    }
+    bundle.set_org(org_id);
+
+    let refs = bundle.external_references();
+    for name in refs.templates {
+        if templates::get(org_id, &name).await.is_err() {
+            return Err(AlertConfigError::TemplateNotFound(name));
+        }
+    }
+    for name in refs.destinations {
+        if destinations::get(org_id, &name).await.is_err() {
+            return Err(AlertConfigError::DestinationNotFound(name));
+        }
+    }
+
+    // templates first, then the destinations using them and the alerts using
+    // the destinations
+    for template in bundle.templates {
+        let name = template.name.clone();
+        let ret = match templates::get(org_id, &name).await {
+            // a template of the default org has the same name
+            Ok(existing) if existing.org_id != org_id => Err(TemplateError::AlreadyExists),
+            Ok(_) => templates::save(&name, template, false).await,
+            Err(_) => templates::save("", template, true).await,
+        };
+        ret.map_err(|e| AlertConfigError::Template(name, e))?;
+    }
+
+    for destination in bundle.destinations {
+        let name = destination.name.clone();
+        let ret = if destinations::get(org_id, &name).await.is_ok() {
+            destinations::save(&name, destination, false).await
+        } else {
+            destinations::save("", destination, true).await
+        };
+        ret.map_err(|e| AlertConfigError::Destination(name, e))?;
+    }
+
+    // the folders of the bundle map to the folders of the org with the same
+    // name, created when missing
+    let mut folder_ids = HashMap::new();
+    for folder in bundle.folders {
+        let name = folder.name.clone();
+        let bundle_folder_id = folder.folder_id.clone();
+        let folder_id = match folders::get_folder_by_name(org_id, &name, FolderType::Alerts).await {
+            Ok(existing) => existing.folder_id,
+            Err(FolderError::NotFound) => {
+                folders::save_folder(org_id, folder, FolderType::Alerts, false)
+                    .await
+                    .map_err(|e| AlertConfigError::Folder(name, e))?
+                    .folder_id
+            }
+            Err(e) => return Err(AlertConfigError::Folder(name, e)),
+        };
+        folder_ids.insert(bundle_folder_id, folder_id);
+    }
+
+    let client = ORM_CLIENT.get_or_init(connect_to_orm).await;
+    for BundleAlert {
+        folder_id,
+        mut alert,
+    } in bundle.alerts
+    {
+        let name = alert.name.clone();
+        let folder_id = match folder_ids.get(&folder_id) {
+            Some(id) => id.clone(),
+            None => match table::folders::exists(org_id, &folder_id, FolderType::Alerts).await {
+                Ok(true) => folder_id,
+                _ => DEFAULT_FOLDER.to_string(),
+            },
+        };
+        let existing =
+            alert::get_by_name(org_id, alert.stream_type, &alert.stream_name, &alert.name).await;
+        let ret = match existing {
+            Ok(Some(existing)) => {
+                alert.id = existing.id;
+                alert::update(client, org_id, Some(&folder_id), alert).await
+            }
+            Ok(None) => alert::create(client, org_id, &folder_id, alert).await,
+            Err(e) => Err(e),
+        };
+        ret.map_err(|e| AlertConfigError::Alert(name, e))?;
+    }
+    Ok(())
+}
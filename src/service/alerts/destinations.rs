@@ -13,7 +13,9 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use config::meta::destinations::{Destination, DestinationType, Module, Template};
+use config::meta::destinations::{
+    Destination, DestinationTestResponse, DestinationType, Module, Template,
+};
 
 use crate::{
     common::{
@@ -106,12 +108,16 @@ pub async fn get(org_id: &str, name: &str) -> Result<Destination, DestinationErr
     db::alerts::destinations::get(org_id, name).await
 }
 
+/// Gets the destination and the template to render for it. The template of the
+/// destination is used unless a `template_override` is given.
 pub async fn get_with_template(
     org_id: &str,
     name: &str,
+    template_override: Option<&str>,
 ) -> Result<(Destination, Template), DestinationError> {
     let dest = get(org_id, name).await?;
     if let Module::Alert { template, .. } = &dest.module {
+        let template = template_override.unwrap_or(template);
         let template = db::alerts::templates::get(org_id, template)
             .await
             .map_err(|_| DestinationError::TemplateNotFound)?;
@@ -121,6 +127,21 @@ pub async fn get_with_template(
     }
 }
 
+/// Sends a test notification to the destination using its template.
+pub async fn test_destination(
+    org_id: &str,
+    name: &str,
+) -> Result<DestinationTestResponse, DestinationError> {
+    let (dest, template) = get_with_template(org_id, name, None).await?;
+    let Module::Alert {
+        destination_type, ..
+    } = &dest.module
+    else {
+        return Err(DestinationError::UnsupportedType);
+    };
+    Ok(super::alert::send_test_notification(org_id, destination_type, &template).await)
+}
+
 pub async fn list(
     org_id: &str,
     module: Option<&str>,
@@ -36,6 +36,7 @@ use super::promql;
 use crate::service::search as SearchService;
 
 pub mod alert;
+pub mod bundle;
 pub mod derived_streams;
 pub mod destinations;
 pub mod scheduler;
@@ -365,6 +366,10 @@ impl QueryConditionExt for QueryCondition {
                     skip_wal: false,
                     streaming_output: false,
                     streaming_id: None,
+                    search_after: None,
+                    quick_scan: false,
+                    segment_ids: None,
+                    params: None,
                 },
                 encoding: config::meta::search::RequestEncoding::Empty,
                 regions: vec![],
@@ -29,6 +29,7 @@ use config::{
         stream::{StreamParams, StreamType},
         triggers::ScheduledTriggerData,
     },
+    metrics,
     utils::{
         json,
         rand::get_rand_num_within,
@@ -52,6 +53,18 @@ use crate::service::{
     self_reporting::publish_triggers_usage,
 };
 
+/// Records how long an alert evaluation took and whether it failed.
+fn record_alert_evaluation(org_id: &str, alert_name: &str, took_secs: f64, failed: bool) {
+    metrics::ALERT_EVAL_DURATION
+        .with_label_values(&[org_id, alert_name])
+        .observe(took_secs);
+    if failed {
+        metrics::ALERT_EVAL_FAILURES
+            .with_label_values(&[org_id, alert_name])
+            .inc();
+    }
+}
+
 pub async fn run() -> Result<(), anyhow::Error> {
     let trace_id = ider::generate();
     log::debug!("[SCHEDULER trace_id {trace_id}] Pulling jobs from scheduler");
@@ -362,6 +375,7 @@ async fn handle_alert_triggers(
     let result = alert.evaluate(None, (start_time, now)).await;
     let evaluation_took = evaluation_took.elapsed().as_secs_f64();
     trigger_data_stream.evaluation_took_in_secs = Some(evaluation_took);
+    record_alert_evaluation(&org_id, alert_name, evaluation_took, result.is_err());
     if result.is_err() {
         let err = result.err().unwrap();
         trigger_data_stream.status = TriggerDataStatus::Failed;
@@ -1235,3 +1249,25 @@ async fn handle_derived_stream_triggers(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_alert_evaluation() {
+        let (org_id, alert_name) = ("test_org", "test_record_alert_evaluation");
+        let duration = metrics::ALERT_EVAL_DURATION.with_label_values(&[org_id, alert_name]);
+        let failures = metrics::ALERT_EVAL_FAILURES.with_label_values(&[org_id, alert_name]);
+
+        record_alert_evaluation(org_id, alert_name, 0.5, false);
+        assert_eq!(duration.get_sample_count(), 1);
+        assert_eq!(duration.get_sample_sum(), 0.5);
+        assert_eq!(failures.get(), 0);
+
+        record_alert_evaluation(org_id, alert_name, 1.5, true);
+        assert_eq!(duration.get_sample_count(), 2);
+        assert_eq!(duration.get_sample_sum(), 2.0);
+        assert_eq!(failures.get(), 1);
+    }
+}
@@ -43,6 +43,19 @@ pub async fn save(name: &str, mut template: Template, create: bool) -> Result<()
             return Err(TemplateError::EmptyTitle);
         }
     }
+    template.locales = template
+        .locales
+        .into_iter()
+        .map(|(locale, variant)| (locale.trim().to_string(), variant))
+        .collect();
+    for (locale, variant) in template.locales.iter() {
+        if locale.is_empty() {
+            return Err(TemplateError::EmptyLocale);
+        }
+        if variant.body.is_empty() {
+            return Err(TemplateError::EmptyLocaleBody(locale.to_string()));
+        }
+    }
 
     match db::alerts::templates::get(&template.org_id, &template.name).await {
         Ok(existing) => {
@@ -0,0 +1,340 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use config::{ider, meta::stream::StreamType, utils::time::now_micros};
+use infra::file_list::{self as infra_file_list, FileListJobStatus};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::service::{db, stream};
+
+/// A compaction requested through the API, tracked by the merge jobs it
+/// created in `file_list_jobs`.
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct ManualCompactJob {
+    pub id: String,
+    pub org_id: String,
+    pub stream_type: StreamType,
+    pub stream_name: String,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub created_at: i64,
+    pub merge_jobs: Vec<i64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ManualCompactState {
+    Pending,
+    Running,
+    Done,
+}
+
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct ManualCompactStatus {
+    pub id: String,
+    pub stream_type: StreamType,
+    pub stream_name: String,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub created_at: i64,
+    pub status: ManualCompactState,
+    pub total: usize,
+    pub pending: usize,
+    pub running: usize,
+    pub done: usize,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ManualCompactError {
+    #[error("stream {0} not found")]
+    StreamNotFound(String),
+    #[error("stream {0} is being deleted")]
+    StreamDeleting(String),
+    #[error("start_time must be before end_time")]
+    InvalidTimeRange,
+    #[error("compaction job {0} not found")]
+    JobNotFound(String),
+    #[error(transparent)]
+    Infra(#[from] infra::errors::Error),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+/// Creates merge jobs for the hours of the stream within `time_range` that
+/// have enough small files, the whole stream when not set. Hours after the
+/// compaction offset are left to the scheduled compaction.
+pub async fn compact_stream(
+    org_id: &str,
+    stream_type: StreamType,
+    stream_name: &str,
+    time_range: Option<(i64, i64)>,
+) -> Result<ManualCompactJob, ManualCompactError> {
+    let schema = infra::schema::get(org_id, stream_name, stream_type).await?;
+    if schema.fields().is_empty() {
+        return Err(ManualCompactError::StreamNotFound(stream_name.to_string()));
+    }
+    if db::compact::retention::is_deleting_stream(org_id, stream_type, stream_name, None) {
+        return Err(ManualCompactError::StreamDeleting(stream_name.to_string()));
+    }
+    let (start_time, end_time) = time_range.unwrap_or((
+        stream::stream_created(&schema).unwrap_or_default(),
+        now_micros(),
+    ));
+    if start_time >= end_time {
+        return Err(ManualCompactError::InvalidTimeRange);
+    }
+
+    let (offset, _) = db::compact::files::get_offset(org_id, stream_type, stream_name).await;
+    let end = end_time.min(offset);
+    let hours = if start_time < end {
+        infra_file_list::query_old_data_hours(
+            org_id,
+            stream_type,
+            stream_name,
+            Some((start_time, end - 1)),
+        )
+        .await?
+    } else {
+        vec![]
+    };
+
+    let mut merge_jobs = Vec::with_capacity(hours.len());
+    for hour in hours {
+        let offset = hour_offset(&hour)?;
+        merge_jobs.push(infra_file_list::add_job(org_id, stream_type, stream_name, offset).await?);
+    }
+    // the hours may have been merged before, run their finished jobs again
+    let done = infra_file_list::get_jobs_status(&merge_jobs)
+        .await?
+        .into_iter()
+        .filter_map(|(id, status)| (status == FileListJobStatus::Done).then_some(id))
+        .collect::<Vec<_>>();
+    if !done.is_empty() {
+        infra_file_list::set_job_pending(&done).await?;
+    }
+
+    let job = ManualCompactJob {
+        id: ider::uuid(),
+        org_id: org_id.to_string(),
+        stream_type,
+        stream_name: stream_name.to_string(),
+        start_time,
+        end_time,
+        created_at: now_micros(),
+        merge_jobs,
+    };
+    db::compact::manual::set(&job).await?;
+    log::info!(
+        "[COMPACTOR] manual compaction {} [{}/{}/{}] created {} merge jobs",
+        job.id,
+        org_id,
+        stream_type,
+        stream_name,
+        job.merge_jobs.len()
+    );
+    Ok(job)
+}
+
+pub async fn get_status(org_id: &str, id: &str) -> Result<ManualCompactStatus, ManualCompactError> {
+    let Some(job) = db::compact::manual::get(org_id, id).await? else {
+        return Err(ManualCompactError::JobNotFound(id.to_string()));
+    };
+    let statuses = infra_file_list::get_jobs_status(&job.merge_jobs).await?;
+    Ok(job_status(job, &statuses))
+}
+
+/// Done merge jobs are cleaned up after a while, missing jobs count as done.
+fn job_status(
+    job: ManualCompactJob,
+    statuses: &HashMap<i64, FileListJobStatus>,
+) -> ManualCompactStatus {
+    let (mut pending, mut running, mut done) = (0, 0, 0);
+    for id in job.merge_jobs.iter() {
+        match statuses.get(id) {
+            Some(FileListJobStatus::Pending) => pending += 1,
+            Some(FileListJobStatus::Running) => running += 1,
+            Some(FileListJobStatus::Done) | None => done += 1,
+        }
+    }
+    let status = if running > 0 || (pending > 0 && done > 0) {
+        ManualCompactState::Running
+    } else if pending > 0 {
+        ManualCompactState::Pending
+    } else {
+        ManualCompactState::Done
+    };
+    ManualCompactStatus {
+        id: job.id,
+        stream_type: job.stream_type,
+        stream_name: job.stream_name,
+        start_time: job.start_time,
+        end_time: job.end_time,
+        created_at: job.created_at,
+        status,
+        total: job.merge_jobs.len(),
+        pending,
+        running,
+        done,
+    }
+}
+
+/// Converts a file list date, `YYYY/MM/DD/HH`, to the hour offset of the
+/// merge job.
+fn hour_offset(hour: &str) -> Result<i64, anyhow::Error> {
+    let column = hour.split('/').collect::<Vec<_>>();
+    if column.len() != 4 {
+        return Err(anyhow::anyhow!(
+            "Unexpected hour format in {}, Expected format YYYY/MM/DD/HH",
+            hour
+        ));
+    }
+    let offset = DateTime::parse_from_rfc3339(&format!(
+        "{}-{}-{}T{}:00:00Z",
+        column[0], column[1], column[2], column[3]
+    ))?
+    .with_timezone(&Utc);
+    Ok(offset.timestamp_micros())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(merge_jobs: Vec<i64>) -> ManualCompactJob {
+        ManualCompactJob {
+            id: "job".to_string(),
+            org_id: "default".to_string(),
+            stream_type: StreamType::Logs,
+            stream_name: "default".to_string(),
+            start_time: 0,
+            end_time: 1,
+            created_at: 1,
+            merge_jobs,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compact_stream() {
+        use arrow_schema::{DataType, Field, Schema};
+        use config::{
+            get_config,
+            meta::stream::{FileKey, FileMeta},
+            TIMESTAMP_COL_NAME,
+        };
+        use infra::schema::{SchemaCache, STREAM_SCHEMAS_LATEST};
+
+        infra_file_list::create_table().await.unwrap();
+        let org_id = "test_compact_stream";
+        let stream_name = "manual_logs";
+        STREAM_SCHEMAS_LATEST.write().await.insert(
+            format!("{org_id}/{}/{stream_name}", StreamType::Logs),
+            SchemaCache::new(Schema::new(vec![Field::new(
+                TIMESTAMP_COL_NAME,
+                DataType::Int64,
+                false,
+            )])),
+        );
+        // enough small files in one hour, which the scheduled compaction passed
+        let hour = hour_offset("2025/01/02/03").unwrap();
+        let files = (0..get_config().compact.old_data_min_files)
+            .map(|i| {
+                FileKey::new(
+                    format!("files/{org_id}/logs/{stream_name}/2025/01/02/03/manual_{i}.parquet"),
+                    FileMeta {
+                        min_ts: hour + i,
+                        max_ts: hour + i + 1,
+                        records: 1,
+                        original_size: 100,
+                        compressed_size: 10,
+                        ..Default::default()
+                    },
+                    false,
+                )
+            })
+            .collect::<Vec<_>>();
+        infra_file_list::batch_add(&files).await.unwrap();
+        db::compact::files::set_offset(
+            org_id,
+            StreamType::Logs,
+            stream_name,
+            hour + 2 * 3_600_000_000,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let range = Some((hour, hour + 3_600_000_000));
+        let job = compact_stream(org_id, StreamType::Logs, stream_name, range)
+            .await
+            .unwrap();
+        assert_eq!(job.merge_jobs.len(), 1);
+
+        let status = get_status(org_id, &job.id).await.unwrap();
+        assert_eq!(status.status, ManualCompactState::Pending);
+        assert_eq!((status.total, status.pending), (1, 1));
+
+        assert!(matches!(
+            compact_stream(org_id, StreamType::Logs, stream_name, Some((hour, hour))).await,
+            Err(ManualCompactError::InvalidTimeRange)
+        ));
+        assert!(matches!(
+            compact_stream(org_id, StreamType::Logs, "missing_logs", range).await,
+            Err(ManualCompactError::StreamNotFound(_))
+        ));
+        assert!(matches!(
+            get_status(org_id, "missing_job").await,
+            Err(ManualCompactError::JobNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_hour_offset() {
+        assert_eq!(hour_offset("2025/01/02/03").unwrap(), 1735786800000000);
+        assert!(hour_offset("2025/01/02").is_err());
+        assert!(hour_offset("2025/13/02/03").is_err());
+    }
+
+    #[test]
+    fn test_job_status() {
+        let statuses = HashMap::from([
+            (1, FileListJobStatus::Pending),
+            (2, FileListJobStatus::Pending),
+            (3, FileListJobStatus::Running),
+            (4, FileListJobStatus::Done),
+        ]);
+
+        let status = job_status(job(vec![1, 2]), &statuses);
+        assert_eq!(status.status, ManualCompactState::Pending);
+        assert_eq!((status.total, status.pending), (2, 2));
+
+        let status = job_status(job(vec![1, 3, 4]), &statuses);
+        assert_eq!(status.status, ManualCompactState::Running);
+        assert_eq!((status.pending, status.running, status.done), (1, 1, 1));
+
+        // merge jobs cleaned up after finishing count as done
+        let status = job_status(job(vec![4, 5]), &statuses);
+        assert_eq!(status.status, ManualCompactState::Done);
+        assert_eq!(status.done, 2);
+
+        // nothing to compact
+        let status = job_status(job(vec![]), &statuses);
+        assert_eq!(status.status, ManualCompactState::Done);
+        assert_eq!(status.total, 0);
+    }
+}
@@ -1104,11 +1104,13 @@ async fn generate_inverted_index(
         InvertedIndexFormat::Tantivy | InvertedIndexFormat::Both
     ) {
         let (schema, mut reader) = get_recordbatch_reader_from_bytes(buf).await?;
+        let stream_settings = infra::schema::get_settings(org_id, stream_name, stream_type).await;
         let index_size =  create_tantivy_index(
                 "COMPACTOR",
                 new_file_key,
                 full_text_search_fields,
                 index_fields,
+                &infra::schema::get_stream_setting_split_chars(&stream_settings),
                 schema,
                 &mut reader,
             )
@@ -34,6 +34,7 @@ use crate::{common::infra::cluster::get_node_from_consistent_hash, service::db};
 
 pub mod deleted;
 pub mod flatten;
+pub mod manual;
 pub mod merge;
 pub mod retention;
 pub mod stats;
@@ -0,0 +1,221 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use config::meta::{
+    dashboards::{
+        bundle::{BundleDashboard, BundleFolder, DashboardConfigBundle},
+        Dashboard, ListDashboardsParams,
+    },
+    folder::{FolderType, DEFAULT_FOLDER},
+};
+use infra::table;
+
+use super::{
+    create, create_default_folder, move_dashboard, update_dashboard, validate_dashboard,
+    DashboardError,
+};
+use crate::service::folders::{self, FolderError};
+
+/// Errors that can occur when exporting or importing the dashboards of an org.
+#[derive(Debug, thiserror::Error)]
+pub enum DashboardConfigError {
+    #[error("Failed to read the dashboards: {0}")]
+    Export(String),
+
+    #[error("Unsupported dashboard config version {0}")]
+    UnsupportedVersion(u32),
+
+    /// A folder holding imported dashboards is neither part of the imported
+    /// config nor in the org.
+    #[error("Folder {0} not found")]
+    FolderNotFound(String),
+
+    /// Dashboards are matched by id on import, so each must have one.
+    #[error("Dashboard {0} has no id")]
+    MissingDashboardId(String),
+
+    #[error("Folder {0}: {1}")]
+    Folder(String, FolderError),
+
+    #[error("Dashboard {0}: {1}")]
+    Dashboard(String, DashboardError),
+}
+
+/// Exports the dashboard folders of the org with the dashboards in them. The
+/// default folder is left out, it's created on import when needed.
+pub async fn export_all(org_id: &str) -> Result<DashboardConfigBundle, DashboardConfigError> {
+    let folders = table::folders::list_folders(org_id, FolderType::Dashboards)
+        .await
+        .map_err(|e| DashboardConfigError::Export(e.to_string()))?
+        .into_iter()
+        .filter(|f| f.folder_id != DEFAULT_FOLDER)
+        .map(BundleFolder::from)
+        .collect();
+    let dashboards = table::dashboards::list(ListDashboardsParams::new(org_id))
+        .await
+        .map_err(|e| DashboardConfigError::Export(e.to_string()))?
+        .into_iter()
+        .map(|(folder, dashboard)| BundleDashboard {
+            folder_id: folder.folder_id,
+            dashboard,
+        })
+        .collect();
+    Ok(DashboardConfigBundle::new(folders, dashboards))
+}
+
+/// Imports exported dashboards and their folders into the org. Folders and
+/// dashboards are matched by id: the missing ones are created with the id they
+/// were exported with and the existing ones are updated, dashboards moving to
+/// the folder they are in in the config. A folder missing from the org is
+/// matched by name before it's created, so the dashboards of a config exported
+/// from another org go to the folder of the same name.
+///
+/// The dashboards are validated and the folders checked before anything is
+/// written.
+pub async fn import_all(
+    org_id: &str,
+    bundle: DashboardConfigBundle,
+) -> Result<(), DashboardConfigError> {
+    if bundle.version == 0 || bundle.version > DashboardConfigBundle::VERSION {
+        return Err(DashboardConfigError::UnsupportedVersion(bundle.version));
+    }
+
+    for folder_id in bundle.external_folders() {
+        match table::folders::exists(org_id, &folder_id, FolderType::Dashboards).await {
+            Ok(true) => {}
+            Ok(false) => return Err(DashboardConfigError::FolderNotFound(folder_id)),
+            Err(e) => {
+                return Err(DashboardConfigError::Folder(folder_id, e.into()));
+            }
+        }
+    }
+    for BundleDashboard { dashboard, .. } in bundle.dashboards.iter() {
+        let title = dashboard.title().unwrap_or_default().to_string();
+        if dashboard.dashboard_id().is_none_or(|id| id.is_empty()) {
+            return Err(DashboardConfigError::MissingDashboardId(title));
+        }
+        validate_dashboard(org_id, dashboard)
+            .await
+            .map_err(|e| DashboardConfigError::Dashboard(title, e))?;
+    }
+
+    let mut folder_ids = HashMap::new();
+    let mut folder_writes = Vec::new();
+    for folder in bundle.folders {
+        // created on demand by the dashboards in it
+        if folder.folder_id == DEFAULT_FOLDER {
+            continue;
+        }
+        let name = folder.name.clone();
+        let bundle_folder_id = folder.folder_id.clone();
+        let (folder_id, write) = resolve_folder(org_id, folder)
+            .await
+            .map_err(|e| DashboardConfigError::Folder(name, e))?;
+        folder_ids.insert(bundle_folder_id, folder_id);
+        folder_writes.extend(write);
+    }
+
+    for (exists, folder) in folder_writes {
+        let name = folder.name.clone();
+        let ret = import_folder(org_id, exists, folder).await;
+        ret.map_err(|e| DashboardConfigError::Folder(name, e))?;
+    }
+
+    for BundleDashboard {
+        folder_id,
+        dashboard,
+    } in bundle.dashboards
+    {
+        let title = dashboard.title().unwrap_or_default().to_string();
+        let folder_id = folder_ids.get(&folder_id).unwrap_or(&folder_id);
+        let ret = import_dashboard(org_id, folder_id, dashboard).await;
+        ret.map_err(|e| DashboardConfigError::Dashboard(title, e))?;
+    }
+    Ok(())
+}
+
+/// Finds the folder of the org an imported folder maps to, with the folder to
+/// create or update in the org if any. Nothing is written, so that a conflict
+/// is reported before the import changes the org.
+async fn resolve_folder(
+    org_id: &str,
+    mut folder: BundleFolder,
+) -> Result<(String, Option<(bool, BundleFolder)>), FolderError> {
+    folder.name = folder.name.trim().to_string();
+    if folder.name.is_empty() {
+        return Err(FolderError::MissingName);
+    }
+    let same_name = table::folders::get_by_name(org_id, &folder.name, FolderType::Dashboards)
+        .await?
+        .map(|f| f.folder_id);
+    if table::folders::exists(org_id, &folder.folder_id, FolderType::Dashboards).await? {
+        if same_name.is_some_and(|id| id != folder.folder_id) {
+            return Err(FolderError::FolderNameAlreadyExists);
+        }
+        return Ok((folder.folder_id.clone(), Some((true, folder))));
+    }
+    match same_name {
+        Some(folder_id) => Ok((folder_id, None)),
+        None => Ok((folder.folder_id.clone(), Some((false, folder)))),
+    }
+}
+
+async fn import_folder(
+    org_id: &str,
+    exists: bool,
+    folder: BundleFolder,
+) -> Result<(), FolderError> {
+    if exists {
+        let folder_id = folder.folder_id.clone();
+        folders::update_folder(org_id, &folder_id, FolderType::Dashboards, folder.into()).await?;
+    } else {
+        folders::create_folder(org_id, folder.into(), FolderType::Dashboards).await?;
+    }
+    Ok(())
+}
+
+async fn import_dashboard(
+    org_id: &str,
+    folder_id: &str,
+    dashboard: Dashboard,
+) -> Result<(), DashboardError> {
+    let dashboard_id = dashboard.dashboard_id().unwrap_or_default().to_string();
+    let Some((folder, existing)) = table::dashboards::get_by_id(org_id, &dashboard_id).await?
+    else {
+        create(org_id, &dashboard_id, folder_id, dashboard).await?;
+        return Ok(());
+    };
+
+    // the imported dashboard replaces the existing one whatever its hash
+    update_dashboard(
+        org_id,
+        &dashboard_id,
+        &folder.folder_id,
+        dashboard,
+        Some(&existing.hash),
+    )
+    .await?;
+    if folder.folder_id != folder_id {
+        if folder_id == DEFAULT_FOLDER
+            && !table::folders::exists(org_id, DEFAULT_FOLDER, FolderType::Dashboards).await?
+        {
+            create_default_folder(org_id).await?;
+        }
+        move_dashboard(org_id, &dashboard_id, &folder.folder_id, folder_id).await?;
+    }
+    Ok(())
+}
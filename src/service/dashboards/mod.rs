@@ -18,20 +18,25 @@ use config::{
     meta::{
         dashboards::{Dashboard, ListDashboardsParams},
         folder::{Folder, FolderType, DEFAULT_FOLDER},
+        sql::{resolve_stream_names_with_type, TableReferenceExt},
         stream::{DistinctField, StreamType},
     },
+    utils::json,
 };
 use hashbrown::HashMap;
 use infra::table::{
     self,
     distinct_values::{DistinctFieldRecord, OriginType},
 };
+use once_cell::sync::Lazy;
+use regex::Regex;
 
 use super::{db::distinct_values, folders, stream::save_stream_settings};
 use crate::common::{
     meta::authz::Authz,
     utils::auth::{remove_ownership, set_ownership},
 };
+pub mod bundle;
 pub mod reports;
 pub mod timed_annotations;
 
@@ -106,6 +111,29 @@ pub enum DashboardError {
     /// get.
     #[error(transparent)]
     ListPermittedDashboardsError(actix_web::Error),
+
+    /// Error that occurs when the dashboard JSON fails import validation, for
+    /// example a panel query that cannot be parsed.
+    #[error("invalid dashboard: {}", .0.iter().map(|e| e.to_string()).collect::<Vec<_>>().join(", "))]
+    InvalidDashboard(Vec<DashboardFieldError>),
+}
+
+/// A validation error for a single field of the dashboard JSON.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, thiserror::Error)]
+#[error("{field}: {message}")]
+pub struct DashboardFieldError {
+    /// Path to the invalid field, e.g. `v5.tabs[0].panels[1].queries[0].query`.
+    pub field: String,
+    pub message: String,
+}
+
+impl DashboardFieldError {
+    fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
 }
 
 async fn add_distinct_field_entry(
@@ -284,48 +312,38 @@ pub async fn create_dashboard(
     folder_id: &str,
     dashboard: Dashboard,
 ) -> Result<Dashboard, DashboardError> {
+    validate_dashboard(org_id, &dashboard).await?;
+
     // NOTE: Overwrite whatever `dashboard_id` the client has sent us
+    create(org_id, &ider::generate(), folder_id, dashboard).await
+}
+
+/// Creates the dashboard with the given id in the folder.
+async fn create(
+    org_id: &str,
+    dashboard_id: &str,
+    folder_id: &str,
+    dashboard: Dashboard,
+) -> Result<Dashboard, DashboardError> {
     // If folder is default folder & doesn't exist then create it
+    if !table::folders::exists(org_id, folder_id, FolderType::Dashboards).await? {
+        if folder_id != DEFAULT_FOLDER {
+            return Err(DashboardError::CreateFolderNotFound);
+        }
+        create_default_folder(org_id).await?;
+    }
 
-    let dashboard = if table::folders::exists(org_id, folder_id, FolderType::Dashboards).await? {
-        let dashboard_id = ider::generate();
-        let saved = put(org_id, &dashboard_id, folder_id, None, dashboard, None).await?;
-        set_ownership(
-            org_id,
-            "dashboards",
-            Authz {
-                obj_id: dashboard_id,
-                parent_type: "folders".to_owned(),
-                parent: folder_id.to_owned(),
-            },
-        )
-        .await;
-        Ok(saved)
-    } else if folder_id == DEFAULT_FOLDER {
-        let folder = Folder {
-            folder_id: DEFAULT_FOLDER.to_string(),
-            name: DEFAULT_FOLDER.to_string(),
-            description: DEFAULT_FOLDER.to_string(),
-        };
-        folders::save_folder(org_id, folder, FolderType::Dashboards, true)
-            .await
-            .map_err(|_| DashboardError::CreateDefaultFolder)?;
-        let dashboard_id = ider::generate();
-        let saved = put(org_id, &dashboard_id, folder_id, None, dashboard, None).await?;
-        set_ownership(
-            org_id,
-            "dashboards",
-            Authz {
-                obj_id: dashboard_id,
-                parent_type: "folders".to_owned(),
-                parent: folder_id.to_owned(),
-            },
-        )
-        .await;
-        Ok(saved)
-    } else {
-        Err(DashboardError::CreateFolderNotFound)
-    }?;
+    let dashboard = put(org_id, dashboard_id, folder_id, None, dashboard, None).await?;
+    set_ownership(
+        org_id,
+        "dashboards",
+        Authz {
+            obj_id: dashboard_id.to_owned(),
+            parent_type: "folders".to_owned(),
+            parent: folder_id.to_owned(),
+        },
+    )
+    .await;
 
     #[cfg(feature = "enterprise")]
     if get_o2_config().super_cluster.enabled {
@@ -340,6 +358,18 @@ pub async fn create_dashboard(
     Ok(dashboard)
 }
 
+async fn create_default_folder(org_id: &str) -> Result<(), DashboardError> {
+    let folder = Folder {
+        folder_id: DEFAULT_FOLDER.to_string(),
+        name: DEFAULT_FOLDER.to_string(),
+        description: DEFAULT_FOLDER.to_string(),
+    };
+    folders::save_folder(org_id, folder, FolderType::Dashboards, true)
+        .await
+        .map_err(|_| DashboardError::CreateDefaultFolder)?;
+    Ok(())
+}
+
 #[tracing::instrument(skip(dashboard))]
 pub async fn update_dashboard(
     org_id: &str,
@@ -348,6 +378,7 @@ pub async fn update_dashboard(
     dashboard: Dashboard,
     hash: Option<&str>,
 ) -> Result<Dashboard, DashboardError> {
+    validate_dashboard(org_id, &dashboard).await?;
     let dashboard = put(org_id, dashboard_id, folder_id, None, dashboard, hash).await?;
 
     #[cfg(feature = "enterprise")]
@@ -363,6 +394,225 @@ pub async fn update_dashboard(
     Ok(dashboard)
 }
 
+/// Validates a dashboard before it is created or updated.
+///
+/// Checks that the declared `version` matches the populated `vN` field and that
+/// every SQL panel query parses. All problems are collected and returned
+/// together so the client can point at each offending field.
+///
+/// The streams of a dashboard may have no data yet, like on an import to a new
+/// org, so the queries reading them are only checked to parse.
+pub async fn validate_dashboard(org_id: &str, dashboard: &Dashboard) -> Result<(), DashboardError> {
+    let mut errors = check_dashboard_version(dashboard);
+    if errors.is_empty() {
+        for (field, sql, stream_type) in sql_panel_queries(dashboard) {
+            let sql = replace_variables(&sql);
+            let streams = match resolve_stream_names_with_type(&sql) {
+                Ok(streams) => streams,
+                Err(e) => {
+                    errors.push(DashboardFieldError::new(field, e.to_string()));
+                    continue;
+                }
+            };
+            let mut missing = Vec::new();
+            for stream in streams.iter() {
+                let name = stream.stream_name();
+                let stream_type = stream.get_stream_type(stream_type);
+                if infra::schema::get(org_id, &name, stream_type)
+                    .await
+                    .map_or(true, |schema| schema.fields().is_empty())
+                {
+                    missing.push(name);
+                }
+            }
+            if !missing.is_empty() {
+                log::warn!(
+                    "[DASHBOARD] org {org_id} {field}: streams {missing:?} have no schema, only checking the query parses"
+                );
+                continue;
+            }
+            let query = config::meta::search::Query {
+                sql,
+                ..Default::default()
+            };
+            if let Err(e) = crate::service::search::sql::Sql::new(&query, org_id, stream_type).await
+            {
+                errors.push(DashboardFieldError::new(field, e.to_string()));
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(DashboardError::InvalidDashboard(errors))
+    }
+}
+
+/// Returns an error for each `vN` field that disagrees with `version`.
+fn check_dashboard_version(dashboard: &Dashboard) -> Vec<DashboardFieldError> {
+    let populated = [
+        dashboard.v1.is_some(),
+        dashboard.v2.is_some(),
+        dashboard.v3.is_some(),
+        dashboard.v4.is_some(),
+        dashboard.v5.is_some(),
+    ];
+    if !(1..=populated.len() as i32).contains(&dashboard.version) {
+        return vec![DashboardFieldError::new(
+            "version",
+            format!("unsupported dashboard version {}", dashboard.version),
+        )];
+    }
+    let mut errors = Vec::new();
+    for (i, is_some) in populated.into_iter().enumerate() {
+        let v = i as i32 + 1;
+        if v == dashboard.version && !is_some {
+            errors.push(DashboardFieldError::new(
+                format!("v{v}"),
+                format!("dashboard declares version {v} but v{v} is missing"),
+            ));
+        } else if v != dashboard.version && is_some {
+            errors.push(DashboardFieldError::new(
+                format!("v{v}"),
+                format!(
+                    "dashboard declares version {} but v{v} is populated",
+                    dashboard.version
+                ),
+            ));
+        }
+    }
+    errors
+}
+
+/// Collects `(field path, sql, stream type)` for every non-empty SQL panel
+/// query of the populated dashboard version.
+fn sql_panel_queries(dashboard: &Dashboard) -> Vec<(String, String, StreamType)> {
+    let Some(inner) = dashboard_json(dashboard) else {
+        return vec![];
+    };
+    let root = format!("v{}", dashboard.version);
+
+    let mut panels = Vec::new();
+    if let Some(tabs) = inner.get("tabs").and_then(|v| v.as_array()) {
+        for (ti, tab) in tabs.iter().enumerate() {
+            let Some(tab_panels) = tab.get("panels").and_then(|v| v.as_array()) else {
+                continue;
+            };
+            for (pi, panel) in tab_panels.iter().enumerate() {
+                panels.push((format!("{root}.tabs[{ti}].panels[{pi}]"), panel));
+            }
+        }
+    } else if let Some(top_panels) = inner.get("panels").and_then(|v| v.as_array()) {
+        for (pi, panel) in top_panels.iter().enumerate() {
+            panels.push((format!("{root}.panels[{pi}]"), panel));
+        }
+    }
+
+    let mut queries = Vec::new();
+    for (path, panel) in panels {
+        let query_type = panel
+            .get("queryType")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        if !query_type.eq_ignore_ascii_case("sql") {
+            continue;
+        }
+        // v1 panels carry a single query, later versions a list of them
+        let panel_queries = match panel.get("queries").and_then(|v| v.as_array()) {
+            Some(list) => list
+                .iter()
+                .enumerate()
+                .map(|(qi, q)| (format!("{path}.queries[{qi}]"), q))
+                .collect(),
+            None => vec![(path, panel)],
+        };
+        for (path, query) in panel_queries {
+            let Some(sql) = query
+                .get("query")
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.trim().is_empty())
+            else {
+                continue;
+            };
+            let stream_type = query
+                .get("fields")
+                .and_then(|f| f.get("stream_type"))
+                .and_then(|v| v.as_str())
+                .map(StreamType::from)
+                .unwrap_or_default();
+            queries.push((format!("{path}.query"), sql.to_string(), stream_type));
+        }
+    }
+    queries
+}
+
+/// The populated dashboard version as JSON.
+fn dashboard_json(dashboard: &Dashboard) -> Option<json::Value> {
+    match dashboard.version {
+        1 => dashboard.v1.as_ref().and_then(|d| json::to_value(d).ok()),
+        2 => dashboard.v2.as_ref().and_then(|d| json::to_value(d).ok()),
+        3 => dashboard.v3.as_ref().and_then(|d| json::to_value(d).ok()),
+        4 => dashboard.v4.as_ref().and_then(|d| json::to_value(d).ok()),
+        5 => dashboard.v5.as_ref().and_then(|d| json::to_value(d).ok()),
+        _ => None,
+    }
+}
+
+/// Lists the dashboards of the org with a panel query or a variable reading
+/// the stream.
+pub async fn list_dashboards_using_stream(
+    org_id: &str,
+    stream_type: StreamType,
+    stream_name: &str,
+) -> Result<Vec<Dashboard>, DashboardError> {
+    let dashboards = table::dashboards::list(ListDashboardsParams::new(org_id)).await?;
+    Ok(dashboards
+        .into_iter()
+        .map(|(_folder, dashboard)| dashboard)
+        .filter(|dashboard| dashboard_uses_stream(dashboard, stream_type, stream_name))
+        .collect())
+}
+
+fn dashboard_uses_stream(
+    dashboard: &Dashboard,
+    stream_type: StreamType,
+    stream_name: &str,
+) -> bool {
+    dashboard_json(dashboard)
+        .is_some_and(|inner| refers_to_stream(&inner, stream_type.as_str(), stream_name))
+}
+
+/// Whether any object of the value has the `stream` and `stream_type` keys
+/// of the stream, as panel query fields and variable query data have.
+fn refers_to_stream(value: &json::Value, stream_type: &str, stream_name: &str) -> bool {
+    match value {
+        json::Value::Object(map) => {
+            (map.get("stream").and_then(|v| v.as_str()) == Some(stream_name)
+                && map.get("stream_type").and_then(|v| v.as_str()) == Some(stream_type))
+                || map
+                    .values()
+                    .any(|v| refers_to_stream(v, stream_type, stream_name))
+        }
+        json::Value::Array(list) => list
+            .iter()
+            .any(|v| refers_to_stream(v, stream_type, stream_name)),
+        _ => false,
+    }
+}
+
+/// Stands for the dashboard variables, a plain identifier so it parses both as
+/// a value and as a stream or field name.
+const VARIABLE_PLACEHOLDER: &str = "o2_variable";
+
+/// Replaces dashboard variable placeholders (`$var` and `${var}`) with
+/// [VARIABLE_PLACEHOLDER] so the query can be parsed without the variable
+/// values.
+fn replace_variables(sql: &str) -> String {
+    static RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"\$\{[^}]*\}|\$[A-Za-z_][A-Za-z0-9_]*").unwrap());
+    RE.replace_all(sql, VARIABLE_PLACEHOLDER).into_owned()
+}
+
 #[tracing::instrument]
 pub async fn list_dashboards(
     user_id: &str,
@@ -582,3 +832,153 @@ async fn filter_permitted_dashboards(
         .collect();
     Ok(permitted_dashboards)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v5_dashboard(queries: &[&str]) -> Dashboard {
+        let panels: Vec<json::Value> = queries
+            .iter()
+            .enumerate()
+            .map(|(i, q)| {
+                json::json!({
+                    "id": format!("Panel_ID{i}"),
+                    "type": "bar",
+                    "title": "panel",
+                    "description": "",
+                    "config": { "show_legends": true },
+                    "queryType": "sql",
+                    "queries": [{
+                        "query": q,
+                        "customQuery": true,
+                        "fields": {
+                            "stream": "default",
+                            "stream_type": "logs",
+                            "x": [],
+                            "y": [],
+                            "filter": {
+                                "filterType": "group",
+                                "logicalOperator": "AND",
+                                "conditions": []
+                            }
+                        },
+                        "config": { "promql_legend": "" }
+                    }],
+                    "layout": { "x": 0, "y": 0, "w": 12, "h": 12, "i": i + 1 }
+                })
+            })
+            .collect();
+        let v5 = json::from_value(json::json!({
+            "title": "test",
+            "description": "",
+            "tabs": [{ "tabId": "default", "name": "Default", "panels": panels }]
+        }))
+        .unwrap();
+        Dashboard {
+            v5: Some(v5),
+            version: 5,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_check_dashboard_version() {
+        let dashboard = v5_dashboard(&[]);
+        assert!(check_dashboard_version(&dashboard).is_empty());
+
+        let mut mismatch = dashboard.clone();
+        mismatch.version = 4;
+        let errors = check_dashboard_version(&mismatch);
+        let fields: Vec<_> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["v4", "v5"]);
+
+        let mut unsupported = dashboard;
+        unsupported.version = 9;
+        assert_eq!(check_dashboard_version(&unsupported)[0].field, "version");
+    }
+
+    #[test]
+    fn test_sql_panel_queries() {
+        let dashboard = v5_dashboard(&["SELECT * FROM \"default\"", ""]);
+        let queries = sql_panel_queries(&dashboard);
+        assert_eq!(queries.len(), 1);
+        assert_eq!(queries[0].0, "v5.tabs[0].panels[0].queries[0].query");
+        assert_eq!(queries[0].2, StreamType::Logs);
+    }
+
+    #[test]
+    fn test_dashboard_uses_stream() {
+        let dashboard = v5_dashboard(&["SELECT * FROM \"default\""]);
+        assert!(dashboard_uses_stream(
+            &dashboard,
+            StreamType::Logs,
+            "default"
+        ));
+        assert!(!dashboard_uses_stream(
+            &dashboard,
+            StreamType::Metrics,
+            "default"
+        ));
+        assert!(!dashboard_uses_stream(
+            &dashboard,
+            StreamType::Logs,
+            "other"
+        ));
+        assert!(!dashboard_uses_stream(
+            &v5_dashboard(&[]),
+            StreamType::Logs,
+            "default"
+        ));
+    }
+
+    #[test]
+    fn test_replace_variables() {
+        assert_eq!(
+            replace_variables("SELECT * FROM t WHERE a = $host AND b IN (${ids})"),
+            "SELECT * FROM t WHERE a = o2_variable AND b IN (o2_variable)"
+        );
+        // the stream names are variables too
+        assert_eq!(
+            replace_variables("SELECT * FROM $stream WHERE a = '${host}'"),
+            "SELECT * FROM o2_variable WHERE a = 'o2_variable'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_dashboard() {
+        let mut mismatch = v5_dashboard(&[]);
+        mismatch.version = 3;
+        let Err(DashboardError::InvalidDashboard(errors)) =
+            validate_dashboard("default", &mismatch).await
+        else {
+            panic!("expected version mismatch");
+        };
+        assert_eq!(errors[0].field, "v3");
+
+        let invalid = v5_dashboard(&["SELEC * FORM \"default\" WHERE"]);
+        let Err(DashboardError::InvalidDashboard(errors)) =
+            validate_dashboard("default", &invalid).await
+        else {
+            panic!("expected invalid panel query");
+        };
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "v5.tabs[0].panels[0].queries[0].query");
+
+        let empty = v5_dashboard(&[";"]);
+        let Err(DashboardError::InvalidDashboard(errors)) =
+            validate_dashboard("default", &empty).await
+        else {
+            panic!("expected empty panel query");
+        };
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "v5.tabs[0].panels[0].queries[0].query");
+
+        // streams without data yet and variable streams are accepted
+        let no_schema = v5_dashboard(&[
+            "SELECT * FROM \"new_stream\" WHERE a = 'x'",
+            "SELECT count(*) FROM $stream WHERE host = '$host'",
+        ]);
+        assert!(validate_dashboard("default", &no_schema).await.is_ok());
+    }
+}
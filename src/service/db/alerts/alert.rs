@@ -180,8 +180,18 @@ pub async fn update<C: ConnectionTrait + TransactionTrait>(
     alert: Alert,
 ) -> Result<Alert, infra::errors::Error> {
     let alert = table::update(conn, org_id, folder_id, alert).await?;
+    after_update(org_id, folder_id, &alert).await?;
+    Ok(alert)
+}
 
-    cluster::emit_put_event(org_id, &alert).await?;
+/// Emits the update events of an alert updated in the table and updates its
+/// trigger.
+pub async fn after_update(
+    org_id: &str,
+    #[allow(unused_variables)] folder_id: Option<&str>,
+    alert: &Alert,
+) -> Result<(), infra::errors::Error> {
+    cluster::emit_put_event(org_id, alert).await?;
     #[cfg(feature = "enterprise")]
     super_cluster::emit_update_event(org_id, folder_id, alert.clone()).await?;
 
@@ -210,7 +220,7 @@ pub async fn update<C: ConnectionTrait + TransactionTrait>(
         });
     }
 
-    Ok(alert)
+    Ok(())
 }
 
 pub async fn delete_by_id<C: ConnectionTrait>(
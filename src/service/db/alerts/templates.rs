@@ -44,6 +44,10 @@ pub enum TemplateError {
     EmptyTitle,
     #[error("Template body cannot be empty")]
     EmptyBody,
+    #[error("Template locale cannot be empty")]
+    EmptyLocale,
+    #[error("Template body for locale {0} cannot be empty")]
+    EmptyLocaleBody(String),
     #[error("Template with the same name already exists")]
     AlreadyExists,
     #[error("Template is in use for destination {0}")]
@@ -0,0 +1,36 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use config::utils::json;
+
+use crate::service::{compact::manual::ManualCompactJob, db};
+
+#[inline]
+fn mk_key(org_id: &str, id: &str) -> String {
+    format!("/compact/manual/{org_id}/{id}")
+}
+
+pub async fn get(org_id: &str, id: &str) -> Result<Option<ManualCompactJob>, anyhow::Error> {
+    let ret = match db::get(&mk_key(org_id, id)).await {
+        Ok(ret) => ret,
+        Err(_) => return Ok(None),
+    };
+    Ok(Some(json::from_slice(&ret)?))
+}
+
+pub async fn set(job: &ManualCompactJob) -> Result<(), anyhow::Error> {
+    let key = mk_key(&job.org_id, &job.id);
+    Ok(db::put(&key, json::to_vec(job)?.into(), db::NO_NEED_WATCH, None).await?)
+}
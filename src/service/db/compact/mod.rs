@@ -16,6 +16,7 @@
 pub mod downsampling;
 pub mod file_list;
 pub mod files;
+pub mod manual;
 pub mod organization;
 pub mod retention;
 pub mod stats;
@@ -25,11 +25,23 @@ use vrl::prelude::NotNan;
 
 use crate::{
     common::infra::config::ENRICHMENT_TABLES,
-    service::{enrichment::StreamTable, search as SearchService},
+    service::{
+        enrichment::{storage, CompressedStreamTable},
+        search as SearchService,
+    },
 };
 
-pub async fn get(org_id: &str, name: &str) -> Result<Vec<vrl::value::Value>, anyhow::Error> {
+/// Returns the zstd compressed rows of the table, see [storage::decompress].
+pub async fn get(org_id: &str, name: &str) -> Result<Vec<u8>, anyhow::Error> {
     let stats = stats::get_stream_stats(org_id, name, StreamType::EnrichmentTables);
+    let version = storage::table_version(&stats);
+
+    // load from the local compressed copy if the table didn't change since it was stored
+    if stats.doc_num > 0 {
+        if let Some(data) = storage::load(org_id, name, &version).await {
+            return Ok(data);
+        }
+    }
 
     let rec_num = if stats.doc_num == 0 {
         100000
@@ -57,20 +69,53 @@ pub async fn get(org_id: &str, name: &str) -> Result<Vec<vrl::value::Value>, any
     // do search
     match SearchService::search("", org_id, StreamType::EnrichmentTables, None, &req).await {
         Ok(res) => {
+            let data = storage::compress(&res.hits)?;
             if !res.hits.is_empty() {
-                Ok(res.hits.iter().map(convert_to_vrl).collect())
-            } else {
-                Ok(vec![])
+                if let Err(e) = storage::save(org_id, name, &version, &data).await {
+                    log::warn!("save enrichment table {org_id}/{name} to local storage error: {e}");
+                }
             }
+            Ok(data)
         }
         Err(err) => {
             log::error!("get enrichment table data error: {:?}", err);
-            Ok(vec![])
+            storage::compress(&[])
         }
     }
 }
 
-fn convert_to_vrl(value: &json::Value) -> vrl::value::Value {
+/// Returns at most `limit` rows of the table, read from the stream without
+/// loading the whole table.
+pub async fn get_rows(
+    org_id: &str,
+    name: &str,
+    limit: usize,
+) -> Result<Vec<json::Value>, anyhow::Error> {
+    if limit == 0 {
+        return Ok(vec![]);
+    }
+    let query = config::meta::search::Query {
+        sql: format!("SELECT * FROM \"{name}\""),
+        size: limit as i64,
+        start_time: BASE_TIME.timestamp_micros(),
+        end_time: Utc::now().timestamp_micros(),
+        ..Default::default()
+    };
+    let req = config::meta::search::Request {
+        query,
+        encoding: config::meta::search::RequestEncoding::Empty,
+        regions: vec![],
+        clusters: vec![],
+        timeout: 0,
+        search_type: None,
+        search_event_context: None,
+        use_cache: None,
+    };
+    let res = SearchService::search("", org_id, StreamType::EnrichmentTables, None, &req).await?;
+    Ok(res.hits)
+}
+
+pub(crate) fn convert_to_vrl(value: &json::Value) -> vrl::value::Value {
     match value {
         json::Value::Null => vrl::value::Value::Null,
         json::Value::Bool(b) => vrl::value::Value::Boolean(*b),
@@ -136,19 +181,21 @@ pub async fn watch() -> Result<(), anyhow::Error> {
                 let org_id = keys[0];
                 let stream_name = keys[2];
 
+                // the table was updated, the local copy is outdated
+                storage::remove(org_id, stream_name).await;
                 let data = super::enrichment_table::get(org_id, stream_name)
                     .await
                     .unwrap();
                 ENRICHMENT_TABLES.insert(
                     item_key.to_owned(),
-                    StreamTable {
-                        org_id: org_id.to_string(),
-                        stream_name: stream_name.to_string(),
-                        data,
-                    },
+                    CompressedStreamTable::new(org_id, stream_name, data),
                 );
             }
-            db::Event::Delete(_) => {}
+            db::Event::Delete(ev) => {
+                let item_key = ev.key.strip_prefix(key).unwrap();
+                let keys = item_key.split('/').collect::<Vec<&str>>();
+                storage::remove(keys[0], keys[2]).await;
+            }
             db::Event::Empty => {}
         }
     }
@@ -16,7 +16,11 @@
 use std::sync::Arc;
 
 use bytes::Bytes;
-use config::{cluster::LOCAL_NODE, meta::promql::ClusterLeader, utils::json};
+use config::{
+    cluster::LOCAL_NODE,
+    meta::promql::{ClusterLeader, RollupOffset},
+    utils::json,
+};
 
 use crate::{common::infra::config::METRIC_CLUSTER_LEADER, service::db};
 
@@ -53,6 +57,36 @@ pub async fn set_prom_cluster_leader(
     Ok(())
 }
 
+/// Returns the time range already rolled up for a metric stream at the given
+/// resolution, `None` if the stream was never rolled up at that resolution.
+pub async fn get_rollup_offset(
+    org_id: &str,
+    stream_name: &str,
+    resolution: i64,
+) -> Option<RollupOffset> {
+    let key = format!("/metrics_rollup/{org_id}/{stream_name}/{resolution}");
+    match db::get(&key).await {
+        Ok(ret) => json::from_slice(&ret).ok(),
+        Err(_) => None,
+    }
+}
+
+pub async fn set_rollup_offset(
+    org_id: &str,
+    stream_name: &str,
+    resolution: i64,
+    offset: RollupOffset,
+) -> Result<(), anyhow::Error> {
+    let key = format!("/metrics_rollup/{org_id}/{stream_name}/{resolution}");
+    Ok(db::put(
+        &key,
+        json::to_vec(&offset).unwrap().into(),
+        db::NO_NEED_WATCH,
+        None,
+    )
+    .await?)
+}
+
 pub async fn watch_prom_cluster_leader() -> Result<(), anyhow::Error> {
     let key = "/metrics_leader/";
     let cluster_coordinator = db::get_coordinator().await;
@@ -38,6 +38,7 @@ pub mod metrics;
 pub mod ofga;
 pub mod organization;
 pub mod pipeline;
+pub mod pipeline_backfill;
 pub mod saved_view;
 pub mod scheduler;
 pub mod schema;
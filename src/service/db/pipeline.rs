@@ -28,7 +28,7 @@ use once_cell::sync::Lazy;
 
 use crate::{
     common::infra::config::{PIPELINE_STREAM_MAPPING, STREAM_EXECUTABLE_PIPELINES},
-    service::pipeline::batch_execution::ExecutablePipeline,
+    service::pipeline::batch_execution::{self, ExecutablePipeline},
 };
 
 #[derive(Debug, thiserror::Error)]
@@ -39,6 +39,8 @@ pub enum PipelineError {
     // not found
     #[error("Pipeline with ID {0} not found.")]
     NotFound(String),
+    #[error("Pipeline backfill {0} not found.")]
+    BackfillNotFound(String),
     // conflict
     #[error("Pipeline with ID {0} modified by someone else. Please refresh.")]
     Modified(String),
@@ -51,6 +53,8 @@ pub enum PipelineError {
     InvalidDerivedStream(String),
     #[error("Error deleting previous DerivedStream: {0}")]
     DeleteDerivedStream(String),
+    #[error("Invalid backfill: {0}")]
+    InvalidBackfill(String),
 }
 
 /// Stores a new pipeline to database.
@@ -258,6 +262,13 @@ pub async fn watch() -> Result<(), anyhow::Error> {
                     log::error!("[Pipeline::watch] error getting pipeline by id from db");
                     continue;
                 };
+                // drop the stats of the nodes removed by the update
+                let node_ids = pipeline
+                    .nodes
+                    .iter()
+                    .map(|node| node.get_node_id())
+                    .collect::<Vec<_>>();
+                batch_execution::retain_node_stats(pipeline_id, &node_ids);
                 // Only realtime & enabled pipeline should be added cache
                 if let PipelineSource::Realtime(stream_params) = &pipeline.source {
                     let mut pipeline_stream_mapping_cache = PIPELINE_STREAM_MAPPING.write().await;
@@ -298,6 +309,7 @@ pub async fn watch() -> Result<(), anyhow::Error> {
             }
             db::Event::Delete(ev) => {
                 let pipeline_id = ev.key.strip_prefix(PIPELINES_WATCH_PREFIX).unwrap();
+                batch_execution::remove_pipeline_stats(pipeline_id);
                 if let Some(removed) = PIPELINE_STREAM_MAPPING.write().await.remove(pipeline_id) {
                     if STREAM_EXECUTABLE_PIPELINES
                         .write()
@@ -0,0 +1,58 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use config::utils::{json, time::now_micros};
+
+use crate::service::{db, pipeline::backfill::BackfillJob};
+
+#[inline]
+fn mk_key(org_id: &str, id: &str) -> String {
+    format!("/pipeline/backfill/{org_id}/{id}")
+}
+
+pub async fn get(org_id: &str, id: &str) -> Result<Option<BackfillJob>, anyhow::Error> {
+    let ret = match db::get(&mk_key(org_id, id)).await {
+        Ok(ret) => ret,
+        Err(_) => return Ok(None),
+    };
+    Ok(Some(json::from_slice(&ret)?))
+}
+
+pub async fn set(job: &BackfillJob) -> Result<(), anyhow::Error> {
+    let key = mk_key(&job.org_id, &job.id);
+    Ok(db::put(&key, json::to_vec(job)?.into(), db::NO_NEED_WATCH, None).await?)
+}
+
+/// The cancel request is kept apart from the job, which the node running it
+/// keeps overwriting with its progress.
+#[inline]
+fn mk_cancel_key(org_id: &str, id: &str) -> String {
+    format!("/pipeline/backfill_cancel/{org_id}/{id}")
+}
+
+pub async fn cancel(org_id: &str, id: &str) -> Result<(), anyhow::Error> {
+    let key = mk_cancel_key(org_id, id);
+    let data = bytes::Bytes::from(now_micros().to_string());
+    Ok(db::put(&key, data, db::NO_NEED_WATCH, None).await?)
+}
+
+pub async fn is_cancelled(org_id: &str, id: &str) -> bool {
+    db::get(&mk_cancel_key(org_id, id)).await.is_ok()
+}
+
+pub async fn delete_cancel(org_id: &str, id: &str) -> Result<(), anyhow::Error> {
+    let key = mk_cancel_key(org_id, id);
+    Ok(db::delete(&key, false, db::NO_NEED_WATCH, None).await?)
+}
@@ -44,7 +44,7 @@ use crate::{
         infra::{cluster::get_cached_online_querier_nodes, config::ENRICHMENT_TABLES},
         meta::stream::StreamSchema,
     },
-    service::{db, enrichment::StreamTable},
+    service::{db, enrichment::CompressedStreamTable},
 };
 
 pub async fn merge(
@@ -405,11 +405,7 @@ pub async fn watch() -> Result<(), anyhow::Error> {
                         .unwrap();
                     ENRICHMENT_TABLES.insert(
                         item_key.to_owned(),
-                        StreamTable {
-                            org_id: org_id.to_string(),
-                            stream_name: stream_name.to_string(),
-                            data,
-                        },
+                        CompressedStreamTable::new(org_id, stream_name, data),
                     );
                 }
             }
@@ -569,11 +565,7 @@ pub async fn cache_enrichment_tables() -> Result<(), anyhow::Error> {
         }
         tables.insert(
             schema_key.to_owned(),
-            StreamTable {
-                org_id: org_id.to_string(),
-                stream_name: stream_name.to_string(),
-                data: vec![],
-            },
+            CompressedStreamTable::new(org_id, stream_name, vec![]),
         );
     }
     drop(r);
@@ -600,11 +592,7 @@ pub async fn cache_enrichment_tables() -> Result<(), anyhow::Error> {
         let data = super::enrichment_table::get(&tbl.org_id, &tbl.stream_name).await?;
         ENRICHMENT_TABLES.insert(
             key,
-            StreamTable {
-                org_id: tbl.org_id,
-                stream_name: tbl.stream_name,
-                data,
-            },
+            CompressedStreamTable::new(&tbl.org_id, &tbl.stream_name, data),
         );
     }
     log::info!("EnrichmentTables Cached");
@@ -13,19 +13,66 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use config::utils::time::parse_str_to_time;
+use once_cell::sync::OnceCell;
 use vector_enrichment::{Case, IndexHandle, Table};
 use vrl::value::{ObjectMap, Value};
 
+use crate::service::db::enrichment_table::convert_to_vrl;
+
+pub mod storage;
+
 #[derive(Clone)]
 pub struct StreamTableConfig {}
 
+/// An enrichment table as held in memory, its rows stay zstd compressed until they are
+/// first loaded into a [StreamTable] for lookups. The decoded rows are then shared by every
+/// later load, until the table is replaced on upload.
+#[derive(Debug, Clone)]
+pub struct CompressedStreamTable {
+    pub org_id: String,
+    pub stream_name: String,
+    pub data: bytes::Bytes,
+    decoded: Arc<OnceCell<Arc<Vec<Value>>>>,
+}
+
+impl CompressedStreamTable {
+    pub fn new(org_id: &str, stream_name: &str, data: Vec<u8>) -> Self {
+        Self {
+            org_id: org_id.to_string(),
+            stream_name: stream_name.to_string(),
+            data: data.into(),
+            decoded: Arc::default(),
+        }
+    }
+
+    /// Returns a table that can be looked up, decompressing the rows only the
+    /// first time.
+    pub fn to_table(&self) -> Result<StreamTable, anyhow::Error> {
+        let data = self.decoded.get_or_try_init(|| {
+            let rows = if self.data.is_empty() {
+                vec![]
+            } else {
+                storage::decompress(&self.data)?
+            };
+            Ok::<_, anyhow::Error>(Arc::new(rows.iter().map(convert_to_vrl).collect()))
+        })?;
+        Ok(StreamTable {
+            org_id: self.org_id.clone(),
+            stream_name: self.stream_name.clone(),
+            data: data.clone(),
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct StreamTable {
     pub org_id: String,
     pub stream_name: String,
-    pub data: Vec<vrl::value::Value>,
+    pub data: Arc<Vec<vrl::value::Value>>,
 }
 impl StreamTable {}
 
@@ -0,0 +1,182 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! zstd compressed enrichment tables, both the copies held in memory and the local copies
+//! on disk, so a table can be loaded into the lookup map without searching the stream again.
+
+use std::path::PathBuf;
+
+use config::{
+    meta::stream::StreamStats,
+    utils::{
+        async_file::{get_file_contents, put_file_contents},
+        json,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+const COMPRESSION_LEVEL: i32 = 3;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredTable {
+    /// Version of the stream data when the table was stored, see [table_version]
+    version: String,
+    /// zstd compressed rows, see [compress]
+    data: Vec<u8>,
+}
+
+/// Changes whenever rows are ingested into or removed from the table stream,
+/// new rows always move `doc_time_max` and removed ones `doc_num`.
+pub fn table_version(stats: &StreamStats) -> String {
+    format!(
+        "{}-{}-{}",
+        stats.doc_num, stats.doc_time_min, stats.doc_time_max
+    )
+}
+
+fn table_path(org_id: &str, name: &str) -> PathBuf {
+    PathBuf::from(format!(
+        "{}enrichment_tables/{org_id}/{name}.zst",
+        config::get_config().common.data_cache_dir
+    ))
+}
+
+pub fn compress(rows: &[json::Value]) -> Result<Vec<u8>, anyhow::Error> {
+    let data = json::to_vec(rows)?;
+    Ok(zstd::encode_all(data.as_slice(), COMPRESSION_LEVEL)?)
+}
+
+pub fn decompress(data: &[u8]) -> Result<Vec<json::Value>, anyhow::Error> {
+    let data = zstd::decode_all(data)?;
+    Ok(json::from_slice(&data)?)
+}
+
+pub async fn save(
+    org_id: &str,
+    name: &str,
+    version: &str,
+    data: &[u8],
+) -> Result<(), anyhow::Error> {
+    let path = table_path(org_id, name);
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    let table = StoredTable {
+        version: version.to_string(),
+        data: data.to_vec(),
+    };
+    put_file_contents(&path, &json::to_vec(&table)?).await?;
+    Ok(())
+}
+
+/// Loads the compressed rows of the stored table, returns `None` if it is missing or was
+/// stored for a different version of the stream data.
+pub async fn load(org_id: &str, name: &str, version: &str) -> Option<Vec<u8>> {
+    let data = get_file_contents(table_path(org_id, name), None)
+        .await
+        .ok()?;
+    match json::from_slice::<StoredTable>(&data) {
+        Ok(table) if table.version == version => Some(table.data),
+        Ok(_) => None,
+        Err(e) => {
+            log::warn!("load enrichment table {org_id}/{name} from local storage error: {e}");
+            None
+        }
+    }
+}
+
+pub async fn remove(org_id: &str, name: &str) {
+    let path = table_path(org_id, name);
+    if let Err(e) = tokio::fs::remove_file(&path).await {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            log::warn!("remove enrichment table {org_id}/{name} from local storage error: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vector_enrichment::{Case, Condition, Table};
+
+    use super::*;
+    use crate::service::enrichment::CompressedStreamTable;
+
+    fn rows() -> Vec<json::Value> {
+        (0..100)
+            .map(|i| json::json!({"ip": format!("10.0.0.{i}"), "city": format!("city_{}", i % 5)}))
+            .collect()
+    }
+
+    #[test]
+    fn test_compress_roundtrip() {
+        let rows = rows();
+        let data = compress(&rows).unwrap();
+        assert!(data.len() < json::to_vec(&rows).unwrap().len());
+        assert_eq!(decompress(&data).unwrap(), rows);
+    }
+
+    #[test]
+    fn test_table_version() {
+        let stats = StreamStats {
+            doc_time_min: 1,
+            doc_time_max: 2,
+            doc_num: 100,
+            ..Default::default()
+        };
+        // the table was replaced with the same number of rows
+        let replaced = StreamStats {
+            doc_time_max: 3,
+            ..stats.clone()
+        };
+        assert_ne!(table_version(&stats), table_version(&replaced));
+    }
+
+    #[tokio::test]
+    async fn test_storage_lookup() {
+        let org_id = "test_enrichment_storage";
+        save(org_id, "ips", "100-1-2", &compress(&rows()).unwrap())
+            .await
+            .unwrap();
+        assert!(load(org_id, "ips", "100-1-3").await.is_none());
+
+        let data = load(org_id, "ips", "100-1-2").await.unwrap();
+        let compressed = CompressedStreamTable::new(org_id, "ips", data);
+        let table = compressed.to_table().unwrap();
+        // later loads share the rows decoded by the first one
+        assert!(std::sync::Arc::ptr_eq(
+            &table.data,
+            &compressed.clone().to_table().unwrap().data
+        ));
+        let found = table
+            .find_table_rows(
+                Case::Sensitive,
+                &[Condition::Equals {
+                    field: "ip",
+                    value: vrl::value::Value::from("10.0.0.42"),
+                }],
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].get("city"),
+            Some(&vrl::value::Value::from("city_2"))
+        );
+
+        remove(org_id, "ips").await;
+        assert!(load(org_id, "ips", "100-1-2").await.is_none());
+    }
+}
@@ -46,6 +46,7 @@ use crate::{
     service::{
         compact::retention,
         db::{self, enrichment_table},
+        enrichment::storage,
         format_stream_name,
         ingestion::write_file,
         schema::{check_for_schema, stream_schema_exists},
@@ -55,6 +56,9 @@ use crate::{
 
 pub mod geoip;
 
+/// Maximum number of rows returned by an enrichment table preview.
+pub const PREVIEW_MAX_ROWS: usize = 1000;
+
 pub async fn save_enrichment_data(
     org_id: &str,
     table_name: &str,
@@ -253,6 +257,53 @@ pub async fn save_enrichment_data(
     )))
 }
 
+/// Loads all rows of an enrichment table, `None` if the table doesn't exist.
+pub async fn get_enrichment_table_rows(
+    org_id: &str,
+    table_name: &str,
+) -> Result<Option<Vec<json::Value>>, anyhow::Error> {
+    let stream_name = format_stream_name(table_name.trim());
+    let schema = infra::schema::get(org_id, &stream_name, StreamType::EnrichmentTables).await?;
+    if schema.fields().is_empty() {
+        return Ok(None);
+    }
+    let data = enrichment_table::get(org_id, &stream_name).await?;
+    Ok(Some(storage::decompress(&data)?))
+}
+
+/// Returns the rows whose `key` column equals `value`. Columns uploaded from
+/// csv are strings, other values are compared by their json representation.
+pub fn lookup_rows(rows: Vec<json::Value>, key: &str, value: &str) -> Vec<json::Value> {
+    rows.into_iter()
+        .filter(|row| match row.get(key) {
+            Some(json::Value::String(v)) => v == value,
+            Some(v) => v.to_string() == value,
+            None => false,
+        })
+        .collect()
+}
+
+/// Reads the first `size` rows of an enrichment table, capped at
+/// [`PREVIEW_MAX_ROWS`], `None` if the table doesn't exist.
+pub async fn get_enrichment_table_preview(
+    org_id: &str,
+    table_name: &str,
+    size: usize,
+) -> Result<Option<Vec<json::Value>>, anyhow::Error> {
+    let stream_name = format_stream_name(table_name.trim());
+    let schema = infra::schema::get(org_id, &stream_name, StreamType::EnrichmentTables).await?;
+    if schema.fields().is_empty() {
+        return Ok(None);
+    }
+    let rows = enrichment_table::get_rows(org_id, &stream_name, preview_size(size)).await?;
+    Ok(Some(rows))
+}
+
+/// The number of rows to preview, capped at [`PREVIEW_MAX_ROWS`].
+pub fn preview_size(size: usize) -> usize {
+    size.min(PREVIEW_MAX_ROWS)
+}
+
 async fn delete_enrichment_table(org_id: &str, stream_name: &str, stream_type: StreamType) {
     log::info!("deleting enrichment table  {stream_name}");
     // delete stream schema
@@ -343,3 +394,53 @@ pub async fn extract_multipart(
 
     Ok(records)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(n: usize) -> Vec<json::Value> {
+        (0..n)
+            .map(|i| json::json!({"ip": format!("10.0.0.{i}"), "city": "london", "id": i}))
+            .collect()
+    }
+
+    #[test]
+    fn test_lookup_rows_hit() {
+        let hits = lookup_rows(rows(5), "ip", "10.0.0.3");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0]["ip"], "10.0.0.3");
+        assert_eq!(lookup_rows(rows(5), "city", "london").len(), 5);
+        assert_eq!(lookup_rows(rows(5), "id", "2").len(), 1);
+    }
+
+    #[test]
+    fn test_lookup_rows_miss() {
+        assert!(lookup_rows(rows(5), "ip", "10.0.0.9").is_empty());
+        assert!(lookup_rows(rows(5), "country", "uk").is_empty());
+        assert!(lookup_rows(vec![], "ip", "10.0.0.1").is_empty());
+    }
+
+    #[test]
+    fn test_preview_size_capped() {
+        assert_eq!(preview_size(3), 3);
+        assert_eq!(preview_size(0), 0);
+        assert_eq!(preview_size(PREVIEW_MAX_ROWS), PREVIEW_MAX_ROWS);
+        assert_eq!(preview_size(usize::MAX), PREVIEW_MAX_ROWS);
+    }
+
+    #[tokio::test]
+    async fn test_preview_missing_table() {
+        use infra::schema::{SchemaCache, STREAM_SCHEMAS_LATEST};
+
+        let org_id = "test_enrichment_preview";
+        STREAM_SCHEMAS_LATEST.write().await.insert(
+            format!("{org_id}/{}/missing", StreamType::EnrichmentTables),
+            SchemaCache::new(arrow_schema::Schema::empty()),
+        );
+        assert!(get_enrichment_table_preview(org_id, "missing", 10)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -95,6 +95,17 @@ pub async fn save_folder(
         folder.folder_id = ider::generate();
     }
 
+    create_folder(org_id, folder, folder_type).await
+}
+
+/// Creates the folder with the id it has, used to import folders under the
+/// id they were exported with.
+#[tracing::instrument(skip(folder))]
+pub(crate) async fn create_folder(
+    org_id: &str,
+    folder: Folder,
+    folder_type: FolderType,
+) -> Result<Folder, FolderError> {
     // Check if there is already a folder with the same name in the organization
     if get_folder_by_name(org_id, &folder.name, folder_type)
         .await
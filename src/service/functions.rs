@@ -21,17 +21,26 @@ use actix_web::{
 };
 use config::{
     meta::{
-        function::{FunctionList, TestVRLResponse, Transform, VRLResult, VRLResultResolver},
-        pipeline::{PipelineDependencyItem, PipelineDependencyResponse},
+        alerts::alert::{Alert, ListAlertsParams},
+        function::{
+            FieldDiff, FunctionBundle, FunctionImportError, FunctionImportResponse,
+            FunctionInUseResponse, FunctionList, TestVRLResponse, Transform, VRLResult,
+            VRLResultResolver,
+        },
+        pipeline::{AlertDependencyItem, PipelineDependencyItem, PipelineDependencyResponse},
     },
-    utils::json,
+    utils::{base64, json},
 };
+use infra::db::{connect_to_orm, ORM_CLIENT};
 
 use crate::{
     common,
     common::{
         meta::{authz::Authz, http::HttpResponse as MetaHttpResponse},
-        utils::auth::{remove_ownership, set_ownership},
+        utils::{
+            auth::{remove_ownership, set_ownership},
+            functions::capture_vrl_logs,
+        },
     },
     service::{db, ingestion::compile_vrl_function, search::RESULT_ARRAY},
 };
@@ -117,18 +126,24 @@ pub async fn test_run_function(
     let fields = runtime_config.fields;
     let program = runtime_config.program;
 
+    let start = std::time::Instant::now();
+    let mut logs = vec![];
     let mut transformed_events = vec![];
     if apply_over_hits {
-        let (ret_val, err) = crate::service::ingestion::apply_vrl_fn(
-            &mut runtime,
-            &VRLResultResolver {
-                program: program.clone(),
-                fields: fields.clone(),
-            },
-            json::Value::Array(events),
-            org_id,
-            &[String::new()],
-        );
+        let inputs = events.clone();
+        let ((ret_val, err), fn_logs) = capture_vrl_logs(|| {
+            crate::service::ingestion::apply_vrl_fn(
+                &mut runtime,
+                &VRLResultResolver {
+                    program: program.clone(),
+                    fields: fields.clone(),
+                },
+                json::Value::Array(events),
+                org_id,
+                &[String::new()],
+            )
+        });
+        logs.extend(fn_logs);
 
         if err.is_some() {
             return Ok(HttpResponse::BadRequest().json(MetaHttpResponse::error(
@@ -141,7 +156,8 @@ pub async fn test_run_function(
             .as_array()
             .unwrap()
             .iter()
-            .filter_map(|v| {
+            .enumerate()
+            .filter_map(|(i, v)| {
                 let flattened_array = v
                     .as_array()
                     .unwrap_or(&vec![])
@@ -151,23 +167,40 @@ pub async fn test_run_function(
                 if flattened_array.is_empty() {
                     return None;
                 }
-                Some(serde_json::Value::Array(flattened_array))
+                Some((i, flattened_array))
             })
-            .for_each(|transform| {
-                transformed_events.push(VRLResult::new("", transform));
+            .for_each(|(i, transform)| {
+                let input_hits = inputs
+                    .get(i)
+                    .and_then(|hits| hits.as_array())
+                    .map(|hits| {
+                        hits.iter()
+                            .map(|hit| {
+                                config::utils::flatten::flatten(hit.clone()).unwrap_or(hit.clone())
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                let diff = hits_field_diff(&input_hits, &transform);
+                let mut result = VRLResult::new("", serde_json::Value::Array(transform));
+                result.diff = diff;
+                transformed_events.push(result);
             });
     } else {
         events.into_iter().for_each(|event| {
-            let (ret_val, err) = crate::service::ingestion::apply_vrl_fn(
-                &mut runtime,
-                &config::meta::function::VRLResultResolver {
-                    program: program.clone(),
-                    fields: fields.clone(),
-                },
-                event.clone(),
-                org_id,
-                &[String::new()],
-            );
+            let ((ret_val, err), fn_logs) = capture_vrl_logs(|| {
+                crate::service::ingestion::apply_vrl_fn(
+                    &mut runtime,
+                    &config::meta::function::VRLResultResolver {
+                        program: program.clone(),
+                        fields: fields.clone(),
+                    },
+                    event.clone(),
+                    org_id,
+                    &[String::new()],
+                )
+            });
+            logs.extend(fn_logs);
             if let Some(err) = err {
                 transformed_events.push(VRLResult::new(&err, event));
                 return;
@@ -178,17 +211,43 @@ pub async fn test_run_function(
             } else {
                 "".into()
             };
-            transformed_events.push(VRLResult::new("", transform));
+            let input = config::utils::flatten::flatten(event.clone()).unwrap_or(event);
+            let mut result = VRLResult::new("", transform);
+            result.diff = FieldDiff::new(&input, &result.event);
+            transformed_events.push(result);
         });
     }
 
     let results = TestVRLResponse {
         results: transformed_events,
+        took: start.elapsed().as_micros() as u64,
+        logs,
     };
 
     Ok(HttpResponse::Ok().json(results))
 }
 
+/// Field diff of a function run over all the hits, each output hit is compared
+/// with the input hit at the same position.
+fn hits_field_diff(inputs: &[json::Value], outputs: &[json::Value]) -> FieldDiff {
+    let empty = json::Value::Object(Default::default());
+    let mut diff = FieldDiff::default();
+    for i in 0..inputs.len().max(outputs.len()) {
+        let hit_diff = FieldDiff::new(
+            inputs.get(i).unwrap_or(&empty),
+            outputs.get(i).unwrap_or(&empty),
+        );
+        diff.added.extend(hit_diff.added);
+        diff.removed.extend(hit_diff.removed);
+        diff.modified.extend(hit_diff.modified);
+    }
+    for fields in [&mut diff.added, &mut diff.removed, &mut diff.modified] {
+        fields.sort();
+        fields.dedup();
+    }
+    diff
+}
+
 #[tracing::instrument(skip(func))]
 pub async fn update_function(
     org_id: &str,
@@ -231,28 +290,142 @@ pub async fn update_function(
     }
 
     // update associated pipelines
+    if let Err(e) = update_associated_pipelines(org_id, &func.name).await {
+        return Ok(
+            HttpResponse::InternalServerError().json(MetaHttpResponse::message(
+                http::StatusCode::INTERNAL_SERVER_ERROR.into(),
+                e,
+            )),
+        );
+    }
+
+    Ok(HttpResponse::Ok().json(MetaHttpResponse::message(
+        http::StatusCode::OK.into(),
+        FN_SUCCESS.to_string(),
+    )))
+}
+
+async fn update_associated_pipelines(org_id: &str, fn_name: &str) -> Result<(), String> {
     if let Ok(associated_pipelines) = db::pipeline::list_by_org(org_id).await {
         for pipeline in associated_pipelines {
-            if pipeline.contains_function(&func.name) {
+            if pipeline.contains_function(fn_name) {
                 if let Err(e) = db::pipeline::update(&pipeline, None).await {
-                    return Ok(HttpResponse::InternalServerError().json(
-                        MetaHttpResponse::message(
-                            http::StatusCode::INTERNAL_SERVER_ERROR.into(),
-                            format!(
-                                "Failed to update associated pipeline({}/{}): {}",
-                                pipeline.id, pipeline.name, e
-                            ),
-                        ),
+                    return Err(format!(
+                        "Failed to update associated pipeline({}/{}): {}",
+                        pipeline.id, pipeline.name, e
                     ));
                 }
             }
         }
     }
+    Ok(())
+}
 
-    Ok(HttpResponse::Ok().json(MetaHttpResponse::message(
-        http::StatusCode::OK.into(),
-        FN_SUCCESS.to_string(),
-    )))
+pub async fn export_functions(
+    org_id: &str,
+    permitted: Option<Vec<String>>,
+) -> Result<HttpResponse, Error> {
+    let functions = db::functions::list(org_id).await.unwrap_or_default();
+    let functions = functions
+        .into_iter()
+        .filter(|function| {
+            permitted.as_ref().is_none_or(|permitted| {
+                permitted.contains(&format!("function:{}", function.name))
+                    || permitted.contains(&format!("function:_all_{}", org_id))
+            })
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(FunctionBundle::new(functions)))
+}
+
+/// Imports the functions of a bundle into the org. Functions whose name already
+/// exists are skipped, or replaced when `overwrite` is set. Each function is
+/// validated independently, so one invalid function doesn't abort the import.
+pub async fn import_functions(
+    org_id: &str,
+    bundle: FunctionBundle,
+    overwrite: bool,
+) -> Result<HttpResponse, Error> {
+    if bundle.version > config::meta::function::FUNCTION_BUNDLE_VERSION {
+        return Ok(HttpResponse::BadRequest().json(MetaHttpResponse::error(
+            StatusCode::BAD_REQUEST.into(),
+            format!("Unsupported function bundle version: {}", bundle.version),
+        )));
+    }
+
+    let mut resp = FunctionImportResponse::default();
+    let mut seen = std::collections::HashSet::new();
+    for mut func in bundle.functions {
+        func.name = func.name.trim().to_string();
+        func.function = func.function.trim().to_string();
+        if func.name.is_empty() {
+            resp.errors.push(FunctionImportError {
+                name: func.name,
+                error: "Function name is required".to_string(),
+            });
+            continue;
+        }
+        if !seen.insert(func.name.clone()) {
+            resp.errors.push(FunctionImportError {
+                name: func.name,
+                error: "Duplicate function name in bundle".to_string(),
+            });
+            continue;
+        }
+
+        let existing_fn = check_existing_fn(org_id, &func.name).await;
+        if existing_fn.is_some() && !overwrite {
+            resp.skipped.push(func.name);
+            continue;
+        }
+
+        if let Err(e) = prepare_function(org_id, &mut func) {
+            resp.errors.push(FunctionImportError {
+                name: func.name,
+                error: e,
+            });
+            continue;
+        }
+        // keep the stream associations of the target org
+        func.streams = existing_fn.as_ref().and_then(|f| f.streams.clone());
+
+        if let Err(e) = db::functions::set(org_id, &func.name, &func).await {
+            resp.errors.push(FunctionImportError {
+                name: func.name,
+                error: e.to_string(),
+            });
+            continue;
+        }
+        if existing_fn.is_some() {
+            if let Err(e) = update_associated_pipelines(org_id, &func.name).await {
+                resp.errors.push(FunctionImportError {
+                    name: func.name,
+                    error: e,
+                });
+                continue;
+            }
+            resp.updated.push(func.name);
+        } else {
+            set_ownership(org_id, "functions", Authz::new(&func.name)).await;
+            resp.created.push(func.name);
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+fn prepare_function(org_id: &str, func: &mut Transform) -> Result<(), String> {
+    if !func.function.ends_with('.') {
+        func.function = format!("{} \n .", func.function);
+    }
+    let trans_type = *func.trans_type.get_or_insert(0);
+    if trans_type == 0 {
+        compile_vrl_function(&func.function, org_id).map_err(|e| e.to_string())?;
+    } else if !(func.function.contains('(') && func.function.contains(')')) {
+        return Err("Invalid function definition".to_string());
+    }
+    extract_num_args(func);
+    Ok(())
 }
 
 pub async fn list_functions(
@@ -282,7 +455,11 @@ pub async fn list_functions(
     }
 }
 
-pub async fn delete_function(org_id: String, fn_name: String) -> Result<HttpResponse, Error> {
+pub async fn delete_function(
+    org_id: String,
+    fn_name: String,
+    force: bool,
+) -> Result<HttpResponse, Error> {
     let existing_fn = match check_existing_fn(&org_id, &fn_name).await {
         Some(function) => function,
         None => {
@@ -315,18 +492,11 @@ pub async fn delete_function(org_id: String, fn_name: String) -> Result<HttpResp
             }
         }
     }
-    let pipeline_dep = get_dependencies(&org_id, &fn_name).await;
-    if !pipeline_dep.is_empty() {
-        let pipeline_data = serde_json::to_string(&pipeline_dep).unwrap_or("[]".to_string());
-        return Ok(HttpResponse::Conflict().json(MetaHttpResponse::error(
-            http::StatusCode::CONFLICT.into(),
-            format!(
-                "Warning: Function '{}' has {} pipeline dependencies. Please remove these pipelines first: {}",
-                fn_name,
-                pipeline_dep.len(),
-                pipeline_data
-            ),
-        )));
+    if !force {
+        let dependents = list_dependents(&org_id, &fn_name, &existing_fn.function).await;
+        if let Some(resp) = function_in_use(&fn_name, dependents) {
+            return Ok(resp);
+        }
     }
     let result = db::functions::delete(&org_id, &fn_name).await;
     match result {
@@ -349,8 +519,79 @@ pub async fn get_pipeline_dependencies(
     org_id: &str,
     func_name: &str,
 ) -> Result<HttpResponse, Error> {
-    let list = get_dependencies(org_id, func_name).await;
-    Ok(HttpResponse::Ok().json(PipelineDependencyResponse { list }))
+    let function = check_existing_fn(org_id, func_name)
+        .await
+        .map(|func| func.function)
+        .unwrap_or_default();
+    Ok(HttpResponse::Ok().json(list_dependents(org_id, func_name, &function).await))
+}
+
+/// Pipelines and alerts running the function.
+async fn list_dependents(
+    org_id: &str,
+    func_name: &str,
+    function: &str,
+) -> PipelineDependencyResponse {
+    PipelineDependencyResponse {
+        list: get_dependencies(org_id, func_name).await,
+        alerts: get_alert_dependencies(org_id, function).await,
+    }
+}
+
+/// The 409 refusing to delete a function which still has dependents.
+fn function_in_use(fn_name: &str, dependents: PipelineDependencyResponse) -> Option<HttpResponse> {
+    if dependents.list.is_empty() && dependents.alerts.is_empty() {
+        return None;
+    }
+    Some(HttpResponse::Conflict().json(FunctionInUseResponse {
+        code: http::StatusCode::CONFLICT.into(),
+        message: format!(
+            "Function '{}' is used by {} pipelines and {} alerts, remove it from them first or delete with force=true",
+            fn_name,
+            dependents.list.len(),
+            dependents.alerts.len()
+        ),
+        dependents,
+    }))
+}
+
+/// Alerts store a copy of the vrl function they run, so an alert depends on a
+/// function when its query function is the same program.
+async fn get_alert_dependencies(org_id: &str, function: &str) -> Vec<AlertDependencyItem> {
+    let client = ORM_CLIENT.get_or_init(connect_to_orm).await;
+    db::alerts::alert::list_with_folders(client, ListAlertsParams::new(org_id))
+        .await
+        .map_or(vec![], |alerts| {
+            alerts
+                .into_iter()
+                .filter(|(_folder, alert)| alert_uses_function(alert, function))
+                .map(|(_folder, alert)| AlertDependencyItem {
+                    id: alert.id.map(|id| id.to_string()),
+                    name: alert.name,
+                    stream_type: alert.stream_type,
+                    stream_name: alert.stream_name,
+                })
+                .collect()
+        })
+}
+
+fn alert_uses_function(alert: &Alert, function: &str) -> bool {
+    let function = normalize_vrl(function);
+    if function.is_empty() {
+        return false;
+    }
+    alert
+        .query_condition
+        .vrl_function
+        .as_ref()
+        .and_then(|vrl| base64::decode_url(vrl).ok())
+        .is_some_and(|vrl| normalize_vrl(&vrl) == function)
+}
+
+/// Strips whitespace and the trailing `.` that is appended when saving.
+fn normalize_vrl(vrl: &str) -> &str {
+    let vrl = vrl.trim();
+    vrl.strip_suffix('.').unwrap_or(vrl).trim_end()
 }
 
 async fn get_dependencies(org_id: &str, func_name: &str) -> Vec<PipelineDependencyItem> {
@@ -395,9 +636,18 @@ async fn check_existing_fn(org_id: &str, fn_name: &str) -> Option<Transform> {
 #[cfg(test)]
 mod tests {
     use actix_http::body::to_bytes;
-    use config::meta::{function::StreamOrder, stream::StreamType};
+    use config::meta::{
+        folder::{Folder, FolderType, DEFAULT_FOLDER},
+        function::StreamOrder,
+        pipeline::{
+            components::{Edge, FunctionParams, Node, NodeData, PipelineSource},
+            Pipeline,
+        },
+        stream::{StreamParams, StreamType},
+    };
 
     use super::*;
+    use crate::service::folders;
 
     #[tokio::test]
     async fn test_functions() {
@@ -439,9 +689,211 @@ mod tests {
         let list_resp = list_functions("nexus".to_string(), None).await;
         assert!(list_resp.is_ok());
 
-        assert!(delete_function("nexus".to_string(), "dummyfn".to_owned())
+        assert!(
+            delete_function("nexus".to_string(), "dummyfn".to_owned(), false)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_import_functions() {
+        let trans = Transform {
+            function: ".bundled = true".to_owned(),
+            name: "bundle_fn".to_owned(),
+            params: "row".to_owned(),
+            streams: None,
+            num_args: 0,
+            trans_type: Some(0),
+        };
+        assert!(save_function("bundle_src".to_owned(), trans).await.is_ok());
+
+        let resp = export_functions("bundle_src", None).await.unwrap();
+        assert_eq!(resp.status(), http::StatusCode::OK);
+        let bundle: FunctionBundle =
+            serde_json::from_slice(&to_bytes(resp.into_body()).await.unwrap()).unwrap();
+        assert_eq!(bundle.functions.len(), 1);
+        assert_eq!(bundle.functions[0].name, "bundle_fn");
+
+        let resp = import_functions("bundle_dst", bundle.clone(), false)
+            .await
+            .unwrap();
+        let body: FunctionImportResponse =
+            serde_json::from_slice(&to_bytes(resp.into_body()).await.unwrap()).unwrap();
+        assert_eq!(body.created, vec!["bundle_fn".to_string()]);
+        assert!(body.errors.is_empty());
+        let imported = check_existing_fn("bundle_dst", "bundle_fn").await.unwrap();
+        assert_eq!(imported, bundle.functions[0]);
+
+        // importing again skips existing names unless overwrite is set
+        let resp = import_functions("bundle_dst", bundle.clone(), false)
+            .await
+            .unwrap();
+        let body: FunctionImportResponse =
+            serde_json::from_slice(&to_bytes(resp.into_body()).await.unwrap()).unwrap();
+        assert_eq!(body.skipped, vec!["bundle_fn".to_string()]);
+
+        let resp = import_functions("bundle_dst", bundle, true).await.unwrap();
+        let body: FunctionImportResponse =
+            serde_json::from_slice(&to_bytes(resp.into_body()).await.unwrap()).unwrap();
+        assert_eq!(body.updated, vec!["bundle_fn".to_string()]);
+
+        // invalid functions are reported without aborting the import
+        let bundle = FunctionBundle::new(vec![Transform {
+            function: ".a = ".to_owned(),
+            name: "broken_fn".to_owned(),
+            params: "row".to_owned(),
+            streams: None,
+            num_args: 0,
+            trans_type: Some(0),
+        }]);
+        let resp = import_functions("bundle_dst", bundle, false).await.unwrap();
+        let body: FunctionImportResponse =
+            serde_json::from_slice(&to_bytes(resp.into_body()).await.unwrap()).unwrap();
+        assert!(body.created.is_empty());
+        assert_eq!(body.errors.len(), 1);
+        assert_eq!(body.errors[0].name, "broken_fn");
+    }
+
+    #[test]
+    fn test_alert_uses_function() {
+        let mut alert = Alert::default();
+        assert!(!alert_uses_function(&alert, ".a = 1 \n ."));
+
+        alert.query_condition.vrl_function = Some(base64::encode_url(".a = 1\n."));
+        assert!(alert_uses_function(&alert, ".a = 1 \n ."));
+        assert!(alert_uses_function(&alert, ".a = 1"));
+        assert!(!alert_uses_function(&alert, ".b = 1 \n ."));
+        assert!(!alert_uses_function(&alert, "."));
+
+        alert.query_condition.vrl_function = Some("not base64!".to_string());
+        assert!(!alert_uses_function(&alert, ".a = 1"));
+    }
+
+    #[tokio::test]
+    async fn test_delete_function_in_use() {
+        infra::pipeline::init().await.unwrap();
+        let org_id = "fn_in_use_org";
+        let func = Transform {
+            function: ".a = 1 \n .".to_owned(),
+            name: "pipeline_fn".to_owned(),
+            params: "row".to_owned(),
+            streams: None,
+            num_args: 0,
+            trans_type: Some(0),
+        };
+        save_function(org_id.to_owned(), func).await.unwrap();
+
+        let source = StreamParams::new(org_id, "fn_src", StreamType::Logs);
+        let dest = StreamParams::new(org_id, "fn_dst", StreamType::Logs);
+        let pipeline = Pipeline {
+            id: "test_delete_function_in_use".to_string(),
+            version: 0,
+            enabled: true,
+            org: org_id.to_string(),
+            name: "pipeline_using_fn".to_string(),
+            description: "".to_string(),
+            source: PipelineSource::Realtime(source.clone()),
+            nodes: vec![
+                Node::new(
+                    "1".to_string(),
+                    NodeData::Stream(source),
+                    0.0,
+                    0.0,
+                    "input".to_string(),
+                ),
+                Node::new(
+                    "2".to_string(),
+                    NodeData::Function(FunctionParams {
+                        name: "pipeline_fn".to_string(),
+                        after_flatten: false,
+                        num_args: 0,
+                    }),
+                    0.0,
+                    0.0,
+                    "default".to_string(),
+                ),
+                Node::new(
+                    "3".to_string(),
+                    NodeData::Stream(dest),
+                    0.0,
+                    0.0,
+                    "output".to_string(),
+                ),
+            ],
+            edges: vec![
+                Edge::new("1".to_string(), "2".to_string()),
+                Edge::new("2".to_string(), "3".to_string()),
+            ],
+            dead_letter_stream: None,
+        };
+        db::pipeline::set(&pipeline).await.unwrap();
+
+        // the pipeline still runs the function
+        let resp = delete_function(org_id.to_owned(), "pipeline_fn".to_owned(), false)
             .await
-            .is_ok());
+            .unwrap();
+        assert_eq!(resp.status(), http::StatusCode::CONFLICT);
+        let body: FunctionInUseResponse =
+            serde_json::from_slice(&to_bytes(resp.into_body()).await.unwrap()).unwrap();
+        assert_eq!(body.code, 409);
+        assert_eq!(body.dependents.list.len(), 1);
+        assert_eq!(body.dependents.list[0].id, pipeline.id);
+        assert_eq!(body.dependents.list[0].name, "pipeline_using_fn");
+        assert!(check_existing_fn(org_id, "pipeline_fn").await.is_some());
+
+        // force deletes it anyway
+        let resp = delete_function(org_id.to_owned(), "pipeline_fn".to_owned(), true)
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), http::StatusCode::OK);
+        assert!(check_existing_fn(org_id, "pipeline_fn").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_function_alert_dependencies() {
+        infra::table::migrate().await.unwrap();
+        let org_id = "fn_alert_dep_org";
+        let func = Transform {
+            function: ".a = 1 \n .".to_owned(),
+            name: "alert_fn".to_owned(),
+            params: "row".to_owned(),
+            streams: None,
+            num_args: 0,
+            trans_type: Some(0),
+        };
+        save_function(org_id.to_owned(), func).await.unwrap();
+
+        let folder = Folder {
+            folder_id: DEFAULT_FOLDER.to_owned(),
+            name: "default".to_owned(),
+            description: "default".to_owned(),
+        };
+        let _ = folders::save_folder(org_id, folder, FolderType::Alerts, true).await;
+        let mut alert = Alert::default();
+        alert.name = "alert_using_fn".to_string();
+        alert.stream_name = "fn_alert_src".to_string();
+        alert.query_condition.vrl_function = Some(base64::encode_url(".a = 1\n."));
+        let client = ORM_CLIENT.get_or_init(connect_to_orm).await;
+        db::alerts::alert::create(client, org_id, DEFAULT_FOLDER, alert)
+            .await
+            .unwrap();
+
+        let resp = get_pipeline_dependencies(org_id, "alert_fn").await.unwrap();
+        assert_eq!(resp.status(), http::StatusCode::OK);
+        let body: PipelineDependencyResponse =
+            serde_json::from_slice(&to_bytes(resp.into_body()).await.unwrap()).unwrap();
+        assert!(body.list.is_empty());
+        assert_eq!(body.alerts.len(), 1);
+        assert_eq!(body.alerts[0].name, "alert_using_fn");
+        assert_eq!(body.alerts[0].stream_name, "fn_alert_src");
+
+        // the alert blocks deleting the function
+        let resp = delete_function(org_id.to_owned(), "alert_fn".to_owned(), false)
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), http::StatusCode::CONFLICT);
+        assert!(check_existing_fn(org_id, "alert_fn").await.is_some());
     }
 
     #[tokio::test]
@@ -478,4 +930,79 @@ mod tests {
             json! {{"nested_key":42,"new_field":"new_value"}}
         );
     }
+
+    #[tokio::test]
+    async fn test_run_function_field_diff() {
+        let function = r#"
+            .added = "new"
+            .level = upcase!(.level)
+            del(.removed)
+            log("diffed", level: "info")
+            ."#;
+        let events = vec![json::json!({
+            "level": "info",
+            "message": "unchanged",
+            "removed": true
+        })];
+        let resp = test_run_function("default", function.to_string(), events)
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), http::StatusCode::OK);
+        let body: TestVRLResponse =
+            serde_json::from_slice(&to_bytes(resp.into_body()).await.unwrap()).unwrap();
+        assert_eq!(body.results.len(), 1);
+        assert_eq!(
+            body.results[0].diff,
+            FieldDiff {
+                added: vec!["added".to_string()],
+                removed: vec!["removed".to_string()],
+                modified: vec!["level".to_string()],
+            }
+        );
+        assert_eq!(body.logs, vec!["[INFO] diffed".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_run_function_field_diff_over_hits() {
+        let function = r#"#ResultArray#
+            . = map_values(array!(.)) -> |hits| {
+                map_values(array!(hits)) -> |hit| {
+                    merge(object!(hit), {"added": "new"})
+                }
+            }
+            ."#;
+        let events = vec![json::json!([
+            {"level": "info"},
+            {"level": "warn", "message": "unchanged"}
+        ])];
+        let resp = test_run_function("default", function.to_string(), events)
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), http::StatusCode::OK);
+        let body: TestVRLResponse =
+            serde_json::from_slice(&to_bytes(resp.into_body()).await.unwrap()).unwrap();
+        assert_eq!(body.results.len(), 1);
+        assert_eq!(
+            body.results[0].diff,
+            FieldDiff {
+                added: vec!["added".to_string()],
+                removed: vec![],
+                modified: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_hits_field_diff() {
+        let inputs = vec![json::json!({"a": 1}), json::json!({"a": 2, "b": 1})];
+        let outputs = vec![json::json!({"a": 1, "c": 1})];
+        assert_eq!(
+            hits_field_diff(&inputs, &outputs),
+            FieldDiff {
+                added: vec!["c".to_string()],
+                removed: vec!["a".to_string(), "b".to_string()],
+                modified: vec![],
+            }
+        );
+    }
 }
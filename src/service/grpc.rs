@@ -18,7 +18,9 @@ use std::sync::Arc;
 use config::{get_config, meta::cluster::NodeInfo, utils::rand::get_rand_element, RwAHashMap};
 use infra::errors::{Error, ErrorCodes};
 use once_cell::sync::Lazy;
-use proto::cluster_rpc::{self, metrics_client::MetricsClient, search_client::SearchClient};
+use proto::cluster_rpc::{
+    self, ingest_client::IngestClient, metrics_client::MetricsClient, search_client::SearchClient,
+};
 use tonic::{
     codec::CompressionEncoding,
     metadata::{MetadataKey, MetadataValue},
@@ -151,6 +153,44 @@ pub async fn make_grpc_search_client<T>(
         .max_encoding_message_size(cfg.grpc.max_message_size * 1024 * 1024))
 }
 
+pub async fn make_grpc_ingest_client<T>(
+    request: &mut Request<T>,
+    node: &Arc<dyn NodeInfo>,
+) -> Result<
+    IngestClient<InterceptedService<Channel, impl Fn(Request<()>) -> Result<Request<()>, Status>>>,
+    Error,
+> {
+    let cfg = get_config();
+    request.set_timeout(std::time::Duration::from_secs(cfg.limit.query_timeout));
+
+    let token: MetadataValue<_> = node
+        .get_auth_token()
+        .parse()
+        .map_err(|_| Error::Message("invalid token".to_string()))?;
+    let channel = get_cached_channel(&node.get_grpc_addr())
+        .await
+        .map_err(|err| {
+            log::error!(
+                "ingest->grpc: node: {}, connect err: {:?}",
+                &node.get_grpc_addr(),
+                err
+            );
+            Error::ErrorCode(ErrorCodes::ServerInternalError(err.to_string()))
+        })?;
+    let client = cluster_rpc::ingest_client::IngestClient::with_interceptor(
+        channel,
+        move |mut req: Request<()>| {
+            req.metadata_mut().insert("authorization", token.clone());
+            Ok(req)
+        },
+    );
+    Ok(client
+        .send_compressed(CompressionEncoding::Gzip)
+        .accept_compressed(CompressionEncoding::Gzip)
+        .max_decoding_message_size(cfg.grpc.max_message_size * 1024 * 1024)
+        .max_encoding_message_size(cfg.grpc.max_message_size * 1024 * 1024))
+}
+
 #[tracing::instrument(name = "promql:search:grpc:metrics:make_client", skip_all)]
 pub async fn make_grpc_metrics_client<T>(
     trace_id: &str,
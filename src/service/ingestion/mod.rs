@@ -329,7 +329,7 @@ pub fn get_write_partition_key(
         if key.disabled {
             continue;
         }
-        let val = match local_val.get(&key.field) {
+        let val = match key.get_record_value(local_val) {
             Some(v) => get_string_value(v),
             None => "null".to_string(),
         };
@@ -413,6 +413,22 @@ pub fn check_ingestion_allowed(org_id: &str, stream_name: Option<&str>) -> Resul
     Ok(())
 }
 
+/// Refuses the records of a stream whose ingestion is paused by its
+/// `ingestion_paused` setting.
+pub async fn check_ingestion_paused(
+    org_id: &str,
+    stream_type: StreamType,
+    stream_name: &str,
+) -> Result<()> {
+    if infra::schema::get_settings(org_id, stream_name, stream_type)
+        .await
+        .is_some_and(|settings| settings.ingestion_paused)
+    {
+        return Err(anyhow!("ingestion of stream [{stream_name}] is paused"));
+    }
+    Ok(())
+}
+
 pub fn get_val_for_attr(attr_val: &Value) -> Value {
     let local_val = attr_val.as_object().unwrap();
     if let Some((key, value)) = local_val.into_iter().next() {
@@ -573,6 +589,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_write_partition_key_nested_field() {
+        let partition_keys = vec![StreamPartition::new("resource.service.name")];
+        // ingestion flattens records before computing the partition
+        let record = flatten::flatten(json!({
+            "resource": {"service": {"name": "checkout"}},
+            "message": "ok",
+        }))
+        .unwrap();
+        assert_eq!(
+            get_write_partition_key(
+                1620000000,
+                &partition_keys,
+                PartitionTimeLevel::Hourly,
+                record.as_object().unwrap(),
+                None
+            ),
+            "1970/01/01/00/default/resource_service_name=checkout"
+        );
+    }
+
     #[test]
     fn test_get_write_partition_key_no_partition_keys() {
         let mut local_val = Map::new();
@@ -624,6 +661,45 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_check_ingestion_paused() {
+        let mut meta = HashMap::new();
+        meta.insert(
+            "settings".to_string(),
+            r#"{"ingestion_paused": true}"#.to_string(),
+        );
+        let schema = arrow_schema::Schema::empty().with_metadata(meta);
+        let mut settings = unwrap_stream_settings(&schema).unwrap();
+        let key = "default/logs/test_paused";
+        STREAM_SETTINGS
+            .write()
+            .await
+            .insert(key.to_string(), settings.clone());
+        let err = check_ingestion_paused("default", StreamType::Logs, "test_paused")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("is paused"));
+        // other streams are not affected
+        assert!(
+            check_ingestion_paused("default", StreamType::Traces, "test_paused")
+                .await
+                .is_ok()
+        );
+
+        // ingestion resumes once unpaused
+        settings.ingestion_paused = false;
+        STREAM_SETTINGS
+            .write()
+            .await
+            .insert(key.to_string(), settings);
+        assert!(
+            check_ingestion_paused("default", StreamType::Logs, "test_paused")
+                .await
+                .is_ok()
+        );
+        STREAM_SETTINGS.write().await.remove(key);
+    }
+
     #[tokio::test]
     async fn test_compile_vrl_function() {
         let result = compile_vrl_function(
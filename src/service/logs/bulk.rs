@@ -37,7 +37,7 @@ use crate::{
     common::meta::ingestion::{BulkResponse, BulkResponseError, BulkResponseItem, IngestionStatus},
     service::{
         format_stream_name,
-        ingestion::check_ingestion_allowed,
+        ingestion::{check_ingestion_allowed, check_ingestion_paused},
         pipeline::batch_execution::{ExecutablePipeline, ExecutablePipelineBulkInputs},
         schema::get_upto_discard_error,
     },
@@ -46,6 +46,7 @@ use crate::{
 pub const TRANSFORM_FAILED: &str = "document_failed_transform";
 pub const TS_PARSE_FAILED: &str = "timestamp_parsing_failed";
 pub const SCHEMA_CONFORMANCE_FAILED: &str = "schema_conformance_failed";
+pub const SCHEMA_FIELDS_LIMIT_EXCEEDED: &str = "schema_fields_limit_exceeded";
 pub const PIPELINE_EXEC_FAILED: &str = "pipeline_execution_failed";
 
 pub async fn ingest(
@@ -77,6 +78,7 @@ pub async fn ingest(
     let mut doc_id = None;
 
     let mut blocked_stream_warnings: HashMap<String, bool> = HashMap::new();
+    let mut paused_streams: HashMap<String, bool> = HashMap::new();
 
     let mut stream_executable_pipelines: HashMap<String, Option<ExecutablePipeline>> =
         HashMap::new();
@@ -86,6 +88,7 @@ pub async fn ingest(
     let mut streams_need_original_set: HashSet<String> = HashSet::new();
 
     let mut json_data_by_stream = HashMap::new();
+    let mut rejected = super::rejected_stream_enabled(org_id).await.then(Vec::new);
     let mut next_line_is_data = false;
     let reader = BufReader::new(body.as_ref());
     for line in reader.lines() {
@@ -144,6 +147,30 @@ pub async fn ingest(
                 continue; // skip
             }
 
+            // a bulk request writes to several streams, drop the records of
+            // the paused ones instead of refusing the whole request
+            let paused =
+                match paused_streams.get(&stream_name) {
+                    Some(paused) => *paused,
+                    None => {
+                        let paused =
+                            match check_ingestion_paused(org_id, StreamType::Logs, &stream_name)
+                                .await
+                            {
+                                Ok(_) => false,
+                                Err(e) => {
+                                    log::warn!("{e}, dropping its records");
+                                    true
+                                }
+                            };
+                        paused_streams.insert(stream_name.clone(), paused);
+                        paused
+                    }
+                };
+            if paused {
+                continue; // skip
+            }
+
             let mut streams = vec![StreamParams {
                 org_id: org_id.to_owned().into(),
                 stream_type: StreamType::Logs,
@@ -216,6 +243,12 @@ pub async fn ingest(
                         ])
                         .inc();
                     log_failed_record(log_ingestion_errors, &value, TS_PARSE_FAILED);
+                    super::queue_rejected_record(
+                        rejected.as_mut(),
+                        &stream_name,
+                        &value,
+                        TS_PARSE_FAILED,
+                    );
                     add_record_status(
                         stream_name.clone(),
                         &doc_id,
@@ -241,6 +274,12 @@ pub async fn ingest(
                                 ])
                                 .inc();
                             log_failed_record(log_ingestion_errors, &local_val, TS_PARSE_FAILED);
+                            super::queue_rejected_record(
+                                rejected.as_mut(),
+                                &stream_name,
+                                &local_val,
+                                TS_PARSE_FAILED,
+                            );
                             add_record_status(
                                 stream_name.clone(),
                                 &doc_id,
@@ -316,6 +355,12 @@ pub async fn ingest(
                                 ])
                                 .inc();
                             log_failed_record(log_ingestion_errors, &value, TS_PARSE_FAILED);
+                            super::queue_rejected_record(
+                                rejected.as_mut(),
+                                &stream_name,
+                                &local_val,
+                                TS_PARSE_FAILED,
+                            );
                             add_record_status(
                                 stream_name.clone(),
                                 &doc_id,
@@ -344,6 +389,12 @@ pub async fn ingest(
                         ])
                         .inc();
                     log_failed_record(log_ingestion_errors, &value, TS_PARSE_FAILED);
+                    super::queue_rejected_record(
+                        rejected.as_mut(),
+                        &stream_name,
+                        &local_val,
+                        failure_reason.as_deref().unwrap_or(TS_PARSE_FAILED),
+                    );
                     add_record_status(
                         stream_name.clone(),
                         &doc_id,
@@ -473,6 +524,12 @@ pub async fn ingest(
                                     &local_val,
                                     TS_PARSE_FAILED,
                                 );
+                                super::queue_rejected_record(
+                                    rejected.as_mut(),
+                                    &stream_params.stream_name,
+                                    &local_val,
+                                    TS_PARSE_FAILED,
+                                );
                                 add_record_status(
                                     stream_params.stream_name.to_string(),
                                     &doc_ids[idx],
@@ -498,6 +555,12 @@ pub async fn ingest(
                                     ])
                                     .inc();
                                 log_failed_record(log_ingestion_errors, &local_val, &error);
+                                super::queue_rejected_record(
+                                    rejected.as_mut(),
+                                    &stream_params.stream_name,
+                                    &local_val,
+                                    &error,
+                                );
                                 let failure_reason = Some(error);
                                 add_record_status(
                                     stream_params.stream_name.to_string(),
@@ -527,6 +590,10 @@ pub async fn ingest(
         }
     }
 
+    if let Some(rejected) = rejected.filter(|v| !v.is_empty()) {
+        super::write_rejected_records(thread_id, org_id, rejected).await;
+    }
+
     // drop memory-intensive variables
     drop(stream_pipeline_inputs);
     drop(streams_need_original_set);
@@ -24,7 +24,7 @@ use chrono::{Duration, Utc};
 use config::{
     meta::{
         self_reporting::usage::UsageType,
-        stream::{StreamParams, StreamType},
+        stream::{StreamParams, StreamType, WebhookMapping},
     },
     metrics,
     utils::{flatten, json, time::parse_timestamp_micro_from_value},
@@ -47,8 +47,10 @@ use crate::{
         StreamStatus,
     },
     service::{
-        format_stream_name, get_formatted_stream_name, ingestion::check_ingestion_allowed,
-        logs::bulk::TRANSFORM_FAILED, schema::get_upto_discard_error,
+        format_stream_name, get_formatted_stream_name,
+        ingestion::{check_ingestion_allowed, check_ingestion_paused},
+        logs::bulk::TRANSFORM_FAILED,
+        schema::get_upto_discard_error,
     },
 };
 
@@ -74,6 +76,13 @@ pub async fn ingest(
         format_stream_name(in_stream_name)
     };
     check_ingestion_allowed(org_id, Some(&stream_name))?;
+    if let Err(e) = check_ingestion_paused(org_id, StreamType::Logs, &stream_name).await {
+        return Ok(IngestionResponse {
+            code: http::StatusCode::SERVICE_UNAVAILABLE.into(),
+            status: vec![],
+            error: Some(e.to_string()),
+        });
+    }
 
     let min_ts = (Utc::now() - Duration::try_hours(cfg.limit.ingest_allowed_upto).unwrap())
         .timestamp_micros();
@@ -107,6 +116,10 @@ pub async fn ingest(
     .await;
     // End get user defined schema
 
+    let stream_settings = infra::schema::get_settings(org_id, &stream_name, StreamType::Logs)
+        .await
+        .unwrap_or_default();
+
     let json_req: Vec<json::Value>; // to hold json request because of borrow checker
     let (endpoint, usage_type, data) = match in_req {
         IngestionRequest::JSON(req) => {
@@ -140,6 +153,23 @@ pub async fn ingest(
             UsageType::RUM,
             IngestionData::Multi(req),
         ),
+        IngestionRequest::Webhook(req) => {
+            let mut values: Vec<json::Value> = json::from_slice(req).unwrap_or({
+                let val: json::Value = json::from_slice(req)?;
+                vec![val]
+            });
+            if let Some(mapping) = stream_settings.webhook_mapping.as_ref() {
+                for val in values.iter_mut() {
+                    apply_webhook_mapping(val, mapping);
+                }
+            }
+            json_req = values;
+            (
+                "/api/org/ingest/logs/_webhook",
+                UsageType::Json,
+                IngestionData::JSON(&json_req),
+            )
+        }
         IngestionRequest::Usage(req) => {
             // no need to report usage for usage data
             need_usage_report = false;
@@ -157,6 +187,7 @@ pub async fn ingest(
 
     let mut stream_status = StreamStatus::new(&stream_name);
     let mut json_data_by_stream = HashMap::new();
+    let mut rejected = super::rejected_stream_enabled(org_id).await.then(Vec::new);
     for ret in data.iter() {
         let mut item = match ret {
             Ok(item) => item,
@@ -192,6 +223,9 @@ pub async fn ingest(
         };
 
         if executable_pipeline.is_some() {
+            if let Some(field) = stream_settings.timestamp_field.as_deref() {
+                set_timestamp_from_field(&mut item, field);
+            }
             // handle record's timestamp fist in case record is sent to remote destination
             if let Err(e) = handle_timestamp(&mut item, min_ts) {
                 stream_status.status.failed += 1;
@@ -205,6 +239,12 @@ pub async fn ingest(
                     ])
                     .inc();
                 log_failed_record(log_ingestion_errors, &item, &e.to_string());
+                super::queue_rejected_record(
+                    rejected.as_mut(),
+                    &stream_name,
+                    &item,
+                    &e.to_string(),
+                );
                 continue;
             };
             // buffer the records, timestamp, and originals for pipeline batch processing
@@ -215,6 +255,9 @@ pub async fn ingest(
             let mut res = flatten::flatten_with_level(item, cfg.limit.ingest_flatten_level)?;
 
             // handle timestamp
+            if let Some(field) = stream_settings.timestamp_field.as_deref() {
+                set_timestamp_from_field(&mut res, field);
+            }
             let timestamp = match handle_timestamp(&mut res, min_ts) {
                 Ok(ts) => ts,
                 Err(e) => {
@@ -229,6 +272,12 @@ pub async fn ingest(
                         ])
                         .inc();
                     log_failed_record(log_ingestion_errors, &res, &e.to_string());
+                    super::queue_rejected_record(
+                        rejected.as_mut(),
+                        &stream_name,
+                        &res,
+                        &e.to_string(),
+                    );
                     continue;
                 }
             };
@@ -344,6 +393,12 @@ pub async fn ingest(
                                 ])
                                 .inc();
                             log_failed_record(log_ingestion_errors, &local_val, err);
+                            super::queue_rejected_record(
+                                rejected.as_mut(),
+                                &stream_params.stream_name,
+                                &local_val,
+                                err,
+                            );
                             continue;
                         };
 
@@ -358,6 +413,10 @@ pub async fn ingest(
         }
     }
 
+    if let Some(rejected) = rejected.filter(|v| !v.is_empty()) {
+        super::write_rejected_records(thread_id, org_id, rejected).await;
+    }
+
     // if no data, fast return
     if json_data_by_stream.is_empty() {
         return Ok(IngestionResponse::new(
@@ -424,6 +483,48 @@ pub async fn ingest(
     ))
 }
 
+/// Uses the stream's own timestamp field as `_timestamp` unless the record
+/// already has one. The field itself is kept as ingested.
+pub fn set_timestamp_from_field(value: &mut json::Value, field: &str) {
+    let Some(local_val) = value.as_object_mut() else {
+        return;
+    };
+    if local_val.contains_key(TIMESTAMP_COL_NAME) {
+        return;
+    }
+    if let Some(v) = local_val.get(field).filter(|v| !v.is_null()).cloned() {
+        local_val.insert(TIMESTAMP_COL_NAME.to_string(), v);
+    }
+}
+
+/// Copies the mapped fields of a webhook payload to the top level, so they
+/// keep a stable name whatever the nesting depth, and takes the event time
+/// as `_timestamp`. The payload itself is flattened afterwards as usual.
+pub fn apply_webhook_mapping(value: &mut json::Value, mapping: &WebhookMapping) {
+    let mut mapped = Vec::with_capacity(mapping.fields.len() + 1);
+    if let Some(v) = mapping
+        .timestamp_field
+        .as_deref()
+        .and_then(|path| get_value_by_path(value, path))
+    {
+        mapped.push((TIMESTAMP_COL_NAME.to_string(), v.clone()));
+    }
+    for (path, target) in mapping.fields.iter() {
+        if let Some(v) = get_value_by_path(value, path) {
+            mapped.push((target.to_string(), v.clone()));
+        }
+    }
+    if let Some(local_val) = value.as_object_mut() {
+        local_val.extend(mapped);
+    }
+}
+
+fn get_value_by_path<'a>(value: &'a json::Value, path: &str) -> Option<&'a json::Value> {
+    path.split('.')
+        .try_fold(value, |v, key| v.as_object()?.get(key))
+        .filter(|v| !v.is_null())
+}
+
 pub fn handle_timestamp(value: &mut json::Value, min_ts: i64) -> Result<i64, anyhow::Error> {
     let local_val = value
         .as_object_mut()
@@ -831,12 +932,84 @@ fn construct_values_from_open_telemetry_v1_metric(
 
 #[cfg(test)]
 mod tests {
+    use config::{
+        meta::stream::{StreamSettings, WebhookMapping},
+        utils::{flatten, json},
+    };
+
     use super::{
-        decode_and_decompress_to_string, decode_and_decompress_to_vec,
+        apply_webhook_mapping, decode_and_decompress_to_string, decode_and_decompress_to_vec,
         deserialize_aws_record_from_vec, extract_resource_id_from_amazon_resource_number,
-        get_size_of_var_int_header,
+        get_size_of_var_int_header, handle_timestamp, set_timestamp_from_field,
     };
 
+    #[test]
+    fn test_set_timestamp_from_field() {
+        let settings = StreamSettings::from(r#"{"timestamp_field":"event_time"}"#);
+        let field = settings.timestamp_field.as_deref().unwrap();
+
+        let mut record = json::json!({"event_time": "2024-05-01T10:20:30Z", "msg": "a"});
+        set_timestamp_from_field(&mut record, field);
+        assert_eq!(handle_timestamp(&mut record, 0).unwrap(), 1714558830000000);
+        // the source field is still stored and queryable as is
+        assert_eq!(record["event_time"], "2024-05-01T10:20:30Z");
+
+        // epoch values are accepted like for _timestamp
+        let mut record = json::json!({"event_time": 1714558830000_i64});
+        set_timestamp_from_field(&mut record, field);
+        assert_eq!(handle_timestamp(&mut record, 0).unwrap(), 1714558830000000);
+
+        // an explicit _timestamp wins
+        let mut record = json::json!({"event_time": "2024-05-01T10:20:30Z", "_timestamp": 1});
+        set_timestamp_from_field(&mut record, field);
+        assert_eq!(record["_timestamp"], 1);
+    }
+
+    #[test]
+    fn test_apply_webhook_mapping_github_push() {
+        let mut payload = json::json!({
+            "ref": "refs/heads/main",
+            "repository": {
+                "id": 1296269,
+                "full_name": "octocat/Hello-World",
+                "owner": {"login": "octocat"}
+            },
+            "pusher": {"name": "octocat", "email": null},
+            "sender": {"login": "octocat", "type": "User"},
+            "head_commit": {
+                "id": "6dcb09b5b57875f334f61aebed695e2e4193db5e",
+                "message": "Fix all the bugs",
+                "timestamp": "2024-05-01T10:20:30Z"
+            }
+        });
+        let mapping: WebhookMapping = json::from_value(json::json!({
+            "timestamp_field": "head_commit.timestamp",
+            "fields": {
+                "repository.full_name": "repo",
+                "sender.login": "user",
+                "pusher.email": "email",
+                "missing.path": "missing"
+            }
+        }))
+        .unwrap();
+        apply_webhook_mapping(&mut payload, &mapping);
+
+        let mut record = flatten::flatten_with_level(payload, 3).unwrap();
+        let ts = handle_timestamp(&mut record, 0).unwrap();
+        assert_eq!(ts, 1714558830000000);
+
+        let record = record.as_object().unwrap();
+        assert_eq!(record["_timestamp"], 1714558830000000_i64);
+        assert_eq!(record["repo"], "octocat/Hello-World");
+        assert_eq!(record["user"], "octocat");
+        assert_eq!(record["repository_owner_login"], "octocat");
+        assert_eq!(record["head_commit_message"], "Fix all the bugs");
+        assert_eq!(record["ref"], "refs/heads/main");
+        // null and missing sources are not mapped
+        assert!(!record.contains_key("email"));
+        assert!(!record.contains_key("missing"));
+    }
+
     #[test]
     fn test_decode_and_decompress_success_string() {
         let encoded_data = "H4sIAAAAAAAAADWO0QqCMBiFX2XsOkKJZHkXot5YQgpdhMTSPzfSTbaZhPjuzbTLj3M45xtxC1rTGvJPB9jHQXrOL2lyP4VZdoxDvMFyEKDmpJF9NVBTskTW2gaNrGMl+85mC2VGAW0X1P1Dl4p3hksR8caA0ti/Fb9e+AZhZhwxr5a64VbD0NaOuR5xPLJzycEh+81fbxa4JmjVQ6uejwIG5YuLGjGgjWFIPlFll7ig8zOKuAImNWzxVExfL8ipzewAAAA=";
@@ -14,28 +14,29 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     io::Write,
     sync::Arc,
     time::Instant,
 };
 
 use anyhow::Result;
-use arrow_schema::{DataType, Field};
-use bulk::SCHEMA_CONFORMANCE_FAILED;
+use arrow_schema::{DataType, Field, Schema};
+use bulk::{SCHEMA_CONFORMANCE_FAILED, SCHEMA_FIELDS_LIMIT_EXCEEDED};
 use config::{
     get_config,
     meta::{
         alerts::alert::Alert,
         self_reporting::usage::{RequestStats, UsageType},
-        stream::{PartitionTimeLevel, StreamParams, StreamPartition, StreamType},
+        stream::{PartitionTimeLevel, SchemaMode, StreamParams, StreamPartition, StreamType},
     },
     metrics,
     utils::{
-        json::{estimate_json_bytes, get_string_value, pickup_string_value, Map, Value},
+        flatten,
+        json::{self, estimate_json_bytes, get_string_value, pickup_string_value, Map, Value},
         schema_ext::SchemaExt,
     },
-    DISTINCT_FIELDS,
+    DISTINCT_FIELDS, ID_COL_NAME, ORIGINAL_DATA_COL_NAME, TIMESTAMP_COL_NAME,
 };
 use infra::schema::{unwrap_partition_time_level, SchemaCache};
 
@@ -46,12 +47,18 @@ use super::{
         distinct_values::{DvItem, DISTINCT_STREAM_PREFIX},
         write, MetadataItem, MetadataType,
     },
-    schema::stream_schema_exists,
+    schema::{drop_fields_over_limit, stream_schema_exists},
 };
 use crate::{
-    common::meta::{ingestion::IngestionStatus, stream::SchemaRecords},
+    common::meta::{
+        ingestion::{IngestionStatus, RecordStatus},
+        stream::SchemaRecords,
+    },
     service::{
-        alerts::alert::AlertExt, db, ingestion::get_write_partition_key, schema::check_for_schema,
+        alerts::alert::AlertExt,
+        db,
+        ingestion::{check_ingestion_paused, get_write_partition_key},
+        schema::check_for_schema,
         self_reporting::report_request_usage_stats,
     },
 };
@@ -64,6 +71,9 @@ pub mod syslog;
 
 static BULK_OPERATORS: [&str; 3] = ["create", "index", "update"];
 
+/// Stream receiving the records dropped during ingestion, when enabled for the org
+pub const REJECTED_STREAM: &str = "_rejected";
+
 pub type O2IngestJsonData = (Vec<(i64, Map<String, Value>)>, Option<usize>);
 
 fn parse_bulk_index(v: &Value) -> Option<(String, String, Option<String>)> {
@@ -195,7 +205,7 @@ fn set_parsing_error(parse_error: &mut String, field: &Field) {
     ));
 }
 
-async fn write_logs_by_stream(
+pub(crate) async fn write_logs_by_stream(
     thread_id: usize,
     org_id: &str,
     user_email: &str,
@@ -204,6 +214,8 @@ async fn write_logs_by_stream(
     status: &mut IngestionStatus,
     json_data_by_stream: HashMap<String, O2IngestJsonData>,
 ) -> Result<()> {
+    let rejected_enabled = rejected_stream_enabled(org_id).await;
+    let mut rejected = Vec::new();
     for (stream_name, (json_data, fn_num)) in json_data_by_stream {
         // check if we are allowed to ingest
         if db::compact::retention::is_deleting_stream(org_id, StreamType::Logs, &stream_name, None)
@@ -211,9 +223,24 @@ async fn write_logs_by_stream(
             log::warn!("stream [{stream_name}] is being deleted");
             continue; // skip
         }
+        // the destination streams of a pipeline may be paused too
+        if let Err(e) = check_ingestion_paused(org_id, StreamType::Logs, &stream_name).await {
+            log::warn!("{e}, dropping its records");
+            continue; // skip
+        }
 
         // write json data by stream
-        let mut req_stats = write_logs(thread_id, org_id, &stream_name, status, json_data).await?;
+        let rejected_records =
+            (rejected_enabled && stream_name != REJECTED_STREAM).then_some(&mut rejected);
+        let mut req_stats = write_logs(
+            thread_id,
+            org_id,
+            &stream_name,
+            status,
+            json_data,
+            rejected_records,
+        )
+        .await?;
 
         let time_took = time_stats.1.elapsed().as_secs_f64();
         req_stats.response_time = time_took;
@@ -254,15 +281,81 @@ async fn write_logs_by_stream(
             .await;
         }
     }
+
+    if !rejected.is_empty() {
+        write_rejected_records(thread_id, org_id, rejected).await;
+    }
     Ok(())
 }
 
+async fn rejected_stream_enabled(org_id: &str) -> bool {
+    match get_org_setting(org_id).await {
+        Ok(org_settings) => org_settings.enable_rejected_stream,
+        Err(_) => false,
+    }
+}
+
+/// Wraps a dropped record for the rejected stream, keeping the original record
+/// as a string so it can't conflict with the rejected stream's schema.
+fn rejected_record<T: serde::Serialize>(
+    stream_name: &str,
+    record: &T,
+    reason: &str,
+) -> (i64, Map<String, Value>) {
+    let timestamp = chrono::Utc::now().timestamp_micros();
+    let mut rejected = Map::new();
+    rejected.insert(TIMESTAMP_COL_NAME.to_string(), Value::from(timestamp));
+    rejected.insert("stream".to_string(), Value::from(stream_name));
+    rejected.insert("reason".to_string(), Value::from(reason));
+    rejected.insert(
+        "record".to_string(),
+        Value::from(json::to_string(record).unwrap_or_default()),
+    );
+    (timestamp, rejected)
+}
+
+/// Queues a record that failed to parse for the rejected stream, the records
+/// of the rejected stream itself are never queued.
+fn queue_rejected_record<T: serde::Serialize>(
+    rejected: Option<&mut Vec<(i64, Map<String, Value>)>>,
+    stream_name: &str,
+    record: &T,
+    reason: &str,
+) {
+    if let Some(rejected) = rejected {
+        if stream_name != REJECTED_STREAM {
+            rejected.push(rejected_record(stream_name, record, reason));
+        }
+    }
+}
+
+async fn write_rejected_records(
+    thread_id: usize,
+    org_id: &str,
+    records: Vec<(i64, Map<String, Value>)>,
+) {
+    let mut status = IngestionStatus::Record(RecordStatus::default());
+    if let Err(e) = write_logs(
+        thread_id,
+        org_id,
+        REJECTED_STREAM,
+        &mut status,
+        records,
+        None,
+    )
+    .await
+    {
+        log::error!("Error while writing rejected records for org {org_id}: {e}");
+    }
+}
+
 async fn write_logs(
     thread_id: usize,
     org_id: &str,
     stream_name: &str,
     status: &mut IngestionStatus,
-    json_data: Vec<(i64, Map<String, Value>)>,
+    mut json_data: Vec<(i64, Map<String, Value>)>,
+    mut rejected: Option<&mut Vec<(i64, Map<String, Value>)>>,
 ) -> Result<RequestStats> {
     let cfg = get_config();
     let log_ingest_errors = ingestion_log_enabled().await;
@@ -287,6 +380,66 @@ async fn write_logs(
     };
     let stream_settings = infra::schema::unwrap_stream_settings(&schema).unwrap_or_default();
 
+    // store aliased fields under their canonical names
+    if !stream_settings.field_aliases.is_empty() {
+        for (_, record_val) in json_data.iter_mut() {
+            apply_field_aliases(record_val, &stream_settings.field_aliases);
+        }
+    }
+
+    // strict streams only accept the fields already in the schema
+    if stream_settings.schema_mode == SchemaMode::Strict && !schema.fields().is_empty() {
+        let mut accepted = Vec::with_capacity(json_data.len());
+        for (timestamp, mut record_val) in json_data {
+            match check_strict_schema(
+                &mut record_val,
+                &schema,
+                stream_settings.drop_unknown_fields,
+            ) {
+                Ok(()) => accepted.push((timestamp, record_val)),
+                Err(e) => set_record_failed(
+                    status,
+                    org_id,
+                    stream_name,
+                    &record_val,
+                    &e,
+                    log_ingest_errors,
+                    rejected.as_deref_mut(),
+                ),
+            }
+        }
+        if accepted.is_empty() {
+            return Ok(RequestStats::default());
+        }
+        json_data = accepted;
+    }
+
+    // keep the schema within the max fields limit, records are still ingested
+    let max_schema_fields = if stream_settings.max_schema_fields > 0 {
+        stream_settings.max_schema_fields
+    } else {
+        cfg.limit.schema_max_fields
+    };
+    let dropped_fields = drop_fields_over_limit(
+        &schema,
+        json_data.iter_mut().map(|(_, v)| v),
+        max_schema_fields,
+    );
+    if !dropped_fields.is_empty() {
+        metrics::INGEST_ERRORS
+            .with_label_values(&[
+                org_id,
+                StreamType::Logs.as_str(),
+                stream_name,
+                SCHEMA_FIELDS_LIMIT_EXCEEDED,
+            ])
+            .inc();
+        log::warn!(
+            "stream [{org_id}/{stream_name}] reached the max schema fields limit {max_schema_fields}, dropped fields: {}",
+            dropped_fields.join(", ")
+        );
+    }
+
     let mut partition_keys: Vec<StreamPartition> = vec![];
     let mut partition_time_level = PartitionTimeLevel::from(cfg.limit.logs_file_retention.as_str());
     if stream_schema.has_partition_keys {
@@ -373,43 +526,15 @@ async fn write_logs(
                 }
             };
             if let Err(e) = ret_val {
-                // update status(fail)
-                match status {
-                    IngestionStatus::Record(status) => {
-                        status.failed += 1;
-                        status.error = e.to_string();
-                        metrics::INGEST_ERRORS
-                            .with_label_values(&[
-                                org_id,
-                                StreamType::Logs.as_str(),
-                                stream_name,
-                                SCHEMA_CONFORMANCE_FAILED,
-                            ])
-                            .inc();
-                        log_failed_record(log_ingest_errors, &record_val, &e.to_string());
-                    }
-                    IngestionStatus::Bulk(bulk_res) => {
-                        bulk_res.errors = true;
-                        metrics::INGEST_ERRORS
-                            .with_label_values(&[
-                                org_id,
-                                StreamType::Logs.as_str(),
-                                stream_name,
-                                SCHEMA_CONFORMANCE_FAILED,
-                            ])
-                            .inc();
-                        log_failed_record(log_ingest_errors, &record_val, &e.to_string());
-                        bulk::add_record_status(
-                            stream_name.to_string(),
-                            &doc_id,
-                            "".to_string(),
-                            Some(Value::Object(record_val.clone())),
-                            bulk_res,
-                            Some(bulk::SCHEMA_CONFORMANCE_FAILED.to_string()),
-                            Some(e.to_string()),
-                        );
-                    }
-                }
+                set_record_failed(
+                    status,
+                    org_id,
+                    stream_name,
+                    &record_val,
+                    &e.to_string(),
+                    log_ingest_errors,
+                    rejected.as_deref_mut(),
+                );
                 continue;
             }
         }
@@ -527,6 +652,108 @@ async fn write_logs(
     Ok(req_stats)
 }
 
+/// Checks a record of a strict stream against the stream schema. Unknown
+/// fields are removed when `drop_unknown` is set, otherwise the record is
+/// rejected with the list of unknown fields.
+fn check_strict_schema(
+    record: &mut Map<String, Value>,
+    schema: &Schema,
+    drop_unknown: bool,
+) -> std::result::Result<(), String> {
+    let mut unknown = record
+        .keys()
+        .filter(|k| {
+            k.as_str() != TIMESTAMP_COL_NAME
+                && k.as_str() != ID_COL_NAME
+                && k.as_str() != ORIGINAL_DATA_COL_NAME
+                && schema.field_with_name(k).is_err()
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    unknown.sort_unstable();
+    if drop_unknown {
+        for key in unknown {
+            record.remove(&key);
+        }
+        Ok(())
+    } else {
+        Err(format!(
+            "stream schema is strict, unknown fields: {}",
+            unknown.join(", ")
+        ))
+    }
+}
+
+/// Updates the ingestion status for a record that doesn't conform to the
+/// stream schema and queues it for the rejected stream.
+fn set_record_failed(
+    status: &mut IngestionStatus,
+    org_id: &str,
+    stream_name: &str,
+    record_val: &Map<String, Value>,
+    err: &str,
+    log_ingest_errors: bool,
+    rejected: Option<&mut Vec<(i64, Map<String, Value>)>>,
+) {
+    metrics::INGEST_ERRORS
+        .with_label_values(&[
+            org_id,
+            StreamType::Logs.as_str(),
+            stream_name,
+            SCHEMA_CONFORMANCE_FAILED,
+        ])
+        .inc();
+    log_failed_record(log_ingest_errors, record_val, err);
+    match status {
+        IngestionStatus::Record(status) => {
+            status.failed += 1;
+            status.error = err.to_string();
+        }
+        IngestionStatus::Bulk(bulk_res) => {
+            bulk_res.errors = true;
+            let doc_id = record_val
+                .get("_id")
+                .map(|v| v.as_str().unwrap().to_string());
+            bulk::add_record_status(
+                stream_name.to_string(),
+                &doc_id,
+                "".to_string(),
+                Some(Value::Object(record_val.clone())),
+                bulk_res,
+                Some(bulk::SCHEMA_CONFORMANCE_FAILED.to_string()),
+                Some(err.to_string()),
+            );
+        }
+    }
+    if let Some(rejected) = rejected {
+        rejected.push(rejected_record(stream_name, record_val, err));
+    }
+}
+
+/// Renames the aliased fields of a flattened record. Both names are formatted
+/// like the flattened keys, a canonical field that is already present wins
+/// over its alias, `_timestamp` is never renamed.
+fn apply_field_aliases(record: &mut Map<String, Value>, aliases: &BTreeMap<String, String>) {
+    for (alias, canonical) in aliases.iter() {
+        let mut alias = alias.to_string();
+        flatten::format_key(&mut alias);
+        let mut canonical = canonical.to_string();
+        flatten::format_key(&mut canonical);
+        if alias == canonical || alias == TIMESTAMP_COL_NAME || canonical == TIMESTAMP_COL_NAME {
+            continue;
+        }
+        let Some(value) = record.remove(&alias) else {
+            continue;
+        };
+        if !record.contains_key(&canonical) {
+            record.insert(canonical, value);
+        }
+    }
+}
+
 pub fn refactor_map(
     original_map: Map<String, Value>,
     defined_schema_keys: &HashSet<String>,
@@ -587,6 +814,8 @@ fn log_failed_record<T: std::fmt::Debug>(enabled: bool, record: &T, error: &str)
 
 #[cfg(test)]
 mod tests {
+    use config::meta::stream::StreamSettings;
+
     use super::*;
 
     #[test]
@@ -596,6 +825,137 @@ mod tests {
         assert!(!parse_error.is_empty());
     }
 
+    #[test]
+    fn test_rejected_record() {
+        let mut local_val = Map::new();
+        local_val.insert("code".to_string(), Value::from("not_a_number"));
+        let delta = vec![Field::new("code", DataType::Int64, true)];
+        let err = cast_to_type(&mut local_val, delta).unwrap_err();
+
+        let (timestamp, rejected) = rejected_record("app_logs", &local_val, &err.to_string());
+        assert_eq!(
+            rejected.get(TIMESTAMP_COL_NAME).and_then(|v| v.as_i64()),
+            Some(timestamp)
+        );
+        assert_eq!(rejected.get("stream").unwrap(), "app_logs");
+        assert!(rejected
+            .get("reason")
+            .and_then(|v| v.as_str())
+            .unwrap()
+            .contains("Failed to cast code to type Int64"));
+        let record: Value =
+            config::utils::json::from_str(rejected.get("record").unwrap().as_str().unwrap())
+                .unwrap();
+        assert_eq!(record, Value::Object(local_val));
+    }
+
+    #[test]
+    fn test_apply_field_aliases() {
+        let settings = StreamSettings::from(
+            r#"{"field_aliases":{"ts":"timestamp","hostname":"host","message":"msg","_timestamp":"time"}}"#,
+        );
+        // the aliases survive a settings round trip
+        let settings =
+            StreamSettings::from(config::utils::json::to_string(&settings).unwrap().as_str());
+        assert_eq!(settings.field_aliases.len(), 4);
+
+        let mut record = Map::new();
+        record.insert(
+            TIMESTAMP_COL_NAME.to_string(),
+            Value::from(1_700_000_000_000_000_i64),
+        );
+        record.insert("ts".to_string(), Value::from("2023-11-14T22:13:20Z"));
+        record.insert("hostname".to_string(), Value::from("node-1"));
+        record.insert("message".to_string(), Value::from("from alias"));
+        record.insert("msg".to_string(), Value::from("canonical"));
+        apply_field_aliases(&mut record, &settings.field_aliases);
+
+        assert_eq!(record.get("timestamp").unwrap(), "2023-11-14T22:13:20Z");
+        assert_eq!(record.get("host").unwrap(), "node-1");
+        assert_eq!(record.get("msg").unwrap(), "canonical");
+        assert!(!record.contains_key("ts"));
+        assert!(!record.contains_key("hostname"));
+        assert!(!record.contains_key("message"));
+        assert!(record.contains_key(TIMESTAMP_COL_NAME));
+        assert!(!record.contains_key("time"));
+
+        // the inferred schema only knows the canonical names
+        let schema = config::utils::schema::infer_json_schema_from_map(
+            std::iter::once(&record),
+            StreamType::Logs,
+        )
+        .unwrap();
+        assert!(schema.field_with_name("host").is_ok());
+        assert!(schema.field_with_name("hostname").is_err());
+
+        // the aliases match the flattened keys, never the raw ones
+        let settings = StreamSettings::from(
+            r#"{"field_aliases":{"@ts":"event.time","Host-Name":"host","@timestamp":"time","t":"@timestamp"}}"#,
+        );
+        let mut record = Map::new();
+        record.insert(
+            TIMESTAMP_COL_NAME.to_string(),
+            Value::from(1_700_000_000_000_000_i64),
+        );
+        record.insert("_ts".to_string(), Value::from("2023-11-14T22:13:20Z"));
+        record.insert("host_name".to_string(), Value::from("node-2"));
+        record.insert("t".to_string(), Value::from("not a timestamp"));
+        apply_field_aliases(&mut record, &settings.field_aliases);
+        assert_eq!(record.get("event_time").unwrap(), "2023-11-14T22:13:20Z");
+        assert_eq!(record.get("host").unwrap(), "node-2");
+        assert!(!record.contains_key("_ts"));
+        assert!(!record.contains_key("host_name"));
+        // `@timestamp` is flattened to `_timestamp`, which is never renamed
+        assert_eq!(
+            record.get(TIMESTAMP_COL_NAME).unwrap(),
+            1_700_000_000_000_000_i64
+        );
+        assert!(!record.contains_key("time"));
+        assert!(record.contains_key("t"));
+    }
+
+    fn strict_record() -> Map<String, Value> {
+        let mut record = Map::new();
+        record.insert(TIMESTAMP_COL_NAME.to_string(), Value::from(1));
+        record.insert("level".to_string(), Value::from("info"));
+        record.insert("user_agent".to_string(), Value::from("curl"));
+        record.insert("trace".to_string(), Value::from("abc"));
+        record
+    }
+
+    fn strict_schema() -> Schema {
+        Schema::new(vec![
+            Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false),
+            Field::new("level", DataType::Utf8, true),
+        ])
+    }
+
+    #[test]
+    fn test_check_strict_schema_reject() {
+        let mut record = strict_record();
+        let err = check_strict_schema(&mut record, &strict_schema(), false).unwrap_err();
+        assert_eq!(
+            err,
+            "stream schema is strict, unknown fields: trace, user_agent"
+        );
+        // rejected records are left untouched
+        assert_eq!(record, strict_record());
+
+        let mut record = strict_record();
+        record.remove("trace");
+        record.remove("user_agent");
+        assert!(check_strict_schema(&mut record, &strict_schema(), false).is_ok());
+    }
+
+    #[test]
+    fn test_check_strict_schema_drop() {
+        let mut record = strict_record();
+        assert!(check_strict_schema(&mut record, &strict_schema(), true).is_ok());
+        assert_eq!(record.len(), 2);
+        assert!(record.contains_key(TIMESTAMP_COL_NAME));
+        assert_eq!(record.get("level"), Some(&Value::from("info")));
+    }
+
     #[test]
     fn test_cast_to_type() {
         let mut local_val = Map::new();
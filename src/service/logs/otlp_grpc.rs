@@ -37,12 +37,15 @@ use prost::Message;
 
 use super::{bulk::TS_PARSE_FAILED, ingestion_log_enabled, log_failed_record};
 use crate::{
-    common::meta::ingestion::{IngestionStatus, StreamStatus},
+    common::meta::{
+        http::HttpResponse as MetaHttpResponse,
+        ingestion::{IngestionStatus, StreamStatus},
+    },
     handler::http::request::CONTENT_TYPE_PROTO,
     service::{
         format_stream_name,
         ingestion::{
-            check_ingestion_allowed,
+            check_ingestion_allowed, check_ingestion_paused,
             grpc::{get_val, get_val_with_type_retained},
         },
         logs::bulk::TRANSFORM_FAILED,
@@ -67,6 +70,14 @@ pub async fn handle_grpc_request(
         None => "default".to_owned(),
     };
     check_ingestion_allowed(org_id, Some(&stream_name))?;
+    if let Err(e) = check_ingestion_paused(org_id, StreamType::Logs, &stream_name).await {
+        return Ok(
+            HttpResponse::ServiceUnavailable().json(MetaHttpResponse::error(
+                http::StatusCode::SERVICE_UNAVAILABLE.into(),
+                e.to_string(),
+            )),
+        );
+    }
 
     let cfg = get_config();
     let min_ts = (Utc::now() - Duration::try_hours(cfg.limit.ingest_allowed_upto).unwrap())
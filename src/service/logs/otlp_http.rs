@@ -44,7 +44,7 @@ use crate::{
     handler::http::request::CONTENT_TYPE_JSON,
     service::{
         format_stream_name,
-        ingestion::{check_ingestion_allowed, get_val_for_attr},
+        ingestion::{check_ingestion_allowed, check_ingestion_paused, get_val_for_attr},
         logs::bulk::TRANSFORM_FAILED,
         schema::get_upto_discard_error,
     },
@@ -104,6 +104,14 @@ pub async fn logs_json_handler(
         None => "default".to_owned(),
     };
     check_ingestion_allowed(org_id, Some(&stream_name))?;
+    if let Err(e) = check_ingestion_paused(org_id, StreamType::Logs, &stream_name).await {
+        return Ok(
+            HttpResponse::ServiceUnavailable().json(MetaHttpResponse::error(
+                http::StatusCode::SERVICE_UNAVAILABLE.into(),
+                e.to_string(),
+            )),
+        );
+    }
 
     let min_ts = (Utc::now() - Duration::try_hours(cfg.limit.ingest_allowed_upto).unwrap())
         .timestamp_micros();
@@ -46,7 +46,9 @@ use crate::{
         },
     },
     service::{
-        format_stream_name, ingestion::check_ingestion_allowed, logs::bulk::TRANSFORM_FAILED,
+        format_stream_name,
+        ingestion::{check_ingestion_allowed, check_ingestion_paused},
+        logs::bulk::TRANSFORM_FAILED,
     },
 };
 
@@ -83,6 +85,14 @@ pub async fn ingest(msg: &str, addr: SocketAddr) -> Result<HttpResponse> {
             )),
         );
     };
+    if let Err(e) = check_ingestion_paused(org_id, StreamType::Logs, &stream_name).await {
+        return Ok(
+            HttpResponse::ServiceUnavailable().json(MetaHttpResponse::error(
+                http::StatusCode::SERVICE_UNAVAILABLE.into(),
+                e.to_string(),
+            )),
+        );
+    }
 
     let cfg = get_config();
     let min_ts = (Utc::now() - Duration::try_hours(cfg.limit.ingest_allowed_upto).unwrap())
@@ -215,6 +215,22 @@ impl TraceListIndex {
                 distinct_value_fields: vec![],
                 index_updated_at: 0,
                 extended_retention_days: vec![],
+                webhook_mapping: None,
+                field_aliases: Default::default(),
+                timestamp_field: None,
+                schema_mode: Default::default(),
+                drop_unknown_fields: false,
+                max_schema_fields: 0,
+                disable_default_sort: false,
+                field_masks: Default::default(),
+                row_filters: Default::default(),
+                labels: Default::default(),
+                inverted_index_split_chars: None,
+                storage_name: None,
+                ingestion_paused: false,
+                trace_sampling_ratio: None,
+                relabel_rules: vec![],
+                result_cache_ttl: 0,
             };
 
             stream::save_stream_settings(org_id, STREAM_NAME, StreamType::Metadata, settings)
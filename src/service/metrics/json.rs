@@ -32,7 +32,10 @@ use config::{
 use datafusion::arrow::datatypes::Schema;
 use infra::schema::{unwrap_partition_time_level, SchemaCache};
 
-use super::get_exclude_labels;
+use super::{
+    get_exclude_labels,
+    relabel::{get_relabeler, Relabeler},
+};
 use crate::{
     common::meta::{
         authz::Authz,
@@ -42,7 +45,10 @@ use crate::{
     service::{
         alerts::alert::AlertExt,
         db, format_stream_name,
-        ingestion::{evaluate_trigger, get_write_partition_key, write_file, TriggerAlertData},
+        ingestion::{
+            check_ingestion_paused, evaluate_trigger, get_write_partition_key, write_file,
+            TriggerAlertData,
+        },
         pipeline::batch_execution::ExecutablePipeline,
         schema::check_for_schema,
         self_reporting::report_request_usage_stats,
@@ -85,6 +91,8 @@ pub async fn ingest(org_id: &str, body: web::Bytes) -> Result<IngestionResponse>
         HashMap::new();
     let mut stream_pipeline_inputs: HashMap<String, Vec<(json::Value, String)>> = HashMap::new();
 
+    let mut stream_relabelers: HashMap<String, Option<Relabeler>> = HashMap::new();
+
     // realtime alerts
     let mut stream_alerts_map: HashMap<String, Vec<Alert>> = HashMap::new();
     let mut stream_trigger_map: HashMap<String, Option<TriggerAlertData>> = HashMap::new();
@@ -98,7 +106,7 @@ pub async fn ingest(org_id: &str, body: web::Bytes) -> Result<IngestionResponse>
         let mut record = flatten::flatten(record)?;
         // check data type
         let record = record.as_object_mut().unwrap();
-        let stream_name = match record.get(NAME_LABEL).ok_or(anyhow!("missing __name__"))? {
+        let mut stream_name = match record.get(NAME_LABEL).ok_or(anyhow!("missing __name__"))? {
             json::Value::String(s) => format_stream_name(s),
             _ => {
                 return Err(anyhow::anyhow!("invalid __name__, need to be string"));
@@ -111,6 +119,20 @@ pub async fn ingest(org_id: &str, body: web::Bytes) -> Result<IngestionResponse>
             }
         };
 
+        // relabel, the sample may be renamed to another stream
+        if !stream_relabelers.contains_key(&stream_name) {
+            let relabeler = get_relabeler(org_id, &stream_name).await;
+            stream_relabelers.insert(stream_name.clone(), relabeler);
+        }
+        if let Some(relabeler) = stream_relabelers.get(&stream_name).unwrap() {
+            if !relabeler.relabel(record) {
+                continue;
+            }
+            if let Some(json::Value::String(name)) = record.get(NAME_LABEL) {
+                stream_name = format_stream_name(name);
+            }
+        }
+
         // Start retrieve associated pipeline and initialize ExecutablePipeline
         if !stream_executable_pipelines.contains_key(&stream_name) {
             let exec_pl_option = crate::service::ingestion::get_stream_executable_pipeline(
@@ -444,6 +466,10 @@ pub async fn ingest(org_id: &str, body: web::Bytes) -> Result<IngestionResponse>
             log::warn!("stream [{stream_name}] is being deleted");
             continue;
         }
+        if let Err(e) = check_ingestion_paused(org_id, StreamType::Metrics, &stream_name).await {
+            log::warn!("{e}, dropping its records");
+            continue;
+        }
 
         let writer =
             ingester::get_writer(0, org_id, StreamType::Metrics.as_str(), &stream_name).await;
@@ -24,6 +24,8 @@ use regex::Regex;
 pub mod json;
 pub mod otlp;
 pub mod prom;
+pub mod relabel;
+pub mod rollup;
 
 const EXCLUDE_LABELS: [&str; 7] = [
     VALUE_LABEL,
@@ -52,11 +52,14 @@ use crate::{
         alerts::alert::AlertExt,
         db, format_stream_name,
         ingestion::{
-            evaluate_trigger,
+            check_ingestion_paused, evaluate_trigger,
             grpc::{get_exemplar_val, get_metric_val, get_val},
             write_file, TriggerAlertData,
         },
-        metrics::{format_label_name, get_exclude_labels},
+        metrics::{
+            format_label_name, get_exclude_labels,
+            relabel::{get_relabeler, Relabeler},
+        },
         pipeline::batch_execution::ExecutablePipeline,
         schema::{check_for_schema, stream_schema_exists},
         self_reporting::report_request_usage_stats,
@@ -160,6 +163,8 @@ pub async fn handle_otlp_request(
         HashMap::new();
     let mut stream_pipeline_inputs: HashMap<String, Vec<json::Value>> = HashMap::new();
 
+    let mut stream_relabelers: HashMap<String, Option<Relabeler>> = HashMap::new();
+
     // realtime alerts
     let mut stream_alerts_map: HashMap<String, Vec<alert::Alert>> = HashMap::new();
     let mut stream_trigger_map: HashMap<String, Option<TriggerAlertData>> = HashMap::new();
@@ -175,51 +180,9 @@ pub async fn handle_otlp_request(
         }
         for scope_metric in &resource_metric.scope_metrics {
             for metric in &scope_metric.metrics {
+                // the stream of the metric is known once its samples are
+                // relabeled, its schema, pipeline and alerts are loaded then
                 let metric_name = &format_stream_name(&metric.name);
-                // check for schema
-                let schema_exists = stream_schema_exists(
-                    org_id,
-                    metric_name,
-                    StreamType::Metrics,
-                    &mut metric_schema_map,
-                )
-                .await;
-
-                // get partition keys
-                if !stream_partitioning_map.contains_key(metric_name) {
-                    let partition_det = crate::service::ingestion::get_stream_partition_keys(
-                        org_id,
-                        &StreamType::Metrics,
-                        metric_name,
-                    )
-                    .await;
-                    stream_partitioning_map
-                        .insert(metric_name.clone().to_owned(), partition_det.clone());
-                }
-
-                // Start get stream alerts
-                crate::service::ingestion::get_stream_alerts(
-                    &[StreamParams {
-                        org_id: org_id.to_owned().into(),
-                        stream_name: metric_name.to_owned().into(),
-                        stream_type: StreamType::Metrics,
-                    }],
-                    &mut stream_alerts_map,
-                )
-                .await;
-                // End get stream alert
-
-                // get stream pipeline
-                if !stream_executable_pipelines.contains_key(metric_name) {
-                    let pipeline_params =
-                        crate::service::ingestion::get_stream_executable_pipeline(
-                            org_id,
-                            metric_name,
-                            &StreamType::Metrics,
-                        )
-                        .await;
-                    stream_executable_pipelines.insert(metric_name.clone(), pipeline_params);
-                }
 
                 let mut rec = json::json!({});
                 if let Some(res) = &resource_metric.resource {
@@ -266,28 +229,29 @@ pub async fn handle_otlp_request(
                     None => vec![],
                 };
 
-                // update schema metadata
-                if !schema_exists.has_metadata {
-                    if let Err(e) =
-                        update_setting(org_id, metric_name, StreamType::Metrics, prom_meta).await
-                    {
-                        log::error!(
-                            "Failed to set metadata for metric: {} with error: {}",
-                            metric_name,
-                            e
-                        );
-                    }
-                }
                 for mut rec in records {
                     // flattening
                     rec = flatten::flatten(rec)?;
 
+                    // relabel, the sample may be renamed to another stream
+                    let stream_name =
+                        format_stream_name(rec.get(NAME_LABEL).unwrap().as_str().unwrap());
+                    if !stream_relabelers.contains_key(&stream_name) {
+                        let relabeler = get_relabeler(org_id, &stream_name).await;
+                        stream_relabelers.insert(stream_name.clone(), relabeler);
+                    }
+                    if let Some(relabeler) = stream_relabelers.get(&stream_name).unwrap() {
+                        if !relabeler.relabel(rec.as_object_mut().unwrap()) {
+                            continue;
+                        }
+                    }
+
                     let local_metric_name =
                         &format_stream_name(rec.get(NAME_LABEL).unwrap().as_str().unwrap());
 
-                    if local_metric_name != metric_name {
+                    if !stream_executable_pipelines.contains_key(local_metric_name) {
                         // check for schema
-                        stream_schema_exists(
+                        let schema_exists = stream_schema_exists(
                             org_id,
                             local_metric_name,
                             StreamType::Metrics,
@@ -295,6 +259,35 @@ pub async fn handle_otlp_request(
                         )
                         .await;
 
+                        // update schema metadata
+                        if !schema_exists.has_metadata {
+                            let mut prom_meta = prom_meta.clone();
+                            if local_metric_name != metric_name {
+                                let metadata = Metadata {
+                                    metric_family_name: local_metric_name.to_string(),
+                                    ..metadata.clone()
+                                };
+                                prom_meta.insert(
+                                    METADATA_LABEL.to_string(),
+                                    json::to_string(&metadata).unwrap(),
+                                );
+                            }
+                            if let Err(e) = update_setting(
+                                org_id,
+                                local_metric_name,
+                                StreamType::Metrics,
+                                prom_meta,
+                            )
+                            .await
+                            {
+                                log::error!(
+                                    "Failed to set metadata for metric: {} with error: {}",
+                                    local_metric_name,
+                                    e
+                                );
+                            }
+                        }
+
                         // get partition keys
                         if !stream_partitioning_map.contains_key(local_metric_name) {
                             let partition_det =
@@ -321,17 +314,15 @@ pub async fn handle_otlp_request(
                         // End get stream alert
 
                         // get stream pipeline
-                        if !stream_executable_pipelines.contains_key(local_metric_name) {
-                            let pipeline_params =
-                                crate::service::ingestion::get_stream_executable_pipeline(
-                                    org_id,
-                                    local_metric_name,
-                                    &StreamType::Metrics,
-                                )
-                                .await;
-                            stream_executable_pipelines
-                                .insert(local_metric_name.clone(), pipeline_params);
-                        }
+                        let pipeline_params =
+                            crate::service::ingestion::get_stream_executable_pipeline(
+                                org_id,
+                                local_metric_name,
+                                &StreamType::Metrics,
+                            )
+                            .await;
+                        stream_executable_pipelines
+                            .insert(local_metric_name.clone(), pipeline_params);
                     }
 
                     // ready to be buffered for downstream processing
@@ -539,6 +530,10 @@ pub async fn handle_otlp_request(
             log::warn!("stream [{stream_name}] is being deleted");
             continue;
         }
+        if let Err(e) = check_ingestion_paused(org_id, StreamType::Metrics, &stream_name).await {
+            log::warn!("{e}, dropping its records");
+            continue;
+        }
 
         // write to file
         let writer =
@@ -31,6 +31,7 @@ use config::{
     FxIndexMap, TIMESTAMP_COL_NAME,
 };
 use datafusion::arrow::datatypes::Schema;
+use futures::{StreamExt, TryStreamExt};
 use hashbrown::HashSet;
 use infra::{
     cache::stats,
@@ -49,8 +50,12 @@ use crate::{
     service::{
         alerts::alert::AlertExt,
         db, format_stream_name,
-        ingestion::{evaluate_trigger, write_file, TriggerAlertData},
-        metrics::format_label_name,
+        ingestion::{check_ingestion_paused, evaluate_trigger, write_file, TriggerAlertData},
+        metrics::{
+            format_label_name,
+            relabel::{get_relabeler, Relabeler},
+            rollup::is_rollup_stream,
+        },
         pipeline::batch_execution::ExecutablePipeline,
         schema::{check_for_schema, stream_schema_exists},
         search as search_service,
@@ -99,6 +104,8 @@ pub async fn remote_write(
         HashMap::new();
     let mut stream_pipeline_inputs: HashMap<String, Vec<(json::Value, i64)>> = HashMap::new();
 
+    let mut stream_relabelers: HashMap<String, Option<Relabeler>> = HashMap::new();
+
     // realtime alerts
     let mut stream_alerts_map: HashMap<String, Vec<alert::Alert>> = HashMap::new();
     let mut stream_trigger_map: HashMap<String, Option<TriggerAlertData>> = HashMap::new();
@@ -113,6 +120,8 @@ pub async fn remote_write(
     let mut json_data_by_stream: HashMap<String, Vec<(json::Value, i64)>> = HashMap::new();
 
     // parse metadata
+    let mut request_metadata: HashMap<String, Metadata> = HashMap::new();
+    let mut relabeled_streams: HashSet<String> = HashSet::new();
     for item in request.metadata {
         let metric_name = format_stream_name(&item.metric_family_name.clone());
         let metadata = Metadata {
@@ -121,6 +130,16 @@ pub async fn remote_write(
             help: item.help.clone(),
             unit: item.unit.clone(),
         };
+        // a relabeled stream may send its series elsewhere, its metadata is
+        // set on the streams its series end up in
+        if !stream_relabelers.contains_key(&metric_name) {
+            let relabeler = get_relabeler(org_id, &metric_name).await;
+            stream_relabelers.insert(metric_name.clone(), relabeler);
+        }
+        if stream_relabelers.get(&metric_name).unwrap().is_some() {
+            request_metadata.insert(metric_name, metadata);
+            continue;
+        }
         let mut extra_metadata: HashMap<String, String> = HashMap::new();
         extra_metadata.insert(
             METADATA_LABEL.to_string(),
@@ -182,7 +201,7 @@ pub async fn remote_write(
         // get labels
         let mut replica_label = String::new();
 
-        let labels: FxIndexMap<String, String> = event
+        let mut labels: FxIndexMap<String, String> = event
             .labels
             .drain(..)
             .filter(|label| {
@@ -203,11 +222,63 @@ pub async fn remote_write(
             .map(|label| (format_label_name(&label.name), label.value))
             .collect();
 
-        let metric_name = match labels.get(NAME_LABEL) {
+        let mut metric_name = match labels.get(NAME_LABEL) {
             Some(v) => v.to_owned(),
             None => continue,
         };
 
+        // relabel, the series may be renamed to another stream
+        if !stream_relabelers.contains_key(&metric_name) {
+            let relabeler = get_relabeler(org_id, &metric_name).await;
+            stream_relabelers.insert(metric_name.clone(), relabeler);
+        }
+        if let Some(relabeler) = stream_relabelers.get(&metric_name).unwrap() {
+            if !relabeler.relabel(&mut labels) {
+                continue;
+            }
+            if let Some(name) = labels.get(NAME_LABEL) {
+                let renamed = name.to_owned();
+                if relabeled_streams.insert(renamed.clone()) {
+                    // the metadata of the series goes with it to its stream
+                    if let Some(metadata) = request_metadata.get(&format_stream_name(&metric_name))
+                    {
+                        let schema_exists = stream_schema_exists(
+                            org_id,
+                            &renamed,
+                            StreamType::Metrics,
+                            &mut metric_schema_map,
+                        )
+                        .await;
+                        if !schema_exists.has_metadata {
+                            let metadata = Metadata {
+                                metric_family_name: renamed.clone(),
+                                ..metadata.clone()
+                            };
+                            let extra_metadata = HashMap::from([(
+                                METADATA_LABEL.to_string(),
+                                json::to_string(&metadata).unwrap(),
+                            )]);
+                            if let Err(e) = update_setting(
+                                org_id,
+                                &renamed,
+                                StreamType::Metrics,
+                                extra_metadata,
+                            )
+                            .await
+                            {
+                                log::error!(
+                                    "Error updating metadata for stream: {}, err: {}",
+                                    renamed,
+                                    e
+                                );
+                            }
+                        }
+                    }
+                }
+                metric_name = renamed;
+            }
+        }
+
         // parse samples
         for sample in event.samples {
             let mut sample_val = sample.value;
@@ -528,6 +599,10 @@ pub async fn remote_write(
             log::warn!("stream [{stream_name}] is being deleted");
             continue;
         }
+        if let Err(e) = check_ingestion_paused(org_id, StreamType::Metrics, &stream_name).await {
+            log::warn!("{e}, dropping its records");
+            continue;
+        }
 
         // write to file
         let writer =
@@ -672,6 +747,7 @@ fn get_metadata_object(schema: &Schema) -> Option<MetadataObject> {
 
 pub(crate) async fn get_series(
     org_id: &str,
+    user_id: Option<&str>,
     selector: Option<parser::VectorSelector>,
     start: i64,
     end: i64,
@@ -749,7 +825,10 @@ pub(crate) async fn get_series(
         search_event_context: None,
         use_cache: None,
     };
-    let series = match search_service::search("", org_id, StreamType::Metrics, None, &req).await {
+    // searched as the user, so the masked labels are matched and returned masked
+    let user_id = user_id.map(|v| v.to_string());
+    let series = match search_service::search("", org_id, StreamType::Metrics, user_id, &req).await
+    {
         Err(err) => {
             log::error!("search series error: {err}");
             return Err(err);
@@ -808,6 +887,7 @@ pub(crate) async fn get_labels(
 // XXX-TODO: filter the results in accordance with `selector.matchers`
 pub(crate) async fn get_label_values(
     org_id: &str,
+    user_id: Option<&str>,
     label_name: String,
     selector: Option<parser::VectorSelector>,
     start: i64,
@@ -892,7 +972,10 @@ pub(crate) async fn get_label_values(
         search_event_context: None,
         use_cache: None,
     };
-    let mut label_values = match search_service::search("", org_id, stream_type, None, &req).await {
+    let user_id = user_id.map(|v| v.to_string());
+    let mut label_values = match search_service::search("", org_id, stream_type, user_id, &req)
+        .await
+    {
         Ok(resp) => resp
             .hits
             .iter()
@@ -909,6 +992,212 @@ pub(crate) async fn get_label_values(
     Ok(label_values)
 }
 
+// series active in this window are counted, like the Prometheus head block
+const TSDB_STATUS_WINDOW: i64 = 2 * 3600 * 1_000_000;
+const TSDB_STATUS_DEFAULT_LIMIT: usize = 10;
+
+/// Returns approximate cardinality statistics of the metric series active in
+/// the last two hours. Each metric contributes at most
+/// `ZO_METRICS_MAX_SERIES_PER_QUERY` series.
+pub(crate) async fn get_tsdb_status(
+    org_id: &str,
+    req: RequestTsdbStatus,
+) -> Result<ResponseTsdbStatus> {
+    let stream_type = StreamType::Metrics;
+    let end = Utc::now().timestamp_micros();
+    let start = end - TSDB_STATUS_WINDOW;
+    let cfg = get_config();
+    let max_series = cfg.limit.metrics_max_series_per_query;
+
+    let streams = get_tsdb_status_streams(org_id, start, end).await?;
+    let results = futures::stream::iter(streams)
+        .map(|stream| async move {
+            let req = config::meta::search::Request {
+                query: config::meta::search::Query {
+                    sql: stream.sql.clone(),
+                    from: 0,
+                    size: max_series as i64,
+                    start_time: start,
+                    end_time: end,
+                    ..Default::default()
+                },
+                encoding: config::meta::search::RequestEncoding::Empty,
+                regions: vec![],
+                clusters: vec![],
+                timeout: 0,
+                search_type: None,
+                search_event_context: None,
+                use_cache: None,
+            };
+            match search_service::search("", org_id, stream_type, None, &req).await {
+                Ok(resp) => Ok((stream, resp.hits)),
+                Err(err) => {
+                    log::error!("search series of {}/{} error: {err}", org_id, stream.name);
+                    Err(err)
+                }
+            }
+        })
+        .buffer_unordered(cfg.limit.cpu_num)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut status = TsdbStatusBuilder::default();
+    for (stream, hits) in results {
+        status.add_stream(&stream, hits);
+    }
+    Ok(status.build(req.limit.unwrap_or(TSDB_STATUS_DEFAULT_LIMIT)))
+}
+
+struct TsdbStatusStream {
+    name: String,
+    sql: String,
+    // stream's time range clamped to the status window
+    min_time: i64,
+    max_time: i64,
+}
+
+/// Returns the metric streams with data in `[start, end]`, skipping the
+/// rollup streams, which only hold downsampled copies of other metrics.
+async fn get_tsdb_status_streams(
+    org_id: &str,
+    start: i64,
+    end: i64,
+) -> Result<Vec<TsdbStatusStream>> {
+    let stream_type = StreamType::Metrics;
+    let stream_schemas = db::schema::list(org_id, Some(stream_type), true)
+        .await
+        .map_err(|e| Error::Message(format!("failed to get metrics' stream schemas: {e}")))?;
+    let mut streams = Vec::with_capacity(stream_schemas.len());
+    for schema in stream_schemas {
+        if is_rollup_stream(&schema.stream_name) {
+            continue;
+        }
+        let stats = stats::get_stream_stats(org_id, &schema.stream_name, stream_type);
+        if !stats.time_range_intersects(start, end) {
+            continue;
+        }
+        let label_names = schema
+            .schema
+            .fields()
+            .iter()
+            .map(|f| f.name().as_str())
+            .filter(|&s| {
+                s != TIMESTAMP_COL_NAME
+                    && s != VALUE_LABEL
+                    && s != HASH_LABEL
+                    && s != TYPE_LABEL
+                    && s != EXEMPLARS_LABEL
+            })
+            .collect::<Vec<_>>()
+            .join("\", \"");
+        if label_names.is_empty() {
+            continue;
+        }
+        streams.push(TsdbStatusStream {
+            sql: format!(
+                "SELECT DISTINCT({HASH_LABEL}), \"{label_names}\" FROM \"{}\"",
+                schema.stream_name
+            ),
+            min_time: stats.doc_time_min.max(start),
+            max_time: stats.doc_time_max.min(end),
+            name: schema.stream_name,
+        });
+    }
+    Ok(streams)
+}
+
+#[derive(Default)]
+struct TsdbStatusBuilder {
+    num_series: u64,
+    num_label_pairs: u64,
+    min_time: Option<i64>,
+    max_time: Option<i64>,
+    series_by_metric_name: HashMap<String, u64>,
+    values_by_label_name: HashMap<String, HashSet<String>>,
+    series_by_label_pair: HashMap<String, u64>,
+}
+
+impl TsdbStatusBuilder {
+    fn add_time_range(&mut self, min: i64, max: i64) {
+        self.min_time = Some(self.min_time.map_or(min, |v| v.min(min)));
+        self.max_time = Some(self.max_time.map_or(max, |v| v.max(max)));
+    }
+
+    fn add_stream(&mut self, stream: &TsdbStatusStream, hits: Vec<json::Value>) {
+        self.add_time_range(stream.min_time, stream.max_time);
+        for hit in hits {
+            if let json::Value::Object(labels) = hit {
+                self.add_series(&stream.name, &labels);
+            }
+        }
+    }
+
+    fn add_series(&mut self, metric_name: &str, labels: &json::Map<String, json::Value>) {
+        self.num_series += 1;
+        *self
+            .series_by_metric_name
+            .entry(metric_name.to_string())
+            .or_default() += 1;
+        let name_label = (NAME_LABEL, metric_name.to_string());
+        let labels = labels
+            .iter()
+            .filter(|(name, _)| name.as_str() != HASH_LABEL && name.as_str() != NAME_LABEL)
+            .filter_map(|(name, value)| match value {
+                json::Value::Null => None,
+                json::Value::String(v) => Some((name.as_str(), v.clone())),
+                v => Some((name.as_str(), v.to_string())),
+            });
+        for (name, value) in std::iter::once(name_label).chain(labels) {
+            self.num_label_pairs += 1;
+            *self
+                .series_by_label_pair
+                .entry(format!("{name}={value}"))
+                .or_default() += 1;
+            self.values_by_label_name
+                .entry(name.to_string())
+                .or_default()
+                .insert(value);
+        }
+    }
+
+    fn build(self, limit: usize) -> ResponseTsdbStatus {
+        ResponseTsdbStatus {
+            head_stats: TsdbHeadStats {
+                num_series: self.num_series,
+                num_label_pairs: self.num_label_pairs,
+                chunk_count: 0,
+                min_time: self.min_time.unwrap_or_default() / 1000,
+                max_time: self.max_time.unwrap_or_default() / 1000,
+            },
+            series_count_by_metric_name: top_tsdb_stats(self.series_by_metric_name, limit),
+            label_value_count_by_label_name: top_tsdb_stats(
+                self.values_by_label_name
+                    .iter()
+                    .map(|(name, values)| (name.clone(), values.len() as u64)),
+                limit,
+            ),
+            memory_in_bytes_by_label_name: top_tsdb_stats(
+                self.values_by_label_name.iter().map(|(name, values)| {
+                    (name.clone(), values.iter().map(|v| v.len() as u64).sum())
+                }),
+                limit,
+            ),
+            series_count_by_label_value_pair: top_tsdb_stats(self.series_by_label_pair, limit),
+        }
+    }
+}
+
+/// Returns the `limit` items with the highest value, by descending value.
+fn top_tsdb_stats(items: impl IntoIterator<Item = (String, u64)>, limit: usize) -> Vec<TsdbStat> {
+    let mut stats = items
+        .into_iter()
+        .map(|(name, value)| TsdbStat { name, value })
+        .collect::<Vec<_>>();
+    stats.sort_by(|a, b| b.value.cmp(&a.value).then_with(|| a.name.cmp(&b.name)));
+    stats.truncate(limit);
+    stats
+}
+
 pub(crate) fn try_into_metric_name(selector: &parser::VectorSelector) -> Option<String> {
     match &selector.name {
         Some(name) => {
@@ -999,3 +1288,134 @@ async fn prom_ha_handler(
 
     _accept_record
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(job: &str, instance: &str) -> json::Map<String, json::Value> {
+        json::json!({
+            HASH_LABEL: format!("{job}{instance}"),
+            "job": job,
+            "instance": instance,
+        })
+        .as_object()
+        .unwrap()
+        .clone()
+    }
+
+    #[tokio::test]
+    async fn test_tsdb_status_streams() {
+        use config::meta::stream::StreamStats;
+        use datafusion::arrow::datatypes::{DataType, Field};
+        use infra::schema::STREAM_SCHEMAS_LATEST;
+
+        let org_id = "test_tsdb_status";
+        let (start, end) = (10_000_000, 20_000_000);
+        for (stream, doc_time_min, doc_time_max) in [
+            ("up", 1_000_000, 15_000_000),
+            ("up_rollup_60", 1_000_000, 15_000_000),
+            ("stale", 1_000_000, 2_000_000),
+        ] {
+            STREAM_SCHEMAS_LATEST.write().await.insert(
+                format!("{org_id}/{}/{stream}", StreamType::Metrics),
+                SchemaCache::new(Schema::new(vec![
+                    Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false),
+                    Field::new(HASH_LABEL, DataType::Utf8, false),
+                    Field::new(VALUE_LABEL, DataType::Float64, false),
+                    Field::new("instance", DataType::Utf8, true),
+                    Field::new("job", DataType::Utf8, true),
+                ])),
+            );
+            stats::set_stream_stats(
+                org_id,
+                stream,
+                StreamType::Metrics,
+                StreamStats {
+                    doc_time_min,
+                    doc_time_max,
+                    ..Default::default()
+                },
+            );
+        }
+
+        let streams = get_tsdb_status_streams(org_id, start, end).await.unwrap();
+        assert_eq!(streams.len(), 1);
+        let stream = &streams[0];
+        assert_eq!(stream.name, "up");
+        assert_eq!(
+            stream.sql,
+            format!("SELECT DISTINCT({HASH_LABEL}), \"instance\", \"job\" FROM \"up\"")
+        );
+        assert_eq!((stream.min_time, stream.max_time), (start, 15_000_000));
+
+        let mut status = TsdbStatusBuilder::default();
+        status.add_stream(
+            stream,
+            ["a:9090", "b:9090"]
+                .into_iter()
+                .map(|instance| json::Value::Object(series("node", instance)))
+                .collect(),
+        );
+        let resp = json::to_value(status.build(10)).unwrap();
+        assert_eq!(resp["headStats"]["numSeries"], 2);
+        assert_eq!(resp["headStats"]["minTime"], 10_000);
+        assert_eq!(resp["headStats"]["maxTime"], 15_000);
+        assert_eq!(
+            resp["seriesCountByMetricName"],
+            json::json!([{"name": "up", "value": 2}])
+        );
+    }
+
+    #[test]
+    fn test_tsdb_status() {
+        let mut status = TsdbStatusBuilder::default();
+        status.add_time_range(2_000_000, 5_000_000);
+        status.add_time_range(1_000_000, 3_000_000);
+        for instance in ["a:9090", "b:9090", "c:9090"] {
+            status.add_series("up", &series("node", instance));
+        }
+        status.add_series("up", &series("prometheus", "a:9090"));
+        status.add_series("zo_requests", &series("prometheus", "a:9090"));
+
+        let resp = json::to_value(status.build(2)).unwrap();
+        assert_eq!(
+            resp["headStats"],
+            json::json!({
+                "numSeries": 5,
+                "numLabelPairs": 15,
+                "chunkCount": 0,
+                "minTime": 1000,
+                "maxTime": 5000,
+            })
+        );
+        assert_eq!(
+            resp["seriesCountByMetricName"],
+            json::json!([
+                {"name": "up", "value": 4},
+                {"name": "zo_requests", "value": 1},
+            ])
+        );
+        assert_eq!(
+            resp["labelValueCountByLabelName"],
+            json::json!([
+                {"name": "instance", "value": 3},
+                {"name": "__name__", "value": 2},
+            ])
+        );
+        assert_eq!(
+            resp["memoryInBytesByLabelName"],
+            json::json!([
+                {"name": "instance", "value": 18},
+                {"name": "job", "value": 14},
+            ])
+        );
+        assert_eq!(
+            resp["seriesCountByLabelValuePair"],
+            json::json!([
+                {"name": "__name__=up", "value": 4},
+                {"name": "instance=a:9090", "value": 3},
+            ])
+        );
+    }
+}
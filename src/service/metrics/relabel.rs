@@ -0,0 +1,250 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use config::{
+    meta::{
+        promql::{HASH_LABEL, NAME_LABEL, TYPE_LABEL, VALUE_LABEL},
+        stream::{RelabelAction, RelabelRule, StreamType},
+    },
+    utils::json,
+    FxIndexMap, TIMESTAMP_COL_NAME,
+};
+use regex::Regex;
+
+// never set or removed by relabeling, `__name__` can be renamed but not removed
+const RESERVED_LABELS: [&str; 5] = [
+    TIMESTAMP_COL_NAME,
+    VALUE_LABEL,
+    TYPE_LABEL,
+    HASH_LABEL,
+    NAME_LABEL,
+];
+
+/// Labels of a sample, as built by the different metrics ingestion protocols.
+pub trait LabelSet {
+    /// Empty when the label is missing.
+    fn label_value(&self, name: &str) -> String;
+    fn set_label(&mut self, name: &str, value: String);
+    fn remove_label(&mut self, name: &str);
+    fn label_names(&self) -> Vec<String>;
+}
+
+impl LabelSet for json::Map<String, json::Value> {
+    fn label_value(&self, name: &str) -> String {
+        match self.get(name) {
+            None | Some(json::Value::Null) => String::new(),
+            Some(json::Value::String(v)) => v.clone(),
+            Some(v) => v.to_string(),
+        }
+    }
+
+    fn set_label(&mut self, name: &str, value: String) {
+        self.insert(name.to_string(), json::Value::String(value));
+    }
+
+    fn remove_label(&mut self, name: &str) {
+        self.remove(name);
+    }
+
+    fn label_names(&self) -> Vec<String> {
+        self.keys().cloned().collect()
+    }
+}
+
+impl LabelSet for FxIndexMap<String, String> {
+    fn label_value(&self, name: &str) -> String {
+        self.get(name).cloned().unwrap_or_default()
+    }
+
+    fn set_label(&mut self, name: &str, value: String) {
+        self.insert(name.to_string(), value);
+    }
+
+    fn remove_label(&mut self, name: &str) {
+        self.shift_remove(name);
+    }
+
+    fn label_names(&self) -> Vec<String> {
+        self.keys().cloned().collect()
+    }
+}
+
+/// The relabel rules of a metric stream, with their regexes compiled.
+pub struct Relabeler {
+    rules: Vec<(RelabelRule, Regex)>,
+}
+
+impl Relabeler {
+    pub fn new(rules: &[RelabelRule]) -> Self {
+        let rules = rules
+            .iter()
+            .filter_map(|rule| match Regex::new(&format!("^(?:{})$", rule.regex)) {
+                Ok(re) => Some((rule.clone(), re)),
+                Err(e) => {
+                    log::error!("invalid relabel regex {}: {e}", rule.regex);
+                    None
+                }
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Applies the rules in order, returns false when the sample is dropped.
+    pub fn relabel(&self, labels: &mut impl LabelSet) -> bool {
+        for (rule, re) in self.rules.iter() {
+            match rule.action {
+                RelabelAction::Replace => {
+                    let target = rule.target_label.as_str();
+                    if target != NAME_LABEL && RESERVED_LABELS.contains(&target) {
+                        continue;
+                    }
+                    let value = labels.label_value(&rule.source_label);
+                    let Some(caps) = re.captures(&value) else {
+                        continue;
+                    };
+                    let mut new_value = String::new();
+                    caps.expand(&rule.replacement, &mut new_value);
+                    if !new_value.is_empty() {
+                        labels.set_label(target, new_value);
+                    } else if target != NAME_LABEL {
+                        labels.remove_label(target);
+                    }
+                }
+                RelabelAction::Keep => {
+                    if !re.is_match(&labels.label_value(&rule.source_label)) {
+                        return false;
+                    }
+                }
+                RelabelAction::Drop => {
+                    if re.is_match(&labels.label_value(&rule.source_label)) {
+                        return false;
+                    }
+                }
+                RelabelAction::LabelDrop => {
+                    for name in labels.label_names() {
+                        if !RESERVED_LABELS.contains(&name.as_str()) && re.is_match(&name) {
+                            labels.remove_label(&name);
+                        }
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Returns the relabeler of a metric stream, `None` when it has no rules.
+pub async fn get_relabeler(org_id: &str, stream_name: &str) -> Option<Relabeler> {
+    let settings = infra::schema::get_settings(org_id, stream_name, StreamType::Metrics).await?;
+    if settings.relabel_rules.is_empty() {
+        return None;
+    }
+    Some(Relabeler::new(&settings.relabel_rules))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(rules: json::Value) -> Relabeler {
+        let rules: Vec<RelabelRule> = json::from_value(rules).unwrap();
+        for rule in rules.iter() {
+            rule.validate().unwrap();
+        }
+        Relabeler::new(&rules)
+    }
+
+    fn sample() -> json::Map<String, json::Value> {
+        json::json!({
+            NAME_LABEL: "http_requests_total",
+            "instance": "web-1:9090",
+            "env": "prod",
+            "pod_uid": "1234",
+            VALUE_LABEL: 1.0,
+        })
+        .as_object()
+        .unwrap()
+        .clone()
+    }
+
+    #[test]
+    fn test_relabel_replace() {
+        let relabeler = rules(json::json!([
+            {"source_label": "instance", "regex": "(.*):\\d+", "target_label": "host"},
+            {"source_label": "env", "target_label": "environment"},
+            {"action": "labeldrop", "regex": "env|pod_.*"},
+            {"source_label": "__name__", "regex": "http_(.*)", "target_label": "__name__", "replacement": "web_$1"},
+        ]));
+        let mut record = sample();
+        assert!(relabeler.relabel(&mut record));
+        assert_eq!(
+            json::Value::Object(record),
+            json::json!({
+                NAME_LABEL: "web_requests_total",
+                "instance": "web-1:9090",
+                "host": "web-1",
+                "environment": "prod",
+                VALUE_LABEL: 1.0,
+            })
+        );
+
+        // no match leaves the labels as they are
+        let relabeler = rules(json::json!([
+            {"source_label": "instance", "regex": "db-.*", "target_label": "role", "replacement": "db"},
+        ]));
+        let mut record = sample();
+        assert!(relabeler.relabel(&mut record));
+        assert_eq!(record, sample());
+
+        let mut labels = FxIndexMap::default();
+        labels.insert(NAME_LABEL.to_string(), "up".to_string());
+        labels.insert("job".to_string(), "node".to_string());
+        let relabeler = rules(json::json!([
+            {"source_label": "job", "target_label": "service", "replacement": "svc-$1"},
+        ]));
+        assert!(relabeler.relabel(&mut labels));
+        assert_eq!(labels.get("service").unwrap(), "svc-node");
+    }
+
+    #[test]
+    fn test_relabel_drop() {
+        let relabeler = rules(json::json!([
+            {"action": "drop", "source_label": "env", "regex": "dev|test"},
+        ]));
+        let mut record = sample();
+        assert!(relabeler.relabel(&mut record));
+        record.insert("env".to_string(), "test".into());
+        assert!(!relabeler.relabel(&mut record));
+        // the regex is anchored
+        record.insert("env".to_string(), "testing".into());
+        assert!(relabeler.relabel(&mut record));
+
+        let relabeler = rules(json::json!([
+            {"action": "keep", "source_label": "env", "regex": "prod"},
+        ]));
+        assert!(relabeler.relabel(&mut sample()));
+        record.remove("env");
+        assert!(!relabeler.relabel(&mut record));
+    }
+
+    #[test]
+    fn test_relabel_rule_validate() {
+        let rule = |v: json::Value| json::from_value::<RelabelRule>(v).unwrap().validate();
+        assert!(rule(json::json!({"action": "drop"})).is_err());
+        assert!(rule(json::json!({"source_label": "job"})).is_err());
+        assert!(rule(json::json!({"action": "labeldrop", "regex": "("})).is_err());
+        assert!(rule(json::json!({"action": "labeldrop", "regex": "tmp_.*"})).is_ok());
+    }
+}
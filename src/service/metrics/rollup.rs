@@ -0,0 +1,373 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use config::{
+    get_config, ider,
+    meta::{
+        promql::{RollupOffset, HASH_LABEL, NAME_LABEL, TYPE_LABEL, VALUE_LABEL},
+        search::{self, RequestEncoding, SearchEventType},
+        stream::StreamType,
+    },
+    utils::{json, time::now_micros},
+    TIMESTAMP_COL_NAME,
+};
+use proto::cluster_rpc;
+
+use crate::service::{db, ingestion::ingestion_service, search as SearchService};
+
+const ROLLUP_STREAM_SUFFIX: &str = "_rollup_";
+// raw samples read from the source stream at once
+const ROLLUP_BATCH_SIZE: i64 = 10000;
+// raw samples read at once when a single microsecond holds more than a batch
+const ROLLUP_MAX_SAMPLES_PER_MICROSECOND: i64 = 1_000_000;
+
+/// Name of the stream holding the rollup of a metric stream at the given
+/// resolution, in seconds.
+pub fn rollup_stream_name(stream_name: &str, resolution: i64) -> String {
+    format!("{stream_name}{ROLLUP_STREAM_SUFFIX}{resolution}")
+}
+
+pub fn is_rollup_stream(stream_name: &str) -> bool {
+    stream_name
+        .rsplit_once(ROLLUP_STREAM_SUFFIX)
+        .is_some_and(|(_, res)| !res.is_empty() && res.bytes().all(|b| b.is_ascii_digit()))
+}
+
+struct RollupBucket {
+    sum: f64,
+    count: u64,
+    last_ts: i64,
+    last: json::Map<String, json::Value>,
+}
+
+/// Aggregates raw metric samples into one sample per series and resolution
+/// bucket.
+///
+/// Counters, histograms and summaries keep the last value of the bucket so
+/// rates can still be computed, the other metric types the average value. The
+/// rollup sample carries the labels and the timestamp of the last raw sample of
+/// the bucket.
+pub struct Rollup {
+    resolution: i64, // microseconds
+    buckets: HashMap<(String, i64), RollupBucket>,
+}
+
+impl Rollup {
+    pub fn new(resolution: i64) -> Self {
+        Self {
+            resolution: resolution * 1_000_000,
+            buckets: HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, record: json::Map<String, json::Value>) {
+        let Some(ts) = record.get(TIMESTAMP_COL_NAME).and_then(|v| v.as_i64()) else {
+            return;
+        };
+        let Some(value) = record.get(VALUE_LABEL).and_then(|v| v.as_f64()) else {
+            return;
+        };
+        let series = record
+            .get(HASH_LABEL)
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        let bucket = ts - ts.rem_euclid(self.resolution);
+        let entry = self
+            .buckets
+            .entry((series, bucket))
+            .or_insert_with(|| RollupBucket {
+                sum: 0.0,
+                count: 0,
+                last_ts: i64::MIN,
+                last: json::Map::new(),
+            });
+        entry.sum += value;
+        entry.count += 1;
+        if ts >= entry.last_ts {
+            entry.last_ts = ts;
+            entry.last = record;
+        }
+    }
+
+    /// Returns the rollup samples, named after the rollup stream so they are
+    /// ingested into it.
+    pub fn finish(self, rollup_stream: &str) -> Vec<json::Value> {
+        self.buckets
+            .into_values()
+            .map(|bucket| {
+                let mut record = bucket.last;
+                let keep_last = record
+                    .get(TYPE_LABEL)
+                    .and_then(|v| v.as_str())
+                    .is_some_and(|t| {
+                        matches!(
+                            t.to_lowercase().as_str(),
+                            "counter" | "histogram" | "summary"
+                        )
+                    });
+                if !keep_last {
+                    record.insert(
+                        VALUE_LABEL.to_string(),
+                        json::json!(bucket.sum / bucket.count as f64),
+                    );
+                }
+                record.insert(NAME_LABEL.to_string(), json::json!(rollup_stream));
+                // recomputed on ingestion from the new name
+                record.remove(HASH_LABEL);
+                json::Value::Object(record)
+            })
+            .collect()
+    }
+}
+
+/// Rolls up the complete resolution buckets of a metric stream received since
+/// the last run. The first run only rolls up the last complete bucket.
+///
+/// A bucket is only complete once it is older than the ingestion lag, as
+/// samples may be ingested up to `ingest_allowed_upto` hours in the past and a
+/// bucket is never rolled up again.
+pub async fn rollup_stream(org_id: &str, stream_name: &str) -> Result<()> {
+    let cfg = get_config();
+    let resolution = cfg.common.metrics_rollup_resolution;
+    let step = resolution * 1_000_000;
+    let end = rollup_end(now_micros(), step, cfg.limit.ingest_allowed_upto);
+    let mut offset = db::metrics::get_rollup_offset(org_id, stream_name, resolution)
+        .await
+        .unwrap_or(RollupOffset {
+            start: end - step,
+            end: end - step,
+        });
+    let rollup_stream = rollup_stream_name(stream_name, resolution);
+    while offset.end < end {
+        let mut rollup = Rollup::new(resolution);
+        read_bucket(
+            org_id,
+            stream_name,
+            offset.end,
+            offset.end + step,
+            &mut rollup,
+        )
+        .await?;
+        let records = rollup.finish(&rollup_stream);
+        if !records.is_empty() {
+            write_samples(org_id, &rollup_stream, records).await?;
+        }
+        offset.end += step;
+        db::metrics::set_rollup_offset(org_id, stream_name, resolution, offset).await?;
+    }
+    Ok(())
+}
+
+/// The end of the last bucket, `step` in microseconds, which can't receive
+/// samples anymore at `now`.
+fn rollup_end(now: i64, step: i64, ingest_allowed_upto: i64) -> i64 {
+    let closed = now - ingest_allowed_upto * 3_600_000_000;
+    closed - closed.rem_euclid(step)
+}
+
+/// Adds the raw samples in `[start, end)` to the rollup, reading them in time
+/// windows small enough to stay under the batch size.
+async fn read_bucket(
+    org_id: &str,
+    stream_name: &str,
+    start: i64,
+    end: i64,
+    rollup: &mut Rollup,
+) -> Result<()> {
+    let mut cursor = start;
+    let mut window = end - start;
+    while cursor < end {
+        let window_end = end.min(cursor.saturating_add(window));
+        // the samples of a scrape share their timestamp
+        let limit = if window_end - cursor > 1 {
+            ROLLUP_BATCH_SIZE + 1
+        } else {
+            ROLLUP_MAX_SAMPLES_PER_MICROSECOND + 1
+        };
+        let hits = read_samples(org_id, stream_name, cursor, window_end, limit).await?;
+        let count = hits.len() as i64;
+        if count >= limit {
+            if window_end - cursor == 1 {
+                return Err(anyhow!(
+                    "read {org_id}/{stream_name} more than {ROLLUP_MAX_SAMPLES_PER_MICROSECOND} samples at {cursor}"
+                ));
+            }
+            window = ((window_end - cursor) / 2).max(1);
+            continue;
+        }
+        for hit in hits {
+            if let json::Value::Object(record) = hit {
+                rollup.add(record);
+            }
+        }
+        cursor = window_end;
+        // quiet windows grow back
+        if count < ROLLUP_BATCH_SIZE / 2 {
+            window = window.saturating_mul(2);
+        }
+    }
+    Ok(())
+}
+
+/// The raw samples in `[start_time, end_time)`, at most `limit`.
+async fn read_samples(
+    org_id: &str,
+    stream_name: &str,
+    start_time: i64,
+    end_time: i64,
+    limit: i64,
+) -> Result<Vec<json::Value>> {
+    let req = search::Request {
+        query: search::Query {
+            sql: format!(
+                "SELECT * FROM \"{stream_name}\" WHERE {TIMESTAMP_COL_NAME} >= {start_time} AND {TIMESTAMP_COL_NAME} < {end_time} ORDER BY {TIMESTAMP_COL_NAME} ASC"
+            ),
+            size: limit,
+            start_time,
+            end_time,
+            ..Default::default()
+        },
+        encoding: RequestEncoding::Empty,
+        regions: vec![],
+        clusters: vec![],
+        timeout: 0,
+        search_type: Some(SearchEventType::Other),
+        search_event_context: None,
+        use_cache: None,
+    };
+    let trace_id = ider::generate();
+    let resp = SearchService::search(&trace_id, org_id, StreamType::Metrics, None, &req)
+        .await
+        .map_err(|e| anyhow!("read {org_id}/{stream_name} error: {e}"))?;
+    if resp.is_partial {
+        return Err(anyhow!(
+            "read {org_id}/{stream_name} partial response: {}",
+            resp.function_error
+        ));
+    }
+    Ok(resp.hits)
+}
+
+async fn write_samples(org_id: &str, stream_name: &str, records: Vec<json::Value>) -> Result<()> {
+    let req = cluster_rpc::IngestionRequest {
+        org_id: org_id.to_string(),
+        stream_name: stream_name.to_string(),
+        stream_type: StreamType::Metrics.to_string(),
+        data: Some(cluster_rpc::IngestionData::from(records)),
+        ingestion_type: Some(cluster_rpc::IngestionType::Json.into()),
+        metadata: None,
+    };
+    match ingestion_service::ingest(req).await {
+        Ok(resp) if resp.status_code == 200 => Ok(()),
+        Ok(resp) => Err(anyhow!(
+            "write {org_id}/{stream_name} error: {}",
+            resp.message
+        )),
+        Err(e) => Err(anyhow!("write {org_id}/{stream_name} error: {e}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(hash: u64, metric_type: &str, ts: i64, value: f64) -> json::Map<String, json::Value> {
+        json::json!({
+            NAME_LABEL: "http_requests",
+            TYPE_LABEL: metric_type,
+            HASH_LABEL: hash,
+            "job": format!("job{hash}"),
+            TIMESTAMP_COL_NAME: ts,
+            VALUE_LABEL: value,
+        })
+        .as_object()
+        .unwrap()
+        .clone()
+    }
+
+    #[test]
+    fn test_rollup_stream_name() {
+        assert_eq!(rollup_stream_name("cpu", 3600), "cpu_rollup_3600");
+        assert!(is_rollup_stream("cpu_rollup_3600"));
+        assert!(!is_rollup_stream("cpu"));
+        assert!(!is_rollup_stream("cpu_rollup_"));
+        assert!(!is_rollup_stream("cpu_rollup_x"));
+    }
+
+    #[test]
+    fn test_rollup_end() {
+        let hour = 3_600_000_000;
+        // samples up to 5 hours old are still accepted
+        assert_eq!(rollup_end(10 * hour, hour, 5), 5 * hour);
+        assert_eq!(rollup_end(10 * hour + hour / 2, hour, 5), 5 * hour);
+        assert_eq!(rollup_end(10 * hour + hour / 2, hour, 0), 10 * hour);
+        assert_eq!(rollup_end(10 * hour, 2 * hour, 5), 4 * hour);
+    }
+
+    #[test]
+    fn test_rollup_matches_raw_samples() {
+        let minute = 60 * 1_000_000;
+        let hour = 60 * minute;
+        let mut rollup = Rollup::new(3600);
+        // one gauge and one counter series, a sample every minute for two hours
+        let mut gauge_sums = [0.0; 2];
+        for i in 0..120 {
+            let ts = i * minute;
+            let gauge = (i % 7) as f64;
+            gauge_sums[(i / 60) as usize] += gauge;
+            rollup.add(sample(1, "gauge", ts, gauge));
+            rollup.add(sample(2, "counter", ts, (i * 10) as f64));
+        }
+
+        let mut records = rollup
+            .finish("http_requests_rollup_3600")
+            .into_iter()
+            .map(|v| v.as_object().unwrap().clone())
+            .collect::<Vec<_>>();
+        records.sort_by_key(|r| {
+            (
+                r["job"].as_str().unwrap().to_string(),
+                r[TIMESTAMP_COL_NAME].as_i64().unwrap(),
+            )
+        });
+        assert_eq!(records.len(), 4);
+        for r in records.iter() {
+            assert_eq!(r[NAME_LABEL], "http_requests_rollup_3600");
+            assert!(r.get(HASH_LABEL).is_none());
+        }
+
+        // gauges are averaged over the bucket
+        assert_eq!(records[0]["job"], "job1");
+        assert_eq!(records[0][TIMESTAMP_COL_NAME], 59 * minute);
+        assert_eq!(
+            records[0][VALUE_LABEL].as_f64().unwrap(),
+            gauge_sums[0] / 60.0
+        );
+        assert_eq!(records[1][TIMESTAMP_COL_NAME], hour + 59 * minute);
+        assert_eq!(
+            records[1][VALUE_LABEL].as_f64().unwrap(),
+            gauge_sums[1] / 60.0
+        );
+
+        // counters keep the last value of the bucket
+        assert_eq!(records[2]["job"], "job2");
+        assert_eq!(records[2][TIMESTAMP_COL_NAME], 59 * minute);
+        assert_eq!(records[2][VALUE_LABEL].as_f64().unwrap(), 590.0);
+        assert_eq!(records[3][VALUE_LABEL].as_f64().unwrap(), 1190.0);
+    }
+}
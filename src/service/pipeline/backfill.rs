@@ -0,0 +1,634 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{collections::HashMap, future::Future, time::Instant};
+
+use anyhow::anyhow;
+use config::{
+    cluster::LOCAL_NODE,
+    ider,
+    meta::{
+        pipeline::components::PipelineSource,
+        search::{self, RequestEncoding, SearchEventType},
+        self_reporting::usage::UsageType,
+        stream::{StreamParams, StreamType},
+    },
+    utils::{json::Value, time::now_micros},
+    TIMESTAMP_COL_NAME,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::batch_execution::ExecutablePipeline;
+use crate::{
+    common::{
+        infra::cluster as infra_cluster,
+        meta::ingestion::{IngestionStatus, RecordStatus},
+    },
+    service::{
+        db::{self, pipeline::PipelineError},
+        logs, search as SearchService,
+    },
+};
+
+// records read from the source stream and run through the pipeline at once,
+// a time window holding more is split
+const BACKFILL_BATCH_SIZE: i64 = 1000;
+// the time window first read from the source stream, in microseconds
+const BACKFILL_WINDOW: i64 = 60_000_000;
+// records read at most from a single microsecond, which can't be split, they
+// still go through the pipeline a batch at a time
+const BACKFILL_MAX_RECORDS_PER_MICROSECOND: i64 = 10 * BACKFILL_BATCH_SIZE;
+
+/// Replay of the records a realtime pipeline source stream received over a
+/// time range through the current version of the pipeline.
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct BackfillJob {
+    pub id: String,
+    pub org_id: String,
+    pub pipeline_id: String,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub status: BackfillStatus,
+    /// Records read from the source stream
+    pub read: usize,
+    /// Records accepted by the destination streams
+    pub written: usize,
+    /// The records before this time are replayed
+    #[serde(default)]
+    pub cursor: i64,
+    /// Node running the job
+    #[serde(default)]
+    pub node: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BackfillStatus {
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+/// Starts replaying the source stream records between `start_time` and
+/// `end_time` through the pipeline, the returned job tracks the progress.
+pub async fn backfill(
+    org_id: &str,
+    pipeline_id: &str,
+    start_time: i64,
+    end_time: i64,
+) -> Result<BackfillJob, PipelineError> {
+    let pipeline = match db::pipeline::get_by_id(pipeline_id).await {
+        Ok(pipeline) if pipeline.org == org_id => pipeline,
+        _ => return Err(PipelineError::NotFound(pipeline_id.to_string())),
+    };
+    let PipelineSource::Realtime(source) = pipeline.source.clone() else {
+        return Err(PipelineError::InvalidBackfill(
+            "only realtime pipelines can be backfilled".to_string(),
+        ));
+    };
+    if start_time >= end_time {
+        return Err(PipelineError::InvalidBackfill(
+            "start_time must be before end_time".to_string(),
+        ));
+    }
+    let exec_pl = ExecutablePipeline::new(&pipeline)
+        .await
+        .map_err(|e| PipelineError::InvalidPipeline(e.to_string()))?;
+    let destinations = exec_pl.get_all_destination_streams();
+    // the replay would read back the records it writes
+    if destinations.contains(&source) {
+        return Err(PipelineError::InvalidBackfill(
+            "the pipeline writes to its source stream".to_string(),
+        ));
+    }
+    if let Some(dest) = destinations
+        .iter()
+        .find(|dest| dest.stream_type != StreamType::Logs)
+    {
+        return Err(PipelineError::InvalidBackfill(format!(
+            "only logs destinations can be backfilled, {dest} is not"
+        )));
+    }
+
+    let now = now_micros();
+    let job = BackfillJob {
+        id: ider::uuid(),
+        org_id: org_id.to_string(),
+        pipeline_id: pipeline_id.to_string(),
+        start_time,
+        end_time,
+        created_at: now,
+        updated_at: now,
+        status: BackfillStatus::Running,
+        read: 0,
+        written: 0,
+        cursor: start_time,
+        node: LOCAL_NODE.uuid.clone(),
+        error: None,
+    };
+    db::pipeline_backfill::set(&job)
+        .await
+        .map_err(|e| PipelineError::InfraError(infra::errors::Error::Message(e.to_string())))?;
+    log::info!(
+        "[Pipeline] backfill {} of pipeline {} from {} [{}, {}) started",
+        job.id,
+        pipeline_id,
+        source,
+        start_time,
+        end_time
+    );
+
+    let mut running = job.clone();
+    tokio::spawn(async move {
+        let (org_id, id) = (running.org_id.clone(), running.id.clone());
+        let ret = replay(
+            &mut running,
+            &exec_pl,
+            |start, end, limit| read_source(&source, start, end, limit),
+            write_destination,
+            || db::pipeline_backfill::is_cancelled(&org_id, &id),
+        )
+        .await;
+        running.status = match ret {
+            Ok(()) if running.status == BackfillStatus::Cancelled => {
+                log::info!(
+                    "[Pipeline] backfill {} cancelled at {}",
+                    running.id,
+                    running.cursor
+                );
+                BackfillStatus::Cancelled
+            }
+            Ok(()) => BackfillStatus::Done,
+            Err(e) => {
+                log::error!("[Pipeline] backfill {} failed: {}", running.id, e);
+                running.error = Some(e.to_string());
+                BackfillStatus::Failed
+            }
+        };
+        running.updated_at = now_micros();
+        if let Err(e) = db::pipeline_backfill::set(&running).await {
+            log::error!(
+                "[Pipeline] backfill {} save status error: {}",
+                running.id,
+                e
+            );
+        }
+        if let Err(e) = db::pipeline_backfill::delete_cancel(&org_id, &id).await {
+            log::warn!("[Pipeline] backfill {} delete cancel error: {}", id, e);
+        }
+    });
+    Ok(job)
+}
+
+/// Asks the running backfill job to stop, it stops before replaying its next
+/// time window whichever node runs it.
+pub async fn cancel_backfill(
+    org_id: &str,
+    pipeline_id: &str,
+    id: &str,
+) -> Result<BackfillJob, PipelineError> {
+    let job = get_backfill(org_id, pipeline_id, id).await?;
+    if job.status != BackfillStatus::Running {
+        return Err(PipelineError::InvalidBackfill(format!(
+            "backfill {id} is not running"
+        )));
+    }
+    db::pipeline_backfill::cancel(org_id, id)
+        .await
+        .map_err(|e| PipelineError::InfraError(infra::errors::Error::Message(e.to_string())))?;
+    Ok(job)
+}
+
+/// The backfill job of the pipeline. A running job whose node is gone was
+/// interrupted, it is reported as failed with how far it got.
+pub async fn get_backfill(
+    org_id: &str,
+    pipeline_id: &str,
+    id: &str,
+) -> Result<BackfillJob, PipelineError> {
+    let mut job = match db::pipeline_backfill::get(org_id, id).await {
+        Ok(Some(job)) if job.pipeline_id == pipeline_id => job,
+        _ => return Err(PipelineError::BackfillNotFound(id.to_string())),
+    };
+    if job.status == BackfillStatus::Running
+        && job.node != LOCAL_NODE.uuid
+        && infra_cluster::get_node_by_uuid(&job.node).await.is_none()
+    {
+        job.status = BackfillStatus::Failed;
+        job.error = Some(format!(
+            "backfill interrupted, the records before {} were replayed",
+            job.cursor
+        ));
+        job.updated_at = now_micros();
+        if let Err(e) = db::pipeline_backfill::set(&job).await {
+            log::error!("[Pipeline] backfill {} save status error: {}", job.id, e);
+        }
+    }
+    Ok(job)
+}
+
+/// Reads the source records window by window from the job cursor, runs them
+/// through the pipeline and hands the results to `write`, saving the
+/// progress after each window. Each window is read in full, a window with
+/// more than a batch of records is split first. The job stops as
+/// `Cancelled` once `cancelled` says so.
+async fn replay<R, RFut, W, WFut, C, CFut>(
+    job: &mut BackfillJob,
+    exec_pl: &ExecutablePipeline,
+    mut read: R,
+    mut write: W,
+    mut cancelled: C,
+) -> anyhow::Result<()>
+where
+    R: FnMut(i64, i64, i64) -> RFut,
+    RFut: Future<Output = anyhow::Result<Vec<Value>>>,
+    W: FnMut(StreamParams, Vec<Value>) -> WFut,
+    WFut: Future<Output = anyhow::Result<usize>>,
+    C: FnMut() -> CFut,
+    CFut: Future<Output = bool>,
+{
+    let mut window = BACKFILL_WINDOW;
+    while job.cursor < job.end_time {
+        if cancelled().await {
+            job.status = BackfillStatus::Cancelled;
+            return Ok(());
+        }
+        let window_end = job.end_time.min(job.cursor.saturating_add(window));
+        let limit = if window_end - job.cursor > 1 {
+            BACKFILL_BATCH_SIZE + 1
+        } else {
+            BACKFILL_MAX_RECORDS_PER_MICROSECOND + 1
+        };
+        let records = read(job.cursor, window_end, limit).await?;
+        let num_records = records.len();
+        if num_records as i64 >= limit {
+            if window_end - job.cursor == 1 {
+                return Err(anyhow!(
+                    "more than {BACKFILL_MAX_RECORDS_PER_MICROSECOND} records at {}",
+                    job.cursor
+                ));
+            }
+            window = ((window_end - job.cursor) / 2).max(1);
+            continue;
+        }
+        let mut records = records.into_iter();
+        loop {
+            let batch = records
+                .by_ref()
+                .take(BACKFILL_BATCH_SIZE as usize)
+                .collect::<Vec<_>>();
+            if batch.is_empty() {
+                break;
+            }
+            let results = exec_pl.process_batch(&job.org_id, batch).await?;
+            for (dest, results) in results {
+                let records = results.into_iter().map(|(_, r)| r).collect::<Vec<_>>();
+                job.written += write(dest, records).await?;
+            }
+        }
+        job.read += num_records;
+        job.cursor = window_end;
+        job.updated_at = now_micros();
+        if num_records > 0 {
+            if let Err(e) = db::pipeline_backfill::set(job).await {
+                log::warn!("[Pipeline] backfill {} save progress error: {}", job.id, e);
+            }
+        }
+        // quiet windows grow back
+        if (num_records as i64) < BACKFILL_BATCH_SIZE / 2 {
+            window = window.saturating_mul(2);
+        }
+    }
+    Ok(())
+}
+
+/// The source records in `[start, end)`, at most `limit`.
+async fn read_source(
+    source: &StreamParams,
+    start: i64,
+    end: i64,
+    limit: i64,
+) -> anyhow::Result<Vec<Value>> {
+    let req = search::Request {
+        query: search::Query {
+            sql: format!(
+                "SELECT * FROM \"{}\" WHERE {TIMESTAMP_COL_NAME} >= {start} AND {TIMESTAMP_COL_NAME} < {end} ORDER BY {TIMESTAMP_COL_NAME} ASC",
+                source.stream_name
+            ),
+            size: limit,
+            start_time: start,
+            end_time: end,
+            ..Default::default()
+        },
+        encoding: RequestEncoding::Empty,
+        regions: vec![],
+        clusters: vec![],
+        timeout: 0,
+        search_type: Some(SearchEventType::Other),
+        search_event_context: None,
+        use_cache: None,
+    };
+    let trace_id = ider::generate();
+    let resp = SearchService::search(&trace_id, &source.org_id, source.stream_type, None, &req)
+        .await
+        .map_err(|e| anyhow!("read {source} error: {e}"))?;
+    if resp.is_partial {
+        return Err(anyhow!(
+            "read {source} partial response: {}",
+            resp.function_error
+        ));
+    }
+    Ok(resp.hits)
+}
+
+/// Writes the records to a logs destination stream the way the realtime
+/// pipeline does, but without the ingestion time limit, as the replayed
+/// records keep their original time. Returns the number of records accepted.
+async fn write_destination(dest: StreamParams, records: Vec<Value>) -> anyhow::Result<usize> {
+    let json_data = records
+        .into_iter()
+        .filter_map(|record| match record {
+            Value::Object(record) => {
+                let timestamp = record.get(TIMESTAMP_COL_NAME)?.as_i64()?;
+                Some((timestamp, record))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if json_data.is_empty() {
+        return Ok(0);
+    }
+    let mut status = IngestionStatus::Record(RecordStatus::default());
+    logs::write_logs_by_stream(
+        0,
+        &dest.org_id,
+        "",
+        (now_micros(), &Instant::now()),
+        UsageType::Json,
+        &mut status,
+        HashMap::from([(dest.stream_name.to_string(), (json_data, None))]),
+    )
+    .await
+    .map_err(|e| anyhow!("write {dest} error: {e}"))?;
+    match status {
+        IngestionStatus::Record(status) => Ok(status.successful as usize),
+        IngestionStatus::Bulk(_) => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use config::{
+        meta::{
+            function::Transform,
+            pipeline::{
+                components::{Edge, FunctionParams, Node, NodeData},
+                Pipeline,
+            },
+        },
+        utils::json,
+    };
+
+    use super::*;
+    use crate::common::infra::config::QUERY_FUNCTIONS;
+
+    /// A pipeline upper casing the `level` of the source records.
+    fn upcase_pipeline(id: &str, source: &StreamParams, dest: &StreamParams) -> Pipeline {
+        QUERY_FUNCTIONS.insert(
+            "default/backfill_upcase".to_string(),
+            Transform {
+                function: ".level = upcase!(.level) \n .".to_string(),
+                name: "backfill_upcase".to_string(),
+                params: "row".to_string(),
+                num_args: 0,
+                trans_type: Some(0),
+                streams: None,
+            },
+        );
+        Pipeline {
+            id: id.to_string(),
+            version: 0,
+            enabled: true,
+            org: "default".to_string(),
+            name: "backfill".to_string(),
+            description: "".to_string(),
+            source: PipelineSource::Realtime(source.clone()),
+            nodes: vec![
+                Node::new(
+                    "1".to_string(),
+                    NodeData::Stream(source.clone()),
+                    0.0,
+                    0.0,
+                    "input".to_string(),
+                ),
+                Node::new(
+                    "2".to_string(),
+                    NodeData::Function(FunctionParams {
+                        name: "backfill_upcase".to_string(),
+                        after_flatten: false,
+                        num_args: 0,
+                    }),
+                    0.0,
+                    0.0,
+                    "default".to_string(),
+                ),
+                Node::new(
+                    "3".to_string(),
+                    NodeData::Stream(dest.clone()),
+                    0.0,
+                    0.0,
+                    "output".to_string(),
+                ),
+            ],
+            edges: vec![
+                Edge::new("1".to_string(), "2".to_string()),
+                Edge::new("2".to_string(), "3".to_string()),
+            ],
+            dead_letter_stream: None,
+        }
+    }
+
+    fn backfill_job(pipeline_id: &str, start_time: i64, end_time: i64) -> BackfillJob {
+        BackfillJob {
+            id: pipeline_id.to_string(),
+            org_id: "default".to_string(),
+            pipeline_id: pipeline_id.to_string(),
+            start_time,
+            end_time,
+            created_at: 0,
+            updated_at: 0,
+            status: BackfillStatus::Running,
+            read: 0,
+            written: 0,
+            cursor: start_time,
+            node: LOCAL_NODE.uuid.clone(),
+            error: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backfill_replay() {
+        let source = StreamParams::new("default", "backfill_src", StreamType::Logs);
+        let dest = StreamParams::new("default", "backfill_dst", StreamType::Logs);
+        let pipeline = upcase_pipeline("test_backfill_replay", &source, &dest);
+        let exec_pl = ExecutablePipeline::new(&pipeline).await.unwrap();
+
+        // older than the ingestion time limit, and more records at the same
+        // time than fit in a batch
+        let start = now_micros() - 30 * 24 * 3_600_000_000;
+        let mut stored = ["info", "error", "warn"]
+            .iter()
+            .enumerate()
+            .map(|(i, level)| json::json!({"_timestamp": start + i as i64 * 1_000_000, "level": level}))
+            .collect::<Vec<_>>();
+        stored.extend(
+            (0..BACKFILL_BATCH_SIZE + 10)
+                .map(|_| json::json!({"_timestamp": start + 1_500_000, "level": "debug"})),
+        );
+        let reads = Mutex::new(0);
+        let mut job = backfill_job(&pipeline.id, start, start + 3_600_000_000);
+        replay(
+            &mut job,
+            &exec_pl,
+            |start, end, limit| {
+                *reads.lock().unwrap() += 1;
+                let records = stored
+                    .iter()
+                    .filter(|r| {
+                        let ts = r["_timestamp"].as_i64().unwrap();
+                        ts >= start && ts < end
+                    })
+                    .take(limit as usize)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                async move { Ok(records) }
+            },
+            write_destination,
+            || async { false },
+        )
+        .await
+        .unwrap();
+        assert_eq!(job.status, BackfillStatus::Running);
+
+        // every record is read once, whatever the windows
+        assert_eq!(job.read, stored.len());
+        assert_eq!(job.cursor, job.end_time);
+        assert!(*reads.lock().unwrap() > 1);
+        // the historical records are accepted by the destination
+        assert_eq!(job.written, stored.len());
+
+        let entries = ingester::read_from_memtable(
+            "default",
+            StreamType::Logs.as_str(),
+            "backfill_dst",
+            None,
+            &[],
+        )
+        .await
+        .unwrap();
+        let batches = entries
+            .iter()
+            .flat_map(|(_, entries)| entries.iter().map(|e| &e.data))
+            .collect::<Vec<_>>();
+        let mut levels = config::utils::arrow::record_batches_to_json_rows(&batches)
+            .unwrap()
+            .into_iter()
+            .map(|r| r["level"].as_str().unwrap().to_string())
+            .filter(|level| level != "DEBUG")
+            .collect::<Vec<_>>();
+        levels.sort();
+        assert_eq!(levels, vec!["ERROR", "INFO", "WARN"]);
+        assert!(ingester::read_from_memtable(
+            "default",
+            StreamType::Logs.as_str(),
+            "backfill_src",
+            None,
+            &[],
+        )
+        .await
+        .unwrap()
+        .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_backfill_replay_cancel_and_limit() {
+        let source = StreamParams::new("default", "backfill_limit_src", StreamType::Logs);
+        let dest = StreamParams::new("default", "backfill_limit_dst", StreamType::Logs);
+        let pipeline = upcase_pipeline("test_backfill_replay_limit", &source, &dest);
+        let exec_pl = ExecutablePipeline::new(&pipeline).await.unwrap();
+        let start = now_micros() - 24 * 3_600_000_000;
+
+        // the job stops before the window following the cancel request
+        let windows = Mutex::new(0);
+        let mut job = backfill_job(&pipeline.id, start, start + 3_600_000_000);
+        replay(
+            &mut job,
+            &exec_pl,
+            |start, _, _| {
+                *windows.lock().unwrap() += 1;
+                let record = json::json!({"_timestamp": start, "level": "info"});
+                async move { Ok(vec![record]) }
+            },
+            |_, records| async move { Ok(records.len()) },
+            || {
+                let cancelled = *windows.lock().unwrap() >= 2;
+                async move { cancelled }
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(job.status, BackfillStatus::Cancelled);
+        assert_eq!(job.read, 2);
+        assert_eq!(job.written, 2);
+        assert!(job.cursor < job.end_time);
+
+        // a microsecond holding too many records fails instead of being read
+        // in full, the records are never read past the limit
+        let max_limit = Mutex::new(0);
+        let mut job = backfill_job(&pipeline.id, start, start + 3_600_000_000);
+        let ret = replay(
+            &mut job,
+            &exec_pl,
+            |start, _, limit| {
+                let mut max_limit = max_limit.lock().unwrap();
+                *max_limit = (*max_limit).max(limit);
+                let records = (0..limit)
+                    .map(|_| json::json!({"_timestamp": start, "level": "debug"}))
+                    .collect::<Vec<_>>();
+                async move { Ok(records) }
+            },
+            |_, records| async move { Ok(records.len()) },
+            || async { false },
+        )
+        .await;
+        assert!(ret.unwrap_err().to_string().contains(&format!(
+            "more than {BACKFILL_MAX_RECORDS_PER_MICROSECOND} records"
+        )));
+        assert_eq!(
+            *max_limit.lock().unwrap(),
+            BACKFILL_MAX_RECORDS_PER_MICROSECOND + 1
+        );
+        assert_eq!(job.read, 0);
+        assert_eq!(job.cursor, start);
+    }
+}
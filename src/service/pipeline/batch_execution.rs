@@ -21,7 +21,7 @@ use chrono::Utc;
 use config::{
     meta::{
         function::{Transform, VRLResultResolver},
-        pipeline::{components::NodeData, Pipeline},
+        pipeline::{components::NodeData, Pipeline, PipelineNodeStats, PipelineStats},
         self_reporting::error::{ErrorData, ErrorSource, PipelineError},
         stream::{StreamParams, StreamType},
     },
@@ -47,6 +47,107 @@ use crate::{
 static DYNAMIC_STREAM_NAME_PATTERN: Lazy<regex::Regex> =
     Lazy::new(|| regex::Regex::new(r"\{([^}]+)\}").unwrap());
 
+// IN_FLIGHT_BATCHES for the number of batches being processed by each pipeline
+static IN_FLIGHT_BATCHES: Lazy<parking_lot::RwLock<HashMap<String, usize>>> =
+    Lazy::new(Default::default);
+
+/// Marks a batch as in flight for a pipeline until dropped.
+pub(crate) struct InFlightGuard {
+    pipeline_id: String,
+}
+
+impl InFlightGuard {
+    pub(crate) fn new(pipeline_id: &str) -> Self {
+        *IN_FLIGHT_BATCHES
+            .write()
+            .entry(pipeline_id.to_string())
+            .or_insert(0) += 1;
+        Self {
+            pipeline_id: pipeline_id.to_string(),
+        }
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        let mut w = IN_FLIGHT_BATCHES.write();
+        if let Some(count) = w.get_mut(&self.pipeline_id) {
+            *count -= 1;
+            if *count == 0 {
+                w.remove(&self.pipeline_id);
+            }
+        }
+    }
+}
+
+// NODE_STATS for the records counters of each node, pipeline_id -> node_id -> stats
+static NODE_STATS: Lazy<parking_lot::RwLock<HashMap<String, HashMap<String, PipelineNodeStats>>>> =
+    Lazy::new(Default::default);
+
+fn add_node_stats(
+    pipeline_id: &str,
+    node_id: &str,
+    node_type: &str,
+    received: u64,
+    processed: u64,
+    errors: u64,
+) {
+    let mut w = NODE_STATS.write();
+    let stats = w
+        .entry(pipeline_id.to_string())
+        .or_default()
+        .entry(node_id.to_string())
+        .or_insert_with(|| PipelineNodeStats {
+            node_id: node_id.to_string(),
+            node_type: node_type.to_string(),
+            ..Default::default()
+        });
+    stats.received += received;
+    stats.processed += processed;
+    stats.dropped += received.saturating_sub(processed);
+    stats.errors += errors;
+}
+
+/// Returns the records counters of each node of the pipeline, counted by this server since
+/// startup.
+pub fn get_pipeline_stats(pipeline_id: &str) -> PipelineStats {
+    let mut nodes = NODE_STATS
+        .read()
+        .get(pipeline_id)
+        .map(|nodes| nodes.values().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+    nodes.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+    PipelineStats {
+        pipeline_id: pipeline_id.to_string(),
+        nodes,
+    }
+}
+
+/// Drops the counters of the nodes that are no longer part of the pipeline.
+pub fn retain_node_stats(pipeline_id: &str, node_ids: &[String]) {
+    let mut w = NODE_STATS.write();
+    if let Some(nodes) = w.get_mut(pipeline_id) {
+        nodes.retain(|node_id, _| node_ids.contains(node_id));
+        if nodes.is_empty() {
+            w.remove(pipeline_id);
+        }
+    }
+}
+
+/// Drops the counters of all the nodes of a deleted pipeline.
+pub fn remove_pipeline_stats(pipeline_id: &str) {
+    NODE_STATS.write().remove(pipeline_id);
+}
+
+/// Returns the number of batches currently processed by the pipeline.
+pub fn in_flight_batches(pipeline_id: &str) -> usize {
+    IN_FLIGHT_BATCHES
+        .read()
+        .get(pipeline_id)
+        .copied()
+        .unwrap_or_default()
+}
+
 #[async_trait]
 pub trait PipelineExt: Sync + Send + 'static {
     /// Registers the function of all the FunctionNode of this pipeline once for execution.
@@ -88,8 +189,11 @@ pub struct ExecutablePipeline {
     sorted_nodes: Vec<String>,
     vrl_map: HashMap<String, VRLResultResolver>,
     node_map: HashMap<String, ExecutableNode>,
+    dead_letter_stream: Option<StreamParams>,
 }
 
+type DeadLetter = (StreamParams, Sender<(usize, StreamParams, Value)>);
+
 #[derive(Debug, Clone)]
 pub struct ExecutableNode {
     id: String,
@@ -183,6 +287,7 @@ impl ExecutablePipeline {
             node_map,
             sorted_nodes,
             vrl_map,
+            dead_letter_stream: pipeline.dead_letter_stream.clone(),
         })
     }
 
@@ -191,6 +296,7 @@ impl ExecutablePipeline {
         org_id: &str,
         records: Vec<Value>,
     ) -> Result<HashMap<StreamParams, Vec<(usize, Value)>>> {
+        let _in_flight = InFlightGuard::new(&self.id);
         let batch_size = records.len();
         log::debug!("[Pipeline]: process batch of size {}", batch_size);
 
@@ -225,6 +331,10 @@ impl ExecutablePipeline {
             let result_sender_cp = node.children.is_empty().then_some(result_sender.clone());
             let error_sender_cp = error_sender.clone();
             let vrl_runtime = self.vrl_map.get(node_id).cloned();
+            let dead_letter = self
+                .dead_letter_stream
+                .clone()
+                .map(|stream| (stream, result_sender.clone()));
 
             let task = tokio::spawn(async move {
                 process_node(
@@ -237,6 +347,7 @@ impl ExecutablePipeline {
                     vrl_runtime,
                     result_sender_cp,
                     error_sender_cp,
+                    dead_letter,
                 )
                 .await
             });
@@ -264,15 +375,17 @@ impl ExecutablePipeline {
         let error_task = tokio::spawn(async move {
             log::debug!("[Pipeline]: starts error collecting job");
             let mut count = 0;
+            let mut node_errors: HashMap<String, u64> = HashMap::new();
             while let Some((node_id, node_type, error)) = error_receiver.recv().await {
+                *node_errors.entry(node_id.clone()).or_default() += 1;
                 pipeline_error.add_node_error(node_id, node_type, error);
                 count += 1;
             }
             log::debug!("[Pipeline]: collected {count} errors");
             if count > 0 {
-                Some(pipeline_error)
+                (Some(pipeline_error), node_errors)
             } else {
-                None
+                (None, node_errors)
             }
         });
 
@@ -302,10 +415,16 @@ impl ExecutablePipeline {
         }
 
         // Publish errors if received any
-        if let Some(pipeline_errors) = error_task.await.map_err(|e| {
+        let (pipeline_errors, node_errors) = error_task.await.map_err(|e| {
             log::error!("[Pipeline] error collecting job failed: {}", e);
             anyhow!("[Pipeline] error collecting job failed: {}", e)
-        })? {
+        })?;
+        for (node_id, errors) in node_errors {
+            if let Some(node) = self.node_map.get(&node_id) {
+                add_node_stats(&self.id, &node_id, &node.node_type(), 0, 0, errors);
+            }
+        }
+        if let Some(pipeline_errors) = pipeline_errors {
             let stream_params = self.get_source_stream_params();
             let error_data = ErrorData {
                 _timestamp: Utc::now().timestamp_micros(),
@@ -338,6 +457,7 @@ impl ExecutablePipeline {
                     None
                 }
             })
+            .chain(self.dead_letter_stream.clone())
             .collect()
     }
 
@@ -485,9 +605,11 @@ async fn process_node(
     vrl_runtime: Option<VRLResultResolver>,
     result_sender: Option<Sender<(usize, StreamParams, Value)>>,
     error_sender: Sender<(String, String, String)>,
+    dead_letter: Option<DeadLetter>,
 ) -> Result<()> {
     let cfg = config::get_config();
     let mut count: usize = 0;
+    let mut received: usize = 0;
     match &node.node_data {
         NodeData::Stream(stream_params) => {
             if node.children.is_empty() {
@@ -496,6 +618,7 @@ async fn process_node(
                 // send received results directly via `result_sender` for collection
                 let result_sender = result_sender.unwrap();
                 while let Some((idx, mut record, flattened)) = receiver.recv().await {
+                    received += 1;
                     if !flattened {
                         record = match flatten::flatten_with_level(
                             record,
@@ -533,7 +656,7 @@ async fn process_node(
                                 };
                                 log::warn!("{err_msg}");
                                 if let Err(send_err) = error_sender
-                                    .send((node.id.to_string(), node.node_type(), err_msg))
+                                    .send((node.id.to_string(), node.node_type(), err_msg.clone()))
                                     .await
                                 {
                                     log::error!(
@@ -541,6 +664,10 @@ async fn process_node(
                                     );
                                     break;
                                 }
+                                if let Some(dead_letter) = &dead_letter {
+                                    send_to_dead_letter(dead_letter, &node, idx, record, &err_msg)
+                                        .await;
+                                }
                                 continue;
                             }
                         }
@@ -561,6 +688,7 @@ async fn process_node(
                 log::debug!("[Pipeline]: source node {node_idx} starts processing");
                 // source stream node: send received record to all its children
                 while let Some(item) = receiver.recv().await {
+                    received += 1;
                     send_to_children(&mut child_senders, item, "StreamNode").await;
                     count += 1;
                 }
@@ -570,6 +698,7 @@ async fn process_node(
         NodeData::Condition(condition_params) => {
             log::debug!("[Pipeline]: cond node {node_idx} starts processing");
             while let Some((idx, mut record, mut flattened)) = receiver.recv().await {
+                received += 1;
                 // value must be flattened before condition params can take effect
                 if !flattened {
                     record = match flatten::flatten_with_level(
@@ -614,6 +743,7 @@ async fn process_node(
             log::debug!("[Pipeline]: func node {node_idx} starts processing");
             let mut runtime = crate::service::ingestion::init_functions_runtime();
             while let Some((idx, mut record, mut flattened)) = receiver.recv().await {
+                received += 1;
                 if let Some(vrl_runtime) = &vrl_runtime {
                     if func_params.after_flatten && !flattened {
                         record = match flatten::flatten_with_level(
@@ -647,7 +777,7 @@ async fn process_node(
                         (res, Some(error)) => {
                             let err_msg = format!("FunctionNode error: {}", error);
                             if let Err(send_err) = error_sender
-                                .send((node.id.to_string(), node.node_type(), err_msg))
+                                .send((node.id.to_string(), node.node_type(), err_msg.clone()))
                                 .await
                             {
                                 log::error!(
@@ -655,6 +785,11 @@ async fn process_node(
                                 );
                                 break;
                             }
+                            // failed records only go to the dead letter stream when configured
+                            if let Some(dead_letter) = &dead_letter {
+                                send_to_dead_letter(dead_letter, &node, idx, res, &err_msg).await;
+                                continue;
+                            }
                             res
                         }
                     };
@@ -670,6 +805,7 @@ async fn process_node(
             // source node for Scheduled pipeline. Directly send to children nodes
             log::debug!("[Pipeline]: query node {node_idx} starts processing");
             while let Some(item) = receiver.recv().await {
+                received += 1;
                 send_to_children(&mut child_senders, item, "QueryNode").await;
                 count += 1;
             }
@@ -683,6 +819,7 @@ async fn process_node(
                 remote_stream
             );
             while let Some((_, record, _)) = receiver.recv().await {
+                received += 1;
                 // External destinations will automatically flatten the payload, hence
                 // no need to flatten the records here
                 records.push(record);
@@ -724,11 +861,50 @@ async fn process_node(
         }
     }
 
+    add_node_stats(
+        &pipeline_id,
+        &node.id,
+        &node.node_type(),
+        received as u64,
+        count as u64,
+        0,
+    );
+
     // all cloned senders dropped when function goes out of scope -> close the channel
 
     Ok(())
 }
 
+/// Writes a record failed at the node into the dead letter stream, along with the error and
+/// the failing node.
+async fn send_to_dead_letter(
+    dead_letter: &DeadLetter,
+    node: &ExecutableNode,
+    idx: usize,
+    record: Value,
+    err_msg: &str,
+) {
+    let (stream_params, sender) = dead_letter;
+    let raw = record.to_string();
+    let mut record = match flatten::flatten_with_level(
+        record,
+        config::get_config().limit.ingest_flatten_level,
+    ) {
+        Ok(flattened) if flattened.is_object() => flattened,
+        _ => config::utils::json::json!({ "_original": raw }),
+    };
+    let map = record.as_object_mut().unwrap();
+    map.insert("_pipeline_error".to_string(), err_msg.into());
+    map.insert("_pipeline_node_id".to_string(), node.id.as_str().into());
+    map.insert("_pipeline_node_type".to_string(), node.node_type().into());
+    if let Err(send_err) = sender.send((idx, stream_params.clone(), record)).await {
+        log::error!(
+            "[Pipeline]: {} failed sending record to dead letter stream caused by: {send_err}",
+            node.node_type()
+        );
+    }
+}
+
 async fn send_to_children(
     child_senders: &mut [Sender<(usize, Value, bool)>],
     item: (usize, Value, bool),
@@ -844,7 +1020,13 @@ fn resolve_stream_name(haystack: &str, record: &Value) -> Result<String> {
 
 #[cfg(test)]
 mod tests {
-    use config::utils::json;
+    use config::{
+        meta::{
+            pipeline::components::{ConditionParams, FunctionParams},
+            stream::{Operator, RoutingCondition},
+        },
+        utils::json,
+    };
 
     use super::*;
 
@@ -873,4 +1055,180 @@ mod tests {
         let err1 = resolve_stream_name("{{eulav}}", &record);
         assert!(err1.is_err());
     }
+
+    #[tokio::test]
+    async fn test_pipeline_node_stats() {
+        let pipeline_id = "test_pipeline_node_stats".to_string();
+        let nodes = [
+            (
+                "1_source",
+                NodeData::Stream(StreamParams::new("default", "src", StreamType::Logs)),
+                vec!["2_cond".to_string()],
+            ),
+            (
+                "2_cond",
+                NodeData::Condition(ConditionParams {
+                    conditions: vec![RoutingCondition {
+                        column: "level".to_string(),
+                        operator: Operator::EqualTo,
+                        value: json::json!("error"),
+                        ignore_case: false,
+                    }],
+                }),
+                vec!["3_dest".to_string()],
+            ),
+            (
+                "3_dest",
+                NodeData::Stream(StreamParams::new("default", "dst", StreamType::Logs)),
+                vec![],
+            ),
+        ];
+        let pipeline = ExecutablePipeline {
+            id: pipeline_id.clone(),
+            name: "test".to_string(),
+            source_node_id: "1_source".to_string(),
+            sorted_nodes: nodes.iter().map(|(id, ..)| id.to_string()).collect(),
+            vrl_map: HashMap::new(),
+            node_map: nodes
+                .into_iter()
+                .map(|(id, node_data, children)| {
+                    (
+                        id.to_string(),
+                        ExecutableNode {
+                            id: id.to_string(),
+                            node_data,
+                            children,
+                        },
+                    )
+                })
+                .collect(),
+            dead_letter_stream: None,
+        };
+
+        let records = ["error", "info", "error", "info", "info"]
+            .iter()
+            .map(|level| json::json!({"level": level}))
+            .collect::<Vec<_>>();
+        pipeline.process_batch("default", records).await.unwrap();
+
+        let stats = get_pipeline_stats(&pipeline_id);
+        let counts = stats
+            .nodes
+            .iter()
+            .map(|n| {
+                (
+                    n.node_id.as_str(),
+                    n.received,
+                    n.processed,
+                    n.dropped,
+                    n.errors,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            counts,
+            vec![
+                ("1_source", 5, 5, 0, 0),
+                ("2_cond", 5, 2, 3, 0),
+                ("3_dest", 2, 2, 0, 0),
+            ]
+        );
+
+        // the condition node was removed from the pipeline
+        retain_node_stats(
+            &pipeline_id,
+            &["1_source".to_string(), "3_dest".to_string()],
+        );
+        let node_ids = get_pipeline_stats(&pipeline_id)
+            .nodes
+            .into_iter()
+            .map(|n| n.node_id)
+            .collect::<Vec<_>>();
+        assert_eq!(node_ids, vec!["1_source", "3_dest"]);
+
+        // the pipeline was deleted
+        remove_pipeline_stats(&pipeline_id);
+        assert!(get_pipeline_stats(&pipeline_id).nodes.is_empty());
+        assert!(!NODE_STATS.read().contains_key(&pipeline_id));
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_stream() {
+        let vrl_config = compile_vrl_function("abort", "default").unwrap();
+        vrl_config
+            .config
+            .get_custom::<vector_enrichment::TableRegistry>()
+            .unwrap()
+            .finish_load();
+        let nodes = [
+            (
+                "1_source",
+                NodeData::Stream(StreamParams::new("default", "src", StreamType::Logs)),
+                vec!["2_func".to_string()],
+            ),
+            (
+                "2_func",
+                NodeData::Function(FunctionParams {
+                    name: "abort_fn".to_string(),
+                    after_flatten: false,
+                    num_args: 0,
+                }),
+                vec!["3_dest".to_string()],
+            ),
+            (
+                "3_dest",
+                NodeData::Stream(StreamParams::new("default", "dst", StreamType::Logs)),
+                vec![],
+            ),
+        ];
+        let dead_letter = StreamParams::new("default", "dead_letter", StreamType::Logs);
+        let pipeline = ExecutablePipeline {
+            id: "test_dead_letter_stream".to_string(),
+            name: "test".to_string(),
+            source_node_id: "1_source".to_string(),
+            sorted_nodes: nodes.iter().map(|(id, ..)| id.to_string()).collect(),
+            vrl_map: HashMap::from([(
+                "2_func".to_string(),
+                VRLResultResolver {
+                    program: vrl_config.program,
+                    fields: vrl_config.fields,
+                },
+            )]),
+            node_map: nodes
+                .into_iter()
+                .map(|(id, node_data, children)| {
+                    (
+                        id.to_string(),
+                        ExecutableNode {
+                            id: id.to_string(),
+                            node_data,
+                            children,
+                        },
+                    )
+                })
+                .collect(),
+            dead_letter_stream: Some(dead_letter.clone()),
+        };
+        assert!(pipeline
+            .get_all_destination_streams()
+            .contains(&dead_letter));
+
+        let records = vec![json::json!({"level": "error", "message": "failed"})];
+        let results = pipeline.process_batch("default", records).await.unwrap();
+
+        let dest = StreamParams::new("default", "dst", StreamType::Logs);
+        assert!(results.get(&dest).is_none());
+        let dead_letters = results.get(&dead_letter).unwrap();
+        assert_eq!(dead_letters.len(), 1);
+        let (idx, record) = &dead_letters[0];
+        assert_eq!(*idx, 0);
+        assert_eq!(record["level"], "error");
+        assert_eq!(record["message"], "failed");
+        assert_eq!(record["_pipeline_node_id"], "2_func");
+        assert_eq!(record["_pipeline_node_type"], "function");
+        assert!(record["_pipeline_error"]
+            .as_str()
+            .unwrap()
+            .starts_with("FunctionNode error"));
+    }
 }
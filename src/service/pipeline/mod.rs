@@ -13,20 +13,39 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use config::meta::{
-    pipeline::{components::PipelineSource, Pipeline, PipelineList},
-    search::SearchEventType,
-    stream::ListStreamParams,
+use std::{collections::HashMap, sync::Arc};
+
+use config::{
+    cluster::LOCAL_NODE,
+    meta::{
+        cluster::NodeInfo,
+        pipeline::{
+            components::PipelineSource, Pipeline, PipelineDestinations, PipelineDrainStatus,
+            PipelineList, PipelineNodeStats, PipelineStats,
+        },
+        search::SearchEventType,
+        stream::ListStreamParams,
+    },
 };
+use infra::errors::Error;
+use proto::cluster_rpc;
 
-use super::db::pipeline::{self, PipelineError};
+use super::{
+    db::pipeline::{self, PipelineError},
+    grpc::make_grpc_ingest_client,
+};
 use crate::common::{
+    infra::cluster::get_cached_online_ingester_nodes,
     meta::authz::Authz,
     utils::auth::{remove_ownership, set_ownership},
 };
 
+pub mod backfill;
 pub mod batch_execution;
 
+// max time to wait for in flight batches when disabling a pipeline
+const DRAIN_TIMEOUT_SECS: u64 = 30;
+
 #[tracing::instrument(skip(pipeline))]
 pub async fn save_pipeline(mut pipeline: Pipeline) -> Result<(), PipelineError> {
     // check if another realtime pipeline with the same source stream already exists
@@ -171,8 +190,9 @@ pub async fn enable_pipeline(
     pipeline_id: &str,
     value: bool,
 ) -> Result<(), PipelineError> {
-    let Ok(mut pipeline) = pipeline::get_by_id(pipeline_id).await else {
-        return Err(PipelineError::NotFound(pipeline_id.to_string()));
+    let mut pipeline = match pipeline::get_by_id(pipeline_id).await {
+        Ok(pipeline) if pipeline.org == org_id => pipeline,
+        _ => return Err(PipelineError::NotFound(pipeline_id.to_string())),
     };
 
     pipeline.enabled = value;
@@ -195,9 +215,171 @@ pub async fn enable_pipeline(
     }
 
     pipeline::update(&pipeline, None).await?;
+
+    // once disabled the pipeline gets no new batches, let the ones already
+    // routed into it on every ingester finish
+    if !value
+        && !drain_in_flight(
+            pipeline_id,
+            std::time::Duration::from_secs(DRAIN_TIMEOUT_SECS),
+        )
+        .await
+    {
+        log::warn!(
+            "[Pipeline] {pipeline_id} disabled before in flight batches were drained in {DRAIN_TIMEOUT_SECS}s"
+        );
+    }
     Ok(())
 }
 
+/// Waits until no ingester has in flight batches for the pipeline.
+/// Returns false if the batches are not drained within the timeout.
+async fn drain_in_flight(pipeline_id: &str, timeout: std::time::Duration) -> bool {
+    let start = std::time::Instant::now();
+    while cluster_in_flight_batches(pipeline_id).await > 0 {
+        if start.elapsed() >= timeout {
+            return false;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    true
+}
+
+/// Returns the batches of the pipeline in flight on every ingester.
+async fn cluster_in_flight_batches(pipeline_id: &str) -> usize {
+    get_ingesters_pipeline_status(pipeline_id)
+        .await
+        .iter()
+        .map(|status| status.in_flight_batches as usize)
+        .sum()
+}
+
+/// Returns the status of the pipeline on this node.
+pub fn get_local_pipeline_status(pipeline_id: &str) -> cluster_rpc::PipelineStatusResponse {
+    let nodes = batch_execution::get_pipeline_stats(pipeline_id)
+        .nodes
+        .into_iter()
+        .map(|stats| cluster_rpc::PipelineNodeStats {
+            node_id: stats.node_id,
+            node_type: stats.node_type,
+            received: stats.received,
+            processed: stats.processed,
+            dropped: stats.dropped,
+            errors: stats.errors,
+        })
+        .collect();
+    cluster_rpc::PipelineStatusResponse {
+        in_flight_batches: batch_execution::in_flight_batches(pipeline_id) as u64,
+        nodes,
+    }
+}
+
+/// Returns the status of the pipeline on every online ingester, this node
+/// answering without a grpc call. Ingesters failing to answer are left out.
+async fn get_ingesters_pipeline_status(
+    pipeline_id: &str,
+) -> Vec<cluster_rpc::PipelineStatusResponse> {
+    let mut statuses = Vec::new();
+    if LOCAL_NODE.is_ingester() {
+        statuses.push(get_local_pipeline_status(pipeline_id));
+    }
+    let nodes = get_cached_online_ingester_nodes()
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|node| node.uuid != LOCAL_NODE.uuid);
+    let tasks = nodes.map(|node| async move {
+        let mut request = tonic::Request::new(cluster_rpc::PipelineStatusRequest {
+            pipeline_id: pipeline_id.to_string(),
+        });
+        let node = Arc::new(node) as Arc<dyn NodeInfo>;
+        let mut client = make_grpc_ingest_client(&mut request, &node).await?;
+        let status = client
+            .pipeline_status(request)
+            .await
+            .map_err(|e| Error::Message(e.message().to_string()))?
+            .into_inner();
+        Ok::<_, Error>(status)
+    });
+    for res in futures::future::join_all(tasks).await {
+        match res {
+            Ok(status) => statuses.push(status),
+            Err(e) => log::warn!("[Pipeline] {pipeline_id} get ingester status error: {e}"),
+        }
+    }
+    statuses
+}
+
+#[tracing::instrument]
+pub async fn get_pipeline_destinations(
+    org_id: &str,
+    pipeline_id: &str,
+) -> Result<PipelineDestinations, PipelineError> {
+    let pipeline = match pipeline::get_by_id(pipeline_id).await {
+        Ok(pipeline) if pipeline.org == org_id => pipeline,
+        _ => return Err(PipelineError::NotFound(pipeline_id.to_string())),
+    };
+    pipeline
+        .get_downstream_destinations()
+        .map_err(|e| PipelineError::InvalidPipeline(e.to_string()))
+}
+
+/// Returns the records counters of each node of the pipeline, summed over
+/// every ingester.
+pub async fn get_pipeline_stats(
+    org_id: &str,
+    pipeline_id: &str,
+) -> Result<PipelineStats, PipelineError> {
+    match pipeline::get_by_id(pipeline_id).await {
+        Ok(pipeline) if pipeline.org == org_id => {}
+        _ => return Err(PipelineError::NotFound(pipeline_id.to_string())),
+    };
+    let statuses = get_ingesters_pipeline_status(pipeline_id).await;
+    Ok(merge_pipeline_stats(pipeline_id, statuses))
+}
+
+fn merge_pipeline_stats(
+    pipeline_id: &str,
+    statuses: Vec<cluster_rpc::PipelineStatusResponse>,
+) -> PipelineStats {
+    let mut nodes: HashMap<String, PipelineNodeStats> = HashMap::new();
+    for node in statuses.into_iter().flat_map(|status| status.nodes) {
+        let stats = nodes
+            .entry(node.node_id.clone())
+            .or_insert_with(|| PipelineNodeStats {
+                node_id: node.node_id,
+                node_type: node.node_type,
+                ..Default::default()
+            });
+        stats.received += node.received;
+        stats.processed += node.processed;
+        stats.dropped += node.dropped;
+        stats.errors += node.errors;
+    }
+    let mut nodes = nodes.into_values().collect::<Vec<_>>();
+    nodes.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+    PipelineStats {
+        pipeline_id: pipeline_id.to_string(),
+        nodes,
+    }
+}
+
+pub async fn get_drain_status(
+    org_id: &str,
+    pipeline_id: &str,
+) -> Result<PipelineDrainStatus, PipelineError> {
+    match pipeline::get_by_id(pipeline_id).await {
+        Ok(pipeline) if pipeline.org == org_id => {}
+        _ => return Err(PipelineError::NotFound(pipeline_id.to_string())),
+    };
+    let in_flight_batches = cluster_in_flight_batches(pipeline_id).await;
+    Ok(PipelineDrainStatus {
+        pipeline_id: pipeline_id.to_string(),
+        in_flight_batches,
+        drained: in_flight_batches == 0,
+    })
+}
+
 #[tracing::instrument]
 pub async fn delete_pipeline(pipeline_id: &str) -> Result<(), PipelineError> {
     let Ok(existing_pipeline) = pipeline::get_by_id(pipeline_id).await else {
@@ -226,3 +408,82 @@ pub async fn delete_pipeline(pipeline_id: &str) -> Result<(), PipelineError> {
     .await;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use config::meta::{
+        pipeline::components::{Edge, Node, NodeData},
+        stream::{StreamParams, StreamType},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_disable_pipeline_drains_in_flight() {
+        infra::pipeline::init().await.unwrap();
+        let org_id = "test_disable_pipeline_drains";
+        let source = StreamParams::new(org_id, "drain_src", StreamType::Logs);
+        let dest = StreamParams::new(org_id, "drain_dst", StreamType::Logs);
+        let pipeline = Pipeline {
+            id: "test_disable_pipeline_drains".to_string(),
+            version: 0,
+            enabled: true,
+            org: org_id.to_string(),
+            name: "drain".to_string(),
+            description: "".to_string(),
+            source: PipelineSource::Realtime(source.clone()),
+            nodes: vec![
+                Node::new(
+                    "1".to_string(),
+                    NodeData::Stream(source),
+                    0.0,
+                    0.0,
+                    "input".to_string(),
+                ),
+                Node::new(
+                    "2".to_string(),
+                    NodeData::Stream(dest),
+                    0.0,
+                    0.0,
+                    "output".to_string(),
+                ),
+            ],
+            edges: vec![Edge::new("1".to_string(), "2".to_string())],
+            dead_letter_stream: None,
+        };
+        pipeline::set(&pipeline).await.unwrap();
+
+        // another org can neither disable it nor read its drain status
+        assert!(matches!(
+            enable_pipeline("other_org", &pipeline.id, false).await,
+            Err(PipelineError::NotFound(_))
+        ));
+        assert!(get_drain_status("other_org", &pipeline.id).await.is_err());
+        assert!(pipeline::get_by_id(&pipeline.id).await.unwrap().enabled);
+
+        // a batch already routed into the pipeline when it gets disabled
+        let batch = batch_execution::InFlightGuard::new(&pipeline.id);
+        let status = get_drain_status(org_id, &pipeline.id).await.unwrap();
+        assert_eq!(status.in_flight_batches, 1);
+        assert!(!status.drained);
+        let finish = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            drop(batch);
+        });
+
+        // disabling returns once the batch is done
+        let start = Instant::now();
+        enable_pipeline(org_id, &pipeline.id, false).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        assert!(!pipeline::get_by_id(&pipeline.id).await.unwrap().enabled);
+        assert!(
+            get_drain_status(org_id, &pipeline.id)
+                .await
+                .unwrap()
+                .drained
+        );
+        finish.await.unwrap();
+    }
+}
@@ -13,12 +13,20 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{collections::HashSet, str::FromStr, sync::Arc, time::Duration};
+use std::{
+    collections::{BTreeMap, HashSet},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
 
 use arrow::array::Array;
 use async_recursion::async_recursion;
 use config::{
-    meta::promql::{HashLabelValue, EXEMPLARS_LABEL, HASH_LABEL, NAME_LABEL, VALUE_LABEL},
+    meta::{
+        promql::{HashLabelValue, EXEMPLARS_LABEL, HASH_LABEL, NAME_LABEL, VALUE_LABEL},
+        stream::MaskStrategy,
+    },
     utils::json,
     TIMESTAMP_COL_NAME,
 };
@@ -44,7 +52,7 @@ use promql_parser::{
 use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
 
 use super::{
-    utils::{apply_label_selector, apply_matchers},
+    utils::{apply_field_masks, apply_label_selector, apply_matchers, apply_row_filter},
     PromqlContext,
 };
 use crate::service::promql::{
@@ -632,10 +640,20 @@ impl Engine {
             )
             .await?;
 
+        let max_series = max_series_per_query();
+        let field_masks = self
+            .ctx
+            .field_masks
+            .get(table_name.as_str())
+            .cloned()
+            .unwrap_or_default();
+        let row_filter = self.ctx.row_filters.get(table_name.as_str()).cloned();
         let mut tasks = Vec::new();
         for (ctx, schema, scan_stats) in ctxs {
             let selector = selector.clone();
             let col_filters = &self.col_filters;
+            let field_masks = &field_masks;
+            let row_filter = row_filter.as_deref();
             let query_exemplars = self.ctx.query_exemplars;
             let trace_id = self.trace_id.to_string();
             let task = tokio::time::timeout(Duration::from_secs(self.ctx.timeout), async move {
@@ -647,7 +665,10 @@ impl Engine {
                     start,
                     end,
                     col_filters,
+                    row_filter,
+                    field_masks,
                     query_exemplars,
+                    max_series,
                 )
                 .await
             });
@@ -677,6 +698,8 @@ impl Engine {
             }
         }
 
+        check_max_series(metrics.len(), max_series)?;
+
         log::info!(
             "[trace_id: {}] load data done for stream: {}, took: {} ms",
             self.trace_id,
@@ -1105,6 +1128,26 @@ impl Engine {
     }
 }
 
+fn max_series_per_query() -> usize {
+    let max_series = config::get_config().limit.metrics_max_series_per_query;
+    if max_series > 0 {
+        max_series
+    } else {
+        DEFAULT_MAX_SERIES_PER_QUERY
+    }
+}
+
+/// Fails the query when a selector matches more series than allowed, instead
+/// of returning a truncated result.
+fn check_max_series(series: usize, max_series: usize) -> Result<()> {
+    if series > max_series {
+        return Err(DataFusionError::ResourcesExhausted(format!(
+            "query matches more than {max_series} series, the limit set by ZO_METRICS_MAX_SERIES_PER_QUERY"
+        )));
+    }
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn selector_load_data_from_datafusion(
     trace_id: &str,
@@ -1114,9 +1157,11 @@ async fn selector_load_data_from_datafusion(
     start: i64,
     end: i64,
     label_selector: &Option<HashSet<String>>,
+    row_filter: Option<&str>,
+    field_masks: &BTreeMap<String, MaskStrategy>,
     query_exemplars: bool,
+    max_series: usize,
 ) -> Result<HashMap<HashLabelValue, RangeValue>> {
-    let cfg = config::get_config();
     let table_name = selector.name.as_ref().unwrap();
     let mut df_group = match ctx.table(table_name).await {
         Ok(v) => v.filter(
@@ -1129,6 +1174,10 @@ async fn selector_load_data_from_datafusion(
         }
     };
 
+    // filter on the clear values, then mask before the matchers, they must
+    // not match the clear values
+    df_group = apply_row_filter(&ctx, df_group, row_filter)?;
+    df_group = apply_field_masks(&ctx, df_group, field_masks)?;
     df_group = apply_matchers(df_group, &schema, &selector.matchers)?;
 
     match apply_label_selector(df_group, &schema, label_selector) {
@@ -1154,12 +1203,6 @@ async fn selector_load_data_from_datafusion(
         })
         .collect::<Vec<_>>();
 
-    let max_series = if cfg.limit.metrics_max_series_per_query > 0 {
-        cfg.limit.metrics_max_series_per_query
-    } else {
-        DEFAULT_MAX_SERIES_PER_QUERY
-    };
-
     // get hash & timestamp
     let start_time = std::time::Instant::now();
     let sub_batch = df_group
@@ -1169,9 +1212,14 @@ async fn selector_load_data_from_datafusion(
             vec![max(col(TIMESTAMP_COL_NAME)).alias(TIMESTAMP_COL_NAME)],
         )?
         .sort(vec![col(HASH_LABEL).sort(true, true)])?
-        .limit(0, Some(max_series))?
+        // one more than the limit to tell whether it is exceeded
+        .limit(0, Some(max_series + 1))?
         .collect()
         .await?;
+    check_max_series(
+        sub_batch.iter().map(|batch| batch.num_rows()).sum(),
+        max_series,
+    )?;
 
     let hash_field_type = schema.field_with_name(HASH_LABEL).unwrap().data_type();
     let (mut timestamp_values, hash_value_set): (Vec<_>, HashSet<HashLabelValue>) =
@@ -1559,3 +1607,137 @@ async fn load_exemplars_from_datafusion(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use arrow::record_batch::RecordBatch;
+    use datafusion::{arrow::datatypes::Field, datasource::MemTable};
+    use promql_parser::parser;
+
+    use super::*;
+
+    // three series of two samples each
+    async fn load_cpu(max_series: usize) -> Result<HashMap<HashLabelValue, RangeValue>> {
+        load_cpu_as("cpu", None, &BTreeMap::new(), max_series).await
+    }
+
+    async fn load_cpu_as(
+        query: &str,
+        row_filter: Option<&str>,
+        field_masks: &BTreeMap<String, MaskStrategy>,
+        max_series: usize,
+    ) -> Result<HashMap<HashLabelValue, RangeValue>> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false),
+            Field::new(HASH_LABEL, DataType::Utf8, false),
+            Field::new(VALUE_LABEL, DataType::Float64, false),
+            Field::new("job", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![10, 10, 10, 20, 20, 20])),
+                Arc::new(StringArray::from(vec!["a", "b", "c", "a", "b", "c"])),
+                Arc::new(Float64Array::from(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0])),
+                Arc::new(StringArray::from(vec!["a", "b", "c", "a", "b", "c"])),
+            ],
+        )
+        .unwrap();
+        let ctx = SessionContext::new();
+        let table = MemTable::try_new(schema.clone(), vec![vec![batch]]).unwrap();
+        ctx.register_table("cpu", Arc::new(table)).unwrap();
+
+        let PromExpr::VectorSelector(selector) = parser::parse(query).unwrap() else {
+            unreachable!()
+        };
+        selector_load_data_from_datafusion(
+            "test",
+            ctx,
+            schema,
+            selector,
+            0,
+            100,
+            &None,
+            row_filter,
+            field_masks,
+            false,
+            max_series,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_max_series_exceeded() {
+        let err = load_cpu(2).await.unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("more than 2 series"), "{msg}");
+        assert!(msg.contains("ZO_METRICS_MAX_SERIES_PER_QUERY"), "{msg}");
+    }
+
+    #[tokio::test]
+    async fn test_max_series_under_limit() {
+        let metrics = load_cpu(3).await.unwrap();
+        assert_eq!(metrics.len(), 3);
+        for series in metrics.values() {
+            assert_eq!(series.samples.len(), 2);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_load_data_with_field_masks() {
+        let masks = BTreeMap::from([("job".to_string(), MaskStrategy::Full)]);
+        // the matchers don't see the clear values
+        let metrics = load_cpu_as("cpu{job=\"a\"}", None, &masks, 3)
+            .await
+            .unwrap();
+        assert!(metrics.is_empty());
+
+        let metrics = load_cpu_as("cpu{job=\"********\"}", None, &masks, 3)
+            .await
+            .unwrap();
+        assert_eq!(metrics.len(), 3);
+        for series in metrics.values() {
+            let job = series.labels.iter().find(|l| l.name == "job").unwrap();
+            assert_eq!(job.value, "********");
+            assert_eq!(series.samples.len(), 2);
+        }
+
+        // users who may see the clear values
+        let metrics = load_cpu_as("cpu{job=\"a\"}", None, &BTreeMap::new(), 3)
+            .await
+            .unwrap();
+        assert_eq!(metrics.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_load_data_with_row_filter() {
+        let no_masks = BTreeMap::new();
+        let metrics = load_cpu_as("cpu", Some("job = 'a' OR job = 'b'"), &no_masks, 3)
+            .await
+            .unwrap();
+        assert_eq!(metrics.len(), 2);
+        // the matchers can't reach the filtered series
+        let metrics = load_cpu_as("cpu{job=\"c\"}", Some("job = 'a'"), &no_masks, 3)
+            .await
+            .unwrap();
+        assert!(metrics.is_empty());
+        // roles without a filter are denied every series
+        let metrics = load_cpu_as("cpu", Some("FALSE"), &no_masks, 3)
+            .await
+            .unwrap();
+        assert!(metrics.is_empty());
+        // the row filter sees the clear values of the masked labels
+        let masks = BTreeMap::from([("job".to_string(), MaskStrategy::Full)]);
+        let metrics = load_cpu_as("cpu", Some("job = 'a'"), &masks, 3)
+            .await
+            .unwrap();
+        assert_eq!(metrics.len(), 1);
+    }
+
+    #[test]
+    fn test_check_max_series() {
+        assert!(check_max_series(0, 1).is_ok());
+        assert!(check_max_series(1, 1).is_ok());
+        assert!(check_max_series(2, 1).is_err());
+    }
+}
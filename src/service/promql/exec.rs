@@ -14,11 +14,12 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::{
+    collections::BTreeMap,
     sync::Arc,
     time::{Duration, SystemTime},
 };
 
-use config::meta::search::ScanStats;
+use config::meta::{search::ScanStats, stream::MaskStrategy};
 use datafusion::error::{DataFusionError, Result};
 use hashbrown::{HashMap, HashSet};
 use promql_parser::parser::EvalStmt;
@@ -48,6 +49,10 @@ pub struct PromqlContext {
     pub scan_stats: Arc<RwLock<ScanStats>>,
     pub timeout: u64, // seconds, query timeout
     pub data_loading: Arc<Mutex<HashSet<String>>>,
+    /// key — metric name; value — the labels masked for the user
+    pub field_masks: HashMap<String, BTreeMap<String, MaskStrategy>>,
+    /// key — metric name; value — the row filter of the user's roles
+    pub row_filters: HashMap<String, String>,
 }
 
 impl PromqlContext {
@@ -69,6 +74,8 @@ impl PromqlContext {
             data_loading: Arc::new(Mutex::new(HashSet::default())),
             scan_stats: Arc::new(RwLock::new(ScanStats::default())),
             timeout,
+            field_masks: HashMap::default(),
+            row_filters: HashMap::default(),
         }
     }
 
@@ -0,0 +1,688 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use config::{
+    get_config,
+    meta::{
+        promql::{MetricType, RollupOffset, NAME_LABEL},
+        stream::StreamType,
+    },
+};
+use hashbrown::HashMap;
+use promql_parser::{
+    label::MatchOp,
+    parser::{self, Expr, Offset},
+    util::walk_expr,
+};
+
+use super::{
+    micros,
+    name_visitor::MetricNameVisitor,
+    value::{signature, Label, Labels, Value},
+};
+use crate::service::{
+    db,
+    metrics::{get_prom_metadata_from_schema, rollup::rollup_stream_name},
+};
+
+/// How the rollup job aggregated the raw samples of a series in a bucket,
+/// which decides the functions giving the same result on the rollup.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RollupValue {
+    /// The last raw sample, for counters, histograms and summaries.
+    Last,
+    /// The average of the raw samples, for gauges.
+    Avg,
+}
+
+#[derive(Debug, Clone)]
+struct RollupSource {
+    offset: RollupOffset,
+    value: RollupValue,
+}
+
+/// A range query rewritten to read the rollup streams of its metrics.
+#[derive(Debug)]
+pub struct RollupQuery {
+    pub query: String,
+    /// The original metric name by rollup stream name
+    pub names: HashMap<String, String>,
+    /// The last evaluation time served from the rollups. The later ones read
+    /// samples not rolled up yet, so they are evaluated on the raw samples.
+    pub end: i64,
+}
+
+/// Rewrites a range query with a step of at least the rollup resolution to read
+/// the rollup streams of its metrics, for the metrics having one that covers
+/// the start of the time range read by the query.
+///
+/// Rollups stop `ZO_INGEST_ALLOWED_UPTO` before now, so a query ending near
+/// now is only served from the rollups up to [RollupQuery::end].
+///
+/// The evaluation times must fall on bucket boundaries, so `start` and `step`
+/// must be multiples of the resolution.
+///
+/// Returns `None` when the query is left as is.
+pub async fn use_rollup_streams(
+    org_id: &str,
+    query: &str,
+    start: i64,
+    end: i64,
+    step: i64,
+) -> Option<RollupQuery> {
+    let cfg = get_config();
+    let resolution = cfg.common.metrics_rollup_resolution;
+    let resolution_micros = resolution * 1_000_000;
+    if !cfg.common.metrics_rollup_enabled
+        || start == end
+        || step < resolution_micros
+        || step % resolution_micros != 0
+        || start % resolution_micros != 0
+    {
+        return None;
+    }
+
+    let mut expr = parser::parse(query).ok()?;
+    let mut visitor = MetricNameVisitor::new();
+    walk_expr(&mut visitor, &expr).ok()?;
+    let mut rollups = HashMap::new();
+    for name in visitor.name {
+        let Some(offset) = db::metrics::get_rollup_offset(org_id, &name, resolution).await else {
+            continue;
+        };
+        let value = match infra::schema::get(org_id, &name, StreamType::Metrics)
+            .await
+            .ok()
+            .and_then(|schema| get_prom_metadata_from_schema(&schema))
+            .map(|metadata| metadata.metric_type)
+        {
+            Some(MetricType::Counter | MetricType::Histogram | MetricType::Summary) => {
+                RollupValue::Last
+            }
+            Some(MetricType::Gauge) => RollupValue::Avg,
+            _ => continue,
+        };
+        let rollup_name = rollup_stream_name(&name, resolution);
+        if infra::schema::get(org_id, &rollup_name, StreamType::Metrics)
+            .await
+            .is_ok_and(|schema| !schema.fields().is_empty())
+        {
+            rollups.insert(name, RollupSource { offset, value });
+        }
+    }
+    if rollups.is_empty() {
+        return None;
+    }
+    let until = rollups_cover(&expr, &rollups, start, resolution_micros)?;
+    let rollup_end = last_eval_before(start, end, step, until)?;
+
+    let names = rollups
+        .into_keys()
+        .map(|name| {
+            let rollup_name = rollup_stream_name(&name, resolution);
+            (name, rollup_name)
+        })
+        .collect::<HashMap<_, _>>();
+
+    rename_metrics(&mut expr, &names);
+    Some(RollupQuery {
+        query: expr.to_string(),
+        names: names
+            .into_iter()
+            .map(|(name, rollup)| (rollup, name))
+            .collect(),
+        end: rollup_end,
+    })
+}
+
+/// The last evaluation time of `[start, end]` at `step` before `until`, `None`
+/// when there is none.
+fn last_eval_before(start: i64, end: i64, step: i64, until: i64) -> Option<i64> {
+    if until <= start {
+        return None;
+    }
+    if until > end {
+        return Some(end);
+    }
+    Some(start + (until - 1 - start) / step * step)
+}
+
+/// Checks the rollups in `rollups` can serve every selector of their metrics in
+/// `expr` evaluated from `start`, `resolution` in microseconds. Returns the
+/// evaluation time up to which, exclusive, they all serve it, `None` when they
+/// don't.
+///
+/// A rollup holds a single sample per series and bucket, so its metrics may
+/// only be read by the range functions giving the same result on it as on the
+/// raw samples: `rate` and `increase` of counters over at least two buckets,
+/// `last_over_time` of counters and `avg_over_time` of gauges. The selectors
+/// must also only read raw samples already rolled up.
+fn rollups_cover(
+    expr: &Expr,
+    rollups: &HashMap<String, RollupSource>,
+    start: i64,
+    resolution: i64,
+) -> Option<i64> {
+    let cover = |expr: &Expr| rollups_cover(expr, rollups, start, resolution);
+    let is_rollup = |vs: &parser::VectorSelector| {
+        vs.name
+            .as_ref()
+            .is_some_and(|name| rollups.contains_key(name))
+    };
+    match expr {
+        Expr::Aggregate(expr) => rollups_cover_all(
+            std::iter::once(expr.expr.as_ref()).chain(expr.param.as_deref()),
+            rollups,
+            start,
+            resolution,
+        ),
+        Expr::Unary(expr) => cover(&expr.expr),
+        Expr::Binary(expr) => rollups_cover_all(
+            [expr.lhs.as_ref(), expr.rhs.as_ref()],
+            rollups,
+            start,
+            resolution,
+        ),
+        Expr::Paren(expr) => cover(&expr.expr),
+        // subqueries evaluate their expression at their own steps
+        Expr::Subquery(expr) => {
+            let mut visitor = MetricNameVisitor::new();
+            (walk_expr(&mut visitor, &expr.expr).is_ok()
+                && visitor.name.iter().all(|name| !rollups.contains_key(name)))
+            .then_some(i64::MAX)
+        }
+        Expr::VectorSelector(vs) => (!is_rollup(vs)).then_some(i64::MAX),
+        Expr::MatrixSelector(ms) => (!is_rollup(&ms.vs)).then_some(i64::MAX),
+        Expr::Call(call) => match call.args.args.first().map(|arg| arg.as_ref()) {
+            Some(Expr::MatrixSelector(ms)) if is_rollup(&ms.vs) => {
+                let rollup = &rollups[ms.vs.name.as_ref().unwrap()];
+                if call.args.args.len() != 1 {
+                    return None;
+                }
+                rollup_serves(call.func.name, ms, rollup, start, resolution)
+            }
+            _ => rollups_cover_all(
+                call.args.args.iter().map(|arg| arg.as_ref()),
+                rollups,
+                start,
+                resolution,
+            ),
+        },
+        Expr::NumberLiteral(_) | Expr::StringLiteral(_) | Expr::Extension(_) => Some(i64::MAX),
+    }
+}
+
+/// [rollups_cover] of all the `exprs`.
+fn rollups_cover_all<'a>(
+    exprs: impl IntoIterator<Item = &'a Expr>,
+    rollups: &HashMap<String, RollupSource>,
+    start: i64,
+    resolution: i64,
+) -> Option<i64> {
+    exprs.into_iter().try_fold(i64::MAX, |until, expr| {
+        Some(until.min(rollups_cover(expr, rollups, start, resolution)?))
+    })
+}
+
+/// Checks `func` over the range selector `ms` gives the same result on the
+/// rollup as on the raw samples for the evaluation times from `start`.
+/// Returns the evaluation time up to which, exclusive, the window of the
+/// selector only reads rolled up samples.
+fn rollup_serves(
+    func: &str,
+    ms: &parser::MatrixSelector,
+    rollup: &RollupSource,
+    start: i64,
+    resolution: i64,
+) -> Option<i64> {
+    let range = micros(ms.range);
+    let supported = match (func, rollup.value) {
+        // the rate between the last samples of the first and last buckets
+        ("rate" | "increase", RollupValue::Last) => range >= 2 * resolution,
+        ("last_over_time", RollupValue::Last) | ("avg_over_time", RollupValue::Avg) => {
+            range >= resolution
+        }
+        _ => false,
+    };
+    if !supported || range % resolution != 0 || ms.vs.at.is_some() {
+        return None;
+    }
+    let shift = match ms.vs.offset {
+        Some(Offset::Pos(off)) => micros(off),
+        Some(Offset::Neg(off)) => -micros(off),
+        None => 0,
+    };
+    (shift % resolution == 0 && rollup.offset.start <= start - range - shift)
+        .then_some(rollup.offset.end.saturating_add(shift))
+}
+
+/// Appends the series of `recent`, the result of a range query over later
+/// evaluation times, to the matching series of `value`.
+pub fn merge_range_values(value: Value, recent: Value) -> Value {
+    match (value, recent) {
+        (Value::Matrix(mut series), Value::Matrix(recent)) => {
+            let mut index = series
+                .iter()
+                .enumerate()
+                .map(|(i, range)| (signature(&range.labels), i))
+                .collect::<HashMap<_, _>>();
+            for range in recent {
+                let sig = signature(&range.labels);
+                match index.get(&sig) {
+                    Some(&i) => series[i].samples.extend(range.samples),
+                    None => {
+                        index.insert(sig, series.len());
+                        series.push(range);
+                    }
+                }
+            }
+            Value::Matrix(series)
+        }
+        (Value::None, recent) => recent,
+        (value, _) => value,
+    }
+}
+
+/// Renames the metrics of the selectors of `expr` found in `names`.
+fn rename_metrics(expr: &mut Expr, names: &HashMap<String, String>) {
+    match expr {
+        Expr::Aggregate(expr) => {
+            rename_metrics(&mut expr.expr, names);
+            if let Some(param) = expr.param.as_mut() {
+                rename_metrics(param, names);
+            }
+        }
+        Expr::Unary(expr) => rename_metrics(&mut expr.expr, names),
+        Expr::Binary(expr) => {
+            rename_metrics(&mut expr.lhs, names);
+            rename_metrics(&mut expr.rhs, names);
+        }
+        Expr::Paren(expr) => rename_metrics(&mut expr.expr, names),
+        Expr::Subquery(expr) => rename_metrics(&mut expr.expr, names),
+        Expr::VectorSelector(vs) => rename_selector(vs, names),
+        Expr::MatrixSelector(ms) => rename_selector(&mut ms.vs, names),
+        Expr::Call(call) => {
+            for arg in call.args.args.iter_mut() {
+                rename_metrics(arg, names);
+            }
+        }
+        Expr::NumberLiteral(_) | Expr::StringLiteral(_) | Expr::Extension(_) => {}
+    }
+}
+
+fn rename_selector(vs: &mut parser::VectorSelector, names: &HashMap<String, String>) {
+    let Some(new_name) = vs.name.as_ref().and_then(|name| names.get(name)) else {
+        return;
+    };
+    for matcher in vs.matchers.matchers.iter_mut() {
+        if matcher.name == NAME_LABEL && matcher.op == MatchOp::Equal {
+            matcher.value = new_name.clone();
+        }
+    }
+    vs.name = Some(new_name.clone());
+}
+
+/// Puts back the original metric names in the `__name__` label of the series
+/// read from rollup streams.
+pub fn restore_metric_names(value: &mut Value, names: &HashMap<String, String>) {
+    match value {
+        Value::Instant(v) => restore_labels(&mut v.labels, names),
+        Value::Range(v) => restore_labels(&mut v.labels, names),
+        Value::Vector(values) => values
+            .iter_mut()
+            .for_each(|v| restore_labels(&mut v.labels, names)),
+        Value::Matrix(values) => values
+            .iter_mut()
+            .for_each(|v| restore_labels(&mut v.labels, names)),
+        Value::Sample(_) | Value::Float(_) | Value::String(_) | Value::None => {}
+    }
+}
+
+fn restore_labels(labels: &mut Labels, names: &HashMap<String, String>) {
+    for label in labels.iter_mut() {
+        if label.name != NAME_LABEL {
+            continue;
+        }
+        if let Some(name) = names.get(&label.value) {
+            *label = Arc::new(Label::new(NAME_LABEL, name.as_str()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use config::{
+        meta::promql::{HASH_LABEL, TYPE_LABEL, VALUE_LABEL},
+        TIMESTAMP_COL_NAME,
+    };
+
+    use super::*;
+    use crate::service::{
+        metrics::rollup::Rollup,
+        promql::{
+            functions,
+            value::{RangeValue, Sample, TimeWindow},
+        },
+    };
+
+    #[test]
+    fn test_rename_metrics() {
+        let names = HashMap::from([("cpu".to_string(), "cpu_rollup_3600".to_string())]);
+        let mut expr =
+            parser::parse(r#"sum(rate(cpu{job="a"}[1h])) / sum(mem) + avg_over_time(cpu[2h])"#)
+                .unwrap();
+        rename_metrics(&mut expr, &names);
+        let query = expr.to_string();
+        assert!(query.contains(r#"cpu_rollup_3600{job="a"}[1h]"#), "{query}");
+        assert!(
+            query.contains("avg_over_time(cpu_rollup_3600[2h])"),
+            "{query}"
+        );
+        assert!(query.contains("sum(mem)"), "{query}");
+        assert!(parser::parse(&query).is_ok());
+    }
+
+    #[test]
+    fn test_rollups_cover() {
+        let hour = 3_600_000_000;
+        let offset = RollupOffset {
+            start: 10 * hour,
+            end: 20 * hour,
+        };
+        let rollups = HashMap::from([
+            (
+                "requests".to_string(),
+                RollupSource {
+                    offset,
+                    value: RollupValue::Last,
+                },
+            ),
+            (
+                "cpu".to_string(),
+                RollupSource {
+                    offset,
+                    value: RollupValue::Avg,
+                },
+            ),
+        ]);
+        // served from the rollups at every evaluation time
+        let cover = |query: &str, start: i64, end: i64| {
+            rollups_cover(&parser::parse(query).unwrap(), &rollups, start, hour)
+                .and_then(|until| last_eval_before(start, end, hour, until))
+                == Some(end)
+        };
+
+        assert!(cover(
+            "sum(rate(requests[2h])) / sum(mem)",
+            12 * hour,
+            19 * hour
+        ));
+        assert!(cover("increase(requests[3h])", 13 * hour, 19 * hour));
+        assert!(cover("last_over_time(requests[1h])", 11 * hour, 19 * hour));
+        assert!(cover("avg_over_time(cpu[2h])", 12 * hour, 19 * hour));
+        // raw samples before the rollup start or after its end
+        assert!(!cover("rate(requests[2h])", 11 * hour, 19 * hour));
+        assert!(!cover("rate(requests[2h])", 12 * hour, 20 * hour));
+        assert!(!cover("rate(requests[2h] offset 2h)", 12 * hour, 19 * hour));
+        assert!(cover("rate(requests[2h] offset 2h)", 14 * hour, 21 * hour));
+        // a single rollup sample per window
+        assert!(!cover("rate(requests[1h])", 11 * hour, 19 * hour));
+        // functions which differ on the rollup
+        for func in [
+            "count_over_time",
+            "sum_over_time",
+            "max_over_time",
+            "min_over_time",
+            "irate",
+            "changes",
+        ] {
+            assert!(
+                !cover(&format!("{func}(requests[2h])"), 12 * hour, 19 * hour),
+                "{func}"
+            );
+        }
+        assert!(!cover("quantile_over_time(0.9, cpu[2h])", 12 * hour, 19 * hour));
+        assert!(!cover("rate(cpu[2h])", 12 * hour, 19 * hour));
+        assert!(!cover("last_over_time(cpu[2h])", 12 * hour, 19 * hour));
+        assert!(!cover("avg_over_time(requests[2h])", 12 * hour, 19 * hour));
+        // ranges not made of whole buckets, instant selectors and subqueries
+        assert!(!cover("rate(requests[5m])", 11 * hour, 19 * hour));
+        assert!(!cover("rate(requests[150m])", 13 * hour, 19 * hour));
+        assert!(!cover("sum(cpu)", 11 * hour, 19 * hour));
+        assert!(!cover("rate(requests[2h] @ 0)", 12 * hour, 19 * hour));
+        assert!(!cover(
+            "max_over_time(rate(requests[2h])[4h:1h])",
+            16 * hour,
+            19 * hour
+        ));
+
+        // the evaluation times reading samples after the rollup end don't
+        let until = rollups_cover(
+            &parser::parse("sum(rate(requests[2h])) + avg_over_time(cpu[2h] offset 1h)").unwrap(),
+            &rollups,
+            13 * hour,
+            hour,
+        )
+        .unwrap();
+        assert_eq!(until, 20 * hour);
+        assert_eq!(
+            last_eval_before(13 * hour, 30 * hour, hour, until),
+            Some(19 * hour)
+        );
+        assert_eq!(last_eval_before(20 * hour, 30 * hour, hour, until), None);
+    }
+
+    #[test]
+    fn test_rollup_last_7d() {
+        let hour = 3_600_000_000;
+        let day = 24 * hour;
+        // a dashboard showing the last 7 days at 00:30, the rollup running up
+        // to the default ZO_INGEST_ALLOWED_UPTO of 5h before now
+        let now = 1000 * day + hour / 2;
+        let offset = RollupOffset {
+            start: 900 * day,
+            end: 1000 * day - 5 * hour,
+        };
+        let rollups = HashMap::from([(
+            "requests".to_string(),
+            RollupSource {
+                offset,
+                value: RollupValue::Last,
+            },
+        )]);
+        let start = now - 7 * day - hour / 2;
+        let until = rollups_cover(
+            &parser::parse("sum(rate(requests[2h]))").unwrap(),
+            &rollups,
+            start,
+            hour,
+        )
+        .unwrap();
+        // all but the last few hours are read from the rollup
+        let rollup_end = last_eval_before(start, now, hour, until).unwrap();
+        assert_eq!(rollup_end, 1000 * day - 6 * hour);
+
+        // and the raw samples of the later evaluation times are appended
+        let series = |samples: &[i64]| RangeValue {
+            labels: vec![Arc::new(Label::new("job", "a"))],
+            samples: samples.iter().map(|&t| Sample::new(t, 1.0)).collect(),
+            exemplars: None,
+            time_window: None,
+        };
+        let rollup = Value::Matrix(vec![series(&[rollup_end - hour, rollup_end])]);
+        let raw = Value::Matrix(vec![
+            series(&[rollup_end + hour, now - hour / 2]),
+            RangeValue {
+                labels: vec![Arc::new(Label::new("job", "b"))],
+                ..series(&[now - hour / 2])
+            },
+        ]);
+        let Value::Matrix(merged) = merge_range_values(rollup, raw) else {
+            unreachable!()
+        };
+        assert_eq!(merged.len(), 2);
+        assert_eq!(
+            merged[0]
+                .samples
+                .iter()
+                .map(|s| s.timestamp)
+                .collect::<Vec<_>>(),
+            vec![
+                rollup_end - hour,
+                rollup_end,
+                rollup_end + hour,
+                now - hour / 2
+            ]
+        );
+        assert_eq!(merged[1].labels[0].value, "b");
+    }
+
+    /// Evaluates a range function over the samples of `[eval_ts - range,
+    /// eval_ts]`, like the engine does for a range selector.
+    fn eval_range_func(
+        func: fn(Value) -> datafusion::error::Result<Value>,
+        samples: &[Sample],
+        eval_ts: i64,
+        range: i64,
+    ) -> Option<f64> {
+        let samples = samples
+            .iter()
+            .filter(|s| s.timestamp >= eval_ts - range && s.timestamp <= eval_ts)
+            .cloned()
+            .collect();
+        let value = func(Value::Matrix(vec![RangeValue {
+            labels: vec![],
+            samples,
+            exemplars: None,
+            time_window: Some(TimeWindow::new(
+                eval_ts,
+                std::time::Duration::from_micros(range as u64),
+            )),
+        }]))
+        .unwrap();
+        let Value::Vector(values) = value else {
+            return None;
+        };
+        values.first().map(|v| v.sample.value)
+    }
+
+    #[test]
+    fn test_rollup_results_match_raw() {
+        let minute = 60_000_000;
+        let hour = 60 * minute;
+        let mut raw_counter = vec![];
+        let mut raw_gauge = vec![];
+        let mut counter_rollup = Rollup::new(3600);
+        let mut gauge_rollup = Rollup::new(3600);
+        // a scrape every minute, half way through the minute, for 12 hours
+        for i in 0..12 * 60 {
+            let timestamp = 10 * hour + i * minute + minute / 2;
+            let counter = 100_000.0 + (i * 10) as f64;
+            let gauge = (i % 7) as f64;
+            raw_counter.push(Sample::new(timestamp, counter));
+            raw_gauge.push(Sample::new(timestamp, gauge));
+            for (rollup, metric_type, value) in [
+                (&mut counter_rollup, "counter", counter),
+                (&mut gauge_rollup, "gauge", gauge),
+            ] {
+                rollup.add(
+                    config::utils::json::json!({
+                        NAME_LABEL: "m",
+                        TYPE_LABEL: metric_type,
+                        HASH_LABEL: 1,
+                        TIMESTAMP_COL_NAME: timestamp,
+                        VALUE_LABEL: value,
+                    })
+                    .as_object()
+                    .unwrap()
+                    .clone(),
+                );
+            }
+        }
+        let rollup_samples = |rollup: Rollup| {
+            let mut samples = rollup
+                .finish("m_rollup_3600")
+                .into_iter()
+                .map(|v| {
+                    Sample::new(
+                        v[TIMESTAMP_COL_NAME].as_i64().unwrap(),
+                        v[VALUE_LABEL].as_f64().unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            samples.sort_by_key(|s| s.timestamp);
+            samples
+        };
+        let rollup_counter = rollup_samples(counter_rollup);
+        let rollup_gauge = rollup_samples(gauge_rollup);
+
+        type RangeFunc = fn(Value) -> datafusion::error::Result<Value>;
+        let cases: [(&str, RangeFunc, &[Sample], &[Sample], i64); 4] = [
+            ("rate", functions::rate, &raw_counter, &rollup_counter, 2),
+            ("increase", functions::increase, &raw_counter, &rollup_counter, 3),
+            (
+                "last_over_time",
+                functions::last_over_time,
+                &raw_counter,
+                &rollup_counter,
+                1,
+            ),
+            (
+                "avg_over_time",
+                functions::avg_over_time,
+                &raw_gauge,
+                &rollup_gauge,
+                2,
+            ),
+        ];
+        for (name, func, raw, rollup, buckets) in cases {
+            let range = buckets * hour;
+            // evaluation times on bucket boundaries
+            for eval_ts in (10 * hour + range..=22 * hour).step_by(hour as usize) {
+                let expected = eval_range_func(func, raw, eval_ts, range).unwrap();
+                let got = eval_range_func(func, rollup, eval_ts, range).unwrap();
+                assert!(
+                    (expected - got).abs() < 1e-9,
+                    "{name} at {eval_ts}: raw {expected}, rollup {got}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_restore_metric_names() {
+        let names = HashMap::from([("cpu_rollup_3600".to_string(), "cpu".to_string())]);
+        let mut value = Value::Matrix(vec![RangeValue {
+            labels: vec![
+                Arc::new(Label::new(NAME_LABEL, "cpu_rollup_3600")),
+                Arc::new(Label::new("job", "a")),
+            ],
+            samples: vec![Sample {
+                timestamp: 0,
+                value: 1.0,
+            }],
+            exemplars: None,
+            time_window: None,
+        }]);
+        restore_metric_names(&mut value, &names);
+        let Value::Matrix(values) = value else {
+            unreachable!()
+        };
+        assert_eq!(values[0].labels[0].value, "cpu");
+        assert_eq!(values[0].labels[1].value, "a");
+    }
+}
@@ -25,7 +25,10 @@ use config::{
         search::ScanStats,
         stream::{FileKey, PartitionTimeLevel, StreamType},
     },
-    utils::time::{now_micros, second_micros},
+    utils::{
+        json,
+        time::{now_micros, second_micros},
+    },
 };
 use datafusion::{arrow::datatypes::Schema, error::DataFusionError, prelude::SessionContext};
 use infra::{cache::tmpfs, errors::Result};
@@ -184,11 +187,34 @@ pub async fn search(
     Ok(resp)
 }
 
+/// Clears the session file_list and tmpfs of a trace when dropped, so the
+/// session is released on both success and error paths.
+struct SessionCleanup {
+    trace_id: String,
+}
+
+impl Drop for SessionCleanup {
+    fn drop(&mut self) {
+        // clear session
+        search::datafusion::storage::file_list::clear(&self.trace_id);
+        // clear tmpfs
+        if let Err(e) = tmpfs::delete(&self.trace_id, true) {
+            log::error!(
+                "[trace_id {}] promql->search->grpc: clear tmpfs error: {e}",
+                self.trace_id
+            );
+        }
+    }
+}
+
 #[tracing::instrument(name = "promql:search:grpc:search_inner", skip_all, fields(org_id = req.org_id))]
 pub async fn search_inner(
     req: &cluster_rpc::MetricsQueryRequest,
 ) -> Result<(Value, String, ScanStats)> {
     let trace_id = req.job.as_ref().unwrap().trace_id.to_string();
+    let _cleanup = SessionCleanup {
+        trace_id: trace_id.clone(),
+    };
 
     let org_id = &req.org_id;
     let query = req.query.as_ref().unwrap();
@@ -221,6 +247,14 @@ pub async fn search_inner(
         query.query_exemplars,
         timeout,
     );
+    if !req.field_masks.is_empty() {
+        ctx.field_masks = json::from_str(&req.field_masks)
+            .map_err(|e| DataFusionError::Execution(format!("invalid field masks: {e}")))?;
+    }
+    if !req.row_filters.is_empty() {
+        ctx.row_filters = json::from_str(&req.row_filters)
+            .map_err(|e| DataFusionError::Execution(format!("invalid row filters: {e}")))?;
+    }
 
     let (value, result_type, mut scan_stats) = if query.query_exemplars {
         ctx.query_exemplars(&trace_id, eval_stmt).await?
@@ -232,11 +266,6 @@ pub async fn search_inner(
         None => value.get_type().to_string(),
     };
 
-    // clear session
-    search::datafusion::storage::file_list::clear(&trace_id);
-    // clear tmpfs
-    tmpfs::delete(&trace_id, true).unwrap();
-
     scan_stats.format_to_mb();
     Ok((value, result_type, scan_stats))
 }
@@ -486,4 +515,29 @@ mod tests {
         assert!(resp.is_ok());
         assert_eq!(resp.unwrap(), expected);
     }
+
+    #[tokio::test]
+    async fn test_search_inner_clears_session_on_error() {
+        let trace_id = "test_search_inner_clears_session_on_error";
+        let session_id = format!("{trace_id}-storage-default");
+        search::datafusion::storage::file_list::set(&session_id, "default", &[FileKey::default()])
+            .await;
+        let key = format!("{session_id}/schema=default");
+        assert!(search::datafusion::storage::file_list::get(&key).is_ok());
+
+        let req = cluster_rpc::MetricsQueryRequest {
+            job: Some(cluster_rpc::Job {
+                trace_id: trace_id.to_string(),
+                ..Default::default()
+            }),
+            org_id: "default".to_string(),
+            query: Some(cluster_rpc::MetricsQueryStmt {
+                query: "sum(rate(".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(search_inner(&req).await.is_err());
+        assert!(search::datafusion::storage::file_list::get(&key).is_err());
+    }
 }
@@ -92,10 +92,10 @@ pub(crate) async fn create_context(
         unwrap_partition_time_level(stream_settings.partition_time_level, stream_type);
 
     // rewrite partition filters
-    let partition_keys: HashMap<&String, &StreamPartition> = stream_settings
+    let partition_keys: HashMap<String, &StreamPartition> = stream_settings
         .partition_keys
         .iter()
-        .map(|v| (&v.field, v))
+        .map(|v| (v.column(), v))
         .collect();
     for entry in filters.iter_mut() {
         if let Some(partition_key) = partition_keys.get(&entry.0) {
@@ -15,6 +15,7 @@
 
 use std::{
     cmp::{max, min},
+    collections::{BTreeMap, HashSet},
     sync::Arc,
 };
 
@@ -24,9 +25,10 @@ use config::{
         cluster::RoleGroup,
         search::ScanStats,
         self_reporting::usage::{RequestStats, UsageType},
-        stream::StreamType,
+        stream::{MaskStrategy, StreamType},
     },
     utils::{
+        json,
         rand::generate_random_string,
         time::{now_micros, second_micros},
     },
@@ -38,14 +40,16 @@ use proto::cluster_rpc;
 use tracing::{info_span, Instrument};
 
 use crate::{
-    common::infra::cluster,
+    common::{infra::cluster, utils::auth::is_root_user},
     service::{
         grpc::make_grpc_metrics_client,
         promql::{
-            adjust_start_end, micros, value::*, MetricsQueryRequest, DEFAULT_LOOKBACK,
-            DEFAULT_MAX_POINTS_PER_SERIES,
+            adjust_start_end, micros, name_visitor, rollup, value::*, MetricsQueryRequest,
+            DEFAULT_LOOKBACK, DEFAULT_MAX_POINTS_PER_SERIES,
+        },
+        search::{
+            masking::get_stream_field_masks, server_internal_error, sql::get_stream_row_filter,
         },
-        search::server_internal_error,
         self_reporting::report_request_usage_stats,
     },
 };
@@ -71,12 +75,133 @@ pub async fn search(
     user_email: &str,
     timeout: i64,
 ) -> Result<Value> {
+    let _gate = crate::service::search::enter_search_gate(trace_id)?;
     let mut req: cluster_rpc::MetricsQueryRequest = req.to_owned().into();
     req.org_id = org_id.to_string();
     req.timeout = timeout;
+    // read wide range queries from the metric rollup streams
+    let stmt = req.query.as_ref().unwrap();
+    let Some(rollup) =
+        rollup::use_rollup_streams(org_id, &stmt.query, stmt.start, stmt.end, stmt.step).await
+    else {
+        return search_with_names(trace_id, org_id, req, user_email, &HashMap::new()).await;
+    };
+    log::info!(
+        "[trace_id {trace_id}] promql->search->rollup: query rewritten to {} up to {}",
+        rollup.query,
+        rollup.end
+    );
+    let (step, end) = (stmt.step, stmt.end);
+    let mut rollup_req = req.clone();
+    let stmt = rollup_req.query.as_mut().unwrap();
+    stmt.query = rollup.query;
+    stmt.end = rollup.end;
+    let mut value =
+        search_with_names(trace_id, org_id, rollup_req, user_email, &rollup.names).await?;
+    rollup::restore_metric_names(&mut value, &rollup.names);
+    if rollup.end < end {
+        // the evaluation times reading samples not rolled up yet
+        req.query.as_mut().unwrap().start = rollup.end + step;
+        let recent = search_with_names(trace_id, org_id, req, user_email, &HashMap::new()).await?;
+        value = rollup::merge_range_values(value, recent);
+    }
+    Ok(value)
+}
+
+/// Searches the cluster with the field masks and row filters of the user,
+/// `rollup_names` giving the original metric of the rollup streams read by the
+/// query.
+async fn search_with_names(
+    trace_id: &str,
+    org_id: &str,
+    mut req: cluster_rpc::MetricsQueryRequest,
+    user_email: &str,
+    rollup_names: &HashMap<String, String>,
+) -> Result<Value> {
+    let stmt = req.query.as_ref().unwrap();
+    // the cached results are shared by all the users, so masked or filtered
+    // queries skip it
+    let metric_names = query_metric_names(&stmt.query);
+    let field_masks = get_field_masks(org_id, user_email, &metric_names, rollup_names).await;
+    if !field_masks.is_empty() {
+        req.field_masks = json::to_string(&field_masks)
+            .map_err(|e| Error::ErrorCode(ErrorCodes::ServerInternalError(e.to_string())))?;
+        req.no_cache = true;
+    }
+    let row_filters = get_row_filters(org_id, user_email, &metric_names, rollup_names).await;
+    if !row_filters.is_empty() {
+        req.row_filters = json::to_string(&row_filters)
+            .map_err(|e| Error::ErrorCode(ErrorCodes::ServerInternalError(e.to_string())))?;
+        req.no_cache = true;
+    }
     search_in_cluster(trace_id, req, user_email).await
 }
 
+fn query_metric_names(query: &str) -> HashSet<String> {
+    // an invalid query fails on the queriers
+    let Ok(ast) = promql_parser::parser::parse(query) else {
+        return HashSet::new();
+    };
+    let mut visitor = name_visitor::MetricNameVisitor::default();
+    promql_parser::util::walk_expr(&mut visitor, &ast).unwrap();
+    visitor.name
+}
+
+/// The labels of each metric of the query the user may not see in clear,
+/// keyed by the metric name in the query. The metrics read from rollup
+/// streams are masked like their original metric. Internal queries without
+/// a user, like alerts, are not masked.
+async fn get_field_masks(
+    org_id: &str,
+    user_email: &str,
+    metric_names: &HashSet<String>,
+    rollup_names: &HashMap<String, String>,
+) -> HashMap<String, BTreeMap<String, MaskStrategy>> {
+    let mut field_masks = HashMap::new();
+    if user_email.is_empty() {
+        return field_masks;
+    }
+    for name in metric_names {
+        let stream_name = rollup_names.get(name).unwrap_or(name);
+        let masks =
+            get_stream_field_masks(org_id, user_email, StreamType::Metrics, stream_name).await;
+        if !masks.is_empty() {
+            field_masks.insert(name.to_string(), masks);
+        }
+    }
+    field_masks
+}
+
+/// The row filter of the user's roles for each metric of the query, keyed
+/// like [get_field_masks]. Root users and internal queries are not filtered.
+async fn get_row_filters(
+    org_id: &str,
+    user_email: &str,
+    metric_names: &HashSet<String>,
+    rollup_names: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    let mut row_filters = HashMap::new();
+    if user_email.is_empty() || is_root_user(user_email) {
+        return row_filters;
+    }
+    let mut roles = None;
+    for name in metric_names {
+        let stream_name = rollup_names.get(name).unwrap_or(name);
+        if let Some(filter) = get_stream_row_filter(
+            org_id,
+            user_email,
+            StreamType::Metrics,
+            stream_name,
+            &mut roles,
+        )
+        .await
+        {
+            row_filters.insert(name.to_string(), filter);
+        }
+    }
+    row_filters
+}
+
 #[tracing::instrument(name = "promql:search:cluster", skip_all, fields(org_id = req.org_id))]
 async fn search_in_cluster(
     trace_id: &str,
@@ -13,16 +13,19 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 use config::{
-    meta::promql::{BUCKET_LABEL, HASH_LABEL, VALUE_LABEL},
+    meta::{
+        promql::{BUCKET_LABEL, HASH_LABEL, NAME_LABEL, VALUE_LABEL},
+        stream::MaskStrategy,
+    },
     TIMESTAMP_COL_NAME,
 };
 use datafusion::{
     arrow::datatypes::Schema,
     error::Result,
-    prelude::{col, lit, DataFrame},
+    prelude::{col, lit, DataFrame, SessionContext},
 };
 use promql_parser::label::{MatchOp, Matchers};
 
@@ -58,6 +61,53 @@ pub fn apply_matchers(df: DataFrame, schema: &Schema, matchers: &Matchers) -> Re
     Ok(df)
 }
 
+/// Keeps the samples matching the row filter of the user's roles.
+pub fn apply_row_filter(
+    ctx: &SessionContext,
+    df: DataFrame,
+    row_filter: Option<&str>,
+) -> Result<DataFrame> {
+    let Some(row_filter) = row_filter else {
+        return Ok(df);
+    };
+    let filter = ctx.state().create_logical_expr(row_filter, df.schema())?;
+    df.filter(filter)
+}
+
+/// Reads the masked labels through their mask expression, so the matchers
+/// and the returned series only see the masked values.
+pub fn apply_field_masks(
+    ctx: &SessionContext,
+    df: DataFrame,
+    masks: &BTreeMap<String, MaskStrategy>,
+) -> Result<DataFrame> {
+    if masks.is_empty() {
+        return Ok(df);
+    }
+    let state = ctx.state();
+    let exprs = df
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| {
+            let name = field.name();
+            let strategy = masks.get(&name.to_lowercase()).filter(|_| {
+                name != TIMESTAMP_COL_NAME
+                    && name != HASH_LABEL
+                    && name != VALUE_LABEL
+                    && name != NAME_LABEL
+            });
+            match strategy {
+                Some(strategy) => Ok(state
+                    .create_logical_expr(&strategy.sql_expr(&format!("\"{name}\"")), df.schema())?
+                    .alias(name)),
+                None => Ok(col(name)),
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    df.select(exprs)
+}
+
 pub fn apply_label_selector(
     df: DataFrame,
     schema: &Schema,
@@ -504,6 +504,41 @@ pub fn get_schema_changes(schema: &SchemaCache, inferred_schema: &Schema) -> (bo
     (is_schema_changed, field_datatype_delta)
 }
 
+/// Removes the fields that aren't in the schema yet once the schema would
+/// grow past `max_fields`. New fields are accepted in the order they appear,
+/// `_timestamp` is always accepted. Returns the dropped field names.
+pub fn drop_fields_over_limit<'a>(
+    schema: &Schema,
+    records: impl Iterator<Item = &'a mut Map<String, Value>>,
+    max_fields: usize,
+) -> Vec<String> {
+    if max_fields == 0 {
+        return vec![];
+    }
+    let mut accepted: HashSet<String> = HashSet::new();
+    let mut dropped: Vec<String> = Vec::new();
+    for record in records {
+        let new_keys = record
+            .keys()
+            .filter(|k| schema.field_with_name(k).is_err() && !accepted.contains(*k))
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in new_keys {
+            if dropped.contains(&key) {
+                record.remove(&key);
+            } else if key == TIMESTAMP_COL_NAME
+                || schema.fields().len() + accepted.len() < max_fields
+            {
+                accepted.insert(key);
+            } else {
+                record.remove(&key);
+                dropped.push(key);
+            }
+        }
+    }
+    dropped
+}
+
 pub async fn stream_schema_exists(
     org_id: &str,
     stream_name: &str,
@@ -551,6 +586,42 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_drop_fields_over_limit() {
+        let schema = Schema::new(vec![
+            Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false),
+            Field::new("level", DataType::Utf8, true),
+        ]);
+        let mut records = (0..3)
+            .map(|i| {
+                let mut record = Map::new();
+                record.insert(TIMESTAMP_COL_NAME.to_string(), Value::from(i));
+                record.insert("level".to_string(), Value::from("info"));
+                record.insert("message".to_string(), Value::from("hello"));
+                record.insert(format!("field_{i}"), Value::from(i));
+                record
+            })
+            .collect::<Vec<_>>();
+
+        let dropped = drop_fields_over_limit(&schema, records.iter_mut(), 4);
+        // message and field_0 fit in the limit, later fields are dropped
+        assert_eq!(dropped, vec!["field_1".to_string(), "field_2".to_string()]);
+        for record in records.iter() {
+            assert!(record.contains_key(TIMESTAMP_COL_NAME));
+            assert!(record.contains_key("level"));
+            assert!(record.contains_key("message"));
+            assert!(!record.contains_key("field_1"));
+            assert!(!record.contains_key("field_2"));
+        }
+        assert!(records[0].contains_key("field_0"));
+
+        // no limit
+        let mut record = Map::new();
+        record.insert("field_3".to_string(), Value::from(3));
+        assert!(drop_fields_over_limit(&schema, std::iter::once(&mut record), 0).is_empty());
+        assert!(record.contains_key("field_3"));
+    }
+
     #[tokio::test]
     async fn test_check_for_schema() {
         let stream_name = "Sample";
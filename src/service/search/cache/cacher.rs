@@ -303,6 +303,7 @@ pub async fn check_cache(
                         end_time: cached_resp.response_end_time,
                         is_aggregate,
                         is_descending,
+                        created_at: 0,
                     }),
                     req.query.start_time,
                     req.query.end_time,
@@ -362,6 +363,11 @@ pub async fn get_cached_results(
     let query_key = file_path.replace('/', "_");
     let is_cached = r.get(&query_key).cloned();
     drop(r);
+    let is_cached = match is_cached {
+        Some(cache_metas) => Some(drop_expired(file_path, cache_metas).await),
+        None => None,
+    }
+    .filter(|cache_metas| !cache_metas.is_empty());
 
     if let Some(cache_metas) = is_cached {
         match
@@ -637,16 +643,69 @@ pub fn get_ts_col_order_by(
     }
 }
 
+/// Leaves out the cached results older than the result cache ttl of their
+/// stream and deletes them, so the query recomputes and caches them again.
+pub async fn drop_expired(
+    file_path: &str,
+    cache_metas: Vec<ResultCacheMeta>,
+) -> Vec<ResultCacheMeta> {
+    let ttl = result_cache_ttl(file_path).await;
+    let now = Utc::now().timestamp_micros();
+    let (expired, cache_metas): (Vec<_>, Vec<_>) = cache_metas
+        .into_iter()
+        .partition(|meta| meta.is_expired(ttl, now));
+    for meta in expired {
+        let file = format!("results/{}/{}", file_path, result_file_name(&meta));
+        if let Err(e) = disk::remove("", &file).await {
+            log::error!("Error deleting expired cache {file}: {:?}", e);
+        }
+        forget_cached_result(&file_path.replace('/', "_"), &meta).await;
+    }
+    cache_metas
+}
+
+/// The `result_cache_ttl` setting of the stream the results under
+/// `{org}/{type}/{stream}/...` were cached for, `ZO_RESULT_CACHE_TTL` when
+/// the stream doesn't set one.
+async fn result_cache_ttl(file_path: &str) -> i64 {
+    let stream_ttl = match file_path.split('/').collect::<Vec<_>>()[..] {
+        [org_id, stream_type, stream_name, ..] => {
+            infra::schema::get_settings(org_id, stream_name, StreamType::from(stream_type))
+                .await
+                .map(|settings| settings.result_cache_ttl)
+                .unwrap_or_default()
+        }
+        _ => 0,
+    };
+    if stream_ttl > 0 {
+        stream_ttl
+    } else {
+        get_config().common.result_cache_ttl
+    }
+}
+
+/// Deletes the cached results under `path`, only the ones overlapping
+/// `time_range` when set.
 #[tracing::instrument]
-pub async fn delete_cache(path: &str) -> std::io::Result<bool> {
+pub async fn delete_cache(path: &str, time_range: Option<(i64, i64)>) -> std::io::Result<bool> {
     let root_dir = disk::get_dir().await;
     let pattern = format!("{}/results/{}", root_dir, path);
     let prefix = format!("{}/", root_dir);
     let files = scan_files(&pattern, "json", None).unwrap_or_default();
-    let mut remove_files: Vec<String> = vec![];
+    let mut remove_files: Vec<(String, Option<ResultCacheMeta>)> = vec![];
     for file in files {
-        match disk::remove("", file.strip_prefix(&prefix).unwrap()).await {
-            Ok(_) => remove_files.push(file),
+        let file_key = file.strip_prefix(&prefix).unwrap();
+        let meta = parse_result_file_name(file_key);
+        if let Some((start_time, end_time)) = time_range {
+            if !meta
+                .as_ref()
+                .is_some_and(|meta| meta.overlaps(start_time, end_time))
+            {
+                continue;
+            }
+        }
+        match disk::remove("", file_key).await {
+            Ok(_) => remove_files.push((file_key.to_string(), meta)),
             Err(e) => {
                 log::error!("Error deleting cache: {:?}", e);
                 return Err(std::io::Error::new(
@@ -656,23 +715,63 @@ pub async fn delete_cache(path: &str) -> std::io::Result<bool> {
             }
         }
     }
-    for file in remove_files {
-        let columns = file
-            .strip_prefix(&prefix)
-            .unwrap()
-            .split('/')
-            .collect::<Vec<&str>>();
-
+    for (file_key, meta) in remove_files {
+        let columns = file_key.split('/').collect::<Vec<&str>>();
         let query_key = format!(
             "{}_{}_{}_{}",
             columns[1], columns[2], columns[3], columns[4]
         );
-        let mut r = QUERY_RESULT_CACHE.write().await;
-        r.remove(&query_key);
+        match meta {
+            Some(meta) => forget_cached_result(&query_key, &meta).await,
+            None => {
+                QUERY_RESULT_CACHE.write().await.remove(&query_key);
+            }
+        }
     }
     Ok(true)
 }
 
+async fn forget_cached_result(query_key: &str, meta: &ResultCacheMeta) {
+    let mut w = QUERY_RESULT_CACHE.write().await;
+    if let Some(metas) = w.get_mut(query_key) {
+        metas.retain(|m| result_file_name(m) != result_file_name(meta));
+        if metas.is_empty() {
+            w.remove(query_key);
+        }
+    }
+}
+
+fn result_file_name(meta: &ResultCacheMeta) -> String {
+    format!(
+        "{}_{}_{}_{}.json",
+        meta.start_time,
+        meta.end_time,
+        if meta.is_aggregate { 1 } else { 0 },
+        if meta.is_descending { 1 } else { 0 }
+    )
+}
+
+/// Time range and kind of the results cached in a file named
+/// `results/{org}/{type}/{stream}/{hash}/{start}_{end}_{aggregate}_{descending}.json`
+fn parse_result_file_name(file_key: &str) -> Option<ResultCacheMeta> {
+    let columns = file_key.split('/').collect::<Vec<&str>>();
+    if columns.len() < 6 {
+        return None;
+    }
+    let name = columns[5].strip_suffix(".json").unwrap_or(columns[5]);
+    let meta = name.split('_').collect::<Vec<&str>>();
+    if meta.len() < 4 {
+        return None;
+    }
+    Some(ResultCacheMeta {
+        start_time: meta[0].parse().ok()?,
+        end_time: meta[1].parse().ok()?,
+        is_aggregate: meta[2] == "1",
+        is_descending: meta[3] == "1",
+        created_at: 0,
+    })
+}
+
 fn handle_histogram(origin_sql: &mut String, q_time_range: Option<(i64, i64)>) {
     let caps = RE_HISTOGRAM.captures(origin_sql.as_str()).unwrap();
     let attrs = caps
@@ -764,3 +863,122 @@ fn calculate_deltas_multi(
 
     (deltas, None, cache_duration)
 }
+
+#[cfg(test)]
+mod tests {
+    use config::meta::stream::StreamSettings;
+
+    use super::*;
+
+    async fn cache_response(file_path: &str, start_time: i64, end_time: i64, created_at: i64) {
+        let mut resp = Response::new(0, 10);
+        for ts in [start_time, (start_time + end_time) / 2, end_time] {
+            resp.add_hit(&json::json!({ TIMESTAMP_COL_NAME: ts }));
+        }
+        let meta = ResultCacheMeta {
+            start_time,
+            end_time,
+            is_aggregate: false,
+            is_descending: false,
+            created_at,
+        };
+        cache_results_to_disk(
+            "test",
+            file_path,
+            &result_file_name(&meta),
+            json::to_string(&resp).unwrap(),
+        )
+        .await
+        .unwrap();
+        QUERY_RESULT_CACHE
+            .write()
+            .await
+            .entry(file_path.replace('/', "_"))
+            .or_default()
+            .push(meta);
+    }
+
+    fn cache_req(q_start_time: i64, q_end_time: i64) -> CacheQueryRequest {
+        CacheQueryRequest {
+            q_start_time,
+            q_end_time,
+            is_aggregate: false,
+            ts_column: TIMESTAMP_COL_NAME.to_string(),
+            discard_interval: 0,
+            is_descending: false,
+        }
+    }
+
+    #[test]
+    fn test_result_cache_ttl() {
+        let meta = ResultCacheMeta {
+            start_time: 0,
+            end_time: 10,
+            is_aggregate: false,
+            is_descending: false,
+            created_at: 1_000_000,
+        };
+        assert!(!meta.is_expired(0, i64::MAX));
+        assert!(!meta.is_expired(10, 11_000_000));
+        assert!(meta.is_expired(10, 11_000_001));
+    }
+
+    #[tokio::test]
+    async fn test_delete_cache_time_range() {
+        let file_path = "cache_org/logs/cache_stream/query_hash";
+        let now = Utc::now().timestamp_micros();
+        cache_response(file_path, 1000, 2000, now).await;
+        cache_response(file_path, 5000, 6000, now).await;
+        assert!(get_cached_results(file_path, "test", cache_req(1000, 2000))
+            .await
+            .is_some());
+
+        // the invalidated range is recomputed, the rest stays cached
+        delete_cache("cache_org/logs/cache_stream", Some((1500, 1800)))
+            .await
+            .unwrap();
+        assert!(get_cached_results(file_path, "test", cache_req(1000, 2000))
+            .await
+            .is_none());
+        assert!(get_cached_results(file_path, "test", cache_req(5000, 6000))
+            .await
+            .is_some());
+
+        delete_cache("cache_org/logs/cache_stream", None)
+            .await
+            .unwrap();
+        assert!(get_cached_results(file_path, "test", cache_req(5000, 6000))
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stream_result_cache_ttl() {
+        let mut settings = infra::schema::STREAM_SETTINGS.write().await;
+        settings.insert(
+            "cache_ttl_org/logs/short_ttl".to_string(),
+            StreamSettings {
+                result_cache_ttl: 1,
+                ..Default::default()
+            },
+        );
+        settings.insert(
+            "cache_ttl_org/logs/global_ttl".to_string(),
+            StreamSettings::default(),
+        );
+        drop(settings);
+        let old = Utc::now().timestamp_micros() - 2_000_000;
+        let short = "cache_ttl_org/logs/short_ttl/query_hash";
+        let global = "cache_ttl_org/logs/global_ttl/query_hash";
+        cache_response(short, 1000, 2000, old).await;
+        cache_response(global, 1000, 2000, old).await;
+
+        // the stream ttl expires its results, the others keep the global ttl
+        assert!(get_cached_results(short, "test", cache_req(1000, 2000))
+            .await
+            .is_none());
+        assert!(get_cached_results(global, "test", cache_req(1000, 2000))
+            .await
+            .is_some());
+    }
+}
@@ -33,7 +33,7 @@ use infra::{
     errors::Error,
 };
 use proto::cluster_rpc::SearchQuery;
-use result_utils::get_ts_value;
+use result_utils::{calc_result_cache_ratio, get_ts_value};
 use tracing::Instrument;
 
 use crate::{
@@ -42,7 +42,12 @@ use crate::{
         utils::{functions, http::get_work_group},
     },
     service::{
-        search::{self as SearchService, cache::cacher::check_cache},
+        search::{
+            self as SearchService,
+            cache::cacher::check_cache,
+            masking::{field_masks_cache_key, get_field_masks},
+            sql::row_filters_cache_key,
+        },
         self_reporting::{http_report_metrics, report_request_usage_stats},
     },
 };
@@ -63,8 +68,9 @@ pub async fn search(
     let start = std::time::Instant::now();
     let started_at = Utc::now().timestamp_micros();
     let cfg = get_config();
-    // result cache can be enable only when its from the start
-    let use_cache = if in_req.query.from == 0 {
+    // result cache can be enable only when its from the start, and debug
+    // searches of explicit segments never use it
+    let use_cache = if in_req.query.from == 0 && in_req.query.segment_ids.is_none() {
         in_req.use_cache.unwrap_or(false)
     } else {
         false
@@ -102,12 +108,24 @@ pub async fn search(
     if let Some(action_id) = action {
         hash_body.push(action_id.to_string());
     }
+    if let Some(params) = &req.query.params {
+        hash_body.push(json::to_string(params).unwrap_or_default());
+    }
     if !req.regions.is_empty() {
         hash_body.extend(req.regions.clone());
     }
     if !req.clusters.is_empty() {
         hash_body.extend(req.clusters.clone());
     }
+    if let Some(row_filters) =
+        row_filters_cache_key(org_id, user_id.as_deref(), stream_type, &origin_sql).await?
+    {
+        hash_body.push(row_filters);
+    }
+    let masks = get_field_masks(org_id, user_id.as_deref(), stream_type, &origin_sql).await?;
+    if let Some(masks) = field_masks_cache_key(&masks) {
+        hash_body.push(masks);
+    }
     let mut h = config::utils::hash::gxhash::new();
     let hashed_query = h.sum64(&hash_body.join(","));
 
@@ -209,13 +227,15 @@ pub async fn search(
 
         // get a local search queue lock
         #[cfg(not(feature = "enterprise"))]
-        let locker = SearchService::QUEUE_LOCKER.clone();
-        #[cfg(not(feature = "enterprise"))]
-        let locker = locker.lock().await;
-        #[cfg(not(feature = "enterprise"))]
-        if !cfg.common.feature_query_queue_enabled {
-            drop(locker);
-        }
+        let _locker = match SearchService::acquire_queue_permit(trace_id).await {
+            Ok(permit) => permit,
+            Err(e) => {
+                metrics::QUERY_PENDING_NUMS
+                    .with_label_values(&[org_id])
+                    .dec();
+                return Err(e);
+            }
+        };
         #[cfg(not(feature = "enterprise"))]
         let took_wait = start.elapsed().as_millis() as usize;
         #[cfg(feature = "enterprise")]
@@ -468,7 +488,7 @@ pub fn merge_response(
     cache_response.scan_size = 0;
 
     let mut files_cache_ratio = 0;
-    let mut result_cache_len = 0;
+    let mut search_hits_len = 0;
 
     let mut res_took = ResponseTook::default();
 
@@ -479,7 +499,7 @@ pub fn merge_response(
         files_cache_ratio += res.cached_ratio;
         cache_response.histogram_interval = res.histogram_interval;
 
-        result_cache_len += res.total;
+        search_hits_len += res.hits.len();
 
         if res.hits.is_empty() {
             continue;
@@ -515,15 +535,13 @@ pub fn merge_response(
     }
     cache_response.size = cache_response.hits.len() as i64;
     log::info!(
-        "[trace_id {trace_id}] cache_response.hits.len: {}, Result cache len: {}",
+        "[trace_id {trace_id}] cache_response.hits.len: {}, search hits len: {}",
         cache_hits_len,
-        result_cache_len
+        search_hits_len
     );
     cache_response.took_detail = Some(res_took);
     cache_response.order_by = search_response.first().and_then(|res| res.order_by);
-    cache_response.result_cache_ratio = (((cache_hits_len as f64) * 100_f64)
-        / ((result_cache_len + cache_hits_len) as f64))
-        as usize;
+    cache_response.result_cache_ratio = calc_result_cache_ratio(cache_hits_len, search_hits_len);
     if !fn_error.is_empty() {
         cache_response.function_error = fn_error;
     }
@@ -634,6 +652,7 @@ pub async fn _write_results(
                         end_time: cache_end_time,
                         is_aggregate,
                         is_descending,
+                        created_at: Utc::now().timestamp_micros(),
                     });
                 drop(w);
             }
@@ -790,6 +809,7 @@ pub async fn write_results_v2(
                         end_time: cache_end_time,
                         is_aggregate,
                         is_descending,
+                        created_at: Utc::now().timestamp_micros(),
                     });
                 drop(w);
             }
@@ -805,6 +825,7 @@ pub async fn check_cache_v2(
     trace_id: &str,
     org_id: &str,
     stream_type: StreamType,
+    user_id: Option<&str>,
     in_req: &search::Request,
     use_cache: bool,
 ) -> Result<MultiCachedQueryResponse, Error> {
@@ -838,6 +859,15 @@ pub async fn check_cache_v2(
     if !req.clusters.is_empty() {
         hash_body.extend(req.clusters.clone());
     }
+    if let Some(row_filters) =
+        row_filters_cache_key(org_id, user_id, stream_type, &origin_sql).await?
+    {
+        hash_body.push(row_filters);
+    }
+    let masks = get_field_masks(org_id, user_id, stream_type, &origin_sql).await?;
+    if let Some(masks) = field_masks_cache_key(&masks) {
+        hash_body.push(masks);
+    }
     let mut h = config::utils::hash::gxhash::new();
     let hashed_query = h.sum64(&hash_body.join(","));
 
@@ -911,3 +941,59 @@ fn convert_ts_value_to_datetime(ts_value: &serde_json::Value) -> Option<chrono::
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(timestamps: &[i64]) -> search::Response {
+        let mut resp = search::Response::new(0, 100);
+        for ts in timestamps {
+            resp.add_hit(&json::json!({ TIMESTAMP_COL_NAME: ts }));
+        }
+        resp.total = timestamps.len();
+        resp
+    }
+
+    #[test]
+    fn test_merge_response_result_cache_ratio() {
+        // uncached query
+        let resp = merge_response(
+            "test",
+            &mut vec![],
+            &mut vec![response(&[1, 2, 3, 4])],
+            TIMESTAMP_COL_NAME,
+            100,
+            false,
+            0,
+        );
+        assert_eq!(resp.hits.len(), 4);
+        assert_eq!(resp.result_cache_ratio, 0);
+
+        // cached query with a delta searched
+        let resp = merge_response(
+            "test",
+            &mut vec![response(&[1, 2, 3])],
+            &mut vec![response(&[4])],
+            TIMESTAMP_COL_NAME,
+            100,
+            false,
+            0,
+        );
+        assert_eq!(resp.hits.len(), 4);
+        assert_eq!(resp.result_cache_ratio, 75);
+
+        // fully cached query
+        let resp = merge_response(
+            "test",
+            &mut vec![response(&[1, 2, 3, 4])],
+            &mut vec![],
+            TIMESTAMP_COL_NAME,
+            100,
+            false,
+            0,
+        );
+        assert_eq!(resp.hits.len(), 4);
+        assert_eq!(resp.result_cache_ratio, 100);
+    }
+}
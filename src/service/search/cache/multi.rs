@@ -20,7 +20,10 @@ use chrono::Utc;
 use config::{get_config, meta::search::Response, utils::json};
 use infra::cache::{file_data::disk::QUERY_RESULT_CACHE, meta::ResultCacheMeta};
 
-use super::{cacher::get_results, sort_response};
+use super::{
+    cacher::{self, get_results},
+    sort_response,
+};
 use crate::{
     common::meta::search::{CacheQueryRequest, ResultCacheSelectionStrategy},
     service::search::cache::{
@@ -39,6 +42,11 @@ pub async fn get_cached_results(
     let query_key = file_path.replace('/', "_");
     let is_cached = r.get(&query_key).cloned();
     drop(r);
+    let is_cached = match is_cached {
+        Some(cache_metas) => Some(cacher::drop_expired(file_path, cache_metas).await),
+        None => None,
+    }
+    .filter(|cache_metas| !cache_metas.is_empty());
     if is_cached.is_none() {
         log::info!(
             "[CACHE RESULT {trace_id}] No cache found for query key: {}",
@@ -28,6 +28,16 @@ pub fn get_ts_value(ts_column: &str, record: &json::Value) -> i64 {
     }
 }
 
+/// Percentage of the hits of a response that came from the result cache
+/// instead of a search.
+pub fn calc_result_cache_ratio(cache_hits: usize, search_hits: usize) -> usize {
+    let total_hits = cache_hits + search_hits;
+    if total_hits == 0 {
+        return 0; // avoid division by zero
+    }
+    ((cache_hits as f64 / total_hits as f64) * 100.0).round() as usize
+}
+
 pub fn round_down_to_nearest_minute(microseconds: i64) -> i64 {
     let microseconds_per_second = 1_000_000;
     let seconds_per_minute = 60;
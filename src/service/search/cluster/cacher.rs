@@ -253,7 +253,12 @@ pub async fn get_cached_results(
     }
 }
 
-pub async fn delete_cached_results(path: String) -> bool {
+/// Deletes the cached results under `path` on all the queriers, only the
+/// ones overlapping `time_range` when set.
+pub async fn delete_cached_results(path: String, time_range: Option<(i64, i64)>) -> bool {
+    // the rpc sends no time range as an end_time of 0, read it the same way
+    // here so the local node deletes what the remote ones do
+    let time_range = time_range.filter(|(_, end_time)| *end_time > 0);
     let trace_id = path.clone();
     let mut delete_response = true;
     // get nodes from cluster
@@ -294,8 +299,11 @@ pub async fn delete_cached_results(path: String) -> bool {
         let local_path = path.clone();
         let task = tokio::task::spawn(
             async move {
+                let (start_time, end_time) = time_range.unwrap_or_default();
                 let req = DeleteResultCacheRequest {
-                   path: local_path.clone(),
+                    path: local_path.clone(),
+                    start_time,
+                    end_time,
                 };
 
                 let request = tonic::Request::new(req);
@@ -352,7 +360,7 @@ pub async fn delete_cached_results(path: String) -> bool {
         );
         tasks.push(task);
     }
-    match crate::service::search::cache::cacher::delete_cache(&path).await {
+    match crate::service::search::cache::cacher::delete_cache(&path, time_range).await {
         Ok(_) => {
             log::info!(
                 "[trace_id {trace_id}] delete_cached_results->grpc: local node delete success"
@@ -22,6 +22,7 @@ use config::{
     meta::{
         bitvec::BitVec,
         cluster::{IntoArcVec, Node, Role, RoleGroup},
+        inverted_index::InvertedIndexFormat,
         search::{ScanStats, SearchEventType},
         sql::TableReferenceExt,
         stream::{FileKey, QueryPartitionStrategy, StreamType},
@@ -100,13 +101,16 @@ pub async fn search(
     }
 
     // 1. get file id list
-    let file_id_list = get_file_id_lists(
+    let mut file_id_list = get_file_id_lists(
         &sql.org_id,
         sql.stream_type,
         &sql.stream_names,
         sql.time_range,
     )
     .await?;
+    // quick scan only searches the most recent files
+    let quick_scan_skipped =
+        query.quick_scan && keep_recent_files(&mut file_id_list, cfg.limit.quick_scan_max_files);
     let file_id_list_vec = file_id_list.values().flatten().collect::<Vec<_>>();
     let file_id_list_took = start.elapsed().as_millis() as usize;
     log::info!(
@@ -121,9 +125,19 @@ pub async fn search(
         ..Default::default()
     };
 
-    // 2. get inverted index file list
-    let (use_ttv_inverted_index, idx_file_list, idx_scan_size, idx_took) =
-        get_inverted_index_file_lists(trace_id, &req, &sql, &query).await?;
+    // 2. get inverted index file list, quick scan skips the inverted index and
+    // explicit segment ids replace it
+    let (use_ttv_inverted_index, idx_file_list, idx_scan_size, idx_took) = if query.quick_scan {
+        (false, vec![], 0, 0)
+    } else if !query.segment_ids.is_empty() {
+        log::info!(
+            "[trace_id {trace_id}] flight->search: use explicit segment_ids of {} files",
+            query.segment_ids.len()
+        );
+        (false, get_segment_ids_file_list(&query), 0, 0)
+    } else {
+        get_inverted_index_file_lists(trace_id, &req, &sql, &query).await?
+    };
     scan_stats.idx_scan_size = idx_scan_size as i64;
     req.set_use_inverted_index(use_ttv_inverted_index);
 
@@ -278,50 +292,29 @@ pub async fn search(
         .instrument(datafusion_span)
         .await
     });
-    tokio::pin!(query_task);
 
     // 8. execute physical plan
-    let task = tokio::select! {
-        ret = &mut query_task => {
-            match ret {
-                Ok(ret) => Ok(ret),
-                Err(err) => {
-                    log::error!("[trace_id {trace_id}] flight->search: datafusion execute error: {}", err);
-                    Err(DataFusionError::Execution(err.to_string()))
-                }
-            }
-        },
-        _ = tokio::time::sleep(tokio::time::Duration::from_secs(timeout)) => {
-            query_task.abort();
-            log::error!("[trace_id {trace_id}] flight->search: search timeout");
-            Err(DataFusionError::ResourcesExhausted("flight->search: search timeout".to_string()))
-        },
-        _ = async {
+    let task = wait_query_task(
+        trace_id,
+        tokio::time::Duration::from_secs(timeout),
+        query_task,
+        async {
             #[cfg(feature = "enterprise")]
             let _ = abort_receiver.await;
             #[cfg(not(feature = "enterprise"))]
             futures::future::pending::<()>().await;
-        } => {
-            query_task.abort();
-            log::info!("[trace_id {trace_id}] flight->search: search canceled");
-            Err(DataFusionError::ResourcesExhausted("flight->search: search canceled".to_string()))
-        }
-    };
+        },
+    )
+    .await;
 
     // release source
     drop(_defer);
 
     // 9. get data from datafusion
-    let (data, mut scan_stats, partial_err): (Vec<RecordBatch>, ScanStats, String) = match task {
-        Ok(Ok(data)) => Ok(data),
-        Ok(Err(err)) => Err(err),
-        Err(err) => match err {
-            DataFusionError::ResourcesExhausted(err) => Err(Error::ErrorCode(
-                ErrorCodes::SearchCancelQuery(err.to_string()),
-            )),
-            _ => Err(Error::Message(err.to_string())),
-        },
-    }?;
+    let (data, mut scan_stats, mut partial_err): (Vec<RecordBatch>, ScanStats, String) = task?;
+    if quick_scan_skipped && partial_err.is_empty() {
+        partial_err = quick_scan_partial_err(cfg.limit.quick_scan_max_files);
+    }
 
     log::info!("[trace_id {trace_id}] flight->search: search finished");
 
@@ -336,6 +329,42 @@ pub async fn search(
     ))
 }
 
+/// Waits for the datafusion task, aborting it when the search times out or is
+/// canceled. A timeout is reported as `ErrorCodes::SearchTimeout`.
+async fn wait_query_task<T>(
+    trace_id: &str,
+    timeout: tokio::time::Duration,
+    mut query_task: tokio::task::JoinHandle<Result<T>>,
+    canceled: impl std::future::Future<Output = ()>,
+) -> Result<T> {
+    tokio::select! {
+        ret = &mut query_task => {
+            match ret {
+                Ok(ret) => ret,
+                Err(err) => {
+                    log::error!("[trace_id {trace_id}] flight->search: datafusion execute error: {}", err);
+                    Err(Error::Message(DataFusionError::Execution(err.to_string()).to_string()))
+                }
+            }
+        },
+        _ = tokio::time::sleep(timeout) => {
+            query_task.abort();
+            log::error!("[trace_id {trace_id}] flight->search: search timeout");
+            Err(Error::ErrorCode(ErrorCodes::SearchTimeout(format!(
+                "[trace_id {trace_id}] flight->search: search timeout after {}s",
+                timeout.as_secs()
+            ))))
+        },
+        _ = canceled => {
+            query_task.abort();
+            log::info!("[trace_id {trace_id}] flight->search: search canceled");
+            Err(Error::ErrorCode(ErrorCodes::SearchCancelQuery(
+                "flight->search: search canceled".to_string(),
+            )))
+        }
+    }
+}
+
 #[tracing::instrument(name = "service:search:cluster:flight:run_datafusion", skip_all)]
 pub async fn run_datafusion(
     trace_id: String,
@@ -819,6 +848,35 @@ pub async fn get_file_id_lists(
     Ok(file_lists)
 }
 
+/// Keeps the `max_files` most recent files of each stream, returns whether
+/// any file was removed.
+fn keep_recent_files(
+    file_lists: &mut HashMap<TableReference, Vec<FileId>>,
+    max_files: usize,
+) -> bool {
+    let mut skipped = false;
+    for files in file_lists.values_mut() {
+        skipped |= keep_recent_stream_files(files, max_files);
+    }
+    skipped
+}
+
+/// Keeps the `max_files` most recent files of one stream, returns whether
+/// any file was removed.
+pub(crate) fn keep_recent_stream_files(files: &mut Vec<FileId>, max_files: usize) -> bool {
+    if files.len() <= max_files {
+        return false;
+    }
+    files.sort_unstable_by(|a, b| b.max_ts.cmp(&a.max_ts));
+    files.truncate(max_files);
+    true
+}
+
+/// The partial error reported when a quick scan skipped older files.
+pub(crate) fn quick_scan_partial_err(max_files: usize) -> String {
+    format!("quick scan only searched the {max_files} most recent files of each stream")
+}
+
 #[tracing::instrument(
     name = "service:search:cluster:flight:get_inverted_index_file_list",
     skip_all
@@ -831,10 +889,10 @@ async fn get_inverted_index_file_lists(
 ) -> Result<(bool, Vec<FileKey>, usize, usize)> {
     let cfg = get_config();
     #[allow(deprecated)]
-    let inverted_index_type = cfg.common.inverted_index_search_format.clone();
+    let inverted_index_type = InvertedIndexFormat::from(&cfg.common.inverted_index_search_format);
     let (use_inverted_index, index_terms) = super::super::is_use_inverted_index(sql);
-    let use_parquet_inverted_index = use_inverted_index && inverted_index_type == "parquet";
-    let use_ttv_inverted_index = use_inverted_index && inverted_index_type == "tantivy";
+    let use_parquet_inverted_index = use_inverted_index && inverted_index_type.use_parquet();
+    let use_ttv_inverted_index = use_inverted_index && inverted_index_type.use_tantivy();
     log::info!(
         "[trace_id {trace_id}] flight->search: use_inverted_index with parquet format {}",
         use_parquet_inverted_index
@@ -884,12 +942,32 @@ pub async fn get_inverted_index_file_list(
     let org_id = req.org_id.clone();
     let stream_type = req.stream_type;
 
+    // too many terms make the index lookup slower than scanning the files
+    if exceeds_index_terms_limit(
+        match_terms,
+        index_terms,
+        cfg.common.inverted_index_max_terms,
+    ) {
+        log::info!(
+            "[trace_id {}] flight->search: skip inverted index for stream {}/{}/{}, the query has more than {} terms",
+            req.trace_id,
+            org_id,
+            stream_type,
+            stream_name,
+            cfg.common.inverted_index_max_terms
+        );
+        return Ok((vec![], 0, 0));
+    }
+
+    // tokenize the same way the index of the stream was built
+    let stream_settings = infra::schema::get_settings(&org_id, stream_name, stream_type).await;
+    let split_chars = infra::schema::get_stream_setting_split_chars(&stream_settings);
+
     // Get all the unique terms which the user has searched.
     let terms = match_terms
         .iter()
         .filter_map(|t| {
-            #[allow(deprecated)]
-            let tokens = split_token(t, &cfg.common.inverted_index_split_chars);
+            let tokens = split_token(t, &split_chars);
             if tokens.is_empty() {
                 None
             } else {
@@ -1022,6 +1100,38 @@ pub async fn get_inverted_index_file_list(
     ))
 }
 
+/// Turns the segment ids given with the search request into the file list
+/// otherwise computed by the inverted index.
+fn get_segment_ids_file_list(query: &SearchQuery) -> Vec<FileKey> {
+    query
+        .segment_ids
+        .iter()
+        .map(|f| FileKey {
+            key: f.key.clone(),
+            segment_ids: f.segment_ids.as_ref().map(|v| BitVec::from_slice(v)),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Whether the query has more terms than the inverted index should search,
+/// `max_terms` 0 means no limit.
+fn exceeds_index_terms_limit(
+    match_terms: &[String],
+    index_terms: &[(String, Vec<String>)],
+    max_terms: usize,
+) -> bool {
+    if max_terms == 0 {
+        return false;
+    }
+    let terms_num = match_terms.len()
+        + index_terms
+            .iter()
+            .map(|(_, values)| values.len())
+            .sum::<usize>();
+    terms_num > max_terms
+}
+
 pub fn print_plan(physical_plan: &Arc<dyn ExecutionPlan>, stage: &str) {
     let plan = displayable(physical_plan.as_ref())
         .indent(false)
@@ -1031,3 +1141,77 @@ pub fn print_plan(physical_plan: &Arc<dyn ExecutionPlan>, stage: &str) {
     println!("+---------------------------+----------+");
     println!("{}", plan);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_query_task_timeout() {
+        let query_task = tokio::spawn(async {
+            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+            Ok::<_, Error>(())
+        });
+        let ret = wait_query_task(
+            "trace_id",
+            tokio::time::Duration::from_millis(10),
+            query_task,
+            futures::future::pending::<()>(),
+        )
+        .await;
+        assert!(matches!(
+            ret,
+            Err(Error::ErrorCode(ErrorCodes::SearchTimeout(_)))
+        ));
+
+        let query_task = tokio::spawn(async { Ok::<_, Error>(1) });
+        let ret = wait_query_task(
+            "trace_id",
+            tokio::time::Duration::from_secs(5),
+            query_task,
+            futures::future::pending::<()>(),
+        )
+        .await;
+        assert_eq!(ret.unwrap(), 1);
+    }
+
+    #[test]
+    fn test_keep_recent_files() {
+        let files = |n: i64| {
+            (0..n)
+                .map(|i| FileId {
+                    id: i,
+                    min_ts: i * 10,
+                    max_ts: i * 10 + 5,
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut file_lists = HashMap::new();
+        file_lists.insert(TableReference::from("big"), files(10));
+        file_lists.insert(TableReference::from("small"), files(2));
+
+        assert!(keep_recent_files(&mut file_lists, 3));
+        let big = &file_lists[&TableReference::from("big")];
+        assert_eq!(big.iter().map(|f| f.id).collect::<Vec<_>>(), vec![9, 8, 7]);
+        assert_eq!(file_lists[&TableReference::from("small")].len(), 2);
+
+        // nothing to skip, the search isn't partial
+        assert!(!keep_recent_files(&mut file_lists, 3));
+    }
+
+    #[test]
+    fn test_exceeds_index_terms_limit() {
+        let match_terms = (0..40).map(|i| format!("term{i}")).collect::<Vec<_>>();
+        let index_terms = vec![(
+            "service".to_string(),
+            (0..25).map(|i| format!("svc{i}")).collect::<Vec<_>>(),
+        )];
+        // 65 terms with a cap of 64, the files are kept without the index
+        assert!(exceeds_index_terms_limit(&match_terms, &index_terms, 64));
+        assert!(!exceeds_index_terms_limit(&match_terms, &index_terms, 65));
+        assert!(!exceeds_index_terms_limit(&match_terms[..10], &[], 64));
+        // no limit
+        assert!(!exceeds_index_terms_limit(&match_terms, &index_terms, 0));
+    }
+}
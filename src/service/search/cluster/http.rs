@@ -267,11 +267,13 @@ pub async fn search(
     );
     result.set_idx_scan_size(scan_stats.idx_scan_size as usize);
 
-    result.set_idx_took(if idx_took > 0 {
+    let idx_took = if idx_took > 0 {
         idx_took
     } else {
         scan_stats.idx_took as usize
-    });
+    };
+    result.set_idx_took(idx_took);
+    result.set_scan_stats(search::ResponseScanStats::new(&scan_stats, idx_took));
 
     if query_type == "table" {
         result.response_type = "table".to_string();
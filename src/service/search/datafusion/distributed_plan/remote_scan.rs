@@ -348,6 +348,10 @@ async fn get_remote_batch(
         scan_size = stats.original_size;
         scan_stats.lock().add(&stats);
     }
+    // the follower searched only part of the data
+    if let Some(err) = schema.metadata().get("partial_err") {
+        append_partial_err(&partial_err, err);
+    }
 
     Ok(Box::pin(FlightStream::new(
         trace_id,
@@ -386,12 +390,15 @@ fn get_empty_record_batch_stream(
 }
 
 fn process_partial_err(partial_err: Arc<Mutex<String>>, e: tonic::Status) {
+    append_partial_err(&partial_err, &e.to_string());
+}
+
+fn append_partial_err(partial_err: &Mutex<String>, err: &str) {
     let mut guard = partial_err.lock();
-    let partial_err = guard.clone();
-    if partial_err.is_empty() {
-        guard.push_str(e.to_string().as_str());
+    if guard.is_empty() {
+        guard.push_str(err);
     } else {
-        guard.push_str(format!(" \n {}", e).as_str());
+        guard.push_str(format!(" \n {}", err).as_str());
     }
 }
 
@@ -506,3 +513,21 @@ impl RecordBatchStream for FlightStream {
         Arc::clone(&self.schema)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_partial_err() {
+        let partial_err = Mutex::new(String::new());
+        append_partial_err(&partial_err, "quick scan skipped files");
+        assert_eq!(*partial_err.lock(), "quick scan skipped files");
+
+        append_partial_err(&partial_err, "node timeout");
+        assert_eq!(
+            *partial_err.lock(),
+            "quick scan skipped files \n node timeout"
+        );
+    }
+}
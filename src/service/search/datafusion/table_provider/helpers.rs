@@ -35,8 +35,12 @@
 use std::sync::Arc;
 
 use arrow_schema::{DataType, Schema, SchemaRef};
-use config::{INDEX_SEGMENT_LENGTH, PARQUET_MAX_ROW_GROUP_SIZE};
+use config::{
+    get_config, meta::inverted_index::InvertedIndexFormat, INDEX_SEGMENT_LENGTH,
+    PARQUET_MAX_ROW_GROUP_SIZE,
+};
 use datafusion::{
+    arrow::compute::CastOptions,
     common::{
         project_schema,
         stats::Precision,
@@ -171,11 +175,11 @@ pub fn split_files(
 }
 
 pub fn generate_access_plan(file: &PartitionedFile) -> Option<Arc<ParquetAccessPlan>> {
+    // with both formats, the storage search turns the segment ids of the
+    // parquet index into row ids and intersects them with the tantivy ones
     #[allow(deprecated)]
-    if config::get_config()
-        .common
-        .inverted_index_search_format
-        .eq("tantivy")
+    if InvertedIndexFormat::from(&config::get_config().common.inverted_index_search_format)
+        .use_tantivy()
     {
         return generate_access_plan_row_level(file);
     };
@@ -310,6 +314,7 @@ pub fn apply_projection(
         return Ok(memory_exec);
     }
     let projected_schema = project_schema(schema, projection)?;
+    let lenient = get_config().common.feature_query_lenient_schema_coercion;
     let mut exprs: Vec<(Arc<dyn PhysicalExpr>, String)> =
         Vec::with_capacity(projected_schema.fields().len());
     for (idx, field) in projected_schema.fields().iter().enumerate() {
@@ -318,7 +323,7 @@ pub fn apply_projection(
             &name, idx,
         ));
         if let Some(data_type) = diff_rules.get(&name) {
-            exprs.push((Arc::new(CastExpr::new(col, data_type.clone(), None)), name));
+            exprs.push((coerce_column(col, data_type, lenient), name));
         } else {
             exprs.push((col, name));
         }
@@ -326,6 +331,21 @@ pub fn apply_projection(
     Ok(Arc::new(ProjectionExec::try_new(exprs, memory_exec)?))
 }
 
+/// Casts a column of an older schema version to the latest type. In lenient
+/// mode values which can't be converted become null instead of failing the
+/// query.
+fn coerce_column(
+    col: Arc<dyn PhysicalExpr>,
+    data_type: &DataType,
+    lenient: bool,
+) -> Arc<dyn PhysicalExpr> {
+    let cast_options = lenient.then(|| CastOptions {
+        safe: true,
+        ..Default::default()
+    });
+    Arc::new(CastExpr::new(col, data_type.clone(), cast_options))
+}
+
 pub fn apply_filter(
     index_condition: Option<&IndexCondition>,
     schema: &Schema,
@@ -422,4 +442,44 @@ mod tests {
         // this helper function
         assert!(expr_applicable_for_cols(&[], &lit(true)));
     }
+
+    #[test]
+    fn test_coerce_column_between_schema_versions() {
+        use arrow_schema::Field;
+        use datafusion::arrow::array::{Array, Int64Array, RecordBatch, StringArray};
+
+        // the column was written as a string in the older schema version
+        let old_schema = Arc::new(Schema::new(vec![Field::new("code", DataType::Utf8, true)]));
+        let latest_schema = Schema::new(vec![Field::new("code", DataType::Int64, true)]);
+        let latest_schema_map = latest_schema
+            .fields()
+            .iter()
+            .map(|f| (f.name(), f))
+            .collect::<HashMap<_, _>>();
+        let rules =
+            crate::service::search::generate_search_schema_diff(&old_schema, &latest_schema_map);
+        let data_type = rules.get("code").unwrap();
+
+        let batch = RecordBatch::try_new(
+            old_schema,
+            vec![Arc::new(StringArray::from(vec!["200", "abc"]))],
+        )
+        .unwrap();
+        let column = Arc::new(datafusion::physical_plan::expressions::Column::new(
+            "code", 0,
+        ));
+
+        let strict = coerce_column(column.clone(), data_type, false);
+        assert!(strict.evaluate(&batch).is_err());
+
+        let lenient = coerce_column(column, data_type, true);
+        let values = lenient
+            .evaluate(&batch)
+            .unwrap()
+            .into_array(batch.num_rows())
+            .unwrap();
+        let values = values.as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(values.value(0), 200);
+        assert!(values.is_null(1));
+    }
 }
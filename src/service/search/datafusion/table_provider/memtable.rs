@@ -166,3 +166,64 @@ fn apply_sort(
         exec_plan
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Array, Int64Array, StringArray};
+    use arrow_schema::{Field, Schema};
+    use datafusion::{physical_plan::collect, prelude::SessionContext};
+
+    use super::*;
+
+    async fn scan_code_column(values: Vec<&str>) -> Result<Vec<RecordBatch>> {
+        // the column was written as a string in the older schema version
+        let old_schema = Arc::new(Schema::new(vec![Field::new("code", DataType::Utf8, true)]));
+        let latest_schema = Schema::new(vec![Field::new("code", DataType::Int64, true)]);
+        let latest_schema_map = latest_schema
+            .fields()
+            .iter()
+            .map(|f| (f.name(), f))
+            .collect::<HashMap<_, _>>();
+        let rules =
+            crate::service::search::generate_search_schema_diff(&old_schema, &latest_schema_map);
+        let batch = RecordBatch::try_new(
+            old_schema.clone(),
+            vec![Arc::new(StringArray::from(values))],
+        )?;
+
+        let ctx = SessionContext::new();
+        let table =
+            NewMemTable::try_new(old_schema, vec![vec![batch]], rules, false, None, vec![])?;
+        let plan = table.scan(&ctx.state(), None, &[], None).await?;
+        collect(plan, ctx.task_ctx()).await
+    }
+
+    #[tokio::test]
+    async fn test_scan_coerces_older_schema_version() {
+        let batches = scan_code_column(vec!["200", "404"]).await.unwrap();
+        let values = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(values.values(), &[200, 404]);
+
+        // a value which can't be converted only fails the query in strict mode
+        let ret = scan_code_column(vec!["200", "abc"]).await;
+        if config::get_config()
+            .common
+            .feature_query_lenient_schema_coercion
+        {
+            let batches = ret.unwrap();
+            let values = batches[0]
+                .column(0)
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .unwrap();
+            assert_eq!(values.value(0), 200);
+            assert!(values.is_null(1));
+        } else {
+            assert!(ret.is_err());
+        }
+    }
+}
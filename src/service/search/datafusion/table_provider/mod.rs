@@ -66,7 +66,7 @@ use crate::service::search::index::IndexCondition;
 
 pub mod catalog;
 pub mod empty_table;
-mod helpers;
+pub(crate) mod helpers;
 pub mod memtable;
 pub mod uniontable;
 
@@ -21,17 +21,19 @@ use config::{
     get_config, is_local_disk_storage,
     meta::{
         bitvec::BitVec,
-        inverted_index::{InvertedIndexOptimizeMode, InvertedIndexTantivyMode},
+        inverted_index::{
+            InvertedIndexFormat, InvertedIndexOptimizeMode, InvertedIndexTantivyMode,
+        },
         search::{ScanStats, StorageType},
         stream::FileKey,
     },
     utils::{
         file::is_exists,
         inverted_index::convert_parquet_idx_file_name_to_tantivy_file,
-        tantivy::tokenizer::{o2_tokenizer_build, O2_TOKENIZER},
+        tantivy::tokenizer::{o2_tokenizer_build_with, O2_TOKENIZER},
         time::BASE_TIME,
     },
-    FILE_EXT_TANTIVY, FILE_EXT_TANTIVY_FOLDER, INDEX_FIELD_NAME_FOR_ALL,
+    FILE_EXT_TANTIVY, FILE_EXT_TANTIVY_FOLDER, INDEX_FIELD_NAME_FOR_ALL, INDEX_SEGMENT_LENGTH,
 };
 use datafusion::execution::cache::cache_manager::FileStatisticsCache;
 use futures::future::try_join_all;
@@ -124,8 +126,19 @@ pub async fn search(
     // check inverted index
     let cfg = get_config();
     #[allow(deprecated)]
-    let inverted_index_type = cfg.common.inverted_index_search_format.clone();
-    let use_inverted_index = query.use_inverted_index && inverted_index_type == "tantivy";
+    let inverted_index_type = InvertedIndexFormat::from(&cfg.common.inverted_index_search_format);
+    let use_inverted_index = query.use_inverted_index && inverted_index_type.use_tantivy();
+    // the access plan reads row ids when tantivy is searched, turn the
+    // segment ids selected by the parquet index or given with the request
+    // into row ids
+    if inverted_index_type.use_tantivy() {
+        for file in files.iter_mut() {
+            if let Some(segment_ids) = file.segment_ids.take() {
+                let row_ids = segment_ids_to_row_ids(&segment_ids, file.meta.records as usize);
+                file.with_segment_ids(row_ids);
+            }
+        }
+    }
     if use_inverted_index {
         log::info!(
             "[trace_id {}] flight->search: use_inverted_index with tantivy format {}",
@@ -522,6 +535,11 @@ pub async fn filter_file_list_by_tantivy_index(
         cfg.limit.cpu_num
     };
 
+    // tokenize the same way the index of the stream was built
+    let stream_settings =
+        infra::schema::get_settings(&query.org_id, &query.stream_name, query.stream_type).await;
+    let split_chars = infra::schema::get_stream_setting_split_chars(&stream_settings);
+
     let search_start = std::time::Instant::now();
     let mut is_add_filter_back = file_list_map.len() != index_file_names.len();
     let time_range = query.time_range.unwrap_or((0, 0));
@@ -590,6 +608,7 @@ pub async fn filter_file_list_by_tantivy_index(
             // secondary index search queries are executed
             let index_condition_clone = index_condition.clone();
             let idx_optimize_rule_clone = idx_optimize_rule.clone();
+            let split_chars = split_chars.clone();
             let permit = semaphore.clone().acquire_owned().await.unwrap();
             let task = tokio::task::spawn(async move {
                 let ret = search_tantivy_index(
@@ -598,6 +617,7 @@ pub async fn filter_file_list_by_tantivy_index(
                     index_condition_clone,
                     idx_optimize_rule_clone,
                     &file,
+                    &split_chars,
                 )
                 .await;
                 drop(permit);
@@ -629,13 +649,19 @@ pub async fn filter_file_list_by_tantivy_index(
                             hits_in_file,
                             file_name
                         );
-                        if hits_in_file == 0 {
-                            // if the bitmap is empty then we remove the file from the list
-                            file_list_map.remove(&file_name);
+                        let file = file_list_map.get_mut(&file_name).unwrap();
+                        let row_ids = if hits_in_file == 0 {
+                            None
                         } else {
+                            intersect_row_ids(file.segment_ids.as_ref(), res)
+                        };
+                        match row_ids {
                             // Replace the segment IDs in the existing `FileKey` with the found
-                            let file = file_list_map.get_mut(&file_name).unwrap();
-                            file.with_segment_ids(res);
+                            Some(row_ids) => file.with_segment_ids(row_ids),
+                            // if the bitmap is empty then we remove the file from the list
+                            None => {
+                                file_list_map.remove(&file_name);
+                            }
                         }
                     } else {
                         // if the bitmap is empty then we remove the file from the list
@@ -712,6 +738,7 @@ async fn search_tantivy_index(
     index_condition: Option<IndexCondition>,
     idx_optimize_rule: Option<InvertedIndexOptimizeMode>,
     parquet_file: &FileKey,
+    split_chars: &str,
 ) -> anyhow::Result<(String, Option<BitVec>, usize)> {
     let Some(ttv_file_name) = convert_parquet_idx_file_name_to_tantivy_file(&parquet_file.key)
     else {
@@ -764,7 +791,7 @@ async fn search_tantivy_index(
             let index = tantivy::Index::open(reader_directory)?;
             index
                 .tokenizers()
-                .register(O2_TOKENIZER, o2_tokenizer_build());
+                .register(O2_TOKENIZER, o2_tokenizer_build_with(split_chars));
             let reader = index
                 .reader_builder()
                 .reload_policy(tantivy::ReloadPolicy::Manual)
@@ -788,7 +815,7 @@ async fn search_tantivy_index(
     let condition: IndexCondition = index_condition.ok_or(anyhow::anyhow!(
         "[trace_id {trace_id}] search->storage: IndexCondition not found"
     ))?;
-    let query = condition.to_tantivy_query(tantivy_schema.clone(), fts_field)?;
+    let query = condition.to_tantivy_query(tantivy_schema.clone(), fts_field, split_chars)?;
 
     // warm up the terms in the query
     if cfg.common.inverted_index_tantivy_mode == InvertedIndexTantivyMode::Puffin.to_string() {
@@ -979,6 +1006,36 @@ fn find_max_group_index(groups: &[Vec<FileKey>]) -> usize {
         })
 }
 
+/// Expands the segment ids of the parquet index, one bit per
+/// `INDEX_SEGMENT_LENGTH` rows, to one bit per row.
+fn segment_ids_to_row_ids(segment_ids: &BitVec, num_rows: usize) -> BitVec {
+    let mut row_ids = BitVec::repeat(false, num_rows);
+    for segment_id in segment_ids.iter_ones() {
+        let start = segment_id * INDEX_SEGMENT_LENGTH;
+        if start >= num_rows {
+            break;
+        }
+        let end = (start + INDEX_SEGMENT_LENGTH).min(num_rows);
+        row_ids[start..end].fill(true);
+    }
+    row_ids
+}
+
+/// Intersects the rows found by tantivy with the rows already selected for
+/// the file, `None` when no row is left.
+fn intersect_row_ids(selected: Option<&BitVec>, found: BitVec) -> Option<BitVec> {
+    let row_ids = match selected {
+        Some(selected) => found
+            .iter()
+            .by_vals()
+            .zip(selected.iter().by_vals())
+            .map(|(found, selected)| found && selected)
+            .collect::<BitVec>(),
+        None => found,
+    };
+    row_ids.any().then_some(row_ids)
+}
+
 #[cfg(test)]
 mod tests {
     use config::meta::stream::FileMeta;
@@ -997,6 +1054,82 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_intersect_parquet_and_tantivy_index() {
+        let num_rows = INDEX_SEGMENT_LENGTH * 2 + 500;
+        // the parquet index selects the first and the last segment
+        let segment_ids = [true, false, true].into_iter().collect::<BitVec>();
+        let row_ids = segment_ids_to_row_ids(&segment_ids, num_rows);
+        assert_eq!(row_ids.len(), num_rows);
+        assert_eq!(row_ids.count_ones(), INDEX_SEGMENT_LENGTH + 500);
+        assert!(row_ids[0] && !row_ids[INDEX_SEGMENT_LENGTH] && row_ids[num_rows - 1]);
+
+        // tantivy finds a row in each segment
+        let hits = [10, INDEX_SEGMENT_LENGTH + 10, INDEX_SEGMENT_LENGTH * 2 + 10];
+        let mut found = BitVec::repeat(false, num_rows);
+        for row in hits {
+            found.set(row, true);
+        }
+        let combined = intersect_row_ids(Some(&row_ids), found.clone()).unwrap();
+        assert_eq!(
+            combined.iter_ones().collect::<Vec<_>>(),
+            vec![hits[0], hits[2]]
+        );
+
+        // only tantivy selects the rows
+        assert_eq!(intersect_row_ids(None, found.clone()).unwrap(), found);
+
+        // no common row, the file is skipped
+        let mut found = BitVec::repeat(false, num_rows);
+        found.set(INDEX_SEGMENT_LENGTH + 1, true);
+        assert!(intersect_row_ids(Some(&row_ids), found).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_explicit_segment_ids_access_plan() {
+        use datafusion::{
+            common::{stats::Precision, Statistics},
+            datasource::{listing::PartitionedFile, physical_plan::parquet::RowGroupAccess},
+            parquet::arrow::arrow_reader::RowSelector,
+        };
+
+        use crate::service::search::datafusion::{
+            storage::file_list, table_provider::helpers::generate_access_plan_row_level,
+        };
+
+        // the request only asks for the second and the fourth segment
+        let num_rows = INDEX_SEGMENT_LENGTH * 4;
+        let segment_ids = config::meta::search::segment_ranges_to_bitvec(&[(1, 1), (3, 3)]);
+        let mut file = create_file_key(0, 10);
+        file.meta.records = num_rows as i64;
+        file.with_segment_ids(segment_ids_to_row_ids(&segment_ids, num_rows));
+
+        let trace_id = "test_explicit_segment_ids_access_plan";
+        file_list::set(trace_id, "0", &[file]).await;
+        let object_meta = file_list::get(&format!("{trace_id}/schema=0"))
+            .unwrap()
+            .remove(0);
+        let mut part_file = PartitionedFile::from(object_meta);
+        let mut statistics = Statistics::new_unknown(&Schema::empty());
+        statistics.num_rows = Precision::Exact(num_rows);
+        part_file.statistics = Some(statistics);
+
+        let access_plan = generate_access_plan_row_level(&part_file).unwrap();
+        file_list::clear(trace_id);
+        let RowGroupAccess::Selection(selection) = &access_plan.inner()[0] else {
+            panic!("expected a row selection");
+        };
+        assert_eq!(
+            selection.iter().cloned().collect::<Vec<_>>(),
+            vec![
+                RowSelector::skip(INDEX_SEGMENT_LENGTH),
+                RowSelector::select(INDEX_SEGMENT_LENGTH),
+                RowSelector::skip(INDEX_SEGMENT_LENGTH),
+                RowSelector::select(INDEX_SEGMENT_LENGTH),
+            ]
+        );
+    }
+
     #[test]
     fn test_group_files_by_time_range() {
         let files = vec![
@@ -315,8 +315,8 @@ pub async fn search_memtable(
             .unwrap_or_default();
     let partition_keys = &stream_settings.partition_keys;
     let mut filters = generate_filter_from_equal_items(search_partition_keys);
-    let partition_keys: HashMap<&String, &StreamPartition> =
-        partition_keys.iter().map(|v| (&v.field, v)).collect();
+    let partition_keys: HashMap<String, &StreamPartition> =
+        partition_keys.iter().map(|v| (v.column(), v)).collect();
     for (key, value) in filters.iter_mut() {
         if let Some(partition_key) = partition_keys.get(key) {
             for val in value.iter_mut() {
@@ -514,8 +514,8 @@ async fn get_file_list_inner(
         query.stream_type,
     ));
     let mut filters = generate_filter_from_equal_items(search_partition_keys);
-    let partition_keys: HashMap<&String, &StreamPartition> =
-        partition_keys.iter().map(|v| (&v.field, v)).collect();
+    let partition_keys: HashMap<String, &StreamPartition> =
+        partition_keys.iter().map(|v| (v.column(), v)).collect();
     for (key, value) in filters.iter_mut() {
         if let Some(partition_key) = partition_keys.get(key) {
             for val in value.iter_mut() {
@@ -18,7 +18,7 @@ use std::{
     sync::Arc,
 };
 
-use config::{utils::tantivy::tokenizer::o2_collect_tokens, INDEX_FIELD_NAME_FOR_ALL};
+use config::{utils::tantivy::tokenizer::o2_collect_tokens_with, INDEX_FIELD_NAME_FOR_ALL};
 use datafusion::{
     arrow::datatypes::{DataType, SchemaRef},
     logical_expr::Operator,
@@ -119,13 +119,14 @@ impl IndexCondition {
         &self,
         schema: Schema,
         default_field: Option<Field>,
+        split_chars: &str,
     ) -> anyhow::Result<Box<dyn Query>> {
         let queries = self
             .conditions
             .iter()
             .map(|condition| {
                 condition
-                    .to_tantivy_query(&schema, default_field)
+                    .to_tantivy_query(&schema, default_field, split_chars)
                     .map(|condition| (Occur::Must, condition))
             })
             .collect::<anyhow::Result<Vec<_>>>()?;
@@ -268,10 +269,13 @@ impl Condition {
         }
     }
 
+    /// `split_chars` tokenize the `match_all` values the same way the full
+    /// text search fields of the stream were indexed.
     pub fn to_tantivy_query(
         &self,
         schema: &Schema,
         default_field: Option<Field>,
+        split_chars: &str,
     ) -> anyhow::Result<Box<dyn Query>> {
         Ok(match self {
             Condition::Equal(field, value) => {
@@ -316,7 +320,7 @@ impl Condition {
                             "The value of match_all() function can't be empty"
                         ));
                     }
-                    let mut terms: Vec<Box<dyn Query>> = o2_collect_tokens(value)
+                    let mut terms: Vec<Box<dyn Query>> = o2_collect_tokens_with(value, split_chars)
                         .into_iter()
                         .map(|value| {
                             let term = Term::from_field_text(default_field, &value);
@@ -345,13 +349,13 @@ impl Condition {
                 Box::new(FuzzyTermQuery::new(term, *distance, false))
             }
             Condition::Or(left, right) => {
-                let left_query = left.to_tantivy_query(schema, default_field)?;
-                let right_query = right.to_tantivy_query(schema, default_field)?;
+                let left_query = left.to_tantivy_query(schema, default_field, split_chars)?;
+                let right_query = right.to_tantivy_query(schema, default_field, split_chars)?;
                 Box::new(BooleanQuery::union(vec![left_query, right_query]))
             }
             Condition::And(left, right) => {
-                let left_query = left.to_tantivy_query(schema, default_field)?;
-                let right_query = right.to_tantivy_query(schema, default_field)?;
+                let left_query = left.to_tantivy_query(schema, default_field, split_chars)?;
+                let right_query = right.to_tantivy_query(schema, default_field, split_chars)?;
                 Box::new(BooleanQuery::intersection(vec![left_query, right_query]))
             }
         })
@@ -0,0 +1,701 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{collections::BTreeMap, ops::ControlFlow};
+
+use config::{
+    meta::{
+        sql::{resolve_stream_names_with_type, TableReferenceExt},
+        stream::{MaskStrategy, StreamType},
+    },
+    ORIGINAL_DATA_COL_NAME,
+};
+use hashbrown::HashMap;
+use infra::{
+    errors::Error,
+    schema::{get_stream_setting_fts_fields, unwrap_stream_settings},
+};
+use sqlparser::{
+    ast::{
+        Expr, Function, FunctionArguments, Ident, ObjectName, Query, Select, SelectItem, SetExpr,
+        TableFactor, VisitMut, VisitorMut,
+    },
+    dialect::PostgreSqlDialect,
+    parser::Parser,
+};
+
+use crate::{
+    common::{
+        meta::user::UserRole,
+        utils::auth::{check_permissions, is_root_user},
+    },
+    service::search::datafusion::udf::{
+        match_all_udf::{
+            FUZZY_MATCH_ALL_UDF_NAME, MATCH_ALL_RAW_IGNORE_CASE_UDF_NAME, MATCH_ALL_RAW_UDF_NAME,
+            MATCH_ALL_UDF_NAME,
+        },
+        FUZZY_MATCH_UDF_NAME,
+    },
+};
+
+/// The masked fields of a stream, keyed by lowercase field name, the fields
+/// of its schema, used to expand `*`, and its full text search fields, used
+/// to expand `match_all()`.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StreamMasks {
+    pub(crate) masks: BTreeMap<String, MaskStrategy>,
+    pub(crate) fields: Vec<String>,
+    pub(crate) fts_fields: Vec<String>,
+}
+
+/// Collects the `field_masks` of the queried streams the user may not see in
+/// clear, keyed by stream name. When any stream is masked, the other streams
+/// of the query are returned with no masks, so a `*` over a join can still be
+/// expanded. Root users, users allowed to update the stream and internal
+/// searches without a user, like alerts and reports, are not masked.
+pub(crate) async fn get_field_masks(
+    org_id: &str,
+    user_id: Option<&str>,
+    stream_type: StreamType,
+    sql: &str,
+) -> Result<HashMap<String, StreamMasks>, Error> {
+    let mut streams = HashMap::new();
+    let Some(user_id) = user_id else {
+        return Ok(streams);
+    };
+    if is_root_user(user_id) {
+        return Ok(streams);
+    }
+    let stream_names =
+        resolve_stream_names_with_type(sql).map_err(|e| Error::Message(e.to_string()))?;
+    let mut has_masks = false;
+    for stream in stream_names.iter() {
+        let stream_name = stream.stream_name();
+        let stream_type = stream.get_stream_type(stream_type);
+        let schema = infra::schema::get(org_id, &stream_name, stream_type)
+            .await
+            .unwrap_or_else(|_| arrow_schema::Schema::empty());
+        let fields = schema
+            .fields()
+            .iter()
+            .map(|f| f.name().to_string())
+            .collect::<Vec<_>>();
+        let fts_fields = get_stream_setting_fts_fields(&unwrap_stream_settings(&schema))
+            .into_iter()
+            .filter(|field| fields.contains(field))
+            .collect();
+        let masks = get_stream_field_masks(org_id, user_id, stream_type, &stream_name).await;
+        has_masks |= !masks.is_empty();
+        streams.insert(
+            stream_name,
+            StreamMasks {
+                masks,
+                fields,
+                fts_fields,
+            },
+        );
+    }
+    if !has_masks {
+        streams.clear();
+    }
+    Ok(streams)
+}
+
+/// The `field_masks` of a stream the user may not see in clear, keyed by
+/// lowercase field name, empty when the user sees the clear values.
+pub(crate) async fn get_stream_field_masks(
+    org_id: &str,
+    user_id: &str,
+    stream_type: StreamType,
+    stream_name: &str,
+) -> BTreeMap<String, MaskStrategy> {
+    let mut masks = BTreeMap::new();
+    if let Some(settings) = infra::schema::get_settings(org_id, stream_name, stream_type).await {
+        if !settings.field_masks.is_empty()
+            && !is_stream_privileged(org_id, user_id, stream_type, stream_name).await
+        {
+            masks.extend(
+                settings
+                    .field_masks
+                    .into_iter()
+                    .map(|(field, strategy)| (field.to_lowercase(), strategy)),
+            );
+            // the original record holds the clear values
+            masks.insert(ORIGINAL_DATA_COL_NAME.to_string(), MaskStrategy::Full);
+        }
+    }
+    masks
+}
+
+/// The field masks applied to the user's search, in a stable order, for the
+/// result cache key. `None` when nothing is masked.
+pub(crate) fn field_masks_cache_key(streams: &HashMap<String, StreamMasks>) -> Option<String> {
+    let masks = streams
+        .iter()
+        .filter(|(_, stream)| !stream.masks.is_empty())
+        .map(|(name, stream)| (name, &stream.masks))
+        .collect::<BTreeMap<_, _>>();
+    if masks.is_empty() {
+        return None;
+    }
+    Some(
+        masks
+            .iter()
+            .map(|(name, masks)| {
+                let masks = masks
+                    .iter()
+                    .map(|(field, strategy)| format!("{field}={strategy:?}"))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                format!("{name}:{masks}")
+            })
+            .collect::<Vec<_>>()
+            .join(";"),
+    )
+}
+
+/// Root users, org admins and users allowed to update the stream see it
+/// unrestricted: the clear values, and every row unless one of their roles has
+/// a row filter.
+pub(crate) async fn is_stream_privileged(
+    org_id: &str,
+    user_id: &str,
+    stream_type: StreamType,
+    stream_name: &str,
+) -> bool {
+    is_root_user(user_id)
+        || crate::service::users::get_user(Some(org_id), user_id)
+            .await
+            .is_some_and(|user| user.role == UserRole::Admin)
+        || check_permissions(
+            Some(stream_name.to_string()),
+            org_id,
+            user_id,
+            stream_type.as_str(),
+            "PUT",
+        )
+        .await
+}
+
+/// Rewrites every SELECT reading from a masked stream, including subqueries
+/// and UNION branches, so the masked fields are only ever read through their
+/// mask expression: in the projection, WHERE, GROUP BY, HAVING, ORDER BY and
+/// join conditions. `*` is expanded to the stream fields, and masked columns
+/// keep their name in the output.
+pub(crate) fn add_field_masks(
+    sql: &str,
+    streams: &HashMap<String, StreamMasks>,
+) -> Result<String, Error> {
+    let mut statement = Parser::parse_sql(&PostgreSqlDialect {}, sql)
+        .map_err(|e| Error::Message(e.to_string()))?
+        .pop()
+        .unwrap();
+    let mut visitor = FieldMaskVisitor::new(streams);
+    if let ControlFlow::Break(e) = statement.visit(&mut visitor) {
+        return Err(e);
+    }
+    Ok(statement.to_string())
+}
+
+struct FieldMaskVisitor<'a> {
+    streams: &'a HashMap<String, StreamMasks>,
+}
+
+impl<'a> FieldMaskVisitor<'a> {
+    fn new(streams: &'a HashMap<String, StreamMasks>) -> Self {
+        Self { streams }
+    }
+
+    fn mask_query(&self, query: &mut Query) -> Result<(), Error> {
+        let masks = match query.body.as_mut() {
+            SetExpr::Select(select) => self.mask_select(select)?,
+            SetExpr::SetOperation { left, right, .. } => {
+                // the ORDER BY of a UNION refers to its output columns
+                self.mask_set_expr(left)?;
+                self.mask_set_expr(right)?;
+                return Ok(());
+            }
+            _ => return Ok(()),
+        };
+        if let Some(order_by) = query.order_by.as_mut() {
+            mask_exprs(order_by, &masks, &[])?;
+        }
+        Ok(())
+    }
+
+    fn mask_set_expr(&self, body: &mut SetExpr) -> Result<(), Error> {
+        match body {
+            SetExpr::Select(select) => self.mask_select(select).map(|_| ()),
+            SetExpr::SetOperation { left, right, .. } => {
+                self.mask_set_expr(left)?;
+                self.mask_set_expr(right)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Masks the select and returns the masks of its streams.
+    fn mask_select(&self, select: &mut Select) -> Result<BTreeMap<String, MaskStrategy>, Error> {
+        // (stream name, name the select refers to it by)
+        let tables = select
+            .from
+            .iter()
+            .flat_map(|from| {
+                std::iter::once(&from.relation).chain(from.joins.iter().map(|j| &j.relation))
+            })
+            .map(|relation| match relation {
+                TableFactor::Table { name, alias, .. } => {
+                    let stream = name.0.last().map(|v| v.value.clone());
+                    let reference = match alias {
+                        Some(alias) => ObjectName(vec![alias.name.clone()]),
+                        None => name.clone(),
+                    };
+                    (stream, Some(reference))
+                }
+                TableFactor::Derived { alias, .. } => (
+                    None,
+                    alias.as_ref().map(|a| ObjectName(vec![a.name.clone()])),
+                ),
+                _ => (None, None),
+            })
+            .collect::<Vec<_>>();
+        let masks = tables
+            .iter()
+            .filter_map(|(stream, _)| self.streams.get(stream.as_ref()?))
+            .flat_map(|stream| stream.masks.clone())
+            .collect::<BTreeMap<_, _>>();
+        if masks.is_empty() {
+            return Ok(masks);
+        }
+        // the index and `match_all()` read the clear values of the full text
+        // search fields, so it's expanded to match on the masked values
+        let fts_fields = tables
+            .iter()
+            .filter_map(|(stream, _)| self.streams.get(stream.as_ref()?))
+            .flat_map(|stream| stream.fts_fields.clone())
+            .collect::<Vec<_>>();
+        let match_fields = if fts_fields
+            .iter()
+            .any(|field| masks.contains_key(&field.to_lowercase()))
+        {
+            fts_fields
+        } else {
+            vec![]
+        };
+
+        // expand `*`, so the masked columns can be replaced
+        let qualify = tables.len() > 1;
+        let mut projection = Vec::with_capacity(select.projection.len());
+        for item in select.projection.drain(..) {
+            match item {
+                SelectItem::Wildcard(options) => {
+                    for (stream, reference) in tables.iter() {
+                        match stream.as_ref().and_then(|s| self.streams.get(s)) {
+                            Some(stream) => projection.extend(stream_columns(
+                                stream,
+                                reference.as_ref().filter(|_| qualify),
+                            )),
+                            None => match reference {
+                                Some(reference) => projection.push(SelectItem::QualifiedWildcard(
+                                    reference.clone(),
+                                    options.clone(),
+                                )),
+                                None => projection.push(SelectItem::Wildcard(options.clone())),
+                            },
+                        }
+                    }
+                }
+                SelectItem::QualifiedWildcard(name, options) => {
+                    let stream = tables
+                        .iter()
+                        .find(|(_, reference)| reference.as_ref() == Some(&name))
+                        .and_then(|(stream, _)| self.streams.get(stream.as_ref()?));
+                    match stream {
+                        Some(stream) => projection.extend(stream_columns(stream, Some(&name))),
+                        None => projection.push(SelectItem::QualifiedWildcard(name, options)),
+                    }
+                }
+                item => projection.push(item),
+            }
+        }
+        // keep the output name of the masked columns
+        select.projection = projection
+            .into_iter()
+            .map(|item| match item {
+                SelectItem::UnnamedExpr(expr) if references_masks(&expr, &masks) => {
+                    // `(field)` is named like `field`
+                    let mut named = &expr;
+                    while let Expr::Nested(inner) = named {
+                        named = inner;
+                    }
+                    let alias = match named {
+                        Expr::Identifier(ident) => ident.clone(),
+                        Expr::CompoundIdentifier(idents) => idents.last().unwrap().clone(),
+                        _ => Ident::with_quote('"', expr.to_string()),
+                    };
+                    SelectItem::ExprWithAlias { expr, alias }
+                }
+                item => item,
+            })
+            .collect();
+
+        mask_exprs(select, &masks, &match_fields)?;
+        Ok(masks)
+    }
+}
+
+impl VisitorMut for FieldMaskVisitor<'_> {
+    type Break = Error;
+
+    fn pre_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        match self.mask_query(query) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(e) => ControlFlow::Break(e),
+        }
+    }
+}
+
+/// The stream fields as select items, qualified by the table reference.
+fn stream_columns(stream: &StreamMasks, reference: Option<&ObjectName>) -> Vec<SelectItem> {
+    stream
+        .fields
+        .iter()
+        .filter(|field| field.as_str() != ORIGINAL_DATA_COL_NAME)
+        .map(|field| {
+            let field = Ident::with_quote('"', field);
+            SelectItem::UnnamedExpr(match reference {
+                Some(reference) => {
+                    let mut idents = reference.0.clone();
+                    idents.push(field);
+                    Expr::CompoundIdentifier(idents)
+                }
+                None => Expr::Identifier(field),
+            })
+        })
+        .collect()
+}
+
+fn masked_field<'a>(
+    expr: &Expr,
+    masks: &'a BTreeMap<String, MaskStrategy>,
+) -> Option<&'a MaskStrategy> {
+    let ident = match expr {
+        Expr::Identifier(ident) => ident,
+        Expr::CompoundIdentifier(idents) => idents.last()?,
+        _ => return None,
+    };
+    masks.get(&ident.value.to_lowercase())
+}
+
+fn references_masks(expr: &Expr, masks: &BTreeMap<String, MaskStrategy>) -> bool {
+    sqlparser::ast::visit_expressions(expr, |expr| match masked_field(expr, masks) {
+        Some(_) => ControlFlow::Break(()),
+        None => ControlFlow::Continue(()),
+    })
+    .is_break()
+}
+
+/// Replaces the masked fields in `node` with their mask expression, leaving
+/// subqueries to their own select. When `match_fields` is not empty, the
+/// `match_all()` functions are expanded to match on each of them, masked.
+fn mask_exprs<V: VisitMut>(
+    node: &mut V,
+    masks: &BTreeMap<String, MaskStrategy>,
+    match_fields: &[String],
+) -> Result<(), Error> {
+    let mut visitor = MaskExprVisitor {
+        masks,
+        match_fields,
+        depth: 0,
+    };
+    match node.visit(&mut visitor) {
+        ControlFlow::Break(e) => Err(e),
+        ControlFlow::Continue(()) => Ok(()),
+    }
+}
+
+struct MaskExprVisitor<'a> {
+    masks: &'a BTreeMap<String, MaskStrategy>,
+    match_fields: &'a [String],
+    depth: usize,
+}
+
+impl MaskExprVisitor<'_> {
+    /// `match_all('v')` as `f1 ILIKE '%v%' OR f2 ILIKE '%v%'` over the match
+    /// fields, like the full text search rewrite of the planner.
+    fn expand_match_all(&self, func: &Function) -> Option<String> {
+        if self.match_fields.is_empty() {
+            return None;
+        }
+        let FunctionArguments::List(list) = &func.args else {
+            return None;
+        };
+        let args = list
+            .args
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>();
+        let value = args.first()?;
+        let fields = self.match_fields.iter().map(|field| {
+            let column = format!("\"{field}\"");
+            match self.masks.get(&field.to_lowercase()) {
+                Some(strategy) => format!("({})", strategy.sql_expr(&column)),
+                None => column,
+            }
+        });
+        let conditions = match func.name.to_string().to_lowercase().as_str() {
+            MATCH_ALL_UDF_NAME | MATCH_ALL_RAW_IGNORE_CASE_UDF_NAME => fields
+                .map(|field| format!("{field} ILIKE concat('%', {value}, '%')"))
+                .collect::<Vec<_>>(),
+            MATCH_ALL_RAW_UDF_NAME => fields
+                .map(|field| format!("{field} LIKE concat('%', {value}, '%')"))
+                .collect(),
+            FUZZY_MATCH_ALL_UDF_NAME => {
+                let distance = args.get(1)?;
+                fields
+                    .map(|field| format!("{FUZZY_MATCH_UDF_NAME}({field}, {value}, {distance})"))
+                    .collect()
+            }
+            _ => return None,
+        };
+        Some(conditions.join(" OR "))
+    }
+}
+
+impl VisitorMut for MaskExprVisitor<'_> {
+    type Break = Error;
+
+    fn pre_visit_query(&mut self, _query: &mut Query) -> ControlFlow<Self::Break> {
+        self.depth += 1;
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &mut Query) -> ControlFlow<Self::Break> {
+        self.depth -= 1;
+        ControlFlow::Continue(())
+    }
+
+    // replaced after the children are visited, so the field inside the mask
+    // expression isn't masked again
+    fn post_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        if self.depth > 0 {
+            return ControlFlow::Continue(());
+        }
+        let masked = match expr {
+            Expr::Function(func) => match self.expand_match_all(func) {
+                Some(expanded) => expanded,
+                None => return ControlFlow::Continue(()),
+            },
+            _ => match masked_field(expr, self.masks) {
+                Some(strategy) => strategy.sql_expr(&expr.to_string()),
+                None => return ControlFlow::Continue(()),
+            },
+        };
+        match Parser::new(&PostgreSqlDialect {})
+            .try_with_sql(&masked)
+            .and_then(|mut parser| parser.parse_expr())
+        {
+            Ok(masked) => {
+                *expr = Expr::Nested(Box::new(masked));
+                ControlFlow::Continue(())
+            }
+            Err(e) => ControlFlow::Break(Error::Message(format!(
+                "invalid mask expression {masked}: {e}"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::meta::organization::DEFAULT_ORG;
+
+    fn streams() -> HashMap<String, StreamMasks> {
+        HashMap::from([(
+            "users".to_string(),
+            StreamMasks {
+                masks: BTreeMap::from([
+                    ("email".to_string(), MaskStrategy::Full),
+                    ("card".to_string(), MaskStrategy::Partial),
+                    ("ssn".to_string(), MaskStrategy::Hash),
+                ]),
+                fields: vec![
+                    "_timestamp".to_string(),
+                    "email".to_string(),
+                    "level".to_string(),
+                ],
+                fts_fields: vec!["email".to_string(), "level".to_string()],
+            },
+        )])
+    }
+
+    const EMAIL: &str = "(CASE WHEN email IS NULL THEN NULL ELSE '********' END)";
+
+    #[test]
+    fn test_add_field_masks() {
+        let cases = [
+            (
+                "SELECT * FROM users",
+                "SELECT \"_timestamp\", (CASE WHEN \"email\" IS NULL THEN NULL ELSE '********' END) AS \"email\", \"level\" FROM users".to_string(),
+            ),
+            (
+                "SELECT email AS e, level FROM users WHERE email = 'jane@example.com'",
+                format!("SELECT {EMAIL} AS e, level FROM users WHERE {EMAIL} = 'jane@example.com'"),
+            ),
+            (
+                "SELECT lower(email) FROM users GROUP BY lower(email) ORDER BY email",
+                format!(
+                    "SELECT lower({EMAIL}) AS \"lower(email)\" FROM users GROUP BY lower({EMAIL}) ORDER BY {EMAIL}"
+                ),
+            ),
+            (
+                "SELECT count(*) FROM (SELECT email FROM users) AS t WHERE t.email LIKE 'j%'",
+                format!(
+                    "SELECT count(*) FROM (SELECT {EMAIL} AS email FROM users) AS t WHERE t.email LIKE 'j%'"
+                ),
+            ),
+            (
+                "SELECT email FROM other UNION ALL SELECT email FROM users",
+                format!("SELECT email FROM other UNION ALL SELECT {EMAIL} AS email FROM users"),
+            ),
+            (
+                "SELECT ssn FROM users",
+                "SELECT (CASE WHEN ssn IS NULL THEN NULL ELSE encode(sha256(CAST(ssn AS VARCHAR)), 'hex') END) AS ssn FROM users".to_string(),
+            ),
+            ("SELECT email FROM other", "SELECT email FROM other".to_string()),
+            (
+                "SELECT DISTINCT(email) FROM users",
+                format!("SELECT DISTINCT ({EMAIL}) AS email FROM users"),
+            ),
+        ];
+        for (sql, expected) in cases {
+            assert_eq!(add_field_masks(sql, &streams()).unwrap(), expected, "{sql}");
+        }
+    }
+
+    #[test]
+    fn test_add_field_masks_match_all() {
+        const MASKED: &str =
+            "(CASE WHEN \"email\" IS NULL THEN NULL ELSE '********' END)";
+        let cases = [
+            (
+                "SELECT * FROM users WHERE match_all('jane')",
+                format!(
+                    "WHERE ({MASKED} ILIKE concat('%', 'jane', '%') OR \"level\" ILIKE concat('%', 'jane', '%'))"
+                ),
+            ),
+            (
+                "SELECT count(*) FROM users WHERE match_all_raw('jane') AND level = 'info'",
+                format!(
+                    "WHERE ({MASKED} LIKE concat('%', 'jane', '%') OR \"level\" LIKE concat('%', 'jane', '%')) AND level = 'info'"
+                ),
+            ),
+            (
+                "SELECT count(*) FROM users WHERE fuzzy_match_all('jane', 1)",
+                format!(
+                    "WHERE (fuzzy_match({MASKED}, 'jane', 1) OR fuzzy_match(\"level\", 'jane', 1))"
+                ),
+            ),
+        ];
+        for (sql, expected) in cases {
+            let masked = add_field_masks(sql, &streams()).unwrap();
+            assert!(masked.ends_with(&expected), "{sql}: {masked}");
+            // no match_all() is left for the index to answer on clear values
+            assert!(!masked.contains("match_all"), "{sql}: {masked}");
+        }
+
+        // match_all() is kept when no full text search field is masked
+        let mut streams = streams();
+        streams.get_mut("users").unwrap().fts_fields = vec!["level".to_string()];
+        let sql = "SELECT count(*) FROM users WHERE match_all('jane')";
+        assert_eq!(add_field_masks(sql, &streams).unwrap(), sql);
+    }
+
+    #[test]
+    fn test_field_masks_cache_key() {
+        assert_eq!(
+            field_masks_cache_key(&streams()).as_deref(),
+            Some("users:card=Partial,email=Full,ssn=Hash")
+        );
+        assert_eq!(field_masks_cache_key(&HashMap::new()), None);
+    }
+
+    #[tokio::test]
+    async fn test_get_field_masks_by_user() {
+        let org_id = "test_get_field_masks_by_user";
+        let mut settings = config::meta::stream::StreamSettings::default();
+        settings
+            .field_masks
+            .insert("Email".to_string(), MaskStrategy::Full);
+        infra::schema::STREAM_SETTINGS.write().await.insert(
+            format!("{org_id}/{}/{}", StreamType::Logs, "users"),
+            settings,
+        );
+        let sql = "SELECT * FROM \"users\"";
+
+        // a user without permission on the stream gets the masked value
+        let streams = get_field_masks(org_id, Some("viewer@example.com"), StreamType::Logs, sql)
+            .await
+            .unwrap();
+        assert_eq!(
+            streams["users"].masks.get("email"),
+            Some(&MaskStrategy::Full)
+        );
+        assert_eq!(
+            add_field_masks("SELECT email FROM \"users\"", &streams).unwrap(),
+            format!("SELECT {EMAIL} AS email FROM \"users\"")
+        );
+
+        // internal searches get the clear value
+        let streams = get_field_masks(org_id, None, StreamType::Logs, sql)
+            .await
+            .unwrap();
+        assert!(streams.is_empty());
+
+        // and so do privileged users
+        crate::common::infra::config::USERS.insert(
+            format!("{DEFAULT_ORG}/root@example.com"),
+            crate::common::meta::user::User {
+                email: "root@example.com".to_string(),
+                first_name: "root".to_string(),
+                last_name: "".to_string(),
+                password: "Complexpass#123".to_string(),
+                salt: "".to_string(),
+                token: "".to_string(),
+                rum_token: None,
+                role: crate::common::meta::user::UserRole::Root,
+                org: DEFAULT_ORG.to_string(),
+                is_external: false,
+                password_ext: None,
+            },
+        );
+        let streams = get_field_masks(org_id, Some("root@example.com"), StreamType::Logs, sql)
+            .await
+            .unwrap();
+        assert!(streams.is_empty());
+        assert!(
+            get_stream_field_masks(org_id, "root@example.com", StreamType::Logs, "users")
+                .await
+                .is_empty()
+        );
+        assert_eq!(
+            get_stream_field_masks(org_id, "viewer@example.com", StreamType::Logs, "users")
+                .await
+                .get("email"),
+            Some(&MaskStrategy::Full)
+        );
+    }
+}
@@ -13,7 +13,13 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{cmp::max, sync::Arc};
+use std::{
+    cmp::max,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 use arrow_schema::{DataType, Field, Schema};
 use cache::cacher::get_ts_col_order_by;
@@ -47,15 +53,16 @@ use opentelemetry::trace::TraceContextExt;
 use proto::cluster_rpc::{self, SearchQuery};
 use regex::Regex;
 use sql::Sql;
-use tokio::runtime::Runtime;
 #[cfg(not(feature = "enterprise"))]
-use tokio::sync::Mutex;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::{runtime::Runtime, sync::Notify};
 use tracing::Instrument;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 #[cfg(feature = "enterprise")]
 use {
-    crate::service::grpc::make_grpc_search_client, o2_enterprise::enterprise::search::TaskStatus,
-    o2_enterprise::enterprise::search::WorkGroup, std::collections::HashSet, tracing::info_span,
+    crate::service::grpc::make_grpc_search_client, config::meta::cluster::NodeInfo,
+    o2_enterprise::enterprise::search::TaskStatus, o2_enterprise::enterprise::search::WorkGroup,
+    std::collections::HashSet, tracing::info_span,
 };
 
 use super::self_reporting::report_request_usage_stats;
@@ -70,10 +77,12 @@ pub(crate) mod datafusion;
 pub(crate) mod grpc;
 pub(crate) mod grpc_search;
 pub(crate) mod index;
+pub(crate) mod masking;
 pub(crate) mod request;
 pub(crate) mod sql;
 #[cfg(feature = "enterprise")]
 pub(crate) mod super_cluster;
+pub(crate) mod tail;
 pub(crate) mod tantivy;
 pub(crate) mod utils;
 
@@ -85,8 +94,183 @@ pub static RESULT_ARRAY: Lazy<Regex> =
 pub static SEARCH_SERVER: Lazy<Searcher> = Lazy::new(Searcher::new);
 
 #[cfg(not(feature = "enterprise"))]
-pub(crate) static QUEUE_LOCKER: Lazy<Arc<Mutex<bool>>> =
-    Lazy::new(|| Arc::new(Mutex::const_new(false)));
+pub(crate) static QUEUE_LOCKER: Lazy<Arc<Semaphore>> = Lazy::new(|| {
+    Arc::new(Semaphore::new(max(
+        1,
+        get_config().limit.query_queue_permits,
+    )))
+});
+
+/// Waits for a slot in the local search queue. The permit must be held until
+/// the search finishes, `None` is returned when the queue is disabled.
+#[cfg(not(feature = "enterprise"))]
+pub(crate) async fn acquire_queue_permit(
+    trace_id: &str,
+) -> Result<Option<OwnedSemaphorePermit>, Error> {
+    let cfg = get_config();
+    if !cfg.common.feature_query_queue_enabled {
+        return Ok(None);
+    }
+    let timeout = std::time::Duration::from_secs(cfg.limit.query_queue_timeout);
+    acquire_permit(QUEUE_LOCKER.clone(), timeout)
+        .await
+        .map(Some)
+        .map_err(|e| {
+            Error::ErrorCode(ErrorCodes::SearchQueueTimeout(format!(
+                "[trace_id {trace_id}] search: {e}"
+            )))
+        })
+}
+
+/// A zero timeout waits forever.
+#[cfg(not(feature = "enterprise"))]
+async fn acquire_permit(
+    queue: Arc<Semaphore>,
+    timeout: std::time::Duration,
+) -> Result<OwnedSemaphorePermit, String> {
+    let permit = if timeout.is_zero() {
+        queue.acquire_owned().await
+    } else {
+        match tokio::time::timeout(timeout, queue.acquire_owned()).await {
+            Ok(permit) => permit,
+            Err(_) => {
+                return Err(format!(
+                    "waiting in queue timed out after {} seconds",
+                    timeout.as_secs()
+                ));
+            }
+        }
+    };
+    permit.map_err(|e| format!("waiting in queue failed: {e}"))
+}
+
+/// Searches running on this node, closed on shutdown so that in-flight
+/// searches can finish before their WAL locks are released.
+pub static SEARCH_GATE: Lazy<Arc<SearchGate>> = Lazy::new(|| Arc::new(SearchGate::default()));
+
+#[derive(Debug, Default)]
+pub struct SearchGate {
+    closed: AtomicBool,
+    in_flight: AtomicUsize,
+    idle: Notify,
+}
+
+/// Held for the duration of a search, see [SearchGate::enter].
+pub struct SearchGateGuard(Arc<SearchGate>);
+
+impl Drop for SearchGateGuard {
+    fn drop(&mut self) {
+        if self.0.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+impl SearchGate {
+    /// Registers a new search, `None` once the gate is closed.
+    pub fn enter(self: &Arc<Self>) -> Option<SearchGateGuard> {
+        if self.closed.load(Ordering::SeqCst) {
+            return None;
+        }
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = SearchGateGuard(self.clone());
+        // the gate may have been closed while registering
+        if self.closed.load(Ordering::SeqCst) {
+            return None;
+        }
+        Some(guard)
+    }
+
+    /// Registers the node side of a search, even once the gate is closed, as
+    /// it may belong to a search this node let in before closing.
+    pub fn track(self: &Arc<Self>) -> SearchGateGuard {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        SearchGateGuard(self.clone())
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Stops accepting new searches and waits up to `timeout` for the running
+    /// ones to finish. Returns `false` if some were still running.
+    pub async fn close(&self, timeout: std::time::Duration) -> bool {
+        self.closed.store(true, Ordering::SeqCst);
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let idle = self.idle.notified();
+            if self.in_flight() == 0 {
+                return true;
+            }
+            if tokio::time::timeout_at(deadline, idle).await.is_err() {
+                return self.in_flight() == 0;
+            }
+        }
+    }
+}
+
+/// Registers a search running on this node, refused once the node is shutting
+/// down. The guard must be held until the search and its WAL locks are done.
+pub(crate) fn enter_search_gate(trace_id: &str) -> Result<SearchGateGuard, Error> {
+    SEARCH_GATE.enter().ok_or_else(|| {
+        Error::ErrorCode(ErrorCodes::SearchNodeShuttingDown(format!(
+            "[trace_id {trace_id}] search: node is shutting down"
+        )))
+    })
+}
+
+/// Stops accepting searches on this node, waits (bounded by `timeout`) for
+/// the in-flight ones and then releases the WAL files they leaked.
+pub async fn shutdown(timeout: std::time::Duration) {
+    let drained = SEARCH_GATE.close(timeout).await;
+    if !drained {
+        // the running searches still read their WAL files
+        log::warn!(
+            "[SEARCH] {} searches still running after {} seconds, keep their WAL locks",
+            SEARCH_GATE.in_flight(),
+            timeout.as_secs()
+        );
+        return;
+    }
+    // no search is running, the remaining locks were never released
+    let released = crate::common::infra::wal::release_expired_requests(i64::MAX);
+    if !released.is_empty() {
+        log::info!(
+            "[SEARCH] released WAL locks of {} searches on shutdown",
+            released.len()
+        );
+    }
+}
+
+/// The debug `segment_ids` option is reserved to the root user, and must only
+/// select segments of the given files.
+async fn check_segment_ids(
+    user_id: Option<&str>,
+    segment_ids: &std::collections::HashMap<String, Vec<(usize, usize)>>,
+) -> Result<(), Error> {
+    if !user_id.is_some_and(common::utils::auth::is_root_user) {
+        return Err(Error::ErrorCode(ErrorCodes::InvalidParams(
+            "segment_ids is only allowed for the root user".to_string(),
+        )));
+    }
+    for (file, ranges) in segment_ids.iter() {
+        let meta = infra::file_list::get(file).await.map_err(|_| {
+            Error::ErrorCode(ErrorCodes::InvalidParams(format!(
+                "segment_ids file not found: {file}"
+            )))
+        })?;
+        let segments = (meta.records.max(0) as usize).div_ceil(config::INDEX_SEGMENT_LENGTH);
+        if let Some((start, end)) = ranges
+            .iter()
+            .find(|(start, end)| start > end || *end >= segments)
+        {
+            return Err(Error::ErrorCode(ErrorCodes::InvalidParams(format!(
+                "segment_ids range {start}-{end} is out of the {segments} segments of {file}"
+            ))));
+        }
+    }
+    Ok(())
+}
 
 pub static DATAFUSION_RUNTIME: Lazy<Runtime> = Lazy::new(|| {
     tokio::runtime::Builder::new_multi_thread()
@@ -123,6 +307,11 @@ pub async fn search(
         trace_id.to_string()
     };
 
+    let _gate = enter_search_gate(&trace_id)?;
+    if let Some(segment_ids) = in_req.query.segment_ids.as_ref() {
+        check_segment_ids(user_id.as_deref(), segment_ids).await?;
+    }
+
     #[cfg(feature = "enterprise")]
     {
         let sql = Some(in_req.query.sql.clone());
@@ -540,6 +729,7 @@ pub async fn search_multi(
         )
         .await;
     }
+
     Ok(multi_res)
 }
 
@@ -554,11 +744,19 @@ pub async fn search_partition(
 ) -> Result<search::SearchPartitionResponse, Error> {
     let start = std::time::Instant::now();
     let cfg = get_config();
+    let _gate = enter_search_gate(trace_id)?;
 
     let query = cluster_rpc::SearchQuery {
         start_time: req.start_time,
         end_time: req.end_time,
         sql: req.sql.to_string(),
+        params: req
+            .params
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, value)| (name, value.to_string()))
+            .collect(),
         ..Default::default()
     };
     let sql = Sql::new(&query, org_id, stream_type).await?;
@@ -661,6 +859,8 @@ pub async fn search_partition(
                 id: Utc::now().timestamp_micros(),
                 records,
                 original_size,
+                min_ts: 0,
+                max_ts: 0,
                 deleted: false,
             });
         }
@@ -750,23 +950,49 @@ pub async fn search_partition(
         };
     }
 
+    let strategy = req.partition_strategy.unwrap_or_else(|| {
+        search::SearchPartitionStrategy::from(&cfg.common.feature_search_partition_strategy)
+    });
+    // approximated stream stats carry no time range, so those fall back to time slices
+    let partition_by_size = strategy == search::SearchPartitionStrategy::Size
+        && part_num > 1
+        && files.iter().all(|f| f.max_ts > 0);
+
     // Generate partitions by DESC order
     let mut partitions = Vec::with_capacity(part_num);
-    let mut end = req.end_time;
-    let mut last_partition_step = end % min_step;
-    let duration = req.end_time - req.start_time;
-    while end > req.start_time {
-        let mut start = max(end - step, req.start_time);
-        if last_partition_step > 0 && duration > min_step && part_num > 1 {
-            partitions.push([end - last_partition_step, end]);
-            start -= last_partition_step;
-            end -= last_partition_step;
+    if partition_by_size {
+        let max_query_range = if skip_max_query_range || max_query_range <= 0 {
+            0
+        } else if min_step < max_query_range {
+            max_query_range - max_query_range % min_step
         } else {
-            start = max(start - last_partition_step, req.start_time);
+            max_query_range
+        };
+        partitions = generate_size_partitions(
+            &files,
+            req.start_time,
+            req.end_time,
+            part_num,
+            min_step,
+            max_query_range,
+        );
+    } else {
+        let mut end = req.end_time;
+        let mut last_partition_step = end % min_step;
+        let duration = req.end_time - req.start_time;
+        while end > req.start_time {
+            let mut start = max(end - step, req.start_time);
+            if last_partition_step > 0 && duration > min_step && part_num > 1 {
+                partitions.push([end - last_partition_step, end]);
+                start -= last_partition_step;
+                end -= last_partition_step;
+            } else {
+                start = max(start - last_partition_step, req.start_time);
+            }
+            partitions.push([start, end]);
+            end = start;
+            last_partition_step = 0;
         }
-        partitions.push([start, end]);
-        end = start;
-        last_partition_step = 0;
     }
     if partitions.is_empty() {
         partitions.push([req.start_time, req.end_time]);
@@ -784,6 +1010,60 @@ pub async fn search_partition(
     Ok(resp)
 }
 
+/// Splits `[start_time, end_time]` into at most `part_num` partitions in DESC order, each
+/// holding roughly the same number of records according to the file_list stats.
+fn generate_size_partitions(
+    files: &[infra::file_list::FileId],
+    start_time: i64,
+    end_time: i64,
+    part_num: usize,
+    min_step: i64,
+    max_query_range: i64,
+) -> Vec<[i64; 2]> {
+    let mut files = files
+        .iter()
+        .filter(|f| f.max_ts >= start_time && f.min_ts <= end_time)
+        .collect::<Vec<_>>();
+    files.sort_by(|a, b| b.max_ts.cmp(&a.max_ts));
+    let total_records = files.iter().map(|f| f.records).sum::<i64>();
+    let target = max(1, total_records / part_num as i64);
+
+    let mut partitions = Vec::with_capacity(part_num);
+    let mut end = end_time;
+    let mut records = 0;
+    for f in files {
+        records += f.records;
+        if records < target || partitions.len() + 1 >= part_num {
+            continue;
+        }
+        let mut start = f.min_ts;
+        if min_step > 0 {
+            start -= start % min_step;
+        }
+        if start <= start_time || start >= end {
+            continue;
+        }
+        partitions.push([start, end]);
+        end = start;
+        records = 0;
+    }
+    partitions.push([start_time, end]);
+
+    if max_query_range <= 0 {
+        return partitions;
+    }
+    // this is to ensure we create partitions less than max_query_range
+    let mut split = Vec::with_capacity(partitions.len());
+    for [start, mut end] in partitions {
+        while end - start > max_query_range {
+            split.push([end - max_query_range, end]);
+            end -= max_query_range;
+        }
+        split.push([start, end]);
+    }
+    split
+}
+
 #[cfg(feature = "enterprise")]
 pub async fn query_status() -> Result<search::QueryStatusResponse, Error> {
     // get nodes from cluster
@@ -796,22 +1076,43 @@ pub async fn query_status() -> Result<search::QueryStatusResponse, Error> {
     };
     // sort nodes by node_id this will improve hit cache ratio
     nodes.dedup_by(|a, b| a.grpc_addr == b.grpc_addr);
-    let nodes = nodes;
+    let nodes = nodes
+        .into_iter()
+        .map(|node| Arc::new(node) as Arc<dyn NodeInfo>)
+        .collect();
+    query_status_on_nodes(nodes).await
+}
 
+/// Query status of the nodes of all the clusters of the super cluster.
+#[cfg(feature = "enterprise")]
+pub async fn super_cluster_query_status(
+    trace_id: &str,
+) -> Result<search::QueryStatusResponse, Error> {
+    let nodes = o2_enterprise::enterprise::super_cluster::search::get_cluster_nodes(
+        trace_id,
+        vec![],
+        vec![],
+    )
+    .await?;
+    query_status_on_nodes(nodes).await
+}
+
+#[cfg(feature = "enterprise")]
+async fn query_status_on_nodes(
+    nodes: Vec<Arc<dyn NodeInfo>>,
+) -> Result<search::QueryStatusResponse, Error> {
     // make cluster request
     let mut tasks = Vec::new();
-    for node in nodes.iter().cloned() {
-        let node_addr = node.grpc_addr.clone();
+    for node in nodes.into_iter() {
+        let node_addr = node.get_grpc_addr();
         let grpc_span = info_span!(
             "service:search:cluster:grpc_query_status",
-            node_id = node.id,
             node_addr = node_addr.as_str(),
         );
 
         let task = tokio::task::spawn(
             async move {
                 let mut request = tonic::Request::new(proto::cluster_rpc::QueryStatusRequest {});
-                let node = Arc::new(node) as _;
                 let mut client = make_grpc_search_client(&mut request, &node).await?;
                 let response = match client.query_status(request).await {
                     Ok(res) => res.into_inner(),
@@ -852,9 +1153,20 @@ pub async fn query_status() -> Result<search::QueryStatusResponse, Error> {
         }
     }
 
+    Ok(query_status_from_results(
+        results.into_iter().flat_map(|v| v.status.into_iter()),
+    ))
+}
+
+/// Converts the task status reported by the nodes, a query running on several nodes is
+/// reported once.
+#[cfg(feature = "enterprise")]
+pub(crate) fn query_status_from_results(
+    results: impl Iterator<Item = cluster_rpc::QueryStatus>,
+) -> search::QueryStatusResponse {
     let mut status = vec![];
     let mut set = HashSet::new();
-    for result in results.into_iter().flat_map(|v| v.status.into_iter()) {
+    for result in results {
         if set.contains(&result.trace_id) {
             continue;
         } else {
@@ -910,7 +1222,7 @@ pub async fn query_status() -> Result<search::QueryStatusResponse, Error> {
         });
     }
 
-    Ok(search::QueryStatusResponse { status })
+    search::QueryStatusResponse { status }
 }
 
 #[cfg(feature = "enterprise")]
@@ -1014,8 +1326,8 @@ pub async fn match_file(
 
     // slow path
     let mut filters = generate_filter_from_equal_items(equal_items);
-    let partition_keys: HashMap<&String, &StreamPartition> =
-        partition_keys.iter().map(|v| (&v.field, v)).collect();
+    let partition_keys: HashMap<String, &StreamPartition> =
+        partition_keys.iter().map(|v| (v.column(), v)).collect();
     for (key, value) in filters.iter_mut() {
         if let Some(partition_key) = partition_keys.get(key) {
             for val in value.iter_mut() {
@@ -1094,6 +1406,42 @@ pub async fn match_source(
     true
 }
 
+/// Runs the query stored in a saved view, the parts the request query sets
+/// take precedence over the stored ones.
+pub async fn apply_saved_view(
+    org_id: &str,
+    view_id: &str,
+    query: &mut search::Query,
+) -> Result<(), Error> {
+    let view = crate::service::db::saved_view::get_view(org_id, view_id)
+        .await
+        .map_err(|_| Error::Message(format!("saved view {view_id} not found")))?;
+    let view_query = view
+        .search_query()
+        .map_err(|e| Error::Message(format!("saved view {view_id}: {e}")))?;
+    merge_view_query(query, view_query);
+    Ok(())
+}
+
+fn merge_view_query(query: &mut search::Query, view_query: search::Query) {
+    if query.sql.is_empty() {
+        query.sql = view_query.sql;
+    }
+    if query.start_time == 0 && query.end_time == 0 {
+        query.start_time = view_query.start_time;
+        query.end_time = view_query.end_time;
+    }
+    if query.from == 0 {
+        query.from = view_query.from;
+    }
+    if query.size == search::default_size() {
+        query.size = view_query.size;
+    }
+    if query.query_fn.is_none() {
+        query.query_fn = view_query.query_fn;
+    }
+}
+
 pub fn server_internal_error(error: impl ToString) -> Error {
     Error::ErrorCode(ErrorCodes::ServerInternalError(error.to_string()))
 }
@@ -1123,6 +1471,8 @@ pub async fn search_partition_multi(
                 clusters: req.clusters.clone(),
                 query_fn: req.query_fn.clone(),
                 streaming_output: req.streaming_output,
+                partition_strategy: req.partition_strategy,
+                params: None,
             },
             false,
         )
@@ -1238,6 +1588,146 @@ pub fn generate_filter_from_quick_text(
 mod tests {
     use super::*;
 
+    #[cfg(not(feature = "enterprise"))]
+    #[tokio::test]
+    async fn test_acquire_permit() {
+        let queue = Arc::new(Semaphore::new(2));
+        let timeout = std::time::Duration::from_millis(50);
+
+        // searches proceed concurrently up to the permit count
+        let p1 = acquire_permit(queue.clone(), timeout).await.unwrap();
+        let p2 = acquire_permit(queue.clone(), timeout).await.unwrap();
+        assert_eq!(queue.available_permits(), 0);
+
+        // the next one waits and gives up after the timeout
+        assert!(acquire_permit(queue.clone(), timeout).await.is_err());
+
+        // and proceeds as soon as a running search finishes
+        drop(p1);
+        let p3 = acquire_permit(queue.clone(), timeout).await.unwrap();
+        drop((p2, p3));
+        assert_eq!(queue.available_permits(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_gate_close() {
+        let gate = Arc::new(SearchGate::default());
+        let timeout = std::time::Duration::from_secs(5);
+
+        // an in-flight search finishing while the node shuts down
+        let guard = gate.enter().unwrap();
+        let search = tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            drop(guard);
+        });
+        assert!(gate.close(timeout).await);
+        assert_eq!(gate.in_flight(), 0);
+        search.await.unwrap();
+
+        // no new searches are accepted afterwards
+        assert!(gate.enter().is_none());
+        assert_eq!(gate.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_search_gate_close_timeout() {
+        let gate = Arc::new(SearchGate::default());
+
+        // a search that outlives the shutdown timeout is left to be aborted
+        let guard = gate.enter().unwrap();
+        assert!(!gate.close(std::time::Duration::from_millis(50)).await);
+        assert_eq!(gate.in_flight(), 1);
+        drop(guard);
+        assert_eq!(gate.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_check_segment_ids_requires_root() {
+        let segment_ids = std::collections::HashMap::from([(
+            "files/default/logs/t/a.parquet".to_string(),
+            vec![(0, usize::MAX)],
+        )]);
+        for user_id in [None, Some("user@example.com")] {
+            let err = check_segment_ids(user_id, &segment_ids).await.unwrap_err();
+            assert!(
+                matches!(err, Error::ErrorCode(ErrorCodes::InvalidParams(_))),
+                "{err}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_gate_track() {
+        let gate = Arc::new(SearchGate::default());
+        let leader = gate.enter().unwrap();
+        let closing = {
+            let gate = gate.clone();
+            tokio::spawn(async move { gate.close(std::time::Duration::from_secs(5)).await })
+        };
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        // the node side of the running search is still let in and waited for
+        assert!(gate.enter().is_none());
+        let follower = gate.track();
+        drop(leader);
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!closing.is_finished());
+        drop(follower);
+        assert!(closing.await.unwrap());
+    }
+
+    #[test]
+    fn test_generate_size_partitions() {
+        let hour = 3600 * 1_000_000;
+        let base = 1_700_006_400_000_000;
+        let mut files = Vec::new();
+        // light ingestion for the first 9 hours
+        for i in 0..9 {
+            files.push(infra::file_list::FileId {
+                id: i,
+                records: 10,
+                min_ts: base + i * hour,
+                max_ts: base + (i + 1) * hour - 1,
+                ..Default::default()
+            });
+        }
+        // heavy ingestion in the last hour
+        for i in 0..10 {
+            let min_ts = base + 9 * hour + i * hour / 10;
+            files.push(infra::file_list::FileId {
+                id: 100 + i,
+                records: 1000,
+                min_ts,
+                max_ts: min_ts + hour / 10 - 1,
+                ..Default::default()
+            });
+        }
+        let (start_time, end_time) = (base, base + 10 * hour);
+
+        let partitions = generate_size_partitions(&files, start_time, end_time, 4, 1_000_000, 0);
+        assert_eq!(partitions.len(), 4);
+        assert_eq!(partitions.first().unwrap()[1], end_time);
+        assert_eq!(partitions.last().unwrap()[0], start_time);
+        let target = files.iter().map(|f| f.records).sum::<i64>() / 4;
+        for [start, end] in partitions.iter() {
+            let records = files
+                .iter()
+                .filter(|f| f.max_ts >= *start && f.max_ts < *end)
+                .map(|f| f.records)
+                .sum::<i64>();
+            assert!(records > 0);
+            assert!(records <= target + 1000, "unbalanced partition: {records}");
+        }
+
+        // every partition stays within max_query_range
+        let partitions =
+            generate_size_partitions(&files, start_time, end_time, 4, 1_000_000, 2 * hour);
+        assert!(partitions
+            .iter()
+            .all(|[start, end]| end - start <= 2 * hour));
+        assert_eq!(partitions.last().unwrap()[0], start_time);
+    }
+
     #[test]
     fn test_matches_by_partition_key_with_sql() {
         use config::meta::sql;
@@ -1332,4 +1822,107 @@ mod tests {
             );
         }
     }
+
+    #[tokio::test]
+    async fn test_apply_saved_view() {
+        let save = |view_name: &str, data: json::Value| {
+            let view = crate::common::meta::saved_view::CreateViewRequest {
+                data,
+                view_name: view_name.to_string(),
+            };
+            async move { crate::service::db::saved_view::set_view("saved_view_org", &view).await }
+        };
+        // the logs page state saved by the UI, in filter mode
+        let view = save(
+            "errors",
+            json::json!({
+                "meta": { "sqlMode": false, "toggleFunction": true },
+                "data": {
+                    "query": "level = 'error'",
+                    "stream": { "selectedStream": ["default"], "streamType": "logs" },
+                    "datetime": {
+                        "type": "absolute",
+                        "startTime": 100,
+                        "endTime": 200,
+                        "relativeTimePeriod": "15m"
+                    },
+                    "tempFunctionContent": ".a = 1",
+                    "timezone": "UTC"
+                }
+            }),
+        )
+        .await
+        .unwrap();
+
+        // the stored query runs when the request only references the view
+        let mut query: search::Query = json::from_str(r#"{"sql": ""}"#).unwrap();
+        apply_saved_view("saved_view_org", &view.view_id, &mut query)
+            .await
+            .unwrap();
+        assert_eq!(query.sql, "SELECT * FROM \"default\" WHERE level = 'error'");
+        assert_eq!((query.start_time, query.end_time), (100, 200));
+        assert_eq!(query.query_fn.as_deref(), Some(".a = 1"));
+
+        // the request overrides the stored time range
+        let mut query: search::Query =
+            json::from_str(r#"{"sql": "", "start_time": 300, "end_time": 400}"#).unwrap();
+        apply_saved_view("saved_view_org", &view.view_id, &mut query)
+            .await
+            .unwrap();
+        assert_eq!((query.start_time, query.end_time), (300, 400));
+        assert_eq!(query.sql, "SELECT * FROM \"default\" WHERE level = 'error'");
+
+        // sql mode with a relative time range, saved by an older UI
+        let view = save(
+            "sql",
+            json::json!({
+                "meta": { "sqlMode": true, "toggleFunction": false },
+                "data": {
+                    "query": "SELECT count(*) FROM \"k8s\"",
+                    "stream": {
+                        "selectedStream": { "label": "k8s", "value": "k8s" },
+                        "streamType": "logs"
+                    },
+                    "datetime": { "type": "relative", "relativeTimePeriod": "2h" },
+                    "tempFunctionContent": ".a = 1"
+                }
+            }),
+        )
+        .await
+        .unwrap();
+        let mut query: search::Query = json::from_str(r#"{"sql": ""}"#).unwrap();
+        apply_saved_view("saved_view_org", &view.view_id, &mut query)
+            .await
+            .unwrap();
+        assert_eq!(query.sql, "SELECT count(*) FROM \"k8s\"");
+        assert_eq!(query.end_time - query.start_time, 2 * 3600 * 1_000_000);
+        assert!(query.query_fn.is_none());
+
+        // a filter over several streams can't be turned into a single sql
+        let view = save(
+            "multi",
+            json::json!({
+                "meta": { "sqlMode": false },
+                "data": {
+                    "query": "",
+                    "stream": { "selectedStream": ["a", "b"] },
+                    "datetime": { "type": "relative", "relativeTimePeriod": "15m" }
+                }
+            }),
+        )
+        .await
+        .unwrap();
+        let mut query: search::Query = json::from_str(r#"{"sql": ""}"#).unwrap();
+        assert!(apply_saved_view("saved_view_org", &view.view_id, &mut query)
+            .await
+            .is_err());
+
+        let mut query = search::Query::default();
+        assert!(apply_saved_view("saved_view_org", "missing", &mut query)
+            .await
+            .is_err());
+
+        // the sql stays required everywhere else
+        assert!(json::from_str::<search::Query>("{}").is_err());
+    }
 }
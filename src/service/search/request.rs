@@ -29,6 +29,7 @@ pub struct Request {
     pub use_inverted_index: bool,
     pub streaming_output: bool,
     pub streaming_id: Option<String>,
+    pub quick_scan: bool,
 }
 
 impl Default for Request {
@@ -45,6 +46,7 @@ impl Default for Request {
             use_inverted_index: false,
             streaming_output: false,
             streaming_id: None,
+            quick_scan: false,
         }
     }
 }
@@ -72,6 +74,7 @@ impl Request {
             use_inverted_index: false,
             streaming_output: false,
             streaming_id: None,
+            quick_scan: false,
         }
     }
 
@@ -99,6 +102,10 @@ impl Request {
         self.streaming_output = streaming_output;
         self.streaming_id = streaming_id;
     }
+
+    pub fn set_quick_scan(&mut self, quick_scan: bool) {
+        self.quick_scan = quick_scan;
+    }
 }
 
 impl From<FlightSearchRequest> for Request {
@@ -115,6 +122,7 @@ impl From<FlightSearchRequest> for Request {
             use_inverted_index: req.index_info.use_inverted_index,
             streaming_output: false,
             streaming_id: None,
+            quick_scan: req.super_cluster_info.quick_scan,
         }
     }
 }
@@ -20,11 +20,11 @@ use chrono::Duration;
 use config::{
     get_config,
     meta::{
-        inverted_index::InvertedIndexOptimizeMode,
+        inverted_index::{InvertedIndexFormat, InvertedIndexOptimizeMode},
         sql::{resolve_stream_names_with_type, OrderBy, Sql as MetaSql, TableReferenceExt},
         stream::StreamType,
     },
-    utils::sql::AGGREGATE_UDF_LIST,
+    utils::{json, sql::AGGREGATE_UDF_LIST},
     ID_COL_NAME, ORIGINAL_DATA_COL_NAME, TIMESTAMP_COL_NAME,
 };
 use datafusion::{arrow::datatypes::Schema, common::TableReference};
@@ -43,8 +43,8 @@ use sqlparser::{
     ast::{
         BinaryOperator, DuplicateTreatment, Expr, Function, FunctionArg, FunctionArgExpr,
         FunctionArgumentList, FunctionArguments, GroupByExpr, Ident, ObjectName, OrderByExpr,
-        Query, Select, SelectItem, SetExpr, Statement, TableFactor, TableWithJoins, VisitMut,
-        VisitorMut,
+        Query, Select, SelectItem, SetExpr, Statement, TableFactor, TableWithJoins, Value,
+        VisitMut, VisitorMut,
     },
     dialect::PostgreSqlDialect,
     parser::Parser,
@@ -58,6 +58,7 @@ use super::{
         MATCH_ALL_UDF_NAME,
     },
     index::{get_index_condition_from_expr, IndexCondition},
+    masking::{add_field_masks, get_field_masks},
     request::Request,
     utils::{is_field, is_value, split_conjunction, trim_quotes},
 };
@@ -96,7 +97,32 @@ pub struct Sql {
 
 impl Sql {
     pub async fn new_from_req(req: &Request, query: &SearchQuery) -> Result<Sql, Error> {
-        Self::new(query, &req.org_id, req.stream_type).await
+        let masks = get_field_masks(
+            &req.org_id,
+            req.user_id.as_deref(),
+            req.stream_type,
+            &query.sql,
+        )
+        .await?;
+        let filters = get_row_filters(
+            &req.org_id,
+            req.user_id.as_deref(),
+            req.stream_type,
+            &query.sql,
+        )
+        .await?;
+        if masks.is_empty() && filters.is_empty() {
+            return Self::new(query, &req.org_id, req.stream_type).await;
+        }
+        let mut query = query.clone();
+        // masks first, the row filters still see the clear values
+        if !masks.is_empty() {
+            query.sql = add_field_masks(&query.sql, &masks)?;
+        }
+        if !filters.is_empty() {
+            query.sql = add_row_filters(&query.sql, &filters)?;
+        }
+        Self::new(&query, &req.org_id, req.stream_type).await
     }
 
     pub async fn new(
@@ -130,7 +156,27 @@ impl Sql {
         let mut statement = Parser::parse_sql(&PostgreSqlDialect {}, &sql)
             .map_err(|e| Error::Message(e.to_string()))?
             .pop()
-            .unwrap();
+            .ok_or_else(|| Error::Message("sql is empty".to_string()))?;
+
+        // bind the placeholders as literals, so the values can't change the query
+        let mut params_visitor = ParamsVisitor::new(&query.params);
+        if let ControlFlow::Break(e) = statement.visit(&mut params_visitor) {
+            return Err(Error::Message(e));
+        }
+
+        // sort and histogram by the stream's own timestamp field use the event time it was
+        // ingested into
+        let timestamp_fields = total_schemas
+            .values()
+            .filter_map(|schema| {
+                infra::schema::unwrap_stream_settings(schema.schema())
+                    .and_then(|settings| settings.timestamp_field)
+            })
+            .collect::<HashSet<_>>();
+        if !timestamp_fields.is_empty() {
+            let mut timestamp_field_visitor = TimestampFieldVisitor::new(&timestamp_fields);
+            statement.visit(&mut timestamp_field_visitor);
+        }
 
         // 2. rewrite track_total_hits
         if query.track_total_hits {
@@ -150,6 +196,7 @@ impl Sql {
             .collect::<Vec<_>>();
         let group_by = column_visitor.group_by;
         let mut order_by = column_visitor.order_by;
+        let has_order_by = !order_by.is_empty();
 
         // check if need sort by time
         if order_by.is_empty()
@@ -228,7 +275,17 @@ impl Sql {
         statement.visit(&mut histogram_interval_visitor);
 
         // NOTE: only this place modify the sql
-        // 10. add _timestamp and _o2_id if need
+        // 10. add _timestamp and _o2_id if need, and the default sort
+        let default_sort = cfg.common.query_default_sort_enabled
+            && !has_order_by
+            && !query.track_total_hits
+            && used_schemas.values().all(|schema| {
+                !infra::schema::unwrap_stream_settings(schema.schema())
+                    .is_some_and(|s| s.disable_default_sort)
+            });
+        if default_sort {
+            add_default_order_by(&mut statement);
+        }
         if !is_complex_query(&mut statement) {
             let mut add_timestamp_visitor = AddTimestampVisitor::new();
             statement.visit(&mut add_timestamp_visitor);
@@ -243,7 +300,7 @@ impl Sql {
         let mut index_condition = None;
         let mut can_optimize = false;
         #[allow(deprecated)]
-        if cfg.common.inverted_index_search_format.eq("tantivy")
+        if InvertedIndexFormat::from(&cfg.common.inverted_index_search_format).use_tantivy()
             && stream_names.len() == 1
             && cfg.common.inverted_index_enabled
             && use_inverted_index
@@ -981,6 +1038,43 @@ impl VisitorMut for MatchVisitor {
     }
 }
 
+/// replace the `$name` and `$1` placeholders with the literal of their value
+struct ParamsVisitor<'a> {
+    params: &'a std::collections::HashMap<String, String>,
+}
+
+impl<'a> ParamsVisitor<'a> {
+    fn new(params: &'a std::collections::HashMap<String, String>) -> Self {
+        Self { params }
+    }
+}
+
+impl VisitorMut for ParamsVisitor<'_> {
+    type Break = String;
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        if let Expr::Value(Value::Placeholder(placeholder)) = expr {
+            let name = placeholder.trim_start_matches(['$', '?']);
+            let Some(value) = self.params.get(name) else {
+                return ControlFlow::Break(format!("missing value of parameter {placeholder}"));
+            };
+            let value = match json::from_str::<json::Value>(value) {
+                Ok(json::Value::Null) => Value::Null,
+                Ok(json::Value::Bool(v)) => Value::Boolean(v),
+                Ok(json::Value::Number(v)) => Value::Number(v.to_string(), false),
+                Ok(json::Value::String(v)) => Value::SingleQuotedString(v),
+                _ => {
+                    return ControlFlow::Break(format!(
+                        "parameter {placeholder} must be a string, number, bool or null"
+                    ));
+                }
+            };
+            *expr = Expr::Value(value);
+        }
+        ControlFlow::Continue(())
+    }
+}
+
 struct FieldNameVisitor {
     pub field_names: HashSet<String>,
 }
@@ -1004,6 +1098,241 @@ impl VisitorMut for FieldNameVisitor {
     }
 }
 
+/// Parses the condition of a stream row filter, like `tenant = 'acme'`.
+pub(crate) fn parse_row_filter(filter: &str) -> Result<Expr, Error> {
+    Parser::new(&PostgreSqlDialect {})
+        .try_with_sql(filter)
+        .and_then(|mut parser| parser.parse_expr())
+        .map_err(|e| Error::Message(format!("invalid row filter {filter}: {e}")))
+}
+
+/// Collects the row filters of the user's roles for the streams of the query,
+/// keyed by stream name. The filters of several roles are ORed, see
+/// [get_stream_row_filter] for the users none of whose roles has a filter.
+/// Root users and internal searches without a user are not filtered.
+pub(crate) async fn get_row_filters(
+    org_id: &str,
+    user_id: Option<&str>,
+    stream_type: StreamType,
+    sql: &str,
+) -> Result<HashMap<String, String>, Error> {
+    let mut row_filters = HashMap::new();
+    let Some(user_id) = user_id else {
+        return Ok(row_filters);
+    };
+    if crate::common::utils::auth::is_root_user(user_id) {
+        return Ok(row_filters);
+    }
+    let stream_names =
+        resolve_stream_names_with_type(sql).map_err(|e| Error::Message(e.to_string()))?;
+    let mut roles = None;
+    for stream in stream_names.iter() {
+        let stream_name = stream.stream_name();
+        let stream_type = stream.get_stream_type(stream_type);
+        if let Some(filter) =
+            get_stream_row_filter(org_id, user_id, stream_type, &stream_name, &mut roles).await
+        {
+            row_filters.insert(stream_name, filter);
+        }
+    }
+    Ok(row_filters)
+}
+
+/// The row filter of the user's roles for a stream, `None` when the stream
+/// has no row filters or when none of the user's roles has one and the user is
+/// [privileged](super::masking::is_stream_privileged) on the stream. The other
+/// users without a filter get no rows. `roles` caches the user's roles across
+/// streams.
+pub(crate) async fn get_stream_row_filter(
+    org_id: &str,
+    user_id: &str,
+    stream_type: StreamType,
+    stream_name: &str,
+    roles: &mut Option<Vec<String>>,
+) -> Option<String> {
+    let settings = infra::schema::get_settings(org_id, stream_name, stream_type).await?;
+    if settings.row_filters.is_empty() {
+        return None;
+    }
+    if roles.is_none() {
+        *roles = Some(get_user_roles(org_id, user_id).await);
+    }
+    let filters = roles
+        .as_ref()
+        .unwrap()
+        .iter()
+        .filter_map(|role| settings.row_filters.get(role))
+        .map(|filter| format!("({filter})"))
+        .collect::<Vec<_>>();
+    if !filters.is_empty() {
+        return Some(filters.join(" OR "));
+    }
+    if super::masking::is_stream_privileged(org_id, user_id, stream_type, stream_name).await {
+        None
+    } else {
+        Some("FALSE".to_string())
+    }
+}
+
+/// The row filters applied to the user's search, in a stable order, for the
+/// result cache key: users with different filters must not share cached
+/// results. `None` when the search isn't filtered.
+pub(crate) async fn row_filters_cache_key(
+    org_id: &str,
+    user_id: Option<&str>,
+    stream_type: StreamType,
+    sql: &str,
+) -> Result<Option<String>, Error> {
+    let filters = get_row_filters(org_id, user_id, stream_type, sql).await?;
+    if filters.is_empty() {
+        return Ok(None);
+    }
+    let filters = filters
+        .into_iter()
+        .collect::<std::collections::BTreeMap<_, _>>();
+    Ok(Some(
+        filters
+            .iter()
+            .map(|(stream, filter)| format!("{stream}:{filter}"))
+            .collect::<Vec<_>>()
+            .join(";"),
+    ))
+}
+
+async fn get_user_roles(org_id: &str, user_id: &str) -> Vec<String> {
+    let mut roles = Vec::new();
+    if let Some(user) = crate::service::users::get_user(Some(org_id), user_id).await {
+        roles.push(user.role.to_string());
+    }
+    #[cfg(feature = "enterprise")]
+    if o2_openfga::config::get_config().enabled {
+        roles.extend(o2_openfga::authorizer::roles::get_roles_for_user(user_id).await);
+    }
+    roles
+}
+
+/// ANDs the row filter of each stream to the WHERE clause of every SELECT
+/// reading from it, including subqueries and UNION branches.
+pub(crate) fn add_row_filters(
+    sql: &str,
+    filters: &HashMap<String, String>,
+) -> Result<String, Error> {
+    let filters = filters
+        .iter()
+        .map(|(stream, filter)| Ok((stream.clone(), parse_row_filter(filter)?)))
+        .collect::<Result<HashMap<_, _>, Error>>()?;
+    let mut statement = Parser::parse_sql(&PostgreSqlDialect {}, sql)
+        .map_err(|e| Error::Message(e.to_string()))?
+        .pop()
+        .ok_or_else(|| Error::Message("sql is empty".to_string()))?;
+    let mut visitor = RowFilterVisitor::new(&filters);
+    statement.visit(&mut visitor);
+    Ok(statement.to_string())
+}
+
+struct RowFilterVisitor<'a> {
+    filters: &'a HashMap<String, Expr>,
+}
+
+impl<'a> RowFilterVisitor<'a> {
+    fn new(filters: &'a HashMap<String, Expr>) -> Self {
+        Self { filters }
+    }
+
+    fn add_filters(&self, body: &mut SetExpr) {
+        match body {
+            SetExpr::Select(select) => {
+                // (stream name, name the select refers to it by)
+                let tables = select
+                    .from
+                    .iter()
+                    .flat_map(|from| {
+                        std::iter::once(&from.relation)
+                            .chain(from.joins.iter().map(|j| &j.relation))
+                    })
+                    .filter_map(|relation| match relation {
+                        TableFactor::Table { name, alias, .. } => {
+                            let reference = match alias {
+                                Some(alias) => ObjectName(vec![alias.name.clone()]),
+                                None => name.clone(),
+                            };
+                            name.0.last().map(|v| (v.value.clone(), reference))
+                        }
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                for (table, reference) in tables {
+                    let Some(filter) = self.filters.get(&table) else {
+                        continue;
+                    };
+                    // bind the columns of the filter to its stream, not to
+                    // another table of a join
+                    let mut filter = filter.clone();
+                    let _ = filter.visit(&mut QualifyColumnsVisitor {
+                        reference: &reference,
+                        depth: 0,
+                    });
+                    let filter = Expr::Nested(Box::new(filter));
+                    select.selection = Some(match select.selection.take() {
+                        Some(selection) => Expr::BinaryOp {
+                            left: Box::new(Expr::Nested(Box::new(selection))),
+                            op: BinaryOperator::And,
+                            right: Box::new(filter),
+                        },
+                        None => filter,
+                    });
+                }
+            }
+            SetExpr::SetOperation { left, right, .. } => {
+                self.add_filters(left);
+                self.add_filters(right);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl VisitorMut for RowFilterVisitor<'_> {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        self.add_filters(query.body.as_mut());
+        ControlFlow::Continue(())
+    }
+}
+
+/// Qualifies the bare columns of an expression with a table reference,
+/// leaving subqueries to their own tables.
+struct QualifyColumnsVisitor<'a> {
+    reference: &'a ObjectName,
+    depth: usize,
+}
+
+impl VisitorMut for QualifyColumnsVisitor<'_> {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, _query: &mut Query) -> ControlFlow<Self::Break> {
+        self.depth += 1;
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &mut Query) -> ControlFlow<Self::Break> {
+        self.depth -= 1;
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        if self.depth == 0 {
+            if let Expr::Identifier(ident) = expr {
+                let mut idents = self.reference.0.clone();
+                idents.push(ident.clone());
+                *expr = Expr::CompoundIdentifier(idents);
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
 // add _timestamp to the query like `SELECT name FROM t` -> `SELECT _timestamp, name FROM t`
 struct AddTimestampVisitor {}
 
@@ -1316,6 +1645,62 @@ impl VisitorMut for HistogramIntervalVistor {
     }
 }
 
+/// Replaces the custom timestamp fields of the streams by `_timestamp` in ORDER BY and as the
+/// field of `histogram()`, other references still read the field as ingested.
+struct TimestampFieldVisitor<'a> {
+    fields: &'a HashSet<String>,
+}
+
+impl<'a> TimestampFieldVisitor<'a> {
+    fn new(fields: &'a HashSet<String>) -> Self {
+        Self { fields }
+    }
+
+    fn resolve(&self, expr: &mut Expr) {
+        match expr {
+            Expr::Identifier(ident) if self.fields.contains(&ident.value) => {
+                *ident = Ident::new(TIMESTAMP_COL_NAME);
+            }
+            Expr::CompoundIdentifier(idents)
+                if idents
+                    .last()
+                    .is_some_and(|ident| self.fields.contains(&ident.value)) =>
+            {
+                *idents.last_mut().unwrap() = Ident::new(TIMESTAMP_COL_NAME);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl VisitorMut for TimestampFieldVisitor<'_> {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        if let Some(order_by) = query.order_by.as_mut() {
+            for item in order_by.exprs.iter_mut() {
+                self.resolve(&mut item.expr);
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        if let Expr::Function(func) = expr {
+            if func.name.to_string().to_lowercase() == "histogram" {
+                if let FunctionArguments::List(list) = &mut func.args {
+                    if let Some(FunctionArg::Unnamed(FunctionArgExpr::Expr(field))) =
+                        list.args.first_mut()
+                    {
+                        self.resolve(field);
+                    }
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
 struct TrackTotalHitsVisitor {}
 
 impl TrackTotalHitsVisitor {
@@ -1670,7 +2055,7 @@ pub fn check_or_add_order_by_timestamp(sql: &str, is_asc: bool) -> infra::errors
     let mut statement = Parser::parse_sql(&PostgreSqlDialect {}, sql)
         .map_err(|e| Error::Message(e.to_string()))?
         .pop()
-        .unwrap();
+        .ok_or_else(|| Error::Message("sql is empty".to_string()))?;
     if is_complex_query(&mut statement) {
         return Ok(sql.to_string());
     }
@@ -1679,6 +2064,223 @@ pub fn check_or_add_order_by_timestamp(sql: &str, is_asc: bool) -> infra::errors
     Ok(statement.to_string())
 }
 
+/// Adds `ORDER BY _timestamp DESC` to a simple query without ORDER BY, so the
+/// results come back in the same order whichever partitions served them.
+/// Returns whether the sort was added.
+fn add_default_order_by(statement: &mut Statement) -> bool {
+    if is_complex_query(statement) {
+        return false;
+    }
+    let Statement::Query(query) = statement else {
+        return false;
+    };
+    if query.order_by.is_some() {
+        return false;
+    }
+    let mut visitor = AddOrderingTermVisitor::new(TIMESTAMP_COL_NAME.to_string(), false);
+    query.visit(&mut visitor);
+    true
+}
+
+/// Add a condition to the sql to only return rows sorted after the `search_after` cursor.
+/// The cursor holds one value per ORDER BY column, if the sql has no ORDER BY it is sorted
+/// by timestamp descending. The record id `_o2_id` is added as the last ORDER BY column so
+/// rows sharing the other sort values are never skipped, when the stream stores it:
+/// `has_record_id`. Otherwise the cursor is made of the sort values alone, and the rows
+/// sharing all of them with the last hit of a page are skipped. Returns the new sql and the
+/// ORDER BY column names used to build the cursor of the next page.
+pub fn apply_search_after(
+    sql: &str,
+    search_after: &[json::Value],
+    has_record_id: bool,
+) -> infra::errors::Result<(String, Vec<String>)> {
+    let mut statement = Parser::parse_sql(&PostgreSqlDialect {}, sql)
+        .map_err(|e| Error::Message(e.to_string()))?
+        .pop()
+        .ok_or_else(|| Error::Message("search_after requires a SELECT query".to_string()))?;
+    if is_complex_query(&mut statement) {
+        return Err(Error::Message(
+            "search_after is not supported for aggregate or complex queries".to_string(),
+        ));
+    }
+    let Statement::Query(query) = &mut statement else {
+        return Err(Error::Message(
+            "search_after is only supported for SELECT queries".to_string(),
+        ));
+    };
+    let mut visitor = AddOrderingTermVisitor::new(TIMESTAMP_COL_NAME.to_string(), false);
+    query.visit(&mut visitor);
+    let Some(order_by) = query.order_by.as_mut() else {
+        return Err(Error::Message(
+            "search_after requires an ORDER BY".to_string(),
+        ));
+    };
+
+    // (column expr, column name, is_descending)
+    let mut sort_keys = Vec::new();
+    for item in order_by.exprs.iter() {
+        let name = match &item.expr {
+            Expr::Identifier(ident) => Some(ident.value.clone()),
+            Expr::CompoundIdentifier(idents) => idents.last().map(|ident| ident.value.clone()),
+            _ => None,
+        };
+        let Some(name) = name else {
+            return Err(Error::Message(format!(
+                "search_after requires ORDER BY on columns, got: {}",
+                item.expr
+            )));
+        };
+        sort_keys.push((item.expr.to_string(), name, item.asc == Some(false)));
+    }
+    // the unique tiebreaker
+    if has_record_id
+        && sort_keys
+            .last()
+            .is_none_or(|(_, name, _)| name != ID_COL_NAME)
+    {
+        order_by.exprs.push(OrderByExpr {
+            expr: Expr::Identifier(Ident::new(ID_COL_NAME)),
+            asc: Some(true),
+            nulls_first: None,
+            with_fill: None,
+        });
+        sort_keys.push((ID_COL_NAME.to_string(), ID_COL_NAME.to_string(), false));
+    }
+
+    // the cursor of the next page is read from the sort columns of the last hit
+    let SetExpr::Select(select) = query.body.as_mut() else {
+        return Err(Error::Message(
+            "search_after is only supported for SELECT queries".to_string(),
+        ));
+    };
+    let selects_all = select
+        .projection
+        .iter()
+        .any(|item| matches!(item, SelectItem::Wildcard(_)));
+    if !selects_all {
+        let selected = select
+            .projection
+            .iter()
+            .filter_map(|item| match item {
+                SelectItem::UnnamedExpr(Expr::Identifier(ident)) => Some(ident.value.as_str()),
+                SelectItem::UnnamedExpr(Expr::CompoundIdentifier(idents)) => {
+                    idents.last().map(|ident| ident.value.as_str())
+                }
+                SelectItem::ExprWithAlias { alias, .. } => Some(alias.value.as_str()),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        if has_record_id && !selected.contains(ID_COL_NAME) {
+            select
+                .projection
+                .push(SelectItem::UnnamedExpr(Expr::Identifier(Ident::new(
+                    ID_COL_NAME,
+                ))));
+        }
+        if let Some((_, name, _)) = sort_keys
+            .iter()
+            .find(|(_, name, _)| name != ID_COL_NAME && !selected.contains(name.as_str()))
+        {
+            return Err(Error::Message(format!(
+                "search_after requires the ORDER BY column {name} to be selected"
+            )));
+        }
+    }
+
+    let fields = sort_keys.iter().map(|(_, name, _)| name.clone()).collect();
+    if search_after.is_empty() {
+        return Ok((statement.to_string(), fields));
+    }
+    if search_after.len() != sort_keys.len() {
+        return Err(Error::Message(format!(
+            "search_after must have {} values to match the ORDER BY columns, got {}",
+            sort_keys.len(),
+            search_after.len()
+        )));
+    }
+
+    // (a, b) after (x, y) is: a > x OR (a = x AND b > y), with < for descending columns
+    let mut values = Vec::with_capacity(search_after.len());
+    for value in search_after {
+        values.push(match value {
+            json::Value::Number(v) => v.to_string(),
+            json::Value::String(v) => format!("'{}'", v.replace('\'', "''")),
+            json::Value::Bool(v) => v.to_string(),
+            v => {
+                return Err(Error::Message(format!(
+                    "search_after only supports number, string or bool values, got: {v}"
+                )));
+            }
+        });
+    }
+    let mut terms = Vec::with_capacity(sort_keys.len());
+    for (i, (col, _, is_desc)) in sort_keys.iter().enumerate() {
+        let mut term = sort_keys[..i]
+            .iter()
+            .zip(values.iter())
+            .map(|((col, ..), value)| format!("{col} = {value}"))
+            .collect::<Vec<_>>();
+        let op = if *is_desc { "<" } else { ">" };
+        term.push(format!("{col} {op} {}", values[i]));
+        terms.push(term.join(" AND "));
+    }
+    let condition = if terms.len() > 1 {
+        terms
+            .iter()
+            .map(|term| format!("({term})"))
+            .collect::<Vec<_>>()
+            .join(" OR ")
+    } else {
+        terms.join("")
+    };
+    let condition = Parser::new(&PostgreSqlDialect {})
+        .try_with_sql(&condition)
+        .and_then(|mut parser| parser.parse_expr())
+        .map_err(|e| Error::Message(e.to_string()))?;
+
+    let Statement::Query(query) = &mut statement else {
+        unreachable!("checked above");
+    };
+    let SetExpr::Select(select) = query.body.as_mut() else {
+        unreachable!("checked above");
+    };
+    select.selection = Some(match select.selection.take() {
+        Some(selection) => Expr::BinaryOp {
+            left: Box::new(Expr::Nested(Box::new(selection))),
+            op: BinaryOperator::And,
+            right: Box::new(Expr::Nested(Box::new(condition))),
+        },
+        None => condition,
+    });
+    Ok((statement.to_string(), fields))
+}
+
+/// Build the `search_after` cursor of the next page from the last hit, `None` when there
+/// are no hits left. A last hit missing a sort value can't be paged after, rather than
+/// ending the paging early it is an error.
+pub fn get_search_after_cursor(
+    hits: &[json::Value],
+    fields: &[String],
+) -> infra::errors::Result<Option<Vec<json::Value>>> {
+    let Some(last) = hits.last() else {
+        return Ok(None);
+    };
+    fields
+        .iter()
+        .map(|field| {
+            last.get(field)
+                .filter(|v| !v.is_null())
+                .cloned()
+                .ok_or_else(|| {
+                    Error::Message(format!(
+                        "search_after can't page after a hit without a value for the ORDER BY column {field}"
+                    ))
+                })
+        })
+        .collect::<infra::errors::Result<Vec<_>>>()
+        .map(Some)
+}
+
 struct AddOrderingTermVisitor {
     field: String,
     is_asc: bool,
@@ -1716,6 +2318,184 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_timestamp_field_visitor() {
+        let fields = HashSet::from(["event_time".to_string()]);
+        let cases = [
+            (
+                "SELECT * FROM logs ORDER BY event_time",
+                "SELECT * FROM logs ORDER BY _timestamp",
+            ),
+            (
+                "SELECT histogram(logs.event_time) AS t, count(*) FROM logs GROUP BY t",
+                "SELECT histogram(logs._timestamp) AS t, count(*) FROM logs GROUP BY t",
+            ),
+            // filters and projections still read the ingested field
+            (
+                "SELECT event_time FROM logs WHERE event_time > '2024'",
+                "SELECT event_time FROM logs WHERE event_time > '2024'",
+            ),
+        ];
+        for (sql, expected) in cases {
+            let mut statement = Parser::parse_sql(&PostgreSqlDialect {}, sql)
+                .unwrap()
+                .pop()
+                .unwrap();
+            statement.visit(&mut TimestampFieldVisitor::new(&fields));
+            assert_eq!(statement.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_add_row_filters() {
+        let filters = HashMap::from([("logs".to_string(), "tenant = 'acme'".to_string())]);
+        let cases = [
+            (
+                "SELECT * FROM logs",
+                "SELECT * FROM logs WHERE (logs.tenant = 'acme')",
+            ),
+            (
+                "SELECT * FROM logs WHERE a = 1 OR b = 2",
+                "SELECT * FROM logs WHERE (a = 1 OR b = 2) AND (logs.tenant = 'acme')",
+            ),
+            (
+                "SELECT count(*) FROM (SELECT * FROM logs WHERE a = 1) t",
+                "SELECT count(*) FROM (SELECT * FROM logs WHERE (a = 1) AND (logs.tenant = 'acme')) AS t",
+            ),
+            (
+                "SELECT a FROM logs UNION ALL SELECT a FROM other",
+                "SELECT a FROM logs WHERE (logs.tenant = 'acme') UNION ALL SELECT a FROM other",
+            ),
+            ("SELECT * FROM other", "SELECT * FROM other"),
+            // the filter is bound to the stream, not to the other side of the join
+            (
+                "SELECT o.a FROM other AS o JOIN logs AS l ON o.id = l.id",
+                "SELECT o.a FROM other AS o JOIN logs AS l ON o.id = l.id WHERE (l.tenant = 'acme')",
+            ),
+            (
+                "SELECT * FROM \"logs\" WHERE tenant IN (SELECT tenant FROM other)",
+                "SELECT * FROM \"logs\" WHERE (tenant IN (SELECT tenant FROM other)) AND (\"logs\".tenant = 'acme')",
+            ),
+        ];
+        for (sql, expected) in cases {
+            assert_eq!(add_row_filters(sql, &filters).unwrap(), expected);
+        }
+
+        let invalid = HashMap::from([("logs".to_string(), "tenant = ".to_string())]);
+        assert!(add_row_filters("SELECT * FROM logs", &invalid).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_row_filters_by_role() {
+        let org_id = "test_get_row_filters_by_role";
+        let mut settings = config::meta::stream::StreamSettings::default();
+        settings
+            .row_filters
+            .insert("member".to_string(), "tenant = 'acme'".to_string());
+        infra::schema::STREAM_SETTINGS
+            .write()
+            .await
+            .insert(format!("{org_id}/{}/logs", StreamType::Logs), settings);
+        crate::common::infra::config::USERS.insert(
+            format!("{org_id}/restricted@example.com"),
+            crate::common::meta::user::User {
+                email: "restricted@example.com".to_string(),
+                first_name: "".to_string(),
+                last_name: "".to_string(),
+                password: "".to_string(),
+                salt: "".to_string(),
+                token: "".to_string(),
+                rum_token: None,
+                role: crate::common::meta::user::UserRole::Member,
+                org: org_id.to_string(),
+                is_external: false,
+                password_ext: None,
+            },
+        );
+        let sql = "SELECT * FROM \"logs\" WHERE level = 'error'";
+
+        // the restricted user's query is filtered
+        let filters = get_row_filters(
+            org_id,
+            Some("restricted@example.com"),
+            StreamType::Logs,
+            sql,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            add_row_filters(sql, &filters).unwrap(),
+            "SELECT * FROM \"logs\" WHERE (level = 'error') AND ((\"logs\".tenant = 'acme'))"
+        );
+
+        // internal searches are not filtered
+        let filters = get_row_filters(org_id, None, StreamType::Logs, sql)
+            .await
+            .unwrap();
+        assert!(filters.is_empty());
+        assert_eq!(
+            row_filters_cache_key(org_id, None, StreamType::Logs, sql)
+                .await
+                .unwrap(),
+            None
+        );
+
+        // users with different filters get different result cache keys
+        let key = row_filters_cache_key(
+            org_id,
+            Some("restricted@example.com"),
+            StreamType::Logs,
+            sql,
+        )
+        .await
+        .unwrap();
+        assert_eq!(key.as_deref(), Some("logs:(tenant = 'acme')"));
+
+        let user = |email: &str, role| {
+            let mut user = crate::common::infra::config::USERS
+                .get(&format!("{org_id}/restricted@example.com"))
+                .unwrap()
+                .clone();
+            user.email = email.to_string();
+            user.role = role;
+            crate::common::infra::config::USERS.insert(format!("{org_id}/{email}"), user);
+        };
+
+        // admins without a filter see every row
+        user(
+            "admin@example.com",
+            crate::common::meta::user::UserRole::Admin,
+        );
+        let filters = get_row_filters(org_id, Some("admin@example.com"), StreamType::Logs, sql)
+            .await
+            .unwrap();
+        assert!(filters.is_empty());
+        assert_eq!(
+            row_filters_cache_key(org_id, Some("admin@example.com"), StreamType::Logs, sql)
+                .await
+                .unwrap(),
+            None
+        );
+
+        // the other roles without a filter are denied every row
+        user(
+            "service@example.com",
+            crate::common::meta::user::UserRole::ServiceAccount,
+        );
+        let filters = get_row_filters(org_id, Some("service@example.com"), StreamType::Logs, sql)
+            .await
+            .unwrap();
+        assert_eq!(
+            add_row_filters(sql, &filters).unwrap(),
+            "SELECT * FROM \"logs\" WHERE (level = 'error') AND (FALSE)"
+        );
+        let service_key =
+            row_filters_cache_key(org_id, Some("service@example.com"), StreamType::Logs, sql)
+                .await
+                .unwrap();
+        assert_ne!(service_key, key);
+    }
+
     #[test]
     fn test_index_visitor1() {
         let sql = "SELECT * FROM t WHERE name = 'a' AND age = 1 AND (name = 'b' OR (match_all('good') AND match_all('bar'))) AND (match_all('foo') OR age = 2)";
@@ -1957,6 +2737,36 @@ mod tests {
         assert_eq!(is_simple_count_query(&mut statement), false);
     }
 
+    #[test]
+    fn test_add_default_order_by() {
+        let parse = |sql: &str| {
+            Parser::parse_sql(&PostgreSqlDialect {}, sql)
+                .unwrap()
+                .pop()
+                .unwrap()
+        };
+
+        // no ORDER BY, the default sort is added
+        let mut statement = parse("SELECT * FROM t WHERE a = 1");
+        assert!(add_default_order_by(&mut statement));
+        assert_eq!(
+            statement.to_string(),
+            "SELECT * FROM t WHERE a = 1 ORDER BY _timestamp DESC"
+        );
+
+        // explicit ORDER BY is kept
+        let sql = "SELECT * FROM t ORDER BY a ASC";
+        let mut statement = parse(sql);
+        assert!(!add_default_order_by(&mut statement));
+        assert_eq!(statement.to_string(), sql);
+
+        // aggregations have no natural order by time
+        let sql = "SELECT a, count(*) FROM t GROUP BY a";
+        let mut statement = parse(sql);
+        assert!(!add_default_order_by(&mut statement));
+        assert_eq!(statement.to_string(), sql);
+    }
+
     #[test]
     fn test_check_or_add_order_by_timestamp_no_order_asc() {
         let sql = "SELECT * FROM logs";
@@ -2130,4 +2940,224 @@ mod tests {
             1000000
         );
     }
+
+    #[test]
+    fn test_apply_search_after() {
+        let sql = "SELECT * FROM t WHERE a = 1 ORDER BY _timestamp DESC";
+        let (new_sql, fields) = apply_search_after(sql, &[], true).unwrap();
+        assert_eq!(
+            new_sql,
+            "SELECT * FROM t WHERE a = 1 ORDER BY _timestamp DESC, _o2_id ASC"
+        );
+        assert_eq!(
+            fields,
+            vec!["_timestamp".to_string(), "_o2_id".to_string()]
+        );
+
+        let (new_sql, _) =
+            apply_search_after(sql, &[json::json!(100), json::json!("7")], true).unwrap();
+        assert_eq!(
+            new_sql,
+            "SELECT * FROM t WHERE (a = 1) AND ((_timestamp < 100) OR (_timestamp = 100 AND _o2_id > '7')) ORDER BY _timestamp DESC, _o2_id ASC"
+        );
+
+        // default ordering by timestamp descending
+        let (new_sql, fields) =
+            apply_search_after("SELECT * FROM t", &[json::json!(100), json::json!("7")], true)
+                .unwrap();
+        assert_eq!(
+            new_sql,
+            "SELECT * FROM t WHERE (_timestamp < 100) OR (_timestamp = 100 AND _o2_id > '7') ORDER BY _timestamp DESC, _o2_id ASC"
+        );
+        assert_eq!(
+            fields,
+            vec!["_timestamp".to_string(), "_o2_id".to_string()]
+        );
+
+        // the record id is selected to build the next cursor
+        let (new_sql, _) =
+            apply_search_after("SELECT _timestamp, msg FROM t", &[], true).unwrap();
+        assert_eq!(
+            new_sql,
+            "SELECT _timestamp, msg, _o2_id FROM t ORDER BY _timestamp DESC, _o2_id ASC"
+        );
+
+        // without the record id the cursor is made of the sort values alone
+        let (new_sql, fields) =
+            apply_search_after("SELECT _timestamp, msg FROM t", &[json::json!(100)], false)
+                .unwrap();
+        assert_eq!(
+            new_sql,
+            "SELECT _timestamp, msg FROM t WHERE _timestamp < 100 ORDER BY _timestamp DESC"
+        );
+        assert_eq!(fields, vec!["_timestamp".to_string()]);
+    }
+
+    #[test]
+    fn test_apply_search_after_errors() {
+        let sql = "SELECT * FROM t ORDER BY _timestamp DESC, name ASC";
+        assert!(apply_search_after(sql, &[json::json!(100)], true).is_err());
+        assert!(apply_search_after(
+            sql,
+            &[json::json!(100), json::json!("a"), json::Value::Null],
+            true
+        )
+        .is_err());
+        assert!(apply_search_after("SELECT count(*) FROM t", &[], true).is_err());
+        // an empty statement, a sort column missing from the hits
+        assert!(apply_search_after(";", &[], true).is_err());
+        assert!(apply_search_after("", &[], true).is_err());
+        assert!(apply_search_after("SELECT msg FROM t ORDER BY name", &[], true).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search_after_pagination() {
+        use datafusion::{
+            arrow::{
+                array::{Int64Array, StringArray},
+                datatypes::{DataType, Field},
+                record_batch::RecordBatch,
+            },
+            datasource::MemTable,
+            prelude::SessionContext,
+        };
+
+        // 10 rows with timestamps shared by up to 3 rows, across two batches
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false),
+            Field::new(ID_COL_NAME, DataType::Utf8, false),
+        ]));
+        let batch = |ids: std::ops::Range<i64>| {
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int64Array::from_iter_values(ids.clone().map(|i| 100 - i / 3))),
+                    Arc::new(StringArray::from_iter_values(
+                        ids.map(|i| format!("{:02}", 9 - i)),
+                    )),
+                ],
+            )
+            .unwrap()
+        };
+        let table = MemTable::try_new(schema.clone(), vec![vec![batch(5..10)], vec![batch(0..5)]])
+            .unwrap();
+        let ctx = SessionContext::new();
+        ctx.register_table("t", Arc::new(table)).unwrap();
+
+        let mut cursor = vec![];
+        let mut seen = Vec::new();
+        loop {
+            let (sql, fields) = apply_search_after("SELECT * FROM t", &cursor, true).unwrap();
+            let batches = ctx
+                .sql(&format!("{sql} LIMIT 4"))
+                .await
+                .unwrap()
+                .collect()
+                .await
+                .unwrap();
+            let page = config::utils::arrow::record_batches_to_json_rows(
+                &batches.iter().collect::<Vec<_>>(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(json::Value::Object)
+            .collect::<Vec<_>>();
+            if page.is_empty() {
+                break;
+            }
+            assert!(page.len() <= 4);
+            seen.extend(
+                page.iter()
+                    .map(|row| row[ID_COL_NAME].as_str().unwrap().to_string()),
+            );
+            cursor = get_search_after_cursor(&page, &fields).unwrap().unwrap();
+        }
+        // every row once, newest first and by record id within a timestamp
+        let mut expected = (0..10)
+            .map(|i| (100 - i / 3, format!("{:02}", 9 - i)))
+            .collect::<Vec<_>>();
+        expected.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        assert_eq!(
+            seen,
+            expected.into_iter().map(|(_, id)| id).collect::<Vec<_>>()
+        );
+        assert!(get_search_after_cursor(&[], &["_timestamp".to_string()])
+            .unwrap()
+            .is_none());
+        // a null sort value can't be paged after
+        assert!(get_search_after_cursor(
+            &[json::json!({"_timestamp": 100, "name": null})],
+            &["_timestamp".to_string(), "name".to_string()]
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sql_params() {
+        let literal = SearchQuery {
+            sql: "SELECT * FROM params_logs WHERE level = 'error' AND code = 500".to_string(),
+            start_time: 0,
+            end_time: 10,
+            ..Default::default()
+        };
+        let query: config::meta::search::Query = json::from_value(json::json!({
+            "sql": "SELECT * FROM params_logs WHERE level = $level AND code = ?",
+            "start_time": 0,
+            "end_time": 10,
+            "params": { "level": "error", "1": 500 }
+        }))
+        .unwrap();
+        let mut parameterized = SearchQuery::from(query);
+        parameterized.sql = config::meta::sql::number_positional_params(&parameterized.sql);
+
+        let literal = Sql::new(&literal, "default", StreamType::Logs)
+            .await
+            .unwrap();
+        let parameterized = Sql::new(&parameterized, "default", StreamType::Logs)
+            .await
+            .unwrap();
+        assert_eq!(literal.sql, parameterized.sql);
+
+        // a value can't escape its literal
+        let mut injected = parameterized_query("error' OR '1'='1");
+        let sql = Sql::new(&injected, "default", StreamType::Logs)
+            .await
+            .unwrap();
+        assert!(sql.sql.contains("level = 'error'' OR ''1''=''1'"));
+        let statement = Parser::parse_sql(&PostgreSqlDialect {}, &sql.sql)
+            .unwrap()
+            .pop()
+            .unwrap();
+        let Statement::Query(query) = statement else {
+            panic!("not a query");
+        };
+        let SetExpr::Select(select) = query.body.as_ref() else {
+            panic!("not a select");
+        };
+        assert!(matches!(
+            select.selection,
+            Some(Expr::BinaryOp {
+                op: BinaryOperator::Eq,
+                ..
+            })
+        ));
+
+        injected.params.clear();
+        assert!(Sql::new(&injected, "default", StreamType::Logs)
+            .await
+            .is_err());
+    }
+
+    fn parameterized_query(level: &str) -> SearchQuery {
+        SearchQuery {
+            sql: "SELECT * FROM params_logs WHERE level = $level".to_string(),
+            start_time: 0,
+            end_time: 10,
+            params: std::collections::HashMap::from([(
+                "level".to_string(),
+                json::Value::String(level.to_string()).to_string(),
+            )]),
+            ..Default::default()
+        }
+    }
 }
@@ -18,6 +18,7 @@ use std::sync::Arc;
 use config::{
     meta::{
         cluster::{IntoArcVec, RoleGroup},
+        inverted_index::InvertedIndexFormat,
         search::{ScanStats, SearchEventType},
         sql::TableReferenceExt,
         stream::{FileKey, StreamType},
@@ -41,7 +42,7 @@ use tracing_opentelemetry::OpenTelemetrySpanExt;
 use crate::service::search::{
     cluster::flight::{
         check_work_group, get_inverted_index_file_list, get_online_querier_nodes,
-        partition_filt_list,
+        keep_recent_stream_files, partition_filt_list, quick_scan_partial_err,
     },
     datafusion::{
         distributed_plan::{
@@ -74,6 +75,7 @@ pub async fn search(
     Arc<dyn ExecutionPlan>,
     AsyncDefer,
     ScanStats,
+    String,
 )> {
     let start = std::time::Instant::now();
     let cfg = config::get_config();
@@ -120,7 +122,14 @@ pub async fn search(
     let stream_type = stream.get_stream_type(req.stream_type);
 
     // 1. get file id list
-    let file_id_list = get_file_id_lists(&req.org_id, stream_type, &stream, req.time_range).await?;
+    let mut file_id_list =
+        get_file_id_lists(&req.org_id, stream_type, &stream, req.time_range).await?;
+    // quick scan only searches the most recent files
+    let partial_err = quick_scan_files(
+        req.quick_scan,
+        &mut file_id_list,
+        cfg.limit.quick_scan_max_files,
+    );
 
     let file_id_list_vec = file_id_list.iter().collect::<Vec<_>>();
     let file_id_list_took = start.elapsed().as_millis() as usize;
@@ -136,8 +145,10 @@ pub async fn search(
         ..Default::default()
     };
 
-    // 2. get inverted index file list
-    let (use_ttv_inverted_index, idx_file_list, idx_scan_size, _idx_took) =
+    // 2. get inverted index file list, quick scan skips the inverted index
+    let (use_ttv_inverted_index, idx_file_list, idx_scan_size, _idx_took) = if req.quick_scan {
+        (false, vec![], 0, 0)
+    } else {
         get_inverted_index_file_lists(
             &trace_id,
             &req,
@@ -145,7 +156,8 @@ pub async fn search(
             &flight_request.index_info.equal_keys,
             &flight_request.index_info.match_all_keys,
         )
-        .await?;
+        .await?
+    };
     scan_stats.idx_scan_size = idx_scan_size as i64;
     req.set_use_inverted_index(use_ttv_inverted_index);
 
@@ -242,7 +254,17 @@ pub async fn search(
 
     log::info!("[trace_id {trace_id}] flight->follower_leader: generate physical plan finish",);
 
-    Ok((ctx, physical_plan, defer, scan_stats))
+    Ok((ctx, physical_plan, defer, scan_stats, partial_err))
+}
+
+/// Applies the quick scan file limit to the stream's files, returns the
+/// partial error to report back to the super cluster leader.
+fn quick_scan_files(quick_scan: bool, files: &mut Vec<FileId>, max_files: usize) -> String {
+    if quick_scan && keep_recent_stream_files(files, max_files) {
+        quick_scan_partial_err(max_files)
+    } else {
+        String::new()
+    }
 }
 
 #[tracing::instrument(
@@ -283,10 +305,10 @@ async fn get_inverted_index_file_lists(
 ) -> Result<(bool, Vec<FileKey>, usize, usize)> {
     let cfg = config::get_config();
     #[allow(deprecated)]
-    let inverted_index_type = cfg.common.inverted_index_search_format.clone();
+    let inverted_index_type = InvertedIndexFormat::from(&cfg.common.inverted_index_search_format);
     let use_inverted_index = req.use_inverted_index;
-    let use_parquet_inverted_index = use_inverted_index && inverted_index_type == "parquet";
-    let use_ttv_inverted_index = use_inverted_index && inverted_index_type == "tantivy";
+    let use_parquet_inverted_index = use_inverted_index && inverted_index_type.use_parquet();
+    let use_ttv_inverted_index = use_inverted_index && inverted_index_type.use_tantivy();
     log::info!(
         "[trace_id {trace_id}] flight->follower_leader: use_inverted_index with parquet format {}",
         use_parquet_inverted_index
@@ -338,3 +360,60 @@ async fn get_inverted_index_file_lists(
         idx_took,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use proto::cluster_rpc::{IndexInfo, QueryIdentifier, SearchInfo, SuperClusterInfo};
+
+    use super::*;
+
+    fn files(n: i64) -> Vec<FileId> {
+        (0..n)
+            .map(|i| FileId {
+                id: i,
+                min_ts: i * 10,
+                max_ts: i * 10 + 5,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_quick_scan_request_from_leader() {
+        let flight_request = FlightSearchRequest {
+            query_identifier: QueryIdentifier::default(),
+            search_info: SearchInfo::default(),
+            index_info: IndexInfo::default(),
+            super_cluster_info: SuperClusterInfo {
+                is_super_cluster: true,
+                quick_scan: true,
+                ..Default::default()
+            },
+        };
+        // the flag survives the trip to the follower
+        let encoded: proto::cluster_rpc::FlightSearchRequest = flight_request.into();
+        let req: Request = FlightSearchRequest::from(encoded).into();
+        assert!(req.quick_scan);
+    }
+
+    #[test]
+    fn test_quick_scan_files() {
+        let mut file_list = files(10);
+        let partial_err = quick_scan_files(true, &mut file_list, 3);
+        assert_eq!(
+            file_list.iter().map(|f| f.id).collect::<Vec<_>>(),
+            vec![9, 8, 7]
+        );
+        assert_eq!(partial_err, quick_scan_partial_err(3));
+
+        // nothing skipped, the search isn't partial
+        let mut file_list = files(2);
+        assert!(quick_scan_files(true, &mut file_list, 3).is_empty());
+        assert_eq!(file_list.len(), 2);
+
+        // without quick scan every file is searched
+        let mut file_list = files(10);
+        assert!(quick_scan_files(false, &mut file_list, 3).is_empty());
+        assert_eq!(file_list.len(), 10);
+    }
+}
@@ -56,7 +56,7 @@ pub async fn search(
     trace_id: &str,
     sql: Arc<Sql>,
     mut req: Request,
-    _query: cluster_rpc::SearchQuery,
+    query: cluster_rpc::SearchQuery,
     req_regions: Vec<String>,
     req_clusters: Vec<String>,
 ) -> Result<(Vec<RecordBatch>, ScanStats, usize, bool, usize, String)> {
@@ -81,6 +81,8 @@ pub async fn search(
 
     let (use_inverted_index, _) = super::super::is_use_inverted_index(&sql);
     req.set_use_inverted_index(use_inverted_index);
+    // followers apply the quick scan file limit in their own cluster
+    req.set_quick_scan(query.quick_scan);
 
     // 2. get nodes
     let nodes = get_cluster_nodes(trace_id, req_regions, req_clusters).await?;
@@ -122,38 +124,31 @@ pub async fn search(
     });
     tokio::pin!(query_task);
 
-    let task = tokio::select! {
+    let data = tokio::select! {
         ret = &mut query_task => {
             match ret {
-                Ok(ret) => Ok(ret),
+                Ok(ret) => ret,
                 Err(err) => {
                     log::error!("[trace_id {trace_id}] super cluster leader: datafusion execute error: {}", err);
-                    Err(DataFusionError::Execution(err.to_string()))
+                    Err(Error::Message(DataFusionError::Execution(err.to_string()).to_string()))
                 }
             }
         },
         _ = tokio::time::sleep(tokio::time::Duration::from_secs(timeout)) => {
             query_task.abort();
             log::error!("[trace_id {trace_id}] super cluster leader: search timeout");
-            Err(DataFusionError::ResourcesExhausted("super cluster leader: search timeout".to_string()))
+            Err(Error::ErrorCode(ErrorCodes::SearchTimeout(format!(
+                "[trace_id {trace_id}] super cluster leader: search timeout after {timeout}s"
+            ))))
         },
         _ = abort_receiver => {
             query_task.abort();
             log::info!("[trace_id {trace_id}] super cluster leader: search canceled");
-            Err(DataFusionError::ResourcesExhausted("super cluster leader: search canceled".to_string()))
+            Err(Error::ErrorCode(ErrorCodes::SearchCancelQuery(
+                "super cluster leader: search canceled".to_string(),
+            )))
         }
     };
-
-    let data = match task {
-        Ok(Ok(data)) => Ok(data),
-        Ok(Err(err)) => Err(err),
-        Err(err) => match err {
-            DataFusionError::ResourcesExhausted(err) => Err(Error::ErrorCode(
-                ErrorCodes::SearchCancelQuery(err.to_string()),
-            )),
-            _ => Err(Error::Message(err.to_string())),
-        },
-    };
     let (data, mut scan_stats, partial_err) = match data {
         Ok(v) => v,
         Err(e) => {
@@ -0,0 +1,441 @@
+// Copyright 2025 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{future::Future, ops::ControlFlow, sync::Arc, time::Duration};
+
+use config::{
+    meta::{cluster::NodeInfo, stream::StreamType},
+    utils::{arrow::record_batches_to_json_rows, json},
+};
+use datafusion::{common::TableReference, datasource::MemTable};
+use hashbrown::HashMap;
+use infra::errors::{Error, Result};
+use proto::cluster_rpc::TailRequest;
+use sqlparser::{ast::Expr, dialect::PostgreSqlDialect, parser::Parser, tokenizer::Token};
+use tracing::{info_span, Instrument};
+
+use super::{
+    datafusion::exec::{prepare_datafusion_context, register_udf},
+    grpc::wal::adapt_batch,
+    masking::{add_field_masks, get_field_masks},
+    sql::{add_row_filters, get_row_filters},
+};
+use crate::{common::infra::cluster as infra_cluster, service::grpc::make_grpc_search_client};
+
+/// What a live tail reads: the stream and the sql run over its new records.
+#[derive(Clone, Debug)]
+pub struct TailQuery {
+    pub trace_id: String,
+    pub org_id: String,
+    pub stream_type: StreamType,
+    pub stream_name: String,
+    pub sql: String,
+}
+
+/// Remembers, for each ingester, the last memtable batch already pushed to a
+/// live tail, so each poll only returns what was written since, in ingestion
+/// order. A node without a cursor starts from its current batch.
+#[derive(Debug, Default)]
+pub struct TailCursor {
+    seqs: HashMap<String, u64>,
+}
+
+impl TailCursor {
+    pub fn since(&self, node: &str) -> u64 {
+        self.seqs.get(node).copied().unwrap_or_default()
+    }
+
+    pub fn advance(&mut self, node: &str, seq: u64) {
+        let since = self.seqs.entry(node.to_string()).or_default();
+        *since = (*since).max(seq);
+    }
+}
+
+/// The sql of a tail over the stream, keeping the records matching the
+/// optional `filter`. The filter must be a single condition on the stream:
+/// anything after it or a subquery is rejected.
+pub fn build_tail_sql(stream_name: &str, filter: Option<&str>) -> Result<String> {
+    let mut sql = format!("SELECT * FROM \"{}\"", stream_name.replace('"', "\"\""));
+    let Some(filter) = filter.filter(|f| !f.trim().is_empty()) else {
+        return Ok(sql);
+    };
+    let invalid = |e: String| Error::Message(format!("invalid tail filter {filter}: {e}"));
+    let mut parser = Parser::new(&PostgreSqlDialect {})
+        .try_with_sql(filter)
+        .map_err(|e| invalid(e.to_string()))?;
+    let expr = parser.parse_expr().map_err(|e| invalid(e.to_string()))?;
+    if parser.peek_token().token != Token::EOF {
+        return Err(invalid("unexpected input after the condition".to_string()));
+    }
+    let has_subquery = sqlparser::ast::visit_expressions(&expr, |expr| match expr {
+        Expr::Subquery(_) | Expr::InSubquery { .. } | Expr::Exists { .. } => ControlFlow::Break(()),
+        _ => ControlFlow::Continue(()),
+    })
+    .is_break();
+    if has_subquery {
+        return Err(invalid("subqueries are not allowed".to_string()));
+    }
+    sql.push_str(&format!(" WHERE {expr}"));
+    Ok(sql)
+}
+
+/// The sql of the user's tail, with the field masks and row filters of the
+/// stream applied like for a search.
+pub async fn tail_sql(
+    org_id: &str,
+    user_id: &str,
+    stream_type: StreamType,
+    stream_name: &str,
+    filter: Option<&str>,
+) -> Result<String> {
+    let mut sql = build_tail_sql(stream_name, filter)?;
+    let masks = get_field_masks(org_id, Some(user_id), stream_type, &sql).await?;
+    let filters = get_row_filters(org_id, Some(user_id), stream_type, &sql).await?;
+    if !masks.is_empty() {
+        sql = add_field_masks(&sql, &masks)?;
+    }
+    if !filters.is_empty() {
+        sql = add_row_filters(&sql, &filters)?;
+    }
+    Ok(sql)
+}
+
+/// Polls the ingesters for the records written since the last poll and hands
+/// at most `max_hits` of them to `push`, until `is_alive` turns false or the
+/// push fails, which both mean the subscriber is gone.
+pub async fn run<A, P, PFut>(
+    interval: Duration,
+    max_hits: usize,
+    query: TailQuery,
+    is_alive: A,
+    mut push: P,
+) where
+    A: Fn() -> bool,
+    P: FnMut(Vec<json::Value>) -> PFut,
+    PFut: Future<Output = Result<()>>,
+{
+    let mut cursor = TailCursor::default();
+    let mut interval = tokio::time::interval(interval);
+    loop {
+        interval.tick().await;
+        if !is_alive() {
+            log::info!("[TAIL] stop tailing: subscriber is gone");
+            return;
+        }
+        let mut hits = poll_ingesters(&query, &mut cursor).await;
+        if hits.is_empty() {
+            continue;
+        }
+        let dropped = cap_hits(&mut hits, max_hits);
+        if dropped > 0 {
+            log::warn!(
+                "[trace_id {}] tail dropped {} records over the limit of {}",
+                query.trace_id,
+                dropped,
+                max_hits
+            );
+        }
+        if let Err(e) = push(hits).await {
+            log::info!("[TAIL] stop tailing: {}", e);
+            return;
+        }
+    }
+}
+
+/// Keeps the newest `max_hits` of the `hits`, which are oldest first, and
+/// returns how many were dropped. A `max_hits` of 0 keeps them all.
+pub fn cap_hits(hits: &mut Vec<json::Value>, max_hits: usize) -> usize {
+    if max_hits == 0 || hits.len() <= max_hits {
+        return 0;
+    }
+    let dropped = hits.len() - max_hits;
+    hits.drain(..dropped);
+    dropped
+}
+
+/// Asks every ingester for the records of the tail in its memtable, written
+/// after its cursor. A node failing is retried from the same cursor on the
+/// next poll.
+async fn poll_ingesters(query: &TailQuery, cursor: &mut TailCursor) -> Vec<json::Value> {
+    let nodes = infra_cluster::get_cached_online_ingester_nodes()
+        .await
+        .unwrap_or_default();
+    let mut tasks = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        let req = TailRequest {
+            trace_id: query.trace_id.clone(),
+            org_id: query.org_id.clone(),
+            stream_type: query.stream_type.to_string(),
+            stream_name: query.stream_name.clone(),
+            sql: query.sql.clone(),
+            since: cursor.since(&node.uuid),
+        };
+        let grpc_span = info_span!(
+            "service:search:tail:grpc_tail",
+            node_id = node.id,
+            node_addr = node.grpc_addr.as_str(),
+        );
+        let task = tokio::task::spawn(
+            async move {
+                let node_uuid = node.uuid.clone();
+                let mut request = tonic::Request::new(req);
+                let node = Arc::new(node) as Arc<dyn NodeInfo>;
+                let mut client = make_grpc_search_client(&mut request, &node).await?;
+                let res = client
+                    .tail(request)
+                    .await
+                    .map_err(|e| Error::Message(e.message().to_string()))?
+                    .into_inner();
+                let hits = json::from_slice::<Vec<json::Value>>(&res.hits)
+                    .map_err(|e| Error::Message(e.to_string()))?;
+                Ok::<_, Error>((node_uuid, hits, res.seq))
+            }
+            .instrument(grpc_span),
+        );
+        tasks.push(task);
+    }
+
+    let mut hits = Vec::new();
+    for task in tasks {
+        match task.await {
+            Ok(Ok((node, node_hits, seq))) => {
+                hits.extend(node_hits);
+                cursor.advance(&node, seq);
+            }
+            Ok(Err(e)) => log::warn!("[trace_id {}] tail ingester error: {}", query.trace_id, e),
+            Err(e) => log::warn!("[trace_id {}] tail task error: {}", query.trace_id, e),
+        }
+    }
+    hits
+}
+
+/// Runs the tail `sql` over the batches of the stream written to the memtable
+/// of this node after the batch `since`, oldest first. Returns the hits and the
+/// cursor of the next poll: the sequence up to which every batch was visible
+/// when reading, so a batch still being written is read by the next poll. A
+/// new tail, with a `since` of 0, only gets the cursor back. At most
+/// `ZO_WEBSOCKET_TAIL_MAX_HITS` of the newest hits are returned.
+pub async fn search_memtable_since(
+    org_id: &str,
+    stream_type: StreamType,
+    stream_name: &str,
+    sql: &str,
+    since: u64,
+) -> Result<(Vec<json::Value>, u64)> {
+    let until = ingester::last_batch_seq().max(since);
+    if since == 0 {
+        return Ok((vec![], until));
+    }
+    let schema = infra::schema::get(org_id, stream_name, stream_type).await?;
+    if schema.fields().is_empty() {
+        return Ok((vec![], until));
+    }
+
+    let mut entries =
+        ingester::read_from_memtable(org_id, stream_type.as_str(), stream_name, None, &[])
+            .await
+            .map_err(|e| Error::Message(e.to_string()))?;
+    entries.extend(
+        ingester::read_from_immutable(org_id, stream_type.as_str(), stream_name, None, &[])
+            .await
+            .map_err(|e| Error::Message(e.to_string()))?,
+    );
+    let mut entries = entries
+        .into_iter()
+        .flat_map(|(_, entries)| entries)
+        .filter(|entry| entry.seq() > since && entry.seq() <= until)
+        .collect::<Vec<_>>();
+    if entries.is_empty() {
+        return Ok((vec![], until));
+    }
+    entries.sort_by_key(|entry| entry.seq());
+
+    let latest_schema = Arc::new(schema.with_metadata(Default::default()));
+    let batches = entries
+        .iter()
+        .map(|entry| adapt_batch(latest_schema.clone(), &entry.data))
+        .collect::<Vec<_>>();
+    let table = MemTable::try_new(latest_schema, vec![batches])?;
+    let ctx = prepare_datafusion_context(None, vec![], false, 1).await?;
+    register_udf(&ctx, org_id)?;
+    ctx.register_table(TableReference::bare(stream_name), Arc::new(table))?;
+    let batches = ctx.sql(sql).await?.collect().await?;
+    let mut hits = record_batches_to_json_rows(&batches.iter().collect::<Vec<_>>())
+        .map_err(|e| Error::Message(e.to_string()))?
+        .into_iter()
+        .map(json::Value::Object)
+        .collect::<Vec<_>>();
+    cap_hits(&mut hits, config::get_config().websocket.tail_max_hits);
+    Ok((hits, until))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap as StdHashMap;
+
+    use arrow_schema::{DataType, Field, Schema};
+    use config::{meta::stream::PartitionTimeLevel, TIMESTAMP_COL_NAME};
+    use infra::schema::{SchemaCache, STREAM_SCHEMAS_LATEST};
+
+    use super::*;
+    use crate::{common::meta::stream::SchemaRecords, service::ingestion};
+
+    #[test]
+    fn test_build_tail_sql() {
+        assert_eq!(
+            build_tail_sql("logs", None).unwrap(),
+            "SELECT * FROM \"logs\""
+        );
+        assert_eq!(
+            build_tail_sql("logs", Some("level = 'error' AND code > 500")).unwrap(),
+            "SELECT * FROM \"logs\" WHERE level = 'error' AND code > 500"
+        );
+        // the filter can't escape the WHERE clause or read another stream
+        assert!(build_tail_sql("logs", Some("1 = 1) UNION SELECT * FROM other --")).is_err());
+        assert!(build_tail_sql("logs", Some("1 = 1 UNION SELECT * FROM other")).is_err());
+        assert!(build_tail_sql("logs", Some("user IN (SELECT user FROM other)")).is_err());
+    }
+
+    #[test]
+    fn test_tail_cursor() {
+        let mut cursor = TailCursor::default();
+        assert_eq!(cursor.since("node1"), 0);
+        cursor.advance("node1", 10);
+        cursor.advance("node2", 5);
+        // a late response never moves the cursor back
+        cursor.advance("node1", 7);
+        assert_eq!(cursor.since("node1"), 10);
+        assert_eq!(cursor.since("node2"), 5);
+    }
+
+    #[test]
+    fn test_cap_hits() {
+        let mut hits = (0..5).map(|i| json::json!({"i": i})).collect::<Vec<_>>();
+        assert_eq!(cap_hits(&mut hits, 0), 0);
+        assert_eq!(cap_hits(&mut hits, 5), 0);
+        assert_eq!(cap_hits(&mut hits, 2), 3);
+        assert_eq!(hits, vec![json::json!({"i": 3}), json::json!({"i": 4})]);
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_when_subscriber_is_gone() {
+        let query = TailQuery {
+            trace_id: "test_run_stops".to_string(),
+            org_id: "test_run_stops".to_string(),
+            stream_type: StreamType::Logs,
+            stream_name: "quiet".to_string(),
+            sql: build_tail_sql("quiet", None).unwrap(),
+        };
+        let polls = std::sync::atomic::AtomicUsize::new(0);
+        let is_alive = || polls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) < 3;
+        // a quiet stream never pushes, the tail still ends with its subscriber
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            run(Duration::from_millis(1), 0, query, is_alive, |_| async {
+                Ok(())
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(polls.load(std::sync::atomic::Ordering::SeqCst), 4);
+    }
+
+    async fn write_records(org_id: &str, stream_name: &str, records: &[json::Value]) {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false),
+            Field::new("level", DataType::Utf8, true),
+        ]));
+        let mut buf = StdHashMap::new();
+        for record in records {
+            let timestamp = record[TIMESTAMP_COL_NAME].as_i64().unwrap();
+            let key = ingestion::get_write_partition_key(
+                timestamp,
+                &vec![],
+                PartitionTimeLevel::Hourly,
+                record.as_object().unwrap(),
+                Some("tail"),
+            );
+            let entry = buf.entry(key).or_insert_with(|| SchemaRecords {
+                schema_key: "tail".to_string(),
+                schema: schema.clone(),
+                records: vec![],
+                records_size: 0,
+            });
+            entry.records.push(Arc::new(record.clone()));
+            entry.records_size += json::to_vec(record).unwrap().len();
+        }
+        let writer = ingester::get_writer(0, org_id, StreamType::Logs.as_str(), stream_name).await;
+        ingestion::write_file(&writer, stream_name, buf, false)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_search_memtable_since() {
+        let org_id = "test_search_memtable_since";
+        let stream_name = "tail_logs";
+        let schema = Schema::new(vec![
+            Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false),
+            Field::new("level", DataType::Utf8, true),
+        ]);
+        STREAM_SCHEMAS_LATEST.write().await.insert(
+            format!("{org_id}/{}/{stream_name}", StreamType::Logs),
+            SchemaCache::new(schema),
+        );
+        let now = chrono::Utc::now().timestamp_micros();
+        let hour = 3_600_000_000;
+        write_records(
+            org_id,
+            stream_name,
+            &[json::json!({TIMESTAMP_COL_NAME: now, "level": "info"})],
+        )
+        .await;
+
+        // a new tail starts after what is already in the memtable
+        let sql = build_tail_sql(stream_name, Some("level = 'error'")).unwrap();
+        let (hits, since) = search_memtable_since(org_id, StreamType::Logs, stream_name, &sql, 0)
+            .await
+            .unwrap();
+        assert!(hits.is_empty());
+        assert!(since > 0);
+
+        // records written later are returned even with an older event time,
+        // and the filter applies
+        write_records(
+            org_id,
+            stream_name,
+            &[
+                json::json!({TIMESTAMP_COL_NAME: now - hour, "level": "error"}),
+                json::json!({TIMESTAMP_COL_NAME: now - hour, "level": "info"}),
+            ],
+        )
+        .await;
+        let (hits, next) =
+            search_memtable_since(org_id, StreamType::Logs, stream_name, &sql, since)
+                .await
+                .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0]["level"], "error");
+        assert_eq!(hits[0][TIMESTAMP_COL_NAME], now - hour);
+        assert!(next > since);
+
+        // nothing is returned twice
+        let (hits, last) = search_memtable_since(org_id, StreamType::Logs, stream_name, &sql, next)
+            .await
+            .unwrap();
+        assert!(hits.is_empty());
+        assert!(last >= next);
+    }
+}
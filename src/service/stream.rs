@@ -13,7 +13,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::io::Error;
+use std::{collections::BTreeMap, io::Error};
 
 use actix_web::{http, http::StatusCode, HttpResponse};
 use arrow_schema::DataType;
@@ -26,8 +26,13 @@ use config::{
             UpdateStreamSettings,
         },
     },
-    utils::{json, time::now_micros},
-    SIZE_IN_MB, SQL_FULL_TEXT_SEARCH_FIELDS,
+    utils::{
+        flatten,
+        hash::{gxhash, Sum64},
+        json,
+        time::now_micros,
+    },
+    SIZE_IN_MB, SQL_FULL_TEXT_SEARCH_FIELDS, TIMESTAMP_COL_NAME,
 };
 use datafusion::arrow::datatypes::Schema;
 use hashbrown::HashMap;
@@ -41,10 +46,13 @@ use infra::{
 };
 
 use crate::{
-    common::meta::{
-        authz::Authz,
-        http::HttpResponse as MetaHttpResponse,
-        stream::{Stream, StreamProperty},
+    common::{
+        infra::config::STREAM_ALERTS,
+        meta::{
+            authz::Authz,
+            http::HttpResponse as MetaHttpResponse,
+            stream::{Stream, StreamDeletePlan, StreamProperty},
+        },
     },
     service::{db, db::distinct_values, metrics::get_prom_metadata_from_schema},
 };
@@ -74,6 +82,33 @@ pub async fn get_stream(
     }
 }
 
+/// Returns the stream schema converted to a standard format, `avro` or
+/// `json_schema`, for code generation.
+pub async fn get_stream_schema_as(
+    org_id: &str,
+    stream_name: &str,
+    stream_type: StreamType,
+    format: &str,
+) -> Result<HttpResponse, Error> {
+    let convert = match format.to_lowercase().as_str() {
+        "avro" => config::utils::schema::to_avro_schema,
+        "json_schema" | "jsonschema" => config::utils::schema::to_json_schema,
+        _ => {
+            return Ok(MetaHttpResponse::bad_request(format!(
+                "unsupported schema format: {format}, expected avro or json_schema"
+            )));
+        }
+    };
+    let schema = match infra::schema::get(org_id, stream_name, stream_type).await {
+        Ok(schema) => schema,
+        Err(e) => return Ok(MetaHttpResponse::internal_error(e)),
+    };
+    if schema == Schema::empty() {
+        return Ok(MetaHttpResponse::not_found("stream not found"));
+    }
+    Ok(MetaHttpResponse::json(convert(stream_name, &schema)))
+}
+
 pub async fn get_streams(
     org_id: &str,
     stream_type: Option<StreamType>,
@@ -305,6 +340,19 @@ pub async fn save_stream_settings(
     }
     settings.partition_keys = old_partition_keys;
 
+    // the index files already written were tokenized with the old split chars
+    let old_split_chars = unwrap_stream_settings(&schema)
+        .unwrap_or_default()
+        .inverted_index_split_chars;
+    if settings.inverted_index_split_chars != old_split_chars {
+        let stats = stats::get_stream_stats(org_id, stream_name, stream_type);
+        if let Err(e) = check_split_chars_change(
+            !schema.fields().is_empty() || stats.doc_num > 0 || stats.file_num > 0,
+        ) {
+            return Ok(MetaHttpResponse::bad_request(e));
+        }
+    }
+
     for range in settings.extended_retention_days.iter() {
         if range.start > range.end {
             return Ok(HttpResponse::BadRequest().json(MetaHttpResponse::error(
@@ -423,6 +471,136 @@ pub async fn update_stream_settings(
                     .retain(|range| !new_settings.extended_retention_days.remove.contains(range));
             }
 
+            if let Some(mapping) = new_settings.webhook_mapping {
+                settings.webhook_mapping = Some(mapping);
+            }
+
+            if let Some(aliases) = new_settings.field_aliases {
+                // store the aliases under the names of the flattened keys
+                let aliases: BTreeMap<String, String> = aliases
+                    .into_iter()
+                    .map(|(mut alias, mut canonical)| {
+                        flatten::format_key(&mut alias);
+                        flatten::format_key(&mut canonical);
+                        (alias, canonical)
+                    })
+                    .collect();
+                if let Some((field, _)) = aliases
+                    .iter()
+                    .find(|(k, v)| k.is_empty() || v.is_empty() || *k == v)
+                {
+                    return Ok(MetaHttpResponse::bad_request(format!(
+                        "invalid field alias for field: {field}"
+                    )));
+                }
+                settings.field_aliases = aliases;
+            }
+
+            if let Some(field) = new_settings.timestamp_field {
+                settings.timestamp_field =
+                    (!field.is_empty() && field != TIMESTAMP_COL_NAME).then_some(field);
+            }
+
+            if let Some(mode) = new_settings.schema_mode {
+                settings.schema_mode = mode;
+            }
+
+            if let Some(drop_unknown_fields) = new_settings.drop_unknown_fields {
+                settings.drop_unknown_fields = drop_unknown_fields;
+            }
+
+            if let Some(max_schema_fields) = new_settings.max_schema_fields {
+                settings.max_schema_fields = max_schema_fields;
+            }
+
+            if let Some(disable_default_sort) = new_settings.disable_default_sort {
+                settings.disable_default_sort = disable_default_sort;
+            }
+
+            if let Some(masks) = new_settings.field_masks {
+                if let Some(field) = masks
+                    .keys()
+                    .find(|k| k.is_empty() || *k == TIMESTAMP_COL_NAME)
+                {
+                    return Ok(MetaHttpResponse::bad_request(format!(
+                        "invalid field mask for field: {field}"
+                    )));
+                }
+                settings.field_masks = masks;
+            }
+
+            if let Some(filters) = new_settings.row_filters {
+                for (role, filter) in filters.iter() {
+                    if role.is_empty()
+                        || crate::service::search::sql::parse_row_filter(filter).is_err()
+                    {
+                        return Ok(MetaHttpResponse::bad_request(format!(
+                            "invalid row filter for role: {role}"
+                        )));
+                    }
+                }
+                settings.row_filters = filters;
+            }
+
+            if let Some(split_chars) = new_settings.inverted_index_split_chars {
+                settings.inverted_index_split_chars =
+                    (!split_chars.is_empty()).then_some(split_chars);
+            }
+
+            if let Some(storage_name) = new_settings.storage_name {
+                if !storage_name.is_empty()
+                    && !config::parse_named_stores(&config::get_config().s3.named_stores)
+                        .unwrap_or_default()
+                        .iter()
+                        .any(|(name, _)| *name == storage_name)
+                {
+                    return Ok(MetaHttpResponse::bad_request(format!(
+                        "unknown storage: {storage_name}"
+                    )));
+                }
+                settings.storage_name = (!storage_name.is_empty()).then_some(storage_name);
+            }
+
+            if let Some(ingestion_paused) = new_settings.ingestion_paused {
+                settings.ingestion_paused = ingestion_paused;
+            }
+
+            if let Some(ratio) = new_settings.trace_sampling_ratio {
+                if !(0.0..=1.0).contains(&ratio) {
+                    return Ok(MetaHttpResponse::bad_request(
+                        "trace_sampling_ratio must be between 0 and 1",
+                    ));
+                }
+                settings.trace_sampling_ratio = (ratio < 1.0).then_some(ratio);
+            }
+
+            if let Some(ttl) = new_settings.result_cache_ttl {
+                if ttl < 0 {
+                    return Ok(MetaHttpResponse::bad_request(
+                        "result_cache_ttl must not be negative",
+                    ));
+                }
+                settings.result_cache_ttl = ttl;
+            }
+
+            if let Some(rules) = new_settings.relabel_rules {
+                if let Err(e) = rules.iter().try_for_each(|rule| rule.validate()) {
+                    return Ok(MetaHttpResponse::bad_request(e));
+                }
+                settings.relabel_rules = rules;
+            }
+
+            if let Some(labels) = new_settings.labels {
+                if let Some(key) = labels.iter().find_map(|(k, v)| {
+                    (k.is_empty() || k.contains([',', ':']) || v.contains(',')).then_some(k)
+                }) {
+                    return Ok(MetaHttpResponse::bad_request(format!(
+                        "invalid stream label: {key}"
+                    )));
+                }
+                settings.labels = labels;
+            }
+
             if !new_settings.distinct_value_fields.add.is_empty() {
                 for f in &new_settings.distinct_value_fields.add {
                     // we ignore full text search fields
@@ -540,20 +718,121 @@ pub async fn update_stream_settings(
     }
 }
 
+/// Gathers what deleting the stream removes and what still uses it, `None`
+/// when the stream doesn't exist.
+pub async fn get_delete_plan(
+    org_id: &str,
+    stream_name: &str,
+    stream_type: StreamType,
+) -> Result<Option<StreamDeletePlan>, anyhow::Error> {
+    let schema_versions = infra::schema::get_versions(org_id, stream_name, stream_type, None)
+        .await?
+        .len();
+    if schema_versions == 0 {
+        return Ok(None);
+    }
+    let key = format!("{org_id}/{stream_type}/{stream_name}");
+    let alerts = STREAM_ALERTS
+        .read()
+        .await
+        .get(&key)
+        .map(|alerts| alerts.iter().map(|a| a.name.clone()).collect())
+        .unwrap_or_default();
+    let dashboards =
+        crate::service::dashboards::list_dashboards_using_stream(org_id, stream_type, stream_name)
+            .await?
+            .iter()
+            .map(|d| d.title().unwrap_or_default().to_string())
+            .collect();
+    let stats = stats::get_stream_stats(org_id, stream_name, stream_type);
+    Ok(Some(new_delete_plan(
+        &key,
+        &stats,
+        schema_versions,
+        alerts,
+        dashboards,
+    )))
+}
+
+fn new_delete_plan(
+    key: &str,
+    stats: &StreamStats,
+    schema_versions: usize,
+    mut alerts: Vec<String>,
+    mut dashboards: Vec<String>,
+) -> StreamDeletePlan {
+    alerts.sort();
+    dashboards.sort();
+    // the token changes with the dependents, a stale dry run can't confirm
+    let confirm_token = (!alerts.is_empty() || !dashboards.is_empty()).then(|| {
+        let data = format!("{key}|{}|{}", alerts.join(","), dashboards.join(","));
+        format!("{:x}", gxhash::new().sum64(&data))
+    });
+    StreamDeletePlan {
+        file_num: stats.file_num,
+        storage_size: stats.storage_size,
+        compressed_size: stats.compressed_size,
+        schema_versions,
+        alerts,
+        dashboards,
+        confirm_token,
+    }
+}
+
+/// Split chars can't be changed once index files were written with the old
+/// ones.
+fn check_split_chars_change(has_data: bool) -> Result<(), String> {
+    if has_data {
+        return Err(
+            "inverted_index_split_chars can't be changed once the stream has data".to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// A stream with dependents is only deleted with the token of its dry run.
+fn check_delete_confirmation(plan: &StreamDeletePlan, confirm: Option<&str>) -> Result<(), String> {
+    match plan.confirm_token.as_deref() {
+        Some(token) if confirm != Some(token) => Err(format!(
+            "stream is used by {} alerts and {} dashboards, pass the confirm_token of a dry_run delete as confirm to delete it",
+            plan.alerts.len(),
+            plan.dashboards.len()
+        )),
+        _ => Ok(()),
+    }
+}
+
 #[tracing::instrument]
 pub async fn delete_stream(
     org_id: &str,
     stream_name: &str,
     stream_type: StreamType,
+    dry_run: bool,
+    confirm: Option<&str>,
 ) -> Result<HttpResponse, Error> {
-    let schema = infra::schema::get_versions(org_id, stream_name, stream_type, None)
-        .await
-        .unwrap();
-    if schema.is_empty() {
-        return Ok(HttpResponse::NotFound().json(MetaHttpResponse::error(
-            StatusCode::NOT_FOUND.into(),
-            "stream not found".to_string(),
-        )));
+    let plan = match get_delete_plan(org_id, stream_name, stream_type).await {
+        Ok(Some(plan)) => plan,
+        Ok(None) => {
+            return Ok(HttpResponse::NotFound().json(MetaHttpResponse::error(
+                StatusCode::NOT_FOUND.into(),
+                "stream not found".to_string(),
+            )));
+        }
+        Err(e) => {
+            return Ok(
+                HttpResponse::InternalServerError().json(MetaHttpResponse::error(
+                    StatusCode::INTERNAL_SERVER_ERROR.into(),
+                    format!("failed to check stream dependents: {e}"),
+                )),
+            );
+        }
+    };
+    if dry_run {
+        return Ok(HttpResponse::Ok().json(plan));
+    }
+    if let Err(e) = check_delete_confirmation(&plan, confirm) {
+        return Ok(HttpResponse::BadRequest()
+            .json(MetaHttpResponse::error(StatusCode::BAD_REQUEST.into(), e)));
     }
 
     // create delete for compactor
@@ -672,12 +951,285 @@ pub async fn delete_fields(
     Ok(())
 }
 
+/// Sort key of the stream list.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StreamSortBy {
+    #[default]
+    Name,
+    Size,
+    DocCount,
+}
+
+/// Name filter, sorting and pagination of the stream list.
+#[derive(Clone, Debug, Default)]
+pub struct StreamListQuery {
+    pub keyword: Option<String>,
+    pub sort_by: StreamSortBy,
+    pub desc: bool,
+    /// Starts from 1
+    pub page_num: usize,
+    /// 0 returns all the streams
+    pub page_size: usize,
+}
+
+impl TryFrom<&std::collections::HashMap<String, String>> for StreamListQuery {
+    type Error = String;
+
+    fn try_from(query: &std::collections::HashMap<String, String>) -> Result<Self, Self::Error> {
+        let sort_by = match query.get("sort").map(|v| v.to_lowercase()).as_deref() {
+            None | Some("name") => StreamSortBy::Name,
+            Some("size") => StreamSortBy::Size,
+            Some("doc_count") => StreamSortBy::DocCount,
+            Some(v) => return Err(format!("invalid sort: {v}, allowed: name, size, doc_count")),
+        };
+        let desc = match query.get("order").map(|v| v.to_lowercase()).as_deref() {
+            None | Some("asc") => false,
+            Some("desc") => true,
+            Some(v) => return Err(format!("invalid order: {v}, allowed: asc, desc")),
+        };
+        let parse = |name: &str, default: usize| match query.get(name) {
+            Some(v) => v
+                .parse::<usize>()
+                .map_err(|_| format!("invalid {name}: {v}")),
+            None => Ok(default),
+        };
+        let page_num = parse("page_num", 1)?;
+        if page_num == 0 {
+            return Err("page_num starts from 1".to_string());
+        }
+        Ok(Self {
+            keyword: query
+                .get("keyword")
+                .map(|v| v.trim().to_lowercase())
+                .filter(|v| !v.is_empty()),
+            sort_by,
+            desc,
+            page_num,
+            page_size: parse("page_size", 0)?,
+        })
+    }
+}
+
+impl StreamListQuery {
+    /// Returns the requested page and the number of streams matching the
+    /// keyword.
+    pub fn apply(&self, mut streams: Vec<Stream>) -> (Vec<Stream>, usize) {
+        if let Some(keyword) = &self.keyword {
+            streams.retain(|s| s.name.to_lowercase().contains(keyword));
+        }
+        streams.sort_by(|a, b| {
+            let ord = match self.sort_by {
+                StreamSortBy::Name => a.name.cmp(&b.name),
+                StreamSortBy::Size => a.stats.storage_size.total_cmp(&b.stats.storage_size),
+                StreamSortBy::DocCount => a.stats.doc_num.cmp(&b.stats.doc_num),
+            }
+            // keep the order stable between pages
+            .then_with(|| a.name.cmp(&b.name));
+            if self.desc {
+                ord.reverse()
+            } else {
+                ord
+            }
+        });
+        let total = streams.len();
+        if self.page_size == 0 {
+            return (streams, total);
+        }
+        let page = streams
+            .into_iter()
+            .skip((self.page_num - 1).saturating_mul(self.page_size))
+            .take(self.page_size)
+            .collect();
+        (page, total)
+    }
+}
+
+/// Keeps the streams having all the labels of `filter`, a comma separated
+/// list of `key:value`, or just `key` to match any value.
+pub fn filter_streams_by_labels(streams: Vec<Stream>, filter: &str) -> Vec<Stream> {
+    let filter = filter
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|label| match label.split_once(':') {
+            Some((key, value)) => (key.trim(), Some(value.trim())),
+            None => (label, None),
+        })
+        .collect::<Vec<_>>();
+    streams
+        .into_iter()
+        .filter(|stream| {
+            filter.iter().all(|(key, value)| {
+                stream
+                    .settings
+                    .labels
+                    .get(*key)
+                    .is_some_and(|v| value.is_none_or(|value| v.as_str() == value))
+            })
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use datafusion::arrow::datatypes::{DataType, Field};
 
     use super::*;
 
+    fn stream_with_docs(name: &str, doc_num: i64) -> Stream {
+        let mut stream = stream_res(name, StreamType::Logs, Schema::empty(), None);
+        stream.stats.doc_num = doc_num;
+        stream.stats.storage_size = doc_num as f64 * 10.0;
+        stream
+    }
+
+    fn list_query(params: &[(&str, &str)]) -> Result<StreamListQuery, String> {
+        let query = params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<std::collections::HashMap<_, _>>();
+        StreamListQuery::try_from(&query)
+    }
+
+    #[test]
+    fn test_check_split_chars_change() {
+        assert!(check_split_chars_change(false).is_ok());
+        // the existing index files were built with the old split chars
+        assert!(check_split_chars_change(true).is_err());
+    }
+
+    #[test]
+    fn test_delete_plan_confirmation() {
+        let stats = StreamStats {
+            file_num: 3,
+            storage_size: 300.0,
+            ..Default::default()
+        };
+        // the dry run reports the dependents
+        let plan = new_delete_plan(
+            "default/logs/app",
+            &stats,
+            2,
+            vec!["errors".to_string(), "5xx".to_string()],
+            vec!["Overview".to_string()],
+        );
+        assert_eq!(plan.file_num, 3);
+        assert_eq!(plan.schema_versions, 2);
+        assert_eq!(plan.alerts, vec!["5xx", "errors"]);
+        assert_eq!(plan.dashboards, vec!["Overview"]);
+        let token = plan.confirm_token.clone().unwrap();
+
+        // the real delete refuses without the token
+        assert!(check_delete_confirmation(&plan, None).is_err());
+        assert!(check_delete_confirmation(&plan, Some("wrong")).is_err());
+        assert!(check_delete_confirmation(&plan, Some(&token)).is_ok());
+
+        // a new dependent invalidates the token
+        let changed = new_delete_plan(
+            "default/logs/app",
+            &stats,
+            2,
+            vec!["errors".to_string(), "5xx".to_string(), "slow".to_string()],
+            vec!["Overview".to_string()],
+        );
+        assert!(check_delete_confirmation(&changed, Some(&token)).is_err());
+
+        // no dependents, no confirmation needed
+        let plan = new_delete_plan("default/logs/app", &stats, 1, vec![], vec![]);
+        assert!(plan.confirm_token.is_none());
+        assert!(check_delete_confirmation(&plan, None).is_ok());
+    }
+
+    #[test]
+    fn test_stream_list_pagination() {
+        let streams = (1..=5)
+            .map(|i| stream_with_docs(&format!("stream_{i}"), i))
+            .collect::<Vec<_>>();
+        let names = |params: &[(&str, &str)]| {
+            let (list, total) = list_query(params).unwrap().apply(streams.clone());
+            (list.into_iter().map(|s| s.name).collect::<Vec<_>>(), total)
+        };
+
+        // no page size returns all the streams
+        assert_eq!(names(&[]).0.len(), 5);
+        assert_eq!(
+            names(&[("page_size", "2")]),
+            (vec!["stream_1".to_string(), "stream_2".to_string()], 5)
+        );
+        assert_eq!(
+            names(&[("page_size", "2"), ("page_num", "3")]),
+            (vec!["stream_5".to_string()], 5)
+        );
+        // past the last page
+        assert_eq!(names(&[("page_size", "2"), ("page_num", "4")]), (vec![], 5));
+        assert_eq!(
+            names(&[("keyword", "STREAM_3"), ("page_size", "2")]),
+            (vec!["stream_3".to_string()], 1)
+        );
+
+        assert!(list_query(&[("page_num", "0")]).is_err());
+        assert!(list_query(&[("page_size", "-1")]).is_err());
+        assert!(list_query(&[("sort", "created")]).is_err());
+    }
+
+    #[test]
+    fn test_stream_list_sort_by_doc_count() {
+        let streams = vec![
+            stream_with_docs("b", 10),
+            stream_with_docs("a", 30),
+            stream_with_docs("c", 20),
+            stream_with_docs("d", 20),
+        ];
+        let names = |params: &[(&str, &str)]| {
+            list_query(params)
+                .unwrap()
+                .apply(streams.clone())
+                .0
+                .into_iter()
+                .map(|s| s.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&[("sort", "doc_count")]), vec!["b", "c", "d", "a"]);
+        assert_eq!(
+            names(&[("sort", "doc_count"), ("order", "desc")]),
+            vec!["a", "d", "c", "b"]
+        );
+        assert_eq!(
+            names(&[("sort", "size"), ("order", "desc"), ("page_size", "1")]),
+            vec!["a"]
+        );
+        assert_eq!(names(&[]), vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_filter_streams_by_labels() {
+        let labeled = |name: &str, labels: &[(&str, &str)]| {
+            let mut stream = stream_res(name, StreamType::Logs, Schema::empty(), None);
+            stream.settings.labels = labels
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            stream
+        };
+        let streams = vec![
+            labeled("payments", &[("team", "billing"), ("env", "prod")]),
+            labeled("checkout", &[("team", "billing"), ("env", "dev")]),
+            labeled("nginx", &[("team", "infra")]),
+            labeled("unlabeled", &[]),
+        ];
+        let names = |filter: &str| {
+            filter_streams_by_labels(streams.clone(), filter)
+                .into_iter()
+                .map(|s| s.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("team:billing"), vec!["payments", "checkout"]);
+        assert_eq!(names("team:billing, env:prod"), vec!["payments"]);
+        assert_eq!(names("env"), vec!["payments", "checkout"]);
+        assert!(names("team:unknown").is_empty());
+        assert_eq!(names("").len(), 4);
+    }
+
     #[test]
     fn test_stream_res() {
         let stats = StreamStats::default();
@@ -38,25 +38,30 @@ use opentelemetry_proto::tonic::{
     collector::trace::v1::{
         ExportTracePartialSuccess, ExportTraceServiceRequest, ExportTraceServiceResponse,
     },
-    trace::v1::{status::StatusCode, Status},
+    common::v1::KeyValue,
+    trace::v1::{status::StatusCode, ResourceSpans, Status},
 };
 use prost::Message;
 use serde_json::Map;
 
 use super::{
-    logs::O2IngestJsonData, metadata::distinct_values::DISTINCT_STREAM_PREFIX,
-    pipeline::batch_execution::ExecutablePipelineTraceInputs,
+    logs::O2IngestJsonData,
+    metadata::distinct_values::DISTINCT_STREAM_PREFIX,
+    pipeline::batch_execution::{ExecutablePipeline, ExecutablePipelineTraceInputs},
 };
 use crate::{
     common::meta::{
         http::HttpResponse as MetaHttpResponse,
+        organization::TracesStreamRoute,
         stream::SchemaRecords,
         traces::{Event, Span, SpanLink, SpanLinkContext, SpanRefType},
     },
     service::{
         alerts::alert::AlertExt,
         db, format_stream_name,
-        ingestion::{evaluate_trigger, grpc::get_val, write_file, TriggerAlertData},
+        ingestion::{
+            check_ingestion_paused, evaluate_trigger, grpc::get_val, write_file, TriggerAlertData,
+        },
         metadata::{
             distinct_values::DvItem, trace_list_index::TraceListItem, write, MetadataItem,
             MetadataType,
@@ -122,8 +127,8 @@ pub async fn otlp_json(
     body: web::Bytes,
     in_stream_name: Option<&str>,
 ) -> Result<HttpResponse, Error> {
-    let request = match serde_json::from_slice::<ExportTraceServiceRequest>(body.as_ref()) {
-        Ok(req) => req,
+    let (request, rejected_spans) = match parse_otlp_json(body.as_ref()) {
+        Ok(v) => v,
         Err(e) => {
             log::error!("[TRACES:OTLP] Invalid json: {}", e);
             return Ok(HttpResponse::BadRequest().json(MetaHttpResponse::error(
@@ -132,7 +137,15 @@ pub async fn otlp_json(
             )));
         }
     };
-    match handle_otlp_request(org_id, request, OtlpRequestType::HttpJson, in_stream_name).await {
+    match handle_request(
+        org_id,
+        request,
+        OtlpRequestType::HttpJson,
+        in_stream_name,
+        rejected_spans,
+    )
+    .await
+    {
         Ok(v) => Ok(v),
         Err(e) => {
             log::error!(
@@ -144,11 +157,122 @@ pub async fn otlp_json(
     }
 }
 
+/// Parses an OTLP/JSON trace request, dropping the spans that can't be decoded instead of
+/// rejecting the whole batch. Returns the request with the valid spans and the number of
+/// dropped spans.
+fn parse_otlp_json(body: &[u8]) -> Result<(ExportTraceServiceRequest, i64), json::Error> {
+    let mut value: json::Value = json::from_slice(body)?;
+    let mut rejected_spans = 0;
+    if let Some(res_spans) = value
+        .get_mut("resourceSpans")
+        .and_then(|v| v.as_array_mut())
+    {
+        for res_span in res_spans.iter_mut() {
+            let Some(scope_spans) = res_span
+                .get_mut("scopeSpans")
+                .and_then(|v| v.as_array_mut())
+            else {
+                continue;
+            };
+            for scope_span in scope_spans.iter_mut() {
+                let Some(spans) = scope_span.get_mut("spans").and_then(|v| v.as_array_mut()) else {
+                    continue;
+                };
+                spans.retain(|span| {
+                    match json::from_value::<opentelemetry_proto::tonic::trace::v1::Span>(
+                        span.clone(),
+                    ) {
+                        Ok(_) => true,
+                        Err(e) => {
+                            log::error!("[TRACES:OTLP] skipping invalid span: {}", e);
+                            rejected_spans += 1;
+                            false
+                        }
+                    }
+                });
+            }
+        }
+    }
+    let request = json::from_value::<ExportTraceServiceRequest>(value)?;
+    Ok((request, rejected_spans))
+}
+
+/// Returns the stream the spans of a resource are routed to by the first route matching one of
+/// the resource attributes, if any.
+fn get_routed_stream_name(routes: &[TracesStreamRoute], attributes: &[KeyValue]) -> Option<String> {
+    routes.iter().find_map(|route| {
+        attributes
+            .iter()
+            .any(|attr| {
+                attr.key == route.attribute
+                    && get_val(&attr.value.as_ref()).as_str() == Some(route.value.as_str())
+            })
+            .then(|| format_stream_name(&route.stream))
+    })
+}
+
+/// Returns the ids of the traces having a span with an error status, kept regardless of the
+/// sampling ratio of their stream.
+///
+/// Sampling is decided per request, as the spans arrive, not once the trace is complete. Only
+/// the spans sent in the same request as the failed one are kept for sure: spans of the trace
+/// sent in an earlier or a later request are kept or dropped by the ratio alone.
+fn get_error_trace_ids(res_spans: &[ResourceSpans]) -> HashSet<Vec<u8>> {
+    res_spans
+        .iter()
+        .flat_map(|res_span| res_span.scope_spans.iter())
+        .flat_map(|scope_span| scope_span.spans.iter())
+        .filter(|span| {
+            span.status
+                .as_ref()
+                .is_some_and(|status| status.code() == StatusCode::Error)
+        })
+        .map(|span| span.trace_id.clone())
+        .collect()
+}
+
+/// Whether a span of the given trace is kept by the stream's sampling ratio.
+///
+/// Like the OpenTelemetry `TraceIdRatioBased` sampler, the decision is derived from the random
+/// low bytes of the trace id, so all the spans of a trace are kept or dropped together.
+fn is_span_sampled(
+    trace_id: &[u8],
+    sampling_ratio: Option<f64>,
+    error_trace_ids: &HashSet<Vec<u8>>,
+) -> bool {
+    let Some(ratio) = sampling_ratio else {
+        return true;
+    };
+    if error_trace_ids.contains(trace_id) {
+        return true;
+    }
+    let Some(low_bytes) = trace_id
+        .get(8..TRACE_ID_BYTES_COUNT)
+        .and_then(|b| <[u8; 8]>::try_from(b).ok())
+    else {
+        return true;
+    };
+    let upper_bound = (ratio.clamp(0.0, 1.0) * (1u64 << 63) as f64) as u64;
+    (u64::from_be_bytes(low_bytes) >> 1) < upper_bound
+}
+
 pub async fn handle_otlp_request(
     org_id: &str,
     request: ExportTraceServiceRequest,
     req_type: OtlpRequestType,
     in_stream_name: Option<&str>,
+) -> Result<HttpResponse, Error> {
+    handle_request(org_id, request, req_type, in_stream_name, 0).await
+}
+
+/// Ingests the spans of an OTLP request. `rejected_spans` are the spans already dropped while
+/// decoding the request, reported back in the partial success response.
+async fn handle_request(
+    org_id: &str,
+    request: ExportTraceServiceRequest,
+    req_type: OtlpRequestType,
+    in_stream_name: Option<&str>,
+    rejected_spans: i64,
 ) -> Result<HttpResponse, Error> {
     let start = std::time::Instant::now();
     let started_at = Utc::now().timestamp_micros();
@@ -190,27 +314,85 @@ pub async fn handle_otlp_request(
         Some(name) => format_stream_name(name),
         None => "default".to_owned(),
     };
+    // the org routes only apply when the request doesn't name its stream
+    let stream_routes = match in_stream_name {
+        Some(_) => vec![],
+        None => db::organization::get_org_setting(org_id)
+            .await
+            .map(|setting| setting.traces_stream_routing)
+            .unwrap_or_default(),
+    };
+    if let Err(e) = check_ingestion_paused(org_id, StreamType::Traces, &traces_stream_name).await {
+        return Ok(
+            HttpResponse::ServiceUnavailable().json(MetaHttpResponse::error(
+                http::StatusCode::SERVICE_UNAVAILABLE.into(),
+                e.to_string(),
+            )),
+        );
+    }
     let min_ts = (Utc::now()
         - Duration::try_hours(cfg.limit.ingest_allowed_upto)
             .expect("configuration error: too large ingest_allowed_upto"))
     .timestamp_micros();
 
     // Start retrieving associated pipeline and construct pipeline params
-    let executable_pipeline = crate::service::ingestion::get_stream_executable_pipeline(
-        org_id,
-        &traces_stream_name,
-        &StreamType::Traces,
-    )
-    .await;
-    let mut stream_pipeline_inputs = ExecutablePipelineTraceInputs::new();
+    let mut stream_executable_pipelines: HashMap<String, Option<ExecutablePipeline>> =
+        HashMap::new();
+    stream_executable_pipelines.insert(
+        traces_stream_name.clone(),
+        crate::service::ingestion::get_stream_executable_pipeline(
+            org_id,
+            &traces_stream_name,
+            &StreamType::Traces,
+        )
+        .await,
+    );
+    let mut stream_pipeline_inputs: HashMap<String, ExecutablePipelineTraceInputs> = HashMap::new();
     // End pipeline params construction
 
     let mut service_name: String = traces_stream_name.to_string();
     let res_spans = request.resource_spans;
     let mut json_data_by_stream = HashMap::new();
     let mut span_metrics = Vec::with_capacity(res_spans.len());
-    let mut partial_success = ExportTracePartialSuccess::default();
+    let mut partial_success = ExportTracePartialSuccess {
+        rejected_spans,
+        ..Default::default()
+    };
+    let error_trace_ids = get_error_trace_ids(&res_spans);
+    let mut stream_sampling_ratios: HashMap<String, Option<f64>> = HashMap::new();
     for res_span in res_spans {
+        let stream_name = res_span
+            .resource
+            .as_ref()
+            .and_then(|resource| get_routed_stream_name(&stream_routes, &resource.attributes))
+            .unwrap_or_else(|| traces_stream_name.clone());
+        if !stream_executable_pipelines.contains_key(&stream_name) {
+            if let Err(e) = check_ingestion_paused(org_id, StreamType::Traces, &stream_name).await {
+                log::error!("[TRACES:OTLP] skipping spans routed to stream {stream_name}: {e}");
+                partial_success.rejected_spans += res_span
+                    .scope_spans
+                    .iter()
+                    .map(|scope_span| scope_span.spans.len() as i64)
+                    .sum::<i64>();
+                continue;
+            }
+            let executable_pipeline = crate::service::ingestion::get_stream_executable_pipeline(
+                org_id,
+                &stream_name,
+                &StreamType::Traces,
+            )
+            .await;
+            stream_executable_pipelines.insert(stream_name.clone(), executable_pipeline);
+        }
+        let has_pipeline = stream_executable_pipelines[&stream_name].is_some();
+        if !stream_sampling_ratios.contains_key(&stream_name) {
+            let ratio = infra::schema::get_settings(org_id, &stream_name, StreamType::Traces)
+                .await
+                .and_then(|settings| settings.trace_sampling_ratio);
+            stream_sampling_ratios.insert(stream_name.clone(), ratio);
+        }
+        let sampling_ratio = stream_sampling_ratios[&stream_name];
+
         let mut service_att_map: HashMap<String, json::Value> = HashMap::new();
         if let Some(resource) = res_span.resource {
             for res_attr in resource.attributes {
@@ -237,6 +419,9 @@ pub async fn handle_otlp_request(
                     partial_success.rejected_spans += 1;
                     continue;
                 }
+                if !is_span_sampled(&span.trace_id, sampling_ratio, &error_trace_ids) {
+                    continue;
+                }
                 let trace_id: String =
                     TraceId::from_bytes(span.trace_id.try_into().unwrap()).to_string();
                 if span.span_id.len() != SPAN_ID_BYTES_COUNT {
@@ -365,15 +550,18 @@ pub async fn handle_otlp_request(
                     json::Value::Number(timestamp.into()),
                 );
 
-                if executable_pipeline.is_some() {
-                    stream_pipeline_inputs.add_input(
-                        value,
-                        service_name.to_owned(),
-                        span.name,
-                        span_status_for_spanmetric,
-                        span.kind.to_string(),
-                        ((end_time - start_time) / 1_000_000) as f64,
-                    );
+                if has_pipeline {
+                    stream_pipeline_inputs
+                        .entry(stream_name.clone())
+                        .or_insert_with(ExecutablePipelineTraceInputs::new)
+                        .add_input(
+                            value,
+                            service_name.to_owned(),
+                            span.name,
+                            span_status_for_spanmetric,
+                            span.kind.to_string(),
+                            ((end_time - start_time) / 1_000_000) as f64,
+                        );
                 } else {
                     // JSON Flattening
                     value = flatten::flatten(value).map_err(|e| {
@@ -387,7 +575,7 @@ pub async fn handle_otlp_request(
                                 // build span metrics item
                                 let sm = crate::job::metrics::TraceMetricsItem {
                                     organization: org_id.to_string(),
-                                    traces_stream_name: traces_stream_name.clone(),
+                                    traces_stream_name: stream_name.clone(),
                                     service_name: service_name.clone(),
                                     span_name: v
                                         .remove("o2_span_metrics_name")
@@ -418,7 +606,7 @@ pub async fn handle_otlp_request(
                     };
 
                     let (ts_data, _) = json_data_by_stream
-                        .entry(traces_stream_name.to_string())
+                        .entry(stream_name.clone())
                         .or_insert((Vec::new(), None));
                     ts_data.push((timestamp, record_val));
                 }
@@ -427,7 +615,10 @@ pub async fn handle_otlp_request(
     }
 
     // batch process records through pipeline
-    if let Some(exec_pl) = &executable_pipeline {
+    for (stream_name, pipeline_inputs) in stream_pipeline_inputs {
+        let Some(Some(exec_pl)) = stream_executable_pipelines.get(&stream_name) else {
+            continue;
+        };
         let (
             records,
             services,
@@ -435,14 +626,14 @@ pub async fn handle_otlp_request(
             span_status_for_spanmetrics,
             span_kinds,
             span_durations,
-        ) = stream_pipeline_inputs.into_parts();
+        ) = pipeline_inputs.into_parts();
         let records_count = records.len();
         match exec_pl.process_batch(org_id, records).await {
             Err(e) => {
                 log::error!(
                     "[TRACES:OTLP] pipeline({}/{}) batch execution error: {}.",
                     org_id,
-                    traces_stream_name,
+                    stream_name,
                     e
                 );
                 partial_success.rejected_spans += records_count as i64;
@@ -502,7 +693,7 @@ pub async fn handle_otlp_request(
                             continue;
                         };
                         let (ts_data, _) = json_data_by_stream
-                            .entry(traces_stream_name.to_string())
+                            .entry(stream_name.clone())
                             .or_insert((Vec::new(), None));
                         ts_data.push((timestamp, record_val));
                     }
@@ -608,6 +799,14 @@ pub async fn ingest_json(
             )),
         );
     }
+    if let Err(e) = check_ingestion_paused(org_id, StreamType::Traces, traces_stream_name).await {
+        return Ok(
+            HttpResponse::ServiceUnavailable().json(MetaHttpResponse::error(
+                http::StatusCode::SERVICE_UNAVAILABLE.into(),
+                e.to_string(),
+            )),
+        );
+    }
 
     let cfg = get_config();
     let min_ts = (Utc::now()
@@ -729,9 +928,13 @@ fn format_response(
 ) -> Result<HttpResponse, Error> {
     let partial = partial_success.rejected_spans > 0;
 
-    let res = if partial {
+    if partial && partial_success.error_message.is_empty() {
         partial_success.error_message =
-            "Some spans were rejected due to exceeding the allowed retention period".to_string();
+            "Some spans were rejected due to being invalid or exceeding the allowed retention period"
+                .to_string();
+    }
+
+    let res = if partial {
         ExportTraceServiceResponse {
             partial_success: Some(partial_success),
         }
@@ -762,6 +965,13 @@ async fn write_traces_by_stream(
     json_data_by_stream: HashMap<String, O2IngestJsonData>,
 ) -> Result<(), Error> {
     for (traces_stream_name, (json_data, fn_num)) in json_data_by_stream {
+        // the destination streams of a pipeline may be paused too
+        if let Err(e) =
+            check_ingestion_paused(org_id, StreamType::Traces, &traces_stream_name).await
+        {
+            log::warn!("{e}, dropping its records");
+            continue;
+        }
         let mut req_stats = match write_traces(org_id, &traces_stream_name, json_data).await {
             Ok(v) => v,
             Err(e) => {
@@ -994,8 +1204,19 @@ async fn write_traces(
 #[cfg(test)]
 mod tests {
     use config::utils::json::json;
+    use hashbrown::HashSet;
+    use opentelemetry_proto::tonic::{
+        common::v1::{any_value::Value, AnyValue, KeyValue},
+        trace::v1::{status::StatusCode, ResourceSpans, ScopeSpans, Span, Status},
+    };
 
-    use crate::service::ingestion::grpc::get_val_for_attr;
+    use super::{
+        get_error_trace_ids, get_routed_stream_name, http, is_span_sampled, otlp_json,
+        parse_otlp_json, ExportTraceServiceResponse,
+    };
+    use crate::{
+        common::meta::organization::TracesStreamRoute, service::ingestion::grpc::get_val_for_attr,
+    };
 
     #[test]
     fn test_get_val_for_attr() {
@@ -1004,4 +1225,162 @@ mod tests {
         let resp = get_val_for_attr(input);
         assert_eq!(resp.as_str().unwrap(), in_val.to_string());
     }
+
+    #[test]
+    fn test_parse_otlp_json_skips_invalid_spans() {
+        let body = json!({
+            "resourceSpans": [{
+                "scopeSpans": [{
+                    "spans": [
+                        {
+                            "traceId": "5b8efff798038103d269b633813fc60c",
+                            "spanId": "eee19b7ec3c1b174",
+                            "name": "valid",
+                            "kind": 1
+                        },
+                        {
+                            "traceId": "not a trace id",
+                            "spanId": "eee19b7ec3c1b175",
+                            "name": "invalid",
+                            "kind": 1
+                        }
+                    ]
+                }]
+            }]
+        });
+        let (request, rejected_spans) = parse_otlp_json(body.to_string().as_bytes()).unwrap();
+        assert_eq!(rejected_spans, 1);
+        let spans = &request.resource_spans[0].scope_spans[0].spans;
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].name, "valid");
+
+        assert!(parse_otlp_json(b"not json").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_otlp_json_partial_ingestion() {
+        let start = chrono::Utc::now().timestamp_nanos_opt().unwrap();
+        let span = |trace_id: &str, span_id: &str, name: &str| {
+            json!({
+                "traceId": trace_id,
+                "spanId": span_id,
+                "name": name,
+                "kind": 1,
+                "startTimeUnixNano": start.to_string(),
+                "endTimeUnixNano": (start + 1_000_000).to_string()
+            })
+        };
+        let body = json!({
+            "resourceSpans": [{
+                "scopeSpans": [{
+                    "spans": [
+                        span("5b8efff798038103d269b633813fc60c", "eee19b7ec3c1b174", "valid"),
+                        span("not a trace id", "eee19b7ec3c1b175", "invalid")
+                    ]
+                }]
+            }]
+        });
+        let resp = otlp_json(
+            "test_otlp_json_partial",
+            body.to_string().into(),
+            Some("partial_traces"),
+        )
+        .await
+        .unwrap();
+        // the valid span is ingested, the invalid one reported as rejected
+        assert_eq!(resp.status(), http::StatusCode::PARTIAL_CONTENT);
+        let body = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+        let resp: ExportTraceServiceResponse = serde_json::from_slice(&body).unwrap();
+        let partial_success = resp.partial_success.unwrap();
+        assert_eq!(partial_success.rejected_spans, 1);
+        assert!(partial_success.error_message.contains("invalid"));
+    }
+
+    #[test]
+    fn test_get_routed_stream_name() {
+        let route = |value: &str, stream: &str| TracesStreamRoute {
+            attribute: "service.namespace".to_string(),
+            value: value.to_string(),
+            stream: stream.to_string(),
+        };
+        let routes = vec![
+            route("payments", "payments_traces"),
+            route("checkout", "checkout_traces"),
+        ];
+        let resource_attrs = |namespace: &str| {
+            vec![KeyValue {
+                key: "service.namespace".to_string(),
+                value: Some(AnyValue {
+                    value: Some(Value::StringValue(namespace.to_string())),
+                }),
+            }]
+        };
+
+        assert_eq!(
+            get_routed_stream_name(&routes, &resource_attrs("payments")),
+            Some("payments_traces".to_string())
+        );
+        assert_eq!(
+            get_routed_stream_name(&routes, &resource_attrs("checkout")),
+            Some("checkout_traces".to_string())
+        );
+        assert_eq!(
+            get_routed_stream_name(&routes, &resource_attrs("search")),
+            None
+        );
+        assert_eq!(
+            get_routed_stream_name(&[], &resource_attrs("payments")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_span_sampled() {
+        let no_errors = HashSet::new();
+        let trace_ids = (0..10_000)
+            .map(|_| rand::random::<[u8; 16]>().to_vec())
+            .collect::<Vec<_>>();
+        let kept = trace_ids
+            .iter()
+            .filter(|id| is_span_sampled(id, Some(0.1), &no_errors))
+            .count();
+        assert!((800..=1200).contains(&kept), "kept {kept} of 10000 traces");
+
+        assert!(trace_ids
+            .iter()
+            .all(|id| is_span_sampled(id, None, &no_errors)));
+        assert!(trace_ids
+            .iter()
+            .all(|id| !is_span_sampled(id, Some(0.0), &no_errors)));
+    }
+
+    #[test]
+    fn test_sampling_keeps_error_traces() {
+        let span = |trace_id: u8, code: StatusCode| Span {
+            trace_id: vec![trace_id; 16],
+            span_id: vec![trace_id; 8],
+            status: Some(Status {
+                code: code as i32,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let res_spans = vec![ResourceSpans {
+            scope_spans: vec![ScopeSpans {
+                spans: vec![
+                    span(1, StatusCode::Ok),
+                    span(2, StatusCode::Error),
+                    span(2, StatusCode::Unset),
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }];
+        let error_trace_ids = get_error_trace_ids(&res_spans);
+        assert_eq!(error_trace_ids.len(), 1);
+
+        assert!(!is_span_sampled(&[1; 16], Some(0.0), &error_trace_ids));
+        // all the spans of a trace with an error are kept, not only the failed one
+        assert!(is_span_sampled(&[2; 16], Some(0.0), &error_trace_ids));
+    }
 }
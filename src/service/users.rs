@@ -21,6 +21,7 @@ use config::{get_config, ider, utils::rand::generate_random_string};
 use o2_openfga::{
     authorizer::authz::delete_service_account_from_org, config::get_config as get_openfga_config,
 };
+use once_cell::sync::Lazy;
 use regex::Regex;
 
 use crate::{
@@ -30,7 +31,8 @@ use crate::{
             http::HttpResponse as MetaHttpResponse,
             organization::DEFAULT_ORG,
             user::{
-                DBUser, UpdateUser, User, UserList, UserOrg, UserRequest, UserResponse, UserRole,
+                DBUser, EffectivePermission, UpdateUser, User, UserList, UserOrg, UserRequest,
+                UserResponse, UserRole,
             },
         },
         utils::auth::{get_hash, get_role, is_root_user},
@@ -38,21 +40,105 @@ use crate::{
     service::db,
 };
 
+static RE_EMAIL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^([a-z0-9_+]([a-z0-9_+.-]*[a-z0-9_+])?)@([a-z0-9]+([\-\.]{1}[a-z0-9]+)*\.[a-z]{2,6})",
+    )
+    .expect("Email regex is valid")
+});
+
+pub fn is_valid_email(email: &str) -> bool {
+    RE_EMAIL.is_match(email)
+}
+
+#[derive(Debug, Default)]
+struct PasswordPolicy {
+    min_length: usize,
+    require_uppercase: bool,
+    require_lowercase: bool,
+    require_digit: bool,
+    require_special: bool,
+}
+
+impl From<&config::Auth> for PasswordPolicy {
+    fn from(auth: &config::Auth) -> Self {
+        Self {
+            min_length: auth.password_min_length,
+            require_uppercase: auth.password_require_uppercase,
+            require_lowercase: auth.password_require_lowercase,
+            require_digit: auth.password_require_digit,
+            require_special: auth.password_require_special,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    fn check(&self, password: &str) -> Result<(), String> {
+        let mut missing = Vec::new();
+        if password.chars().count() < self.min_length {
+            missing.push(format!("at least {} characters", self.min_length));
+        }
+        if self.require_uppercase && !password.chars().any(|c| c.is_uppercase()) {
+            missing.push("an uppercase letter".to_string());
+        }
+        if self.require_lowercase && !password.chars().any(|c| c.is_lowercase()) {
+            missing.push("a lowercase letter".to_string());
+        }
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            missing.push("a digit".to_string());
+        }
+        if self.require_special && password.chars().all(|c| c.is_alphanumeric()) {
+            missing.push("a special character".to_string());
+        }
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Password must contain {}", missing.join(", ")))
+        }
+    }
+}
+
+/// Validates a password against the configured password policy, returning a
+/// description of the unmet requirements on failure.
+pub fn validate_password(password: &str) -> Result<(), String> {
+    PasswordPolicy::from(&get_config().auth).check(password)
+}
+
+fn weak_password_response(message: String) -> HttpResponse {
+    HttpResponse::BadRequest().json(MetaHttpResponse::error(
+        http::StatusCode::BAD_REQUEST.into(),
+        message,
+    ))
+}
+
+/// The response rejecting a new user whose password breaks the policy.
+/// Service accounts get a generated password and external users never log in
+/// with a local password, so the policy only applies to regular users.
+fn new_user_password_error(policy: &PasswordPolicy, usr_req: &UserRequest) -> Option<HttpResponse> {
+    if usr_req.is_external || usr_req.role.eq(&UserRole::ServiceAccount) {
+        return None;
+    }
+    policy
+        .check(&usr_req.password)
+        .err()
+        .map(weak_password_response)
+}
+
 pub async fn post_user(
     org_id: &str,
     usr_req: UserRequest,
     initiator_id: &str,
 ) -> Result<HttpResponse, Error> {
-    let email_regex = Regex::new(
-        r"^([a-z0-9_+]([a-z0-9_+.-]*[a-z0-9_+])?)@([a-z0-9]+([\-\.]{1}[a-z0-9]+)*\.[a-z]{2,6})",
-    )
-    .expect("Email regex is valid");
-    if !email_regex.is_match(&usr_req.email) {
+    if !is_valid_email(&usr_req.email) {
         return Ok(HttpResponse::BadRequest().json(MetaHttpResponse::error(
             http::StatusCode::BAD_REQUEST.into(),
             "Invalid email".to_string(),
         )));
     }
+    if let Some(resp) = new_user_password_error(&PasswordPolicy::from(&get_config().auth), &usr_req)
+    {
+        return Ok(resp);
+    }
     let cfg = get_config();
     let is_allowed = if is_root_user(initiator_id) {
         true
@@ -243,6 +329,9 @@ pub async fn update_user(
                         &local_user.salt,
                     )) {
                         let new_pass = user.new_password.unwrap();
+                        if let Err(e) = validate_password(&new_pass) {
+                            return Ok(weak_password_response(e));
+                        }
 
                         new_user.password = get_hash(&new_pass, &local_user.salt);
                         new_user.password_ext = Some(get_hash(&new_pass, password_ext_salt));
@@ -263,6 +352,9 @@ pub async fn update_user(
                     && !local_user.is_external
                 {
                     let new_pass = user.new_password.unwrap();
+                    if let Err(e) = validate_password(&new_pass) {
+                        return Ok(weak_password_response(e));
+                    }
 
                     new_user.password = get_hash(&new_pass, &local_user.salt);
                     new_user.password_ext = Some(get_hash(&new_pass, password_ext_salt));
@@ -518,6 +610,22 @@ pub async fn get_user(org_id: Option<&str>, name: &str) -> Option<User> {
     }
 }
 
+/// Looks up the service account of `org_id` that owns the given API token.
+pub fn get_service_account_by_token(org_id: &str, token: &str) -> Option<User> {
+    if token.is_empty() {
+        return None;
+    }
+    let prefix = format!("{org_id}/");
+    USERS
+        .iter()
+        .find(|user| {
+            user.key().starts_with(&prefix)
+                && user.value().role.eq(&UserRole::ServiceAccount)
+                && user.value().token.eq(token)
+        })
+        .map(|user| user.value().clone())
+}
+
 pub async fn get_user_by_token(org_id: &str, token: &str) -> Option<User> {
     let rum_tokens = USERS_RUM_TOKEN.clone();
     let key = format!("{DEFAULT_ORG}/{token}");
@@ -799,6 +907,67 @@ pub(crate) async fn create_root_user(org_id: &str, usr_req: UserRequest) -> Resu
     Ok(())
 }
 
+const PERMISSION_ACTIONS: [&str; 4] = ["GET", "POST", "PUT", "DELETE"];
+
+/// Returns the union of the permissions the user gets from its roles and
+/// groups. Without OpenFGA the permissions only depend on the user's role.
+pub async fn get_effective_permissions(
+    _org_id: &str,
+    user: &User,
+) -> Result<Vec<EffectivePermission>, anyhow::Error> {
+    #[cfg(feature = "enterprise")]
+    if get_openfga_config().enabled && !is_root_user(&user.email) {
+        let mut permitted = Vec::new();
+        for resource in o2_openfga::meta::mapping::OFGA_MODELS.values() {
+            for action in PERMISSION_ACTIONS {
+                let objects = o2_openfga::authorizer::authz::list_objects(
+                    &user.email,
+                    action,
+                    resource.key,
+                    _org_id,
+                )
+                .await?;
+                permitted.push((action.to_string(), objects));
+            }
+        }
+        return Ok(merge_permissions(permitted));
+    }
+
+    Ok(role_permissions(&user.role))
+}
+
+/// Merges the objects permitted per action into a sorted set of permissions.
+#[cfg_attr(not(feature = "enterprise"), allow(dead_code))]
+fn merge_permissions(
+    permitted: impl IntoIterator<Item = (String, Vec<String>)>,
+) -> Vec<EffectivePermission> {
+    permitted
+        .into_iter()
+        .flat_map(|(action, objects)| {
+            objects
+                .into_iter()
+                .map(move |object| EffectivePermission::new(&object, &action))
+        })
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Permissions granted by the role alone, on all the objects of the org.
+fn role_permissions(role: &UserRole) -> Vec<EffectivePermission> {
+    let actions: &[&str] = match role {
+        #[cfg(feature = "enterprise")]
+        UserRole::Viewer => &PERMISSION_ACTIONS[..1],
+        #[cfg(feature = "enterprise")]
+        UserRole::User => &[],
+        _ => &PERMISSION_ACTIONS,
+    };
+    actions
+        .iter()
+        .map(|action| EffectivePermission::new("*", action))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use infra::db as infra_db;
@@ -920,4 +1089,97 @@ mod tests {
 
         assert!(resp.is_ok());
     }
+
+    #[test]
+    fn test_merge_permissions() {
+        // a user in two roles gets the union of the objects permitted by each
+        let role_a = vec![
+            (
+                "GET".to_string(),
+                vec!["logs:default".to_string(), "dashboard:d1".to_string()],
+            ),
+            ("POST".to_string(), vec!["dashboard:d1".to_string()]),
+        ];
+        let role_b = vec![
+            (
+                "GET".to_string(),
+                vec!["logs:default".to_string(), "alert:a1".to_string()],
+            ),
+            ("DELETE".to_string(), vec!["alert:a1".to_string()]),
+        ];
+        let merged = merge_permissions(role_a.into_iter().chain(role_b));
+        assert_eq!(
+            merged,
+            vec![
+                EffectivePermission::new("alert:a1", "DELETE"),
+                EffectivePermission::new("alert:a1", "GET"),
+                EffectivePermission::new("dashboard:d1", "GET"),
+                EffectivePermission::new("dashboard:d1", "POST"),
+                EffectivePermission::new("logs:default", "GET"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_role_permissions() {
+        let permissions = role_permissions(&UserRole::Admin);
+        assert_eq!(permissions.len(), PERMISSION_ACTIONS.len());
+        assert!(permissions.iter().all(|p| p.object == "*"));
+    }
+
+    #[test]
+    fn test_password_policy() {
+        let policy = PasswordPolicy {
+            min_length: 10,
+            require_uppercase: true,
+            require_lowercase: true,
+            require_digit: true,
+            require_special: true,
+        };
+        assert!(policy.check("Complexpass#123").is_ok());
+
+        let err = policy.check("pass").unwrap_err();
+        assert_eq!(
+            err,
+            "Password must contain at least 10 characters, an uppercase letter, a digit, a special character"
+        );
+        assert!(PasswordPolicy::default().check("pass").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_new_user_password_error() {
+        let policy = PasswordPolicy {
+            min_length: 10,
+            require_digit: true,
+            ..Default::default()
+        };
+        let mut usr_req = UserRequest {
+            email: "weak@zo.dev".to_string(),
+            password: "pass".to_string(),
+            role: UserRole::Admin,
+            first_name: "weak".to_owned(),
+            last_name: "".to_owned(),
+            is_external: false,
+        };
+        let resp = new_user_password_error(&policy, &usr_req).unwrap();
+        assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+        let body: MetaHttpResponse =
+            serde_json::from_slice(&actix_web::body::to_bytes(resp.into_body()).await.unwrap())
+                .unwrap();
+        assert_eq!(
+            body.message,
+            "Password must contain at least 10 characters, a digit"
+        );
+
+        usr_req.password = "longer_pass#123".to_string();
+        assert!(new_user_password_error(&policy, &usr_req).is_none());
+
+        // neither service accounts nor external users are held to the policy
+        usr_req.password = "pass".to_string();
+        usr_req.role = UserRole::ServiceAccount;
+        assert!(new_user_password_error(&policy, &usr_req).is_none());
+        usr_req.role = UserRole::Admin;
+        usr_req.is_external = true;
+        assert!(new_user_password_error(&policy, &usr_req).is_none());
+    }
 }
@@ -25,13 +25,24 @@ mod tests {
     use config::{
         get_config,
         meta::{
-            alerts::{alert::Alert, Operator, QueryCondition, TriggerCondition},
-            dashboards::{v1, Dashboard},
+            alerts::{
+                alert::{Alert, ListAlertsParams},
+                Operator, QueryCondition, TriggerCondition,
+            },
+            dashboards::{bundle::DashboardConfigBundle, v1, Dashboard},
+            folder::{Folder, FolderType, DEFAULT_FOLDER},
+            otlp::OtlpRequestType,
+            stream::StreamType,
             triggers::Trigger,
         },
         utils::json,
     };
+    use infra::{
+        db::{connect_to_orm, ORM_CLIENT},
+        table,
+    };
     use openobserve::{
+        common::meta::stream::StreamDeletePlan,
         handler::{
             grpc::{auth::check_auth, flight::FlightServiceImpl},
             http::{
@@ -39,7 +50,12 @@ mod tests {
                 router::*,
             },
         },
-        service::{alerts::scheduler::handle_triggers, search::SEARCH_SERVER},
+        service::{
+            alerts::{alert, bundle, destinations, scheduler::handle_triggers},
+            dashboards, db, folders,
+            search::SEARCH_SERVER,
+            traces,
+        },
     };
     use prost::Message;
     use proto::{cluster_rpc::search_server::SearchServer, prometheus_rpc};
@@ -142,6 +158,7 @@ mod tests {
         e2e_post_json().await;
         e2e_post_multi().await;
         e2e_post_trace().await;
+        e2e_traces_stream_routing().await;
         e2e_post_metrics().await;
         // e2e_post_kinesis_data().await;
 
@@ -150,6 +167,9 @@ mod tests {
         e2e_get_stream_schema().await;
         e2e_get_org_summary().await;
         e2e_post_stream_settings().await;
+        e2e_pause_ingestion().await;
+        e2e_stream_timestamp_field().await;
+        e2e_rejected_stream().await;
         e2e_get_org().await;
 
         // functions
@@ -160,6 +180,7 @@ mod tests {
         // search
         e2e_search().await;
         e2e_search_around().await;
+        e2e_search_trace_id().await;
 
         // users
         e2e_post_user().await;
@@ -195,6 +216,7 @@ mod tests {
             e2e_delete_dashboard(&board.v1.unwrap().dashboard_id).await;
             assert!(e2e_list_dashboards().await.is_empty());
         }
+        e2e_dashboard_config_import().await;
 
         // alert
         e2e_post_alert_template().await;
@@ -207,6 +229,9 @@ mod tests {
         e2e_delete_alert_multirange().await;
         e2e_post_alert().await;
         e2e_get_alert().await;
+        e2e_alert_config_import().await;
+        e2e_backtest_alert().await;
+        e2e_delete_stream_dry_run().await;
         e2e_handle_alert_after_destination_retries().await;
         e2e_handle_alert_after_evaluation_retries().await;
         e2e_handle_alert_reached_max_retries().await;
@@ -433,6 +458,322 @@ mod tests {
         assert!(resp.status().is_success());
     }
 
+    async fn e2e_delete_stream_dry_run() {
+        let auth = setup();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::JsonConfig::default().limit(get_config().limit.req_json_limit))
+                .app_data(web::PayloadConfig::new(
+                    get_config().limit.req_payload_limit,
+                ))
+                .configure(get_service_routes)
+                .configure(get_basic_routes),
+        )
+        .await;
+
+        // a dashboard reading the stream, next to the alert of e2e_post_alert
+        let body_str = r##"{"title":"olympics","dashboardId":"","description":"","role":"","owner":"root@example.com","created":"2023-03-30T07:49:41.744+00:00","panels":[{"id":"Panel_ID1","type":"bar","fields":{"stream":"olympics_schema","stream_type":"logs","x":[],"y":[],"filter":[]},"config":{"title":"p1","description":"","show_legends":true},"query":"SELECT count(*) FROM \"olympics_schema\"","customQuery":true}],"layouts":[]}"##;
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/{}/dashboards", "e2e"))
+            .insert_header(ContentType::json())
+            .append_header(auth)
+            .set_payload(body_str)
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        let board: Dashboard = json::from_slice(&body).unwrap();
+
+        let dry_run = || async {
+            let req = test::TestRequest::delete()
+                .uri(&format!(
+                    "/api/{}/streams/{}?dry_run=true",
+                    "e2e", "olympics_schema"
+                ))
+                .append_header(auth)
+                .to_request();
+            let body = test::call_and_read_body(&app, req).await;
+            json::from_slice::<StreamDeletePlan>(&body).unwrap()
+        };
+        // the alert reaches the cache through the watcher
+        let mut plan = dry_run().await;
+        for _ in 0..50 {
+            if !plan.alerts.is_empty() {
+                break;
+            }
+            tokio::time::sleep(time::Duration::from_millis(100)).await;
+            plan = dry_run().await;
+        }
+        assert!(plan.schema_versions > 0);
+        assert!(plan.alerts.contains(&"alertChk".to_string()));
+        assert_eq!(plan.dashboards, vec!["olympics".to_string()]);
+        assert!(plan.confirm_token.is_some());
+
+        // the stream is still there and isn't deleted without the token
+        let req = test::TestRequest::delete()
+            .uri(&format!("/api/{}/streams/{}", "e2e", "olympics_schema"))
+            .append_header(auth)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 400);
+        assert!(dry_run().await.schema_versions > 0);
+
+        e2e_delete_dashboard(&board.v1.unwrap().dashboard_id).await;
+        assert!(dry_run().await.dashboards.is_empty());
+    }
+
+    async fn e2e_pause_ingestion() {
+        let auth = setup();
+        let thread_id: usize = 0;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::JsonConfig::default().limit(get_config().limit.req_json_limit))
+                .app_data(web::PayloadConfig::new(
+                    get_config().limit.req_payload_limit,
+                ))
+                .app_data(web::Data::new(thread_id))
+                .configure(get_service_routes)
+                .configure(get_basic_routes),
+        )
+        .await;
+        let set_paused = |stream_name: &str, stream_type: &str, paused: bool| {
+            test::TestRequest::put()
+                .uri(&format!(
+                    "/api/e2e/streams/{stream_name}/ingestion?type={stream_type}&paused={paused}"
+                ))
+                .append_header(auth)
+                .to_request()
+        };
+
+        // logs
+        let body_str = r#"[{"Year": 1896, "City": "Athens", "Country": "AUT"}]"#;
+        let resp = test::call_service(&app, set_paused("olympics_schema", "logs", true)).await;
+        assert!(resp.status().is_success());
+        let req = test::TestRequest::post()
+            .uri("/api/e2e/olympics_schema/_json")
+            .insert_header(ContentType::json())
+            .append_header(auth)
+            .set_payload(body_str)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 503);
+        let resp = test::call_service(&app, set_paused("olympics_schema", "logs", false)).await;
+        assert!(resp.status().is_success());
+
+        // traces
+        let resp = test::call_service(&app, set_paused("default", "traces", true)).await;
+        assert!(resp.status().is_success());
+        let resp = traces::ingest_json(
+            "e2e",
+            Bytes::from(r#"[{"trace_id": "t1", "span_id": "s1"}]"#),
+            OtlpRequestType::Grpc,
+            "default",
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.status(), 503);
+        let resp = test::call_service(&app, set_paused("default", "traces", false)).await;
+        assert!(resp.status().is_success());
+
+        // metrics write to a stream per metric, the records of a paused one
+        // are dropped
+        let post_metric = |value: i64| {
+            test::TestRequest::post()
+                .uri("/api/e2e/ingest/metrics/_json")
+                .insert_header(ContentType::json())
+                .append_header(auth)
+                .set_payload(
+                    json::json!([{
+                        "__name__": "e2e_paused_metric",
+                        "__type__": "counter",
+                        "job": "e2e",
+                        "_timestamp": Utc::now().timestamp_micros(),
+                        "value": value,
+                    }])
+                    .to_string(),
+                )
+                .to_request()
+        };
+        let resp = test::call_service(&app, post_metric(1)).await;
+        assert!(resp.status().is_success());
+        let resp = test::call_service(&app, set_paused("e2e_paused_metric", "metrics", true)).await;
+        assert!(resp.status().is_success());
+        let resp = test::call_service(&app, post_metric(2)).await;
+        assert!(resp.status().is_success());
+        let resp =
+            test::call_service(&app, set_paused("e2e_paused_metric", "metrics", false)).await;
+        assert!(resp.status().is_success());
+
+        let now = Utc::now().timestamp_micros();
+        let body_str = json::json!({
+            "query": {
+                "sql": "select count(*) as cnt from e2e_paused_metric",
+                "start_time": now - Duration::try_hours(1).unwrap().num_microseconds().unwrap(),
+                "end_time": now + 1,
+            }
+        })
+        .to_string();
+        let req = test::TestRequest::post()
+            .uri("/api/e2e/_search?type=metrics")
+            .insert_header(ContentType::json())
+            .append_header(auth)
+            .set_payload(body_str)
+            .to_request();
+        let resp: json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(resp["hits"][0]["cnt"], 1);
+    }
+
+    async fn e2e_stream_timestamp_field() {
+        let auth = setup();
+        let thread_id: usize = 0;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::JsonConfig::default().limit(get_config().limit.req_json_limit))
+                .app_data(web::PayloadConfig::new(
+                    get_config().limit.req_payload_limit,
+                ))
+                .app_data(web::Data::new(thread_id))
+                .configure(get_service_routes)
+                .configure(get_basic_routes),
+        )
+        .await;
+        let post_json = |body: json::Value| {
+            test::TestRequest::post()
+                .uri("/api/e2e/e2e_event_time/_json")
+                .insert_header(ContentType::json())
+                .append_header(auth)
+                .set_payload(body.to_string())
+                .to_request()
+        };
+
+        // ingested before the setting, so it is timed at ingestion
+        let resp = test::call_service(&app, post_json(json::json!([{"msg": "before"}]))).await;
+        assert!(resp.status().is_success());
+        let req = test::TestRequest::put()
+            .uri("/api/e2e/streams/e2e_event_time/settings")
+            .insert_header(ContentType::json())
+            .append_header(auth)
+            .set_payload(r#"{"timestamp_field": "event_time"}"#)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        let now = Utc::now();
+        let event_time = |hours: i64| (now - Duration::try_hours(hours).unwrap()).to_rfc3339();
+        let resp = test::call_service(
+            &app,
+            post_json(json::json!([
+                {"msg": "a", "event_time": event_time(3)},
+                {"msg": "b", "event_time": event_time(2)},
+            ])),
+        )
+        .await;
+        assert!(resp.status().is_success());
+
+        // the records are searched by their event time, and the field sorts
+        // and buckets them like _timestamp
+        let search = |sql: &str| {
+            let body_str = json::json!({
+                "query": {
+                    "sql": sql,
+                    "start_time": (now - Duration::try_hours(4).unwrap()).timestamp_micros(),
+                    "end_time": (now - Duration::try_hours(1).unwrap()).timestamp_micros(),
+                }
+            })
+            .to_string();
+            test::TestRequest::post()
+                .uri("/api/e2e/_search")
+                .insert_header(ContentType::json())
+                .append_header(auth)
+                .set_payload(body_str)
+                .to_request()
+        };
+        let resp: json::Value = test::call_and_read_body_json(
+            &app,
+            search("select msg from e2e_event_time order by event_time desc"),
+        )
+        .await;
+        let msgs = resp["hits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["msg"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(msgs, vec!["b", "a"]);
+        let resp: json::Value = test::call_and_read_body_json(
+            &app,
+            search(
+                "select histogram(event_time, '1 hour') as h, count(*) as cnt from e2e_event_time group by h order by h",
+            ),
+        )
+        .await;
+        assert_eq!(resp["hits"].as_array().unwrap().len(), 2);
+    }
+
+    async fn e2e_rejected_stream() {
+        let auth = setup();
+        let org_id = "e2e_rejected";
+        let thread_id: usize = 0;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::JsonConfig::default().limit(get_config().limit.req_json_limit))
+                .app_data(web::PayloadConfig::new(
+                    get_config().limit.req_payload_limit,
+                ))
+                .app_data(web::Data::new(thread_id))
+                .configure(get_service_routes)
+                .configure(get_basic_routes),
+        )
+        .await;
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/{org_id}/settings"))
+            .insert_header(ContentType::json())
+            .append_header(auth)
+            .set_payload(r#"{"enable_rejected_stream": true}"#)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        // the record with an unparsable timestamp is dropped from the stream
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/{org_id}/e2e_app/_json"))
+            .insert_header(ContentType::json())
+            .append_header(auth)
+            .set_payload(
+                json::json!([
+                    {"msg": "kept"},
+                    {"msg": "dropped", "_timestamp": "not a timestamp"},
+                ])
+                .to_string(),
+            )
+            .to_request();
+        let resp: json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(resp["status"][0]["successful"], 1);
+        assert_eq!(resp["status"][0]["failed"], 1);
+
+        // and lands in the rejected stream with its reason
+        let now = Utc::now();
+        let body_str = json::json!({
+            "query": {
+                "sql": "select stream, reason, record from \"_rejected\"",
+                "start_time": (now - Duration::try_hours(1).unwrap()).timestamp_micros(),
+                "end_time": (now + Duration::try_hours(1).unwrap()).timestamp_micros(),
+            }
+        })
+        .to_string();
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/{org_id}/_search"))
+            .insert_header(ContentType::json())
+            .append_header(auth)
+            .set_payload(body_str)
+            .to_request();
+        let resp: json::Value = test::call_and_read_body_json(&app, req).await;
+        let hits = resp["hits"].as_array().unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0]["stream"], "e2e_app");
+        assert_eq!(hits[0]["reason"], "Can't parse timestamp");
+        let record: json::Value = json::from_str(hits[0]["record"].as_str().unwrap()).unwrap();
+        assert_eq!(record["msg"], "dropped");
+    }
+
     async fn e2e_get_org() {
         let auth = setup();
         let app = test::init_service(
@@ -581,6 +922,76 @@ mod tests {
         assert!(resp.status().is_success());
     }
 
+    async fn e2e_search_trace_id() {
+        let auth = setup();
+        let body_str = r#"{
+            "query": {
+                "sql": "select * from olympics_schema",
+                "from": 0,
+                "size": 10,
+                "start_time": 1714857600000,
+                "end_time": 1714944000000
+            }
+        }"#;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::JsonConfig::default().limit(get_config().limit.req_json_limit))
+                .app_data(web::PayloadConfig::new(
+                    get_config().limit.req_payload_limit,
+                ))
+                .configure(get_service_routes)
+                .configure(get_basic_routes),
+        )
+        .await;
+
+        // the client trace id is used for the search and echoed back
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/{}/_search", "e2e"))
+            .insert_header(ContentType::json())
+            .insert_header(("X-Trace-Id", "e2e-search-trace-1"))
+            .append_header(auth)
+            .set_payload(body_str)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        assert_eq!(
+            resp.headers().get("X-Trace-Id").unwrap(),
+            "e2e-search-trace-1"
+        );
+        let body: json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["trace_id"], "e2e-search-trace-1");
+
+        let ts = chrono::Utc::now().timestamp_micros();
+        let req = test::TestRequest::get()
+            .uri(&format!(
+                "/api/{}/{}/_around?key={}&size=10",
+                "e2e", "olympics_schema", ts
+            ))
+            .insert_header(("X-Trace-Id", "e2e-around-trace-1"))
+            .append_header(auth)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        assert_eq!(
+            resp.headers().get("X-Trace-Id").unwrap(),
+            "e2e-around-trace-1"
+        );
+
+        // an invalid trace id is replaced by a generated one
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/{}/_search", "e2e"))
+            .insert_header(ContentType::json())
+            .insert_header(("X-Trace-Id", "not a trace id!"))
+            .append_header(auth)
+            .set_payload(body_str)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let trace_id = resp.headers().get("X-Trace-Id").unwrap().to_str().unwrap();
+        assert!(!trace_id.is_empty());
+        assert_ne!(trace_id, "not a trace id!");
+    }
+
     async fn e2e_list_users() {
         let auth = setup();
         let app = test::init_service(
@@ -864,6 +1275,98 @@ mod tests {
         json::from_slice(&body).unwrap()
     }
 
+    async fn e2e_dashboard_config_import() {
+        let org_id = "e2e_dash_import";
+        let dashboard = |folder_id: &str, dashboard_id: &str, title: &str| {
+            json::json!({
+                "folder_id": folder_id,
+                "dashboard": {
+                    "version": 5,
+                    "hash": "0",
+                    "v5": {
+                        "version": 5,
+                        "dashboardId": dashboard_id,
+                        "title": title,
+                        "description": "",
+                        "role": "",
+                        "owner": "root@example.com",
+                        "created": "2025-01-01T00:00:00Z",
+                        "tabs": [],
+                    },
+                },
+            })
+        };
+        let folder_of = |dashboard_id: &'static str| async move {
+            table::dashboards::get_by_id(org_id, dashboard_id)
+                .await
+                .unwrap()
+                .unwrap()
+                .0
+                .folder_id
+        };
+
+        // the org already has a folder named like one of the exported ones
+        let ops = folders::save_folder(
+            org_id,
+            Folder {
+                folder_id: "".to_string(),
+                name: "Ops".to_string(),
+                description: "".to_string(),
+            },
+            FolderType::Dashboards,
+            false,
+        )
+        .await
+        .unwrap();
+
+        let folders = json::json!([
+            {"folder_id": "e2eOpsFolder", "name": "Ops"},
+            {"folder_id": "e2eNetFolder", "name": "Network"},
+        ]);
+        let bundle: DashboardConfigBundle = json::from_value(json::json!({
+            "version": 1,
+            "folders": folders,
+            "dashboards": [
+                dashboard("e2eOpsFolder", "e2eDash1", "CPU"),
+                dashboard("e2eNetFolder", "e2eDash2", "Traffic"),
+                dashboard(DEFAULT_FOLDER, "e2eDash3", "Overview"),
+            ],
+        }))
+        .unwrap();
+        dashboards::bundle::import_all(org_id, bundle)
+            .await
+            .unwrap();
+        assert_eq!(folder_of("e2eDash1").await, ops.folder_id);
+        assert_eq!(folder_of("e2eDash2").await, "e2eNetFolder");
+        assert_eq!(folder_of("e2eDash3").await, DEFAULT_FOLDER);
+
+        // importing again moves the dashboard to its new folder
+        let bundle: DashboardConfigBundle = json::from_value(json::json!({
+            "version": 1,
+            "folders": folders,
+            "dashboards": [
+                dashboard("e2eOpsFolder", "e2eDash1", "CPU"),
+                dashboard("e2eOpsFolder", "e2eDash2", "Traffic"),
+                dashboard(DEFAULT_FOLDER, "e2eDash3", "Overview"),
+            ],
+        }))
+        .unwrap();
+        dashboards::bundle::import_all(org_id, bundle)
+            .await
+            .unwrap();
+        assert_eq!(folder_of("e2eDash1").await, ops.folder_id);
+        assert_eq!(folder_of("e2eDash2").await, ops.folder_id);
+        assert_eq!(folder_of("e2eDash3").await, DEFAULT_FOLDER);
+        let names = table::folders::list_folders(org_id, FolderType::Dashboards)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|f| f.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names.iter().filter(|n| *n == "Ops").count(), 1);
+        assert_eq!(names.iter().filter(|n| *n == "Network").count(), 1);
+    }
+
     async fn e2e_list_dashboards() -> Vec<Dashboard> {
         let auth = setup();
         let app = test::init_service(
@@ -992,6 +1495,99 @@ mod tests {
         assert!(resp.status().is_success());
     }
 
+    async fn e2e_traces_stream_routing() {
+        let auth = setup();
+        let org_id = "e2e_routing";
+        let thread_id: usize = 0;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::JsonConfig::default().limit(get_config().limit.req_json_limit))
+                .app_data(web::PayloadConfig::new(
+                    get_config().limit.req_payload_limit,
+                ))
+                .app_data(web::Data::new(thread_id))
+                .configure(get_service_routes)
+                .configure(get_basic_routes),
+        )
+        .await;
+
+        let body = json::json!({
+            "traces_stream_routing": [
+                {"attribute": "service.namespace", "value": "payments", "stream": "payments_traces"},
+                {"attribute": "service.namespace", "value": "checkout", "stream": "checkout_traces"},
+            ]
+        });
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/{org_id}/settings"))
+            .insert_header(ContentType::json())
+            .append_header(auth)
+            .set_payload(body.to_string())
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        let now = Utc::now().timestamp_nanos_opt().unwrap();
+        let resource_spans = |namespaces: &[&str]| {
+            let spans = namespaces
+                .iter()
+                .enumerate()
+                .map(|(i, namespace)| {
+                    json::json!({
+                        "resource": {
+                            "attributes": [
+                                {"key": "service.name", "value": {"stringValue": "e2e"}},
+                                {"key": "service.namespace", "value": {"stringValue": namespace}},
+                            ]
+                        },
+                        "scopeSpans": [{
+                            "spans": [{
+                                "traceId": format!("{:032x}", i + 1),
+                                "spanId": format!("{:016x}", i + 1),
+                                "name": "e2e",
+                                "kind": 1,
+                                "startTimeUnixNano": (now - 1_000_000).to_string(),
+                                "endTimeUnixNano": now.to_string(),
+                            }]
+                        }]
+                    })
+                })
+                .collect::<Vec<_>>();
+            json::json!({ "resourceSpans": spans }).to_string()
+        };
+        let stream_exists = |stream_name: &'static str| async move {
+            !infra::schema::get(org_id, stream_name, StreamType::Traces)
+                .await
+                .unwrap()
+                .fields()
+                .is_empty()
+        };
+
+        // spans of a request without a stream are routed by namespace
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/{org_id}/v1/traces"))
+            .insert_header(ContentType::json())
+            .append_header(auth)
+            .set_payload(resource_spans(&["payments", "search"]))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        assert!(stream_exists("payments_traces").await);
+        assert!(stream_exists("default").await);
+
+        // a stream named by the request takes all its spans
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/{org_id}/v1/traces"))
+            .insert_header(ContentType::json())
+            .insert_header((get_config().grpc.stream_header_key.as_str(), "explicit"))
+            .append_header(auth)
+            .set_payload(resource_spans(&["checkout"]))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        assert!(stream_exists("explicit").await);
+        assert!(!stream_exists("checkout_traces").await);
+    }
+
     async fn e2e_post_metrics() {
         let auth = setup();
 
@@ -1857,6 +2453,156 @@ mod tests {
         assert!(resp.status().is_success());
     }
 
+    async fn e2e_alert_config_import() {
+        let auth = setup();
+        let body_str = "[{\"Year\": 1896, \"City\": \"Athens\", \"Country\": \"AUT\",\"_timestamp\":1665136888163792}]";
+        let thread_id: usize = 0;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::JsonConfig::default().limit(get_config().limit.req_json_limit))
+                .app_data(web::PayloadConfig::new(
+                    get_config().limit.req_payload_limit,
+                ))
+                .app_data(web::Data::new(thread_id))
+                .configure(get_service_routes)
+                .configure(get_basic_routes),
+        )
+        .await;
+        // the stream of the alert must exist in the importing org
+        let req = test::TestRequest::post()
+            .uri(&format!(
+                "/api/{}/{}/_json",
+                "e2e_import", "olympics_schema"
+            ))
+            .insert_header(ContentType::json())
+            .append_header(auth)
+            .set_payload(body_str)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        let folder = folders::save_folder(
+            "e2e",
+            Folder {
+                folder_id: "".to_string(),
+                name: "e2e_ops".to_string(),
+                description: "".to_string(),
+            },
+            FolderType::Alerts,
+            false,
+        )
+        .await
+        .unwrap();
+        let alert: Alert = json::from_value(json::json!({
+            "name": "alertInFolder",
+            "org_id": "e2e",
+            "stream_type": "logs",
+            "stream_name": "olympics_schema",
+            "is_real_time": false,
+            "enabled": true,
+            "query_condition": {
+                "conditions": [{
+                    "column": "country",
+                    "operator": "NotContains",
+                    "value": "AUT"
+                }]
+            },
+            "trigger_condition": {
+                "period": 60,
+                "threshold": 1,
+                "silence": 0,
+                "frequency": 60,
+                "operator": ">="
+            },
+            "destinations": ["slack"]
+        }))
+        .unwrap();
+        let client = ORM_CLIENT.get_or_init(connect_to_orm).await;
+        alert::create(client, "e2e", &folder.folder_id, alert)
+            .await
+            .unwrap();
+
+        let bundle = bundle::export_config("e2e").await.unwrap();
+        assert!(bundle.folders.iter().any(|f| f.name == "e2e_ops"));
+        bundle::import_config("e2e_import", bundle).await.unwrap();
+
+        assert!(destinations::get("e2e_import", "slack").await.is_ok());
+        let imported_folder =
+            folders::get_folder_by_name("e2e_import", "e2e_ops", FolderType::Alerts)
+                .await
+                .unwrap();
+        let alerts =
+            db::alerts::alert::list_with_folders(client, ListAlertsParams::new("e2e_import"))
+                .await
+                .unwrap();
+        let (alert_folder, _) = alerts
+            .iter()
+            .find(|(_, a)| a.name == "alertInFolder")
+            .unwrap();
+        assert_eq!(alert_folder.folder_id, imported_folder.folder_id);
+        assert!(alerts
+            .iter()
+            .any(|(f, a)| a.name == "alertChk" && f.folder_id == DEFAULT_FOLDER));
+
+        // importing again matches the folder by name instead of adding one
+        let bundle = bundle::export_config("e2e").await.unwrap();
+        bundle::import_config("e2e_import", bundle).await.unwrap();
+        let folder_list = table::folders::list_folders("e2e_import", FolderType::Alerts)
+            .await
+            .unwrap();
+        assert_eq!(
+            folder_list.iter().filter(|f| f.name == "e2e_ops").count(),
+            1
+        );
+
+        alert::delete_by_name("e2e", StreamType::Logs, "olympics_schema", "alertInFolder")
+            .await
+            .unwrap();
+    }
+
+    async fn e2e_backtest_alert() {
+        // the time of the olympics_schema events
+        let ts = 1665136888163792;
+        let minute = 60_000_000;
+        let mut alert: Alert = json::from_value(json::json!({
+            "name": "alertBacktest",
+            "stream_type": "logs",
+            "stream_name": "olympics_schema",
+            "is_real_time": false,
+            "query_condition": {
+                "conditions": [{
+                    "column": "country",
+                    "operator": "Contains",
+                    "value": "AUT"
+                }]
+            },
+            "trigger_condition": {
+                "period": 10,
+                "threshold": 1,
+                "silence": 0,
+                "frequency": 600,
+                "operator": ">="
+            },
+            "destinations": []
+        }))
+        .unwrap();
+
+        // only the run 5 minutes after the events looks back over them
+        let resp = alert::backtest("e2e", alert.clone(), ts - 65 * minute, ts + 55 * minute)
+            .await
+            .unwrap();
+        assert_eq!(resp.intervals, 12);
+        assert_eq!(resp.errors, 0);
+        assert_eq!(resp.fired_at, vec![ts + 5 * minute]);
+
+        alert.trigger_condition.period = i64::MAX;
+        assert!(
+            alert::backtest("e2e", alert, ts - 65 * minute, ts + 55 * minute)
+                .await
+                .is_err()
+        );
+    }
+
     async fn e2e_handle_alert_after_destination_retries() {
         let now = Utc::now().timestamp_micros();
         let mins_3_later = now